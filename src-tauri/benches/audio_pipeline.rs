@@ -0,0 +1,110 @@
+//! Mixing + encoding cost per capture chunk with synthetic 48 kHz input:
+//! a mono mic mixed with stereo system audio, as in mixed mode. Also the
+//! speed-up applied to a clip before upload, WSOLA against rate relabelling.
+//!
+//! Run with `cargo bench --bench audio_pipeline`.
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+#[path = "../src/audio_pipeline.rs"]
+#[allow(dead_code)]
+mod audio_pipeline;
+#[path = "../src/resample.rs"]
+#[allow(dead_code)]
+mod resample;
+#[path = "../src/time_stretch.rs"]
+#[allow(dead_code)]
+mod time_stretch;
+
+use audio_pipeline::BufferPool;
+
+const SAMPLE_RATE: usize = 48_000;
+const OUTPUT_CHANNELS: usize = 2;
+/// 10 ms, a typical cpal callback size at 48 kHz.
+const FRAMES_PER_CHUNK: usize = SAMPLE_RATE / 100;
+/// Clips go up at the transcription rate, mono.
+const CLIP_SAMPLE_RATE: u32 = 16_000;
+const CLIP_SECONDS: usize = 10;
+
+fn tone(frames: usize, channels: usize, frequency: f32) -> Vec<i16> {
+    (0..frames)
+        .flat_map(|frame| {
+            let phase = frame as f32 * frequency * std::f32::consts::TAU / SAMPLE_RATE as f32;
+            let sample = audio_pipeline::f32_to_i16(phase.sin() * 0.5);
+            std::iter::repeat_n(sample, channels)
+        })
+        .collect()
+}
+
+fn mix_and_encode(c: &mut Criterion) {
+    let mic = tone(FRAMES_PER_CHUNK, 1, 440.0);
+    let system = tone(FRAMES_PER_CHUNK, 2, 220.0);
+    let pool = BufferPool::new();
+    let mut encoded = String::new();
+
+    let mut group = c.benchmark_group("capture_chunk");
+    group.throughput(Throughput::Elements(FRAMES_PER_CHUNK as u64));
+    group.bench_function("mix_encode_48k_stereo", |b| {
+        b.iter(|| {
+            // Mirrors the capture loop: pooled inputs, mix, normalize, encode, recycle.
+            let mut first = pool.take(mic.len());
+            first.extend_from_slice(&mic);
+            let mut secondary = pool.take(system.len());
+            secondary.extend_from_slice(&system);
+
+            let mut mixed = pool.take(FRAMES_PER_CHUNK * OUTPUT_CHANNELS);
+            mixed.resize(FRAMES_PER_CHUNK * OUTPUT_CHANNELS, 0);
+            audio_pipeline::fill_buffer_i16(
+                &mut mixed,
+                &first,
+                1,
+                OUTPUT_CHANNELS,
+                FRAMES_PER_CHUNK,
+            );
+            audio_pipeline::mix_secondary(
+                &mut mixed,
+                &mut secondary,
+                2,
+                OUTPUT_CHANNELS,
+                FRAMES_PER_CHUNK,
+                0.1,
+            );
+            audio_pipeline::normalize_peak(&mut mixed);
+            audio_pipeline::encode_chunk(&mixed, &mut encoded);
+            black_box(&encoded);
+
+            pool.recycle(first);
+            pool.recycle(secondary);
+            pool.recycle(mixed);
+        })
+    });
+    group.finish();
+}
+
+fn speed_up_clip(c: &mut Criterion) {
+    let frames = CLIP_SAMPLE_RATE as usize * CLIP_SECONDS;
+    let samples: Vec<i16> = (0..frames)
+        .map(|frame| {
+            let t = frame as f32 / CLIP_SAMPLE_RATE as f32;
+            let phase = (150.0 + 10.0 * t) * t * std::f32::consts::TAU;
+            audio_pipeline::f32_to_i16(phase.sin() * 0.5)
+        })
+        .collect();
+    let clip = resample::encode_wav(&samples, 1, CLIP_SAMPLE_RATE);
+
+    let mut group = c.benchmark_group("speed_up_wav");
+    group.throughput(Throughput::Elements(frames as u64));
+    for factor in [1.25f32, 1.6, 2.0] {
+        for (method, relabel) in [("wsola", false), ("relabel", true)] {
+            group.bench_with_input(BenchmarkId::new(method, factor), &factor, |b, &factor| {
+                b.iter(|| black_box(time_stretch::speed_up_wav(&clip, factor, relabel).unwrap()))
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, mix_and_encode, speed_up_clip);
+criterion_main!(benches);
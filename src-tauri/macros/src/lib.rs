@@ -0,0 +1,38 @@
+//! Attribute macros for the xexamai backend.
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, ItemFn};
+
+/// `#[tauri::command]` that also times the command: the body runs inside
+/// `crate::perf::track`, under the function's name, and is otherwise left as
+/// written. Only async commands can be timed this way.
+#[proc_macro_attribute]
+pub fn command(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let attr = proc_macro2::TokenStream::from(attr);
+    let ItemFn {
+        attrs,
+        vis,
+        sig,
+        block,
+    } = parse_macro_input!(item as ItemFn);
+    if sig.asyncness.is_none() {
+        return syn::Error::new_spanned(sig.fn_token, "timed commands must be async")
+            .to_compile_error()
+            .into();
+    }
+    let name = sig.ident.to_string();
+    let tauri_attr = if attr.is_empty() {
+        quote!(#[::tauri::command])
+    } else {
+        quote!(#[::tauri::command(#attr)])
+    };
+    quote! {
+        #tauri_attr
+        #(#attrs)*
+        #vis #sig {
+            crate::perf::track(#name, async move #block).await
+        }
+    }
+    .into()
+}
@@ -0,0 +1,173 @@
+use std::sync::Arc;
+use std::time::Instant;
+
+use anyhow::{anyhow, Context, Result};
+use reqwest::header::{HeaderMap, ACCEPT, AUTHORIZATION};
+use reqwest::{Client, Method, StatusCode};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use tauri::{AppHandle, Manager};
+
+use crate::auth_store::AuthStore;
+use crate::config::ConfigState;
+use crate::http::HttpClients;
+use crate::oauth;
+use crate::types::AppConfig;
+
+/// Authenticated client for the xldev backend API.
+#[derive(Default)]
+pub struct ApiClient;
+
+struct Session {
+    account_id: Option<String>,
+    token: Option<String>,
+}
+
+impl ApiClient {
+    pub fn new() -> Self {
+        Self
+    }
+
+    #[allow(dead_code)]
+    pub async fn get_json<T: DeserializeOwned>(&self, app: &AppHandle, path: &str) -> Result<T> {
+        let value = self.request(app, Method::GET, path, None).await?;
+        serde_json::from_value(value).context("decode API response")
+    }
+
+    #[allow(dead_code)]
+    pub async fn post_json<B: Serialize, T: DeserializeOwned>(
+        &self,
+        app: &AppHandle,
+        path: &str,
+        body: &B,
+    ) -> Result<T> {
+        let body = serde_json::to_value(body)?;
+        let value = self.request(app, Method::POST, path, Some(body)).await?;
+        serde_json::from_value(value).context("decode API response")
+    }
+
+    /// Sends a request relative to the API base, retrying once with a refreshed token
+    /// when the backend answers 401.
+    pub async fn request(
+        &self,
+        app: &AppHandle,
+        method: Method,
+        path: &str,
+        body: Option<Value>,
+    ) -> Result<Value> {
+        let cfg = app.state::<Arc<ConfigState>>().get().await;
+        let store = app.state::<Arc<AuthStore>>();
+        let client = app.state::<Arc<HttpClients>>().api(&cfg)?;
+        let url = format!(
+            "{}/{}",
+            oauth::resolve_api_base(Some(&cfg.backend_domain)),
+            path.trim_start_matches('/')
+        );
+        let session = current_session(app, &store, &cfg).await?;
+
+        let mut response = send(
+            &client,
+            &cfg,
+            &method,
+            &url,
+            session.token.as_deref(),
+            body.as_ref(),
+        )
+        .await?;
+        if response.status() == StatusCode::UNAUTHORIZED {
+            if let (Some(account_id), Some(stale)) = (&session.account_id, &session.token) {
+                log::info!(target: "api", "API returned 401, refreshing token: url={url}");
+                let fresh = store
+                    .refresh(app, &cfg.backend_domain, account_id, stale)
+                    .await?;
+                response = send(&client, &cfg, &method, &url, Some(&fresh), body.as_ref()).await?;
+            }
+        }
+
+        let status = response.status();
+        let text = response.text().await.unwrap_or_default();
+        if !status.is_success() {
+            return Err(anyhow!(
+                "API request failed: HTTP {} {}",
+                status.as_u16(),
+                text.trim()
+            ));
+        }
+        if text.trim().is_empty() {
+            return Ok(Value::Null);
+        }
+        Ok(serde_json::from_str(&text).unwrap_or(Value::String(text)))
+    }
+}
+
+async fn current_session(app: &AppHandle, store: &AuthStore, cfg: &AppConfig) -> Result<Session> {
+    let account_id = store.active_account_id(cfg.active_account.as_deref()).await;
+    let token = match account_id.as_deref() {
+        Some(id) => {
+            store
+                .access_token(app, &cfg.backend_domain, Some(id))
+                .await?
+        }
+        None => None,
+    };
+    Ok(Session { account_id, token })
+}
+
+async fn send(
+    client: &Client,
+    cfg: &AppConfig,
+    method: &Method,
+    url: &str,
+    token: Option<&str>,
+    body: Option<&Value>,
+) -> Result<reqwest::Response> {
+    let mut builder = client
+        .request(method.clone(), url)
+        .header(ACCEPT, "application/json");
+    if let Some(token) = token {
+        builder = builder.bearer_auth(token);
+    }
+    if let Some(body) = body {
+        builder = builder.json(body);
+    }
+    let request = builder.build()?;
+    if cfg.api_debug_logging {
+        log::debug!(
+            target: "api",
+            "-> {} {} headers={}",
+            request.method(),
+            request.url(),
+            redacted_headers(request.headers())
+        );
+    }
+    let started = Instant::now();
+    let response = client.execute(request).await?;
+    if cfg.api_debug_logging {
+        log::debug!(
+            target: "api",
+            "<- {} {} status={} elapsed_ms={} headers={}",
+            method,
+            url,
+            response.status().as_u16(),
+            started.elapsed().as_millis(),
+            redacted_headers(response.headers())
+        );
+    }
+    Ok(response)
+}
+
+fn redacted_headers(headers: &HeaderMap) -> String {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            let shown = if name == AUTHORIZATION || name.as_str().contains("cookie") {
+                "<redacted>"
+            } else {
+                value.to_str().unwrap_or("<binary>")
+            };
+            format!("{name}: {shown}")
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
@@ -1,29 +1,69 @@
+use chrono::{DateTime, Local, NaiveDate};
 use log::{Level, LevelFilter, Log, Metadata, Record};
 use std::{
+    borrow::Cow,
     fs::{self, File, OpenOptions},
     io::Write,
     path::{Path, PathBuf},
-    sync::{Mutex, OnceLock},
+    str::FromStr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex, OnceLock,
+    },
     time::{SystemTime, UNIX_EPOCH},
 };
 
 const LOG_FILE_NAME: &str = "xexamai.log";
-const OLD_LOG_FILE_NAME: &str = "xexamai.old.log";
-const MAX_LOG_SIZE_BYTES: u64 = 5 * 1024 * 1024;
+const ROTATED_LOG_PREFIX: &str = "xexamai.";
+const MAX_ROTATED_LOGS: usize = 7;
+const MAX_RECENT_LINES: usize = 5_000;
+// Prefixes of secrets that must never reach the log file.
+const SECRET_PREFIXES: &[&str] = &["sk-", "AIza", "eyJ", "ya29.", "gho_", "ghp_"];
+// Field names whose value is masked when followed by `:` or `=`.
+const SECRET_KEY_NAMES: &[&str] = &[
+    "api_key",
+    "apikey",
+    "access_token",
+    "refresh_token",
+    "password",
+    "secret",
+    "authorization",
+];
+const REDACTED: &str = "***";
+// Words of a field name that get the field dropped from JSON dumps such as the
+// config. Whole words only, so `toggleInputHotkey` stays while `apiKey` goes.
+const SECRET_FIELD_WORDS: &[&str] = &[
+    "key",
+    "keys",
+    "apikey",
+    "token",
+    "tokens",
+    "secret",
+    "secrets",
+    "password",
+    "authorization",
+];
 
 static LOGGER: FileLogger = FileLogger;
-static LOG_FILE: OnceLock<Mutex<File>> = OnceLock::new();
+static LOG_FILE: OnceLock<Mutex<LogSink>> = OnceLock::new();
 static LOG_PATH: OnceLock<PathBuf> = OnceLock::new();
+static MAX_LEVEL: AtomicUsize = AtomicUsize::new(LevelFilter::Info as usize);
+static HEADER: Mutex<Option<String>> = Mutex::new(None);
 
 struct FileLogger;
 
+struct LogSink {
+    file: File,
+    day: NaiveDate,
+}
+
 impl Log for FileLogger {
     fn enabled(&self, metadata: &Metadata<'_>) -> bool {
         let target = metadata.target();
         if target.starts_with("tao") || target.starts_with("wry") {
             return metadata.level() <= Level::Warn;
         }
-        metadata.level() <= Level::Debug
+        metadata.level() as usize <= MAX_LEVEL.load(Ordering::Relaxed)
     }
 
     fn log(&self, record: &Record<'_>) {
@@ -31,23 +71,14 @@ impl Log for FileLogger {
             return;
         }
 
-        let timestamp = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .map(|duration| duration.as_secs())
-            .unwrap_or_default();
+        let message = record.args().to_string();
+        let line = format_line(record.level(), record.target(), &message);
 
-        let line = format!(
-            "[{}][{}][{}] {}",
-            timestamp,
-            record.level(),
-            record.target(),
-            record.args()
-        );
-
-        if let Some(file) = LOG_FILE.get() {
-            if let Ok(mut file) = file.lock() {
-                let _ = writeln!(file, "{line}");
-                let _ = file.flush();
+        if let Some(sink) = LOG_FILE.get() {
+            if let Ok(mut sink) = sink.lock() {
+                roll_over_if_needed(&mut sink);
+                let _ = writeln!(sink.file, "{line}");
+                let _ = sink.file.flush();
             }
         }
 
@@ -56,9 +87,9 @@ impl Log for FileLogger {
     }
 
     fn flush(&self) {
-        if let Some(file) = LOG_FILE.get() {
-            if let Ok(mut file) = file.lock() {
-                let _ = file.flush();
+        if let Some(sink) = LOG_FILE.get() {
+            if let Ok(mut sink) = sink.lock() {
+                let _ = sink.file.flush();
             }
         }
     }
@@ -66,19 +97,18 @@ impl Log for FileLogger {
 
 pub(crate) fn init() -> Result<PathBuf, String> {
     let log_path = app_log_path()?;
-    rotate_large_log(&log_path)?;
+    let today = Local::now().date_naive();
+    if let Some(day) = file_day(&log_path).filter(|day| *day != today) {
+        rotate_log(&log_path, day)?;
+    }
 
-    let file = OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(&log_path)
-        .map_err(|error| format!("Failed to open app log file: {error}"))?;
+    let file = open_log_file(&log_path)?;
 
     let _ = LOG_PATH.set(log_path.clone());
-    let _ = LOG_FILE.set(Mutex::new(file));
+    let _ = LOG_FILE.set(Mutex::new(LogSink { file, day: today }));
 
     log::set_logger(&LOGGER)
-        .map(|()| log::set_max_level(LevelFilter::Debug))
+        .map(|()| log::set_max_level(current_level()))
         .map_err(|error| format!("Failed to initialize app logger: {error}"))?;
 
     log::info!(target: "app", "App log initialized: {}", log_path.to_string_lossy());
@@ -92,6 +122,58 @@ pub(crate) fn current_log_path() -> Result<PathBuf, String> {
     app_log_path()
 }
 
+/// Logs `header` now and repeats it at the top of each new day's file, so every
+/// log file says which build wrote it.
+pub(crate) fn set_header(header: String) {
+    log::info!(target: "app", "{header}");
+    *HEADER.lock().unwrap() = Some(header);
+}
+
+fn format_line(level: Level, target: &str, message: &str) -> String {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default();
+    format!("[{timestamp}][{level}][{target}] {}", redact(message))
+}
+
+/// Applies the configured level at runtime; unknown values fall back to `info`.
+/// `tracing` events follow it too: its `log` feature hands them to this logger.
+pub(crate) fn set_level(level: &str) {
+    let filter = LevelFilter::from_str(level.trim()).unwrap_or(LevelFilter::Info);
+    if MAX_LEVEL.swap(filter as usize, Ordering::Relaxed) != filter as usize {
+        log::set_max_level(filter);
+        log::info!(target: "app", "Log level set to {filter}");
+    }
+}
+
+fn current_level() -> LevelFilter {
+    match MAX_LEVEL.load(Ordering::Relaxed) {
+        0 => LevelFilter::Off,
+        1 => LevelFilter::Error,
+        2 => LevelFilter::Warn,
+        3 => LevelFilter::Info,
+        4 => LevelFilter::Debug,
+        _ => LevelFilter::Trace,
+    }
+}
+
+/// Returns the last `lines` lines of the current log file.
+pub(crate) fn recent_lines(lines: usize) -> Result<Vec<String>, String> {
+    let path = current_log_path()?;
+    let contents = match fs::read(&path) {
+        Ok(bytes) => String::from_utf8_lossy(&bytes).into_owned(),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(error) => return Err(format!("Failed to read app log file: {error}")),
+    };
+    let all: Vec<&str> = contents.lines().collect();
+    let take = lines.clamp(1, MAX_RECENT_LINES).min(all.len());
+    Ok(all[all.len() - take..]
+        .iter()
+        .map(|line| line.to_string())
+        .collect())
+}
+
 pub(crate) fn current_log_dir() -> Result<PathBuf, String> {
     current_log_path()?
         .parent()
@@ -140,17 +222,268 @@ fn app_data_base_dir() -> PathBuf {
     std::env::temp_dir()
 }
 
-fn rotate_large_log(log_path: &Path) -> Result<(), String> {
-    let Ok(metadata) = fs::metadata(log_path) else {
-        return Ok(());
+fn open_log_file(path: &Path) -> Result<File, String> {
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|error| format!("Failed to open app log file: {error}"))
+}
+
+fn file_day(path: &Path) -> Option<NaiveDate> {
+    let modified = fs::metadata(path).ok()?.modified().ok()?;
+    Some(DateTime::<Local>::from(modified).date_naive())
+}
+
+fn roll_over_if_needed(sink: &mut LogSink) {
+    let today = Local::now().date_naive();
+    if sink.day == today {
+        return;
+    }
+    let Some(path) = LOG_PATH.get() else {
+        return;
     };
+    let _ = sink.file.flush();
+    if rotate_log(path, sink.day).is_ok() {
+        if let Ok(file) = open_log_file(path) {
+            sink.file = file;
+            if let Some(header) = HEADER.lock().unwrap().as_deref() {
+                let _ = writeln!(sink.file, "{}", format_line(Level::Info, "app", header));
+            }
+        }
+    }
+    sink.day = today;
+}
 
-    if metadata.len() <= MAX_LOG_SIZE_BYTES {
+/// Moves the active log aside as `xexamai.<date>.log` and prunes old days.
+fn rotate_log(log_path: &Path, day: NaiveDate) -> Result<(), String> {
+    let rotated = log_path.with_file_name(format!(
+        "{ROTATED_LOG_PREFIX}{}.log",
+        day.format("%Y-%m-%d")
+    ));
+    fs::rename(log_path, &rotated)
+        .map_err(|error| format!("Failed to rotate app log file: {error}"))?;
+
+    let Some(dir) = log_path.parent() else {
         return Ok(());
+    };
+    let rotated_logs = rotated_logs(dir);
+    let excess = rotated_logs.len().saturating_sub(MAX_ROTATED_LOGS);
+    for path in rotated_logs.into_iter().take(excess) {
+        let _ = fs::remove_file(path);
+    }
+    Ok(())
+}
+
+/// Rotated logs in `dir`, oldest first.
+fn rotated_logs(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut rotated_logs: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .map(|name| {
+                    name != LOG_FILE_NAME
+                        && name.starts_with(ROTATED_LOG_PREFIX)
+                        && name.ends_with(".log")
+                })
+                .unwrap_or(false)
+        })
+        .collect();
+    rotated_logs.sort();
+    rotated_logs
+}
+
+/// The current log file followed by up to `rotated` of the newest rotated ones.
+pub(crate) fn recent_log_files(rotated: usize) -> Result<Vec<PathBuf>, String> {
+    let path = current_log_path()?;
+    let mut files: Vec<PathBuf> = path.exists().then(|| path.clone()).into_iter().collect();
+    if let Some(dir) = path.parent() {
+        files.extend(rotated_logs(dir).into_iter().rev().take(rotated));
+    }
+    Ok(files)
+}
+
+/// Masks API keys and bearer tokens before a line is written.
+fn redact(message: &str) -> Cow<'_, str> {
+    let lower = message.to_ascii_lowercase();
+    if !SECRET_PREFIXES
+        .iter()
+        .any(|prefix| message.contains(prefix))
+        && !lower.contains("bearer ")
+        && !SECRET_KEY_NAMES.iter().any(|name| lower.contains(name))
+    {
+        return Cow::Borrowed(message);
+    }
+
+    let mut out = String::with_capacity(message.len());
+    let mut rest = message;
+    let mut mask_next = false;
+    while !rest.is_empty() {
+        let token_len = rest
+            .find(|ch: char| !is_secret_char(ch))
+            .unwrap_or(rest.len());
+        if token_len == 0 {
+            let ch = rest.chars().next().unwrap_or_default();
+            out.push(ch);
+            rest = &rest[ch.len_utf8()..];
+            continue;
+        }
+        let token = &rest[..token_len];
+        rest = &rest[token_len..];
+        let token_lower = token.to_ascii_lowercase();
+        let looks_secret = token.len() >= 12
+            && SECRET_PREFIXES
+                .iter()
+                .any(|prefix| token.starts_with(prefix));
+        if mask_next || looks_secret {
+            out.push_str(REDACTED);
+        } else {
+            out.push_str(token);
+        }
+        let is_bearer = token_lower == "bearer";
+        let is_key = SECRET_KEY_NAMES
+            .iter()
+            .any(|name| token_lower.ends_with(name));
+        mask_next = false;
+        if is_bearer || is_key {
+            // Keep separators such as ": " or "=" and mask the value that follows.
+            let sep_len = rest
+                .find(|ch: char| is_secret_char(ch))
+                .unwrap_or(rest.len());
+            let separator = &rest[..sep_len];
+            mask_next = if is_bearer {
+                separator == " "
+            } else {
+                sep_len <= 4 && (separator.contains(':') || separator.contains('='))
+            };
+            out.push_str(separator);
+            rest = &rest[sep_len..];
+        }
+    }
+    Cow::Owned(out)
+}
+
+/// Strips secrets from a JSON dump before it leaves the app: fields whose name
+/// has a secret-sounding word are removed at any depth, and the remaining
+/// strings are masked like log lines.
+pub(crate) fn redact_value(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(fields) => {
+            fields.retain(|name, _| !is_secret_field(name));
+            fields.values_mut().for_each(redact_value);
+        }
+        serde_json::Value::Array(items) => items.iter_mut().for_each(redact_value),
+        serde_json::Value::String(text) => {
+            if let Cow::Owned(masked) = redact(text) {
+                *text = masked;
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Splits `name` into its camelCase, snake_case or kebab-case words and checks
+/// them against `SECRET_FIELD_WORDS`.
+fn is_secret_field(name: &str) -> bool {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut prev_lower = false;
+    for ch in name.chars() {
+        if matches!(ch, '_' | '-' | '.' | ' ') {
+            words.push(std::mem::take(&mut word));
+            prev_lower = false;
+            continue;
+        }
+        if ch.is_uppercase() && prev_lower {
+            words.push(std::mem::take(&mut word));
+        }
+        prev_lower = ch.is_lowercase() || ch.is_ascii_digit();
+        word.extend(ch.to_lowercase());
+    }
+    words.push(word);
+    words
+        .iter()
+        .any(|word| SECRET_FIELD_WORDS.contains(&word.as_str()))
+}
+
+fn is_secret_char(ch: char) -> bool {
+    ch.is_ascii_alphanumeric() || matches!(ch, '-' | '_' | '.')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn secret_fields_match_whole_words() {
+        for name in [
+            "apiKey",
+            "openaiApiKey",
+            "api_key",
+            "APIKey",
+            "geminiKeys",
+            "accessToken",
+            "refresh-token",
+            "tokens",
+            "clientSecret",
+            "password",
+            "Authorization",
+        ] {
+            assert!(is_secret_field(name), "{name} should be dropped");
+        }
+        for name in [
+            "toggleInputHotkey",
+            "durationHotkeys",
+            "stream_send_hotkey",
+            "keyboardLayout",
+            "monkey",
+            "numPredict",
+            "tokenizerPath",
+        ] {
+            assert!(!is_secret_field(name), "{name} should stay");
+        }
     }
 
-    let old_path = log_path.with_file_name(OLD_LOG_FILE_NAME);
-    let _ = fs::remove_file(&old_path);
-    fs::rename(log_path, old_path)
-        .map_err(|error| format!("Failed to rotate app log file: {error}"))
+    #[test]
+    fn redact_value_drops_secrets_at_any_depth() {
+        let mut value = json!({
+            "openaiApiKey": "sk-live",
+            "toggleInputHotkey": "Ctrl+Shift+S",
+            "durationHotkeys": { "5": "Ctrl+5" },
+            "providers": [
+                { "name": "gemini", "apiKey": "AIza123" },
+                { "name": "local", "auth": { "accessToken": "ya29.x", "user": "me" } },
+            ],
+        });
+        redact_value(&mut value);
+        assert_eq!(
+            value,
+            json!({
+                "toggleInputHotkey": "Ctrl+Shift+S",
+                "durationHotkeys": { "5": "Ctrl+5" },
+                "providers": [
+                    { "name": "gemini" },
+                    { "name": "local", "auth": { "user": "me" } },
+                ],
+            })
+        );
+    }
+
+    #[test]
+    fn redact_value_masks_secrets_left_in_strings() {
+        let mut value = json!({
+            "notes": ["plain", "pasted sk-abcdef123 by mistake"],
+            "url": "https://example.com/?api_key=abc123",
+        });
+        redact_value(&mut value);
+        let text = value.to_string();
+        assert!(!text.contains("sk-abcdef123"), "{text}");
+        assert!(!text.contains("abc123"), "{text}");
+        assert_eq!(value["notes"][0], "plain");
+    }
 }
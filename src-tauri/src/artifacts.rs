@@ -0,0 +1,314 @@
+//! At-rest encryption for the debug recordings and the transcript log
+//! (`encrypt_local_artifacts`). AES-256-GCM with a key kept in the OS keychain.
+//! An encrypted file is `MAGIC | nonce | ciphertext`; the transcript log is
+//! appended to line by line, so there each line is `enc:` plus such a record in
+//! base64. Reading accepts plaintext too, for files written before the option.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, bail, Result};
+use base64::Engine as _;
+use rand::RngCore;
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+use tokio::sync::OnceCell;
+use tokio::task::spawn_blocking;
+
+use crate::constants::OAUTH_APP_NAME;
+use crate::events::{self, emit_event};
+use crate::transcript_log::{self, TranscriptLog};
+use crate::types::AppConfig;
+
+const MAGIC: &[u8; 4] = b"XXA1";
+const LINE_PREFIX: &str = "enc:";
+const KEYCHAIN_ACCOUNT: &str = "artifact-key";
+const KEY_BYTES: usize = 32;
+const DEBUG_AUDIO_DIR_NAME: &str = "transcription_debug";
+
+/// Emitted as `artifacts:encrypt-progress` after each file of a migration.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EncryptProgress {
+    pub done: usize,
+    pub total: usize,
+    pub path: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EncryptSummary {
+    pub encrypted: usize,
+    pub already_encrypted: usize,
+    pub failed: usize,
+}
+
+/// Holds the artifact key once it has been read from (or created in) the keychain.
+pub struct ArtifactVault {
+    key: OnceCell<LessSafeKey>,
+}
+
+impl ArtifactVault {
+    pub fn new() -> Self {
+        Self {
+            key: OnceCell::new(),
+        }
+    }
+
+    async fn key(&self) -> Result<&LessSafeKey> {
+        self.key.get_or_try_init(load_or_create_key).await
+    }
+
+    pub async fn seal(&self, plain: &[u8]) -> Result<Vec<u8>> {
+        let key = self.key().await?;
+        let mut nonce = [0u8; NONCE_LEN];
+        rand::rngs::OsRng.fill_bytes(&mut nonce);
+        let mut body = plain.to_vec();
+        key.seal_in_place_append_tag(
+            Nonce::assume_unique_for_key(nonce),
+            Aad::from(MAGIC),
+            &mut body,
+        )
+        .map_err(|_| anyhow!("Encryption failed"))?;
+        let mut sealed = Vec::with_capacity(MAGIC.len() + NONCE_LEN + body.len());
+        sealed.extend_from_slice(MAGIC);
+        sealed.extend_from_slice(&nonce);
+        sealed.extend_from_slice(&body);
+        Ok(sealed)
+    }
+
+    /// One transcript log line, without its newline.
+    pub async fn seal_line(&self, line: &str) -> Result<String> {
+        let sealed = self.seal(line.as_bytes()).await?;
+        Ok(format!(
+            "{LINE_PREFIX}{}",
+            base64::engine::general_purpose::STANDARD.encode(sealed)
+        ))
+    }
+
+    /// Decrypts a sealed record; anything without the header is returned as is.
+    pub async fn open(&self, data: &[u8]) -> Result<Vec<u8>> {
+        let Some(rest) = data.strip_prefix(MAGIC.as_slice()) else {
+            return Ok(data.to_vec());
+        };
+        if rest.len() < NONCE_LEN {
+            bail!("Encrypted file is truncated");
+        }
+        let (nonce, sealed) = rest.split_at(NONCE_LEN);
+        let nonce =
+            Nonce::try_assume_unique_for_key(nonce).map_err(|_| anyhow!("Invalid nonce"))?;
+        let key = self.key().await?;
+        let mut buffer = sealed.to_vec();
+        let plain_len = key
+            .open_in_place(nonce, Aad::from(MAGIC), &mut buffer)
+            .map_err(|_| anyhow!("File could not be decrypted; the key may have changed"))?
+            .len();
+        buffer.truncate(plain_len);
+        Ok(buffer)
+    }
+
+    /// Reads an artifact in whichever form it was written.
+    pub async fn read(&self, path: &Path) -> Result<Vec<u8>> {
+        let data = tokio::fs::read(path).await?;
+        if data.starts_with(MAGIC) {
+            return self.open(&data).await;
+        }
+        let Some(text) = std::str::from_utf8(&data)
+            .ok()
+            .filter(|text| text.lines().any(|line| line.starts_with(LINE_PREFIX)))
+        else {
+            return Ok(data);
+        };
+        let mut plain = String::with_capacity(text.len());
+        for line in text.lines() {
+            match line.strip_prefix(LINE_PREFIX) {
+                Some(encoded) => {
+                    let sealed = base64::engine::general_purpose::STANDARD.decode(encoded)?;
+                    plain.push_str(&String::from_utf8(self.open(&sealed).await?)?);
+                }
+                None => plain.push_str(line),
+            }
+            plain.push('\n');
+        }
+        Ok(plain.into_bytes())
+    }
+
+    /// Encrypts the plaintext debug recordings and transcript logs left from
+    /// before the option was turned on, emitting progress per file.
+    pub async fn encrypt_existing(
+        &self,
+        app: &AppHandle,
+        config: &AppConfig,
+    ) -> Result<EncryptSummary> {
+        let mut files = list_files(&debug_audio_dir(app)?).await?;
+        files.extend(
+            list_files(&transcript_log::directory(app, config)?)
+                .await?
+                .into_iter()
+                .filter(|path| is_transcript_log(path)),
+        );
+        // Keeps a transcript from being appended to a file while it is rewritten.
+        let transcript_log = app.state::<std::sync::Arc<TranscriptLog>>();
+        let _paused = transcript_log.pause().await;
+
+        let total = files.len();
+        let mut summary = EncryptSummary::default();
+        for (index, path) in files.iter().enumerate() {
+            match self.encrypt_file(path).await {
+                Ok(true) => summary.encrypted += 1,
+                Ok(false) => summary.already_encrypted += 1,
+                Err(error) => {
+                    log::warn!(
+                        target: "artifacts",
+                        "Failed to encrypt {}: {error:#}",
+                        path.display()
+                    );
+                    summary.failed += 1;
+                }
+            }
+            let _ = emit_event(
+                app,
+                events::ARTIFACTS_ENCRYPT_PROGRESS,
+                EncryptProgress {
+                    done: index + 1,
+                    total,
+                    path: path.to_string_lossy().to_string(),
+                },
+            );
+        }
+        log::info!(
+            target: "artifacts",
+            "Encrypted {} files, {} already encrypted, {} failed",
+            summary.encrypted,
+            summary.already_encrypted,
+            summary.failed
+        );
+        Ok(summary)
+    }
+
+    /// Returns false when the file needed no change.
+    async fn encrypt_file(&self, path: &Path) -> Result<bool> {
+        let data = tokio::fs::read(path).await?;
+        if data.starts_with(MAGIC) {
+            return Ok(false);
+        }
+        let sealed = if is_transcript_log(path) {
+            let text = String::from_utf8(data)?;
+            if text
+                .lines()
+                .all(|line| line.is_empty() || line.starts_with(LINE_PREFIX))
+            {
+                return Ok(false);
+            }
+            let mut sealed = String::with_capacity(text.len() * 2);
+            for line in text.lines().filter(|line| !line.is_empty()) {
+                if line.starts_with(LINE_PREFIX) {
+                    sealed.push_str(line);
+                } else {
+                    sealed.push_str(&self.seal_line(line).await?);
+                }
+                sealed.push('\n');
+            }
+            sealed.into_bytes()
+        } else {
+            self.seal(&data).await?
+        };
+        // Renamed over the original so an interrupted run leaves one intact copy.
+        let mut partial = path.as_os_str().to_owned();
+        partial.push(".partial");
+        let partial = PathBuf::from(partial);
+        tokio::fs::write(&partial, sealed).await?;
+        tokio::fs::rename(&partial, path).await?;
+        Ok(true)
+    }
+}
+
+/// Whether the file at `path` starts with the header of a sealed artifact.
+pub async fn is_sealed(path: &Path) -> std::io::Result<bool> {
+    use tokio::io::AsyncReadExt;
+
+    let mut file = tokio::fs::File::open(path).await?;
+    let mut header = [0u8; MAGIC.len()];
+    match file.read_exact(&mut header).await {
+        Ok(_) => Ok(&header == MAGIC),
+        Err(error) if error.kind() == std::io::ErrorKind::UnexpectedEof => Ok(false),
+        Err(error) => Err(error),
+    }
+}
+
+/// Where `save_files` puts the debug recordings.
+pub fn debug_audio_dir(app: &AppHandle) -> Result<PathBuf> {
+    Ok(app
+        .path()
+        .app_local_data_dir()
+        .map_err(|error| anyhow!("Failed to resolve app data directory: {error}"))?
+        .join(DEBUG_AUDIO_DIR_NAME))
+}
+
+/// `path` resolved, when it lies in the debug audio or transcript log folder;
+/// the webview may not read anything else through `debug_file_read`.
+pub fn resolve_artifact(app: &AppHandle, config: &AppConfig, path: &str) -> Result<PathBuf> {
+    let path = std::fs::canonicalize(path)
+        .map_err(|error| anyhow!("Failed to resolve {path}: {error}"))?;
+    let allowed = [
+        debug_audio_dir(app)?,
+        transcript_log::directory(app, config)?,
+    ];
+    let inside = allowed
+        .iter()
+        .filter_map(|dir| std::fs::canonicalize(dir).ok())
+        .any(|dir| path.starts_with(dir));
+    if !inside || !path.is_file() {
+        bail!(
+            "{} is not a debug recording or transcript log",
+            path.display()
+        );
+    }
+    Ok(path)
+}
+
+fn is_transcript_log(path: &Path) -> bool {
+    path.extension().is_some_and(|extension| extension == "txt")
+}
+
+async fn list_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut entries = match tokio::fs::read_dir(dir).await {
+        Ok(entries) => entries,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(files),
+        Err(error) => return Err(error.into()),
+    };
+    while let Some(entry) = entries.next_entry().await? {
+        let path = entry.path();
+        let partial = path
+            .extension()
+            .is_some_and(|extension| extension == "partial");
+        if entry.file_type().await?.is_file() && !partial {
+            files.push(path);
+        }
+    }
+    Ok(files)
+}
+
+async fn load_or_create_key() -> Result<LessSafeKey> {
+    let bytes = spawn_blocking(|| -> Result<Vec<u8>> {
+        let entry = keyring::Entry::new(OAUTH_APP_NAME, KEYCHAIN_ACCOUNT)
+            .map_err(|error| anyhow!("Keychain is unavailable: {error}"))?;
+        match entry.get_password() {
+            Ok(encoded) => return Ok(hex::decode(encoded.trim())?),
+            Err(keyring::Error::NoEntry) => {}
+            Err(error) => bail!("Failed to read keychain: {error}"),
+        }
+        let mut key = vec![0u8; KEY_BYTES];
+        rand::rngs::OsRng.fill_bytes(&mut key);
+        entry
+            .set_password(&hex::encode(&key))
+            .map_err(|error| anyhow!("Failed to write keychain: {error}"))?;
+        log::info!(target: "artifacts", "Created the artifact encryption key");
+        Ok(key)
+    })
+    .await??;
+    let key = UnboundKey::new(&AES_256_GCM, &bytes)
+        .map_err(|_| anyhow!("The stored artifact key is invalid"))?;
+    Ok(LessSafeKey::new(key))
+}
@@ -1,15 +1,80 @@
 use anyhow::{anyhow, Result};
-use base64::{engine::general_purpose, Engine as _};
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
 use cpal::{Device, SampleFormat, Stream, StreamConfig, SupportedStreamConfig};
-use crossbeam_channel::{select, unbounded, Receiver, Sender};
+use crossbeam_channel::{select, unbounded, Receiver, RecvTimeoutError, Sender};
 use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
 use std::sync::{mpsc, Mutex};
 use std::thread;
-use tauri::{AppHandle, Emitter};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager};
+
+use crate::audio_monitor::AudioMonitor;
+use crate::audio_pipeline::{self, BufferPool};
+use crate::config::ConfigState;
+use crate::crash;
+use crate::error_bus;
+use crate::errors::{CommandError, CommandResult, ErrorCode};
+use crate::events::{self, emit_event};
+use crate::hresult::{self, HresultFailure};
+use crate::mic_access::{self, MicrophoneAccess};
+use crate::private_session;
+use crate::thread_priority::{self, ThreadBoost};
+use crate::types::{AppConfig, AudioInputType, PreRollSource};
+use crate::wake_word::WakeWordDetector;
 
 const DEFAULT_SAMPLE_RATE: u32 = 48_000;
 const DEFAULT_CHANNELS: u16 = 2;
+// Chunks emitted but not yet acknowledged by the webview before emission is throttled.
+const ACK_LAG_LIMIT: u64 = 100;
+// Roughly 30 s of 10 ms chunks held natively while the webview catches up.
+const MAX_BACKLOG_CHUNKS: usize = 3_000;
+const BACKLOG_POLL_INTERVAL: Duration = Duration::from_millis(50);
+const MAX_MONITOR_GAIN: f32 = 2.0;
+// How long `validate_setup` listens to each source.
+const VALIDATE_LISTEN: Duration = Duration::from_millis(400);
+// `micGain` as f32 bits; read by the capture thread for every mic chunk.
+static MIC_GAIN: AtomicU32 = AtomicU32::new(0x3f80_0000);
+
+/// Sets the gain the capture applies to the microphone from now on.
+pub fn set_mic_gain(gain: f32) {
+    MIC_GAIN.store(gain.to_bits(), Ordering::Relaxed);
+}
+
+fn mic_gain() -> f32 {
+    f32::from_bits(MIC_GAIN.load(Ordering::Relaxed))
+}
+
+// Ducking depth in dB, 0 while it is off, and the mic level that triggers it.
+static DUCKING_DB: AtomicU32 = AtomicU32::new(0);
+static DUCKING_THRESHOLD_DB: AtomicU32 = AtomicU32::new(0);
+
+/// Sets how a mixed capture ducks the system audio while the mic is above
+/// `threshold_db`.
+pub fn set_ducking(enabled: bool, depth_db: f32, threshold_db: f32) {
+    let depth_db = if enabled { depth_db } else { 0.0 };
+    DUCKING_DB.store(depth_db.to_bits(), Ordering::Relaxed);
+    DUCKING_THRESHOLD_DB.store(threshold_db.to_bits(), Ordering::Relaxed);
+}
+
+fn ducking() -> (f32, f32) {
+    (
+        f32::from_bits(DUCKING_DB.load(Ordering::Relaxed)),
+        f32::from_bits(DUCKING_THRESHOLD_DB.load(Ordering::Relaxed)),
+    )
+}
+// Reported for a source that delivered nothing but digital silence.
+const SILENCE_DB: f32 = -120.0;
+// Inputs at or below this rate only carry telephone-band speech.
+const NARROWBAND_MAX_SAMPLE_RATE: u32 = 16_000;
+const QUALITY_HINT_NARROWBAND: &str = "narrowband";
+const HANDS_FREE_KEYWORDS: &[&str] = &[
+    "hands-free",
+    "handsfree",
+    "headset_head_unit",
+    "headset-head-unit",
+];
 
 #[cfg(target_os = "macos")]
 const SYSTEM_DEVICE_KEYWORDS: &[&str] =
@@ -36,6 +101,11 @@ fn is_system_device_name(lower: &str) -> bool {
     SYSTEM_DEVICE_KEYWORDS.iter().any(|kw| lower.contains(kw))
 }
 
+/// Bluetooth headsets expose the hands-free profile as a separate 8/16 kHz device.
+fn is_hands_free_name(lower: &str) -> bool {
+    HANDS_FREE_KEYWORDS.iter().any(|kw| lower.contains(kw))
+}
+
 #[cfg(target_os = "macos")]
 fn system_device_priority(lower: &str) -> Option<usize> {
     if lower.contains("blackhole") {
@@ -86,12 +156,103 @@ fn system_audio_help_message() -> &'static str {
 }
 
 #[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct AudioDeviceInfo {
     pub id: String,
     pub name: String,
     pub kind: String, // "mic" | "system" | "other"
     pub channels: u16,
     pub sample_rate: u32,
+    /// `"narrowband"` for hands-free profiles and other telephone-quality inputs.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quality_hint: Option<&'static str>,
+}
+
+/// Device ids for one capture; `None` means the host default.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CaptureDevices {
+    pub mic: Option<String>,
+    pub system: Option<String>,
+}
+
+impl CaptureDevices {
+    /// Picks the saved devices for `source`. An explicit `requested` id wins for the
+    /// device that source is mainly about: the mic, or the loopback in system mode.
+    pub fn for_source(
+        config: &AppConfig,
+        source: AudioInputType,
+        requested: Option<String>,
+    ) -> Self {
+        match source {
+            AudioInputType::Microphone => Self {
+                mic: requested.or_else(|| config.mic_device_id.clone()),
+                system: None,
+            },
+            AudioInputType::System => Self {
+                mic: None,
+                system: requested.or_else(|| config.system_loopback_device_id.clone()),
+            },
+            AudioInputType::Mixed => Self {
+                mic: requested
+                    .or_else(|| config.mixed_mic_device_id.clone())
+                    .or_else(|| config.mic_device_id.clone()),
+                system: config.system_loopback_device_id.clone(),
+            },
+            AudioInputType::Unknown => Self::default(),
+        }
+    }
+}
+
+/// One of the inputs a capture records from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CaptureSource {
+    Mic,
+    System,
+}
+
+impl CaptureSource {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Mic => "mic",
+            Self::System => "system",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MissingSource {
+    pub source: CaptureSource,
+    pub reason: String,
+}
+
+impl MissingSource {
+    fn new(source: CaptureSource, reason: impl std::fmt::Display) -> Self {
+        Self {
+            source,
+            reason: reason.to_string(),
+        }
+    }
+}
+
+/// `audio:partial-start` payload.
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PartialStart {
+    source: AudioInputType,
+    active: Vec<CaptureSource>,
+    missing: Vec<MissingSource>,
+}
+
+/// `audio:source-changed` payload.
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SourceChanged {
+    previous: Option<AudioInputType>,
+    source: AudioInputType,
+    active: Vec<CaptureSource>,
+    missing: Vec<MissingSource>,
 }
 
 struct ActiveThread {
@@ -101,250 +262,1295 @@ struct ActiveThread {
     stop_flag: Option<std::sync::Arc<std::sync::atomic::AtomicBool>>,
 }
 
+/// A running capture a source switch can stop; tests substitute their own.
+trait Halt {
+    fn halt(self);
+}
+
+impl Halt for ActiveThread {
+    /// Stops the thread and waits for it, which drops its streams.
+    fn halt(self) {
+        #[cfg(windows)]
+        {
+            // Set stop flag for WASAPI loopback
+            if let Some(stop_flag) = self.stop_flag {
+                stop_flag.store(true, Ordering::Relaxed);
+            }
+        }
+        let _ = self.stop_tx.send(());
+        if let Some(handle) = self.handle {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// Opens the replacement before the running capture in `slot` is stopped, so a
+/// source that fails to open leaves the old one running.
+fn replace_capture<C: Halt, P>(
+    slot: &Mutex<Option<C>>,
+    open: impl FnOnce() -> Result<P>,
+) -> Result<P> {
+    let pending = open()?;
+    if let Some(old) = slot.lock().unwrap().take() {
+        old.halt();
+    }
+    Ok(pending)
+}
+
+/// A capture whose streams are open but which does not feed the encoder until
+/// `go_tx` fires. WASAPI-only system capture has no such gate.
+struct PendingCapture {
+    thread: ActiveThread,
+    go_tx: Option<Sender<()>>,
+    loopback_source: Option<String>,
+    active: Vec<CaptureSource>,
+    missing: Vec<MissingSource>,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CaptureStatus {
+    pub active: bool,
+    pub source: Option<AudioInputType>,
+    pub error: Option<String>,
+    /// Moving average of mix + encode time per chunk; `None` until a chunk went out.
+    pub chunk_processing_micros: Option<u64>,
+    /// The webview fell behind and only levels are being emitted.
+    pub backpressure: bool,
+    /// Inputs the running capture records from.
+    pub active_sources: Vec<CaptureSource>,
+    /// Inputs a mixed capture started without, with the reason.
+    pub missing_sources: Vec<MissingSource>,
+    /// Sequence number of the newest captured chunk.
+    pub last_seq: u64,
+    /// Chunks captured but never emitted because the webview fell behind; the
+    /// same chunks show up as gaps in `seq` on `audio:chunk`.
+    pub dropped_chunks: u64,
+    /// Priority boost of each running capture, mixing and encoder thread.
+    pub thread_priorities: Vec<ThreadBoost>,
+    /// Capture is stopped, but the pre-roll keeps the last `preRollSeconds`.
+    pub pre_roll_active: bool,
+    /// Counters of the running capture, or of the last one once it stopped.
+    pub stats: CaptureStats,
+}
+
+/// Cumulative counters since the last start, for the recording stats panel.
+#[derive(Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CaptureStats {
+    /// `None` while stopped.
+    pub uptime_ms: Option<u64>,
+    /// Frames handed to the encoder, pre-roll included.
+    pub frames: u64,
+    /// Base64 payload bytes sent out on `audio:chunk`.
+    pub bytes_emitted: u64,
+    pub chunks_emitted: u64,
+    /// Frames of each source that never reached the webview: cut in the mixer
+    /// when a source delivered more than the chunk, or dropped with a chunk
+    /// from the backlog.
+    pub dropped_frames: Vec<SourceFrames>,
+    /// Time from capture to emit of the newest live chunk.
+    pub latency_micros: Option<u64>,
+    /// Chunks scaled down because they hit the edge of the i16 range.
+    pub peak_normalizations: u64,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SourceFrames {
+    pub source: CaptureSource,
+    pub frames: u64,
+}
+
+/// Capture status plus the recent WASAPI failures, for bug reports.
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AudioDiagnostics {
+    pub status: CaptureStatus,
+    /// Oldest first; always empty off Windows.
+    pub wasapi_failures: Vec<HresultFailure>,
+    pub microphone_access: MicrophoneAccess,
+}
+
+/// One source as `validate_setup` found it.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SourceValidation {
+    pub source: CaptureSource,
+    pub ok: bool,
+    pub device_name: Option<String>,
+    pub sample_rate: Option<u32>,
+    pub channels: Option<u16>,
+    /// RMS over the listening window in dBFS; `None` when no samples arrived.
+    pub level_db: Option<f32>,
+    pub error: Option<String>,
+}
+
+impl SourceValidation {
+    fn failed(source: CaptureSource, error: impl std::fmt::Display) -> Self {
+        Self {
+            source,
+            ok: false,
+            device_name: None,
+            sample_rate: None,
+            channels: None,
+            level_db: None,
+            error: Some(error.to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AudioValidation {
+    /// A capture was running. The test opens its own streams next to it rather
+    /// than refusing, so a device held in exclusive mode can fail here while the
+    /// capture itself is fine.
+    pub capture_active: bool,
+    pub sources: Vec<SourceValidation>,
+    /// A denied switch here explains a microphone that fails or stays silent.
+    pub microphone_access: MicrophoneAccess,
+}
+
+/// Timings and counters written by the capture and encoder threads and read
+/// by `status`.
+#[derive(Default)]
+struct CaptureMetrics {
+    avg_chunk_micros: AtomicU64,
+    started_at: Mutex<Option<Instant>>,
+    frames: AtomicU64,
+    bytes_emitted: AtomicU64,
+    chunks_emitted: AtomicU64,
+    // Cut in the mixer, indexed by `CaptureSource as usize`.
+    mixer_dropped_frames: [AtomicU64; 2],
+    // Dropped with whole chunks, which carry every active source.
+    backlog_dropped_frames: AtomicU64,
+    latency_micros: AtomicU64,
+    peak_normalizations: AtomicU64,
+}
+
+impl CaptureMetrics {
+    fn reset(&self) {
+        self.avg_chunk_micros.store(0, Ordering::Relaxed);
+        *self.started_at.lock().unwrap() = Some(Instant::now());
+        for counter in [
+            &self.frames,
+            &self.bytes_emitted,
+            &self.chunks_emitted,
+            &self.mixer_dropped_frames[0],
+            &self.mixer_dropped_frames[1],
+            &self.backlog_dropped_frames,
+            &self.latency_micros,
+            &self.peak_normalizations,
+        ] {
+            counter.store(0, Ordering::Relaxed);
+        }
+    }
+
+    fn stats(&self, active: bool, sources: &[CaptureSource]) -> CaptureStats {
+        let backlog_dropped = self.backlog_dropped_frames.load(Ordering::Relaxed);
+        let latency = self.latency_micros.load(Ordering::Relaxed);
+        CaptureStats {
+            uptime_ms: active
+                .then(|| *self.started_at.lock().unwrap())
+                .flatten()
+                .map(|started| started.elapsed().as_millis() as u64),
+            frames: self.frames.load(Ordering::Relaxed),
+            bytes_emitted: self.bytes_emitted.load(Ordering::Relaxed),
+            chunks_emitted: self.chunks_emitted.load(Ordering::Relaxed),
+            dropped_frames: sources
+                .iter()
+                .map(|&source| SourceFrames {
+                    source,
+                    frames: self.mixer_dropped_frames[source as usize].load(Ordering::Relaxed)
+                        + backlog_dropped,
+                })
+                .collect(),
+            latency_micros: (latency > 0).then_some(latency),
+            peak_normalizations: self.peak_normalizations.load(Ordering::Relaxed),
+        }
+    }
+
+    // Only the encoder thread records, so a plain load/store is enough.
+    fn record(&self, elapsed: std::time::Duration) {
+        let sample = (elapsed.as_micros() as u64).max(1);
+        let previous = self.avg_chunk_micros.load(Ordering::Relaxed);
+        let next = if previous == 0 {
+            sample
+        } else {
+            (previous * 7 + sample) / 8
+        };
+        self.avg_chunk_micros.store(next, Ordering::Relaxed);
+    }
+
+    fn chunk_micros(&self) -> Option<u64> {
+        let value = self.avg_chunk_micros.load(Ordering::Relaxed);
+        (value > 0).then_some(value)
+    }
+}
+
+/// Sequence and acknowledgement state shared between the capture threads,
+/// `audio_ack` and the encoder thread.
+#[derive(Default)]
+struct ChunkFlow {
+    /// Last sequence number handed out; chunks are numbered from 1 per capture.
+    last_seq: AtomicU64,
+    acked_seq: AtomicU64,
+    /// Chunks dropped from the encoder backlog; each one is a gap in `seq`.
+    dropped_chunks: AtomicU64,
+    throttled: AtomicBool,
+}
+
+impl ChunkFlow {
+    fn reset(&self) {
+        self.last_seq.store(0, Ordering::Relaxed);
+        self.acked_seq.store(0, Ordering::Relaxed);
+        self.dropped_chunks.store(0, Ordering::Relaxed);
+        self.throttled.store(false, Ordering::Relaxed);
+    }
+}
+
 pub struct AudioManager {
     active: Mutex<Option<ActiveThread>>,
+    source: Mutex<Option<AudioInputType>>,
+    crashed: Mutex<Option<String>>,
+    metrics: std::sync::Arc<CaptureMetrics>,
+    flow: std::sync::Arc<ChunkFlow>,
+    monitor: std::sync::Arc<AudioMonitor>,
+    wake_words: std::sync::Arc<WakeWordDetector>,
+    // Output device the running capture records system audio from, if any.
+    loopback_source: Mutex<Option<String>>,
+    // Devices of the last start, reused when capture is restarted after resume.
+    devices: Mutex<Option<CaptureDevices>>,
+    // Active and missing inputs of the running capture.
+    sources: Mutex<(Vec<CaptureSource>, Vec<MissingSource>)>,
+    // Encoder of the running capture; a source switch feeds it from the new
+    // streams, so its backlog and chunk numbering carry on.
+    encoder: Mutex<Option<(ChunkSink, BufferPool)>>,
+    // Pre-roll the config asks for; `None` while `preRollSeconds` is 0.
+    pre_roll_settings: Mutex<Option<PreRollSettings>>,
+    // Running while capture is stopped. A start holds this lock until the
+    // pre-roll is handed over, so a concurrent sync cannot start another.
+    pre_roll: Mutex<Option<PreRoll>>,
 }
 
 impl AudioManager {
     pub fn new() -> Self {
         Self {
             active: Mutex::new(None),
+            source: Mutex::new(None),
+            crashed: Mutex::new(None),
+            metrics: std::sync::Arc::new(CaptureMetrics::default()),
+            flow: std::sync::Arc::new(ChunkFlow::default()),
+            monitor: std::sync::Arc::new(AudioMonitor::new()),
+            wake_words: std::sync::Arc::new(WakeWordDetector::new()),
+            loopback_source: Mutex::new(None),
+            devices: Mutex::new(None),
+            sources: Mutex::new((Vec::new(), Vec::new())),
+            encoder: Mutex::new(None),
+            pre_roll_settings: Mutex::new(None),
+            pre_roll: Mutex::new(None),
+        }
+    }
+
+    pub fn diagnostics(&self) -> AudioDiagnostics {
+        AudioDiagnostics {
+            status: self.status(),
+            wasapi_failures: hresult::recent(),
+            microphone_access: mic_access::check(),
         }
     }
 
+    pub fn status(&self) -> CaptureStatus {
+        let error = self.crashed.lock().unwrap().clone();
+        let active = error.is_none() && self.active.lock().unwrap().is_some();
+        let (source, chunk_processing_micros) = if active {
+            (*self.source.lock().unwrap(), self.metrics.chunk_micros())
+        } else {
+            (None, None)
+        };
+        // Kept after a stop, so the stats still name the sources they counted.
+        let (recorded_sources, recorded_missing) = self.sources.lock().unwrap().clone();
+        let stats = self.metrics.stats(active, &recorded_sources);
+        let (active_sources, missing_sources) = if active {
+            (recorded_sources, recorded_missing)
+        } else {
+            (Vec::new(), Vec::new())
+        };
+        CaptureStatus {
+            active,
+            source,
+            error,
+            chunk_processing_micros,
+            backpressure: active && self.flow.throttled.load(Ordering::Relaxed),
+            last_seq: self.flow.last_seq.load(Ordering::Relaxed),
+            dropped_chunks: self.flow.dropped_chunks.load(Ordering::Relaxed),
+            active_sources,
+            missing_sources,
+            thread_priorities: if active {
+                thread_priority::current()
+            } else {
+                Vec::new()
+            },
+            // Busy only while a start takes the pre-roll over.
+            pre_roll_active: self
+                .pre_roll
+                .try_lock()
+                .map(|pre_roll| pre_roll.is_some())
+                .unwrap_or(false),
+            stats,
+        }
+    }
+
+    /// Records that the webview has processed every chunk up to `seq`.
+    pub fn ack(&self, seq: u64) {
+        self.flow.acked_seq.fetch_max(seq, Ordering::Relaxed);
+    }
+
+    /// Called when a capture thread panicked; cleared by the next start.
+    pub fn mark_crashed(&self, message: &str) {
+        *self.crashed.lock().unwrap() = Some(message.to_string());
+    }
+
     pub fn list_devices(&self) -> Result<Vec<AudioDeviceInfo>> {
         let host = cpal::default_host();
-        let mut out = Vec::new();
-        
-        // List all devices
         // On Windows, WASAPI loopback devices appear as input devices
-        for device in host.devices()? {
-            if let Ok(info) = build_device_info(&device) {
-                out.push(info);
+        Ok(enumerate_devices(&host)?
+            .into_iter()
+            .filter_map(|(id, name, device)| build_device_info(&device, id, name).ok())
+            .collect())
+    }
+
+    /// Follows `wake_words` in the config; detection runs on whatever is captured.
+    pub fn apply_wake_words(&self, app: &AppHandle, config: &AppConfig) {
+        self.wake_words.apply_config(app, config);
+    }
+
+    /// Follows `preRollSeconds` and `preRollSource`. Unchanged settings leave
+    /// the pre-roll as it is.
+    pub fn apply_pre_roll(&self, app: &AppHandle, config: &AppConfig) {
+        let wanted = PreRollSettings::of(config);
+        {
+            let mut current = self.pre_roll_settings.lock().unwrap();
+            if *current == wanted {
+                return;
             }
+            *current = wanted;
         }
-        
-        Ok(out)
+        refresh_pre_roll(app);
+    }
+
+    /// Starts, restarts or stops the pre-roll capture so it runs exactly while
+    /// it is configured, capture is stopped and no private session is on.
+    /// Blocks while its streams open.
+    pub fn sync_pre_roll(&self, app: &AppHandle) {
+        let mut running = self.pre_roll.lock().unwrap();
+        let capturing = self.active.lock().unwrap().is_some();
+        let wanted = self
+            .pre_roll_settings
+            .lock()
+            .unwrap()
+            .clone()
+            .filter(|_| !capturing && !private_session::is_active());
+        if running.as_ref().map(|pre_roll| &pre_roll.settings) == wanted.as_ref() {
+            return;
+        }
+        if let Some(previous) = running.take() {
+            previous.finish(None);
+            log::info!(target: "audio", "Pre-roll stopped");
+        }
+        let Some(settings) = wanted else {
+            return;
+        };
+        match PreRoll::start(app, settings) {
+            Ok(pre_roll) => {
+                log::info!(
+                    target: "audio",
+                    "Pre-roll started: source={} seconds={}",
+                    pre_roll.settings.source.as_str(),
+                    pre_roll.settings.seconds
+                );
+                *running = Some(pre_roll);
+            }
+            Err(error) => log::warn!(target: "audio", "Pre-roll not started: {error:#}"),
+        }
+    }
+
+    /// Turns playback of the captured mix on or off. While capture is stopped the
+    /// setting is only stored and applied by the next `start`.
+    pub fn set_monitor(&self, app: &AppHandle, enabled: bool, gain: f32) -> CommandResult<()> {
+        if !gain.is_finite() || !(0.0..=MAX_MONITOR_GAIN).contains(&gain) {
+            return Err(CommandError::bare(
+                ErrorCode::InvalidArgument,
+                &[("name", "gain")],
+            ));
+        }
+        self.monitor.set_gain(gain);
+        self.monitor.set_enabled(enabled);
+        let capturing = self.active.lock().unwrap().is_some();
+        if !enabled || !capturing {
+            self.monitor.stop();
+            return Ok(());
+        }
+        if self.monitor.is_running() {
+            return Ok(());
+        }
+        let source = self.loopback_source.lock().unwrap().clone();
+        let result = self.monitor.start(app, source.as_deref());
+        if result.is_err() {
+            self.monitor.set_enabled(false);
+        }
+        result
     }
 
     pub fn stop(&self) -> Result<()> {
+        // The monitor is fed by the encoder, so it goes down first.
+        self.monitor.stop();
+        *self.loopback_source.lock().unwrap() = None;
         if let Some(active) = self.active.lock().unwrap().take() {
-            #[cfg(windows)]
-            {
-                // Set stop flag for WASAPI loopback
-                if let Some(stop_flag) = active.stop_flag {
-                    stop_flag.store(true, std::sync::atomic::Ordering::Relaxed);
-                }
-            }
-            let _ = active.stop_tx.send(());
-            if let Some(handle) = active.handle {
-                let _ = handle.join();
-            }
+            active.halt();
         }
+        // The encoder thread exits once the capture's senders are gone too.
+        *self.encoder.lock().unwrap() = None;
         Ok(())
     }
 
-    pub fn start(&self, app: AppHandle, source: &str, device_id: Option<String>) -> Result<()> {
-        self.stop()?;
+    pub fn start(
+        &self,
+        app: AppHandle,
+        source: AudioInputType,
+        selected: CaptureDevices,
+    ) -> Result<()> {
+        *self.devices.lock().unwrap() = Some(selected.clone());
+        if let Err(error) = self.start_capture(app.clone(), source, selected) {
+            // The pre-roll went down with the failed start.
+            self.sync_pre_roll(&app);
+            return Err(error);
+        }
+        self.start_monitor(&app);
+        Ok(())
+    }
+
+    /// Moves a running capture to `source` without a gap: the new streams open
+    /// while the old ones still record, and the old ones stop only once the new
+    /// set is ready. The encoder stays, so its backlog and chunk numbers carry
+    /// on. If the new source fails to open, the old capture keeps running and
+    /// the error is returned. Without a running capture this is `start`.
+    pub fn switch_source(
+        &self,
+        app: AppHandle,
+        source: AudioInputType,
+        selected: CaptureDevices,
+    ) -> Result<()> {
+        let running = self.encoder.lock().unwrap().clone();
+        let Some((encoder, pool)) = running.filter(|_| self.status().active) else {
+            return self.start(app, source, selected);
+        };
+        let previous = *self.source.lock().unwrap();
+        let pending = replace_capture(&self.active, || {
+            open_capture(&app, source, &selected, &encoder, &pool)
+        })?;
+        let loopback_changed = *self.loopback_source.lock().unwrap() != pending.loopback_source;
+        *self.devices.lock().unwrap() = Some(selected);
+        *self.source.lock().unwrap() = Some(source);
+        self.commit(&app, source, pending);
+        // Playing back the new loopback device could feed back, so the monitor
+        // goes through its checks again.
+        if loopback_changed && self.monitor.is_running() {
+            self.monitor.stop();
+            self.start_monitor(&app);
+        }
+        let (active, missing) = self.sources.lock().unwrap().clone();
+        log::info!(
+            target: "audio",
+            "Switched capture source: {} -> {} active={active:?}",
+            previous.map(|previous| previous.as_str()).unwrap_or("none"),
+            source.as_str()
+        );
+        let _ = emit_event(
+            &app,
+            events::AUDIO_SOURCE_CHANGED,
+            SourceChanged {
+                previous,
+                source,
+                active,
+                missing,
+            },
+        );
+        Ok(())
+    }
+
+    fn start_monitor(&self, app: &AppHandle) {
+        if !self.monitor.is_enabled() {
+            return;
+        }
+        let loopback = self.loopback_source.lock().unwrap().clone();
+        // Capture itself is fine, so a monitor failure only turns monitoring off.
+        if let Err(error) = self.monitor.start(app, loopback.as_deref()) {
+            self.monitor.set_enabled(false);
+            error_bus::publish(
+                app,
+                "audio-monitor",
+                error.code,
+                error.detail.unwrap_or_default(),
+                true,
+            );
+        }
+    }
+
+    /// Opens the streams `start` would for `source`, listens for a moment and
+    /// closes them again. Blocks for the listening window of each source.
+    pub fn validate_setup(
+        &self,
+        app: &AppHandle,
+        source: AudioInputType,
+        selected: CaptureDevices,
+    ) -> Result<AudioValidation> {
+        let wanted: &[CaptureSource] = match source {
+            AudioInputType::Microphone => &[CaptureSource::Mic],
+            AudioInputType::System => &[CaptureSource::System],
+            AudioInputType::Mixed => &[CaptureSource::Mic, CaptureSource::System],
+            AudioInputType::Unknown => return Err(anyhow!("Unknown source")),
+        };
         let host = cpal::default_host();
+        let sources: Vec<SourceValidation> = wanted
+            .iter()
+            .map(|&wanted| validate_source(app, &host, wanted, &selected))
+            .collect();
+        log::info!(
+            target: "audio",
+            "Validated setup: source={} ok={:?}",
+            source.as_str(),
+            sources.iter().map(|source| source.ok).collect::<Vec<_>>()
+        );
+        Ok(AudioValidation {
+            capture_active: self.status().active,
+            sources,
+            microphone_access: mic_access::check(),
+        })
+    }
 
-        let (stop_tx, stop_rx) = unbounded::<()>();
-        let mut devices: Vec<Device> = vec![];
-        match source {
-            "mic" => {
-                if let Some(dev) = find_device_by_id(&host, device_id.as_deref())? {
-                    eprintln!("[audio] capture mic device: {}", dev.name().unwrap_or_default());
-                    devices.push(dev);
+    /// Streams rarely survive a suspend, so a running capture is reopened with the
+    /// same source and devices. Returns whether anything was restarted.
+    pub fn restart_if_active(&self, app: &AppHandle) -> Result<bool> {
+        if !self.status().active {
+            // Pre-roll streams do not survive a suspend either.
+            if let Some(pre_roll) = self.pre_roll.lock().unwrap().take() {
+                pre_roll.finish(None);
+            }
+            self.sync_pre_roll(app);
+            return Ok(false);
+        }
+        let Some(source) = *self.source.lock().unwrap() else {
+            return Ok(false);
+        };
+        let devices = self.devices.lock().unwrap().clone().unwrap_or_default();
+        log::info!(target: "audio", "Restarting capture: source={}", source.as_str());
+        self.start(app.clone(), source, devices)?;
+        Ok(true)
+    }
+
+    fn spawn_encoder(&self, app: &AppHandle, pool: &BufferPool) -> ChunkSink {
+        spawn_encoder(
+            app.clone(),
+            pool.clone(),
+            self.metrics.clone(),
+            self.flow.clone(),
+            self.monitor.clone(),
+            self.wake_words.clone(),
+        )
+    }
+
+    fn start_capture(
+        &self,
+        app: AppHandle,
+        source: AudioInputType,
+        selected: CaptureDevices,
+    ) -> Result<()> {
+        self.stop()?;
+        *self.crashed.lock().unwrap() = None;
+        *self.source.lock().unwrap() = Some(source);
+        *self.sources.lock().unwrap() = (Vec::new(), Vec::new());
+        self.metrics.reset();
+        self.flow.reset();
+        let pool = BufferPool::new();
+        let encoder = self.spawn_encoder(&app, &pool);
+        // The pre-roll keeps recording while the new streams open. What it holds
+        // goes out first; what it catches meanwhile only follows when the new
+        // capture waits for its start signal, since an ungated one is already
+        // feeding the encoder.
+        let mut pre_roll_slot = self.pre_roll.lock().unwrap();
+        let pre_roll = pre_roll_slot.take();
+        let replayed = pre_roll
+            .as_ref()
+            .map(|pre_roll| pre_roll.drain_into(&encoder))
+            .unwrap_or(0);
+        let opened = open_capture(&app, source, &selected, &encoder, &pool);
+        if let Some(pre_roll) = pre_roll {
+            let gated = matches!(&opened, Ok(pending) if pending.go_tx.is_some());
+            let replayed = replayed + pre_roll.finish(gated.then_some(&encoder));
+            log::info!(target: "audio", "Pre-roll handed over: chunks={replayed}");
+        }
+        let pending = opened?;
+        *self.encoder.lock().unwrap() = Some((encoder, pool));
+        self.commit(&app, source, pending);
+        drop(pre_roll_slot);
+        Ok(())
+    }
+
+    /// Makes `pending` the running capture and lets its thread feed the encoder.
+    fn commit(&self, app: &AppHandle, source: AudioInputType, pending: PendingCapture) {
+        *self.loopback_source.lock().unwrap() = pending.loopback_source;
+        if let Some(go_tx) = &pending.go_tx {
+            let _ = go_tx.send(());
+        }
+        *self.active.lock().unwrap() = Some(pending.thread);
+        self.record_sources(app, source, pending.active, pending.missing);
+    }
+
+    /// Remembers which sources a capture runs with and announces a mixed capture
+    /// that started without some of them.
+    fn record_sources(
+        &self,
+        app: &AppHandle,
+        source: AudioInputType,
+        active: Vec<CaptureSource>,
+        missing: Vec<MissingSource>,
+    ) {
+        if !missing.is_empty() {
+            log::warn!(
+                target: "audio",
+                "Capture started without some sources: source={} active={active:?} missing={missing:?}",
+                source.as_str()
+            );
+            let _ = emit_event(
+                app,
+                events::AUDIO_PARTIAL_START,
+                PartialStart {
+                    source,
+                    active: active.clone(),
+                    missing: missing.clone(),
+                },
+            );
+        }
+        *self.sources.lock().unwrap() = (active, missing);
+    }
+}
+
+/// Pre-roll as configured: how much to keep and from which inputs.
+#[derive(Debug, Clone, PartialEq)]
+struct PreRollSettings {
+    seconds: u32,
+    source: AudioInputType,
+    devices: CaptureDevices,
+}
+
+impl PreRollSettings {
+    fn of(config: &AppConfig) -> Option<Self> {
+        if config.pre_roll_seconds == 0 {
+            return None;
+        }
+        let source = match config.pre_roll_source {
+            PreRollSource::Mixed => AudioInputType::Mixed,
+            PreRollSource::Mic | PreRollSource::Unknown => AudioInputType::Microphone,
+        };
+        Some(Self {
+            seconds: config.pre_roll_seconds,
+            source,
+            devices: CaptureDevices::for_source(config, source, None),
+        })
+    }
+}
+
+/// A capture kept running while recording is stopped. Its chunks fill a ring
+/// with the last `seconds` instead of going to an encoder; `start_capture`
+/// replays the ring into the new capture ahead of its live audio.
+struct PreRoll {
+    thread: ActiveThread,
+    ring: std::sync::Arc<Mutex<PreRollRing>>,
+    settings: PreRollSettings,
+}
+
+impl PreRoll {
+    fn start(app: &AppHandle, settings: PreRollSettings) -> Result<Self> {
+        let pool = BufferPool::new();
+        let ring = std::sync::Arc::new(Mutex::new(PreRollRing::new(settings.seconds)));
+        let (tx, rx) = unbounded::<EncodeJob>();
+        let sink = ChunkSink {
+            tx,
+            flow: std::sync::Arc::new(ChunkFlow::default()),
+            metrics: std::sync::Arc::new(CaptureMetrics::default()),
+        };
+        let filler = ring.clone();
+        let recycle = pool.clone();
+        // Exits once the capture threads drop their senders.
+        thread::spawn(move || {
+            crash::guard("audio-pre-roll", move || {
+                for job in rx.iter() {
+                    filler.lock().unwrap().push(job, &recycle);
                 }
+            })
+        });
+        let pending = open_capture(app, settings.source, &settings.devices, &sink, &pool)?;
+        if let Some(go_tx) = &pending.go_tx {
+            let _ = go_tx.send(());
+        }
+        Ok(Self {
+            thread: pending.thread,
+            ring,
+            settings,
+        })
+    }
+
+    /// Sends what the ring holds to `encoder`, oldest first. Returns the chunk count.
+    fn drain_into(&self, encoder: &ChunkSink) -> usize {
+        self.ring.lock().unwrap().replay(encoder)
+    }
+
+    /// Stops the capture, then hands the rest of the ring to `encoder` if given.
+    fn finish(self, encoder: Option<&ChunkSink>) -> usize {
+        self.thread.halt();
+        encoder
+            .map(|encoder| self.ring.lock().unwrap().replay(encoder))
+            .unwrap_or(0)
+    }
+}
+
+/// The newest pre-roll chunks, at most `limit_ms` of audio.
+struct PreRollRing {
+    jobs: VecDeque<EncodeJob>,
+    held_ms: f32,
+    limit_ms: f32,
+}
+
+impl PreRollRing {
+    fn new(seconds: u32) -> Self {
+        Self {
+            jobs: VecDeque::new(),
+            held_ms: 0.0,
+            limit_ms: seconds as f32 * 1000.0,
+        }
+    }
+
+    fn push(&mut self, job: EncodeJob, pool: &BufferPool) {
+        self.held_ms += job.duration_ms();
+        self.jobs.push_back(job);
+        while self.held_ms > self.limit_ms {
+            let Some(oldest) = self.jobs.pop_front() else {
+                break;
+            };
+            self.held_ms -= oldest.duration_ms();
+            pool.recycle(oldest.samples);
+        }
+    }
+
+    fn replay(&mut self, encoder: &ChunkSink) -> usize {
+        self.held_ms = 0.0;
+        let count = self.jobs.len();
+        for job in self.jobs.drain(..) {
+            encoder.forward(job);
+        }
+        count
+    }
+}
+
+/// Re-checks the pre-roll off the calling thread, since opening its streams
+/// blocks for up to two seconds.
+pub fn refresh_pre_roll(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        if let Some(audio) = app.try_state::<std::sync::Arc<AudioManager>>() {
+            audio.sync_pre_roll(&app);
+        }
+    });
+}
+
+/// Opens the streams for `source` and starts the thread that reads them. The
+/// thread waits for `PendingCapture::go_tx` before it feeds `encoder`, so a
+/// source switch can stop the old capture in between without mixing the two.
+fn open_capture(
+    app: &AppHandle,
+    source: AudioInputType,
+    selected: &CaptureDevices,
+    encoder: &ChunkSink,
+    pool: &BufferPool,
+) -> Result<PendingCapture> {
+    let host = cpal::default_host();
+
+    let (stop_tx, stop_rx) = unbounded::<()>();
+    let mut loopback_source: Option<String> = None;
+    let mut devices: Vec<(CaptureSource, Device)> = vec![];
+    // Mixed mode runs with whichever sources open; these are the ones that did not.
+    let mut missing: Vec<MissingSource> = vec![];
+    match source {
+        AudioInputType::Microphone => {
+            if let Some(dev) = find_device_by_id(&host, selected.mic.as_deref())? {
+                log::info!(target: "audio", "capture mic device: {}", dev.name().unwrap_or_default());
+                devices.push((CaptureSource::Mic, dev));
             }
-            "system" => {
-                // Use WASAPI loopback directly for system audio capture
-                #[cfg(windows)]
-                {
-                    match start_wasapi_loopback_capture(app.clone(), stop_tx.clone()) {
-                        Ok(stop_flag) => {
-                            // WASAPI loopback started successfully, skip CPAL
-                            let mut guard = self.active.lock().unwrap();
-                            *guard = Some(ActiveThread {
+        }
+        AudioInputType::System => {
+            // Use WASAPI loopback directly for system audio capture
+            #[cfg(windows)]
+            {
+                match start_wasapi_loopback_capture(app.clone(), encoder.clone(), stop_tx.clone()) {
+                    Ok(stop_flag) => {
+                        // WASAPI loopback started successfully, skip CPAL.
+                        // It feeds the encoder from its own thread right away, so
+                        // there is no start signal to hold it back.
+                        return Ok(PendingCapture {
+                            thread: ActiveThread {
                                 stop_tx,
                                 handle: None, // WASAPI runs in its own thread
                                 stop_flag: Some(stop_flag),
-                            });
-                            return Ok(());
-                        }
-                        Err(e) => {
-                            return Err(anyhow!("Failed to start WASAPI loopback capture: {}", e));
-                        }
+                            },
+                            go_tx: None,
+                            loopback_source: default_output_name(&host),
+                            active: vec![CaptureSource::System],
+                            missing,
+                        });
                     }
-                }
-                #[cfg(not(windows))]
-                {
-                    // Try CPAL fallback for non-Windows
-                    if let Some(dev) = find_system_device(&host, device_id.as_deref())? {
-                        eprintln!("[audio] capture system device: {}", dev.name().unwrap_or_default());
-                        devices.push(dev);
-                    } else {
-                        return Err(anyhow!(system_audio_help_message()));
+                    Err(e) => {
+                        return Err(anyhow!("Failed to start WASAPI loopback capture: {}", e));
                     }
                 }
             }
-            "mixed" => {
-                // In mixed mode, use WASAPI loopback for system audio and CPAL for mic
-                #[cfg(windows)]
-                {
-                    // Start WASAPI loopback capture for system audio with channel for mixing
-                    let (wasapi_tx, wasapi_rx) = unbounded::<Vec<i16>>();
-                    match start_wasapi_loopback_capture_for_mixing(stop_tx.clone(), wasapi_tx.clone()) {
-                        Ok(stop_flag) => {
-                            // Add WASAPI receiver to the list
-                            // We'll handle it specially in the capture loop
-                            if let Some(dev) = find_device_by_id(&host, device_id.as_deref())? {
-                                eprintln!("[audio] capture mic device: {}", dev.name().unwrap_or_default());
-                                devices.push(dev);
-                            }
-                            
-                            // Create a special receiver list that includes WASAPI
-                            let app_handle = app.clone();
-                            let stop_rx_clone = stop_rx.clone();
-                            let (ready_tx, ready_rx) = mpsc::channel::<usize>();
+            #[cfg(not(windows))]
+            {
+                // Try CPAL fallback for non-Windows
+                if let Some(dev) = find_system_device(&host, selected.system.as_deref())? {
+                    log::info!(target: "audio", "capture system device: {}", dev.name().unwrap_or_default());
+                    loopback_source = dev.name().ok();
+                    devices.push((CaptureSource::System, dev));
+                } else {
+                    return Err(DeviceNotFound(system_audio_help_message().to_string()).into());
+                }
+            }
+        }
+        AudioInputType::Mixed => {
+            // In mixed mode, use WASAPI loopback for system audio and CPAL for mic
+            #[cfg(windows)]
+            {
+                // Start WASAPI loopback capture for system audio with channel for mixing
+                let (wasapi_tx, wasapi_rx) = unbounded::<Vec<i16>>();
+                match start_wasapi_loopback_capture_for_mixing(app.clone(), stop_tx.clone(), wasapi_tx.clone()) {
+                    Ok(stop_flag) => {
+                        if let Some(dev) = find_mixed_mic(&CpalDevices(&host), selected, &mut missing) {
+                            devices.push((CaptureSource::Mic, dev));
+                        }
+                        
+                        let encoder = encoder.clone();
+                        let pool = pool.clone();
+                        let stream_app = app.clone();
+                        let stop_rx_clone = stop_rx.clone();
+                        let (ready_tx, ready_rx) = mpsc::channel::<OpenedSources>();
+                        let (go_tx, go_rx) = unbounded::<()>();
+                        
+                        let handle = thread::spawn(move || crash::guard("audio-capture", move || {
+                            let _boost = thread_priority::boost("audio-capture", false);
+                            // Сначала добавляем микрофон(ы) — это будет «основной» сигнал
+                            let mut opened = open_streams(&stream_app, devices, &pool);
                             
-                            let handle = thread::spawn(move || {
-                                let mut receivers = Vec::new();
-                                let mut configs = Vec::new();
-                                let mut streams: Vec<Stream> = Vec::new();
-                                
-                                // Сначала добавляем микрофон(ы) — это будет «основной» сигнал
-                                for device in devices {
-                                    let device_name = device.name().unwrap_or_else(|_| "Unknown".into());
-                                    let (tx, rx) = unbounded::<Vec<i16>>();
-                                    match build_input_stream(device, tx) {
-                                        Ok((stream, cfg)) => {
-                                            if stream.play().is_ok() {
-                                                eprintln!("[audio] Successfully started stream for device: {} (sample_rate: {}, channels: {})", 
-                                                    device_name, cfg.sample_rate.0, cfg.channels);
-                                                receivers.push(rx);
-                                                configs.push(cfg);
-                                                streams.push(stream);
-                                            } else {
-                                                eprintln!("[audio] Failed to play stream for device: {}", device_name);
-                                            }
-                                        }
-                                        Err(err) => eprintln!("[audio] failed to build stream for device {}: {}", device_name, err),
-                                    }
-                                }
-                                
-                                // В mixed-режиме системный звук идёт как дополнительный источник
-                                receivers.push(wasapi_rx);
-                                configs.push(StreamConfig {
-                                    channels: DEFAULT_CHANNELS,
-                                    sample_rate: cpal::SampleRate(DEFAULT_SAMPLE_RATE),
-                                    buffer_size: cpal::BufferSize::Default,
-                                });
-                                
-                                if receivers.is_empty() {
-                                    let _ = ready_tx.send(0);
-                                    return;
-                                }
-                                
-                                let _ = ready_tx.send(receivers.len());
-                                capture_loop(app_handle, receivers, stop_rx_clone, configs);
-                                drop(streams);
+                            // В mixed-режиме системный звук идёт как дополнительный источник
+                            opened.receivers.push(wasapi_rx);
+                            opened.configs.push(StreamConfig {
+                                channels: DEFAULT_CHANNELS,
+                                sample_rate: cpal::SampleRate(DEFAULT_SAMPLE_RATE),
+                                buffer_size: cpal::BufferSize::Default,
                             });
+                            opened.sources.active.push(CaptureSource::System);
                             
-                            let count = ready_rx.recv_timeout(std::time::Duration::from_secs(2)).unwrap_or(0);
-                            if count == 0 {
-                                return Err(anyhow!("Failed to start audio capture"));
+                            let _ = ready_tx.send(opened.sources.clone());
+                            if go_rx.recv().is_err() {
+                                return;
                             }
-                            
-                            let mut guard = self.active.lock().unwrap();
-                            *guard = Some(ActiveThread {
+                            capture_loop(encoder, pool, opened.sources.active, opened.receivers, stop_rx_clone, opened.configs);
+                            drop(opened.streams);
+                        }));
+                        
+                        let opened = ready_rx.recv_timeout(std::time::Duration::from_secs(2)).unwrap_or_default();
+                        if opened.active.is_empty() {
+                            stop_flag.store(true, Ordering::Relaxed);
+                            return Err(anyhow!("Failed to start audio capture"));
+                        }
+                        missing.extend(opened.missing);
+                        
+                        return Ok(PendingCapture {
+                            thread: ActiveThread {
                                 stop_tx,
                                 handle: Some(handle),
                                 stop_flag: Some(stop_flag),
-                            });
-                            return Ok(());
-                        }
-                        Err(e) => {
-                            eprintln!("[audio] Failed to start WASAPI loopback for mixed mode: {}", e);
-                            eprintln!("[audio] Falling back to CPAL for system audio in mixed mode");
-                            // Fall through to CPAL approach
-                        }
+                            },
+                            go_tx: Some(go_tx),
+                            loopback_source: default_output_name(&host),
+                            active: opened.active,
+                            missing,
+                        });
+                    }
+                    Err(e) => {
+                        log::warn!(target: "audio", "Failed to start WASAPI loopback for mixed mode: {}", e);
+                        log::info!(target: "audio", "Falling back to CPAL for system audio in mixed mode");
+                        // Fall through to CPAL approach
                     }
                 }
-                // Fallback: use CPAL for both (may not work well on Windows)
-                if let Some(dev) = find_device_by_id(&host, device_id.as_deref())? {
-                    eprintln!("[audio] capture mic device: {}", dev.name().unwrap_or_default());
-                    devices.push(dev);
-                }
-                if let Some(dev) = find_system_device(&host, None)? {
-                    eprintln!("[audio] capture system device for mixed mode: {}", dev.name().unwrap_or_default());
-                    devices.push(dev);
+            }
+            // Fallback: use CPAL for both (may not work well on Windows)
+            devices = mixed_devices(&CpalDevices(&host), selected, &mut missing)?;
+            loopback_source = devices
+                .iter()
+                .find(|(source, _)| *source == CaptureSource::System)
+                .and_then(|(_, dev)| dev.name().ok());
+        }
+        AudioInputType::Unknown => return Err(anyhow!("Unknown source")),
+    }
+
+    if devices.is_empty() {
+        return Err(no_sources_error(&missing));
+    }
+
+    let encoder = encoder.clone();
+    let pool = pool.clone();
+    let (ready_tx, ready_rx) = mpsc::channel::<OpenedSources>();
+    let (go_tx, go_rx) = unbounded::<()>();
+    let stream_app = app.clone();
+
+    let handle = thread::spawn(move || crash::guard("audio-capture", move || {
+        let _boost = thread_priority::boost("audio-capture", false);
+        let opened = open_streams(&stream_app, devices, &pool);
+        let _ = ready_tx.send(opened.sources.clone());
+        if opened.sources.active.is_empty() || go_rx.recv().is_err() {
+            return;
+        }
+        capture_loop(encoder, pool, opened.sources.active, opened.receivers, stop_rx, opened.configs);
+        drop(opened.streams);
+    }));
+
+    let opened = ready_rx.recv_timeout(std::time::Duration::from_secs(2)).unwrap_or_default();
+    missing.extend(opened.missing);
+    if opened.active.is_empty() {
+        return Err(if missing.is_empty() {
+            anyhow!("Failed to start audio capture")
+        } else {
+            no_sources_error(&missing)
+        });
+    }
+
+    Ok(PendingCapture {
+        thread: ActiveThread {
+            stop_tx,
+            handle: Some(handle),
+            #[cfg(windows)]
+            stop_flag: None,
+        },
+        go_tx: Some(go_tx),
+        loopback_source,
+        active: opened.active,
+        missing,
+    })
+}
+
+
+/// Streams opened by a capture thread, plus the bookkeeping reported back to `start`.
+#[derive(Default)]
+struct OpenedStreams {
+    receivers: Vec<Receiver<Vec<i16>>>,
+    configs: Vec<StreamConfig>,
+    streams: Vec<Stream>,
+    sources: OpenedSources,
+}
+
+#[derive(Debug, Clone, Default)]
+struct OpenedSources {
+    active: Vec<CaptureSource>,
+    missing: Vec<MissingSource>,
+}
+
+fn open_streams(
+    app: &AppHandle,
+    devices: Vec<(CaptureSource, Device)>,
+    pool: &BufferPool,
+) -> OpenedStreams {
+    let mut opened = OpenedStreams::default();
+    for (source, device) in devices {
+        let device_name = device.name().unwrap_or_else(|_| "Unknown".into());
+        let (tx, rx) = unbounded::<Vec<i16>>();
+        match build_input_stream(app.clone(), device, tx, pool.clone()) {
+            Ok((stream, cfg)) => {
+                if stream.play().is_ok() {
+                    log::info!(target: "audio", "Successfully started stream for device: {} (sample_rate: {}, channels: {})", 
+                        device_name, cfg.sample_rate.0, cfg.channels);
+                    opened.receivers.push(rx);
+                    opened.configs.push(cfg);
+                    opened.streams.push(stream);
+                    opened.sources.active.push(source);
                 } else {
-                    return Err(anyhow!(system_audio_help_message()));
+                    log::warn!(target: "audio", "Failed to play stream for device: {}", device_name);
+                    opened.sources.missing.push(MissingSource::new(
+                        source,
+                        format!("failed to play stream for {device_name}"),
+                    ));
                 }
             }
-            _ => return Err(anyhow!("Unknown source")),
+            Err(err) => {
+                log::warn!(target: "audio", "failed to build stream for device {}: {}", device_name, err);
+                opened.sources.missing.push(MissingSource::new(source, err));
+            }
         }
+    }
+    opened
+}
+
+fn validate_source(
+    app: &AppHandle,
+    host: &cpal::Host,
+    source: CaptureSource,
+    selected: &CaptureDevices,
+) -> SourceValidation {
+    let device = match source {
+        CaptureSource::Mic => match find_device_by_id(host, selected.mic.as_deref()) {
+            Ok(Some(device)) => device,
+            Ok(None) => return SourceValidation::failed(source, "no input device"),
+            Err(error) => return SourceValidation::failed(source, error),
+        },
+        // Capture records system audio through WASAPI loopback here, not cpal.
+        #[cfg(windows)]
+        CaptureSource::System => return validate_wasapi_loopback(app, host),
+        #[cfg(not(windows))]
+        CaptureSource::System => match find_system_device(host, selected.system.as_deref()) {
+            Ok(Some(device)) => device,
+            Ok(None) => return SourceValidation::failed(source, system_audio_help_message()),
+            Err(error) => return SourceValidation::failed(source, error),
+        },
+    };
+    let device_name = device.name().ok();
+    let (tx, rx) = unbounded::<Vec<i16>>();
+    let (stream, config) = match build_input_stream(app.clone(), device, tx, BufferPool::new()) {
+        Ok(opened) => opened,
+        Err(error) => return SourceValidation::failed(source, error),
+    };
+    if let Err(error) = stream.play() {
+        return SourceValidation::failed(source, error);
+    }
+    let level_db = listen_level(&rx);
+    drop(stream);
+    measured(
+        source,
+        device_name,
+        config.sample_rate.0,
+        config.channels,
+        level_db,
+    )
+}
+
+#[cfg(windows)]
+fn validate_wasapi_loopback(app: &AppHandle, host: &cpal::Host) -> SourceValidation {
+    let (tx, rx) = unbounded::<Vec<i16>>();
+    let (stop_tx, _stop_rx) = unbounded::<()>();
+    let stop_flag = match start_wasapi_loopback_capture_for_mixing(app.clone(), stop_tx, tx) {
+        Ok(stop_flag) => stop_flag,
+        Err(error) => return SourceValidation::failed(CaptureSource::System, error),
+    };
+    let level_db = listen_level(&rx);
+    stop_flag.store(true, Ordering::Relaxed);
+    measured(
+        CaptureSource::System,
+        default_output_name(host),
+        DEFAULT_SAMPLE_RATE,
+        DEFAULT_CHANNELS,
+        level_db,
+    )
+}
+
+/// Opens `mic`, or the default input, outside any capture and starts it. Chunks
+/// of interleaved samples arrive on the receiver until the stream is dropped.
+pub fn open_mic_probe(
+    app: &AppHandle,
+    mic: Option<&str>,
+) -> Result<(Stream, StreamConfig, Receiver<Vec<i16>>)> {
+    let host = cpal::default_host();
+    let device = find_device_by_id(&host, mic)?.ok_or_else(|| anyhow!("no input device"))?;
+    let (tx, rx) = unbounded::<Vec<i16>>();
+    let (stream, config) = build_input_stream(app.clone(), device, tx, BufferPool::new())?;
+    stream.play()?;
+    Ok((stream, config, rx))
+}
 
-        if devices.is_empty() {
-            return Err(anyhow!("No capture devices available"));
+/// RMS level of everything that arrives within `VALIDATE_LISTEN`; `None` for nothing.
+fn listen_level(rx: &Receiver<Vec<i16>>) -> Option<f32> {
+    let deadline = Instant::now() + VALIDATE_LISTEN;
+    let mut samples: Vec<i16> = Vec::new();
+    while let Some(remaining) = deadline.checked_duration_since(Instant::now()) {
+        match rx.recv_timeout(remaining) {
+            Ok(chunk) => samples.extend_from_slice(&chunk),
+            Err(_) => break,
         }
+    }
+    if samples.is_empty() {
+        return None;
+    }
+    let rms = audio_pipeline::rms(&samples);
+    Some(if rms > 0.0 {
+        (20.0 * rms.log10()).max(SILENCE_DB)
+    } else {
+        SILENCE_DB
+    })
+}
 
-        let app_handle = app.clone();
-        let (ready_tx, ready_rx) = mpsc::channel::<usize>();
+fn measured(
+    source: CaptureSource,
+    device_name: Option<String>,
+    sample_rate: u32,
+    channels: u16,
+    level_db: Option<f32>,
+) -> SourceValidation {
+    let error = level_db
+        .is_none()
+        .then(|| format!("No audio arrived within {} ms", VALIDATE_LISTEN.as_millis()));
+    SourceValidation {
+        source,
+        ok: error.is_none(),
+        device_name,
+        sample_rate: Some(sample_rate),
+        channels: Some(channels),
+        level_db,
+        error,
+    }
+}
 
-        let handle = thread::spawn(move || {
-            let mut receivers = Vec::new();
-            let mut configs = Vec::new();
-            let mut streams: Vec<Stream> = Vec::new();
+/// Finds the devices a mixed capture opens; tests substitute their own.
+trait DeviceLookup {
+    type Device;
+    fn mic(&self, id: Option<&str>) -> Result<Option<Self::Device>>;
+    fn system(&self, id: Option<&str>) -> Result<Option<Self::Device>>;
+}
 
-            for device in devices {
-                let device_name = device.name().unwrap_or_else(|_| "Unknown".into());
-                let (tx, rx) = unbounded::<Vec<i16>>();
-                match build_input_stream(device, tx) {
-                    Ok((stream, cfg)) => {
-                        if stream.play().is_ok() {
-                            eprintln!("[audio] Successfully started stream for device: {} (sample_rate: {}, channels: {})", 
-                                device_name, cfg.sample_rate.0, cfg.channels);
-                            receivers.push(rx);
-                            configs.push(cfg);
-                            streams.push(stream);
-                        } else {
-                            eprintln!("[audio] Failed to play stream for device: {}", device_name);
-                        }
-                    }
-                    Err(err) => eprintln!("[audio] failed to build stream for device {}: {}", device_name, err),
-                }
-            }
+struct CpalDevices<'a>(&'a cpal::Host);
 
-            if receivers.is_empty() {
-                let _ = ready_tx.send(0);
-                return;
-            }
+impl DeviceLookup for CpalDevices<'_> {
+    type Device = Device;
 
-            let _ = ready_tx.send(receivers.len());
-            capture_loop(app_handle, receivers, stop_rx, configs);
-            drop(streams);
-        });
+    fn mic(&self, id: Option<&str>) -> Result<Option<Device>> {
+        let found = find_device_by_id(self.0, id)?;
+        if let Some(dev) = &found {
+            log::info!(target: "audio", "capture mic device: {}", dev.name().unwrap_or_default());
+        }
+        Ok(found)
+    }
 
-        let count = ready_rx.recv_timeout(std::time::Duration::from_secs(2)).unwrap_or(0);
-        if count == 0 {
-            return Err(anyhow!("Failed to start audio capture"));
+    fn system(&self, id: Option<&str>) -> Result<Option<Device>> {
+        let found = find_system_device(self.0, id)?;
+        if let Some(dev) = &found {
+            log::info!(target: "audio", "capture system device for mixed mode: {}", dev.name().unwrap_or_default());
         }
+        Ok(found)
+    }
+}
 
-        let mut guard = self.active.lock().unwrap();
-        *guard = Some(ActiveThread {
-            stop_tx,
-            handle: Some(handle),
-            #[cfg(windows)]
-            stop_flag: None,
-        });
-        Ok(())
+/// Whichever of the mic and the system device of a mixed capture can be found;
+/// the others go to `missing`. Fails only when neither is there.
+fn mixed_devices<L: DeviceLookup>(
+    lookup: &L,
+    selected: &CaptureDevices,
+    missing: &mut Vec<MissingSource>,
+) -> Result<Vec<(CaptureSource, L::Device)>> {
+    let mut devices = Vec::new();
+    if let Some(dev) = find_mixed_mic(lookup, selected, missing) {
+        devices.push((CaptureSource::Mic, dev));
+    }
+    match lookup.system(selected.system.as_deref()) {
+        Ok(Some(dev)) => devices.push((CaptureSource::System, dev)),
+        Ok(None) => missing.push(MissingSource::new(
+            CaptureSource::System,
+            system_audio_help_message(),
+        )),
+        Err(error) => missing.push(MissingSource::new(CaptureSource::System, error)),
+    }
+    if devices.is_empty() {
+        return Err(no_sources_error(missing));
+    }
+    Ok(devices)
+}
+
+fn find_mixed_mic<L: DeviceLookup>(
+    lookup: &L,
+    selected: &CaptureDevices,
+    missing: &mut Vec<MissingSource>,
+) -> Option<L::Device> {
+    match lookup.mic(selected.mic.as_deref()) {
+        Ok(Some(dev)) => Some(dev),
+        Ok(None) => {
+            missing.push(MissingSource::new(CaptureSource::Mic, "no input device"));
+            None
+        }
+        Err(error) => {
+            missing.push(MissingSource::new(CaptureSource::Mic, error));
+            None
+        }
+    }
+}
+
+/// Capture failed because none of its devices could be found. Devices that appear
+/// late, such as Bluetooth headsets right after login, fail this way.
+#[derive(Debug)]
+pub struct DeviceNotFound(String);
+
+impl std::fmt::Display for DeviceNotFound {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for DeviceNotFound {}
+
+pub fn is_device_not_found(error: &anyhow::Error) -> bool {
+    error.downcast_ref::<DeviceNotFound>().is_some()
+}
+
+fn no_sources_error(missing: &[MissingSource]) -> anyhow::Error {
+    if missing.is_empty() {
+        return DeviceNotFound("No capture devices available".to_string()).into();
+    }
+    let reasons = missing
+        .iter()
+        .map(|missing| format!("{}: {}", missing.source.as_str(), missing.reason))
+        .collect::<Vec<_>>()
+        .join("; ");
+    DeviceNotFound(format!("No capture source could be started ({reasons})")).into()
+}
+
+/// WASAPI loopback records whatever the default render endpoint plays.
+#[cfg(windows)]
+fn default_output_name(host: &cpal::Host) -> Option<String> {
+    host.default_output_device().and_then(|device| device.name().ok())
+}
+
+/// Every device whose name can be read, with its id. Devices sharing a name get
+/// ids suffixed ` #2`, ` #3`, ... in enumeration order; the first keeps the plain
+/// name, so ids saved before duplicates were told apart still resolve.
+fn enumerate_devices(host: &cpal::Host) -> Result<Vec<(String, String, Device)>> {
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    let mut out = Vec::new();
+    for (index, device) in host.devices()?.enumerate() {
+        let name = match device.name() {
+            Ok(name) => name,
+            Err(error) => {
+                log::warn!(target: "audio", "Skipping device #{index}: name unreadable: {error}");
+                continue;
+            }
+        };
+        let count = seen.entry(name.clone()).or_insert(0);
+        *count += 1;
+        let id = if *count == 1 {
+            name.clone()
+        } else {
+            format!("{name} #{count}")
+        };
+        out.push((id, name, device));
     }
+    Ok(out)
 }
 
-fn build_device_info(device: &Device) -> Result<AudioDeviceInfo> {
-    let name = device.name().unwrap_or_else(|_| "Unknown".into());
+fn build_device_info(device: &Device, id: String, name: String) -> Result<AudioDeviceInfo> {
     let cfg = device
         .default_input_config()
         .or_else(|_| {
@@ -357,50 +1563,107 @@ fn build_device_info(device: &Device) -> Result<AudioDeviceInfo> {
     let sample_rate = cfg.sample_rate().0;
     let channels = cfg.channels();
     let lower = name.to_lowercase();
-    let kind = if is_system_device_name(&lower) {
+    let hands_free = is_hands_free_name(&lower);
+    let kind = if !hands_free && is_system_device_name(&lower) {
         "system"
     } else {
         "mic"
     };
+    let quality_hint = (hands_free || sample_rate <= NARROWBAND_MAX_SAMPLE_RATE)
+        .then_some(QUALITY_HINT_NARROWBAND);
     Ok(AudioDeviceInfo {
-        id: name.clone(),
+        id,
         name,
         kind: kind.to_string(),
         channels,
         sample_rate,
+        quality_hint,
     })
 }
 
 fn find_device_by_id(host: &cpal::Host, id: Option<&str>) -> Result<Option<Device>> {
     if let Some(target) = id {
-        for device in host.devices()? {
-            if let Ok(name) = device.name() {
-                if name == target {
-                    return Ok(Some(device));
-                }
-            }
+        if let Some((_, _, device)) = enumerate_devices(host)?
+            .into_iter()
+            .find(|(device_id, _, _)| device_id == target)
+        {
+            return Ok(Some(device));
         }
+        log::warn!(target: "audio", "Input device \"{target}\" not found, using the default device");
     }
     Ok(host.default_input_device())
 }
 
+/// Clears saved device ids that no longer resolve, off the setup path since device
+/// enumeration can take a while on Windows.
+pub fn prune_stale_devices(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let config_state = app.state::<std::sync::Arc<ConfigState>>();
+        let config = config_state.get().await;
+        let stale = match tauri::async_runtime::spawn_blocking(move || {
+            stale_device_settings(&config)
+        })
+        .await
+        {
+            Ok(Ok(stale)) => stale,
+            Ok(Err(error)) => {
+                log::warn!(target: "audio", "Failed to enumerate devices for config check: {error}");
+                return;
+            }
+            Err(_) => return,
+        };
+        if stale.is_empty() {
+            return;
+        }
+        let partial: serde_json::Map<String, serde_json::Value> = stale
+            .into_iter()
+            .map(|key| (key.to_string(), serde_json::Value::Null))
+            .collect();
+        if let Err(error) = config_state.update(partial.into(), "audio").await {
+            log::warn!(target: "audio", "Failed to clear stale devices: {error}");
+        }
+    });
+}
+
+/// Config keys whose saved device id no longer names a device on this host.
+fn stale_device_settings(config: &AppConfig) -> Result<Vec<&'static str>> {
+    let ids: Vec<String> = enumerate_devices(&cpal::default_host())?
+        .into_iter()
+        .map(|(id, _, _)| id)
+        .collect();
+    let saved = [
+        ("micDeviceId", &config.mic_device_id),
+        ("mixedMicDeviceId", &config.mixed_mic_device_id),
+        ("systemLoopbackDeviceId", &config.system_loopback_device_id),
+    ];
+    Ok(saved
+        .into_iter()
+        .filter_map(|(key, id)| {
+            let id = id.as_deref()?;
+            if ids.iter().any(|known| known == id) {
+                return None;
+            }
+            log::warn!(target: "audio", "Saved device {key}=\"{id}\" is no longer available; clearing it");
+            Some(key)
+        })
+        .collect())
+}
+
 fn find_system_device(host: &cpal::Host, id: Option<&str>) -> Result<Option<Device>> {
     if let Some(target) = id {
-        for device in host.devices()? {
-            if let Ok(name) = device.name() {
-                if name == target {
-                    // Verify it's actually a system device
-                    let lower = name.to_lowercase();
-                    if lower.contains("loopback")
-                        || lower.contains("monitor")
-                        || lower.contains("stereo mix")
-                        || lower.contains("blackhole")
-                        || lower.contains("soundflower")
-                    {
-                        // Check if it has input config
-                        if device.default_input_config().is_ok() || device.supported_input_configs().is_ok() {
-                            return Ok(Some(device));
-                        }
+        for (device_id, name, device) in enumerate_devices(host)? {
+            if device_id == target {
+                // Verify it's actually a system device
+                let lower = name.to_lowercase();
+                if lower.contains("loopback")
+                    || lower.contains("monitor")
+                    || lower.contains("stereo mix")
+                    || lower.contains("blackhole")
+                    || lower.contains("soundflower")
+                {
+                    // Check if it has input config
+                    if device.default_input_config().is_ok() || device.supported_input_configs().is_ok() {
+                        return Ok(Some(device));
                     }
                 }
             }
@@ -424,7 +1687,7 @@ fn find_system_device(host: &cpal::Host, id: Option<&str>) -> Result<Option<Devi
     {
         // On Windows 10+, WASAPI automatically creates loopback devices for each output device
         // These appear as input devices with the same name as the output device
-        eprintln!("[audio] Searching for WASAPI loopback devices...");
+        log::debug!(target: "audio", "Searching for WASAPI loopback devices...");
         let mut device_count = 0;
         let mut all_devices_info = Vec::new();
         
@@ -460,7 +1723,7 @@ fn find_system_device(host: &cpal::Host, id: Option<&str>) -> Result<Option<Devi
                     || lower.contains("микро");
                 
                 if is_mic {
-                    eprintln!("[audio] Skipping microphone: {} (input: {}, output: {})", name, has_input, has_output);
+                    log::debug!(target: "audio", "Skipping microphone: {} (input: {}, output: {})", name, has_input, has_output);
                     all_devices_info.push((name.clone(), lower, has_input, has_output));
                     continue;
                 }
@@ -471,7 +1734,7 @@ fn find_system_device(host: &cpal::Host, id: Option<&str>) -> Result<Option<Devi
                 // but can still be used for loopback capture if they have output
                 // Try to use output devices as loopback candidates
                 if !has_input && !has_output {
-                    eprintln!("[audio] Skipping device without input or output: {}", name);
+                    log::debug!(target: "audio", "Skipping device without input or output: {}", name);
                     continue;
                 }
                 
@@ -479,11 +1742,11 @@ fn find_system_device(host: &cpal::Host, id: Option<&str>) -> Result<Option<Devi
                 // On Windows 11, loopback devices often appear as output-only devices
                 // We'll add them to candidates but with lower priority
                 if !has_input && has_output {
-                    eprintln!("[audio] Found output device (may work as loopback): {} (output: true)", name);
+                    log::debug!(target: "audio", "Found output device (may work as loopback): {} (output: true)", name);
                     // Don't skip - add to candidates with lower priority
                 } else if !has_input {
                     // Skip devices without both input and output
-                    eprintln!("[audio] Skipping device without input: {} (output: {})", name, has_output);
+                    log::debug!(target: "audio", "Skipping device without input: {} (output: {})", name, has_output);
                     continue;
                 }
                 
@@ -495,26 +1758,26 @@ fn find_system_device(host: &cpal::Host, id: Option<&str>) -> Result<Option<Devi
                 // Priority based on name patterns and output capability
                 // On Windows 11, loopback devices often have "(WASAPI)" in the name
                 let priority = if lower.contains("loopback") {
-                    eprintln!("[audio] Found explicit loopback: {}", name);
+                    log::debug!(target: "audio", "Found explicit loopback: {}", name);
                     0
                 } else if lower.contains("(wasapi)") && (lower.contains("speakers") || lower.contains("динамики") || lower.contains("headphones") || lower.contains("наушники")) {
                     // Windows 11 loopback devices often named like "Speakers (WASAPI)" or "Динамики (WASAPI)"
-                    eprintln!("[audio] Found WASAPI loopback device: {}", name);
+                    log::debug!(target: "audio", "Found WASAPI loopback device: {}", name);
                     1
                 } else if lower.contains("monitor") {
-                    eprintln!("[audio] Found monitor: {}", name);
+                    log::debug!(target: "audio", "Found monitor: {}", name);
                     2
                 } else if lower.contains("stereo mix") || lower.contains("стерео микшер") {
-                    eprintln!("[audio] Found Stereo Mix: {}", name);
+                    log::debug!(target: "audio", "Found Stereo Mix: {}", name);
                     3
                 } else if has_output && has_input {
                     // Device with both input and output is likely a loopback device
-                    eprintln!("[audio] Found potential loopback (has both input and output): {}", name);
+                    log::debug!(target: "audio", "Found potential loopback (has both input and output): {}", name);
                     4
                 } else if has_output {
                     // Output device might work as loopback even without input config
                     // This is common on Windows 11
-                    eprintln!("[audio] Found output device (trying as loopback): {}", name);
+                    log::debug!(target: "audio", "Found output device (trying as loopback): {}", name);
                     5
                 } else {
                     // On Windows 11, loopback devices might not report output config correctly
@@ -535,10 +1798,10 @@ fn find_system_device(host: &cpal::Host, id: Option<&str>) -> Result<Option<Devi
                         .unwrap_or(false);
                     
                     if is_output_device_name || matches_default {
-                        eprintln!("[audio] Found potential loopback (output device name or matches default): {}", name);
+                        log::debug!(target: "audio", "Found potential loopback (output device name or matches default): {}", name);
                         6
                     } else {
-                        eprintln!("[audio] Skipping device (no clear loopback indicators): {}", name);
+                        log::debug!(target: "audio", "Skipping device (no clear loopback indicators): {}", name);
                         continue;
                     }
                 };
@@ -547,12 +1810,12 @@ fn find_system_device(host: &cpal::Host, id: Option<&str>) -> Result<Option<Devi
             }
         }
         
-        eprintln!("[audio] Scanned {} total devices, found {} loopback candidates", device_count, candidates.len());
+        log::debug!(target: "audio", "Scanned {} total devices, found {} loopback candidates", device_count, candidates.len());
         if candidates.is_empty() {
-            eprintln!("[audio] All available input devices:");
+            log::info!(target: "audio", "All available input devices:");
             for (name, lower, has_input, has_output) in all_devices_info {
                 if has_input {
-                    eprintln!("[audio]   - {} (input: true, output: {}, is_mic: {})", 
+                    log::info!(target: "audio", "- {} (input: true, output: {}, is_mic: {})", 
                         name, has_output, lower.contains("mic") || lower.contains("microphone"));
                 }
             }
@@ -582,7 +1845,7 @@ fn find_system_device(host: &cpal::Host, id: Option<&str>) -> Result<Option<Devi
 
         candidates.sort_by_key(|(_, _, priority)| *priority);
         if let Some((device, name, _)) = candidates.into_iter().next() {
-            eprintln!("[audio] Found system device: {}", name);
+            log::debug!(target: "audio", "Found system device: {}", name);
             return Ok(Some(device));
         } else {
             return Err(anyhow!(system_audio_help_message()));
@@ -593,55 +1856,60 @@ fn find_system_device(host: &cpal::Host, id: Option<&str>) -> Result<Option<Devi
     candidates.sort_by_key(|(_, _, priority)| *priority);
     
     if let Some((device, name, _)) = candidates.into_iter().next() {
-        eprintln!("[audio] Found system device: {}", name);
+        log::debug!(target: "audio", "Found system device: {}", name);
         Ok(Some(device))
     } else {
-        eprintln!("[audio] No system audio device found. Trying to use default output device as loopback...");
+        log::debug!(target: "audio", "No system audio device found. Trying to use default output device as loopback...");
         #[cfg(windows)]
         {
             if let Some(default_output) = host.default_output_device() {
                 if let Ok(name) = default_output.name() {
-                    eprintln!("[audio] Checking default output device: {}", name);
+                    log::debug!(target: "audio", "Checking default output device: {}", name);
                     if default_output.default_input_config().is_ok()
                         || default_output.supported_input_configs().is_ok()
                     {
-                        eprintln!("[audio] Using default output device as loopback: {}", name);
+                        log::debug!(target: "audio", "Using default output device as loopback: {}", name);
                         return Ok(Some(default_output));
                     }
                 }
             }
         }
-        eprintln!("[audio] Failed to find system audio device. WASAPI loopback may not be available on this system.");
+        log::warn!(target: "audio", "Failed to find system audio device. WASAPI loopback may not be available on this system.");
         Ok(None)
     }
 }
 
-fn build_input_stream(device: Device, tx: Sender<Vec<i16>>) -> Result<(Stream, StreamConfig)> {
+fn build_input_stream(
+    app: AppHandle,
+    device: Device,
+    tx: Sender<Vec<i16>>,
+    pool: BufferPool,
+) -> Result<(Stream, StreamConfig)> {
     let (supported, sample_format) = choose_config(&device)?;
     let mut config: StreamConfig = supported.into();
     if config.sample_rate.0 == 0 {
         config.sample_rate = cpal::SampleRate(DEFAULT_SAMPLE_RATE);
     }
 
-    let err_fn = |err| {
-        eprintln!("[audio] stream error: {}", err);
+    // cpal reports device loss and driver faults here, on its own thread.
+    let err_fn = move |err: cpal::StreamError| {
+        error_bus::publish(
+            &app,
+            "audio-stream",
+            ErrorCode::AudioCaptureFailed,
+            err,
+            false,
+        );
     };
 
-    // Send raw i16 data directly to avoid unnecessary conversions
-    // This reduces latency and prevents robotic sound
+    // Callbacks fill pooled buffers; the mixer hands them back once mixed.
     let stream = match sample_format {
         SampleFormat::F32 => device.build_input_stream(
             &config,
             move |data: &[f32], _| {
-                // Convert f32 to i16 directly without intermediate Vec<f32>
-                let i16_data: Vec<i16> = data
-                    .iter()
-                    .map(|&s| {
-                        let clamped = s.max(-1.0).min(1.0);
-                        (clamped * 32767.0).round() as i16
-                    })
-                    .collect();
-                let _ = tx.try_send(i16_data);
+                let mut buf = pool.take(data.len());
+                buf.extend(data.iter().map(|&s| audio_pipeline::f32_to_i16(s)));
+                let _ = tx.try_send(buf);
             },
             err_fn,
             None,
@@ -649,8 +1917,9 @@ fn build_input_stream(device: Device, tx: Sender<Vec<i16>>) -> Result<(Stream, S
         SampleFormat::I16 => device.build_input_stream(
             &config,
             move |data: &[i16], _| {
-                // Send i16 data directly - no conversion needed
-                let _ = tx.try_send(data.to_vec());
+                let mut buf = pool.take(data.len());
+                buf.extend_from_slice(data);
+                let _ = tx.try_send(buf);
             },
             err_fn,
             None,
@@ -658,15 +1927,9 @@ fn build_input_stream(device: Device, tx: Sender<Vec<i16>>) -> Result<(Stream, S
         SampleFormat::U16 => device.build_input_stream(
             &config,
             move |data: &[u16], _| {
-                // Convert u16 to i16
-                let i16_data: Vec<i16> = data
-                    .iter()
-                    .map(|&s| {
-                        // u16: 0-65535 -> i16: -32768 to 32767
-                        ((s as i32) - 32768) as i16
-                    })
-                    .collect();
-                let _ = tx.try_send(i16_data);
+                let mut buf = pool.take(data.len());
+                buf.extend(data.iter().map(|&s| audio_pipeline::u16_to_i16(s)));
+                let _ = tx.try_send(buf);
             },
             err_fn,
             None,
@@ -707,7 +1970,7 @@ fn choose_config(device: &Device) -> Result<(SupportedStreamConfig, SampleFormat
             }
             // If no matching input config, try to use output config directly
             // This might work for WASAPI loopback on Windows 11
-            eprintln!("[audio] Warning: Device has no input config, trying to use output config parameters");
+            log::warn!(target: "audio", "Device has no input config, trying to use output config parameters");
             // We can't use output config directly, so return error
         }
     }
@@ -715,12 +1978,230 @@ fn choose_config(device: &Device) -> Result<(SupportedStreamConfig, SampleFormat
     Err(anyhow!("No supported input config for device"))
 }
 
-fn capture_loop(app: AppHandle, receivers: Vec<Receiver<Vec<i16>>>, stop_rx: Receiver<()>, configs: Vec<StreamConfig>) {
+/// A mixed chunk on its way to the encoder thread.
+struct EncodeJob {
+    seq: u64,
+    samples: Vec<i16>,
+    sample_rate: u32,
+    channels: u16,
+    received_at: Instant,
+    captured_at_ms: i64,
+    /// Replayed from the pre-roll ring.
+    pre_roll: bool,
+    /// How far the system audio in it was ducked, in dB.
+    ducking_db: f32,
+}
+
+impl EncodeJob {
+    fn frames(&self) -> usize {
+        self.samples.len() / self.channels.max(1) as usize
+    }
+
+    fn duration_ms(&self) -> f32 {
+        self.frames() as f32 * 1000.0 / self.sample_rate.max(1) as f32
+    }
+}
+
+/// Hands captured chunks to the encoder thread, numbering them as they are
+/// captured. Every producer of a capture shares one, and a source switch keeps
+/// it, so `seq` only ever grows until the next `start`.
+#[derive(Clone)]
+struct ChunkSink {
+    tx: Sender<EncodeJob>,
+    flow: std::sync::Arc<ChunkFlow>,
+    metrics: std::sync::Arc<CaptureMetrics>,
+}
+
+impl ChunkSink {
+    /// Returns `false` once the encoder thread is gone.
+    fn send(
+        &self,
+        samples: Vec<i16>,
+        sample_rate: u32,
+        channels: u16,
+        received_at: Instant,
+        ducking_db: f32,
+    ) -> bool {
+        let seq = self.flow.last_seq.fetch_add(1, Ordering::Relaxed) + 1;
+        self.metrics.frames.fetch_add(
+            (samples.len() / channels.max(1) as usize) as u64,
+            Ordering::Relaxed,
+        );
+        let now_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_millis() as i64)
+            .unwrap_or_default();
+        let job = EncodeJob {
+            seq,
+            samples,
+            sample_rate,
+            channels,
+            received_at,
+            captured_at_ms: now_ms - received_at.elapsed().as_millis() as i64,
+            pre_roll: false,
+            ducking_db,
+        };
+        self.tx.send(job).is_ok()
+    }
+
+    /// Numbers a pre-roll chunk into this capture; its capture time stays.
+    fn forward(&self, mut job: EncodeJob) -> bool {
+        job.seq = self.flow.last_seq.fetch_add(1, Ordering::Relaxed) + 1;
+        job.pre_roll = true;
+        self.metrics
+            .frames
+            .fetch_add(job.frames() as u64, Ordering::Relaxed);
+        self.tx.send(job).is_ok()
+    }
+}
+
+/// Encodes and emits chunks on a dedicated thread so capture never waits on IPC.
+/// When the webview stops acknowledging chunks, new audio is held in a bounded
+/// backlog and only levels go out until acks catch up. The thread exits once every
+/// sender is dropped.
+fn spawn_encoder(
+    app: AppHandle,
+    pool: BufferPool,
+    metrics: std::sync::Arc<CaptureMetrics>,
+    flow: std::sync::Arc<ChunkFlow>,
+    monitor: std::sync::Arc<AudioMonitor>,
+    wake_words: std::sync::Arc<WakeWordDetector>,
+) -> ChunkSink {
+    let (tx, rx) = unbounded::<EncodeJob>();
+    let sink = ChunkSink {
+        tx,
+        flow: flow.clone(),
+        metrics: metrics.clone(),
+    };
+    thread::spawn(move || {
+        crash::guard("audio-encoder", move || {
+            let _boost = thread_priority::boost("audio-encoder", false);
+            let mut encoded = String::new();
+            let mut backlog: VecDeque<EncodeJob> = VecDeque::new();
+            // Dropped chunks leave gaps, so acks are measured against the newest emitted seq.
+            let mut emitted_seq: u64 = 0;
+            loop {
+                // Poll while holding a backlog so it drains even if capture goes quiet.
+                let incoming = if backlog.is_empty() {
+                    rx.recv().ok()
+                } else {
+                    match rx.recv_timeout(BACKLOG_POLL_INTERVAL) {
+                        Ok(job) => Some(job),
+                        Err(RecvTimeoutError::Timeout) => None,
+                        Err(RecvTimeoutError::Disconnected) => break,
+                    }
+                };
+                match incoming {
+                    Some(job) => {
+                        // The monitor plays live audio, so it never waits on the webview.
+                        // Pre-roll audio is seconds old: neither played nor searched
+                        // for wake words.
+                        if !job.pre_roll {
+                            monitor.feed(&job.samples, job.sample_rate, job.channels);
+                            wake_words.feed(&job.samples, job.sample_rate, job.channels);
+                        }
+                        backlog.push_back(job);
+                    }
+                    None if backlog.is_empty() => break,
+                    None => {}
+                }
+                if backlog.len() > MAX_BACKLOG_CHUNKS {
+                    if let Some(oldest) = backlog.pop_front() {
+                        metrics
+                            .backlog_dropped_frames
+                            .fetch_add(oldest.frames() as u64, Ordering::Relaxed);
+                        pool.recycle(oldest.samples);
+                        flow.dropped_chunks.fetch_add(1, Ordering::Relaxed);
+                    }
+                }
+
+                while emitted_seq.saturating_sub(flow.acked_seq.load(Ordering::Relaxed))
+                    < ACK_LAG_LIMIT
+                {
+                    let Some(job) = backlog.pop_front() else {
+                        break;
+                    };
+                    audio_pipeline::encode_chunk(&job.samples, &mut encoded);
+                    if !job.pre_roll {
+                        metrics.record(job.received_at.elapsed());
+                    }
+                    let payload = AudioChunkPayload {
+                        seq: job.seq,
+                        capture_timestamp_ms: job.captured_at_ms,
+                        duration_ms: job.duration_ms(),
+                        sample_rate: job.sample_rate,
+                        channels: job.channels,
+                        pre_roll: job.pre_roll,
+                        ducking_db: job.ducking_db,
+                        data_base64: &encoded,
+                    };
+                    let _ = emit_event(&app, events::AUDIO_CHUNK, payload);
+                    metrics.chunks_emitted.fetch_add(1, Ordering::Relaxed);
+                    metrics
+                        .bytes_emitted
+                        .fetch_add(encoded.len() as u64, Ordering::Relaxed);
+                    if !job.pre_roll {
+                        metrics.latency_micros.store(
+                            job.received_at.elapsed().as_micros() as u64,
+                            Ordering::Relaxed,
+                        );
+                    }
+                    emitted_seq = job.seq;
+                    pool.recycle(job.samples);
+                }
+
+                let lagging = !backlog.is_empty();
+                if let (true, Some(newest)) = (lagging, backlog.back()) {
+                    let _ = emit_event(
+                        &app,
+                        events::AUDIO_LEVEL,
+                        AudioLevelPayload {
+                            rms: audio_pipeline::rms(&newest.samples),
+                            ducking_db: newest.ducking_db,
+                        },
+                    );
+                }
+                if flow.throttled.swap(lagging, Ordering::Relaxed) != lagging {
+                    let dropped_chunks = flow.dropped_chunks.load(Ordering::Relaxed);
+                    log::warn!(
+                        target: "audio",
+                        "Chunk backpressure {}: buffered={} dropped={dropped_chunks}",
+                        if lagging { "started" } else { "cleared" },
+                        backlog.len()
+                    );
+                    let _ = emit_event(
+                        &app,
+                        events::AUDIO_BACKPRESSURE,
+                        AudioBackpressurePayload {
+                            active: lagging,
+                            buffered_chunks: backlog.len(),
+                            dropped_chunks,
+                        },
+                    );
+                }
+            }
+        })
+    });
+    sink
+}
+
+fn capture_loop(
+    encoder: ChunkSink,
+    pool: BufferPool,
+    sources: Vec<CaptureSource>,
+    receivers: Vec<Receiver<Vec<i16>>>,
+    stop_rx: Receiver<()>,
+    configs: Vec<StreamConfig>,
+) {
     let output_channels = DEFAULT_CHANNELS as usize;
     let device_channels: Vec<usize> = configs.iter().map(|c| c.channels as usize).collect();
-    let sample_rate = configs.get(0).map(|c| c.sample_rate.0).unwrap_or(DEFAULT_SAMPLE_RATE);
+    let sample_rate = configs
+        .first()
+        .map(|c| c.sample_rate.0)
+        .unwrap_or(DEFAULT_SAMPLE_RATE);
     // Коэффициент вклада системного звука в mixed-режиме (для визуального и фактического микса)
     let system_mix_gain: f32 = 0.1;
+    let mut ducker = audio_pipeline::Ducker::default();
 
     if receivers.is_empty() {
         return;
@@ -728,130 +2209,145 @@ fn capture_loop(app: AppHandle, receivers: Vec<Receiver<Vec<i16>>>, stop_rx: Rec
 
     loop {
         // Wait for first chunk or stop signal
-        let first_chunk = select! {
-            recv(stop_rx) -> _ => { break; }
-            recv(receivers[0]) -> msg => {
-                match msg {
-                    Ok(buf) => Some(buf),
-                    Err(_) => break,
-                }
-            }
+        let mut first_buf = select! {
+            recv(stop_rx) -> _ => break,
+            recv(receivers[0]) -> msg => match msg {
+                Ok(buf) => buf,
+                Err(_) => break,
+            },
         };
-        if first_chunk.is_none() {
-            break;
+        let received_at = Instant::now();
+        let mic_first = sources.first() == Some(&CaptureSource::Mic);
+        if mic_first {
+            audio_pipeline::apply_gain(&mut first_buf, mic_gain());
         }
-        
+
         // Process first chunk
-        let first_buf = first_chunk.unwrap();
         let first_samples = first_buf.len() / device_channels[0].max(1);
-        let mut mixed: Vec<i16> = vec![0i16; first_samples * output_channels];
-        
-        // Fill first device
-        fill_buffer_i16(&mut mixed, &first_buf, device_channels[0], output_channels, first_samples);
+        // The mic opens first in a mixed capture, so its level is known before
+        // the system audio is mixed in.
+        let (ducking_db, ducking_threshold_db) = ducking();
+        let duck_gain = if mic_first && receivers.len() > 1 && ducking_db > 0.0 {
+            ducker.update(
+                audio_pipeline::rms_db(&first_buf),
+                ducking_threshold_db,
+                ducking_db,
+                first_samples as f32 * 1000.0 / sample_rate.max(1) as f32,
+            )
+        } else {
+            ducker.reset();
+            1.0
+        };
+        let mut mixed = pool.take(first_samples * output_channels);
+        mixed.resize(first_samples * output_channels, 0);
+        audio_pipeline::fill_buffer_i16(
+            &mut mixed,
+            &first_buf,
+            device_channels[0],
+            output_channels,
+            first_samples,
+        );
+        pool.recycle(first_buf);
 
         // Process other devices (for mixed mode)
         for (idx, rx) in receivers.iter().enumerate().skip(1) {
             if let Ok(mut buf) = rx.try_recv() {
-                let dev_ch = if idx < device_channels.len() { device_channels[idx] } else { 1 };
-                let samples = buf.len() / dev_ch.max(1);
-                let frames = samples.min(first_samples);
-
-                // Понижаем уровень дополнительных источников (обычно системный звук)
-                for s in buf.iter_mut() {
-                    let v = (*s as f32) * system_mix_gain;
-                    // Клэмпим в диапазон i16
-                    *s = v
-                        .round()
-                        .clamp(i16::MIN as f32, i16::MAX as f32) as i16;
+                let dev_ch = device_channels.get(idx).copied().unwrap_or(1);
+                let available = buf.len() / dev_ch.max(1);
+                let frames = available.min(first_samples);
+                if let Some(&source) = sources.get(idx) {
+                    encoder.metrics.mixer_dropped_frames[source as usize]
+                        .fetch_add((available - frames) as u64, Ordering::Relaxed);
+                    if source == CaptureSource::Mic {
+                        audio_pipeline::apply_gain(&mut buf, mic_gain());
+                    }
                 }
-
-                fill_buffer_i16(&mut mixed, &buf, dev_ch, output_channels, frames);
+                let gain = if sources.get(idx) == Some(&CaptureSource::System) {
+                    system_mix_gain * duck_gain
+                } else {
+                    system_mix_gain
+                };
+                // Понижаем уровень дополнительных источников (обычно системный звук)
+                audio_pipeline::mix_secondary(
+                    &mut mixed,
+                    &mut buf,
+                    dev_ch,
+                    output_channels,
+                    frames,
+                    gain,
+                );
+                pool.recycle(buf);
             }
         }
 
-        // Check for clipping and normalize if needed
-        let max_amp = mixed.iter().fold(0i32, |acc, &s| acc.max(s.abs() as i32));
-        if max_amp > 32767 {
-            // Normalize to prevent clipping
-            let gain = 32767.0 / max_amp as f32;
-            for s in mixed.iter_mut() {
-                *s = ((*s as f32) * gain).round() as i16;
-            }
+        if audio_pipeline::normalize_peak(&mut mixed) {
+            encoder
+                .metrics
+                .peak_normalizations
+                .fetch_add(1, Ordering::Relaxed);
         }
 
-        // Send directly as i16 - no unnecessary conversions
-        let bytes: &[u8] = bytemuck::cast_slice(&mixed);
-        let payload = AudioChunkPayload {
+        if !encoder.send(
+            mixed,
             sample_rate,
-            channels: DEFAULT_CHANNELS,
-            data_base64: general_purpose::STANDARD.encode(bytes),
-        };
-        let _ = app.emit("audio:chunk", payload);
-    }
-}
-
-fn fill_buffer_i16(target: &mut [i16], src: &[i16], src_channels: usize, dst_channels: usize, frames: usize) {
-    if src.is_empty() || target.is_empty() || src_channels == 0 || dst_channels == 0 {
-        return;
-    }
-    
-    // src is interleaved: [L, R, L, R, ...] for stereo or [M, M, M, ...] for mono
-    // target is interleaved: [L, R, L, R, ...] for stereo output
-    let frames = frames.min(target.len() / dst_channels).min(src.len() / src_channels);
-    
-    if src_channels == 1 && dst_channels == 2 {
-        // Mono to stereo: duplicate channel
-        for i in 0..frames {
-            let sample = src[i];
-            let base = i * dst_channels;
-            if base < target.len() {
-                target[base] = target[base].saturating_add(sample);
-            }
-            if base + 1 < target.len() {
-                target[base + 1] = target[base + 1].saturating_add(sample);
-            }
-        }
-    } else if src_channels == dst_channels {
-        // Same channel count: add to target (for mixing)
-        let samples_to_copy = frames * src_channels;
-        let limit = samples_to_copy.min(target.len()).min(src.len());
-        for i in 0..limit {
-            target[i] = target[i].saturating_add(src[i]);
-        }
-    } else if src_channels == 2 && dst_channels == 1 {
-        // Stereo to mono: average channels
-        for i in 0..frames {
-            let left_idx = i * src_channels;
-            let right_idx = left_idx + 1;
-            if left_idx < src.len() && right_idx < src.len() && i < target.len() {
-                let avg = ((src[left_idx] as i32 + src[right_idx] as i32) / 2) as i16;
-                target[i] = target[i].saturating_add(avg);
-            }
-        }
-    } else {
-        // Different channel counts: map channels
-        for i in 0..frames {
-            for dst_ch in 0..dst_channels {
-                let src_ch = dst_ch % src_channels;
-                let src_idx = i * src_channels + src_ch;
-                let dst_idx = i * dst_channels + dst_ch;
-                if src_idx < src.len() && dst_idx < target.len() {
-                    target[dst_idx] = target[dst_idx].saturating_add(src[src_idx]);
-                }
-            }
+            DEFAULT_CHANNELS,
+            received_at,
+            ducker.attenuation_db(),
+        ) {
+            break;
         }
     }
 }
 
 #[derive(Serialize, Clone)]
-struct AudioChunkPayload {
+#[serde(rename_all = "camelCase")]
+struct AudioChunkPayload<'a> {
+    /// Numbered at capture, from 1 per capture and unbroken by source switches;
+    /// a gap means the chunks in it were dropped. Echoed back through `audio_ack`.
+    seq: u64,
+    /// Host wall clock, in Unix milliseconds, when the chunk was captured.
+    capture_timestamp_ms: i64,
+    duration_ms: f32,
     sample_rate: u32,
     channels: u16,
-    data_base64: String,
+    /// Captured before the capture started, by the pre-roll; these come first.
+    pre_roll: bool,
+    /// How far the system audio was ducked under the user's voice, in dB; 0 when not.
+    ducking_db: f32,
+    data_base64: &'a str,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct AudioLevelPayload {
+    rms: f32,
+    ducking_db: f32,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct AudioBackpressurePayload {
+    active: bool,
+    buffered_chunks: usize,
+    dropped_chunks: u64,
+}
+
+/// Logs a failed WASAPI call with its decoded HRESULT, keeps it for
+/// `audio_run_diagnostics` and reports it on the error bus.
+#[cfg(windows)]
+fn wasapi_failure(app: &AppHandle, step: &str, code: windows::core::HRESULT) {
+    let decoded = hresult::record(step, code.0);
+    error_bus::publish(
+        app,
+        "wasapi-loopback",
+        ErrorCode::AudioCaptureFailed,
+        format!("{step} failed: {decoded}"),
+        true,
+    );
 }
 
 #[cfg(windows)]
-fn start_wasapi_loopback_capture(app: AppHandle, _stop_tx: Sender<()>) -> Result<std::sync::Arc<std::sync::atomic::AtomicBool>> {
+fn start_wasapi_loopback_capture(app: AppHandle, encoder: ChunkSink, _stop_tx: Sender<()>) -> Result<std::sync::Arc<std::sync::atomic::AtomicBool>> {
     use std::sync::atomic::{AtomicBool, Ordering};
     use std::sync::Arc;
     use std::time::Duration;
@@ -859,19 +2355,20 @@ fn start_wasapi_loopback_capture(app: AppHandle, _stop_tx: Sender<()>) -> Result
     use windows::Win32::System::Com::*;
     use windows::core::Interface;
     
-    let app_clone = app.clone();
     let stop_flag = Arc::new(AtomicBool::new(false));
     let stop_flag_clone = stop_flag.clone();
     
-    thread::spawn(move || {
+    thread::spawn(move || crash::guard("wasapi-loopback", move || {
+        let _boost = thread_priority::boost("wasapi-loopback", true);
         unsafe {
             // Initialize COM
-            if CoInitializeEx(None, COINIT_MULTITHREADED).is_err() {
-                eprintln!("[audio] Failed to initialize COM");
+            let hr = CoInitializeEx(None, COINIT_MULTITHREADED);
+            if hr.is_err() {
+                wasapi_failure(&app, "CoInitializeEx", hr);
                 return;
             }
             
-            eprintln!("[audio] COM initialized");
+            log::info!(target: "audio", "COM initialized");
             
             // Get device enumerator
             let enumerator: IMMDeviceEnumerator = match CoCreateInstance(
@@ -881,7 +2378,7 @@ fn start_wasapi_loopback_capture(app: AppHandle, _stop_tx: Sender<()>) -> Result
             ) {
                 Ok(e) => e,
                 Err(e) => {
-                    eprintln!("[audio] Failed to create device enumerator: {:?}", e);
+                    wasapi_failure(&app, "Create device enumerator", e.code());
                     CoUninitialize();
                     return;
                 }
@@ -891,7 +2388,7 @@ fn start_wasapi_loopback_capture(app: AppHandle, _stop_tx: Sender<()>) -> Result
             let device = match enumerator.GetDefaultAudioEndpoint(eRender, eConsole) {
                 Ok(d) => d,
                 Err(e) => {
-                    eprintln!("[audio] Failed to get default render device: {:?}", e);
+                    wasapi_failure(&app, "GetDefaultAudioEndpoint", e.code());
                     CoUninitialize();
                     return;
                 }
@@ -903,7 +2400,7 @@ fn start_wasapi_loopback_capture(app: AppHandle, _stop_tx: Sender<()>) -> Result
                 Err(_) => "Unknown".to_string(),
             };
             
-            eprintln!("[audio] Using WASAPI loopback device: {}", device_id);
+            log::info!(target: "audio", "Using WASAPI loopback device: {}", device_id);
             
             // Activate audio client
             // In Windows API, IMMDevice::Activate is used to get IAudioClient
@@ -944,8 +2441,8 @@ fn start_wasapi_loopback_capture(app: AppHandle, _stop_tx: Sender<()>) -> Result
             } {
                 Ok(ac) => ac,
                 Err(e) => {
-                    eprintln!("[audio] Failed to activate audio client: {:?}", e);
-                    eprintln!("[audio] Falling back to CPAL for system audio capture");
+                    wasapi_failure(&app, "Activate audio client", e);
+                    log::info!(target: "audio", "Falling back to CPAL for system audio capture");
                     CoUninitialize();
                     return;
                 }
@@ -955,14 +2452,14 @@ fn start_wasapi_loopback_capture(app: AppHandle, _stop_tx: Sender<()>) -> Result
             let mix_format_ptr = match audio_client.GetMixFormat() {
                 Ok(ptr) => ptr,
                 Err(e) => {
-                    eprintln!("[audio] Failed to get mix format: {:?}", e);
+                    wasapi_failure(&app, "GetMixFormat", e.code());
                     CoUninitialize();
                     return;
                 }
             };
             
             if mix_format_ptr.is_null() {
-                eprintln!("[audio] Mix format pointer is null");
+                log::warn!(target: "audio", "Mix format pointer is null");
                 CoUninitialize();
                 return;
             }
@@ -1004,7 +2501,7 @@ fn start_wasapi_loopback_capture(app: AppHandle, _stop_tx: Sender<()>) -> Result
                 }
             };
             
-            eprintln!("[audio] WASAPI format: sample_rate={}, channels={}, bits_per_sample={}", 
+            log::info!(target: "audio", "WASAPI format: sample_rate={}, channels={}, bits_per_sample={}", 
                 sample_rate, channels, actual_bits_per_sample);
             
             // Initialize audio client in loopback mode
@@ -1020,33 +2517,33 @@ fn start_wasapi_loopback_capture(app: AppHandle, _stop_tx: Sender<()>) -> Result
                 None,
             );
             
-            if hr.is_err() {
-                eprintln!("[audio] Failed to initialize WASAPI loopback client: {:?}", hr);
+            if let Err(error) = hr {
+                wasapi_failure(&app, "Initialize loopback client", error.code());
                 CoTaskMemFree(Some(mix_format_ptr as *const _));
                 CoUninitialize();
                 return;
             }
             
-            eprintln!("[audio] WASAPI loopback client initialized");
+            log::info!(target: "audio", "WASAPI loopback client initialized");
             
             // Get buffer size
             let buffer_frames = match audio_client.GetBufferSize() {
                 Ok(frames) => frames,
                 Err(e) => {
-                    eprintln!("[audio] Failed to get buffer frames: {:?}", e);
+                    wasapi_failure(&app, "GetBufferSize", e.code());
                     CoTaskMemFree(Some(mix_format_ptr as *const _));
                     CoUninitialize();
                     return;
                 }
             };
             
-            eprintln!("[audio] WASAPI buffer frames: {}", buffer_frames);
+            log::info!(target: "audio", "WASAPI buffer frames: {}", buffer_frames);
             
             // Get capture client
             let capture_client: IAudioCaptureClient = match audio_client.GetService::<IAudioCaptureClient>() {
                 Ok(cc) => cc,
                 Err(e) => {
-                    eprintln!("[audio] Failed to get capture client: {:?}", e);
+                    wasapi_failure(&app, "GetService(IAudioCaptureClient)", e.code());
                     CoTaskMemFree(Some(mix_format_ptr as *const _));
                     CoUninitialize();
                     return;
@@ -1055,21 +2552,23 @@ fn start_wasapi_loopback_capture(app: AppHandle, _stop_tx: Sender<()>) -> Result
             
             // Start capture
             let hr = audio_client.Start();
-            if hr.is_err() {
-                eprintln!("[audio] Failed to start WASAPI loopback stream: {:?}", hr);
+            if let Err(error) = hr {
+                wasapi_failure(&app, "Start loopback stream", error.code());
                 CoTaskMemFree(Some(mix_format_ptr as *const _));
                 CoUninitialize();
                 return;
             }
             
-            eprintln!("[audio] WASAPI loopback stream started");
+            log::info!(target: "audio", "WASAPI loopback stream started");
             
             // Capture loop
             let stop_flag_capture = stop_flag_clone.clone();
+            // Reported once per failure streak; the loop keeps polling in case it recovers.
+            let mut buffer_failing = false;
             loop {
                 // Check for stop signal
                 if stop_flag_capture.load(Ordering::Relaxed) {
-                    eprintln!("[audio] WASAPI loopback capture stopped by signal");
+                    log::info!(target: "audio", "WASAPI loopback capture stopped by signal");
                     break;
                 }
                 
@@ -1088,12 +2587,22 @@ fn start_wasapi_loopback_capture(app: AppHandle, _stop_tx: Sender<()>) -> Result
                     Some(&mut qpc_position),
                 );
                 
-                if hr.is_err() || data_ptr.is_null() || available_frames == 0 {
+                if let Err(error) = hr {
+                    if !buffer_failing {
+                        buffer_failing = true;
+                        wasapi_failure(&app, "GetBuffer", error.code());
+                    }
+                    thread::sleep(Duration::from_millis(10));
+                    continue;
+                }
+                buffer_failing = false;
+                if data_ptr.is_null() || available_frames == 0 {
                     thread::sleep(Duration::from_millis(10));
                     continue;
                 }
                 
                 // Convert to i16 samples
+                let received_at = Instant::now();
                 let frame_samples = available_frames as usize * channels as usize;
                 
                 let samples: Vec<i16> = match actual_bits_per_sample {
@@ -1119,7 +2628,7 @@ fn start_wasapi_loopback_capture(app: AppHandle, _stop_tx: Sender<()>) -> Result
                             .collect()
                     }
                     _ => {
-                        eprintln!("[audio] Unsupported bits per sample: {}, trying to convert from bytes", actual_bits_per_sample);
+                        log::debug!(target: "audio", "Unsupported bits per sample: {}, trying to convert from bytes", actual_bits_per_sample);
                         let _ = capture_client.ReleaseBuffer(available_frames);
                         thread::sleep(Duration::from_millis(10));
                         continue;
@@ -1134,14 +2643,8 @@ fn start_wasapi_loopback_capture(app: AppHandle, _stop_tx: Sender<()>) -> Result
                     continue;
                 }
                 
-                // Send chunk
-                let bytes: &[u8] = bytemuck::cast_slice(&samples);
-                let payload = AudioChunkPayload {
-                    sample_rate,
-                    channels,
-                    data_base64: general_purpose::STANDARD.encode(bytes),
-                };
-                let _ = app_clone.emit("audio:chunk", payload);
+                // Hand off to the encoder thread
+                let _ = encoder.send(samples, sample_rate, channels, received_at, 0.0);
             }
             
             // Cleanup
@@ -1149,15 +2652,16 @@ fn start_wasapi_loopback_capture(app: AppHandle, _stop_tx: Sender<()>) -> Result
             CoTaskMemFree(Some(mix_format_ptr as *const _));
             CoUninitialize();
             
-            eprintln!("[audio] WASAPI loopback capture thread ended");
+            log::info!(target: "audio", "WASAPI loopback capture thread ended");
         }
-    });
+    }));
     
     Ok(stop_flag)
 }
 
 #[cfg(windows)]
 fn start_wasapi_loopback_capture_for_mixing(
+    app: AppHandle,
     _stop_tx: Sender<()>,
     tx: Sender<Vec<i16>>,
 ) -> Result<std::sync::Arc<std::sync::atomic::AtomicBool>> {
@@ -1171,15 +2675,17 @@ fn start_wasapi_loopback_capture_for_mixing(
     let stop_flag = Arc::new(AtomicBool::new(false));
     let stop_flag_clone = stop_flag.clone();
     
-    thread::spawn(move || {
+    thread::spawn(move || crash::guard("wasapi-loopback", move || {
+        let _boost = thread_priority::boost("wasapi-loopback", true);
         unsafe {
             // Initialize COM
-            if CoInitializeEx(None, COINIT_MULTITHREADED).is_err() {
-                eprintln!("[audio] Failed to initialize COM");
+            let hr = CoInitializeEx(None, COINIT_MULTITHREADED);
+            if hr.is_err() {
+                wasapi_failure(&app, "CoInitializeEx", hr);
                 return;
             }
             
-            eprintln!("[audio] COM initialized for mixed mode");
+            log::info!(target: "audio", "COM initialized for mixed mode");
             
             // Get device enumerator
             let enumerator: IMMDeviceEnumerator = match CoCreateInstance(
@@ -1189,7 +2695,7 @@ fn start_wasapi_loopback_capture_for_mixing(
             ) {
                 Ok(e) => e,
                 Err(e) => {
-                    eprintln!("[audio] Failed to create device enumerator: {:?}", e);
+                    wasapi_failure(&app, "Create device enumerator", e.code());
                     CoUninitialize();
                     return;
                 }
@@ -1199,7 +2705,7 @@ fn start_wasapi_loopback_capture_for_mixing(
             let device = match enumerator.GetDefaultAudioEndpoint(eRender, eConsole) {
                 Ok(d) => d,
                 Err(e) => {
-                    eprintln!("[audio] Failed to get default render device: {:?}", e);
+                    wasapi_failure(&app, "GetDefaultAudioEndpoint", e.code());
                     CoUninitialize();
                     return;
                 }
@@ -1238,7 +2744,7 @@ fn start_wasapi_loopback_capture_for_mixing(
             } {
                 Ok(ac) => ac,
                 Err(e) => {
-                    eprintln!("[audio] Failed to activate audio client: {:?}", e);
+                    wasapi_failure(&app, "Activate audio client", e);
                     CoUninitialize();
                     return;
                 }
@@ -1248,14 +2754,14 @@ fn start_wasapi_loopback_capture_for_mixing(
             let mix_format_ptr = match audio_client.GetMixFormat() {
                 Ok(ptr) => ptr,
                 Err(e) => {
-                    eprintln!("[audio] Failed to get mix format: {:?}", e);
+                    wasapi_failure(&app, "GetMixFormat", e.code());
                     CoUninitialize();
                     return;
                 }
             };
             
             if mix_format_ptr.is_null() {
-                eprintln!("[audio] Mix format pointer is null");
+                log::warn!(target: "audio", "Mix format pointer is null");
                 CoUninitialize();
                 return;
             }
@@ -1286,7 +2792,7 @@ fn start_wasapi_loopback_capture_for_mixing(
                 }
             };
             
-            eprintln!("[audio] WASAPI format for mixing: sample_rate={}, channels={}, bits_per_sample={}", 
+            log::info!(target: "audio", "WASAPI format for mixing: sample_rate={}, channels={}, bits_per_sample={}", 
                 sample_rate, channels, actual_bits_per_sample);
             
             // Initialize audio client in loopback mode
@@ -1300,8 +2806,8 @@ fn start_wasapi_loopback_capture_for_mixing(
                 None,
             );
             
-            if hr.is_err() {
-                eprintln!("[audio] Failed to initialize WASAPI loopback client: {:?}", hr);
+            if let Err(error) = hr {
+                wasapi_failure(&app, "Initialize loopback client", error.code());
                 CoTaskMemFree(Some(mix_format_ptr as *const _));
                 CoUninitialize();
                 return;
@@ -1311,19 +2817,19 @@ fn start_wasapi_loopback_capture_for_mixing(
             let buffer_frames = match audio_client.GetBufferSize() {
                 Ok(frames) => frames,
                 Err(e) => {
-                    eprintln!("[audio] Failed to get buffer frames: {:?}", e);
+                    wasapi_failure(&app, "GetBufferSize", e.code());
                     CoTaskMemFree(Some(mix_format_ptr as *const _));
                     CoUninitialize();
                     return;
                 }
             };
-            eprintln!("[audio] WASAPI buffer frames (mixing): {}", buffer_frames);
+            log::info!(target: "audio", "WASAPI buffer frames (mixing): {}", buffer_frames);
             
             // Get capture client
             let capture_client: IAudioCaptureClient = match audio_client.GetService::<IAudioCaptureClient>() {
                 Ok(cc) => cc,
                 Err(e) => {
-                    eprintln!("[audio] Failed to get capture client: {:?}", e);
+                    wasapi_failure(&app, "GetService(IAudioCaptureClient)", e.code());
                     CoTaskMemFree(Some(mix_format_ptr as *const _));
                     CoUninitialize();
                     return;
@@ -1332,20 +2838,22 @@ fn start_wasapi_loopback_capture_for_mixing(
             
             // Start capture
             let hr = audio_client.Start();
-            if hr.is_err() {
-                eprintln!("[audio] Failed to start WASAPI loopback stream: {:?}", hr);
+            if let Err(error) = hr {
+                wasapi_failure(&app, "Start loopback stream", error.code());
                 CoTaskMemFree(Some(mix_format_ptr as *const _));
                 CoUninitialize();
                 return;
             }
             
-            eprintln!("[audio] WASAPI loopback stream started for mixing");
+            log::info!(target: "audio", "WASAPI loopback stream started for mixing");
             
             // Capture loop - send to channel instead of emitting directly
             let stop_flag_capture = stop_flag_clone.clone();
+            // Reported once per failure streak; the loop keeps polling in case it recovers.
+            let mut buffer_failing = false;
             loop {
                 if stop_flag_capture.load(Ordering::Relaxed) {
-                    eprintln!("[audio] WASAPI loopback capture stopped by signal");
+                    log::info!(target: "audio", "WASAPI loopback capture stopped by signal");
                     break;
                 }
                 
@@ -1363,7 +2871,16 @@ fn start_wasapi_loopback_capture_for_mixing(
                     Some(&mut qpc_position),
                 );
                 
-                if hr.is_err() || data_ptr.is_null() || available_frames == 0 {
+                if let Err(error) = hr {
+                    if !buffer_failing {
+                        buffer_failing = true;
+                        wasapi_failure(&app, "GetBuffer", error.code());
+                    }
+                    thread::sleep(Duration::from_millis(10));
+                    continue;
+                }
+                buffer_failing = false;
+                if data_ptr.is_null() || available_frames == 0 {
                     thread::sleep(Duration::from_millis(10));
                     continue;
                 }
@@ -1413,9 +2930,278 @@ fn start_wasapi_loopback_capture_for_mixing(
             CoTaskMemFree(Some(mix_format_ptr as *const _));
             CoUninitialize();
             
-            eprintln!("[audio] WASAPI loopback capture thread ended for mixing");
+            log::info!(target: "audio", "WASAPI loopback capture thread ended for mixing");
         }
-    });
+    }));
     
     Ok(stop_flag)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Devices are plain names; `Err` stands for a lookup that failed.
+    struct FakeDevices {
+        mic: Result<Option<&'static str>, &'static str>,
+        system: Result<Option<&'static str>, &'static str>,
+    }
+
+    impl DeviceLookup for FakeDevices {
+        type Device = &'static str;
+
+        fn mic(&self, _id: Option<&str>) -> Result<Option<&'static str>> {
+            self.mic.map_err(|error| anyhow!(error))
+        }
+
+        fn system(&self, _id: Option<&str>) -> Result<Option<&'static str>> {
+            self.system.map_err(|error| anyhow!(error))
+        }
+    }
+
+    type Found = Vec<(CaptureSource, &'static str)>;
+
+    fn lookup(lookup: FakeDevices) -> (Result<Found>, Vec<MissingSource>) {
+        let mut missing = Vec::new();
+        let devices = mixed_devices(&lookup, &CaptureDevices::default(), &mut missing);
+        (devices, missing)
+    }
+
+    fn sources(missing: &[MissingSource]) -> Vec<CaptureSource> {
+        missing.iter().map(|missing| missing.source).collect()
+    }
+
+    #[test]
+    fn mixed_capture_starts_with_both_sources_when_both_are_found() {
+        let (devices, missing) = lookup(FakeDevices {
+            mic: Ok(Some("headset")),
+            system: Ok(Some("monitor")),
+        });
+        assert_eq!(
+            devices.unwrap(),
+            [(CaptureSource::Mic, "headset"), (CaptureSource::System, "monitor")]
+        );
+        assert!(missing.is_empty());
+    }
+
+    #[test]
+    fn mixed_capture_starts_mic_only_without_a_system_device() {
+        let (devices, missing) = lookup(FakeDevices {
+            mic: Ok(Some("headset")),
+            system: Ok(None),
+        });
+        assert_eq!(devices.unwrap(), [(CaptureSource::Mic, "headset")]);
+        assert_eq!(sources(&missing), [CaptureSource::System]);
+        assert_eq!(missing[0].reason, system_audio_help_message());
+    }
+
+    #[test]
+    fn mixed_capture_starts_system_only_without_a_microphone() {
+        let (devices, missing) = lookup(FakeDevices {
+            mic: Ok(None),
+            system: Ok(Some("monitor")),
+        });
+        assert_eq!(devices.unwrap(), [(CaptureSource::System, "monitor")]);
+        assert_eq!(sources(&missing), [CaptureSource::Mic]);
+        assert_eq!(missing[0].reason, "no input device");
+    }
+
+    #[test]
+    fn mixed_capture_fails_only_when_both_sources_are_missing() {
+        let (devices, missing) = lookup(FakeDevices {
+            mic: Err("device enumeration failed"),
+            system: Ok(None),
+        });
+        let error = devices.unwrap_err();
+        assert!(error.is::<DeviceNotFound>());
+        assert!(error
+            .to_string()
+            .starts_with("No capture source could be started (mic: device enumeration failed; system: "));
+        assert_eq!(sources(&missing), [CaptureSource::Mic, CaptureSource::System]);
+    }
+
+    #[test]
+    fn audio_chunk_payload_wire_format_is_camel_case() {
+        let payload = AudioChunkPayload {
+            seq: 3,
+            capture_timestamp_ms: 1_700_000_000_000,
+            duration_ms: 100.0,
+            sample_rate: 48_000,
+            channels: 2,
+            pre_roll: false,
+            ducking_db: -6.0,
+            data_base64: "AAA=",
+        };
+        assert_eq!(
+            serde_json::to_value(&payload).unwrap(),
+            serde_json::json!({
+                "seq": 3,
+                "captureTimestampMs": 1_700_000_000_000i64,
+                "durationMs": 100.0,
+                "sampleRate": 48_000,
+                "channels": 2,
+                "preRoll": false,
+                "duckingDb": -6.0,
+                "dataBase64": "AAA=",
+            })
+        );
+    }
+
+    #[test]
+    fn audio_device_info_wire_format_is_camel_case() {
+        let mut device = AudioDeviceInfo {
+            id: "Headset #2".into(),
+            name: "Headset".into(),
+            kind: "mic".into(),
+            channels: 1,
+            sample_rate: 16_000,
+            quality_hint: Some("narrowband"),
+        };
+        assert_eq!(
+            serde_json::to_value(&device).unwrap(),
+            serde_json::json!({
+                "id": "Headset #2",
+                "name": "Headset",
+                "kind": "mic",
+                "channels": 1,
+                "sampleRate": 16_000,
+                "qualityHint": "narrowband",
+            })
+        );
+        device.quality_hint = None;
+        assert!(serde_json::to_value(&device)
+            .unwrap()
+            .get("qualityHint")
+            .is_none());
+    }
+
+    /// A running capture that remembers being halted.
+    struct FakeCapture(&'static str, std::sync::Arc<AtomicBool>);
+
+    impl Halt for FakeCapture {
+        fn halt(self) {
+            self.1.store(true, Ordering::SeqCst);
+        }
+    }
+
+    fn running(name: &'static str) -> (Mutex<Option<FakeCapture>>, std::sync::Arc<AtomicBool>) {
+        let halted = std::sync::Arc::new(AtomicBool::new(false));
+        (
+            Mutex::new(Some(FakeCapture(name, halted.clone()))),
+            halted,
+        )
+    }
+
+    #[test]
+    fn failed_switch_keeps_the_old_capture_running() {
+        let (slot, halted) = running("mic");
+        let result = replace_capture(&slot, || -> Result<&str> {
+            Err(anyhow!("system device vanished"))
+        });
+        assert_eq!(result.unwrap_err().to_string(), "system device vanished");
+        assert!(!halted.load(Ordering::SeqCst));
+        assert_eq!(slot.lock().unwrap().as_ref().map(|c| c.0), Some("mic"));
+    }
+
+    #[test]
+    fn switch_halts_the_old_capture_only_after_the_new_one_opened() {
+        let (slot, halted) = running("mic");
+        let pending = replace_capture(&slot, || {
+            assert!(!halted.load(Ordering::SeqCst), "old capture stopped too early");
+            Ok("system")
+        });
+        assert_eq!(pending.unwrap(), "system");
+        assert!(halted.load(Ordering::SeqCst));
+        assert!(slot.lock().unwrap().is_none());
+    }
+
+    #[test]
+    fn switch_without_a_running_capture_just_opens() {
+        let slot: Mutex<Option<FakeCapture>> = Mutex::new(None);
+        assert_eq!(replace_capture(&slot, || Ok(7)).unwrap(), 7);
+    }
+
+    /// Feeds a mixed capture with synthetic audio in real time while every
+    /// tokio worker spins, and checks that the boosted mixer loses nothing.
+    #[test]
+    fn mixer_keeps_up_while_the_runtime_is_saturated() {
+        const CHUNKS: u64 = 100;
+        const FRAMES: usize = 480; // 10 ms at 48 kHz
+
+        let workers = thread::available_parallelism().map_or(4, |n| n.get());
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(workers)
+            .build()
+            .unwrap();
+        let busy = std::sync::Arc::new(AtomicBool::new(true));
+        for _ in 0..workers * 2 {
+            let busy = busy.clone();
+            runtime.spawn(async move {
+                let mut acc = 0u64;
+                while busy.load(Ordering::Relaxed) {
+                    for i in 0..10_000u64 {
+                        acc = acc.wrapping_mul(31).wrapping_add(i);
+                    }
+                    std::hint::black_box(acc);
+                    tokio::task::yield_now().await;
+                }
+            });
+        }
+
+        let (job_tx, job_rx) = unbounded::<EncodeJob>();
+        let metrics = std::sync::Arc::new(CaptureMetrics::default());
+        let flow = std::sync::Arc::new(ChunkFlow::default());
+        let sink = ChunkSink {
+            tx: job_tx,
+            flow: flow.clone(),
+            metrics: metrics.clone(),
+        };
+        let (mic_tx, mic_rx) = unbounded::<Vec<i16>>();
+        let (system_tx, system_rx) = unbounded::<Vec<i16>>();
+        let (_stop_tx, stop_rx) = unbounded::<()>();
+        let config = |channels| StreamConfig {
+            channels,
+            sample_rate: cpal::SampleRate(48_000),
+            buffer_size: cpal::BufferSize::Default,
+        };
+        let mixer = thread::spawn(move || {
+            let _boost = thread_priority::boost("audio-capture", false);
+            capture_loop(
+                sink,
+                BufferPool::new(),
+                vec![CaptureSource::Mic, CaptureSource::System],
+                vec![mic_rx, system_rx],
+                stop_rx,
+                vec![config(1), config(2)],
+            );
+        });
+
+        let started = Instant::now();
+        for chunk in 0..CHUNKS {
+            // The secondary source goes first so each mic chunk finds its partner.
+            system_tx.send(vec![500; FRAMES * 2]).unwrap();
+            mic_tx.send(vec![1000; FRAMES]).unwrap();
+            let due = started + Duration::from_millis(10 * (chunk + 1));
+            thread::sleep(due.saturating_duration_since(Instant::now()));
+        }
+        drop((mic_tx, system_tx));
+
+        let mut seqs = Vec::new();
+        let mut frames = 0;
+        while let Ok(job) = job_rx.recv_timeout(Duration::from_secs(5)) {
+            seqs.push(job.seq);
+            frames += job.frames();
+        }
+        mixer.join().unwrap();
+        busy.store(false, Ordering::Relaxed);
+        runtime.shutdown_timeout(Duration::from_secs(1));
+
+        assert_eq!(seqs, (1..=CHUNKS).collect::<Vec<_>>());
+        assert_eq!(frames, CHUNKS as usize * FRAMES);
+        let status = metrics.stats(true, &[CaptureSource::Mic, CaptureSource::System]);
+        for source in status.dropped_frames {
+            assert_eq!(source.frames, 0, "{:?} dropped frames", source.source);
+        }
+        assert_eq!(flow.dropped_chunks.load(Ordering::Relaxed), 0);
+    }
+}
@@ -0,0 +1,318 @@
+//! Plays the captured mix on an output device so the user can hear what is recorded.
+//! Chunks from the encoder thread land in a small jitter buffer that a cpal output
+//! stream drains on its own thread.
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::{Device, FromSample, SampleFormat, SizedSample, Stream, StreamConfig};
+use crossbeam_channel::{bounded, Sender};
+use tauri::AppHandle;
+
+use crate::crash;
+use crate::error_bus;
+use crate::errors::{CommandError, CommandResult, ErrorCode};
+
+// Audio queued before playback starts, and again after an underrun.
+const PREBUFFER: Duration = Duration::from_millis(40);
+// Oldest audio is dropped past this, which keeps the total delay under ~150 ms
+// once the capture chunk and the device buffer are added.
+const MAX_BUFFERED: Duration = Duration::from_millis(100);
+const OPEN_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Interleaved f32 samples in the output device layout, plus the resampler state
+/// carried between chunks.
+#[derive(Default)]
+struct JitterBuffer {
+    output: Option<OutputFormat>,
+    queue: VecDeque<f32>,
+    primed: bool,
+    // Last input frame of the previous chunk, and the read position relative to it.
+    previous: Vec<f32>,
+    position: f64,
+}
+
+#[derive(Clone, Copy)]
+struct OutputFormat {
+    sample_rate: u32,
+    channels: usize,
+}
+
+impl OutputFormat {
+    fn samples_for(&self, duration: Duration) -> usize {
+        (self.sample_rate as f64 * duration.as_secs_f64()) as usize * self.channels
+    }
+}
+
+impl JitterBuffer {
+    fn reset(&mut self, output: Option<OutputFormat>) {
+        *self = Self {
+            output,
+            ..Self::default()
+        };
+    }
+
+    /// Channel-maps and linearly resamples `samples` into the output layout.
+    fn push(&mut self, samples: &[i16], sample_rate: u32, channels: u16) {
+        let Some(output) = self.output else {
+            return;
+        };
+        let channels = channels.max(1) as usize;
+        let frames = samples.len() / channels;
+        if frames == 0 || sample_rate == 0 {
+            return;
+        }
+        if self.previous.len() != channels {
+            self.previous = samples[..channels].iter().map(|&s| to_f32(s)).collect();
+        }
+        let step = sample_rate as f64 / output.sample_rate as f64;
+        // Index 0 is the previous chunk's last frame, 1..=frames this chunk.
+        let frame = |index: usize, channel: usize, previous: &[f32]| {
+            if index == 0 {
+                previous[channel]
+            } else {
+                to_f32(samples[(index - 1) * channels + channel])
+            }
+        };
+        while self.position + 1.0 <= frames as f64 {
+            let index = self.position.floor() as usize;
+            let t = (self.position - index as f64) as f32;
+            for out_channel in 0..output.channels {
+                let channel = out_channel % channels;
+                let a = frame(index, channel, &self.previous);
+                let b = frame(index + 1, channel, &self.previous);
+                self.queue.push_back(a + (b - a) * t);
+            }
+            self.position += step;
+        }
+        self.position -= frames as f64;
+        self.previous.clear();
+        self.previous.extend(
+            samples[(frames - 1) * channels..frames * channels]
+                .iter()
+                .map(|&s| to_f32(s)),
+        );
+
+        let limit = output.samples_for(MAX_BUFFERED);
+        if self.queue.len() > limit {
+            let excess = self.queue.len() - limit;
+            // Drop whole frames so channels stay aligned.
+            self.queue.drain(..excess - excess % output.channels);
+        }
+    }
+
+    /// Fills `data` for the device, emitting silence until enough audio is queued.
+    fn pull<T: SizedSample + FromSample<f32>>(&mut self, data: &mut [T], gain: f32) {
+        let ready = self
+            .output
+            .map(|output| self.queue.len() >= output.samples_for(PREBUFFER))
+            .unwrap_or(false);
+        if !self.primed && !ready {
+            data.fill(T::EQUILIBRIUM);
+            return;
+        }
+        self.primed = true;
+        for sample in data.iter_mut() {
+            *sample = match self.queue.pop_front() {
+                Some(value) => T::from_sample((value * gain).clamp(-1.0, 1.0)),
+                None => {
+                    self.primed = false;
+                    T::EQUILIBRIUM
+                }
+            };
+        }
+    }
+}
+
+fn to_f32(sample: i16) -> f32 {
+    sample as f32 / i16::MAX as f32
+}
+
+struct MonitorThread {
+    stop_tx: Sender<()>,
+    handle: thread::JoinHandle<()>,
+}
+
+/// Monitor settings plus the running output thread. Settings survive capture
+/// restarts; the output stream only runs while capture does.
+pub struct AudioMonitor {
+    enabled: AtomicBool,
+    // f32 bits, read by the output callback on every buffer.
+    gain: Arc<AtomicU32>,
+    buffer: Arc<Mutex<JitterBuffer>>,
+    active: Mutex<Option<MonitorThread>>,
+}
+
+impl AudioMonitor {
+    pub fn new() -> Self {
+        Self {
+            enabled: AtomicBool::new(false),
+            gain: Arc::new(AtomicU32::new(1.0f32.to_bits())),
+            buffer: Arc::new(Mutex::new(JitterBuffer::default())),
+            active: Mutex::new(None),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    pub fn set_gain(&self, gain: f32) {
+        self.gain.store(gain.to_bits(), Ordering::Relaxed);
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.active.lock().unwrap().is_some()
+    }
+
+    /// Called by the encoder thread for every mixed chunk; a no-op while stopped.
+    pub fn feed(&self, samples: &[i16], sample_rate: u32, channels: u16) {
+        self.buffer
+            .lock()
+            .unwrap()
+            .push(samples, sample_rate, channels);
+    }
+
+    /// Opens the default output device. `loopback_source` names the device the
+    /// capture is recording system audio from; playing into it would feed back.
+    pub fn start(&self, app: &AppHandle, loopback_source: Option<&str>) -> CommandResult<()> {
+        self.stop();
+        let device = cpal::default_host()
+            .default_output_device()
+            .ok_or_else(|| CommandError::new(ErrorCode::AudioDeviceFailed, "no output device"))?;
+        let output_name = device.name().unwrap_or_default();
+        if let Some(source) = loopback_source.filter(|source| feeds_back(&output_name, source)) {
+            return Err(CommandError::new(
+                ErrorCode::MonitorFeedback,
+                format!("output \"{output_name}\" is the loopback source \"{source}\""),
+            )
+            .with_data(serde_json::json!({ "output": output_name, "source": source })));
+        }
+
+        let (stop_tx, stop_rx) = bounded::<()>(1);
+        let (ready_tx, ready_rx) = mpsc::channel::<Result<OutputFormat, String>>();
+        let buffer = self.buffer.clone();
+        let gain = self.gain.clone();
+        let stream_app = app.clone();
+        let handle = thread::spawn(move || {
+            crash::guard("audio-monitor", move || {
+                let stream = match build_output_stream(stream_app, &device, buffer, gain) {
+                    Ok((stream, format)) => match stream.play() {
+                        Ok(()) => {
+                            let _ = ready_tx.send(Ok(format));
+                            stream
+                        }
+                        Err(err) => {
+                            let _ = ready_tx.send(Err(err.to_string()));
+                            return;
+                        }
+                    },
+                    Err(err) => {
+                        let _ = ready_tx.send(Err(err.to_string()));
+                        return;
+                    }
+                };
+                let _ = stop_rx.recv();
+                drop(stream);
+            })
+        });
+
+        let format = match ready_rx.recv_timeout(OPEN_TIMEOUT) {
+            Ok(Ok(format)) => format,
+            Ok(Err(detail)) => {
+                let _ = stop_tx.send(());
+                let _ = handle.join();
+                self.buffer.lock().unwrap().reset(None);
+                return Err(CommandError::new(ErrorCode::MonitorFailed, detail));
+            }
+            Err(_) => {
+                let _ = stop_tx.send(());
+                self.buffer.lock().unwrap().reset(None);
+                return Err(CommandError::new(
+                    ErrorCode::MonitorFailed,
+                    "output device did not start in time",
+                ));
+            }
+        };
+        log::info!(
+            target: "audio",
+            "Monitor started on \"{output_name}\" ({} Hz, {} ch)",
+            format.sample_rate,
+            format.channels
+        );
+        *self.active.lock().unwrap() = Some(MonitorThread { stop_tx, handle });
+        Ok(())
+    }
+
+    pub fn stop(&self) {
+        if let Some(active) = self.active.lock().unwrap().take() {
+            let _ = active.stop_tx.send(());
+            let _ = active.handle.join();
+            log::info!(target: "audio", "Monitor stopped");
+        }
+        self.buffer.lock().unwrap().reset(None);
+    }
+}
+
+/// True when `output` is the device `source` captures from, either by name or as
+/// a "Monitor of …" style loopback of it.
+fn feeds_back(output: &str, source: &str) -> bool {
+    let output = output.trim().to_lowercase();
+    !output.is_empty() && source.to_lowercase().contains(&output)
+}
+
+fn build_output_stream(
+    app: AppHandle,
+    device: &Device,
+    buffer: Arc<Mutex<JitterBuffer>>,
+    gain: Arc<AtomicU32>,
+) -> Result<(Stream, OutputFormat)> {
+    let supported = device.default_output_config()?;
+    let sample_format = supported.sample_format();
+    let config: StreamConfig = supported.into();
+    let format = OutputFormat {
+        sample_rate: config.sample_rate.0,
+        channels: config.channels.max(1) as usize,
+    };
+    // The format has to be in place before the first callback reads the queue.
+    buffer.lock().unwrap().reset(Some(format));
+
+    let stream = match sample_format {
+        SampleFormat::F32 => play::<f32>(app, device, &config, buffer, gain)?,
+        SampleFormat::I16 => play::<i16>(app, device, &config, buffer, gain)?,
+        SampleFormat::U16 => play::<u16>(app, device, &config, buffer, gain)?,
+        other => return Err(anyhow!("Unsupported output sample format {other:?}")),
+    };
+    Ok((stream, format))
+}
+
+fn play<T: SizedSample + FromSample<f32>>(
+    app: AppHandle,
+    device: &Device,
+    config: &StreamConfig,
+    buffer: Arc<Mutex<JitterBuffer>>,
+    gain: Arc<AtomicU32>,
+) -> Result<Stream> {
+    let err_fn = move |err: cpal::StreamError| {
+        error_bus::publish(&app, "audio-monitor", ErrorCode::MonitorFailed, err, true);
+    };
+    let stream = device.build_output_stream(
+        config,
+        move |data: &mut [T], _| {
+            let gain = f32::from_bits(gain.load(Ordering::Relaxed));
+            buffer.lock().unwrap().pull(data, gain);
+        },
+        err_fn,
+        None,
+    )?;
+    Ok(stream)
+}
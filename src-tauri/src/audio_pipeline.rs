@@ -0,0 +1,314 @@
+//! Mixing and encoding stages of the capture path. Kept free of Tauri and cpal types
+//! so `benches/audio_pipeline.rs` can drive it with synthetic input.
+
+use base64::{engine::general_purpose, Engine as _};
+use crossbeam_channel::{bounded, Receiver, Sender};
+
+/// Buffers kept around for reuse; enough for a few devices plus the encoder backlog.
+const POOL_CAPACITY: usize = 32;
+/// Ducking reaches its depth within a few chunks of speech and eases off over
+/// about this long once the mic goes quiet.
+const DUCK_ATTACK_MS: f32 = 10.0;
+const DUCK_RELEASE_MS: f32 = 300.0;
+/// Quieter than this is reported as this.
+const SILENCE_DB: f32 = -120.0;
+
+/// Recycles sample buffers between the cpal callbacks, the mixer and the encoder
+/// so steady-state capture does not allocate per chunk.
+#[derive(Clone)]
+pub struct BufferPool {
+    free_tx: Sender<Vec<i16>>,
+    free_rx: Receiver<Vec<i16>>,
+}
+
+impl BufferPool {
+    pub fn new() -> Self {
+        let (free_tx, free_rx) = bounded(POOL_CAPACITY);
+        Self { free_tx, free_rx }
+    }
+
+    /// Returns an empty buffer with room for at least `capacity` samples.
+    pub fn take(&self, capacity: usize) -> Vec<i16> {
+        let mut buf = self.free_rx.try_recv().unwrap_or_default();
+        buf.clear();
+        buf.reserve(capacity);
+        buf
+    }
+
+    /// Hands a buffer back; dropped instead when the pool is already full.
+    pub fn recycle(&self, buf: Vec<i16>) {
+        let _ = self.free_tx.try_send(buf);
+    }
+}
+
+pub fn f32_to_i16(sample: f32) -> i16 {
+    (sample.clamp(-1.0, 1.0) * 32767.0).round() as i16
+}
+
+pub fn u16_to_i16(sample: u16) -> i16 {
+    // u16: 0-65535 -> i16: -32768 to 32767
+    ((sample as i32) - 32768) as i16
+}
+
+/// Adds `src` into `target`, converting between interleaved channel layouts.
+pub fn fill_buffer_i16(
+    target: &mut [i16],
+    src: &[i16],
+    src_channels: usize,
+    dst_channels: usize,
+    frames: usize,
+) {
+    if src.is_empty() || target.is_empty() || src_channels == 0 || dst_channels == 0 {
+        return;
+    }
+
+    // src is interleaved: [L, R, L, R, ...] for stereo or [M, M, M, ...] for mono
+    // target is interleaved: [L, R, L, R, ...] for stereo output
+    let frames = frames
+        .min(target.len() / dst_channels)
+        .min(src.len() / src_channels);
+
+    if src_channels == 1 && dst_channels == 2 {
+        // Mono to stereo: duplicate channel
+        for (i, &sample) in src.iter().enumerate().take(frames) {
+            let base = i * dst_channels;
+            target[base] = target[base].saturating_add(sample);
+            target[base + 1] = target[base + 1].saturating_add(sample);
+        }
+    } else if src_channels == dst_channels {
+        // Same channel count: add to target (for mixing)
+        let limit = (frames * src_channels).min(target.len()).min(src.len());
+        for (dst, &sample) in target.iter_mut().zip(src).take(limit) {
+            *dst = dst.saturating_add(sample);
+        }
+    } else if src_channels == 2 && dst_channels == 1 {
+        // Stereo to mono: average channels
+        for (i, dst) in target.iter_mut().enumerate().take(frames) {
+            let left = src[i * 2] as i32;
+            let right = src[i * 2 + 1] as i32;
+            *dst = dst.saturating_add(((left + right) / 2) as i16);
+        }
+    } else {
+        // Different channel counts: map channels
+        for i in 0..frames {
+            for dst_ch in 0..dst_channels {
+                let src_ch = dst_ch % src_channels;
+                let src_idx = i * src_channels + src_ch;
+                let dst_idx = i * dst_channels + dst_ch;
+                if src_idx < src.len() && dst_idx < target.len() {
+                    target[dst_idx] = target[dst_idx].saturating_add(src[src_idx]);
+                }
+            }
+        }
+    }
+}
+
+/// Mixes an additional source (usually system audio in mixed mode) at `gain`.
+pub fn mix_secondary(
+    target: &mut [i16],
+    src: &mut [i16],
+    src_channels: usize,
+    dst_channels: usize,
+    frames: usize,
+    gain: f32,
+) {
+    for sample in src.iter_mut() {
+        *sample = (*sample as f32 * gain)
+            .round()
+            .clamp(i16::MIN as f32, i16::MAX as f32) as i16;
+    }
+    fill_buffer_i16(target, src, src_channels, dst_channels, frames);
+}
+
+/// Scales the chunk down if any sample sits at the very edge of the i16 range.
+/// Returns whether it did.
+pub fn normalize_peak(samples: &mut [i16]) -> bool {
+    let max_amp = samples
+        .iter()
+        .fold(0i32, |acc, &s| acc.max((s as i32).abs()));
+    if max_amp > 32767 {
+        let gain = 32767.0 / max_amp as f32;
+        for s in samples.iter_mut() {
+            *s = ((*s as f32) * gain).round() as i16;
+        }
+        return true;
+    }
+    false
+}
+
+/// Multiplies every sample by `gain`, saturating at the i16 range.
+pub fn apply_gain(samples: &mut [i16], gain: f32) {
+    if gain == 1.0 {
+        return;
+    }
+    for s in samples.iter_mut() {
+        *s = ((*s as f32) * gain)
+            .round()
+            .clamp(i16::MIN as f32, i16::MAX as f32) as i16;
+    }
+}
+
+/// Root mean square of the chunk on a 0.0-1.0 scale, as the level meter shows it.
+pub fn rms(samples: &[i16]) -> f32 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    let sum: f64 = samples
+        .iter()
+        .map(|&s| {
+            let v = s as f64 / 32767.0;
+            v * v
+        })
+        .sum();
+    (sum / samples.len() as f64).sqrt() as f32
+}
+
+/// `rms` in dBFS, floored at -120.
+pub fn rms_db(samples: &[i16]) -> f32 {
+    let rms = rms(samples);
+    if rms > 0.0 {
+        (20.0 * rms.log10()).max(SILENCE_DB)
+    } else {
+        SILENCE_DB
+    }
+}
+
+/// Envelope follower behind ducking: how far to pull the system audio down,
+/// driven by the mic level of each chunk.
+#[derive(Debug, Default)]
+pub struct Ducker {
+    attenuation_db: f32,
+}
+
+impl Ducker {
+    /// Advances by one chunk of `duration_ms` whose mic sat at `level_db` and
+    /// returns the linear gain for the system audio in it. The attenuation
+    /// moves toward `depth_db` while the level is above `threshold_db` and back
+    /// to 0 otherwise, quickly on the way down and slowly on the way back up.
+    pub fn update(
+        &mut self,
+        level_db: f32,
+        threshold_db: f32,
+        depth_db: f32,
+        duration_ms: f32,
+    ) -> f32 {
+        let target = if level_db > threshold_db {
+            depth_db
+        } else {
+            0.0
+        };
+        let time_constant = if target > self.attenuation_db {
+            DUCK_ATTACK_MS
+        } else {
+            DUCK_RELEASE_MS
+        };
+        let step = 1.0 - (-duration_ms.max(0.0) / time_constant).exp();
+        self.attenuation_db += (target - self.attenuation_db) * step;
+        self.gain()
+    }
+
+    /// Current attenuation in dB, 0 when not ducking.
+    pub fn attenuation_db(&self) -> f32 {
+        self.attenuation_db
+    }
+
+    pub fn gain(&self) -> f32 {
+        10f32.powf(-self.attenuation_db / 20.0)
+    }
+
+    pub fn reset(&mut self) {
+        self.attenuation_db = 0.0;
+    }
+}
+
+/// Encodes little-endian PCM into `out`, reusing its allocation.
+pub fn encode_chunk(samples: &[i16], out: &mut String) {
+    out.clear();
+    general_purpose::STANDARD.encode_string(bytemuck::cast_slice::<i16, u8>(samples), out);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SPEECH_DB: f32 = -20.0;
+    const QUIET_DB: f32 = -60.0;
+    const THRESHOLD_DB: f32 = -40.0;
+    const DEPTH_DB: f32 = 12.0;
+
+    /// Feeds `levels` as 10 ms chunks and returns the attenuation after each.
+    fn follow(ducker: &mut Ducker, levels: impl IntoIterator<Item = f32>) -> Vec<f32> {
+        levels
+            .into_iter()
+            .map(|level| {
+                ducker.update(level, THRESHOLD_DB, DEPTH_DB, 10.0);
+                ducker.attenuation_db()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn quiet_mic_never_ducks() {
+        let mut ducker = Ducker::default();
+        let trace = follow(&mut ducker, [QUIET_DB, THRESHOLD_DB, QUIET_DB]);
+        assert!(trace.iter().all(|&db| db == 0.0), "{trace:?}");
+        assert_eq!(ducker.gain(), 1.0);
+    }
+
+    #[test]
+    fn speech_ducks_within_a_few_chunks() {
+        let mut ducker = Ducker::default();
+        let trace = follow(&mut ducker, [SPEECH_DB; 5]);
+        assert!(trace.windows(2).all(|pair| pair[1] > pair[0]), "{trace:?}");
+        assert!(trace[0] > DEPTH_DB * 0.6, "{trace:?}");
+        assert!(trace[4] > DEPTH_DB * 0.99, "{trace:?}");
+        assert!((ducker.gain() - 10f32.powf(-DEPTH_DB / 20.0)).abs() < 0.01);
+    }
+
+    #[test]
+    fn release_takes_about_300_ms() {
+        let mut ducker = Ducker::default();
+        follow(&mut ducker, [SPEECH_DB; 20]);
+        let trace = follow(&mut ducker, [QUIET_DB; 150]);
+        assert!(trace.windows(2).all(|pair| pair[1] < pair[0]), "{trace:?}");
+        // One time constant in, about 1/e of the depth is left.
+        let after_300_ms = trace[29] / DEPTH_DB;
+        assert!(
+            (after_300_ms - (-1f32).exp()).abs() < 0.02,
+            "{after_300_ms}"
+        );
+        assert!(trace[149] < DEPTH_DB * 0.01, "{trace:?}");
+    }
+
+    #[test]
+    fn a_word_in_the_release_ducks_again() {
+        let mut ducker = Ducker::default();
+        follow(&mut ducker, [SPEECH_DB; 20]);
+        let released = *follow(&mut ducker, [QUIET_DB; 10]).last().unwrap();
+        let trace = follow(&mut ducker, [SPEECH_DB; 3]);
+        assert!(trace[0] > released);
+        assert!(trace[2] > DEPTH_DB * 0.95, "{trace:?}");
+    }
+
+    #[test]
+    fn chunk_size_does_not_change_the_envelope() {
+        let mut fine = Ducker::default();
+        follow(&mut fine, [SPEECH_DB; 4]);
+        let mut coarse = Ducker::default();
+        coarse.update(SPEECH_DB, THRESHOLD_DB, DEPTH_DB, 40.0);
+        assert!((fine.attenuation_db() - coarse.attenuation_db()).abs() < 1e-3);
+
+        follow(&mut fine, [QUIET_DB; 25]);
+        coarse.update(QUIET_DB, THRESHOLD_DB, DEPTH_DB, 250.0);
+        assert!((fine.attenuation_db() - coarse.attenuation_db()).abs() < 1e-3);
+    }
+
+    #[test]
+    fn reset_stops_ducking_at_once() {
+        let mut ducker = Ducker::default();
+        follow(&mut ducker, [SPEECH_DB; 5]);
+        ducker.reset();
+        assert_eq!(ducker.attenuation_db(), 0.0);
+        assert_eq!(ducker.gain(), 1.0);
+    }
+}
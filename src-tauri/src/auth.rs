@@ -1,31 +1,71 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-use tauri::{AppHandle, Emitter};
+use tauri::{AppHandle, Manager};
 use tokio::sync::Mutex;
 use uuid::Uuid;
 
+use crate::auth_store::AuthStore;
+use crate::config::ConfigState;
 use crate::constants::{OAUTH_APP_NAME, OAUTH_SCHEME};
+use crate::events::{self, emit_event};
 use crate::types::{AuthDeepLinkPayload, AuthTokensPayload};
 
+// A sign-in that takes longer than this is treated as abandoned.
+const OAUTH_STATE_TTL: Duration = Duration::from_secs(10 * 60);
+const CONSUMED_STATE_HISTORY: usize = 32;
+
+struct PendingState {
+    provider: String,
+    created_at: Instant,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StateRejection {
+    Missing,
+    Unknown,
+    Expired,
+    Replayed,
+    ProviderMismatch,
+}
+
+impl StateRejection {
+    fn message(self) -> &'static str {
+        match self {
+            StateRejection::Missing => "OAuth state missing",
+            StateRejection::Unknown => "OAuth state mismatch",
+            StateRejection::Expired => "OAuth state expired",
+            StateRejection::Replayed => "OAuth state already used",
+            StateRejection::ProviderMismatch => "OAuth state provider mismatch",
+        }
+    }
+}
+
 #[derive(Default)]
 pub struct AuthQueue {
     pending: Mutex<Vec<AuthDeepLinkPayload>>,
-    states: Mutex<HashMap<String, String>>,
+    states: Mutex<HashMap<String, PendingState>>,
+    consumed: Mutex<VecDeque<String>>,
 }
 
 impl AuthQueue {
     pub fn new() -> Self {
-        Self {
-            pending: Mutex::new(Vec::new()),
-            states: Mutex::new(HashMap::new()),
-        }
+        Self::default()
     }
 
     pub async fn start_state(&self, provider: &str) -> String {
         let provider_key = provider.trim().to_lowercase();
         let state = Uuid::new_v4().simple().to_string();
-        self.states.lock().await.insert(provider_key, state.clone());
+        let mut states = self.states.lock().await;
+        states.retain(|_, pending| pending.created_at.elapsed() < OAUTH_STATE_TTL);
+        states.insert(
+            state.clone(),
+            PendingState {
+                provider: provider_key,
+                created_at: Instant::now(),
+            },
+        );
         log::info!(target: "auth", "OAuth state created: provider={}", provider);
         state
     }
@@ -41,55 +81,112 @@ impl AuthQueue {
         drained
     }
 
-    async fn take_expected_state(&self, provider: &str) -> Option<String> {
-        self.states
-            .lock()
-            .await
-            .remove(&provider.trim().to_lowercase())
-    }
-
+    /// Consumes the state so the same callback cannot be replayed later.
     async fn validate_state(
         &self,
         provider: &str,
         received_state: Option<&str>,
-    ) -> Result<(), String> {
-        let expected = self.take_expected_state(provider).await;
-        match (expected, received_state) {
-            (Some(expected_state), Some(value)) if value == expected_state => Ok(()),
-            _ => Err("Invalid OAuth state".to_string()),
+    ) -> Result<(), StateRejection> {
+        let Some(state) = received_state.filter(|value| !value.trim().is_empty()) else {
+            return Err(StateRejection::Missing);
+        };
+        let pending = self.states.lock().await.remove(state);
+        let Some(pending) = pending else {
+            let replayed = self.consumed.lock().await.iter().any(|used| used == state);
+            return Err(if replayed {
+                StateRejection::Replayed
+            } else {
+                StateRejection::Unknown
+            });
+        };
+        {
+            let mut consumed = self.consumed.lock().await;
+            consumed.push_back(state.to_string());
+            while consumed.len() > CONSUMED_STATE_HISTORY {
+                consumed.pop_front();
+            }
+        }
+        if pending.created_at.elapsed() >= OAUTH_STATE_TTL {
+            return Err(StateRejection::Expired);
         }
+        if pending.provider != provider.trim().to_lowercase() {
+            return Err(StateRejection::ProviderMismatch);
+        }
+        Ok(())
     }
 }
 
 pub async fn handle_deep_link(app: AppHandle, queue: Arc<AuthQueue>, url: String) {
     log::info!(target: "auth", "Deep link received");
     if let Some(payload) = parse_auth_payload(&url) {
-        let payload = match validate_payload_state(queue.clone(), payload).await {
-            Ok(payload) => payload,
-            Err(payload) => payload,
-        };
-        match &payload {
-            AuthDeepLinkPayload::Success { provider, .. } => {
-                log::info!(target: "auth", "OAuth deep link success payload: provider={provider}");
-            }
-            AuthDeepLinkPayload::Error {
-                provider, error, ..
-            } => {
-                log::warn!(
-                    target: "auth",
-                    "OAuth deep link error payload: provider={} error={}",
-                    provider,
-                    error
-                );
-            }
-        }
-        queue.enqueue(payload.clone()).await;
-        let _ = app.emit("auth:deep-link", payload);
+        dispatch_payload(&app, queue, payload).await;
     } else {
         log::warn!(target: "auth", "Deep link ignored: not an auth callback");
     }
 }
 
+pub async fn handle_loopback_callback(app: AppHandle, queue: Arc<AuthQueue>, url: url::Url) {
+    log::info!(target: "auth", "Loopback OAuth callback received");
+    if let Some(payload) = parse_callback_payload(&url) {
+        dispatch_payload(&app, queue, payload).await;
+    } else {
+        log::warn!(target: "auth", "Loopback callback ignored: missing payload");
+    }
+}
+
+async fn dispatch_payload(app: &AppHandle, queue: Arc<AuthQueue>, payload: AuthDeepLinkPayload) {
+    let payload = match validate_payload_state(queue.clone(), payload).await {
+        Ok(payload) => payload,
+        Err(payload) => payload,
+    };
+    match &payload {
+        AuthDeepLinkPayload::Success {
+            provider,
+            tokens,
+            user,
+            ..
+        } => {
+            log::info!(target: "auth", "OAuth deep link success payload: provider={provider}");
+            if let Some(store) = app.try_state::<Arc<AuthStore>>() {
+                match store
+                    .save(app, provider, tokens.clone(), user.clone())
+                    .await
+                {
+                    Ok(account_id) => activate_account(app, &account_id).await,
+                    Err(error) => {
+                        log::error!(target: "auth", "Failed to persist OAuth session: {error}");
+                    }
+                }
+            }
+        }
+        AuthDeepLinkPayload::Error {
+            provider, error, ..
+        } => {
+            log::warn!(
+                target: "auth",
+                "OAuth deep link error payload: provider={} error={}",
+                provider,
+                error
+            );
+        }
+    }
+    queue.enqueue(payload.clone()).await;
+    let _ = emit_event(app, events::AUTH_DEEP_LINK, payload);
+}
+
+/// A fresh sign-in becomes the active account.
+async fn activate_account(app: &AppHandle, account_id: &str) {
+    let Some(config) = app.try_state::<Arc<ConfigState>>() else {
+        return;
+    };
+    if let Err(error) = config
+        .update(serde_json::json!({ "activeAccount": account_id }), "auth")
+        .await
+    {
+        log::error!(target: "auth", "Failed to persist active account: {error}");
+    }
+}
+
 async fn validate_payload_state(
     queue: Arc<AuthQueue>,
     payload: AuthDeepLinkPayload,
@@ -102,16 +199,16 @@ async fn validate_payload_state(
             provider, state, ..
         } => (provider.clone(), state.clone()),
     };
-    if let Err(error) = queue.validate_state(&provider, state.as_deref()).await {
+    if let Err(rejection) = queue.validate_state(&provider, state.as_deref()).await {
         log::warn!(
             target: "auth",
-            "OAuth state validation failed: provider={} error={}",
+            "OAuth state validation failed: provider={} reason={:?}",
             provider,
-            error
+            rejection
         );
         return Err(AuthDeepLinkPayload::Error {
             provider,
-            error,
+            error: rejection.message().to_string(),
             state,
         });
     }
@@ -141,6 +238,21 @@ fn parse_auth_payload(url: &str) -> Option<AuthDeepLinkPayload> {
         log::warn!(target: "auth", "Deep link path mismatch: path={}", parsed.path());
         return None;
     }
+    parse_callback_payload(&parsed)
+}
+
+/// Whether a loopback request is the callback itself: it carries a payload with
+/// a state. Anything else leaves the listener waiting.
+pub(crate) fn is_loopback_callback(url: &url::Url) -> bool {
+    let state = match parse_callback_payload(url) {
+        Some(AuthDeepLinkPayload::Success { state, .. })
+        | Some(AuthDeepLinkPayload::Error { state, .. }) => state,
+        None => None,
+    };
+    state.is_some_and(|state| !state.trim().is_empty())
+}
+
+fn parse_callback_payload(parsed: &url::Url) -> Option<AuthDeepLinkPayload> {
     let payload = parsed
         .query_pairs()
         .find(|(key, _)| key == "payload")
@@ -186,11 +298,16 @@ fn parse_auth_payload(url: &str) -> Option<AuthDeepLinkPayload> {
             .get("refresh")
             .and_then(|value| value.as_str())
             .map(|value| value.to_string());
+        let expires_in = tokens
+            .get("expires_in")
+            .or_else(|| tokens.get("expiresIn"))
+            .and_then(|value| value.as_i64());
         return Some(AuthDeepLinkPayload::Success {
             provider,
             tokens: AuthTokensPayload {
                 access: access_token,
                 refresh,
+                expires_in,
             },
             state,
             user: data.get("user").cloned(),
@@ -202,3 +319,72 @@ fn parse_auth_payload(url: &str) -> Option<AuthDeepLinkPayload> {
         state,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn state_is_accepted_once() {
+        let queue = AuthQueue::new();
+        let state = queue.start_state("Google").await;
+        assert_eq!(queue.validate_state("google", Some(&state)).await, Ok(()));
+        assert_eq!(
+            queue.validate_state("google", Some(&state)).await,
+            Err(StateRejection::Replayed)
+        );
+    }
+
+    #[tokio::test]
+    async fn expired_state_is_rejected() {
+        let queue = AuthQueue::new();
+        let state = queue.start_state("google").await;
+        queue
+            .states
+            .lock()
+            .await
+            .get_mut(&state)
+            .unwrap()
+            .created_at = Instant::now()
+            .checked_sub(OAUTH_STATE_TTL + Duration::from_secs(1))
+            .unwrap();
+        assert_eq!(
+            queue.validate_state("google", Some(&state)).await,
+            Err(StateRejection::Expired)
+        );
+    }
+
+    #[tokio::test]
+    async fn unknown_state_is_rejected() {
+        let queue = AuthQueue::new();
+        queue.start_state("google").await;
+        assert_eq!(
+            queue.validate_state("google", Some("not-issued")).await,
+            Err(StateRejection::Unknown)
+        );
+    }
+
+    #[tokio::test]
+    async fn missing_state_is_rejected() {
+        let queue = AuthQueue::new();
+        queue.start_state("google").await;
+        assert_eq!(
+            queue.validate_state("google", None).await,
+            Err(StateRejection::Missing)
+        );
+        assert_eq!(
+            queue.validate_state("google", Some("  ")).await,
+            Err(StateRejection::Missing)
+        );
+    }
+
+    #[tokio::test]
+    async fn state_is_bound_to_its_provider() {
+        let queue = AuthQueue::new();
+        let state = queue.start_state("google").await;
+        assert_eq!(
+            queue.validate_state("github", Some(&state)).await,
+            Err(StateRejection::ProviderMismatch)
+        );
+    }
+}
@@ -0,0 +1,605 @@
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use base64::Engine as _;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{AppHandle, Manager};
+use tokio::sync::{Mutex, Notify};
+use tokio::task::spawn_blocking;
+
+use crate::config::ConfigState;
+use crate::constants::OAUTH_APP_NAME;
+use crate::events::{self, emit_event};
+use crate::http::HttpClients;
+use crate::oauth;
+use crate::types::AuthTokensPayload;
+
+const KEYCHAIN_ACCOUNT: &str = "accounts";
+// Single-session entry written by earlier versions; migrated on first load.
+const LEGACY_KEYCHAIN_ACCOUNT: &str = "session";
+// Refresh slightly ahead of the real expiry so requests in flight do not race it.
+const ACCESS_TOKEN_REFRESH_MARGIN_MS: i64 = 60_000;
+// The background task refreshes well before expiry so the UI never sees a stale token.
+const BACKGROUND_REFRESH_LEAD_MS: i64 = 5 * 60_000;
+const BACKGROUND_IDLE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StoredSession {
+    provider: String,
+    #[serde(default)]
+    user_id: Option<String>,
+    tokens: AuthTokensPayload,
+    #[serde(default)]
+    user: Option<Value>,
+    #[serde(default)]
+    expires_at: Option<i64>,
+    saved_at: i64,
+}
+
+impl StoredSession {
+    fn account_id(&self) -> String {
+        account_id(&self.provider, self.user_id.as_deref())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthStatus {
+    pub logged_in: bool,
+    pub account_id: Option<String>,
+    pub provider: Option<String>,
+    pub user: Option<Value>,
+    pub expires_at: Option<i64>,
+    pub can_refresh: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthAccount {
+    pub id: String,
+    pub provider: String,
+    pub user_id: Option<String>,
+    pub user: Option<Value>,
+    pub expires_at: Option<i64>,
+    pub can_refresh: bool,
+    pub active: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AuthRefreshedPayload {
+    account_id: String,
+    provider: String,
+    expires_at: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AuthExpiredPayload {
+    account_id: String,
+    provider: String,
+    error: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AuthAccountChangedPayload<'a> {
+    account_id: &'a str,
+    change: &'a str,
+}
+
+/// Account id used by the UI and config: `provider` or `provider:user_id`.
+pub fn account_id(provider: &str, user_id: Option<&str>) -> String {
+    let provider = provider.trim().to_lowercase();
+    match user_id.map(str::trim).filter(|id| !id.is_empty()) {
+        Some(user_id) => format!("{provider}:{user_id}"),
+        None => provider,
+    }
+}
+
+pub(crate) fn emit_account_changed(app: &AppHandle, account_id: &str, change: &str) {
+    let _ = emit_event(
+        app,
+        events::AUTH_ACCOUNT_CHANGED,
+        AuthAccountChangedPayload { account_id, change },
+    );
+}
+
+/// Keeps OAuth sessions in the OS keychain instead of webview storage, one per
+/// provider and user.
+#[derive(Default)]
+pub struct AuthStore {
+    accounts: Mutex<Vec<StoredSession>>,
+    loaded: AtomicBool,
+    refresh_flight: Mutex<()>,
+    changed: Notify,
+}
+
+impl AuthStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub async fn status(&self, selector: Option<&str>) -> AuthStatus {
+        let guard = self.lock_loaded().await;
+        match find(&guard, selector) {
+            Some(session) => AuthStatus {
+                logged_in: true,
+                account_id: Some(session.account_id()),
+                provider: Some(session.provider.clone()),
+                user: session.user.clone(),
+                expires_at: session.expires_at,
+                can_refresh: session.tokens.refresh.is_some(),
+            },
+            None => AuthStatus::default(),
+        }
+    }
+
+    pub async fn list(&self, active: Option<&str>) -> Vec<AuthAccount> {
+        let guard = self.lock_loaded().await;
+        let active_id = find(&guard, active).map(StoredSession::account_id);
+        guard
+            .iter()
+            .map(|session| {
+                let id = session.account_id();
+                AuthAccount {
+                    active: active_id.as_deref() == Some(id.as_str()),
+                    id,
+                    provider: session.provider.clone(),
+                    user_id: session.user_id.clone(),
+                    user: session.user.clone(),
+                    expires_at: session.expires_at,
+                    can_refresh: session.tokens.refresh.is_some(),
+                }
+            })
+            .collect()
+    }
+
+    /// Resolves the configured active account, falling back to the first stored one
+    /// when the configured account no longer exists.
+    pub async fn active_account_id(&self, configured: Option<&str>) -> Option<String> {
+        let guard = self.lock_loaded().await;
+        configured
+            .and_then(|selector| find(&guard, Some(selector)))
+            .or_else(|| guard.first())
+            .map(StoredSession::account_id)
+    }
+
+    pub async fn contains(&self, account_id: &str) -> bool {
+        let guard = self.lock_loaded().await;
+        guard
+            .iter()
+            .any(|session| session.account_id() == account_id)
+    }
+
+    /// Upserts the session for the provider/user pair and returns its account id.
+    pub async fn save(
+        &self,
+        app: &AppHandle,
+        provider: &str,
+        tokens: AuthTokensPayload,
+        user: Option<Value>,
+    ) -> Result<String> {
+        let now = Utc::now().timestamp_millis();
+        let session = StoredSession {
+            provider: provider.trim().to_lowercase(),
+            user_id: user_id_from(user.as_ref()),
+            expires_at: expires_at_from(&tokens, now),
+            tokens,
+            user,
+            saved_at: now,
+        };
+        let id = session.account_id();
+        let mut guard = self.lock_loaded().await;
+        let mut next = guard.clone();
+        let change = match next.iter_mut().find(|stored| stored.account_id() == id) {
+            Some(stored) => {
+                *stored = session;
+                "updated"
+            }
+            None => {
+                next.push(session);
+                "added"
+            }
+        };
+        write_keychain(&next).await?;
+        *guard = next;
+        drop(guard);
+        self.changed.notify_waiters();
+        log::info!(target: "auth", "OAuth account {change}: account={id}");
+        emit_account_changed(app, &id, change);
+        Ok(id)
+    }
+
+    pub async fn remove(&self, app: &AppHandle, account_id: &str) -> Result<()> {
+        let mut guard = self.lock_loaded().await;
+        let mut next = guard.clone();
+        let before = next.len();
+        next.retain(|session| session.account_id() != account_id);
+        if next.len() == before {
+            return Err(anyhow!("Unknown account: {account_id}"));
+        }
+        if next.is_empty() {
+            clear_keychain().await?;
+        } else {
+            write_keychain(&next).await?;
+        }
+        *guard = next;
+        drop(guard);
+        self.changed.notify_waiters();
+        log::info!(target: "auth", "OAuth account removed: account={account_id}");
+        emit_account_changed(app, account_id, "removed");
+        Ok(())
+    }
+
+    /// Returns a usable access token for the selected account, refreshing it first
+    /// when it is about to expire.
+    pub async fn access_token(
+        &self,
+        app: &AppHandle,
+        backend_domain: &str,
+        selector: Option<&str>,
+    ) -> Result<Option<String>> {
+        let session = {
+            let guard = self.lock_loaded().await;
+            match select(&guard, selector)? {
+                Some(session) => session.clone(),
+                None if selector.is_some() => {
+                    return Err(anyhow!("Unknown account: {}", selector.unwrap_or_default()))
+                }
+                None => return Ok(None),
+            }
+        };
+        let now = Utc::now().timestamp_millis();
+        if session.tokens.refresh.is_none()
+            || !expires_within(&session, now, ACCESS_TOKEN_REFRESH_MARGIN_MS)
+        {
+            return Ok(Some(session.tokens.access));
+        }
+        self.refresh(
+            app,
+            backend_domain,
+            &session.account_id(),
+            &session.tokens.access,
+        )
+        .await
+        .map(Some)
+    }
+
+    /// Makes the refresh loop recompute its wait from the wall clock; its timer
+    /// does not advance while the machine sleeps.
+    pub fn recheck_expiry(&self) {
+        self.changed.notify_waiters();
+    }
+
+    /// Single-flight refresh: concurrent callers that saw the same stale token wait for
+    /// the first one and reuse its result instead of hitting the backend again.
+    pub async fn refresh(
+        &self,
+        app: &AppHandle,
+        backend_domain: &str,
+        account_id: &str,
+        stale_access: &str,
+    ) -> Result<String> {
+        let _flight = self.refresh_flight.lock().await;
+        let session = self
+            .snapshot(account_id)
+            .await
+            .ok_or_else(|| anyhow!("Unknown account: {account_id}"))?;
+        if session.tokens.access != stale_access {
+            return Ok(session.tokens.access);
+        }
+        let refresh = session
+            .tokens
+            .refresh
+            .clone()
+            .ok_or_else(|| anyhow!("No refresh token available"))?;
+
+        log::info!(target: "auth", "Refreshing access token: account={account_id}");
+        let config = app.state::<Arc<ConfigState>>().get().await;
+        let client = app.state::<Arc<HttpClients>>().api(&config)?;
+        let mut tokens = match oauth::refresh_tokens(&client, Some(backend_domain), &refresh).await
+        {
+            Ok(tokens) => tokens,
+            Err(error) => {
+                log::warn!(target: "auth", "Access token refresh failed: {error}");
+                let _ = emit_event(
+                    app,
+                    events::AUTH_EXPIRED,
+                    AuthExpiredPayload {
+                        account_id: account_id.to_string(),
+                        provider: session.provider.clone(),
+                        error: error.to_string(),
+                    },
+                );
+                return Err(error);
+            }
+        };
+        if tokens.refresh.is_none() {
+            tokens.refresh = Some(refresh);
+        }
+        let now = Utc::now().timestamp_millis();
+        let refreshed = StoredSession {
+            expires_at: expires_at_from(&tokens, now),
+            tokens,
+            saved_at: now,
+            ..session
+        };
+        let access = refreshed.tokens.access.clone();
+        let payload = AuthRefreshedPayload {
+            account_id: account_id.to_string(),
+            provider: refreshed.provider.clone(),
+            expires_at: refreshed.expires_at,
+        };
+        {
+            let mut guard = self.lock_loaded().await;
+            let mut next = guard.clone();
+            match next
+                .iter_mut()
+                .find(|stored| stored.account_id() == account_id)
+            {
+                Some(stored) => *stored = refreshed,
+                // Removed while the refresh was in flight; do not resurrect it.
+                None => return Ok(access),
+            }
+            write_keychain(&next).await?;
+            *guard = next;
+        }
+        self.changed.notify_waiters();
+        let _ = emit_event(app, events::AUTH_REFRESHED, payload);
+        Ok(access)
+    }
+
+    async fn snapshot(&self, account_id: &str) -> Option<StoredSession> {
+        self.lock_loaded()
+            .await
+            .iter()
+            .find(|session| session.account_id() == account_id)
+            .cloned()
+    }
+
+    async fn lock_loaded(&self) -> tokio::sync::MutexGuard<'_, Vec<StoredSession>> {
+        let mut guard = self.accounts.lock().await;
+        if !self.loaded.swap(true, Ordering::SeqCst) {
+            *guard = match read_keychain().await {
+                Ok(accounts) => accounts,
+                Err(error) => {
+                    log::error!(target: "auth", "Failed to read stored OAuth accounts: {error}");
+                    Vec::new()
+                }
+            };
+        }
+        guard
+    }
+}
+
+pub(crate) fn start_refresh_loop(app: AppHandle, store: Arc<AuthStore>) {
+    tauri::async_runtime::spawn(async move {
+        // Access tokens whose refresh already failed; skipped until the account logs in again.
+        let mut dead_tokens: HashSet<String> = HashSet::new();
+        loop {
+            let changed = store.changed.notified();
+            tokio::pin!(changed);
+            changed.as_mut().enable();
+
+            let candidate = {
+                let guard = store.lock_loaded().await;
+                guard
+                    .iter()
+                    .filter(|session| session.tokens.refresh.is_some())
+                    .filter(|session| !dead_tokens.contains(&session.tokens.access))
+                    .filter(|session| session.expires_at.is_some())
+                    .min_by_key(|session| session.expires_at)
+                    .cloned()
+            };
+            let now = Utc::now().timestamp_millis();
+            let wait = candidate
+                .as_ref()
+                .and_then(|session| session.expires_at)
+                .map(|expires_at| {
+                    let delay = (expires_at - BACKGROUND_REFRESH_LEAD_MS - now).max(0);
+                    Duration::from_millis(delay as u64).min(BACKGROUND_IDLE_INTERVAL)
+                })
+                .unwrap_or(BACKGROUND_IDLE_INTERVAL);
+
+            tokio::select! {
+                _ = tokio::time::sleep(wait) => {}
+                _ = &mut changed => continue,
+            }
+
+            let Some(candidate) = candidate else {
+                continue;
+            };
+            let id = candidate.account_id();
+            let Some(session) = store.snapshot(&id).await else {
+                continue;
+            };
+            let now = Utc::now().timestamp_millis();
+            if !expires_within(&session, now, BACKGROUND_REFRESH_LEAD_MS) {
+                continue;
+            }
+            let backend_domain = match app.try_state::<Arc<ConfigState>>() {
+                Some(config) => config.get().await.backend_domain,
+                None => continue,
+            };
+            if store
+                .refresh(&app, &backend_domain, &id, &session.tokens.access)
+                .await
+                .is_err()
+            {
+                // Do not hammer the backend with a dead token; a new login replaces it.
+                dead_tokens.insert(session.tokens.access);
+            }
+        }
+    });
+}
+
+/// Picks the account for `selector`: its full `provider:user_id` id, or a bare
+/// provider as long as only one account of it is stored. No selector picks the
+/// first account.
+fn select<'a>(
+    accounts: &'a [StoredSession],
+    selector: Option<&str>,
+) -> Result<Option<&'a StoredSession>> {
+    let Some(selector) = selector else {
+        return Ok(accounts.first());
+    };
+    let selector = selector.trim().to_lowercase();
+    if let Some(session) = accounts
+        .iter()
+        .find(|session| session.account_id().to_lowercase() == selector)
+    {
+        return Ok(Some(session));
+    }
+    let mut same_provider = accounts
+        .iter()
+        .filter(|session| session.provider == selector);
+    match (same_provider.next(), same_provider.next()) {
+        (Some(_), Some(_)) => Err(anyhow!(
+            "{selector} has several accounts; select one as provider:user_id"
+        )),
+        (session, _) => Ok(session),
+    }
+}
+
+/// [`select`] for lookups that treat an ambiguous selector as no account.
+fn find<'a>(accounts: &'a [StoredSession], selector: Option<&str>) -> Option<&'a StoredSession> {
+    select(accounts, selector).unwrap_or_else(|error| {
+        log::warn!(target: "auth", "{error}");
+        None
+    })
+}
+
+fn user_id_from(user: Option<&Value>) -> Option<String> {
+    let user = user?;
+    ["id", "pk", "uuid"]
+        .iter()
+        .filter_map(|key| user.get(*key))
+        .find_map(|value| match value {
+            Value::String(text) if !text.trim().is_empty() => Some(text.trim().to_string()),
+            Value::Number(number) => Some(number.to_string()),
+            _ => None,
+        })
+}
+
+fn expires_within(session: &StoredSession, now: i64, margin_ms: i64) -> bool {
+    session
+        .expires_at
+        .map(|expires_at| expires_at - margin_ms <= now)
+        .unwrap_or(false)
+}
+
+fn expires_at_from(tokens: &AuthTokensPayload, now: i64) -> Option<i64> {
+    tokens
+        .expires_in
+        .filter(|seconds| *seconds > 0)
+        .map(|seconds| now + seconds * 1000)
+        .or_else(|| jwt_expiry_ms(&tokens.access))
+}
+
+fn jwt_expiry_ms(token: &str) -> Option<i64> {
+    let claims = token.split('.').nth(1)?;
+    let decoded = base64::engine::general_purpose::URL_SAFE_NO_PAD
+        .decode(claims.trim_end_matches('='))
+        .ok()?;
+    let value: Value = serde_json::from_slice(&decoded).ok()?;
+    value.get("exp")?.as_i64().map(|seconds| seconds * 1000)
+}
+
+fn keychain_entry(account: &str) -> Result<keyring::Entry> {
+    keyring::Entry::new(OAUTH_APP_NAME, account)
+        .map_err(|error| anyhow!("Keychain is unavailable: {error}"))
+}
+
+async fn read_keychain() -> Result<Vec<StoredSession>> {
+    spawn_blocking(|| -> Result<Vec<StoredSession>> {
+        match keychain_entry(KEYCHAIN_ACCOUNT)?.get_password() {
+            Ok(serialized) => return Ok(serde_json::from_str(&serialized)?),
+            Err(keyring::Error::NoEntry) => {}
+            Err(error) => return Err(anyhow!("Failed to read keychain: {error}")),
+        }
+        let legacy = keychain_entry(LEGACY_KEYCHAIN_ACCOUNT)?;
+        let mut session: StoredSession = match legacy.get_password() {
+            Ok(serialized) => serde_json::from_str(&serialized)?,
+            Err(keyring::Error::NoEntry) => return Ok(Vec::new()),
+            Err(error) => return Err(anyhow!("Failed to read keychain: {error}")),
+        };
+        session.user_id = user_id_from(session.user.as_ref());
+        let accounts = vec![session];
+        keychain_entry(KEYCHAIN_ACCOUNT)?
+            .set_password(&serde_json::to_string(&accounts)?)
+            .map_err(|error| anyhow!("Failed to write keychain: {error}"))?;
+        let _ = legacy.delete_credential();
+        log::info!(target: "auth", "Migrated single OAuth session to the account store");
+        Ok(accounts)
+    })
+    .await?
+}
+
+async fn write_keychain(accounts: &[StoredSession]) -> Result<()> {
+    let serialized = serde_json::to_string(accounts)?;
+    spawn_blocking(move || -> Result<()> {
+        keychain_entry(KEYCHAIN_ACCOUNT)?
+            .set_password(&serialized)
+            .map_err(|error| anyhow!("Failed to write keychain: {error}"))
+    })
+    .await?
+}
+
+async fn clear_keychain() -> Result<()> {
+    spawn_blocking(|| -> Result<()> {
+        match keychain_entry(KEYCHAIN_ACCOUNT)?.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(error) => Err(anyhow!("Failed to clear keychain: {error}")),
+        }
+    })
+    .await?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(provider: &str, user_id: Option<&str>) -> StoredSession {
+        StoredSession {
+            provider: provider.to_string(),
+            user_id: user_id.map(str::to_string),
+            tokens: AuthTokensPayload {
+                access: format!("{provider}-{}", user_id.unwrap_or_default()),
+                refresh: None,
+                expires_in: None,
+            },
+            user: None,
+            expires_at: None,
+            saved_at: 0,
+        }
+    }
+
+    #[test]
+    fn full_account_id_selects_one_account() {
+        let accounts = [session("google", Some("1")), session("google", Some("2"))];
+        let selected = select(&accounts, Some("Google:2")).unwrap().unwrap();
+        assert_eq!(selected.account_id(), "google:2");
+    }
+
+    #[test]
+    fn bare_provider_is_ambiguous_with_several_accounts() {
+        let accounts = [session("google", Some("1")), session("google", Some("2"))];
+        assert!(select(&accounts, Some("google")).is_err());
+        assert!(find(&accounts, Some("google")).is_none());
+    }
+
+    #[test]
+    fn bare_provider_selects_its_only_account() {
+        let accounts = [session("github", Some("7")), session("google", Some("1"))];
+        let selected = select(&accounts, Some("google")).unwrap().unwrap();
+        assert_eq!(selected.account_id(), "google:1");
+        assert!(select(&accounts, Some("discord")).unwrap().is_none());
+    }
+}
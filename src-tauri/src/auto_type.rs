@@ -0,0 +1,395 @@
+//! Types text into whichever window has focus (`enable_auto_type`). Windows sends
+//! Unicode keystrokes through `SendInput`; Linux drives `xdotool` on X11 or `wtype`
+//! on Wayland, and macOS goes through System Events. While typing runs, Esc is held
+//! as a global shortcut and stops it between chunks.
+
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::Serialize;
+use tauri::AppHandle;
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, ShortcutState};
+
+use crate::constants::{MAX_AUTO_TYPE_WPM, MIN_AUTO_TYPE_WPM};
+use crate::types::AppConfig;
+
+const ABORT_SHORTCUT: &str = "Escape";
+// Lets the modifiers of the hotkey that started typing come up first.
+const START_DELAY: Duration = Duration::from_millis(500);
+// Characters per "word" in words-per-minute.
+const CHARS_PER_WORD: u64 = 5;
+// Upper bound for one chunk; Esc is checked between chunks.
+const MAX_CHUNK_CHARS: usize = 32;
+
+#[derive(Debug)]
+pub enum AutoTypeError {
+    Disabled,
+    Busy,
+    /// The platform or session cannot synthesize keystrokes; the text says why.
+    Unsupported(String),
+    Failed(anyhow::Error),
+}
+
+impl fmt::Display for AutoTypeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Disabled => write!(f, "auto-type is disabled in settings"),
+            Self::Busy => write!(f, "another text is still being typed"),
+            Self::Unsupported(reason) => write!(f, "{reason}"),
+            Self::Failed(error) => write!(f, "{error:#}"),
+        }
+    }
+}
+
+impl From<anyhow::Error> for AutoTypeError {
+    fn from(error: anyhow::Error) -> Self {
+        Self::Failed(error)
+    }
+}
+
+impl From<std::io::Error> for AutoTypeError {
+    fn from(error: std::io::Error) -> Self {
+        Self::Failed(error.into())
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AutoTypeOutcome {
+    pub typed_chars: usize,
+    pub total_chars: usize,
+    /// Stopped by Esc or `type_text_abort` before the end.
+    pub aborted: bool,
+}
+
+pub struct AutoTyper {
+    running: AtomicBool,
+    abort: Arc<AtomicBool>,
+}
+
+impl AutoTyper {
+    pub fn new() -> Self {
+        Self {
+            running: AtomicBool::new(false),
+            abort: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Stops the current run after the chunk being typed.
+    pub fn abort(&self) {
+        if self.running.load(Ordering::SeqCst) {
+            self.abort.store(true, Ordering::SeqCst);
+        }
+    }
+
+    /// Types `text` at `wpm`, or at the configured speed when `None`.
+    pub async fn type_text(
+        &self,
+        app: &AppHandle,
+        config: &AppConfig,
+        text: &str,
+        wpm: Option<u32>,
+    ) -> Result<AutoTypeOutcome, AutoTypeError> {
+        if !config.enable_auto_type {
+            return Err(AutoTypeError::Disabled);
+        }
+        if self.running.swap(true, Ordering::SeqCst) {
+            return Err(AutoTypeError::Busy);
+        }
+        self.abort.store(false, Ordering::SeqCst);
+        let abort_registered = register_abort(app, self.abort.clone());
+        let result = self.run(config, text, wpm).await;
+        if abort_registered {
+            let _ = app.global_shortcut().unregister(ABORT_SHORTCUT);
+        }
+        self.running.store(false, Ordering::SeqCst);
+        result
+    }
+
+    async fn run(
+        &self,
+        config: &AppConfig,
+        text: &str,
+        wpm: Option<u32>,
+    ) -> Result<AutoTypeOutcome, AutoTypeError> {
+        let backend = Backend::detect()?;
+        let wpm = wpm
+            .unwrap_or(config.auto_type_wpm)
+            .clamp(MIN_AUTO_TYPE_WPM, MAX_AUTO_TYPE_WPM);
+        let char_delay = Duration::from_millis(60_000 / (wpm as u64 * CHARS_PER_WORD));
+        // Windows line endings would type two line breaks.
+        let text = text.replace("\r\n", "\n");
+        let total_chars = text.chars().count();
+        log::info!(
+            target: "auto-type",
+            "Typing {total_chars} chars at {wpm} wpm via {}",
+            backend.name()
+        );
+
+        tokio::time::sleep(START_DELAY).await;
+        let mut typed_chars = 0;
+        for chunk in chunks(&text) {
+            if self.abort.load(Ordering::SeqCst) {
+                log::info!(target: "auto-type", "Typing aborted after {typed_chars} chars");
+                return Ok(AutoTypeOutcome {
+                    typed_chars,
+                    total_chars,
+                    aborted: true,
+                });
+            }
+            backend.type_chunk(chunk, char_delay).await?;
+            typed_chars += chunk.chars().count();
+        }
+        Ok(AutoTypeOutcome {
+            typed_chars,
+            total_chars,
+            aborted: false,
+        })
+    }
+}
+
+/// Holds Esc for the duration of a run. Typing still works when another app
+/// owns the key; it just cannot be stopped from the keyboard.
+fn register_abort(app: &AppHandle, abort: Arc<AtomicBool>) -> bool {
+    let result = app
+        .global_shortcut()
+        .on_shortcut(ABORT_SHORTCUT, move |_, _, event| {
+            if event.state == ShortcutState::Pressed {
+                abort.store(true, Ordering::SeqCst);
+            }
+        });
+    match result {
+        Ok(()) => true,
+        Err(error) => {
+            log::warn!(target: "auto-type", "Esc abort not available: {error}");
+            false
+        }
+    }
+}
+
+/// Word-sized pieces with line breaks on their own, so each backend only has to
+/// handle a newline as a whole chunk.
+fn chunks(text: &str) -> Vec<&str> {
+    let mut out = Vec::new();
+    for (index, line) in text.split('\n').enumerate() {
+        if index > 0 {
+            out.push("\n");
+        }
+        let mut start = 0;
+        let mut count = 0;
+        for (offset, ch) in line.char_indices() {
+            count += 1;
+            if ch == ' ' || count >= MAX_CHUNK_CHARS {
+                let end = offset + ch.len_utf8();
+                out.push(&line[start..end]);
+                start = end;
+                count = 0;
+            }
+        }
+        if start < line.len() {
+            out.push(&line[start..]);
+        }
+    }
+    out
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Backend {
+    #[cfg(windows)]
+    SendInput,
+    #[cfg(target_os = "linux")]
+    Xdotool,
+    #[cfg(target_os = "linux")]
+    Wtype,
+    #[cfg(target_os = "macos")]
+    AppleScript,
+}
+
+impl Backend {
+    fn name(self) -> &'static str {
+        match self {
+            #[cfg(windows)]
+            Self::SendInput => "SendInput",
+            #[cfg(target_os = "linux")]
+            Self::Xdotool => "xdotool",
+            #[cfg(target_os = "linux")]
+            Self::Wtype => "wtype",
+            #[cfg(target_os = "macos")]
+            Self::AppleScript => "System Events",
+        }
+    }
+
+    #[cfg(windows)]
+    fn detect() -> Result<Self, AutoTypeError> {
+        Ok(Self::SendInput)
+    }
+
+    #[cfg(target_os = "linux")]
+    fn detect() -> Result<Self, AutoTypeError> {
+        if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+            Ok(Self::Wtype)
+        } else if std::env::var_os("DISPLAY").is_some() {
+            Ok(Self::Xdotool)
+        } else {
+            Err(AutoTypeError::Unsupported(
+                "No graphical session was found to type into".to_string(),
+            ))
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    fn detect() -> Result<Self, AutoTypeError> {
+        Ok(Self::AppleScript)
+    }
+
+    #[cfg(not(any(windows, target_os = "linux", target_os = "macos")))]
+    fn detect() -> Result<Self, AutoTypeError> {
+        Err(AutoTypeError::Unsupported(
+            "Typing is not supported on this platform".to_string(),
+        ))
+    }
+
+    async fn type_chunk(self, chunk: &str, char_delay: Duration) -> Result<(), AutoTypeError> {
+        match self {
+            #[cfg(windows)]
+            Self::SendInput => {
+                for ch in chunk.chars() {
+                    send_char(ch)?;
+                    tokio::time::sleep(char_delay).await;
+                }
+                Ok(())
+            }
+            #[cfg(target_os = "linux")]
+            Self::Xdotool => {
+                let delay = char_delay.as_millis().to_string();
+                let args = if chunk == "\n" {
+                    vec!["key", "--clearmodifiers", "Return"]
+                } else {
+                    vec![
+                        "type",
+                        "--clearmodifiers",
+                        "--delay",
+                        delay.as_str(),
+                        "--",
+                        chunk,
+                    ]
+                };
+                run_tool("xdotool", &args, "Install xdotool to type answers on X11").await
+            }
+            #[cfg(target_os = "linux")]
+            Self::Wtype => {
+                let delay = char_delay.as_millis().to_string();
+                let args = if chunk == "\n" {
+                    vec!["-k", "Return"]
+                } else {
+                    vec!["-d", delay.as_str(), "--", chunk]
+                };
+                // Compositors without the virtual keyboard protocol (GNOME) refuse wtype.
+                run_tool(
+                    "wtype",
+                    &args,
+                    "Install wtype to type answers on Wayland; xdotool cannot reach native Wayland windows",
+                )
+                .await
+            }
+            #[cfg(target_os = "macos")]
+            Self::AppleScript => {
+                let script = if chunk == "\n" {
+                    "tell application \"System Events\" to key code 36".to_string()
+                } else {
+                    let escaped = chunk.replace('\\', "\\\\").replace('"', "\\\"");
+                    format!("tell application \"System Events\" to keystroke \"{escaped}\"")
+                };
+                run_tool(
+                    "osascript",
+                    &["-e", &script],
+                    "osascript is missing; System Events is needed to type",
+                )
+                .await?;
+                // One call types the whole chunk at once; pace the next one instead.
+                tokio::time::sleep(char_delay * chunk.chars().count() as u32).await;
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Runs a typing helper. A missing helper or a refusal from the session becomes
+/// `Unsupported` so the UI can explain what to install or allow.
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+async fn run_tool(program: &str, args: &[&str], missing_hint: &str) -> Result<(), AutoTypeError> {
+    let output = match tokio::process::Command::new(program)
+        .args(args)
+        .stdin(std::process::Stdio::null())
+        .output()
+        .await
+    {
+        Ok(output) => output,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => {
+            return Err(AutoTypeError::Unsupported(missing_hint.to_string()));
+        }
+        Err(error) => return Err(error.into()),
+    };
+    if output.status.success() {
+        return Ok(());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+    Err(AutoTypeError::Unsupported(format!(
+        "{program} could not type ({}): {stderr}",
+        output.status
+    )))
+}
+
+/// One character as Unicode key presses; line breaks and tabs go out as real keys
+/// so editors treat them as Enter and Tab.
+#[cfg(windows)]
+fn send_char(ch: char) -> Result<(), AutoTypeError> {
+    use windows::Win32::UI::Input::KeyboardAndMouse::{
+        SendInput, INPUT, INPUT_0, INPUT_KEYBOARD, KEYBDINPUT, KEYBD_EVENT_FLAGS, KEYEVENTF_KEYUP,
+        KEYEVENTF_UNICODE, VIRTUAL_KEY, VK_RETURN, VK_TAB,
+    };
+
+    let key = |vk: VIRTUAL_KEY, scan: u16, flags: KEYBD_EVENT_FLAGS| INPUT {
+        r#type: INPUT_KEYBOARD,
+        Anonymous: INPUT_0 {
+            ki: KEYBDINPUT {
+                wVk: vk,
+                wScan: scan,
+                dwFlags: flags,
+                time: 0,
+                dwExtraInfo: 0,
+            },
+        },
+    };
+    let mut inputs = Vec::new();
+    match ch {
+        '\n' | '\t' => {
+            let vk = if ch == '\n' { VK_RETURN } else { VK_TAB };
+            inputs.push(key(vk, 0, KEYBD_EVENT_FLAGS(0)));
+            inputs.push(key(vk, 0, KEYEVENTF_KEYUP));
+        }
+        _ => {
+            // Characters outside the BMP go out as two surrogate units.
+            let mut units = [0u16; 2];
+            for unit in ch.encode_utf16(&mut units) {
+                inputs.push(key(VIRTUAL_KEY(0), *unit, KEYEVENTF_UNICODE));
+                inputs.push(key(
+                    VIRTUAL_KEY(0),
+                    *unit,
+                    KEYEVENTF_UNICODE | KEYEVENTF_KEYUP,
+                ));
+            }
+        }
+    }
+    let sent = unsafe { SendInput(&inputs, std::mem::size_of::<INPUT>() as i32) };
+    if sent as usize != inputs.len() {
+        // UIPI blocks input into windows of elevated processes.
+        return Err(AutoTypeError::Unsupported(format!(
+            "Windows accepted {sent} of {} key events; the focused window may belong to an elevated app",
+            inputs.len()
+        )));
+    }
+    Ok(())
+}
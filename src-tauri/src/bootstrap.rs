@@ -0,0 +1,97 @@
+//! `app_bootstrap`: everything the webview loads on start, gathered in one call.
+//! The sections run concurrently and fail independently; the individual
+//! commands stay for refreshing one part later.
+
+use std::fmt::Display;
+use std::sync::Arc;
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+use crate::audio::{AudioDeviceInfo, AudioManager, CaptureStatus};
+use crate::auth::AuthQueue;
+use crate::config::{ConfigSnapshot, ConfigState};
+use crate::local_speech::FastWhisperManager;
+use crate::ollama;
+use crate::private_session;
+use crate::types::{AuthDeepLinkPayload, FastWhisperStatus};
+
+/// One part of the bootstrap: `value`, or why it could not be loaded.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Section<T> {
+    pub value: Option<T>,
+    pub error: Option<String>,
+}
+
+impl<T> Section<T> {
+    fn loaded(value: T) -> Self {
+        Self {
+            value: Some(value),
+            error: None,
+        }
+    }
+}
+
+impl<T, E: Display> From<Result<T, E>> for Section<T> {
+    fn from(result: Result<T, E>) -> Self {
+        match result {
+            Ok(value) => Self::loaded(value),
+            Err(error) => Self {
+                value: None,
+                error: Some(error.to_string()),
+            },
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppBootstrap {
+    pub config: Section<ConfigSnapshot>,
+    pub audio_devices: Section<Vec<AudioDeviceInfo>>,
+    pub capture_status: Section<CaptureStatus>,
+    pub local_speech: Section<FastWhisperStatus>,
+    /// Drained, as `auth_consume_pending` would.
+    pub pending_auth: Section<Vec<AuthDeepLinkPayload>>,
+    pub ollama_installed: Section<bool>,
+    /// Runtime only; a reloaded webview picks the lock up again from here.
+    pub private_session: Section<bool>,
+}
+
+pub async fn gather(app: &AppHandle) -> AppBootstrap {
+    let config = app.state::<Arc<ConfigState>>().inner().clone();
+    let audio = app.state::<Arc<AudioManager>>().inner().clone();
+    let fast_whisper = app.state::<Arc<FastWhisperManager>>().inner().clone();
+    let auth_queue = app.state::<Arc<AuthQueue>>().inner().clone();
+
+    // Device enumeration blocks in the audio host, so it gets its own thread.
+    // `list_devices` takes no manager lock, and `status` only holds each one
+    // briefly, so neither waits on the other or on a starting capture.
+    let devices_audio = audio.clone();
+    let devices = async move {
+        tauri::async_runtime::spawn_blocking(move || devices_audio.list_devices())
+            .await
+            .map_err(anyhow::Error::from)
+            .and_then(|result| result)
+    };
+    let (config, audio_devices, local_speech, pending_auth, ollama_installed) = tokio::join!(
+        config.snapshot(),
+        devices,
+        fast_whisper.get_status(),
+        auth_queue.drain(),
+        ollama::check_installed(),
+    );
+
+    AppBootstrap {
+        config: Section::loaded(config),
+        audio_devices: audio_devices.map_err(|error| format!("{error:#}")).into(),
+        capture_status: Section::loaded(audio.status()),
+        local_speech: Section::loaded(local_speech),
+        pending_auth: Section::loaded(pending_auth),
+        ollama_installed: ollama_installed
+            .map_err(|error| format!("{error:#}"))
+            .into(),
+        private_session: Section::loaded(private_session::is_active()),
+    }
+}
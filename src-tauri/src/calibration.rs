@@ -0,0 +1,220 @@
+//! Guided microphone calibration. The mic is recorded while the user stays
+//! silent and then while they read a sentence; the two levels give the noise
+//! floor and the speech level, from which `micGain`, `vadThresholdDb` and
+//! `agcTargetDb` are recommended.
+
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::Receiver;
+use serde::Serialize;
+use tauri::AppHandle;
+
+use crate::audio;
+use crate::audio_pipeline;
+use crate::constants::{
+    MAX_AGC_TARGET_DB, MAX_MIC_GAIN, MAX_VAD_THRESHOLD_DB, MIN_AGC_TARGET_DB, MIN_MIC_GAIN,
+    MIN_VAD_THRESHOLD_DB,
+};
+use crate::events::{self, emit_event};
+
+pub const DEFAULT_DURATION_SECS: u32 = 8;
+const MIN_DURATION_SECS: u32 = 4;
+const MAX_DURATION_SECS: u32 = 30;
+// The start of each phase is left out: the stream settles and the user reacts.
+const SETTLE: Duration = Duration::from_millis(300);
+const FRAMES_PER_SECOND: u32 = 20;
+const SILENCE_DB: f32 = -120.0;
+// Speech after the recommended gain lands here.
+const TARGET_SPEECH_DB: f32 = -20.0;
+// Noise above this after the gain lowers the AGC target by the excess.
+const NOISE_CEILING_DB: f32 = -50.0;
+// Less than this between the phases means the user did not speak, or the
+// room is too loud to tell speech from noise.
+const MIN_SNR_DB: f32 = 10.0;
+
+/// Emitted as `audio:calibration` when a phase begins, so the UI can prompt.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CalibrationPhase {
+    /// `silence`, `speech` or `done`.
+    pub phase: &'static str,
+    pub duration_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Calibration {
+    /// Median frame level while silent, in dBFS before any gain.
+    pub noise_floor_db: f32,
+    /// Loud end (90th percentile) of the frame levels while speaking.
+    pub speech_level_db: f32,
+    pub mic_gain: f32,
+    pub vad_threshold_db: f32,
+    pub agc_target_db: f32,
+    /// Written to the config by the command.
+    pub applied: bool,
+}
+
+#[derive(Debug)]
+pub enum CalibrationError {
+    /// The mic could not be opened.
+    Device(String),
+    /// A phase ended without any samples.
+    NoAudio,
+    NoSpeech {
+        noise_db: f32,
+        speech_db: f32,
+    },
+}
+
+impl CalibrationError {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Device(_) => "device",
+            Self::NoAudio => "no_audio",
+            Self::NoSpeech { .. } => "no_speech",
+        }
+    }
+}
+
+impl fmt::Display for CalibrationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Device(reason) => write!(f, "microphone could not be opened: {reason}"),
+            Self::NoAudio => f.write_str("no audio arrived from the microphone"),
+            Self::NoSpeech {
+                noise_db,
+                speech_db,
+            } => write!(
+                f,
+                "speech ({speech_db:.1} dBFS) was not clearly above the noise ({noise_db:.1} dBFS)"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for CalibrationError {}
+
+/// Records both phases from `mic` and recommends levels. Blocks for
+/// `duration_secs` (clamped to 4-30), of which a third, 2-4 s, is silence.
+pub fn run(
+    app: &AppHandle,
+    mic: Option<&str>,
+    duration_secs: u32,
+) -> Result<Calibration, CalibrationError> {
+    let total = duration_secs.clamp(MIN_DURATION_SECS, MAX_DURATION_SECS);
+    let silence = (total / 3).clamp(2, 4);
+    let (stream, config, rx) = audio::open_mic_probe(app, mic)
+        .map_err(|error| CalibrationError::Device(format!("{error:#}")))?;
+    let samples_per_second = config.sample_rate.0 as usize * config.channels.max(1) as usize;
+
+    let quiet = record_phase(app, &rx, "silence", silence, samples_per_second);
+    let spoken = record_phase(app, &rx, "speech", total - silence, samples_per_second);
+    drop(stream);
+    let _ = emit_event(
+        app,
+        events::AUDIO_CALIBRATION,
+        CalibrationPhase {
+            phase: "done",
+            duration_ms: 0,
+        },
+    );
+
+    let noise_db = percentile(quiet, 0.5).ok_or(CalibrationError::NoAudio)?;
+    let speech_db = percentile(spoken, 0.9).ok_or(CalibrationError::NoAudio)?;
+    if speech_db - noise_db < MIN_SNR_DB {
+        return Err(CalibrationError::NoSpeech {
+            noise_db,
+            speech_db,
+        });
+    }
+    let calibration = recommend(noise_db, speech_db);
+    log::info!(
+        target: "audio",
+        "Calibrated mic: noise={noise_db:.1} dBFS speech={speech_db:.1} dBFS gain={} vad={} agc={}",
+        calibration.mic_gain,
+        calibration.vad_threshold_db,
+        calibration.agc_target_db
+    );
+    Ok(calibration)
+}
+
+/// Announces the phase, then records it and returns the level of every frame.
+fn record_phase(
+    app: &AppHandle,
+    rx: &Receiver<Vec<i16>>,
+    phase: &'static str,
+    seconds: u32,
+    samples_per_second: usize,
+) -> Vec<f32> {
+    let duration = Duration::from_secs(seconds as u64);
+    let _ = emit_event(
+        app,
+        events::AUDIO_CALIBRATION,
+        CalibrationPhase {
+            phase,
+            duration_ms: duration.as_millis() as u64,
+        },
+    );
+    // Whatever arrived during the previous phase is not part of this one.
+    while rx.try_recv().is_ok() {}
+    let deadline = Instant::now() + duration;
+    let mut samples: Vec<i16> = Vec::new();
+    while let Some(remaining) = deadline.checked_duration_since(Instant::now()) {
+        match rx.recv_timeout(remaining) {
+            Ok(chunk) => samples.extend_from_slice(&chunk),
+            Err(_) => break,
+        }
+    }
+    let settle = samples_per_second * SETTLE.as_millis() as usize / 1000;
+    let frame = (samples_per_second / FRAMES_PER_SECOND as usize).max(1);
+    samples
+        .get(settle..)
+        .unwrap_or_default()
+        .chunks_exact(frame)
+        .map(|frame| {
+            let rms = audio_pipeline::rms(frame);
+            if rms > 0.0 {
+                (20.0 * rms.log10()).max(SILENCE_DB)
+            } else {
+                SILENCE_DB
+            }
+        })
+        .collect()
+}
+
+fn percentile(mut levels: Vec<f32>, share: f32) -> Option<f32> {
+    if levels.is_empty() {
+        return None;
+    }
+    levels.sort_by(f32::total_cmp);
+    let index = ((levels.len() - 1) as f32 * share).round() as usize;
+    Some(levels[index])
+}
+
+fn recommend(noise_db: f32, speech_db: f32) -> Calibration {
+    let gain_db = (TARGET_SPEECH_DB - speech_db)
+        .clamp(20.0 * MIN_MIC_GAIN.log10(), 20.0 * MAX_MIC_GAIN.log10());
+    let mic_gain = round_to(10f32.powf(gain_db / 20.0), 0.01).clamp(MIN_MIC_GAIN, MAX_MIC_GAIN);
+    let gain_db = 20.0 * mic_gain.log10();
+    let noise = noise_db + gain_db;
+    let speech = speech_db + gain_db;
+    // A third of the way from noise to speech, but at least 6 dB clear of both.
+    let vad = (noise + (speech - noise) * 0.3)
+        .max(noise + 6.0)
+        .min(speech - 6.0);
+    let agc = TARGET_SPEECH_DB - (noise - NOISE_CEILING_DB).max(0.0);
+    Calibration {
+        noise_floor_db: round_to(noise_db, 0.1),
+        speech_level_db: round_to(speech_db, 0.1),
+        mic_gain,
+        vad_threshold_db: round_to(vad, 0.5).clamp(MIN_VAD_THRESHOLD_DB, MAX_VAD_THRESHOLD_DB),
+        agc_target_db: round_to(agc, 0.5).clamp(MIN_AGC_TARGET_DB, MAX_AGC_TARGET_DB),
+        applied: false,
+    }
+}
+
+fn round_to(value: f32, step: f32) -> f32 {
+    (value / step).round() * step
+}
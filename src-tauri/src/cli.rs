@@ -0,0 +1,121 @@
+//! Command line. `--headless` applies to the first launch; the actions are meant
+//! for a second launch, which hands them to the running instance so scripts can
+//! drive it (`xexamai --send-duration 15`).
+
+use std::ffi::OsString;
+
+use clap::{value_parser, Arg, ArgAction, ArgGroup, ArgMatches, Command};
+use tauri::{AppHandle, Manager};
+
+use crate::constants::{MAX_DURATION_SECONDS, MIN_DURATION_SECONDS};
+use crate::hotkeys;
+
+const HEADLESS: &str = "headless";
+const SEND_DURATION: &str = "send-duration";
+const TOGGLE_CAPTURE: &str = "toggle-capture";
+const SHOW: &str = "show";
+const HIDE: &str = "hide";
+const QUIT: &str = "quit";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CliAction {
+    SendDuration(u32),
+    ToggleCapture,
+    Show,
+    Hide,
+    Quit,
+}
+
+#[derive(Debug, Default)]
+pub struct CliArgs {
+    /// Start with the tray icon only; the main window opens from the tray.
+    pub headless: bool,
+    pub action: Option<CliAction>,
+}
+
+fn command() -> Command {
+    let flag = |name: &'static str, help: &'static str| {
+        Arg::new(name)
+            .long(name)
+            .action(ArgAction::SetTrue)
+            .help(help)
+    };
+    Command::new("xexamai")
+        .arg(flag(
+            HEADLESS,
+            "Start in the tray without opening the main window",
+        ))
+        .arg(
+            Arg::new(SEND_DURATION)
+                .long(SEND_DURATION)
+                .value_name("SECONDS")
+                .value_parser(
+                    value_parser!(u32)
+                        .range(MIN_DURATION_SECONDS as i64..=MAX_DURATION_SECONDS as i64),
+                )
+                .help("Act as if the hotkey for this duration was pressed"),
+        )
+        .arg(flag(
+            TOGGLE_CAPTURE,
+            "Act as if the toggle hotkey was pressed",
+        ))
+        .arg(flag(SHOW, "Show the main window"))
+        .arg(flag(HIDE, "Hide the main window"))
+        .arg(flag(QUIT, "Quit the running instance"))
+        .group(
+            ArgGroup::new("action")
+                .args([SEND_DURATION, TOGGLE_CAPTURE, SHOW, HIDE, QUIT])
+                .multiple(false),
+        )
+}
+
+/// Parses a full argv, program name first. Anything clap rejects, such as a
+/// deep link URL, is an error so the caller can fall back to what it did before.
+pub fn parse<I, T>(args: I) -> Result<CliArgs, clap::Error>
+where
+    I: IntoIterator<Item = T>,
+    T: Into<OsString> + Clone,
+{
+    let matches = command().try_get_matches_from(args)?;
+    Ok(CliArgs {
+        headless: matches.get_flag(HEADLESS),
+        action: action(&matches),
+    })
+}
+
+fn action(matches: &ArgMatches) -> Option<CliAction> {
+    if let Some(seconds) = matches.get_one::<u32>(SEND_DURATION) {
+        return Some(CliAction::SendDuration(*seconds));
+    }
+    [
+        (TOGGLE_CAPTURE, CliAction::ToggleCapture),
+        (SHOW, CliAction::Show),
+        (HIDE, CliAction::Hide),
+        (QUIT, CliAction::Quit),
+    ]
+    .into_iter()
+    .find(|(name, _)| matches.get_flag(name))
+    .map(|(_, action)| action)
+}
+
+/// Runs an action forwarded from a second launch.
+pub fn run(app: &AppHandle, action: CliAction) {
+    log::info!(target: "cli", "Running forwarded action: {action:?}");
+    match action {
+        CliAction::SendDuration(seconds) => hotkeys::on_duration_pressed(app, seconds),
+        CliAction::ToggleCapture => hotkeys::on_toggle_pressed(app),
+        CliAction::Show => {
+            if let Err(error) = crate::show_main_window(app) {
+                log::warn!(target: "cli", "Failed to show window: {error}");
+            }
+        }
+        CliAction::Hide => {
+            if let Some(window) = app.get_webview_window("main") {
+                if let Err(error) = window.hide() {
+                    log::warn!(target: "cli", "Failed to hide window: {error}");
+                }
+            }
+        }
+        CliAction::Quit => app.exit(0),
+    }
+}
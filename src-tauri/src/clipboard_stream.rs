@@ -0,0 +1,108 @@
+//! Mirrors a streaming answer into the system clipboard (`llm_stream_to_clipboard`),
+//! for users who read answers on another device through a clipboard sync tool.
+//! Writes are throttled, and a stream stops writing once the clipboard holds
+//! something other than its last write, so it never overwrites the user's own copy.
+//! Nothing is mirrored during a private session, where clipboard history and
+//! sync tools would keep the text.
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tauri::AppHandle;
+use tauri_plugin_clipboard_manager::ClipboardExt;
+
+use crate::private_session;
+
+const FLUSH_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Default)]
+struct StreamState {
+    request_id: Option<String>,
+    text: String,
+    // What this stream last put on the clipboard.
+    written: Option<String>,
+    flush_pending: bool,
+    // The user copied something else; the rest of this stream leaves the clipboard alone.
+    yielded: bool,
+}
+
+#[derive(Default)]
+pub struct ClipboardStream {
+    state: Mutex<StreamState>,
+}
+
+impl ClipboardStream {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends `delta` to the answer of `request_id`. A new request id starts a new
+    /// answer; the clipboard is written at most once per `FLUSH_INTERVAL`.
+    pub fn push(self: &Arc<Self>, app: &AppHandle, request_id: Option<String>, delta: &str) {
+        if private_session::is_active() {
+            return;
+        }
+        let mut state = self.state.lock().unwrap();
+        if state.request_id != request_id {
+            *state = StreamState {
+                request_id,
+                ..StreamState::default()
+            };
+        }
+        if state.yielded {
+            return;
+        }
+        state.text.push_str(delta);
+        if state.flush_pending {
+            return;
+        }
+        state.flush_pending = true;
+        let stream = Arc::clone(self);
+        let app_handle = app.clone();
+        tauri::async_runtime::spawn(async move {
+            tokio::time::sleep(FLUSH_INTERVAL).await;
+            let mut state = stream.state.lock().unwrap();
+            state.flush_pending = false;
+            flush(&app_handle, &mut state);
+        });
+    }
+
+    /// Writes the complete answer right away.
+    pub fn finish(&self, app: &AppHandle, request_id: Option<String>, full: &str) {
+        if private_session::is_active() {
+            return;
+        }
+        let mut state = self.state.lock().unwrap();
+        if state.request_id != request_id {
+            *state = StreamState {
+                request_id,
+                ..StreamState::default()
+            };
+        }
+        state.text = full.to_string();
+        flush(app, &mut state);
+    }
+}
+
+fn flush(app: &AppHandle, state: &mut StreamState) {
+    if state.yielded || state.text.trim().is_empty() || state.written.as_ref() == Some(&state.text)
+    {
+        return;
+    }
+    if let Some(written) = state.written.as_deref() {
+        let current = app.clipboard().read_text().unwrap_or_default();
+        // Some platforms hand text back with CRLF line endings.
+        if current.replace("\r\n", "\n") != written.replace("\r\n", "\n") {
+            log::info!(
+                target: "clipboard",
+                "Clipboard changed outside the answer stream; stopping updates for this answer"
+            );
+            state.yielded = true;
+            return;
+        }
+    }
+    match app.clipboard().write_text(state.text.clone()) {
+        Ok(()) => state.written = Some(state.text.clone()),
+        Err(error) => log::warn!(target: "clipboard", "failed to copy streamed answer: {error}"),
+    }
+}
@@ -0,0 +1,255 @@
+//! App-level commands: logs and crash reports, diagnostics, onboarding,
+//! updates and opening things outside the app.
+
+use std::sync::Arc;
+
+use tauri::{AppHandle, State};
+
+use crate::commands::command_set;
+use crate::config::ConfigState;
+use crate::errors::{CommandError, CommandResult, ErrorCode};
+use crate::integration::IntegrationServer;
+use crate::privacy::{self, CaptureProtectionStatus};
+use crate::self_check::{self, SelfCheck};
+use crate::{
+    app_log, bootstrap, crash, diagnostics, error_bus, foreground, monitors, onboarding, perf,
+    private_session, reveal, sounds, tray, update, versions,
+};
+
+command_set![
+    app_log_path,
+    logs_get_recent,
+    logs_open_folder,
+    reveal_in_file_manager,
+    log_frontend,
+    open_external_url,
+    crash_reports_list,
+    diagnostics_export_bundle,
+    app_bootstrap,
+    app_versions,
+    errors_get_recent,
+    perf_command_stats,
+    app_self_check,
+    tray_reload_icon,
+    foreground_window_info,
+    monitors_list,
+    capture_protection_status,
+    sounds_list,
+    onboarding_status,
+    onboarding_complete_step,
+    integration_get_token,
+    update::check_app_update,
+    update::updates_check,
+];
+
+#[perf::command]
+pub async fn app_log_path() -> CommandResult<String> {
+    app_log::current_log_path()
+        .map(|path| path.to_string_lossy().to_string())
+        .map_err(|error| CommandError::new(ErrorCode::LogsUnavailable, error))
+}
+
+#[perf::command]
+pub async fn logs_get_recent(lines: Option<usize>) -> CommandResult<Vec<String>> {
+    app_log::recent_lines(lines.unwrap_or(200))
+        .map_err(|error| CommandError::new(ErrorCode::LogsUnavailable, error))
+}
+
+#[perf::command]
+pub async fn logs_open_folder(app: tauri::AppHandle) -> CommandResult<()> {
+    use tauri_plugin_opener::OpenerExt;
+    let dir = app_log::current_log_dir()
+        .map_err(|error| CommandError::new(ErrorCode::LogsUnavailable, error))?;
+    app.opener()
+        .open_path(dir.to_string_lossy(), None::<String>)
+        .map_err(|error| {
+            CommandError::with_params(ErrorCode::OpenFailed, &[("target", "logs")], error)
+        })
+}
+
+/// Selects a file of ours (a debug recording, log, crash report...) in the OS file manager.
+#[perf::command]
+pub async fn reveal_in_file_manager(app: tauri::AppHandle, path: String) -> CommandResult<()> {
+    reveal::reveal(&app, &path).await
+}
+
+#[perf::command]
+pub async fn log_frontend(entry: serde_json::Value) -> CommandResult<()> {
+    let level = entry
+        .get("level")
+        .and_then(|value| value.as_str())
+        .unwrap_or("info")
+        .trim()
+        .to_lowercase();
+    let category = entry
+        .get("category")
+        .and_then(|value| value.as_str())
+        .unwrap_or("renderer")
+        .trim()
+        .to_string();
+    let message = entry
+        .get("message")
+        .and_then(|value| value.as_str())
+        .unwrap_or("")
+        .trim()
+        .to_string();
+    let data = entry
+        .get("data")
+        .cloned()
+        .unwrap_or(serde_json::Value::Null);
+    // The data of renderer log calls can carry transcripts and answers.
+    let data_text = if data.is_null() {
+        String::new()
+    } else if private_session::is_active() {
+        " data=<private session>".to_string()
+    } else {
+        let text = serde_json::to_string(&data).unwrap_or_else(|_| "<unserializable>".to_string());
+        format!(" data={}", truncate_log_value(&text, 4000))
+    };
+    let line = format!("[{category}] {message}{data_text}");
+
+    match level.as_str() {
+        "error" => log::error!(target: "frontend", "{line}"),
+        "warn" | "warning" => log::warn!(target: "frontend", "{line}"),
+        "debug" => log::debug!(target: "frontend", "{line}"),
+        _ => log::info!(target: "frontend", "{line}"),
+    }
+    Ok(())
+}
+
+fn truncate_log_value(value: &str, max_len: usize) -> String {
+    if value.chars().count() <= max_len {
+        return value.to_string();
+    }
+    let truncated = value.chars().take(max_len).collect::<String>();
+    format!("{truncated}...<truncated>")
+}
+
+#[perf::command]
+pub async fn open_external_url(app: tauri::AppHandle, url: String) -> CommandResult<()> {
+    use tauri_plugin_opener::OpenerExt;
+
+    let normalized = url.trim();
+    if normalized.is_empty() {
+        return Err(CommandError::bare(
+            ErrorCode::InvalidArgument,
+            &[("name", "url")],
+        ));
+    }
+    if !(normalized.starts_with("https://") || normalized.starts_with("http://")) {
+        return Err(CommandError::new(ErrorCode::UrlNotAllowed, normalized));
+    }
+
+    app.opener()
+        .open_url(normalized.to_string(), None::<String>)
+        .map_err(|error| {
+            CommandError::with_params(ErrorCode::OpenFailed, &[("target", normalized)], error)
+        })
+}
+
+#[perf::command]
+pub async fn crash_reports_list() -> CommandResult<Vec<crash::CrashReportInfo>> {
+    crash::list_reports().map_err(|error| CommandError::new(ErrorCode::LogsUnavailable, error))
+}
+
+#[perf::command]
+pub async fn diagnostics_export_bundle(app: tauri::AppHandle) -> CommandResult<String> {
+    diagnostics::export_bundle(&app)
+        .await
+        .map(|path| path.to_string_lossy().to_string())
+        .map_err(|error| CommandError::new(ErrorCode::DiagnosticsExportFailed, error))
+}
+
+#[perf::command]
+pub async fn app_bootstrap(app: tauri::AppHandle) -> CommandResult<bootstrap::AppBootstrap> {
+    Ok(bootstrap::gather(&app).await)
+}
+
+#[perf::command]
+pub async fn app_versions(app: tauri::AppHandle) -> CommandResult<versions::AppVersions> {
+    Ok(versions::collect(&app).await)
+}
+
+#[perf::command]
+pub async fn errors_get_recent(
+    bus: State<'_, Arc<error_bus::ErrorBus>>,
+) -> CommandResult<Vec<error_bus::BackgroundError>> {
+    Ok(bus.recent())
+}
+
+/// Call count and p50/p95 duration of every command called since startup.
+#[perf::command]
+pub async fn perf_command_stats() -> CommandResult<Vec<perf::CommandStats>> {
+    Ok(perf::command_stats())
+}
+
+/// Health of every subsystem; served from a short cache unless `force` is set.
+#[perf::command]
+pub async fn app_self_check(
+    app: AppHandle,
+    checker: State<'_, Arc<SelfCheck>>,
+    force: Option<bool>,
+) -> CommandResult<self_check::SelfCheckReport> {
+    Ok(checker.run(&app, force.unwrap_or(false)).await)
+}
+
+/// Loads the tray icon again, for `trayIconTheme` or after icon files changed.
+#[perf::command]
+pub async fn tray_reload_icon(
+    app: tauri::AppHandle,
+    state: State<'_, Arc<ConfigState>>,
+) -> CommandResult<tray::IconSource> {
+    let theme = state.get().await.tray_icon_theme;
+    tray::apply_theme(&app, theme);
+    tray::reload_icon(&app).map_err(|error| CommandError::new(ErrorCode::TrayFailed, error))
+}
+
+#[perf::command]
+pub async fn foreground_window_info() -> CommandResult<foreground::ForegroundWindowInfo> {
+    foreground::window_info().await
+}
+
+#[perf::command]
+pub async fn monitors_list(app: AppHandle) -> CommandResult<Vec<monitors::MonitorInfo>> {
+    monitors::list(&app).map_err(|error| CommandError::new(ErrorCode::DisplaysUnavailable, error))
+}
+
+/// Whether the main window is excluded from screen capture right now.
+#[perf::command]
+pub async fn capture_protection_status(
+    app: AppHandle,
+    config: State<'_, Arc<ConfigState>>,
+) -> CommandResult<CaptureProtectionStatus> {
+    Ok(privacy::status(&app, &config.get().await))
+}
+
+#[perf::command]
+pub async fn sounds_list(app: AppHandle) -> CommandResult<sounds::SoundsListing> {
+    sounds::list(&app)
+        .await
+        .map_err(|error| CommandError::new(ErrorCode::SoundsFailed, error))
+}
+
+#[perf::command]
+pub async fn onboarding_status(app: AppHandle) -> CommandResult<onboarding::OnboardingStatus> {
+    onboarding::status(&app)
+        .await
+        .map_err(|error| CommandError::new(ErrorCode::OnboardingFailed, error))
+}
+
+#[perf::command]
+pub async fn onboarding_complete_step(
+    app: AppHandle,
+    step: String,
+) -> CommandResult<onboarding::OnboardingStatus> {
+    onboarding::complete_step(&app, &step)
+        .await
+        .map_err(|error| CommandError::new(ErrorCode::OnboardingFailed, error))
+}
+
+#[perf::command]
+pub async fn integration_get_token(
+    integration: State<'_, Arc<IntegrationServer>>,
+) -> CommandResult<String> {
+    Ok(integration.token())
+}
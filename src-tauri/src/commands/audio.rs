@@ -0,0 +1,265 @@
+//! Capture, monitoring and recording indicator commands.
+
+use std::sync::Arc;
+
+use serde_json::json;
+use tauri::{AppHandle, State};
+
+use crate::audio::{self, AudioManager};
+use crate::calibration::{self, Calibration};
+use crate::capture_started;
+use crate::commands::command_set;
+use crate::config::ConfigState;
+use crate::errors::{CommandError, CommandResult, ErrorCode};
+use crate::mic_access::{self, MicrophoneAccess};
+use crate::types::AudioInputType;
+use crate::{indicator, integration, perf, sounds};
+
+command_set![
+    audio_list_devices,
+    audio_start_capture,
+    audio_switch_source,
+    audio_stop_capture,
+    indicator_show,
+    indicator_hide,
+    audio_ack,
+    audio_set_monitor,
+    audio_get_status,
+    audio_validate_setup,
+    audio_calibrate,
+    audio_run_diagnostics,
+    audio_open_privacy_settings,
+];
+
+#[perf::command]
+pub async fn audio_list_devices(
+    manager: State<'_, Arc<AudioManager>>,
+) -> CommandResult<Vec<audio::AudioDeviceInfo>> {
+    manager
+        .list_devices()
+        .map_err(|error| CommandError::new(ErrorCode::AudioDeviceFailed, error))
+}
+
+#[perf::command]
+pub async fn audio_start_capture(
+    app: tauri::AppHandle,
+    manager: State<'_, Arc<AudioManager>>,
+    config: State<'_, Arc<ConfigState>>,
+    source: AudioInputType,
+    device_id: Option<String>,
+) -> CommandResult<()> {
+    let config = config.get().await;
+    let access = mic_access::check();
+    if source == AudioInputType::Microphone && access.denied() {
+        return Err(access_denied(&access));
+    }
+    let devices = audio::CaptureDevices::for_source(&config, source, device_id);
+    manager
+        .start(app.clone(), source, devices)
+        .map_err(|error| capture_error(source, &access, error))?;
+    capture_started(&app, &config, source);
+    Ok(())
+}
+
+/// Moves a running capture to `source` without stopping it. When the new
+/// source fails the old one keeps recording, which the error data reports as
+/// `stillCapturing`. Starts a capture if none runs.
+#[perf::command]
+pub async fn audio_switch_source(
+    app: tauri::AppHandle,
+    manager: State<'_, Arc<AudioManager>>,
+    config: State<'_, Arc<ConfigState>>,
+    source: AudioInputType,
+    device_id: Option<String>,
+) -> CommandResult<()> {
+    let config = config.get().await;
+    let access = mic_access::check();
+    if source == AudioInputType::Microphone && access.denied() {
+        return Err(access_denied(&access));
+    }
+    let was_active = manager.status().active;
+    let devices = audio::CaptureDevices::for_source(&config, source, device_id);
+    if let Err(error) = manager.switch_source(app.clone(), source, devices) {
+        let error = capture_error(source, &access, error);
+        if error.code != ErrorCode::AudioCaptureFailed {
+            return Err(error);
+        }
+        let status = manager.status();
+        return Err(error.with_data(serde_json::json!({
+            "source": source,
+            "stillCapturing": status.active,
+            "currentSource": status.source,
+        })));
+    }
+    if was_active {
+        indicator::sync(&app, &config);
+        integration::publish_capture_state(&app, true, Some(source.as_str()));
+    } else {
+        capture_started(&app, &config, source);
+    }
+    Ok(())
+}
+
+fn capture_error(
+    source: AudioInputType,
+    access: &MicrophoneAccess,
+    error: anyhow::Error,
+) -> CommandError {
+    // A mixed capture can still run on system audio; it only fails here
+    // when nothing could start, and then the switch is the likely cause.
+    if source == AudioInputType::Mixed && access.denied() {
+        access_denied(access)
+    } else {
+        CommandError::new(ErrorCode::AudioCaptureFailed, error)
+    }
+}
+
+fn access_denied(access: &MicrophoneAccess) -> CommandError {
+    CommandError::bare(ErrorCode::MicrophoneAccessDenied, &[]).with_data(serde_json::json!({
+        "state": access.state,
+        "blockedBy": access.blocked_by,
+        "settingsUrl": access.settings_url,
+    }))
+}
+
+#[perf::command]
+pub async fn audio_stop_capture(
+    app: tauri::AppHandle,
+    manager: State<'_, Arc<AudioManager>>,
+) -> CommandResult<()> {
+    manager
+        .stop()
+        .map_err(|error| CommandError::new(ErrorCode::AudioCaptureFailed, error))?;
+    audio::refresh_pre_roll(&app);
+    integration::publish_capture_state(&app, false, None);
+    indicator::hide(&app);
+    sounds::play_event(&app, sounds::CUE_RECORD_STOP);
+    Ok(())
+}
+
+/// Shows the recording indicator regardless of capture state, at the configured corner.
+#[perf::command]
+pub async fn indicator_show(
+    app: AppHandle,
+    config: State<'_, Arc<ConfigState>>,
+) -> CommandResult<()> {
+    indicator::show(&app, config.get().await.recording_indicator_corner);
+    Ok(())
+}
+
+#[perf::command]
+pub async fn indicator_hide(app: AppHandle) -> CommandResult<()> {
+    indicator::hide(&app);
+    Ok(())
+}
+
+#[perf::command]
+pub async fn audio_ack(manager: State<'_, Arc<AudioManager>>, seq: u64) -> CommandResult<()> {
+    manager.ack(seq);
+    Ok(())
+}
+
+#[perf::command]
+pub async fn audio_set_monitor(
+    app: tauri::AppHandle,
+    manager: State<'_, Arc<AudioManager>>,
+    enabled: bool,
+    gain: f32,
+) -> CommandResult<()> {
+    manager.set_monitor(&app, enabled, gain)
+}
+
+#[perf::command]
+pub async fn audio_get_status(
+    manager: State<'_, Arc<AudioManager>>,
+) -> CommandResult<audio::CaptureStatus> {
+    Ok(manager.status())
+}
+
+/// Checks that the chosen devices open and deliver audio, without starting a capture.
+#[perf::command]
+pub async fn audio_validate_setup(
+    app: tauri::AppHandle,
+    manager: State<'_, Arc<AudioManager>>,
+    config: State<'_, Arc<ConfigState>>,
+    source: AudioInputType,
+    device_id: Option<String>,
+    loopback_device_id: Option<String>,
+) -> CommandResult<audio::AudioValidation> {
+    let config = config.get().await;
+    let mut devices = audio::CaptureDevices::for_source(&config, source, device_id);
+    if loopback_device_id.is_some() {
+        devices.system = loopback_device_id;
+    }
+    let manager = manager.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || manager.validate_setup(&app, source, devices))
+        .await
+        .map_err(|error| CommandError::new(ErrorCode::AudioCaptureFailed, error))?
+        .map_err(|error| CommandError::new(ErrorCode::AudioCaptureFailed, error))
+}
+
+/// Records the microphone through a silent and a spoken phase, announced as
+/// `audio:calibration`, and recommends mic levels. `apply` saves them to the config.
+#[perf::command]
+pub async fn audio_calibrate(
+    app: tauri::AppHandle,
+    config: State<'_, Arc<ConfigState>>,
+    duration_secs: Option<u32>,
+    apply: Option<bool>,
+) -> CommandResult<Calibration> {
+    let access = mic_access::check();
+    if access.denied() {
+        return Err(access_denied(&access));
+    }
+    let snapshot = config.get().await;
+    let mic = audio::CaptureDevices::for_source(&snapshot, AudioInputType::Microphone, None).mic;
+    let duration = duration_secs.unwrap_or(calibration::DEFAULT_DURATION_SECS);
+    let handle = app.clone();
+    let mut result = tauri::async_runtime::spawn_blocking(move || {
+        calibration::run(&handle, mic.as_deref(), duration)
+    })
+    .await
+    .map_err(|error| CommandError::new(ErrorCode::AudioCalibrationFailed, error))?
+    .map_err(|error| {
+        CommandError::new(ErrorCode::AudioCalibrationFailed, &error)
+            .with_data(json!({ "kind": error.kind() }))
+    })?;
+    if apply.unwrap_or(false) {
+        config
+            .update(
+                json!({
+                    "micGain": result.mic_gain,
+                    "vadThresholdDb": result.vad_threshold_db,
+                    "agcTargetDb": result.agc_target_db,
+                }),
+                "calibration",
+            )
+            .await
+            .map_err(|error| CommandError::new(ErrorCode::ConfigFailed, error))?;
+        result.applied = true;
+    }
+    Ok(result)
+}
+
+#[perf::command]
+pub async fn audio_run_diagnostics(
+    manager: State<'_, Arc<AudioManager>>,
+) -> CommandResult<audio::AudioDiagnostics> {
+    Ok(manager.diagnostics())
+}
+
+/// Opens the OS page where microphone access is granted.
+#[perf::command]
+pub async fn audio_open_privacy_settings(app: AppHandle) -> CommandResult<()> {
+    use tauri_plugin_opener::OpenerExt;
+
+    let Some(url) = mic_access::check().settings_url else {
+        return Err(CommandError::new(
+            ErrorCode::OpenFailed,
+            "no privacy settings page on this platform",
+        ));
+    };
+    app.opener().open_url(url, None::<String>).map_err(|error| {
+        CommandError::with_params(ErrorCode::OpenFailed, &[("target", url)], error)
+    })
+}
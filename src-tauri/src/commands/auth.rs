@@ -0,0 +1,257 @@
+//! Sign-in, account and backend API commands.
+
+use std::sync::Arc;
+
+use tauri::State;
+
+use crate::api_client::ApiClient;
+use crate::auth::AuthQueue;
+use crate::auth_store::{self, AuthAccount, AuthStatus, AuthStore};
+use crate::commands::command_set;
+use crate::config::ConfigState;
+use crate::errors::{CommandError, CommandResult, ErrorCode};
+use crate::http::HttpClients;
+use crate::oauth;
+use crate::perf;
+use crate::types::AuthDeepLinkPayload;
+
+command_set![
+    auth_consume_pending,
+    auth_get_status,
+    auth_list_accounts,
+    auth_switch_account,
+    auth_remove_account,
+    auth_logout,
+    auth_get_access_token,
+    api_request,
+    auth_get_methods,
+    auth_start_oauth,
+];
+
+#[perf::command]
+pub async fn auth_consume_pending(
+    queue: State<'_, Arc<AuthQueue>>,
+) -> CommandResult<Vec<AuthDeepLinkPayload>> {
+    Ok(queue.drain().await)
+}
+
+#[perf::command]
+pub async fn auth_get_status(
+    config: State<'_, Arc<ConfigState>>,
+    store: State<'_, Arc<AuthStore>>,
+) -> CommandResult<AuthStatus> {
+    let cfg = config.get().await;
+    let active = store.active_account_id(cfg.active_account.as_deref()).await;
+    Ok(store.status(active.as_deref()).await)
+}
+
+#[perf::command]
+pub async fn auth_list_accounts(
+    config: State<'_, Arc<ConfigState>>,
+    store: State<'_, Arc<AuthStore>>,
+) -> CommandResult<Vec<AuthAccount>> {
+    let cfg = config.get().await;
+    let active = store.active_account_id(cfg.active_account.as_deref()).await;
+    Ok(store.list(active.as_deref()).await)
+}
+
+#[perf::command]
+pub async fn auth_switch_account(
+    app: tauri::AppHandle,
+    config: State<'_, Arc<ConfigState>>,
+    store: State<'_, Arc<AuthStore>>,
+    provider: String,
+    user_id: Option<String>,
+) -> CommandResult<AuthStatus> {
+    let id = auth_store::account_id(&provider, user_id.as_deref());
+    if !store.contains(&id).await {
+        return Err(CommandError::bare(
+            ErrorCode::AuthUnknownAccount,
+            &[("account", &id)],
+        ));
+    }
+    config
+        .update(serde_json::json!({ "activeAccount": id }), "auth")
+        .await
+        .map_err(|error| CommandError::new(ErrorCode::ConfigFailed, error))?;
+    auth_store::emit_account_changed(&app, &id, "activated");
+    Ok(store.status(Some(&id)).await)
+}
+
+#[perf::command]
+pub async fn auth_remove_account(
+    app: tauri::AppHandle,
+    config: State<'_, Arc<ConfigState>>,
+    store: State<'_, Arc<AuthStore>>,
+    account_id: String,
+) -> CommandResult<()> {
+    store
+        .remove(&app, &account_id)
+        .await
+        .map_err(|error| CommandError::new(ErrorCode::AuthFailed, error))?;
+    let cfg = config.get().await;
+    if cfg.active_account.as_deref() == Some(account_id.as_str()) {
+        let next = store.active_account_id(None).await;
+        config
+            .update(serde_json::json!({ "activeAccount": next }), "auth")
+            .await
+            .map_err(|error| CommandError::new(ErrorCode::ConfigFailed, error))?;
+    }
+    Ok(())
+}
+
+#[perf::command]
+pub async fn auth_logout(
+    app: tauri::AppHandle,
+    config: State<'_, Arc<ConfigState>>,
+    store: State<'_, Arc<AuthStore>>,
+) -> CommandResult<()> {
+    let cfg = config.get().await;
+    let Some(active) = store.active_account_id(cfg.active_account.as_deref()).await else {
+        return Ok(());
+    };
+    auth_remove_account(app, config, store, active).await
+}
+
+#[perf::command]
+pub async fn auth_get_access_token(
+    app: tauri::AppHandle,
+    config: State<'_, Arc<ConfigState>>,
+    store: State<'_, Arc<AuthStore>>,
+    account: Option<String>,
+) -> CommandResult<Option<String>> {
+    let cfg = config.get().await;
+    let selector = match account {
+        Some(account) => Some(account),
+        None => store.active_account_id(cfg.active_account.as_deref()).await,
+    };
+    store
+        .access_token(&app, &cfg.backend_domain, selector.as_deref())
+        .await
+        .map_err(|error| {
+            log::error!(target: "auth", "auth_get_access_token failed: {error}");
+            CommandError::new(ErrorCode::AuthFailed, error)
+        })
+}
+
+#[perf::command]
+pub async fn api_request(
+    app: tauri::AppHandle,
+    client: State<'_, Arc<ApiClient>>,
+    method: String,
+    path: String,
+    body: Option<serde_json::Value>,
+) -> CommandResult<serde_json::Value> {
+    let method =
+        reqwest::Method::from_bytes(method.trim().to_uppercase().as_bytes()).map_err(|_| {
+            CommandError::with_params(ErrorCode::InvalidArgument, &[("name", "method")], &method)
+        })?;
+    client
+        .request(&app, method, &path, body)
+        .await
+        .map_err(|error| {
+            log::error!(target: "api", "api_request failed: path={path} error={error}");
+            CommandError::new(ErrorCode::NetworkFailed, error)
+        })
+}
+
+#[perf::command]
+pub async fn auth_get_methods(
+    config: State<'_, Arc<ConfigState>>,
+    http: State<'_, Arc<HttpClients>>,
+) -> CommandResult<oauth::AuthMethods> {
+    let cfg = config.get().await;
+    let client = http
+        .api(&cfg)
+        .map_err(|error| CommandError::new(ErrorCode::NetworkFailed, error))?;
+    log::info!(
+        target: "auth",
+        "auth_get_methods command: backend_domain={}",
+        cfg.backend_domain
+    );
+    oauth::load_auth_methods(&client, Some(cfg.backend_domain.as_str()))
+        .await
+        .map_err(|error| {
+            log::error!(target: "auth", "auth_get_methods failed: {error}");
+            CommandError::new(ErrorCode::NetworkFailed, error)
+        })
+}
+
+#[perf::command]
+pub async fn auth_start_oauth(
+    app: tauri::AppHandle,
+    config: State<'_, Arc<ConfigState>>,
+    http: State<'_, Arc<HttpClients>>,
+    queue: State<'_, Arc<AuthQueue>>,
+    provider: String,
+    loopback: Option<bool>,
+) -> CommandResult<()> {
+    use tauri_plugin_opener::OpenerExt;
+    let cfg = config.get().await;
+    let provider = provider.trim().to_lowercase();
+    log::info!(
+        target: "auth",
+        "auth_start_oauth command: provider={} backend_domain={}",
+        provider,
+        cfg.backend_domain
+    );
+    if !oauth::is_supported_provider(&provider) {
+        log::warn!(target: "auth", "Unsupported OAuth provider requested: {provider}");
+        return Err(CommandError::bare(
+            ErrorCode::AuthProviderUnsupported,
+            &[("provider", &provider)],
+        ));
+    }
+    let client = http
+        .api(&cfg)
+        .map_err(|error| CommandError::new(ErrorCode::NetworkFailed, error))?;
+    let methods = oauth::load_auth_methods(&client, Some(cfg.backend_domain.as_str()))
+        .await
+        .map_err(|error| {
+            log::error!(target: "auth", "Failed to load auth methods before OAuth start: {error}");
+            CommandError::new(ErrorCode::NetworkFailed, error)
+        })?;
+    if !oauth::provider_is_allowed(&methods, &provider) {
+        log::warn!(
+            target: "auth",
+            "OAuth provider blocked: provider={} country={} allowed={:?}",
+            provider,
+            methods.country_code,
+            methods.allowed_oauth_providers
+        );
+        return Err(CommandError::new(
+            ErrorCode::AuthProviderUnavailable,
+            format!("provider={provider}"),
+        ));
+    }
+    let oauth_state = queue.start_state(&provider).await;
+    let loopback = if loopback.unwrap_or_else(oauth::prefers_loopback_redirect) {
+        match oauth::bind_loopback_listener().await {
+            Ok(bound) => Some(bound),
+            Err(error) => {
+                log::warn!(target: "auth", "Loopback OAuth listener unavailable, using deep link: {error}");
+                None
+            }
+        }
+    } else {
+        None
+    };
+    let redirect_uri = loopback.as_ref().map(|(_, uri)| uri.clone());
+    let url = oauth::build_oauth_start_url(
+        &provider,
+        Some(cfg.backend_domain.as_str()),
+        &oauth_state,
+        redirect_uri.as_deref(),
+    )
+    .map_err(|error| CommandError::new(ErrorCode::AuthFailed, error))?;
+    if let Some((listener, _)) = loopback {
+        oauth::spawn_loopback_callback(app.clone(), queue.inner().clone(), listener);
+    }
+    log::info!(target: "auth", "Opening OAuth URL: provider={provider}");
+    app.opener()
+        .open_url(url, None::<String>)
+        .map_err(|error| {
+            log::error!(target: "auth", "Failed to open OAuth URL: provider={} error={}", provider, error);
+            CommandError::with_params(ErrorCode::OpenFailed, &[("target", "browser")], error)
+        })
+}
@@ -0,0 +1,196 @@
+//! Config, window preference and hotkey commands.
+
+use std::sync::Arc;
+
+use tauri::{AppHandle, State};
+
+use crate::commands::command_set;
+use crate::config::{ConfigSnapshot, ConfigState, PersistenceStatus, RevisionConflict};
+use crate::config_lock::LockHeld;
+use crate::durations;
+use crate::errors::{CommandError, CommandResult, ErrorCode};
+use crate::hotkeys::{self, HotkeyManager};
+use crate::key_test::{self, KeyTestResult};
+use crate::perf;
+use crate::stream_mode;
+use crate::types::{AppConfig, WindowPrefs};
+use crate::{handle_config_effects, window_prefs};
+
+command_set![
+    config_get,
+    config_retry_persistence,
+    config_force_takeover,
+    get_initial_window_prefs,
+    config_update,
+    config_reset,
+    config_path,
+    open_config_folder,
+    durations_set,
+    duration_hotkey_set,
+    hotkeys_reapply,
+    hotkeys_clear_pending,
+    stream_mode_set,
+    api_key_test,
+];
+
+#[perf::command]
+pub async fn config_get(state: State<'_, Arc<ConfigState>>) -> CommandResult<ConfigSnapshot> {
+    Ok(state.snapshot().await)
+}
+
+#[perf::command]
+pub async fn config_retry_persistence(
+    state: State<'_, Arc<ConfigState>>,
+) -> CommandResult<PersistenceStatus> {
+    state
+        .retry_persistence()
+        .await
+        .map_err(|error| CommandError::new(ErrorCode::ConfigFailed, format!("{error:#}")))
+}
+
+#[perf::command]
+pub async fn config_force_takeover(
+    state: State<'_, Arc<ConfigState>>,
+) -> CommandResult<PersistenceStatus> {
+    state
+        .force_takeover()
+        .await
+        .map_err(|error| match error.downcast_ref::<LockHeld>() {
+            Some(LockHeld(holder)) => CommandError::with_params(
+                ErrorCode::ConfigLocked,
+                &[("pid", &holder.pid.to_string())],
+                &error,
+            )
+            .with_data(serde_json::json!(holder)),
+            None => CommandError::new(ErrorCode::ConfigFailed, format!("{error:#}")),
+        })
+}
+
+#[perf::command]
+pub async fn get_initial_window_prefs(
+    state: State<'_, Arc<ConfigState>>,
+) -> CommandResult<WindowPrefs> {
+    Ok(window_prefs(&state.get().await))
+}
+
+#[perf::command]
+pub async fn config_update(
+    app: tauri::AppHandle,
+    state: State<'_, Arc<ConfigState>>,
+    window: tauri::Window,
+    hotkeys: State<'_, Arc<HotkeyManager>>,
+    payload: serde_json::Value,
+    expected_revision: Option<u64>,
+) -> CommandResult<ConfigSnapshot> {
+    let apply_window_size =
+        payload.get("windowWidth").is_some() || payload.get("windowHeight").is_some();
+    log::info!(
+        target: "config",
+        "config_update command: keys={:?}",
+        payload
+            .as_object()
+            .map(|value| value.keys().cloned().collect::<Vec<_>>())
+    );
+    let updated = state
+        .update_checked(payload, window.label(), expected_revision)
+        .await
+        .map_err(|error| match error.downcast_ref::<RevisionConflict>() {
+            Some(conflict) => CommandError::new(ErrorCode::ConfigConflict, conflict).with_data(
+                serde_json::json!({
+                    "expectedRevision": conflict.expected,
+                    "currentRevision": conflict.current,
+                }),
+            ),
+            None => CommandError::new(ErrorCode::ConfigFailed, error),
+        })?;
+    handle_config_effects(
+        &app,
+        &updated.config,
+        hotkeys.inner().clone(),
+        apply_window_size,
+    );
+    Ok(updated)
+}
+
+#[perf::command]
+pub async fn config_reset(
+    app: tauri::AppHandle,
+    state: State<'_, Arc<ConfigState>>,
+    window: tauri::Window,
+    hotkeys: State<'_, Arc<HotkeyManager>>,
+) -> CommandResult<ConfigSnapshot> {
+    let updated = state
+        .reset(window.label())
+        .await
+        .map_err(|error| CommandError::new(ErrorCode::ConfigFailed, error))?;
+    handle_config_effects(&app, &updated.config, hotkeys.inner().clone(), true);
+    Ok(updated)
+}
+
+#[perf::command]
+pub async fn config_path(state: State<'_, Arc<ConfigState>>) -> CommandResult<String> {
+    Ok(state.path().await.to_string_lossy().to_string())
+}
+
+#[perf::command]
+pub async fn open_config_folder(
+    app: tauri::AppHandle,
+    state: State<'_, Arc<ConfigState>>,
+) -> CommandResult<()> {
+    let dir = state.directory().await;
+    tauri_plugin_opener::OpenerExt::opener(&app)
+        .open_path(dir.to_string_lossy(), None::<String>)
+        .map_err(|error| {
+            CommandError::with_params(ErrorCode::OpenFailed, &[("target", "settings")], error)
+        })
+}
+
+#[perf::command]
+pub async fn durations_set(app: AppHandle, list: Vec<u32>) -> CommandResult<AppConfig> {
+    durations::set_durations(&app, list).await
+}
+
+#[perf::command]
+pub async fn duration_hotkey_set(
+    app: AppHandle,
+    duration: u32,
+    key: String,
+) -> CommandResult<AppConfig> {
+    durations::set_duration_hotkey(&app, duration, &key).await
+}
+
+/// Unregisters every hotkey and registers the configured ones again.
+#[perf::command]
+pub async fn hotkeys_reapply(
+    app: AppHandle,
+    state: State<'_, Arc<ConfigState>>,
+    hotkeys: State<'_, Arc<HotkeyManager>>,
+) -> CommandResult<hotkeys::HotkeyReapplyReport> {
+    let config = state.get().await;
+    Ok(hotkeys.reapply(&app, &config, "command"))
+}
+
+/// Drops duration presses queued behind a running transcription.
+#[perf::command]
+pub async fn hotkeys_clear_pending(hotkeys: State<'_, Arc<HotkeyManager>>) -> CommandResult<usize> {
+    Ok(hotkeys.clear_pending())
+}
+
+#[perf::command]
+pub async fn stream_mode_set(
+    app: AppHandle,
+    window: tauri::Window,
+    mode: String,
+) -> CommandResult<ConfigSnapshot> {
+    stream_mode::set_stream_mode(&app, &mode, window.label()).await
+}
+
+/// Tests `key`, or the stored one, against `provider` without saving it.
+#[perf::command]
+pub async fn api_key_test(
+    app: AppHandle,
+    provider: String,
+    key: Option<String>,
+) -> CommandResult<KeyTestResult> {
+    key_test::test_key(&app, &provider, key).await
+}
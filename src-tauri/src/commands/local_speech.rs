@@ -0,0 +1,117 @@
+//! Commands for the local fast-whisper server.
+
+use std::sync::Arc;
+
+use tauri::State;
+
+use crate::commands::command_set;
+use crate::errors::{CommandError, CommandResult, ErrorCode};
+use crate::local_speech::FastWhisperManager;
+use crate::perf;
+use crate::types::FastWhisperStatus;
+
+command_set![
+    local_speech_get_status,
+    local_speech_check_health,
+    local_speech_install,
+    local_speech_start,
+    local_speech_restart,
+    local_speech_reinstall,
+    local_speech_stop,
+    local_speech_check_model_downloaded,
+    local_speech_watch,
+];
+
+#[perf::command]
+pub async fn local_speech_get_status(
+    manager: State<'_, Arc<FastWhisperManager>>,
+) -> CommandResult<FastWhisperStatus> {
+    Ok(manager.get_status().await)
+}
+
+#[perf::command]
+pub async fn local_speech_check_health(
+    app: tauri::AppHandle,
+    manager: State<'_, Arc<FastWhisperManager>>,
+) -> CommandResult<FastWhisperStatus> {
+    Ok(manager.check_health(&app).await)
+}
+
+#[perf::command]
+pub async fn local_speech_install(
+    app: tauri::AppHandle,
+    manager: State<'_, Arc<FastWhisperManager>>,
+) -> CommandResult<FastWhisperStatus> {
+    manager
+        .install_and_start(&app)
+        .await
+        .map_err(|error| CommandError::new(ErrorCode::LocalSpeechFailed, error))
+}
+
+#[perf::command]
+pub async fn local_speech_start(
+    app: tauri::AppHandle,
+    manager: State<'_, Arc<FastWhisperManager>>,
+) -> CommandResult<FastWhisperStatus> {
+    manager
+        .start_existing(&app)
+        .await
+        .map_err(|error| CommandError::new(ErrorCode::LocalSpeechFailed, error))
+}
+
+#[perf::command]
+pub async fn local_speech_restart(
+    app: tauri::AppHandle,
+    manager: State<'_, Arc<FastWhisperManager>>,
+) -> CommandResult<FastWhisperStatus> {
+    manager
+        .restart(&app)
+        .await
+        .map_err(|error| CommandError::new(ErrorCode::LocalSpeechFailed, error))
+}
+
+#[perf::command]
+pub async fn local_speech_reinstall(
+    app: tauri::AppHandle,
+    manager: State<'_, Arc<FastWhisperManager>>,
+) -> CommandResult<FastWhisperStatus> {
+    manager
+        .reinstall(&app)
+        .await
+        .map_err(|error| CommandError::new(ErrorCode::LocalSpeechFailed, error))
+}
+
+#[perf::command]
+pub async fn local_speech_stop(
+    app: tauri::AppHandle,
+    manager: State<'_, Arc<FastWhisperManager>>,
+) -> CommandResult<FastWhisperStatus> {
+    manager
+        .stop(&app)
+        .await
+        .map_err(|error| CommandError::new(ErrorCode::LocalSpeechFailed, error))
+}
+
+#[perf::command]
+pub async fn local_speech_check_model_downloaded(
+    app: tauri::AppHandle,
+    manager: State<'_, Arc<FastWhisperManager>>,
+    model: String,
+) -> CommandResult<bool> {
+    manager
+        .is_model_downloaded(&app, &model)
+        .await
+        .map_err(|error| CommandError::new(ErrorCode::LocalSpeechFailed, error))
+}
+
+/// Counts the caller in (`enable`) or out of background health checks, which
+/// push `local-speech:status` when the server state changes. Returns how many
+/// watchers remain.
+#[perf::command]
+pub async fn local_speech_watch(
+    app: tauri::AppHandle,
+    manager: State<'_, Arc<FastWhisperManager>>,
+    enable: bool,
+) -> CommandResult<usize> {
+    Ok(manager.watch(&app, enable))
+}
@@ -0,0 +1,219 @@
+//! Tauri commands, one set per domain. Each file declares its commands with
+//! `command_set!`, and `handler` dispatches an invoke to the set that owns it,
+//! so adding a command only touches its domain file. Commands are declared
+//! with `#[perf::command]`, so they show up in `perf_command_stats`.
+
+use tauri::ipc::{Invoke, InvokeHandler};
+use tauri::Wry;
+
+mod app;
+mod audio;
+mod auth;
+mod config;
+mod local_speech;
+mod ollama;
+mod prompt;
+mod session;
+mod transcription;
+
+/// The commands of one domain and the handler `generate_handler!` built for them.
+pub struct CommandSet {
+    pub names: Vec<&'static str>,
+    handler: Box<InvokeHandler<Wry>>,
+}
+
+impl CommandSet {
+    pub fn new(paths: &[&'static str], handler: Box<InvokeHandler<Wry>>) -> Self {
+        // `stringify!` keeps the module path; the webview invokes the bare name.
+        let names = paths
+            .iter()
+            .map(|path| path.rsplit("::").next().unwrap_or(path).trim())
+            .collect();
+        Self { names, handler }
+    }
+}
+
+/// Declares `handlers()` for the listed commands, by name or module path.
+macro_rules! command_set {
+    ($($($segment:ident)::+),+ $(,)?) => {
+        pub fn handlers() -> $crate::commands::CommandSet {
+            $crate::commands::CommandSet::new(
+                &[$(stringify!($($segment)::+)),+],
+                Box::new(tauri::generate_handler![$($($segment)::+),+]),
+            )
+        }
+    };
+}
+pub(crate) use command_set;
+
+pub fn all() -> Vec<CommandSet> {
+    vec![
+        app::handlers(),
+        audio::handlers(),
+        auth::handlers(),
+        config::handlers(),
+        local_speech::handlers(),
+        ollama::handlers(),
+        prompt::handlers(),
+        session::handlers(),
+        transcription::handlers(),
+    ]
+}
+
+/// The invoke handler for every set. Unknown commands return `false`, which
+/// Tauri reports to the webview as "command not found".
+pub fn handler() -> impl Fn(Invoke<Wry>) -> bool + Send + Sync + 'static {
+    let sets = all();
+    let mut seen = std::collections::HashSet::new();
+    for name in sets.iter().flat_map(|set| &set.names) {
+        if !seen.insert(*name) {
+            log::error!(target: "commands", "Command {name} is registered twice");
+        }
+    }
+    move |invoke| {
+        let command = invoke.message.command();
+        match sets.iter().find(|set| set.names.contains(&command)) {
+            Some(set) => (set.handler)(invoke),
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    /// Every command the webview may invoke, in registration order.
+    const EXPECTED: &[&str] = &[
+        // app
+        "app_log_path",
+        "logs_get_recent",
+        "logs_open_folder",
+        "reveal_in_file_manager",
+        "log_frontend",
+        "open_external_url",
+        "crash_reports_list",
+        "diagnostics_export_bundle",
+        "app_bootstrap",
+        "app_versions",
+        "errors_get_recent",
+        "perf_command_stats",
+        "app_self_check",
+        "tray_reload_icon",
+        "foreground_window_info",
+        "monitors_list",
+        "capture_protection_status",
+        "sounds_list",
+        "onboarding_status",
+        "onboarding_complete_step",
+        "integration_get_token",
+        "check_app_update",
+        "updates_check",
+        // audio
+        "audio_list_devices",
+        "audio_start_capture",
+        "audio_switch_source",
+        "audio_stop_capture",
+        "indicator_show",
+        "indicator_hide",
+        "audio_ack",
+        "audio_set_monitor",
+        "audio_get_status",
+        "audio_validate_setup",
+        "audio_calibrate",
+        "audio_run_diagnostics",
+        "audio_open_privacy_settings",
+        // auth
+        "auth_consume_pending",
+        "auth_get_status",
+        "auth_list_accounts",
+        "auth_switch_account",
+        "auth_remove_account",
+        "auth_logout",
+        "auth_get_access_token",
+        "api_request",
+        "auth_get_methods",
+        "auth_start_oauth",
+        // config
+        "config_get",
+        "config_retry_persistence",
+        "config_force_takeover",
+        "get_initial_window_prefs",
+        "config_update",
+        "config_reset",
+        "config_path",
+        "open_config_folder",
+        "durations_set",
+        "duration_hotkey_set",
+        "hotkeys_reapply",
+        "hotkeys_clear_pending",
+        "stream_mode_set",
+        "api_key_test",
+        // local_speech
+        "local_speech_get_status",
+        "local_speech_check_health",
+        "local_speech_install",
+        "local_speech_start",
+        "local_speech_restart",
+        "local_speech_reinstall",
+        "local_speech_stop",
+        "local_speech_check_model_downloaded",
+        "local_speech_watch",
+        // ollama
+        "ollama_check_installed",
+        "ollama_list_models",
+        "ollama_pull_model",
+        "ollama_recommend_models",
+        "system_resources",
+        "ollama_warmup_model",
+        "ollama_http_request",
+        // prompt
+        "prompt_preview",
+        "prompt_render",
+        "text_sanitize",
+        // session
+        "usage_stats",
+        "usage_reset",
+        "usage_record_llm",
+        "session_export",
+        "session_clear",
+        "session_timeline",
+        "llm_clipboard_delta",
+        "llm_clipboard_done",
+        "type_text",
+        "type_text_abort",
+        "session_record_answer",
+        "session_summary_complete",
+        "transcript_log_open_folder",
+        "debug_file_read",
+        "audio_waveform",
+        "artifacts_encrypt_existing",
+        "provider_rate_limit_acquire",
+        "provider_quota_report",
+        "provider_quota_status",
+        "private_session_set",
+        "private_session_get",
+        // transcription
+        "transcribe_audio",
+        "transcribe_batch",
+    ];
+
+    #[test]
+    fn every_command_is_registered_once() {
+        let sets = all();
+        let names: Vec<&str> = sets
+            .iter()
+            .flat_map(|set| set.names.iter().copied())
+            .collect();
+        let mut seen = HashSet::new();
+        let duplicates: Vec<&str> = names
+            .iter()
+            .copied()
+            .filter(|name| !seen.insert(*name))
+            .collect();
+        assert!(duplicates.is_empty(), "registered twice: {duplicates:?}");
+        assert_eq!(names, EXPECTED);
+    }
+}
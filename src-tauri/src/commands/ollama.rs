@@ -0,0 +1,147 @@
+//! Ollama commands: install checks, models, and the HTTP proxy the webview
+//! talks to the local server through.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use tauri::{AppHandle, State};
+
+use crate::commands::command_set;
+use crate::errors::{CommandError, CommandResult, ErrorCode};
+use crate::events::{self, emit_event};
+use crate::http::HttpClients;
+use crate::{ollama, perf, system_resources};
+
+command_set![
+    ollama_check_installed,
+    ollama_list_models,
+    ollama_pull_model,
+    ollama_recommend_models,
+    system_resources,
+    ollama_warmup_model,
+    ollama_http_request,
+];
+
+#[perf::command]
+pub async fn ollama_check_installed() -> CommandResult<bool> {
+    crate::ollama::check_installed()
+        .await
+        .map_err(|error| CommandError::new(ErrorCode::OllamaFailed, error))
+}
+
+#[perf::command]
+pub async fn ollama_list_models() -> CommandResult<Vec<String>> {
+    crate::ollama::list_models()
+        .await
+        .map_err(|error| CommandError::new(ErrorCode::OllamaFailed, error))
+}
+
+#[perf::command]
+pub async fn ollama_pull_model(
+    app: AppHandle,
+    http: State<'_, Arc<HttpClients>>,
+    model: String,
+) -> CommandResult<ollama::PullOutcome> {
+    let resources = system_resources::probe().await;
+    let check = ollama::check_model(&http.local(), &model, &resources).await;
+    // The pull still runs; the webview decides how loudly to warn.
+    let memory_warning = match check.fit {
+        ollama::ModelFit::Tight | ollama::ModelFit::WontFit => {
+            log::warn!(
+                target: "ollama",
+                "Pulling {} which likely exceeds memory: fit={:?} required={:?}",
+                check.name,
+                check.fit,
+                check.required_bytes
+            );
+            let _ = emit_event(&app, events::OLLAMA_MEMORY_WARNING, &check);
+            Some(check)
+        }
+        _ => None,
+    };
+    crate::ollama::pull_model(&model)
+        .await
+        .map_err(|error| CommandError::new(ErrorCode::OllamaFailed, error))?;
+    Ok(ollama::PullOutcome { memory_warning })
+}
+
+/// Installed models plus `candidates`, classified by whether they fit in memory.
+#[perf::command]
+pub async fn ollama_recommend_models(
+    http: State<'_, Arc<HttpClients>>,
+    candidates: Option<Vec<String>>,
+) -> CommandResult<Vec<ollama::ModelRecommendation>> {
+    let resources = system_resources::probe().await;
+    Ok(ollama::recommend_models(&http.local(), &candidates.unwrap_or_default(), &resources).await)
+}
+
+#[perf::command]
+pub async fn system_resources() -> CommandResult<system_resources::SystemResources> {
+    Ok(system_resources::probe().await)
+}
+
+#[perf::command]
+pub async fn ollama_warmup_model(model: String) -> CommandResult<()> {
+    crate::ollama::warmup_model(&model)
+        .await
+        .map_err(|error| CommandError::new(ErrorCode::OllamaFailed, error))
+}
+
+#[perf::command]
+pub async fn ollama_http_request(
+    http: State<'_, Arc<HttpClients>>,
+    url: String,
+    method: String,
+    headers: serde_json::Value,
+    body: Option<String>,
+    timeout_secs: Option<u64>,
+) -> CommandResult<String> {
+    let client = http.local();
+    let mut request = match method.as_str() {
+        "GET" => client.get(&url),
+        "POST" => client.post(&url),
+        "PUT" => client.put(&url),
+        "DELETE" => client.delete(&url),
+        _ => {
+            return Err(CommandError::with_params(
+                ErrorCode::InvalidArgument,
+                &[("name", "method")],
+                &method,
+            ))
+        }
+    };
+
+    // Добавляем заголовки
+    if let serde_json::Value::Object(map) = headers {
+        for (key, value) in map {
+            if let Some(val_str) = value.as_str() {
+                request = request.header(&key, val_str);
+            }
+        }
+    }
+
+    // Добавляем тело запроса
+    if let Some(body_str) = body {
+        request = request.body(body_str);
+    }
+
+    let response = request
+        .timeout(Duration::from_secs(timeout_secs.unwrap_or(600)))
+        .send()
+        .await
+        .map_err(|error| CommandError::new(ErrorCode::OllamaFailed, error))?;
+    let status = response.status();
+    let text = response
+        .text()
+        .await
+        .map_err(|error| CommandError::new(ErrorCode::OllamaFailed, error))?;
+
+    if !status.is_success() {
+        return Err(CommandError::new(
+            ErrorCode::OllamaFailed,
+            format!("HTTP {}: {}", status.as_u16(), text),
+        ));
+    }
+
+    Ok(text)
+}
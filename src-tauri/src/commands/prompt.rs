@@ -0,0 +1,66 @@
+//! LLM prompt templating: the settings preview and the prompt as sent.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use serde_json::json;
+use tauri::State;
+
+use crate::commands::command_set;
+use crate::config::ConfigState;
+use crate::errors::{CommandError, CommandResult, ErrorCode};
+use crate::perf;
+use crate::prompt_template::{self, PromptPreview, TemplateError};
+use crate::text_sanitize;
+
+command_set![prompt_preview, prompt_render, text_sanitize];
+
+fn template_error(error: TemplateError) -> CommandError {
+    let position = (error.offset() + 1).to_string();
+    CommandError::with_params(
+        ErrorCode::PromptTemplateInvalid,
+        &[("position", &position)],
+        &error,
+    )
+    .with_data(json!({
+        "kind": error.kind(),
+        "name": error.name(),
+        "offset": error.offset(),
+    }))
+}
+
+/// Renders `template` for the settings preview. `sampleVars` stand in for the
+/// values a request would supply; the rest come from config as in `prompt_render`.
+#[perf::command]
+pub async fn prompt_preview(
+    config: State<'_, Arc<ConfigState>>,
+    template: String,
+    sample_vars: Option<HashMap<String, String>>,
+) -> CommandResult<PromptPreview> {
+    let config = config.get().await;
+    prompt_template::preview(&config, &template, sample_vars.unwrap_or_default())
+        .await
+        .map_err(template_error)
+}
+
+/// The configured `llmPrompt` with its variables filled, ready to send. The
+/// webview passes what only it knows, such as `transcript`.
+#[perf::command]
+pub async fn prompt_render(
+    config: State<'_, Arc<ConfigState>>,
+    vars: Option<HashMap<String, String>>,
+) -> CommandResult<String> {
+    let config = config.get().await;
+    let template = config.llm_prompt.as_str();
+    let values = prompt_template::values(&config, template, vars.unwrap_or_default())
+        .await
+        .map_err(template_error)?;
+    prompt_template::render(template, &values).map_err(template_error)
+}
+
+/// The cleanup config normalization applies to prompts, for other fields that
+/// take pasted text.
+#[perf::command]
+pub async fn text_sanitize(text: String) -> CommandResult<String> {
+    Ok(text_sanitize::sanitize(&text))
+}
@@ -0,0 +1,330 @@
+//! Session log, usage, clipboard streaming and auto-type commands, plus the
+//! debug artifacts they leave behind.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use serde_json::json;
+use tauri::{AppHandle, State};
+
+use crate::artifacts::{self, ArtifactVault};
+use crate::auto_type::{self, AutoTypeError, AutoTyper};
+use crate::clipboard_stream::ClipboardStream;
+use crate::commands::command_set;
+use crate::config::ConfigState;
+use crate::errors::{CommandError, CommandResult, ErrorCode};
+use crate::perf;
+use crate::provider_quota::{ProviderQuotaState, ProviderQuotaStatus, QuotaHeaders, QUOTA_HEADERS};
+use crate::rate_limit::RateLimiter;
+use crate::session::{ExportFormat, SessionEntryKind, SessionLog, SessionTimeline};
+use crate::summarizer::{SessionSummary, Summarizer};
+use crate::usage::{UsageEvent, UsageStats, UsageStore};
+use crate::waveform::{self, WaveformMode};
+use crate::{integration, private_session, transcript_log};
+
+command_set![
+    usage_stats,
+    usage_reset,
+    usage_record_llm,
+    session_export,
+    session_clear,
+    session_timeline,
+    llm_clipboard_delta,
+    llm_clipboard_done,
+    type_text,
+    type_text_abort,
+    session_record_answer,
+    session_summary_complete,
+    transcript_log_open_folder,
+    debug_file_read,
+    audio_waveform,
+    artifacts_encrypt_existing,
+    provider_rate_limit_acquire,
+    provider_quota_report,
+    provider_quota_status,
+    private_session_set,
+    private_session_get,
+];
+
+#[perf::command]
+pub async fn usage_stats(
+    usage: State<'_, Arc<UsageStore>>,
+    range: Option<String>,
+) -> CommandResult<UsageStats> {
+    Ok(usage.stats(range.as_deref().unwrap_or("all")).await)
+}
+
+#[perf::command]
+pub async fn usage_reset(usage: State<'_, Arc<UsageStore>>) -> CommandResult<()> {
+    usage
+        .reset()
+        .await
+        .map_err(|error| CommandError::new(ErrorCode::UsageFailed, error))
+}
+
+/// LLM calls still run in the webview; it reports provider usage fields here.
+#[perf::command]
+pub async fn usage_record_llm(
+    usage: State<'_, Arc<UsageStore>>,
+    provider: String,
+    tokens_in: Option<u64>,
+    tokens_out: Option<u64>,
+    ok: bool,
+) -> CommandResult<()> {
+    usage.record(UsageEvent::Llm {
+        provider,
+        tokens_in: tokens_in.unwrap_or(0),
+        tokens_out: tokens_out.unwrap_or(0),
+        ok,
+    });
+    Ok(())
+}
+
+#[perf::command]
+pub async fn session_export(
+    app: AppHandle,
+    session_log: State<'_, Arc<SessionLog>>,
+    format: String,
+    path: Option<String>,
+) -> CommandResult<String> {
+    let format = ExportFormat::parse(&format)
+        .map_err(|error| CommandError::new(ErrorCode::SessionExportFailed, error))?;
+    session_log
+        .export(&app, format, path)
+        .await
+        .map(|path| path.to_string_lossy().to_string())
+        .map_err(|error| CommandError::new(ErrorCode::SessionExportFailed, error))
+}
+
+#[perf::command]
+pub async fn session_clear(session_log: State<'_, Arc<SessionLog>>) -> CommandResult<()> {
+    session_log.clear();
+    Ok(())
+}
+
+/// Ordered interactions of `session_id`, or of the current session when omitted.
+#[perf::command]
+pub async fn session_timeline(
+    session_log: State<'_, Arc<SessionLog>>,
+    session_id: Option<String>,
+) -> CommandResult<SessionTimeline> {
+    session_log.timeline(session_id.as_deref()).ok_or_else(|| {
+        CommandError::bare(
+            ErrorCode::SessionNotFound,
+            &[("id", session_id.as_deref().unwrap_or_default())],
+        )
+    })
+}
+
+/// Streamed answer text for `llm_stream_to_clipboard`; ignored while that is off.
+#[perf::command]
+pub async fn llm_clipboard_delta(
+    app: AppHandle,
+    config: State<'_, Arc<ConfigState>>,
+    clipboard: State<'_, Arc<ClipboardStream>>,
+    request_id: Option<String>,
+    delta: String,
+) -> CommandResult<()> {
+    if config.get().await.llm_stream_to_clipboard {
+        clipboard.push(&app, request_id, &delta);
+    }
+    Ok(())
+}
+
+#[perf::command]
+pub async fn llm_clipboard_done(
+    app: AppHandle,
+    config: State<'_, Arc<ConfigState>>,
+    clipboard: State<'_, Arc<ClipboardStream>>,
+    request_id: Option<String>,
+    full: String,
+) -> CommandResult<()> {
+    if config.get().await.llm_stream_to_clipboard {
+        clipboard.finish(&app, request_id, &full);
+    }
+    Ok(())
+}
+
+/// Types `text` into the focused app; requires `enable_auto_type`. Esc stops it.
+#[perf::command]
+pub async fn type_text(
+    app: AppHandle,
+    config: State<'_, Arc<ConfigState>>,
+    typer: State<'_, Arc<AutoTyper>>,
+    text: String,
+    wpm: Option<u32>,
+) -> CommandResult<auto_type::AutoTypeOutcome> {
+    let config = config.get().await;
+    typer
+        .type_text(&app, &config, &text, wpm)
+        .await
+        .map_err(|error| {
+            let code = match &error {
+                AutoTypeError::Disabled => ErrorCode::AutoTypeDisabled,
+                AutoTypeError::Busy => ErrorCode::AutoTypeBusy,
+                AutoTypeError::Unsupported(_) => ErrorCode::AutoTypeUnsupported,
+                AutoTypeError::Failed(_) => ErrorCode::AutoTypeFailed,
+            };
+            CommandError::new(code, error)
+        })
+}
+
+#[perf::command]
+pub async fn type_text_abort(typer: State<'_, Arc<AutoTyper>>) -> CommandResult<()> {
+    typer.abort();
+    Ok(())
+}
+
+/// Answers are generated in the webview; it reports each completed one here.
+#[perf::command]
+pub async fn session_record_answer(
+    app: AppHandle,
+    session_log: State<'_, Arc<SessionLog>>,
+    text: String,
+    interaction_id: Option<String>,
+) -> CommandResult<()> {
+    integration::publish_answer(&app, &text);
+    session_log.record(
+        SessionEntryKind::Answer,
+        None,
+        &text,
+        interaction_id.as_deref(),
+    );
+    Ok(())
+}
+
+/// The webview's answer to `session:summary-request`: the summary text, or
+/// the error the LLM call ended with.
+#[perf::command]
+pub async fn session_summary_complete(
+    app: AppHandle,
+    summarizer: State<'_, Arc<Summarizer>>,
+    request_id: String,
+    text: Option<String>,
+    error: Option<String>,
+) -> CommandResult<Option<SessionSummary>> {
+    Ok(summarizer.complete(&app, &request_id, text, error))
+}
+
+#[perf::command]
+pub async fn transcript_log_open_folder(
+    app: tauri::AppHandle,
+    state: State<'_, Arc<ConfigState>>,
+) -> CommandResult<()> {
+    use tauri_plugin_opener::OpenerExt;
+    let open_failed = |error: anyhow::Error| {
+        CommandError::with_params(ErrorCode::OpenFailed, &[("target", "transcripts")], error)
+    };
+    let dir = transcript_log::directory(&app, &state.get().await).map_err(open_failed)?;
+    tokio::fs::create_dir_all(&dir)
+        .await
+        .map_err(|error| open_failed(error.into()))?;
+    app.opener()
+        .open_path(dir.to_string_lossy(), None::<String>)
+        .map_err(|error| open_failed(error.into()))
+}
+
+/// Decrypted contents of a debug recording or transcript log, base64-encoded.
+#[perf::command]
+pub async fn debug_file_read(
+    app: tauri::AppHandle,
+    state: State<'_, Arc<ConfigState>>,
+    vault: State<'_, Arc<ArtifactVault>>,
+    path: String,
+) -> CommandResult<String> {
+    use base64::Engine as _;
+    let failed = |error| CommandError::new(ErrorCode::ArtifactsFailed, error);
+    let path = artifacts::resolve_artifact(&app, &state.get().await, &path).map_err(failed)?;
+    let data = vault.read(&path).await.map_err(failed)?;
+    Ok(base64::engine::general_purpose::STANDARD.encode(data))
+}
+
+/// A waveform thumbnail of a saved recording: `buckets` RMS values, or a
+/// min/max pair per bucket for `peaks`.
+#[perf::command]
+pub async fn audio_waveform(
+    app: tauri::AppHandle,
+    state: State<'_, Arc<ConfigState>>,
+    path_or_id: String,
+    buckets: Option<u32>,
+    mode: Option<WaveformMode>,
+) -> CommandResult<Vec<f32>> {
+    let config = state.get().await;
+    waveform::generate(
+        &app,
+        &config,
+        &path_or_id,
+        buckets.unwrap_or(waveform::DEFAULT_BUCKETS),
+        mode.unwrap_or_default(),
+    )
+    .await
+    .map_err(|error| {
+        CommandError::new(ErrorCode::WaveformFailed, &error)
+            .with_data(json!({ "kind": error.kind() }))
+    })
+}
+
+#[perf::command]
+pub async fn artifacts_encrypt_existing(
+    app: tauri::AppHandle,
+    state: State<'_, Arc<ConfigState>>,
+    vault: State<'_, Arc<ArtifactVault>>,
+) -> CommandResult<artifacts::EncryptSummary> {
+    vault
+        .encrypt_existing(&app, &state.get().await)
+        .await
+        .map_err(|error| CommandError::new(ErrorCode::ArtifactsFailed, error))
+}
+
+/// Waits out the shared request budget before the renderer calls a chat API.
+#[perf::command]
+pub async fn provider_rate_limit_acquire(
+    app: tauri::AppHandle,
+    state: State<'_, Arc<ConfigState>>,
+    limiter: State<'_, Arc<RateLimiter>>,
+    provider: String,
+) -> CommandResult<()> {
+    limiter
+        .acquire(&app, &state.get().await, &provider, "llm")
+        .await;
+    Ok(())
+}
+
+/// Keeps the quota headers of a chat response the renderer received.
+#[perf::command]
+pub async fn provider_quota_report(
+    quota: State<'_, Arc<ProviderQuotaState>>,
+    provider: String,
+    headers: HashMap<String, String>,
+) -> CommandResult<()> {
+    let headers: HashMap<String, String> = headers
+        .into_iter()
+        .map(|(name, value)| (name.to_ascii_lowercase(), value))
+        .filter(|(name, _)| QUOTA_HEADERS.contains(&name.as_str()))
+        .collect();
+    quota.record(
+        &provider,
+        QuotaHeaders::parse(|name| headers.get(name).map(String::as_str)),
+    );
+    Ok(())
+}
+
+#[perf::command]
+pub async fn provider_quota_status(
+    quota: State<'_, Arc<ProviderQuotaState>>,
+) -> CommandResult<Vec<ProviderQuotaStatus>> {
+    Ok(quota.status())
+}
+
+/// Turns the private session on or off and returns the new state.
+#[perf::command]
+pub async fn private_session_set(app: AppHandle, enabled: bool) -> CommandResult<bool> {
+    private_session::set(&app, enabled)
+        .await
+        .map_err(|error| CommandError::new(ErrorCode::ConfigFailed, error))
+}
+
+#[perf::command]
+pub async fn private_session_get() -> CommandResult<bool> {
+    Ok(private_session::is_active())
+}
@@ -0,0 +1,9 @@
+//! Transcription commands; the wrappers live in the transcription module.
+
+use crate::commands::command_set;
+use crate::transcription;
+
+command_set![
+    transcription::transcribe_audio,
+    transcription::transcribe_batch
+];
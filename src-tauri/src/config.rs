@@ -1,99 +1,613 @@
+use std::collections::BTreeMap;
 use std::env;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 use anyhow::{anyhow, Context, Result};
+use serde::Serialize;
 use serde_json::{Map, Value};
 use tauri::{AppHandle, Manager};
 use tokio::fs;
 use tokio::sync::RwLock;
 
+use crate::config_lock::{self, LockHeld, LockHolder};
 use crate::constants::{CONFIG_DIR_NAME, CONFIG_FILE_NAME};
-use crate::types::AppConfig;
+use crate::events::{self, emit_event};
+use crate::types::{AppConfig, LanguageProfile, TranscriptionPromptOverrides};
 
+const WRITE_PROBE_FILE_NAME: &str = ".write-probe";
+// A failed write is retried at 2, 4, 8 ... 60 s; after the last attempt the
+// state goes read-only as it would for an unwritable directory.
+const RETRY_INITIAL_DELAY: Duration = Duration::from_secs(2);
+const RETRY_MAX_DELAY: Duration = Duration::from_secs(60);
+const RETRY_ATTEMPTS: u32 = 8;
+
+/// `config_get` payload: the config plus whether changes reach disk.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigSnapshot {
+    #[serde(flatten)]
+    pub config: AppConfig,
+    pub persistence_available: bool,
+    /// False while the last change is only in memory and a background write
+    /// retry is pending.
+    pub persisted: bool,
+    /// Why the last write failed, while `persisted` is false.
+    pub warning: Option<String>,
+    /// Bumped by every change; pass it back to `config_update` to detect
+    /// changes made in between.
+    pub revision: u64,
+}
+
+/// Emitted as `config:updated` after every change, in revision order.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigUpdated {
+    #[serde(flatten)]
+    pub config: AppConfig,
+    /// Label of the window that made the change, or the native component.
+    pub origin: String,
+    pub revision: u64,
+}
+
+/// `config_update` was based on an older revision than the current one.
 #[derive(Debug)]
+pub struct RevisionConflict {
+    pub expected: u64,
+    pub current: u64,
+}
+
+impl std::fmt::Display for RevisionConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "config changed since revision {} (now {})",
+            self.expected, self.current
+        )
+    }
+}
+
+impl std::error::Error for RevisionConflict {}
+
+/// Emitted as `config:persisted` when a retried write reaches disk.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PersistedNotice {
+    pub path: String,
+    pub attempts: u32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PersistenceStatus {
+    pub persistence_available: bool,
+    pub path: String,
+    /// Where usage stats and the integration token go while the config dir is unusable.
+    pub fallback_dir: String,
+    pub error: Option<String>,
+    /// The running copy holding `config.lock`; `config_force_takeover` works
+    /// once it has exited.
+    pub locked_by: Option<LockHolder>,
+}
+
+/// The prompt fields as they were when a private session began. While held,
+/// they are what goes to disk in place of the in-memory values.
+#[derive(Clone)]
+struct HeldPrompts {
+    llm_prompt: String,
+    transcription_prompt: String,
+    transcription_prompt_overrides: TranscriptionPromptOverrides,
+    screen_processing_prompt: String,
+    summary_prompt: String,
+    prompt_variables: BTreeMap<String, String>,
+    language_profiles: BTreeMap<String, LanguageProfile>,
+}
+
+impl HeldPrompts {
+    fn of(config: &AppConfig) -> Self {
+        Self {
+            llm_prompt: config.llm_prompt.clone(),
+            transcription_prompt: config.transcription_prompt.clone(),
+            transcription_prompt_overrides: config.transcription_prompt_overrides.clone(),
+            screen_processing_prompt: config.screen_processing_prompt.clone(),
+            summary_prompt: config.summary_prompt.clone(),
+            prompt_variables: config.prompt_variables.clone(),
+            language_profiles: config.language_profiles.clone(),
+        }
+    }
+
+    fn apply(self, config: &mut AppConfig) {
+        config.llm_prompt = self.llm_prompt;
+        config.transcription_prompt = self.transcription_prompt;
+        config.transcription_prompt_overrides = self.transcription_prompt_overrides;
+        config.screen_processing_prompt = self.screen_processing_prompt;
+        config.summary_prompt = self.summary_prompt;
+        config.prompt_variables = self.prompt_variables;
+        config.language_profiles = self.language_profiles;
+    }
+}
+
 pub struct ConfigState {
+    app: AppHandle,
     inner: RwLock<AppConfig>,
-    path: PathBuf,
+    // `None` when the platform config dir could not be resolved at all.
+    path: Option<PathBuf>,
+    // `config.lock` beside `path`.
+    lock_path: Option<PathBuf>,
+    // Another running copy that held the lock at startup; this one stays read-only.
+    locked_by: Mutex<Option<LockHolder>>,
+    fallback_dir: PathBuf,
+    // Why persistence is off; `None` while changes are written to `path`.
+    readonly: Mutex<Option<String>>,
+    // Error of the last failed write while its retry is pending.
+    pending: Mutex<Option<String>>,
+    retrying: AtomicBool,
+    // Only changed while `inner` is write-locked, so it always matches it.
+    revision: AtomicU64,
+    // Set for the length of a private session.
+    held_prompts: Mutex<Option<HeldPrompts>>,
 }
 
 impl ConfigState {
-    pub async fn initialize(app: &AppHandle) -> Result<Self> {
-        let mut base_dir = app
-            .path()
-            .app_config_dir()
-            .map_err(|error| anyhow!("Не удалось определить директорию конфигурации: {error}"))?;
-        base_dir.push(CONFIG_DIR_NAME);
-        if !base_dir.exists() {
-            fs::create_dir_all(&base_dir).await?;
-        }
-        let mut path = base_dir.clone();
-        path.push(CONFIG_FILE_NAME);
-
-        let config = if Path::new(&path).exists() {
-            let bytes = fs::read(&path).await?;
-            let contents = String::from_utf8(bytes)
-                .map_err(|error| anyhow!("Invalid UTF-8 in config: {error}"))?;
-            let mut config: AppConfig = serde_json::from_str(&contents).unwrap_or_default();
-            hydrate_from_env(&mut config);
-            config.normalize();
-            config
-        } else {
-            let mut config = AppConfig::default();
-            hydrate_from_env(&mut config);
-            config.normalize();
-            let serialized = serde_json::to_string_pretty(&config)?;
-            fs::write(&path, serialized).await?;
-            config
+    /// Never fails: when the config dir cannot be created or written, the config
+    /// lives in memory for this run and `persistence_available` turns false.
+    pub async fn initialize(app: &AppHandle) -> Self {
+        let fallback_dir = env::temp_dir().join(CONFIG_DIR_NAME);
+        let path = match app.path().app_config_dir() {
+            Ok(dir) => Some(dir.join(CONFIG_DIR_NAME).join(CONFIG_FILE_NAME)),
+            Err(error) => {
+                log::warn!(target: "config", "Config directory is unavailable: {error}");
+                None
+            }
         };
 
-        Ok(Self {
+        let lock_path = path.as_deref().map(config_lock::lock_path);
+        let locked_by = lock_path.as_deref().and_then(take_lock);
+
+        let (config, readonly) = match (&path, &locked_by) {
+            (Some(path), Some(holder)) => {
+                log::warn!(
+                    target: "config",
+                    "Config is locked by another running copy ({holder}), continuing read-only"
+                );
+                let config = read_config(path).await.unwrap_or_else(|_| default_config());
+                (config, Some(LockHeld(holder.clone()).to_string()))
+            }
+            (Some(path), None) => match load_or_create(path).await {
+                Ok(config) => (config, None),
+                Err(error) => {
+                    log::warn!(
+                        target: "config",
+                        "Config at {} is not writable, continuing read-only: {error:#}",
+                        path.display()
+                    );
+                    let config = read_config(path).await.unwrap_or_else(|_| default_config());
+                    (config, Some(format!("{error:#}")))
+                }
+            },
+            (None, _) => (
+                default_config(),
+                Some("config directory could not be resolved".to_string()),
+            ),
+        };
+        if readonly.is_some() {
+            if let Err(error) = fs::create_dir_all(&fallback_dir).await {
+                log::warn!(
+                    target: "config",
+                    "Fallback directory {} is unavailable: {error}",
+                    fallback_dir.display()
+                );
+            }
+        }
+
+        Self {
+            app: app.clone(),
             inner: RwLock::new(config),
             path,
-        })
+            lock_path,
+            locked_by: Mutex::new(locked_by),
+            fallback_dir,
+            readonly: Mutex::new(readonly),
+            pending: Mutex::new(None),
+            retrying: AtomicBool::new(false),
+            revision: AtomicU64::new(0),
+            held_prompts: Mutex::new(None),
+        }
     }
 
     pub async fn get(&self) -> AppConfig {
         self.inner.read().await.clone()
     }
 
+    pub async fn snapshot(&self) -> ConfigSnapshot {
+        let guard = self.inner.read().await;
+        self.snapshot_of(guard.clone())
+    }
+
+    /// Call with `inner` locked so the revision belongs to `config`.
+    fn snapshot_of(&self, config: AppConfig) -> ConfigSnapshot {
+        let warning = self.pending.lock().unwrap().clone();
+        ConfigSnapshot {
+            config,
+            persistence_available: self.persistence_available(),
+            persisted: warning.is_none() && self.persistence_available(),
+            warning,
+            revision: self.revision.load(Ordering::SeqCst),
+        }
+    }
+
+    pub fn persistence_available(&self) -> bool {
+        self.readonly.lock().unwrap().is_none()
+    }
+
+    pub fn persistence_status(&self) -> PersistenceStatus {
+        let error = self.readonly.lock().unwrap().clone();
+        PersistenceStatus {
+            persistence_available: error.is_none(),
+            path: self.file_path().to_string_lossy().to_string(),
+            fallback_dir: self.fallback_dir.to_string_lossy().to_string(),
+            error,
+            locked_by: self.locked_by.lock().unwrap().clone(),
+        }
+    }
+
     pub async fn path(&self) -> PathBuf {
-        self.path.clone()
+        self.file_path()
     }
 
+    /// The config dir, or the temp fallback while persistence is unavailable.
     pub async fn directory(&self) -> PathBuf {
+        match &self.path {
+            Some(path) if self.persistence_available() => path
+                .parent()
+                .map(|dir| dir.to_path_buf())
+                .unwrap_or_else(|| path.clone()),
+            _ => self.fallback_dir.clone(),
+        }
+    }
+
+    /// Writes the in-memory config to the config dir again. On success the state
+    /// leaves read-only mode and `config:readonly` reports it. While another copy
+    /// held the lock this is `force_takeover`.
+    pub async fn retry_persistence(&self) -> Result<PersistenceStatus> {
+        if self.locked_by.lock().unwrap().is_some() {
+            return self.force_takeover().await;
+        }
+        let path = self
+            .path
+            .clone()
+            .ok_or_else(|| anyhow!("config directory could not be resolved"))?;
+        let config = self.on_disk(&*self.inner.read().await);
+        write_config(&path, &config).await?;
+        self.pending.lock().unwrap().take();
+        let was_readonly = self.readonly.lock().unwrap().take().is_some();
+        if was_readonly {
+            log::info!(target: "config", "Config persistence restored at {}", path.display());
+            self.emit_status();
+        }
+        Ok(self.persistence_status())
+    }
+
+    /// Takes `config.lock` over once the copy holding it has exited, reloads
+    /// what that copy left in `config.json` and leaves read-only mode. Fails
+    /// with `LockHeld` while the copy still runs.
+    pub async fn force_takeover(&self) -> Result<PersistenceStatus> {
+        let (Some(path), Some(lock)) = (self.path.as_ref(), self.lock_path.as_ref()) else {
+            return Err(anyhow!("config directory could not be resolved"));
+        };
+        let mut guard = self.inner.write().await;
+        config_lock::acquire(lock)?;
+        let Some(previous) = self.locked_by.lock().unwrap().take() else {
+            return Ok(self.persistence_status());
+        };
+        log::info!(target: "config", "Took over config lock from exited copy ({previous})");
+        // The other copy may have saved changes after this one read the file.
+        match read_config(path).await {
+            Ok(config) => {
+                *guard = config;
+                self.commit(&guard, "takeover");
+            }
+            Err(error) => {
+                log::warn!(target: "config", "Config not reloaded after takeover: {error:#}");
+            }
+        }
+        self.readonly.lock().unwrap().take();
+        drop(guard);
+        self.emit_status();
+        Ok(self.persistence_status())
+    }
+
+    /// Removes `config.lock` if this process holds it. Called on exit.
+    pub fn release_lock(&self) {
+        if let Some(lock) = &self.lock_path {
+            config_lock::release(lock);
+        }
+    }
+
+    /// Emits `config:readonly` with the current persistence status.
+    pub fn emit_status(&self) {
+        let _ = emit_event(
+            &self.app,
+            events::CONFIG_READONLY,
+            self.persistence_status(),
+        );
+    }
+
+    fn file_path(&self) -> PathBuf {
         self.path
-            .parent()
-            .map(|dir| dir.to_path_buf())
-            .unwrap_or_else(|| self.path.clone())
+            .clone()
+            .unwrap_or_else(|| self.fallback_dir.join(CONFIG_FILE_NAME))
     }
 
-    pub async fn update(&self, partial: Value) -> Result<AppConfig> {
+    /// Merges `partial` into the config and emits `config:updated` with `origin`.
+    pub async fn update(&self, partial: Value, origin: &str) -> Result<ConfigSnapshot> {
+        self.update_checked(partial, origin, None).await
+    }
+
+    /// `update`, failing with `RevisionConflict` unless the config is still at
+    /// `expected_revision`.
+    pub async fn update_checked(
+        &self,
+        partial: Value,
+        origin: &str,
+        expected_revision: Option<u64>,
+    ) -> Result<ConfigSnapshot> {
+        update_locked(
+            &self.inner,
+            &self.revision,
+            partial,
+            expected_revision,
+            async |next: &AppConfig| self.persist(next).await,
+            |config, revision| self.announce(config, revision, origin),
+        )
+        .await
+    }
+
+    pub async fn reset(&self, origin: &str) -> Result<ConfigSnapshot> {
         let mut guard = self.inner.write().await;
-        let mut current = serde_json::to_value(&*guard)?;
-        merge_values(&mut current, partial);
-        let mut next: AppConfig = serde_json::from_value(current)?;
-        hydrate_from_env(&mut next);
-        next.normalize();
+        let config = default_config();
+        self.persist(&config).await?;
+        *guard = config;
+        Ok(self.commit(&guard, origin))
+    }
+
+    /// Keeps prompt edits in memory from now on: writes carry the prompts as
+    /// they are now until `release_prompts`.
+    pub async fn hold_prompts(&self) {
+        let config = self.inner.read().await;
+        self.held_prompts
+            .lock()
+            .unwrap()
+            .get_or_insert_with(|| HeldPrompts::of(&config));
+    }
+
+    /// Puts the held prompts back in memory, dropping the edits made since
+    /// `hold_prompts`, so no later write can carry them to disk.
+    pub async fn release_prompts(&self) -> Result<()> {
+        let mut guard = self.inner.write().await;
+        let Some(held) = self.held_prompts.lock().unwrap().take() else {
+            return Ok(());
+        };
+        let mut next = guard.clone();
+        held.apply(&mut next);
         self.persist(&next).await?;
-        *guard = next.clone();
-        Ok(next)
+        *guard = next;
+        self.commit(&guard, "private-session");
+        Ok(())
     }
 
-    pub async fn reset(&self) -> Result<AppConfig> {
-        let mut config = AppConfig::default();
-        hydrate_from_env(&mut config);
-        config.normalize();
-        self.persist(&config).await?;
-        *self.inner.write().await = config.clone();
-        Ok(config)
+    /// `config` as it may be written: with the held prompts during a private session.
+    fn on_disk(&self, config: &AppConfig) -> AppConfig {
+        let mut config = config.clone();
+        if let Some(held) = self.held_prompts.lock().unwrap().clone() {
+            held.apply(&mut config);
+        }
+        config
+    }
+
+    /// Bumps the revision for the change now in `config` and announces it.
+    fn commit(&self, config: &AppConfig, origin: &str) -> ConfigSnapshot {
+        let revision = self.revision.fetch_add(1, Ordering::SeqCst) + 1;
+        self.announce(config, revision, origin)
+    }
+
+    /// Emits `config:updated` for `config` at `revision`. Runs under the write
+    /// lock, so events go out in revision order.
+    fn announce(&self, config: &AppConfig, revision: u64, origin: &str) -> ConfigSnapshot {
+        let _ = emit_event(
+            &self.app,
+            events::CONFIG_UPDATED,
+            ConfigUpdated {
+                config: config.clone(),
+                origin: origin.to_string(),
+                revision,
+            },
+        );
+        self.snapshot_of(config.clone())
     }
 
+    /// Writes `state` to disk unless read-only. A failed write does not fail the
+    /// change: it stays in memory and is retried in the background, since the
+    /// usual causes (a OneDrive lock, an antivirus scan) pass.
     async fn persist(&self, state: &AppConfig) -> Result<()> {
-        let serialized = serde_json::to_string_pretty(state).context("serialize config")?;
-        fs::write(&self.path, serialized).await.context("write config")
+        let Some(path) = self.path.as_ref().filter(|_| self.persistence_available()) else {
+            return Ok(());
+        };
+        match write_config(path, &self.on_disk(state)).await {
+            Ok(()) => {
+                if self.pending.lock().unwrap().take().is_some() {
+                    self.emit_persisted(path, 1);
+                }
+            }
+            Err(error) => {
+                log::warn!(
+                    target: "config",
+                    "Config write failed, retrying in the background: {error:#}"
+                );
+                *self.pending.lock().unwrap() = Some(format!("{error:#}"));
+                self.schedule_retry();
+            }
+        }
+        Ok(())
+    }
+
+    fn schedule_retry(&self) {
+        if self.retrying.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        let app = self.app.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Some(state) = app.try_state::<Arc<ConfigState>>() {
+                state.retry_pending_write().await;
+            }
+        });
+    }
+
+    async fn retry_pending_write(&self) {
+        let Some(path) = self.path.clone() else {
+            return;
+        };
+        let mut delay = RETRY_INITIAL_DELAY;
+        for attempt in 1..=RETRY_ATTEMPTS {
+            tokio::time::sleep(delay).await;
+            // Held so an update cannot slip in between the write and the bookkeeping.
+            let config = self.inner.read().await;
+            if self.pending.lock().unwrap().is_none() {
+                // A later update wrote the file.
+                break;
+            }
+            match write_config(&path, &self.on_disk(&config)).await {
+                Ok(()) => {
+                    self.pending.lock().unwrap().take();
+                    log::info!(
+                        target: "config",
+                        "Config written after {attempt} retries"
+                    );
+                    self.emit_persisted(&path, attempt + 1);
+                    break;
+                }
+                Err(error) => {
+                    log::warn!(
+                        target: "config",
+                        "Config write retry {attempt}/{RETRY_ATTEMPTS} failed: {error:#}"
+                    );
+                    *self.pending.lock().unwrap() = Some(format!("{error:#}"));
+                    delay = (delay * 2).min(RETRY_MAX_DELAY);
+                }
+            }
+        }
+        let gave_up = self.pending.lock().unwrap().take();
+        if let Some(error) = gave_up {
+            log::warn!(target: "config", "Config writes keep failing, switching to read-only");
+            *self.readonly.lock().unwrap() = Some(error);
+            if let Err(error) = fs::create_dir_all(&self.fallback_dir).await {
+                log::warn!(target: "config", "Fallback directory is unavailable: {error}");
+            }
+            self.emit_status();
+        }
+        self.retrying.store(false, Ordering::SeqCst);
+    }
+
+    /// Writes a change still waiting for its retry, giving up after `timeout`.
+    /// Called on exit so a pending change is not lost.
+    pub async fn flush(&self, timeout: Duration) {
+        let Some(path) = self.path.as_ref() else {
+            return;
+        };
+        if self.pending.lock().unwrap().is_none() {
+            return;
+        }
+        let config = self.on_disk(&*self.inner.read().await);
+        match tokio::time::timeout(timeout, write_config(path, &config)).await {
+            Ok(Ok(())) => {
+                self.pending.lock().unwrap().take();
+                log::info!(target: "config", "Pending config change written on exit");
+            }
+            Ok(Err(error)) => {
+                log::warn!(target: "config", "Pending config change lost on exit: {error:#}");
+            }
+            Err(_) => {
+                log::warn!(target: "config", "Pending config change not written within {timeout:?}");
+            }
+        }
+    }
+
+    fn emit_persisted(&self, path: &Path, attempts: u32) {
+        let _ = emit_event(
+            &self.app,
+            events::CONFIG_PERSISTED,
+            PersistedNotice {
+                path: path.to_string_lossy().to_string(),
+                attempts,
+            },
+        );
+    }
+}
+
+/// `Some(holder)` while another running copy holds the lock. Other lock errors
+/// are only logged; an unusable directory is reported by `load_or_create`.
+fn take_lock(path: &Path) -> Option<LockHolder> {
+    match config_lock::acquire(path) {
+        Ok(()) => None,
+        Err(error) => match error.downcast::<LockHeld>() {
+            Ok(LockHeld(holder)) => Some(holder),
+            Err(error) => {
+                log::warn!(target: "config", "Config lock not taken: {error:#}");
+                None
+            }
+        },
     }
 }
 
+/// Reads the config at `path`, or creates it with defaults, then checks the
+/// directory is writable.
+async fn load_or_create(path: &Path) -> Result<AppConfig> {
+    let dir = path.parent().context("config path has no parent")?;
+    fs::create_dir_all(dir)
+        .await
+        .with_context(|| format!("create {}", dir.display()))?;
+    if path.exists() {
+        let config = read_config(path).await?;
+        let probe = dir.join(WRITE_PROBE_FILE_NAME);
+        fs::write(&probe, b"")
+            .await
+            .with_context(|| format!("write to {}", dir.display()))?;
+        let _ = fs::remove_file(&probe).await;
+        Ok(config)
+    } else {
+        let config = default_config();
+        write_config(path, &config).await?;
+        Ok(config)
+    }
+}
+
+async fn read_config(path: &Path) -> Result<AppConfig> {
+    let bytes = fs::read(path).await?;
+    let contents =
+        String::from_utf8(bytes).map_err(|error| anyhow!("Invalid UTF-8 in config: {error}"))?;
+    let mut config: AppConfig = serde_json::from_str(&contents).unwrap_or_default();
+    hydrate_from_env(&mut config);
+    config.normalize();
+    Ok(config)
+}
+
+async fn write_config(path: &Path, state: &AppConfig) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).await.context("create config dir")?;
+    }
+    let serialized = serde_json::to_string_pretty(state).context("serialize config")?;
+    fs::write(path, serialized).await.context("write config")
+}
+
+fn default_config() -> AppConfig {
+    let mut config = AppConfig::default();
+    hydrate_from_env(&mut config);
+    config.normalize();
+    config
+}
+
 fn hydrate_from_env(config: &mut AppConfig) {
     if config
         .openai_api_key
@@ -122,6 +636,50 @@ fn hydrate_from_env(config: &mut AppConfig) {
     }
 }
 
+/// The write behind `update_checked`, kept apart from the app handle. Under the
+/// write lock it refuses a stale `expected_revision`, merges `partial`, writes
+/// the result through `persist`, then stores it, bumps `revision` and passes
+/// both to `announce`.
+async fn update_locked<T>(
+    inner: &RwLock<AppConfig>,
+    revision: &AtomicU64,
+    partial: Value,
+    expected_revision: Option<u64>,
+    persist: impl AsyncFnOnce(&AppConfig) -> Result<()>,
+    announce: impl FnOnce(&AppConfig, u64) -> T,
+) -> Result<T> {
+    let mut guard = inner.write().await;
+    let current_revision = revision.load(Ordering::SeqCst);
+    let next = merged(&guard, partial, current_revision, expected_revision)?;
+    persist(&next).await?;
+    *guard = next;
+    let revision = revision.fetch_add(1, Ordering::SeqCst) + 1;
+    Ok(announce(&guard, revision))
+}
+
+/// `current` at `current_revision` with `partial` merged in, or a
+/// `RevisionConflict` when the caller based `partial` on another revision.
+fn merged(
+    current: &AppConfig,
+    partial: Value,
+    current_revision: u64,
+    expected_revision: Option<u64>,
+) -> Result<AppConfig> {
+    if let Some(expected) = expected_revision.filter(|&expected| expected != current_revision) {
+        return Err(RevisionConflict {
+            expected,
+            current: current_revision,
+        }
+        .into());
+    }
+    let mut value = serde_json::to_value(current)?;
+    merge_values(&mut value, partial);
+    let mut next: AppConfig = serde_json::from_value(value)?;
+    hydrate_from_env(&mut next);
+    next.normalize();
+    Ok(next)
+}
+
 fn merge_values(target: &mut Value, patch: Value) {
     match patch {
         Value::Object(patch_map) => {
@@ -139,3 +697,115 @@ fn merge_values(target: &mut Value, patch: Value) {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use tokio::sync::Barrier;
+
+    use super::*;
+
+    /// `ConfigState`'s config and revision, written the way `update_checked`
+    /// writes them.
+    #[derive(Default)]
+    struct Store {
+        inner: RwLock<AppConfig>,
+        revision: AtomicU64,
+    }
+
+    impl Store {
+        async fn update(&self, partial: Value, expected_revision: Option<u64>) -> Result<u64> {
+            update_locked(
+                &self.inner,
+                &self.revision,
+                partial,
+                expected_revision,
+                // Let the other writers run while this one is "on disk".
+                async |_: &AppConfig| {
+                    tokio::task::yield_now().await;
+                    Ok(())
+                },
+                |_, revision| revision,
+            )
+            .await
+        }
+    }
+
+    #[test]
+    fn stale_revisions_are_refused() {
+        let config = AppConfig::default();
+        let error = merged(&config, json!({ "llmPrompt": "x" }), 7, Some(6)).unwrap_err();
+        let conflict = error.downcast_ref::<RevisionConflict>().unwrap();
+        assert_eq!((conflict.expected, conflict.current), (6, 7));
+        assert_eq!(error.to_string(), "config changed since revision 6 (now 7)");
+
+        let next = merged(&config, json!({ "llmPrompt": "x" }), 7, Some(7)).unwrap();
+        assert_eq!(next.llm_prompt, "x");
+        let next = merged(&config, json!({ "llmPrompt": "y" }), 7, None).unwrap();
+        assert_eq!(next.llm_prompt, "y");
+    }
+
+    #[test]
+    fn nested_patches_keep_sibling_entries() {
+        let mut config = AppConfig::default();
+        config
+            .prompt_variables
+            .insert("role".into(), "a tutor".into());
+        let next = merged(
+            &config,
+            json!({ "promptVariables": { "tone": "dry" } }),
+            0,
+            None,
+        )
+        .unwrap();
+        assert_eq!(next.prompt_variables["role"], "a tutor");
+        assert_eq!(next.prompt_variables["tone"], "dry");
+    }
+
+    #[tokio::test]
+    async fn concurrent_writers_on_the_same_revision_conflict_and_retry() {
+        const WRITERS: usize = 8;
+        let store = Arc::new(Store::default());
+        let barrier = Arc::new(Barrier::new(WRITERS));
+        let writers: Vec<_> = (0..WRITERS)
+            .map(|writer| {
+                let store = store.clone();
+                let barrier = barrier.clone();
+                tokio::spawn(async move {
+                    let mut conflicts = 0;
+                    let mut seen = store.revision.load(Ordering::SeqCst);
+                    // Everyone reads revision 0 before anyone writes.
+                    barrier.wait().await;
+                    loop {
+                        let patch =
+                            json!({ "promptVariables": { format!("writer{writer}"): "done" } });
+                        match store.update(patch, Some(seen)).await {
+                            Ok(revision) => return (revision, conflicts),
+                            Err(error) => {
+                                let conflict = error.downcast_ref::<RevisionConflict>().unwrap();
+                                seen = conflict.current;
+                                conflicts += 1;
+                            }
+                        }
+                    }
+                })
+            })
+            .collect();
+
+        let mut revisions = Vec::new();
+        let mut conflicts = 0;
+        for writer in writers {
+            let (revision, refused) = writer.await.unwrap();
+            revisions.push(revision);
+            conflicts += refused;
+        }
+        revisions.sort_unstable();
+        // Every write landed exactly once, each on its own revision.
+        assert_eq!(revisions, (1..=WRITERS as u64).collect::<Vec<_>>());
+        assert!(conflicts >= WRITERS - 1);
+        let config = store.inner.read().await;
+        for writer in 0..WRITERS {
+            assert_eq!(config.prompt_variables[&format!("writer{writer}")], "done");
+        }
+    }
+}
@@ -0,0 +1,338 @@
+//! `config.lock` next to `config.json` names the process that writes the
+//! config. Single-instance does not cover a portable copy running next to the
+//! installed one; without the lock both would write the same file. A lock
+//! whose process is gone was left behind by a crash and is taken over.
+
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::{self, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::constants::CONFIG_LOCK_FILE_NAME;
+
+// An unreadable lock is read again this often before it counts as stale, in
+// case its holder is still writing it on a filesystem without hard links.
+const READ_ATTEMPTS: u32 = 10;
+const READ_RETRY: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LockHolder {
+    pub pid: u32,
+    pub started_at: DateTime<Utc>,
+    /// Executable of the holder, to tell a portable copy from the installed one.
+    pub exe: Option<String>,
+}
+
+impl LockHolder {
+    fn current() -> Self {
+        Self {
+            pid: std::process::id(),
+            started_at: Utc::now(),
+            exe: std::env::current_exe()
+                .ok()
+                .map(|path| path.to_string_lossy().to_string()),
+        }
+    }
+
+    fn is_current(&self) -> bool {
+        self.pid == std::process::id()
+    }
+
+    pub fn is_alive(&self) -> bool {
+        self.is_current() || process_alive(self.pid, self.started_at)
+    }
+}
+
+impl fmt::Display for LockHolder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "PID {}, running since {}",
+            self.pid,
+            self.started_at.to_rfc3339()
+        )?;
+        if let Some(exe) = &self.exe {
+            write!(f, ", {exe}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Returned while another running copy holds the lock.
+#[derive(Debug)]
+pub struct LockHeld(pub LockHolder);
+
+impl fmt::Display for LockHeld {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "config is in use by another xexamai ({})", self.0)
+    }
+}
+
+impl std::error::Error for LockHeld {}
+
+pub fn lock_path(config_path: &Path) -> PathBuf {
+    config_path.with_file_name(CONFIG_LOCK_FILE_NAME)
+}
+
+/// Takes the lock unless a running process holds it; `Err(LockHeld)` names
+/// that process. A lock that stays unreadable or whose process has exited is
+/// replaced.
+pub fn acquire(path: &Path) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).with_context(|| format!("create {}", dir.display()))?;
+    }
+    let holder = serde_json::to_vec(&LockHolder::current())?;
+    // Two rounds: a stale lock is removed in the first and retaken in the second.
+    for _ in 0..2 {
+        match place(path, &holder) {
+            Ok(()) => return Ok(()),
+            Err(error) if error.kind() == ErrorKind::AlreadyExists => {
+                match read_settled(path) {
+                    Some(holder) if holder.is_current() => return Ok(()),
+                    Some(holder) if holder.is_alive() => return Err(LockHeld(holder).into()),
+                    Some(holder) => log::warn!(
+                        target: "config",
+                        "Removing stale config lock of exited process ({holder})"
+                    ),
+                    None => log::warn!(target: "config", "Removing unreadable config lock"),
+                }
+                match fs::remove_file(path) {
+                    Ok(()) => {}
+                    Err(error) if error.kind() == ErrorKind::NotFound => {}
+                    Err(error) => {
+                        return Err(error).with_context(|| format!("remove {}", path.display()))
+                    }
+                }
+            }
+            Err(error) => return Err(error).with_context(|| format!("create {}", path.display())),
+        }
+    }
+    Err(anyhow!(
+        "config lock at {} keeps reappearing",
+        path.display()
+    ))
+}
+
+/// Creates the lock with `holder` in it, failing with `AlreadyExists` when
+/// there is one. The holder goes to a file of its own that is then hard-linked
+/// into place, so no other copy ever sees the lock without its holder.
+fn place(path: &Path, holder: &[u8]) -> io::Result<()> {
+    let mut staged = path.as_os_str().to_owned();
+    staged.push(format!(".{}", std::process::id()));
+    let staged = PathBuf::from(staged);
+    fs::write(&staged, holder)?;
+    let linked = fs::hard_link(&staged, path);
+    let _ = fs::remove_file(&staged);
+    match linked {
+        Err(error) if error.kind() != ErrorKind::AlreadyExists => {
+            // FAT and some network drives have no hard links. Another copy may
+            // then catch the lock before it is written, which `read_settled`
+            // waits out.
+            log::debug!(target: "config", "Config lock not hard-linked: {error}");
+            let mut file = OpenOptions::new().write(true).create_new(true).open(path)?;
+            file.write_all(holder)
+        }
+        linked => linked,
+    }
+}
+
+/// The holder in the lock at `path`, read again for a moment while the file is
+/// there but unreadable. `None` once it is gone or stays unreadable.
+fn read_settled(path: &Path) -> Option<LockHolder> {
+    for attempt in 0..READ_ATTEMPTS {
+        if let Some(holder) = read_holder(path) {
+            return Some(holder);
+        }
+        if attempt + 1 == READ_ATTEMPTS || !path.exists() {
+            break;
+        }
+        thread::sleep(READ_RETRY);
+    }
+    None
+}
+
+/// Removes the lock if this process holds it. Called on graceful exit.
+pub fn release(path: &Path) {
+    match read_holder(path) {
+        Some(holder) if holder.is_current() => {
+            if let Err(error) = fs::remove_file(path) {
+                log::warn!(target: "config", "Config lock not removed: {error}");
+            }
+        }
+        _ => {}
+    }
+}
+
+fn read_holder(path: &Path) -> Option<LockHolder> {
+    let bytes = fs::read(path).ok()?;
+    serde_json::from_slice(&bytes).ok()
+}
+
+/// Whether `pid` still runs as the process that wrote the lock at `started_at`.
+#[cfg(windows)]
+fn process_alive(pid: u32, started_at: DateTime<Utc>) -> bool {
+    use windows::Win32::Foundation::{CloseHandle, ERROR_ACCESS_DENIED, FILETIME, STILL_ACTIVE};
+    use windows::Win32::System::Threading::{
+        GetExitCodeProcess, GetProcessTimes, OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION,
+    };
+
+    let process = match unsafe { OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid) } {
+        Ok(process) => process,
+        // Elevated or another user's process: it exists, so treat it as the holder.
+        Err(error) => return error.code() == ERROR_ACCESS_DENIED.to_hresult(),
+    };
+    let mut exit_code = 0u32;
+    let running = unsafe { GetExitCodeProcess(process, &mut exit_code) }.is_ok()
+        && exit_code == STILL_ACTIVE.0 as u32;
+    let mut created = FILETIME::default();
+    let mut exited = FILETIME::default();
+    let mut kernel = FILETIME::default();
+    let mut user = FILETIME::default();
+    let times =
+        unsafe { GetProcessTimes(process, &mut created, &mut exited, &mut kernel, &mut user) };
+    let _ = unsafe { CloseHandle(process) };
+    // The holder wrote the lock after it started; a process created later only
+    // reuses the PID of one that has exited.
+    let reused = times.is_ok() && filetime_ms(created) > started_at.timestamp_millis();
+    running && !reused
+}
+
+#[cfg(windows)]
+fn filetime_ms(time: windows::Win32::Foundation::FILETIME) -> i64 {
+    // 100 ns ticks since 1601-01-01.
+    const UNIX_EPOCH_MS: i64 = 11_644_473_600_000;
+    let ticks = ((time.dwHighDateTime as u64) << 32) | time.dwLowDateTime as u64;
+    (ticks / 10_000) as i64 - UNIX_EPOCH_MS
+}
+
+#[cfg(unix)]
+fn process_alive(pid: u32, _started_at: DateTime<Utc>) -> bool {
+    // Signal 0 only checks the process exists; EPERM means it does, as another user.
+    let result = unsafe { libc::kill(pid as libc::pid_t, 0) };
+    result == 0 || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+#[cfg(not(any(windows, unix)))]
+fn process_alive(_pid: u32, _started_at: DateTime<Utc>) -> bool {
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A directory under the system temp dir, removed when dropped.
+    struct Scratch(PathBuf);
+
+    impl Scratch {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!(
+                "xexamai-{name}-{}-{}",
+                std::process::id(),
+                Utc::now().timestamp_nanos_opt().unwrap_or_default()
+            ));
+            fs::create_dir_all(&dir).unwrap();
+            Self(dir)
+        }
+
+        fn lock(&self) -> PathBuf {
+            lock_path(&self.0.join("config.json"))
+        }
+    }
+
+    impl Drop for Scratch {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn write_holder(path: &Path, pid: u32) {
+        let holder = LockHolder {
+            pid,
+            started_at: Utc::now(),
+            exe: Some("/opt/xexamai-portable/xexamai".into()),
+        };
+        fs::write(path, serde_json::to_vec(&holder).unwrap()).unwrap();
+    }
+
+    /// A process that surely runs and is not this one.
+    #[cfg(unix)]
+    fn live_pid() -> u32 {
+        std::os::unix::process::parent_id()
+    }
+
+    /// A PID above any the system hands out.
+    const DEAD_PID: u32 = 0x7fff_fff0;
+
+    #[test]
+    fn acquire_writes_this_process_and_release_removes_it() {
+        let scratch = Scratch::new("lock-own");
+        let path = scratch.lock();
+        acquire(&path).unwrap();
+        assert!(read_holder(&path).unwrap().is_current());
+        // Taking it again is a no-op for the holder.
+        acquire(&path).unwrap();
+        release(&path);
+        assert!(!path.exists());
+        let leftovers = fs::read_dir(&scratch.0).unwrap().count();
+        assert_eq!(leftovers, 0, "staged holder file left behind");
+    }
+
+    #[test]
+    fn stale_lock_of_an_exited_process_is_taken_over() {
+        let scratch = Scratch::new("lock-stale");
+        let path = scratch.lock();
+        write_holder(&path, DEAD_PID);
+        acquire(&path).unwrap();
+        assert!(read_holder(&path).unwrap().is_current());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn live_holder_keeps_the_lock() {
+        let scratch = Scratch::new("lock-live");
+        let path = scratch.lock();
+        write_holder(&path, live_pid());
+        let error = acquire(&path).unwrap_err();
+        let LockHeld(holder) = error.downcast::<LockHeld>().unwrap();
+        assert_eq!(holder.pid, live_pid());
+        assert_eq!(read_holder(&path).unwrap().pid, live_pid());
+        // Only the holder removes its lock.
+        release(&path);
+        assert!(path.exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn lock_still_being_written_is_not_taken_over() {
+        let scratch = Scratch::new("lock-writing");
+        let path = scratch.lock();
+        fs::write(&path, b"").unwrap();
+        let writer = {
+            let path = path.clone();
+            thread::spawn(move || {
+                thread::sleep(READ_RETRY * 2);
+                write_holder(&path, live_pid());
+            })
+        };
+        let error = acquire(&path).unwrap_err();
+        writer.join().unwrap();
+        assert_eq!(error.downcast::<LockHeld>().unwrap().0.pid, live_pid());
+    }
+
+    #[test]
+    fn lock_that_stays_unreadable_is_replaced() {
+        let scratch = Scratch::new("lock-garbage");
+        let path = scratch.lock();
+        fs::write(&path, b"{\"pid\":").unwrap();
+        acquire(&path).unwrap();
+        assert!(read_holder(&path).unwrap().is_current());
+    }
+}
@@ -1,35 +1,95 @@
 pub const CONFIG_DIR_NAME: &str = "xexamai";
 pub const CONFIG_FILE_NAME: &str = "config.json";
+pub const CONFIG_LOCK_FILE_NAME: &str = "config.lock";
 
 pub const DEFAULT_WINDOW_WIDTH: u32 = 420;
 pub const DEFAULT_WINDOW_HEIGHT: u32 = 780;
 pub const DEFAULT_WINDOW_MIN_WIDTH: u32 = 400;
 pub const DEFAULT_WINDOW_MIN_HEIGHT: u32 = 500;
 pub const DEFAULT_WINDOW_SCALE: f32 = 1.0;
+pub const MIN_WINDOW_SCALE: f32 = 0.5;
+pub const MAX_WINDOW_SCALE: f32 = 3.0;
 pub const DEFAULT_WINDOW_OPACITY: u32 = 100;
+// Quiet period after the last resize event before the size is saved.
+pub const WINDOW_RESIZE_SAVE_DEBOUNCE_MS: u64 = 500;
 
 pub const DEFAULT_OPENAI_MODEL: &str = "gpt-4.1-nano";
 pub const DEFAULT_OPENAI_TRANSCRIPTION_MODEL: &str = "gpt-4o-mini-transcribe";
 pub const DEFAULT_LOCAL_LLM_MODEL: &str = "gpt-oss:20b";
 pub const DEFAULT_LOCAL_WHISPER_MODEL: &str = "base";
 pub const DEFAULT_LOCAL_DEVICE: &str = "cpu";
+pub const DEFAULT_LOG_LEVEL: &str = "info";
+pub const DEFAULT_UI_LANGUAGE: &str = "en";
+pub const SUPPORTED_UI_LANGUAGES: &[&str] = &["en", "ru"];
 
 pub const DEFAULT_TRANSCRIPTION_MODE: &str = "api";
 pub const DEFAULT_LLM_HOST: &str = "api";
 pub const DEFAULT_AUDIO_INPUT_TYPE: &str = "microphone";
+pub const DEFAULT_PRE_ROLL_SOURCE: &str = "mic";
+pub const MAX_PRE_ROLL_SECONDS: u32 = 10;
+// Mic level settings, usually written by `audio_calibrate`.
+pub const DEFAULT_MIC_GAIN: f32 = 1.0;
+pub const MIN_MIC_GAIN: f32 = 0.25;
+pub const MAX_MIC_GAIN: f32 = 8.0;
+// The wake word check used to skip windows below an RMS of 0.01, which is -40 dBFS.
+pub const DEFAULT_VAD_THRESHOLD_DB: f32 = -40.0;
+pub const MIN_VAD_THRESHOLD_DB: f32 = -80.0;
+pub const MAX_VAD_THRESHOLD_DB: f32 = -10.0;
+pub const DEFAULT_AGC_TARGET_DB: f32 = -20.0;
+pub const MIN_AGC_TARGET_DB: f32 = -40.0;
+pub const MAX_AGC_TARGET_DB: f32 = -6.0;
+// How far system audio is pulled down in a mixed capture while the user speaks.
+pub const DEFAULT_DUCKING_DB: f32 = 12.0;
+pub const MIN_DUCKING_DB: f32 = 1.0;
+pub const MAX_DUCKING_DB: f32 = 40.0;
+pub const DEFAULT_INDICATOR_CORNER: &str = "topright";
+pub const DEFAULT_TRAY_ICON_THEME: &str = "auto";
+pub const DEFAULT_GOOGLE_TRANSCRIPTION_FORMAT: &str = "json";
+pub const DEFAULT_LANGUAGE_DETECTION: &str = "off";
+pub const DEFAULT_TRANSCRIPTION_SAMPLE_RATE: u32 = 16_000;
+pub const TRANSCRIPTION_SAMPLE_RATES: &[u32] = &[16_000, 24_000, 48_000];
+pub const MIN_STT_SPEEDUP_FACTOR: f32 = 1.0;
+pub const MAX_STT_SPEEDUP_FACTOR: f32 = 2.0;
+/// Above this, recognition starts to miss words noticeably.
+pub const STT_SPEEDUP_QUALITY_LIMIT: f32 = 1.6;
+// OpenAI rejects audio uploads above 25 MB.
+pub const OPENAI_TRANSCRIPTION_MAX_BYTES: usize = 25 * 1024 * 1024;
+// Gemini caps inline requests at 20 MB; base64 inflates the audio by 4/3.
+pub const GOOGLE_TRANSCRIPTION_MAX_BYTES: usize = 15 * 1024 * 1024;
 pub const DEFAULT_STREAM_SEND_HOTKEY: &str = "~";
+pub const DEFAULT_STREAM_MODE: &str = "base";
 pub const DEFAULT_TOGGLE_INPUT_HOTKEY: &str = "g";
 
 pub const DEFAULT_DURATIONS: [u32; 6] = [5, 10, 15, 20, 30, 60];
+pub const MIN_DURATION_SECONDS: u32 = 1;
+pub const MAX_DURATION_SECONDS: u32 = 600;
+pub const MAX_DURATIONS: usize = 12;
+
+pub const DEFAULT_AUTO_TYPE_WPM: u32 = 300;
+pub const MIN_AUTO_TYPE_WPM: u32 = 10;
+pub const MAX_AUTO_TYPE_WPM: u32 = 2_000;
+pub const DEFAULT_MAX_PENDING_CLIPS: u32 = 1;
+pub const MAX_PENDING_CLIPS_LIMIT: u32 = 10;
 
 pub const DEFAULT_API_STT_TIMEOUT_MS: u32 = 150_000;
 pub const DEFAULT_API_LLM_TIMEOUT_MS: u32 = 150_000;
 pub const DEFAULT_SCREEN_PROCESSING_TIMEOUT_MS: u32 = 150_000;
+// Commands slower than this are logged and reported as `perf:slow-command`.
+pub const DEFAULT_SLOW_COMMAND_MS: u32 = 1_000;
 
 pub const DEFAULT_SCREEN_PROVIDER: &str = "openai";
 
 pub const DEFAULT_TRANSCRIPTION_PROMPT: &str = "This is a technical interview conducted in English. Please transcribe the speech in Russian, but preserve English programming and technical terms exactly as they are (e.g. Redis, Postgres, Celery, HTTP, API, and etc.).";
 pub const DEFAULT_LLM_PROMPT: &str = "You are a seasoned technical interview coach for software engineers. Provide detailed, precise answers with technical terminology, example code";
+pub const DEFAULT_SUMMARY_PROMPT: &str = "You are following a technical interview. Summarize the transcript excerpt below in a few short bullet points: the questions asked, the answers given and anything left open. Keep technical terms as they are.";
+pub const MAX_AUTO_SUMMARIZE_MINUTES: u32 = 240;
+// Bounds for `localLlmOptions`; values outside them are dropped, not clamped.
+pub const MAX_LLM_TEMPERATURE: f32 = 2.0;
+pub const MIN_LLM_CONTEXT: u32 = 256;
+pub const MAX_LLM_CONTEXT: u32 = 262_144;
+pub const MAX_LLM_STOP_SEQUENCES: usize = 4;
+// Longer prompts are cut when the config is normalized; every provider takes this much.
+pub const MAX_PROMPT_CHARS: usize = 20_000;
 pub const DEFAULT_SCREEN_PROMPT: &str = "You are assisting with a technical interview. Analyze the screenshot and extract key information that could help answer questions about the candidate's environment, tools, or work. Focus on actionable insights.";
 
 pub const BACKEND_DOMAIN_COM: &str = "xlartas.com";
@@ -40,14 +100,30 @@ pub const OAUTH_APP_NAME: &str = "xexamai";
 pub const OAUTH_SCHEME: &str = "xexamai";
 pub const UPDATE_MANIFEST_URL: &str =
     "https://s3.twcstorage.ru/324718a4-2cc5dd7a-917b-4e82-87c5-b9d5f8de16ba/xexamai/latest.json";
+// How long exit waits for a config write that is still being retried.
+pub const CONFIG_EXIT_FLUSH_TIMEOUT_MS: u64 = 2_000;
 pub const UPDATE_INITIAL_CHECK_DELAY_SECS: u64 = 15;
 pub const UPDATE_CHECK_INTERVAL_SECS: u64 = 60 * 60;
+pub const GITHUB_RELEASES_URL: &str =
+    "https://api.github.com/repos/Artasov/xexamai/releases/latest";
+pub const RELEASE_CHECK_CACHE_SECS: u64 = 12 * 60 * 60;
+// Minimum gap between two `api_key_test` calls for the same provider.
+pub const API_KEY_TEST_INTERVAL_SECS: u64 = 5;
 // Shared install location hint for the local speech server so multiple apps reuse one copy.
 pub const FAST_WHISPER_INSTALL_ENV_VAR: &str = "WINKY_LOCAL_SPEECH_DIR";
+// Enables the offline `mock` providers for tests and frontend development.
+pub const MOCK_PROVIDERS_ENV_VAR: &str = "XEXAMAI_MOCK_PROVIDERS";
 pub const FAST_WHISPER_INSTALL_HINT_FILE: &str = "local-speech-path.txt";
 pub const FAST_WHISPER_REPO_URL: &str = "https://github.com/Artasov/fast-fast-whisper.git";
 pub const FAST_WHISPER_REPO_NAME: &str = "fast-fast-whisper";
 pub const FAST_WHISPER_REPO_ARCHIVE_URL: &str =
     "https://github.com/Artasov/fast-fast-whisper/archive/refs/heads/main.zip";
+// Refuses a runaway download before it fills the disk.
+pub const FAST_WHISPER_ARCHIVE_MAX_BYTES: u64 = 4 * 1024 * 1024 * 1024;
+// Written into the repository folder on install: the branch and, when known, the commit.
+pub const FAST_WHISPER_VERSION_FILE: &str = ".xexamai-version";
+// Last known server status, beside the repository folder so it follows the install.
+pub const FAST_WHISPER_STATUS_FILE: &str = "local-speech-status.json";
 pub const FAST_WHISPER_PORT: u16 = 8868;
 pub const FAST_WHISPER_HEALTH_ENDPOINT: &str = "http://127.0.0.1:8868/health";
+pub const DEFAULT_INTEGRATION_SERVER_PORT: u16 = 8870;
@@ -0,0 +1,189 @@
+use std::backtrace::Backtrace;
+use std::fs;
+use std::panic::{self, AssertUnwindSafe, PanicHookInfo};
+use std::path::PathBuf;
+use std::sync::{Arc, OnceLock};
+
+use chrono::Local;
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+use crate::app_log;
+use crate::audio::AudioManager;
+use crate::error_bus;
+use crate::errors::ErrorCode;
+use crate::events::{self, emit_event};
+use crate::versions;
+
+const CRASH_DIR_NAME: &str = "crash-reports";
+const MAX_CRASH_REPORTS: usize = 10;
+const REPORT_LOG_LINES: usize = 100;
+
+static APP_HANDLE: OnceLock<AppHandle> = OnceLock::new();
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CrashReportInfo {
+    pub file_name: String,
+    pub path: String,
+    pub size_bytes: u64,
+    pub created_at: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ThreadCrashedPayload<'a> {
+    component: &'a str,
+    message: &'a str,
+}
+
+/// Writes a report for every panic before the default hook prints it.
+pub(crate) fn install_panic_hook() {
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        match write_report(info) {
+            Ok(path) => log::error!(target: "crash", "Panic report written: {}", path.display()),
+            Err(error) => log::error!(target: "crash", "Failed to write panic report: {error}"),
+        }
+        default_hook(info);
+    }));
+}
+
+pub(crate) fn attach(app: &AppHandle) {
+    let _ = APP_HANDLE.set(app.clone());
+}
+
+/// Runs a long-lived thread body, turning a panic into an `app:thread-crashed` event
+/// and an errored manager status instead of a silently dead feature.
+pub(crate) fn guard<F: FnOnce()>(component: &'static str, body: F) {
+    let Err(payload) = panic::catch_unwind(AssertUnwindSafe(body)) else {
+        return;
+    };
+    let message = payload
+        .downcast_ref::<&str>()
+        .map(|text| text.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string());
+    log::error!(target: "crash", "Thread crashed: component={component} message={message}");
+
+    let Some(app) = APP_HANDLE.get() else {
+        return;
+    };
+    if matches!(component, "audio-capture" | "audio-encoder" | "wasapi-loopback") {
+        if let Some(audio) = app.try_state::<Arc<AudioManager>>() {
+            audio.mark_crashed(&message);
+        }
+    }
+    error_bus::publish(app, component, ErrorCode::Internal, &message, false);
+    let _ = emit_event(
+        app,
+        events::APP_THREAD_CRASHED,
+        ThreadCrashedPayload {
+            component,
+            message: &message,
+        },
+    );
+}
+
+pub(crate) fn list_reports() -> Result<Vec<CrashReportInfo>, String> {
+    let dir = reports_dir()?;
+    let Ok(entries) = fs::read_dir(&dir) else {
+        return Ok(Vec::new());
+    };
+    let mut reports: Vec<CrashReportInfo> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().extension().and_then(|ext| ext.to_str()) == Some("txt"))
+        .map(|entry| {
+            let metadata = entry.metadata().ok();
+            CrashReportInfo {
+                file_name: entry.file_name().to_string_lossy().to_string(),
+                path: entry.path().to_string_lossy().to_string(),
+                size_bytes: metadata.as_ref().map(|meta| meta.len()).unwrap_or_default(),
+                created_at: metadata
+                    .and_then(|meta| meta.modified().ok())
+                    .map(|time| chrono::DateTime::<Local>::from(time).timestamp_millis()),
+            }
+        })
+        .collect();
+    reports.sort_by(|a, b| b.file_name.cmp(&a.file_name));
+    Ok(reports)
+}
+
+fn reports_dir() -> Result<PathBuf, String> {
+    let log_dir = app_log::current_log_dir()?;
+    Ok(log_dir
+        .parent()
+        .map(|base| base.join(CRASH_DIR_NAME))
+        .unwrap_or_else(|| log_dir.join(CRASH_DIR_NAME)))
+}
+
+fn write_report(info: &PanicHookInfo<'_>) -> Result<PathBuf, String> {
+    let dir = reports_dir()?;
+    fs::create_dir_all(&dir)
+        .map_err(|error| format!("Failed to create crash report directory: {error}"))?;
+
+    let now = Local::now();
+    let thread = std::thread::current();
+    let thread_name = thread.name().unwrap_or("unnamed");
+    let location = info
+        .location()
+        .map(|location| format!("{}:{}", location.file(), location.line()))
+        .unwrap_or_else(|| "unknown".to_string());
+    let recent_log = app_log::recent_lines(REPORT_LOG_LINES)
+        .map(|lines| lines.join("\n"))
+        .unwrap_or_else(|error| format!("<log unavailable: {error}>"));
+
+    let report = format!(
+        "XexamAI crash report\n\
+         time: {}\n\
+         versions: {}\n\
+         os: {} {}\n\
+         thread: {}\n\
+         location: {}\n\
+         message: {}\n\n\
+         backtrace:\n{}\n\n\
+         recent log:\n{}\n",
+        now.to_rfc3339(),
+        versions::summary(),
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+        thread_name,
+        location,
+        panic_message(info),
+        Backtrace::force_capture(),
+        recent_log
+    );
+
+    let file_name = format!(
+        "crash-{}-{}.txt",
+        now.format("%Y%m%d-%H%M%S%.3f"),
+        sanitize(thread_name)
+    );
+    let path = dir.join(file_name);
+    fs::write(&path, report).map_err(|error| format!("Failed to write crash report: {error}"))?;
+    prune_reports();
+    Ok(path)
+}
+
+fn panic_message(info: &PanicHookInfo<'_>) -> String {
+    info.payload()
+        .downcast_ref::<&str>()
+        .map(|text| text.to_string())
+        .or_else(|| info.payload().downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string())
+}
+
+fn sanitize(name: &str) -> String {
+    name.chars()
+        .map(|ch| if ch.is_ascii_alphanumeric() { ch } else { '-' })
+        .collect()
+}
+
+fn prune_reports() {
+    let Ok(reports) = list_reports() else {
+        return;
+    };
+    for report in reports.into_iter().skip(MAX_CRASH_REPORTS) {
+        let _ = fs::remove_file(report.path);
+    }
+}
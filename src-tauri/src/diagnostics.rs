@@ -0,0 +1,160 @@
+//! One zip with everything a bug report needs, so users attach a single file
+//! instead of collecting logs from four folders. Secrets are stripped with
+//! `app_log::redact_value` before anything is written.
+
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{anyhow, Context, Result};
+use chrono::Local;
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+use zip::write::FileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use crate::audio::AudioManager;
+use crate::config::ConfigState;
+use crate::events::{self, emit_event};
+use crate::local_speech::FastWhisperManager;
+use crate::{app_log, crash, versions};
+
+const ROTATED_LOGS: usize = 3;
+const CRASH_REPORTS: usize = 5;
+const RECENT_LOG_LINES: usize = 200;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BundleReadyPayload {
+    path: String,
+    files: usize,
+    size_bytes: u64,
+}
+
+/// A file in the bundle: its name inside the zip and where its bytes come from.
+enum Entry {
+    Json(&'static str, serde_json::Value),
+    Text(&'static str, String),
+    Copy(String, PathBuf),
+}
+
+/// Writes the bundle into the downloads folder (app data when there is none)
+/// and emits `diagnostics:bundle-ready` with its path.
+pub async fn export_bundle(app: &AppHandle) -> Result<PathBuf> {
+    let entries = collect(app).await?;
+    let dir = match app.path().download_dir() {
+        Ok(dir) => dir,
+        Err(_) => app
+            .path()
+            .app_local_data_dir()
+            .map_err(|error| anyhow!("Failed to resolve app data directory: {error}"))?,
+    };
+    let target = dir.join(format!(
+        "xexamai-diagnostics-{}.zip",
+        Local::now().format("%Y%m%d-%H%M%S")
+    ));
+    let path = target.clone();
+    let files = tauri::async_runtime::spawn_blocking(move || write_zip(&path, entries))
+        .await
+        .map_err(|error| anyhow!("Diagnostics bundle task failed: {error}"))??;
+
+    let size_bytes = std::fs::metadata(&target)
+        .map(|meta| meta.len())
+        .unwrap_or_default();
+    log::info!(
+        target: "diagnostics",
+        "Diagnostics bundle written: path={} files={files} bytes={size_bytes}",
+        target.display()
+    );
+    let _ = emit_event(
+        app,
+        events::DIAGNOSTICS_BUNDLE_READY,
+        BundleReadyPayload {
+            path: target.to_string_lossy().to_string(),
+            files,
+            size_bytes,
+        },
+    );
+    Ok(target)
+}
+
+async fn collect(app: &AppHandle) -> Result<Vec<Entry>> {
+    let config = app.state::<Arc<ConfigState>>().get().await;
+    let mut entries = vec![
+        Entry::Json("config.json", json_of(&config)?),
+        Entry::Json("versions.json", json_of(&versions::collect(app).await)?),
+        Entry::Json(
+            "audio-diagnostics.json",
+            json_of(&app.state::<Arc<AudioManager>>().diagnostics())?,
+        ),
+    ];
+    if let Some(manager) = app.try_state::<Arc<FastWhisperManager>>() {
+        entries.push(Entry::Json(
+            "fast-whisper.json",
+            json_of(&manager.get_status().await)?,
+        ));
+    }
+    // Log lines are redacted as they are written, so these are copied as-is.
+    let recent = app_log::recent_lines(RECENT_LOG_LINES).unwrap_or_default();
+    entries.push(Entry::Text("recent.log", recent.join("\n")));
+    for path in app_log::recent_log_files(ROTATED_LOGS).unwrap_or_default() {
+        entries.push(Entry::Copy(format!("logs/{}", file_name(&path)), path));
+    }
+    for report in crash::list_reports()
+        .unwrap_or_default()
+        .into_iter()
+        .take(CRASH_REPORTS)
+    {
+        entries.push(Entry::Copy(
+            format!("crash-reports/{}", report.file_name),
+            PathBuf::from(report.path),
+        ));
+    }
+    Ok(entries)
+}
+
+/// Serializes `value` and strips secrets from it, so no dump skips redaction.
+fn json_of<T: Serialize>(value: &T) -> Result<serde_json::Value> {
+    let mut value = serde_json::to_value(value)?;
+    app_log::redact_value(&mut value);
+    Ok(value)
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// Returns how many files made it into the zip.
+fn write_zip(target: &Path, entries: Vec<Entry>) -> Result<usize> {
+    if let Some(parent) = target.parent() {
+        std::fs::create_dir_all(parent).context("create diagnostics directory")?;
+    }
+    // Write next to the target and rename so readers never see a half-written zip.
+    let partial = target.with_extension("zip.partial");
+    let mut zip = ZipWriter::new(File::create(&partial).context("create diagnostics bundle")?);
+    let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
+    let mut written = 0;
+    for entry in entries {
+        let (name, bytes) = match entry {
+            Entry::Json(name, value) => (name.to_string(), serde_json::to_vec_pretty(&value)?),
+            Entry::Text(name, text) => (name.to_string(), text.into_bytes()),
+            Entry::Copy(name, path) => match std::fs::read(&path) {
+                Ok(bytes) => (name, bytes),
+                // A log rotated or a report pruned while we were collecting.
+                Err(error) => {
+                    log::warn!(target: "diagnostics", "Skipped {}: {error}", path.display());
+                    continue;
+                }
+            },
+        };
+        zip.start_file(name, options)?;
+        zip.write_all(&bytes)?;
+        written += 1;
+    }
+    zip.finish()?;
+    std::fs::rename(&partial, target).context("finalize diagnostics bundle")?;
+    Ok(written)
+}
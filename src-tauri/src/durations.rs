@@ -0,0 +1,292 @@
+//! Validated edits of the recording durations and their hotkeys. Shortcuts are swapped
+//! before the config is written, so a rejected hotkey never reaches disk.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use serde_json::json;
+use tauri::{AppHandle, Manager};
+
+use crate::config::ConfigState;
+use crate::constants::{MAX_DURATIONS, MAX_DURATION_SECONDS, MIN_DURATION_SECONDS};
+use crate::errors::{CommandError, CommandResult, ErrorCode};
+use crate::hotkeys::{self, HotkeyManager};
+use crate::types::AppConfig;
+
+pub async fn set_durations(app: &AppHandle, list: Vec<u32>) -> CommandResult<AppConfig> {
+    let current = app.state::<Arc<ConfigState>>().get().await;
+    let next = with_durations(&current, list)?;
+    apply(app, &current, next).await
+}
+
+/// Assigns `key` to `duration`; an empty key clears the hotkey.
+pub async fn set_duration_hotkey(
+    app: &AppHandle,
+    duration: u32,
+    key: &str,
+) -> CommandResult<AppConfig> {
+    let current = app.state::<Arc<ConfigState>>().get().await;
+    let next = with_duration_hotkey(&current, duration, key)?;
+    apply(app, &current, next).await
+}
+
+/// `current` with `list` as its durations, or the error naming the offending entry.
+fn with_durations(current: &AppConfig, list: Vec<u32>) -> CommandResult<AppConfig> {
+    validate_durations(&list)?;
+    let mut next = current.clone();
+    next.durations = list;
+    // Sorts the list and re-keys hotkeys for removed and added durations.
+    next.normalize();
+    Ok(next)
+}
+
+fn with_duration_hotkey(current: &AppConfig, duration: u32, key: &str) -> CommandResult<AppConfig> {
+    if !current.durations.contains(&duration) {
+        let value = duration.to_string();
+        return Err(
+            CommandError::bare(ErrorCode::DurationNotFound, &[("duration", &value)])
+                .with_data(json!({ "duration": duration })),
+        );
+    }
+    let key = key.trim();
+    validate_hotkey(current, duration, key)?;
+    let mut next = current.clone();
+    next.duration_hotkeys.insert(duration, key.to_string());
+    next.normalize();
+    Ok(next)
+}
+
+fn validate_durations(list: &[u32]) -> CommandResult<()> {
+    if list.is_empty() {
+        return Err(CommandError::new(
+            ErrorCode::InvalidArgument,
+            "durations list is empty",
+        ));
+    }
+    if list.len() > MAX_DURATIONS {
+        let max = MAX_DURATIONS.to_string();
+        return Err(
+            CommandError::bare(ErrorCode::TooManyDurations, &[("max", &max)])
+                .with_data(json!({ "count": list.len(), "max": MAX_DURATIONS })),
+        );
+    }
+    let mut seen = HashSet::new();
+    for (index, &duration) in list.iter().enumerate() {
+        let value = duration.to_string();
+        if !(MIN_DURATION_SECONDS..=MAX_DURATION_SECONDS).contains(&duration) {
+            return Err(CommandError::bare(
+                ErrorCode::DurationOutOfRange,
+                &[
+                    ("duration", &value),
+                    ("min", &MIN_DURATION_SECONDS.to_string()),
+                    ("max", &MAX_DURATION_SECONDS.to_string()),
+                ],
+            )
+            .with_data(json!({ "index": index, "duration": duration })));
+        }
+        if !seen.insert(duration) {
+            return Err(
+                CommandError::bare(ErrorCode::DurationDuplicate, &[("duration", &value)])
+                    .with_data(json!({ "index": index, "duration": duration })),
+            );
+        }
+    }
+    Ok(())
+}
+
+fn validate_hotkey(config: &AppConfig, duration: u32, key: &str) -> CommandResult<()> {
+    let shortcut = match hotkeys::parse_hotkey(key) {
+        Ok(Some(shortcut)) => shortcut,
+        Ok(None) => return Ok(()),
+        Err(()) => {
+            return Err(
+                CommandError::bare(ErrorCode::HotkeyInvalid, &[("key", key)])
+                    .with_data(json!({ "duration": duration, "key": key })),
+            )
+        }
+    };
+    let conflict =
+        |data| CommandError::bare(ErrorCode::HotkeyConflict, &[("key", key)]).with_data(data);
+    if hotkeys::parse_hotkey(&config.toggle_input_hotkey) == Ok(Some(shortcut)) {
+        return Err(conflict(json!({
+            "duration": duration,
+            "key": key,
+            "conflictsWith": "toggleInput",
+        })));
+    }
+    let taken_by = config.durations.iter().find(|other| {
+        **other != duration
+            && config
+                .duration_hotkeys
+                .get(other)
+                .is_some_and(|existing| hotkeys::parse_hotkey(existing) == Ok(Some(shortcut)))
+    });
+    if let Some(other) = taken_by {
+        return Err(conflict(json!({
+            "duration": duration,
+            "key": key,
+            "conflictsWith": "duration",
+            "conflictingDuration": other,
+        })));
+    }
+    Ok(())
+}
+
+async fn apply(app: &AppHandle, current: &AppConfig, next: AppConfig) -> CommandResult<AppConfig> {
+    let hotkeys = app.state::<Arc<HotkeyManager>>();
+    hotkeys
+        .replace_duration_hotkeys(app, &next.durations, &next.duration_hotkeys)
+        .map_err(|accelerator| {
+            CommandError::bare(
+                ErrorCode::HotkeyRegistrationFailed,
+                &[("key", &accelerator)],
+            )
+            .with_data(json!({ "key": accelerator }))
+        })?;
+    let partial = json!({
+        "durations": next.durations,
+        "durationHotkeys": next.duration_hotkeys,
+    });
+    let updated = match app
+        .state::<Arc<ConfigState>>()
+        .update(partial, "durations")
+        .await
+    {
+        Ok(updated) => updated.config,
+        Err(error) => {
+            // Put the registered shortcuts back in line with what is still on disk.
+            hotkeys.apply_config(app, current);
+            return Err(CommandError::new(ErrorCode::ConfigFailed, error));
+        }
+    };
+    log::info!(
+        target: "hotkeys",
+        "Durations updated: durations={:?} hotkeys={:?}",
+        updated.durations,
+        updated.duration_hotkeys
+    );
+    Ok(updated)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+
+    fn hotkeys(config: &AppConfig) -> BTreeMap<u32, &str> {
+        config
+            .duration_hotkeys
+            .iter()
+            .map(|(duration, key)| (*duration, key.as_str()))
+            .collect()
+    }
+
+    fn error_data(result: CommandResult<AppConfig>, code: ErrorCode) -> serde_json::Value {
+        let error = result.unwrap_err();
+        assert_eq!(error.code, code);
+        error.data.unwrap_or_default()
+    }
+
+    #[test]
+    fn replacing_a_duration_hands_its_key_to_the_new_one() {
+        let config = AppConfig::default();
+        let next = with_durations(&config, vec![5, 10, 25, 20, 30, 60]).unwrap();
+        assert_eq!(next.durations, [5, 10, 20, 25, 30, 60]);
+        assert_eq!(
+            hotkeys(&next),
+            BTreeMap::from([
+                (5, "1"),
+                (10, "2"),
+                (20, "4"),
+                (25, "3"),
+                (30, "5"),
+                (60, "6")
+            ])
+        );
+    }
+
+    #[test]
+    fn removed_durations_lose_their_keys_and_added_ones_take_free_digits() {
+        let config = AppConfig::default();
+        let next = with_durations(&config, vec![5, 60]).unwrap();
+        assert_eq!(hotkeys(&next), BTreeMap::from([(5, "1"), (60, "6")]));
+
+        let next = with_durations(&next, vec![5, 60, 90, 120]).unwrap();
+        assert_eq!(
+            hotkeys(&next),
+            BTreeMap::from([(5, "1"), (60, "6"), (90, "2"), (120, "3")])
+        );
+    }
+
+    #[test]
+    fn durations_beyond_the_free_digits_get_no_key() {
+        let config = AppConfig::default();
+        let next = with_durations(&config, (1..=12).collect()).unwrap();
+        let keyed = next.duration_hotkeys.len();
+        assert_eq!(keyed, 9);
+        assert!(next.duration_hotkeys.values().all(|key| key != "g"));
+    }
+
+    #[test]
+    fn invalid_duration_lists_name_the_offending_entry() {
+        let config = AppConfig::default();
+        assert_eq!(
+            error_data(with_durations(&config, vec![]), ErrorCode::InvalidArgument),
+            serde_json::Value::Null
+        );
+        let data = error_data(
+            with_durations(&config, vec![5, 0, 10]),
+            ErrorCode::DurationOutOfRange,
+        );
+        assert_eq!(data, json!({ "index": 1, "duration": 0 }));
+        let data = error_data(
+            with_durations(&config, vec![5, 100_000]),
+            ErrorCode::DurationOutOfRange,
+        );
+        assert_eq!(data, json!({ "index": 1, "duration": 100_000 }));
+        let data = error_data(
+            with_durations(&config, vec![5, 10, 5]),
+            ErrorCode::DurationDuplicate,
+        );
+        assert_eq!(data, json!({ "index": 2, "duration": 5 }));
+        let data = error_data(
+            with_durations(&config, (1..=13).collect()),
+            ErrorCode::TooManyDurations,
+        );
+        assert_eq!(data, json!({ "count": 13, "max": MAX_DURATIONS }));
+    }
+
+    #[test]
+    fn hotkey_edits_are_validated_against_other_shortcuts() {
+        let config = AppConfig::default();
+        let data = error_data(
+            with_duration_hotkey(&config, 15, "2"),
+            ErrorCode::HotkeyConflict,
+        );
+        assert_eq!(data["conflictingDuration"], 10);
+        let data = error_data(
+            with_duration_hotkey(&config, 15, "G"),
+            ErrorCode::HotkeyConflict,
+        );
+        assert_eq!(data["conflictsWith"], "toggleInput");
+        error_data(
+            with_duration_hotkey(&config, 15, "NoSuchKey"),
+            ErrorCode::HotkeyInvalid,
+        );
+        error_data(
+            with_duration_hotkey(&config, 45, "9"),
+            ErrorCode::DurationNotFound,
+        );
+
+        let next = with_duration_hotkey(&config, 15, " 9 ").unwrap();
+        assert_eq!(next.duration_hotkeys[&15], "9");
+        let next = with_duration_hotkey(&next, 15, "").unwrap();
+        assert_eq!(next.duration_hotkeys[&15], "");
+        // A key that was cleared on purpose stays cleared when other durations change.
+        let next = with_durations(&next, vec![5, 15, 45]).unwrap();
+        assert_eq!(next.duration_hotkeys[&15], "");
+        // 45 takes the first key freed by the removed durations.
+        assert_eq!(next.duration_hotkeys[&45], "2");
+    }
+}
@@ -0,0 +1,82 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use chrono::Local;
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+use crate::errors::{CommandError, ErrorCode};
+use crate::events::{self, emit_event};
+
+const MAX_RECENT_ERRORS: usize = 50;
+
+/// A failure raised off the command path, where no caller is waiting for a result.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackgroundError {
+    pub component: &'static str,
+    pub code: ErrorCode,
+    pub message: String,
+    pub detail: Option<String>,
+    /// `false` when the feature stays broken until the user restarts it.
+    pub recoverable: bool,
+    pub timestamp: i64,
+}
+
+/// Ring buffer of recent background failures, mirrored to the UI as `app:error`.
+pub struct ErrorBus {
+    recent: Mutex<VecDeque<BackgroundError>>,
+}
+
+impl ErrorBus {
+    pub fn new() -> Self {
+        Self {
+            recent: Mutex::new(VecDeque::with_capacity(MAX_RECENT_ERRORS)),
+        }
+    }
+
+    pub fn recent(&self) -> Vec<BackgroundError> {
+        self.recent.lock().unwrap().iter().cloned().collect()
+    }
+
+    fn push(&self, error: BackgroundError) {
+        let mut recent = self.recent.lock().unwrap();
+        if recent.len() == MAX_RECENT_ERRORS {
+            recent.pop_front();
+        }
+        recent.push_back(error);
+    }
+}
+
+/// Logs the failure and, once the bus is managed, records and emits it. Safe to call
+/// from any thread, including audio callbacks.
+pub fn publish(
+    app: &AppHandle,
+    component: &'static str,
+    code: ErrorCode,
+    detail: impl fmt::Display,
+    recoverable: bool,
+) {
+    let CommandError {
+        message, detail, ..
+    } = CommandError::new(code, detail);
+    log::warn!(
+        target: "errors",
+        "Background error: component={component} code={code:?} recoverable={recoverable} detail={}",
+        detail.as_deref().unwrap_or("")
+    );
+    let Some(bus) = app.try_state::<Arc<ErrorBus>>() else {
+        return;
+    };
+    let error = BackgroundError {
+        component,
+        code,
+        message,
+        detail,
+        recoverable,
+        timestamp: Local::now().timestamp_millis(),
+    };
+    bus.push(error.clone());
+    let _ = emit_event(app, events::APP_ERROR, error);
+}
@@ -0,0 +1,544 @@
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::constants::SUPPORTED_UI_LANGUAGES;
+
+// Index into SUPPORTED_UI_LANGUAGES; updated from config like the log level.
+static UI_LANGUAGE: AtomicUsize = AtomicUsize::new(0);
+
+/// Stable identifiers the frontend can branch on; the text lives in `messages`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    Internal,
+    InvalidArgument,
+    ConfigFailed,
+    ConfigConflict,
+    ConfigLocked,
+    OpenFailed,
+    UrlNotAllowed,
+    PathNotFound,
+    PathNotAllowed,
+    LogsUnavailable,
+    AuthUnknownAccount,
+    AuthFailed,
+    AuthProviderUnsupported,
+    AuthProviderUnavailable,
+    NetworkFailed,
+    KeyTestTooSoon,
+    LocalSpeechFailed,
+    OllamaFailed,
+    AudioDeviceFailed,
+    AudioCaptureFailed,
+    MicrophoneAccessDenied,
+    TranscriptionFailed,
+    TranscriptionModeUnknown,
+    PayloadTooLarge,
+    UpdateCheckFailed,
+    UsageFailed,
+    SessionExportFailed,
+    SessionNotFound,
+    OnboardingFailed,
+    SoundsFailed,
+    TrayFailed,
+    DurationOutOfRange,
+    DurationDuplicate,
+    DurationNotFound,
+    TooManyDurations,
+    HotkeyInvalid,
+    HotkeyConflict,
+    HotkeyRegistrationFailed,
+    MonitorFeedback,
+    MonitorFailed,
+    AutoTypeDisabled,
+    AutoTypeBusy,
+    AutoTypeUnsupported,
+    AutoTypeFailed,
+    ArtifactsFailed,
+    WindowInfoUnsupported,
+    WindowInfoFailed,
+    DisplaysUnavailable,
+    PromptTemplateInvalid,
+    DiagnosticsExportFailed,
+    WaveformFailed,
+    AudioCalibrationFailed,
+    LocalSpeechIncompatible,
+}
+
+/// One entry per supported UI language. Adding a language means adding a field,
+/// which the compiler then demands for every code.
+struct Messages {
+    en: &'static str,
+    ru: &'static str,
+}
+
+fn messages(code: ErrorCode) -> Messages {
+    match code {
+        ErrorCode::Internal => Messages {
+            en: "Something went wrong.",
+            ru: "Что-то пошло не так.",
+        },
+        ErrorCode::InvalidArgument => Messages {
+            en: "Invalid value for {name}.",
+            ru: "Недопустимое значение {name}.",
+        },
+        ErrorCode::ConfigFailed => Messages {
+            en: "Settings could not be saved.",
+            ru: "Не удалось сохранить настройки.",
+        },
+        ErrorCode::ConfigConflict => Messages {
+            en: "Settings were changed elsewhere. Reload them and try again.",
+            ru: "Настройки были изменены в другом месте. Обновите их и повторите попытку.",
+        },
+        ErrorCode::ConfigLocked => Messages {
+            en: "Settings are in use by another running xexamai (PID {pid}). Close it first.",
+            ru: "Настройки используются другой запущенной копией xexamai (PID {pid}). Сначала закройте её.",
+        },
+        ErrorCode::OpenFailed => Messages {
+            en: "Could not open {target}.",
+            ru: "Не удалось открыть {target}.",
+        },
+        ErrorCode::UrlNotAllowed => Messages {
+            en: "Only http(s) links can be opened.",
+            ru: "Можно открывать только ссылки http(s).",
+        },
+        ErrorCode::PathNotFound => Messages {
+            en: "File \"{path}\" does not exist.",
+            ru: "Файл «{path}» не существует.",
+        },
+        ErrorCode::PathNotAllowed => Messages {
+            en: "\"{path}\" is outside the app folders and cannot be shown.",
+            ru: "«{path}» находится вне папок приложения и не может быть показан.",
+        },
+        ErrorCode::KeyTestTooSoon => Messages {
+            en: "Wait {seconds} s before testing the {provider} key again.",
+            ru: "Подождите {seconds} с перед повторной проверкой ключа {provider}.",
+        },
+        ErrorCode::LogsUnavailable => Messages {
+            en: "Logs are not available.",
+            ru: "Журналы недоступны.",
+        },
+        ErrorCode::AuthUnknownAccount => Messages {
+            en: "Account {account} is not signed in.",
+            ru: "Аккаунт {account} не авторизован.",
+        },
+        ErrorCode::AuthFailed => Messages {
+            en: "Sign-in failed.",
+            ru: "Не удалось войти.",
+        },
+        ErrorCode::AuthProviderUnsupported => Messages {
+            en: "Sign-in with {provider} is not supported.",
+            ru: "Вход через {provider} не поддерживается.",
+        },
+        ErrorCode::AuthProviderUnavailable => Messages {
+            en: "This sign-in method is not available in your region.",
+            ru: "Этот способ входа недоступен в вашем регионе.",
+        },
+        ErrorCode::NetworkFailed => Messages {
+            en: "The server request failed.",
+            ru: "Запрос к серверу не выполнен.",
+        },
+        ErrorCode::LocalSpeechFailed => Messages {
+            en: "The local speech server reported an error.",
+            ru: "Локальный сервер распознавания речи сообщил об ошибке.",
+        },
+        ErrorCode::OllamaFailed => Messages {
+            en: "Ollama reported an error.",
+            ru: "Ollama сообщила об ошибке.",
+        },
+        ErrorCode::AudioDeviceFailed => Messages {
+            en: "Audio devices could not be listed.",
+            ru: "Не удалось получить список аудиоустройств.",
+        },
+        ErrorCode::AudioCaptureFailed => Messages {
+            en: "Audio capture could not be changed.",
+            ru: "Не удалось изменить запись звука.",
+        },
+        ErrorCode::MicrophoneAccessDenied => Messages {
+            en: "Microphone access is turned off in the system privacy settings.",
+            ru: "Доступ к микрофону отключён в системных настройках конфиденциальности.",
+        },
+        ErrorCode::TranscriptionFailed => Messages {
+            en: "Transcription failed.",
+            ru: "Не удалось распознать речь.",
+        },
+        ErrorCode::TranscriptionModeUnknown => Messages {
+            en: "Unknown transcription mode: {mode}.",
+            ru: "Неизвестный режим распознавания: {mode}.",
+        },
+        ErrorCode::PayloadTooLarge => Messages {
+            en: "The recording is too large for {provider} ({size}, limit {limit}). Lower the transcription sample rate or record a shorter clip.",
+            ru: "Запись слишком велика для {provider} ({size}, лимит {limit}). Понизьте частоту дискретизации для распознавания или запишите фрагмент короче.",
+        },
+        ErrorCode::UpdateCheckFailed => Messages {
+            en: "Could not check for updates.",
+            ru: "Не удалось проверить обновления.",
+        },
+        ErrorCode::UsageFailed => Messages {
+            en: "Usage statistics could not be updated.",
+            ru: "Не удалось обновить статистику использования.",
+        },
+        ErrorCode::SessionExportFailed => Messages {
+            en: "The session could not be exported.",
+            ru: "Не удалось экспортировать сессию.",
+        },
+        ErrorCode::SessionNotFound => Messages {
+            en: "Session {id} is no longer available.",
+            ru: "Сессия {id} больше недоступна.",
+        },
+        ErrorCode::OnboardingFailed => Messages {
+            en: "Setup checks could not be completed.",
+            ru: "Не удалось выполнить проверки настройки.",
+        },
+        ErrorCode::SoundsFailed => Messages {
+            en: "Sounds could not be loaded.",
+            ru: "Не удалось загрузить звуки.",
+        },
+        ErrorCode::TrayFailed => Messages {
+            en: "The tray icon could not be loaded.",
+            ru: "Не удалось загрузить значок в трее.",
+        },
+        ErrorCode::DurationOutOfRange => Messages {
+            en: "Duration {duration} s is outside the allowed {min}–{max} s.",
+            ru: "Длительность {duration} с вне допустимого диапазона {min}–{max} с.",
+        },
+        ErrorCode::DurationDuplicate => Messages {
+            en: "Duration {duration} s is listed more than once.",
+            ru: "Длительность {duration} с указана несколько раз.",
+        },
+        ErrorCode::DurationNotFound => Messages {
+            en: "There is no {duration} s duration to assign a hotkey to.",
+            ru: "Длительности {duration} с нет в списке, назначить клавишу нельзя.",
+        },
+        ErrorCode::TooManyDurations => Messages {
+            en: "At most {max} durations are allowed.",
+            ru: "Допускается не более {max} длительностей.",
+        },
+        ErrorCode::HotkeyInvalid => Messages {
+            en: "\"{key}\" is not a valid hotkey.",
+            ru: "«{key}» не является допустимой горячей клавишей.",
+        },
+        ErrorCode::HotkeyConflict => Messages {
+            en: "Hotkey \"{key}\" is already assigned to another action.",
+            ru: "Горячая клавиша «{key}» уже назначена другому действию.",
+        },
+        ErrorCode::HotkeyRegistrationFailed => Messages {
+            en: "Hotkey \"{key}\" could not be registered; another application may be using it.",
+            ru: "Не удалось зарегистрировать горячую клавишу «{key}»; возможно, она занята другим приложением.",
+        },
+        ErrorCode::MonitorFeedback => Messages {
+            en: "Monitoring is unavailable: the output device is the system audio source being captured, so playback would feed back into the recording.",
+            ru: "Прослушивание недоступно: устройство вывода является источником захватываемого системного звука, и воспроизведение попадёт обратно в запись.",
+        },
+        ErrorCode::MonitorFailed => Messages {
+            en: "Audio monitoring could not be started on the output device.",
+            ru: "Не удалось включить прослушивание на устройстве вывода.",
+        },
+        ErrorCode::AutoTypeDisabled => Messages {
+            en: "Typing answers into other apps is turned off in settings.",
+            ru: "Набор ответов в других приложениях отключён в настройках.",
+        },
+        ErrorCode::AutoTypeBusy => Messages {
+            en: "Another answer is still being typed.",
+            ru: "Предыдущий ответ ещё набирается.",
+        },
+        ErrorCode::AutoTypeUnsupported => Messages {
+            en: "Typing into other apps is not available here.",
+            ru: "Набор текста в других приложениях здесь недоступен.",
+        },
+        ErrorCode::AutoTypeFailed => Messages {
+            en: "The answer could not be typed.",
+            ru: "Не удалось набрать ответ.",
+        },
+        ErrorCode::WindowInfoUnsupported => Messages {
+            en: "The active window cannot be read on this system.",
+            ru: "На этой системе нельзя определить активное окно.",
+        },
+        ErrorCode::WindowInfoFailed => Messages {
+            en: "The active window could not be read.",
+            ru: "Не удалось определить активное окно.",
+        },
+        ErrorCode::DisplaysUnavailable => Messages {
+            en: "The connected displays could not be read.",
+            ru: "Не удалось получить список подключённых дисплеев.",
+        },
+        ErrorCode::PromptTemplateInvalid => Messages {
+            en: "The prompt template has an error at character {position}.",
+            ru: "В шаблоне промпта ошибка в позиции {position}.",
+        },
+        ErrorCode::DiagnosticsExportFailed => Messages {
+            en: "The diagnostics bundle could not be created.",
+            ru: "Не удалось собрать архив диагностики.",
+        },
+        ErrorCode::WaveformFailed => Messages {
+            en: "The waveform of this recording could not be drawn.",
+            ru: "Не удалось построить волну для этой записи.",
+        },
+        ErrorCode::AudioCalibrationFailed => Messages {
+            en: "The microphone could not be calibrated.",
+            ru: "Не удалось откалибровать микрофон.",
+        },
+        ErrorCode::LocalSpeechIncompatible => Messages {
+            en: "The local speech server {version} is not supported ({required} is needed). Reinstall it from the local speech settings.",
+            ru: "Локальный сервер распознавания речи {version} не поддерживается (нужна версия {required}). Переустановите его в настройках локального распознавания.",
+        },
+        ErrorCode::ArtifactsFailed => Messages {
+            en: "Saved recordings or transcripts could not be read or encrypted.",
+            ru: "Не удалось прочитать или зашифровать сохранённые записи или расшифровки.",
+        },
+    }
+}
+
+/// Localized headline for `code`; `{name}` placeholders are filled from `params`.
+/// Unknown languages fall back to English.
+pub fn format_error(code: ErrorCode, lang: &str, params: &[(&str, &str)]) -> String {
+    let entry = messages(code);
+    let template = template(&entry, lang).unwrap_or(entry.en);
+    params
+        .iter()
+        .fold(template.to_string(), |text, (name, value)| {
+            text.replace(&format!("{{{name}}}"), value)
+        })
+}
+
+fn template(entry: &Messages, lang: &str) -> Option<&'static str> {
+    match lang {
+        "en" => Some(entry.en),
+        "ru" => Some(entry.ru),
+        _ => None,
+    }
+}
+
+pub(crate) fn set_language(lang: &str) {
+    let index = SUPPORTED_UI_LANGUAGES
+        .iter()
+        .position(|supported| *supported == lang)
+        .unwrap_or(0);
+    UI_LANGUAGE.store(index, Ordering::Relaxed);
+}
+
+fn current_language() -> &'static str {
+    SUPPORTED_UI_LANGUAGES
+        .get(UI_LANGUAGE.load(Ordering::Relaxed))
+        .copied()
+        .unwrap_or("en")
+}
+
+/// Error payload returned by every command: a code, a localized headline, and the
+/// raw cause (provider response, OS error) kept out of the headline.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandError {
+    pub code: ErrorCode,
+    pub message: String,
+    pub detail: Option<String>,
+    /// Machine-readable fields for codes the frontend reacts to, e.g. payload limits.
+    pub data: Option<Value>,
+}
+
+impl CommandError {
+    pub fn new(code: ErrorCode, detail: impl fmt::Display) -> Self {
+        Self::with_params(code, &[], detail)
+    }
+
+    pub fn with_params(
+        code: ErrorCode,
+        params: &[(&str, &str)],
+        detail: impl fmt::Display,
+    ) -> Self {
+        let detail = detail.to_string();
+        Self {
+            code,
+            message: format_error(code, current_language(), params),
+            detail: (!detail.is_empty()).then_some(detail),
+            data: None,
+        }
+    }
+
+    pub fn bare(code: ErrorCode, params: &[(&str, &str)]) -> Self {
+        Self::with_params(code, params, "")
+    }
+
+    pub fn with_data(mut self, data: Value) -> Self {
+        self.data = Some(data);
+        self
+    }
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.detail {
+            Some(detail) => write!(f, "{} ({detail})", self.message),
+            None => f.write_str(&self.message),
+        }
+    }
+}
+
+impl From<anyhow::Error> for CommandError {
+    fn from(error: anyhow::Error) -> Self {
+        Self::new(ErrorCode::Internal, error)
+    }
+}
+
+/// Shorthand for command results.
+pub type CommandResult<T> = Result<T, CommandError>;
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use super::*;
+
+    const ALL_CODES: &[ErrorCode] = &[
+        ErrorCode::Internal,
+        ErrorCode::InvalidArgument,
+        ErrorCode::ConfigFailed,
+        ErrorCode::ConfigConflict,
+        ErrorCode::ConfigLocked,
+        ErrorCode::OpenFailed,
+        ErrorCode::UrlNotAllowed,
+        ErrorCode::PathNotFound,
+        ErrorCode::PathNotAllowed,
+        ErrorCode::LogsUnavailable,
+        ErrorCode::AuthUnknownAccount,
+        ErrorCode::AuthFailed,
+        ErrorCode::AuthProviderUnsupported,
+        ErrorCode::AuthProviderUnavailable,
+        ErrorCode::NetworkFailed,
+        ErrorCode::KeyTestTooSoon,
+        ErrorCode::LocalSpeechFailed,
+        ErrorCode::OllamaFailed,
+        ErrorCode::AudioDeviceFailed,
+        ErrorCode::AudioCaptureFailed,
+        ErrorCode::MicrophoneAccessDenied,
+        ErrorCode::TranscriptionFailed,
+        ErrorCode::TranscriptionModeUnknown,
+        ErrorCode::PayloadTooLarge,
+        ErrorCode::UpdateCheckFailed,
+        ErrorCode::UsageFailed,
+        ErrorCode::SessionExportFailed,
+        ErrorCode::SessionNotFound,
+        ErrorCode::OnboardingFailed,
+        ErrorCode::SoundsFailed,
+        ErrorCode::TrayFailed,
+        ErrorCode::DurationOutOfRange,
+        ErrorCode::DurationDuplicate,
+        ErrorCode::DurationNotFound,
+        ErrorCode::TooManyDurations,
+        ErrorCode::HotkeyInvalid,
+        ErrorCode::HotkeyConflict,
+        ErrorCode::HotkeyRegistrationFailed,
+        ErrorCode::MonitorFeedback,
+        ErrorCode::MonitorFailed,
+        ErrorCode::AutoTypeDisabled,
+        ErrorCode::AutoTypeBusy,
+        ErrorCode::AutoTypeUnsupported,
+        ErrorCode::AutoTypeFailed,
+        ErrorCode::ArtifactsFailed,
+        ErrorCode::WindowInfoUnsupported,
+        ErrorCode::WindowInfoFailed,
+        ErrorCode::DisplaysUnavailable,
+        ErrorCode::PromptTemplateInvalid,
+        ErrorCode::DiagnosticsExportFailed,
+        ErrorCode::WaveformFailed,
+        ErrorCode::AudioCalibrationFailed,
+        ErrorCode::LocalSpeechIncompatible,
+    ];
+
+    // Stops compiling when a code is added, as a reminder to list it above.
+    #[allow(dead_code)]
+    fn exhaustive(code: ErrorCode) {
+        match code {
+            ErrorCode::Internal => {}
+            ErrorCode::InvalidArgument => {}
+            ErrorCode::ConfigFailed => {}
+            ErrorCode::ConfigConflict => {}
+            ErrorCode::ConfigLocked => {}
+            ErrorCode::OpenFailed => {}
+            ErrorCode::UrlNotAllowed => {}
+            ErrorCode::PathNotFound => {}
+            ErrorCode::PathNotAllowed => {}
+            ErrorCode::LogsUnavailable => {}
+            ErrorCode::AuthUnknownAccount => {}
+            ErrorCode::AuthFailed => {}
+            ErrorCode::AuthProviderUnsupported => {}
+            ErrorCode::AuthProviderUnavailable => {}
+            ErrorCode::NetworkFailed => {}
+            ErrorCode::KeyTestTooSoon => {}
+            ErrorCode::LocalSpeechFailed => {}
+            ErrorCode::OllamaFailed => {}
+            ErrorCode::AudioDeviceFailed => {}
+            ErrorCode::AudioCaptureFailed => {}
+            ErrorCode::MicrophoneAccessDenied => {}
+            ErrorCode::TranscriptionFailed => {}
+            ErrorCode::TranscriptionModeUnknown => {}
+            ErrorCode::PayloadTooLarge => {}
+            ErrorCode::UpdateCheckFailed => {}
+            ErrorCode::UsageFailed => {}
+            ErrorCode::SessionExportFailed => {}
+            ErrorCode::SessionNotFound => {}
+            ErrorCode::OnboardingFailed => {}
+            ErrorCode::SoundsFailed => {}
+            ErrorCode::TrayFailed => {}
+            ErrorCode::DurationOutOfRange => {}
+            ErrorCode::DurationDuplicate => {}
+            ErrorCode::DurationNotFound => {}
+            ErrorCode::TooManyDurations => {}
+            ErrorCode::HotkeyInvalid => {}
+            ErrorCode::HotkeyConflict => {}
+            ErrorCode::HotkeyRegistrationFailed => {}
+            ErrorCode::MonitorFeedback => {}
+            ErrorCode::MonitorFailed => {}
+            ErrorCode::AutoTypeDisabled => {}
+            ErrorCode::AutoTypeBusy => {}
+            ErrorCode::AutoTypeUnsupported => {}
+            ErrorCode::AutoTypeFailed => {}
+            ErrorCode::ArtifactsFailed => {}
+            ErrorCode::WindowInfoUnsupported => {}
+            ErrorCode::WindowInfoFailed => {}
+            ErrorCode::DisplaysUnavailable => {}
+            ErrorCode::PromptTemplateInvalid => {}
+            ErrorCode::DiagnosticsExportFailed => {}
+            ErrorCode::WaveformFailed => {}
+            ErrorCode::AudioCalibrationFailed => {}
+            ErrorCode::LocalSpeechIncompatible => {}
+        }
+    }
+
+    fn placeholders(template: &str) -> BTreeSet<&str> {
+        template
+            .split('{')
+            .skip(1)
+            .filter_map(|rest| rest.split_once('}').map(|(name, _)| name))
+            .collect()
+    }
+
+    #[test]
+    fn every_code_has_a_message_in_every_ui_language() {
+        for &code in ALL_CODES {
+            let entry = messages(code);
+            let english = placeholders(entry.en);
+            for lang in SUPPORTED_UI_LANGUAGES {
+                let text = template(&entry, lang)
+                    .unwrap_or_else(|| panic!("no {lang} messages for {code:?}"));
+                assert!(!text.trim().is_empty(), "empty {lang} message for {code:?}");
+                assert_eq!(
+                    placeholders(text),
+                    english,
+                    "{lang} message for {code:?} has different placeholders"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn unknown_languages_fall_back_to_english() {
+        assert_eq!(
+            format_error(ErrorCode::InvalidArgument, "de", &[("name", "x")]),
+            "Invalid value for x."
+        );
+    }
+}
@@ -0,0 +1,292 @@
+//! Every event the backend emits, and the helper they are all emitted
+//! through. When the backend is embedded next to other Tauri plugins the
+//! names can be moved under a namespace: `acme` turns `audio:chunk` into
+//! `acme:audio:chunk`. The bundled webview listens on the bare names, so the
+//! namespace stays empty unless `XEXAMAI_EVENT_NAMESPACE` is set, either at
+//! build time or in the environment at startup (see [`set_namespace`]).
+
+use std::borrow::Cow;
+use std::fmt;
+use std::sync::OnceLock;
+
+use serde::Serialize;
+use tauri::{Emitter, Runtime};
+
+pub const APP_ERROR: &str = "app:error";
+pub const APP_THREAD_CRASHED: &str = "app:thread-crashed";
+pub const ARTIFACTS_ENCRYPT_PROGRESS: &str = "artifacts:encrypt-progress";
+pub const AUDIO_AUTO_STARTED: &str = "audio:auto-started";
+pub const AUDIO_BACKPRESSURE: &str = "audio:backpressure";
+pub const AUDIO_CALIBRATION: &str = "audio:calibration";
+pub const AUDIO_CHUNK: &str = "audio:chunk";
+pub const AUDIO_LEVEL: &str = "audio:level";
+pub const AUDIO_PARTIAL_START: &str = "audio:partial-start";
+pub const AUDIO_SOURCE_CHANGED: &str = "audio:source-changed";
+pub const AUDIO_WAKE_WORD: &str = "audio:wake-word";
+pub const AUTH_ACCOUNT_CHANGED: &str = "auth:account-changed";
+pub const AUTH_DEEP_LINK: &str = "auth:deep-link";
+pub const AUTH_EXPIRED: &str = "auth:expired";
+pub const AUTH_REFRESHED: &str = "auth:refreshed";
+pub const CONFIG_PERSISTED: &str = "config:persisted";
+pub const CONFIG_READONLY: &str = "config:readonly";
+pub const CONFIG_UPDATED: &str = "config:updated";
+pub const DIAGNOSTICS_BUNDLE_READY: &str = "diagnostics:bundle-ready";
+pub const HOTKEYS_DURATION: &str = "hotkeys:duration";
+pub const HOTKEYS_DURATION_QUEUED: &str = "hotkeys:duration-queued";
+pub const HOTKEYS_REAPPLIED: &str = "hotkeys:reapplied";
+pub const HOTKEYS_STREAM_SEND: &str = "hotkeys:stream-send";
+pub const HOTKEYS_TOGGLE_INPUT: &str = "hotkeys:toggle-input";
+pub const INTEGRATION_CAPTURE: &str = "integration:capture";
+pub const LOCAL_SPEECH_STATUS: &str = "local-speech:status";
+pub const MONITORS_CHANGED: &str = "monitors:changed";
+pub const OLLAMA_MEMORY_WARNING: &str = "ollama:memory-warning";
+pub const PERF_SLOW_COMMAND: &str = "perf:slow-command";
+pub const PRIVACY_LOST: &str = "privacy:lost";
+pub const PRIVACY_PRIVATE_SESSION: &str = "privacy:private-session";
+pub const PRIVACY_REAPPLIED: &str = "privacy:reapplied";
+pub const PRIVACY_WARNING: &str = "privacy:warning";
+pub const SELF_CHECK_PROGRESS: &str = "self-check:progress";
+pub const SESSION_EXPORTED: &str = "session:exported";
+pub const SESSION_SUMMARY: &str = "session:summary";
+pub const SESSION_SUMMARY_CANCEL: &str = "session:summary-cancel";
+pub const SESSION_SUMMARY_REQUEST: &str = "session:summary-request";
+pub const STREAM_MODE_CHANGED: &str = "stream:mode-changed";
+pub const SYSTEM_RESUMED: &str = "system:resumed";
+pub const TRANSCRIPTION_BATCH_PROGRESS: &str = "transcription:batch-progress";
+pub const TRANSCRIPTION_DEBUG_SAVED: &str = "transcription:debug:saved";
+pub const TRANSCRIPTION_PROMPT_TRUNCATED: &str = "transcription:prompt-truncated";
+pub const TRANSCRIPTION_SPEEDUP_QUALITY: &str = "transcription:speedup-quality";
+pub const TRANSCRIPTION_THROTTLED: &str = "transcription:throttled";
+pub const TRAY_REBUILT: &str = "tray:rebuilt";
+// The updater events predate the `scope:name` convention.
+pub const UPDATE_AVAILABLE: &str = "update-available";
+pub const UPDATE_DOWNLOAD_PROGRESS: &str = "update-download-progress";
+pub const UPDATE_ERROR: &str = "update-error";
+pub const UPDATE_STARTED: &str = "update-started";
+pub const UPDATES_AVAILABLE: &str = "updates:available";
+pub const WINDOW_SCALE_CHANGED: &str = "window:scale-changed";
+
+/// The full catalog, sorted; anything emitted must be listed here.
+pub const ALL: &[&str] = &[
+    APP_ERROR,
+    APP_THREAD_CRASHED,
+    ARTIFACTS_ENCRYPT_PROGRESS,
+    AUDIO_AUTO_STARTED,
+    AUDIO_BACKPRESSURE,
+    AUDIO_CALIBRATION,
+    AUDIO_CHUNK,
+    AUDIO_LEVEL,
+    AUDIO_PARTIAL_START,
+    AUDIO_SOURCE_CHANGED,
+    AUDIO_WAKE_WORD,
+    AUTH_ACCOUNT_CHANGED,
+    AUTH_DEEP_LINK,
+    AUTH_EXPIRED,
+    AUTH_REFRESHED,
+    CONFIG_PERSISTED,
+    CONFIG_READONLY,
+    CONFIG_UPDATED,
+    DIAGNOSTICS_BUNDLE_READY,
+    HOTKEYS_DURATION,
+    HOTKEYS_DURATION_QUEUED,
+    HOTKEYS_REAPPLIED,
+    HOTKEYS_STREAM_SEND,
+    HOTKEYS_TOGGLE_INPUT,
+    INTEGRATION_CAPTURE,
+    LOCAL_SPEECH_STATUS,
+    MONITORS_CHANGED,
+    OLLAMA_MEMORY_WARNING,
+    PERF_SLOW_COMMAND,
+    PRIVACY_LOST,
+    PRIVACY_PRIVATE_SESSION,
+    PRIVACY_REAPPLIED,
+    PRIVACY_WARNING,
+    SELF_CHECK_PROGRESS,
+    SESSION_EXPORTED,
+    SESSION_SUMMARY,
+    SESSION_SUMMARY_CANCEL,
+    SESSION_SUMMARY_REQUEST,
+    STREAM_MODE_CHANGED,
+    SYSTEM_RESUMED,
+    TRANSCRIPTION_BATCH_PROGRESS,
+    TRANSCRIPTION_DEBUG_SAVED,
+    TRANSCRIPTION_PROMPT_TRUNCATED,
+    TRANSCRIPTION_SPEEDUP_QUALITY,
+    TRANSCRIPTION_THROTTLED,
+    TRAY_REBUILT,
+    UPDATE_AVAILABLE,
+    UPDATE_DOWNLOAD_PROGRESS,
+    UPDATE_ERROR,
+    UPDATE_STARTED,
+    UPDATES_AVAILABLE,
+    WINDOW_SCALE_CHANGED,
+];
+
+// Read on the first emit and fixed from then on.
+static NAMESPACE: OnceLock<String> = OnceLock::new();
+
+#[derive(Debug)]
+pub enum NamespaceError {
+    /// Tauri event names only take letters, digits, `-`, `/`, `:` and `_`.
+    Invalid(String),
+    /// An event has already been emitted under the current namespace.
+    AlreadySet,
+}
+
+impl NamespaceError {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Invalid(_) => "invalid",
+            Self::AlreadySet => "already_set",
+        }
+    }
+}
+
+impl fmt::Display for NamespaceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Invalid(namespace) => write!(f, "invalid event namespace {namespace:?}"),
+            Self::AlreadySet => f.write_str("event namespace is already in use"),
+        }
+    }
+}
+
+impl std::error::Error for NamespaceError {}
+
+/// Sets the namespace for every event; must run before anything is emitted.
+/// Surrounding `:` are dropped, and an empty namespace keeps the bare names.
+pub fn set_namespace(namespace: &str) -> Result<(), NamespaceError> {
+    let namespace = clean_namespace(namespace)?;
+    NAMESPACE
+        .set(namespace.to_string())
+        .map_err(|_| NamespaceError::AlreadySet)
+}
+
+fn clean_namespace(namespace: &str) -> Result<&str, NamespaceError> {
+    let namespace = namespace.trim().trim_matches(':');
+    let valid = namespace
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '/' | ':' | '_'));
+    if !valid {
+        return Err(NamespaceError::Invalid(namespace.to_string()));
+    }
+    Ok(namespace)
+}
+
+pub fn namespace() -> &'static str {
+    NAMESPACE.get_or_init(|| {
+        option_env!("XEXAMAI_EVENT_NAMESPACE")
+            .unwrap_or_default()
+            .trim_matches(':')
+            .to_string()
+    })
+}
+
+/// `name` as it goes over the wire.
+pub fn event_name(name: &'static str) -> Cow<'static, str> {
+    namespaced(namespace(), name)
+}
+
+fn namespaced(namespace: &str, name: &'static str) -> Cow<'static, str> {
+    match namespace {
+        "" => Cow::Borrowed(name),
+        namespace => Cow::Owned(format!("{namespace}:{name}")),
+    }
+}
+
+/// Emits `name` from the catalog above to every webview, under the namespace.
+pub fn emit_event<R, E, S>(emitter: &E, name: &'static str, payload: S) -> tauri::Result<()>
+where
+    R: Runtime,
+    E: Emitter<R>,
+    S: Serialize + Clone,
+{
+    debug_assert!(ALL.contains(&name), "event {name} missing from the catalog");
+    emitter.emit(&event_name(name), payload)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    // The updater events kept their names from before `scope:name`.
+    const LEGACY: &[&str] = &[
+        UPDATE_AVAILABLE,
+        UPDATE_DOWNLOAD_PROGRESS,
+        UPDATE_ERROR,
+        UPDATE_STARTED,
+    ];
+
+    /// `(IDENT, "value")` of every `pub const` string in this file, in order.
+    fn declared() -> Vec<(&'static str, &'static str)> {
+        include_str!("events.rs")
+            .lines()
+            .filter_map(|line| line.strip_prefix("pub const "))
+            .filter_map(|rest| {
+                let (ident, value) = rest.split_once(": &str = \"")?;
+                Some((ident, value.strip_suffix("\";")?))
+            })
+            .collect()
+    }
+
+    fn is_word(word: &str) -> bool {
+        !word.is_empty()
+            && !word.starts_with('-')
+            && !word.ends_with('-')
+            && word
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+    }
+
+    #[test]
+    fn names_are_unique() {
+        let mut seen = HashSet::new();
+        for name in ALL {
+            assert!(seen.insert(name), "{name} is listed twice");
+        }
+    }
+
+    #[test]
+    fn every_declared_event_is_in_the_catalog_in_order() {
+        let declared = declared();
+        assert_eq!(declared.len(), ALL.len());
+        let values: Vec<&str> = declared.iter().map(|(_, value)| *value).collect();
+        assert_eq!(values, ALL);
+    }
+
+    #[test]
+    fn names_are_scoped_lowercase_words() {
+        for &name in ALL {
+            if LEGACY.contains(&name) {
+                assert!(is_word(name), "{name}");
+                continue;
+            }
+            let words: Vec<&str> = name.split(':').collect();
+            assert!(words.len() >= 2, "{name} has no scope");
+            assert!(words.iter().all(|word| is_word(word)), "{name}");
+        }
+    }
+
+    #[test]
+    fn namespace_prefixes_every_name() {
+        assert_eq!(namespaced("", AUDIO_CHUNK), "audio:chunk");
+        assert!(matches!(namespaced("", AUDIO_CHUNK), Cow::Borrowed(_)));
+        assert_eq!(namespaced("acme", AUDIO_CHUNK), "acme:audio:chunk");
+        assert_eq!(namespaced("acme/x", UPDATE_ERROR), "acme/x:update-error");
+    }
+
+    #[test]
+    fn namespaces_are_trimmed_and_checked() {
+        assert_eq!(clean_namespace(" :acme: ").unwrap(), "acme");
+        assert_eq!(clean_namespace("org:acme_1/x-y").unwrap(), "org:acme_1/x-y");
+        assert_eq!(clean_namespace("").unwrap(), "");
+        for bad in ["acme corp", "acme.x", "ácme"] {
+            assert!(
+                matches!(clean_namespace(bad), Err(NamespaceError::Invalid(_))),
+                "{bad}"
+            );
+        }
+    }
+}
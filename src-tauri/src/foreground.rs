@@ -0,0 +1,240 @@
+//! The window in front, so prompts can say what the user is looking at. Read with
+//! the Win32 API, NSWorkspace on macOS and `xprop` under X11; Wayland does not let
+//! a client see other windows, so there it is reported as unsupported.
+
+#[cfg(any(windows, target_os = "macos"))]
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::errors::{CommandError, CommandResult, ErrorCode};
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ForegroundWindowInfo {
+    /// Window title. `None` on macOS, where titles need the Screen Recording permission.
+    pub title: Option<String>,
+    /// Executable file name, e.g. `chrome.exe`.
+    pub process_name: Option<String>,
+    /// Name to show: the app's display name, or the executable without extension.
+    pub app_name: Option<String>,
+}
+
+pub async fn window_info() -> CommandResult<ForegroundWindowInfo> {
+    let info = read().await?;
+    log::debug!(
+        target: "foreground",
+        "Foreground app: {:?}",
+        info.app_name.as_deref().unwrap_or("unknown")
+    );
+    Ok(info)
+}
+
+#[cfg(not(any(windows, target_os = "macos")))]
+fn unsupported(reason: &str) -> CommandError {
+    CommandError::new(ErrorCode::WindowInfoUnsupported, reason)
+}
+
+#[cfg(any(windows, target_os = "macos", target_os = "linux"))]
+fn failed(reason: impl std::fmt::Display) -> CommandError {
+    CommandError::new(ErrorCode::WindowInfoFailed, reason)
+}
+
+#[cfg(any(windows, target_os = "macos"))]
+fn file_name(path: &str) -> Option<String> {
+    Path::new(path)
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+}
+
+#[cfg(any(windows, target_os = "macos"))]
+fn file_stem(path: &str) -> Option<String> {
+    Path::new(path)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+}
+
+#[cfg(windows)]
+async fn read() -> CommandResult<ForegroundWindowInfo> {
+    use windows::Win32::UI::WindowsAndMessaging::{
+        GetForegroundWindow, GetWindowTextLengthW, GetWindowTextW, GetWindowThreadProcessId,
+    };
+
+    let hwnd = unsafe { GetForegroundWindow() };
+    if hwnd.is_invalid() {
+        return Err(failed("no window is in the foreground"));
+    }
+    let length = unsafe { GetWindowTextLengthW(hwnd) };
+    let title = (length > 0).then(|| {
+        let mut buffer = vec![0u16; length as usize + 1];
+        let copied = unsafe { GetWindowTextW(hwnd, &mut buffer) };
+        String::from_utf16_lossy(&buffer[..copied.max(0) as usize])
+    });
+    let mut pid = 0u32;
+    unsafe { GetWindowThreadProcessId(hwnd, Some(&mut pid)) };
+    let path = (pid != 0).then(|| process_path(pid)).flatten();
+    Ok(ForegroundWindowInfo {
+        title,
+        process_name: path.as_deref().and_then(file_name),
+        app_name: path.as_deref().and_then(file_stem),
+    })
+}
+
+#[cfg(windows)]
+fn process_path(pid: u32) -> Option<String> {
+    use windows::core::PWSTR;
+    use windows::Win32::Foundation::CloseHandle;
+    use windows::Win32::System::Threading::{
+        OpenProcess, QueryFullProcessImageNameW, PROCESS_NAME_WIN32,
+        PROCESS_QUERY_LIMITED_INFORMATION,
+    };
+
+    // Elevated processes refuse even limited access; the title still comes through.
+    let process = unsafe { OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid) }.ok()?;
+    let mut buffer = [0u16; 1024];
+    let mut size = buffer.len() as u32;
+    let result = unsafe {
+        QueryFullProcessImageNameW(
+            process,
+            PROCESS_NAME_WIN32,
+            PWSTR(buffer.as_mut_ptr()),
+            &mut size,
+        )
+    };
+    let _ = unsafe { CloseHandle(process) };
+    result.ok()?;
+    Some(String::from_utf16_lossy(&buffer[..size as usize]))
+}
+
+#[cfg(target_os = "macos")]
+async fn read() -> CommandResult<ForegroundWindowInfo> {
+    use objc2_app_kit::NSWorkspace;
+
+    let app = NSWorkspace::sharedWorkspace()
+        .frontmostApplication()
+        .ok_or_else(|| failed("no application is frontmost"))?;
+    let path = app
+        .executableURL()
+        .and_then(|url| url.path())
+        .map(|path| path.to_string());
+    Ok(ForegroundWindowInfo {
+        title: None,
+        process_name: path.as_deref().and_then(file_name),
+        app_name: app
+            .localizedName()
+            .map(|name| name.to_string())
+            .or_else(|| path.as_deref().and_then(file_stem)),
+    })
+}
+
+#[cfg(target_os = "linux")]
+async fn read() -> CommandResult<ForegroundWindowInfo> {
+    if std::env::var_os("DISPLAY").is_none() {
+        return Err(unsupported(
+            "no X11 display; Wayland does not expose the active window",
+        ));
+    }
+    let root = xprop(&["-root", "_NET_ACTIVE_WINDOW"]).await?;
+    // `_NET_ACTIVE_WINDOW(WINDOW): window id # 0x3a00007`
+    let id = root
+        .rsplit('#')
+        .next()
+        .map(str::trim)
+        .filter(|id| id.starts_with("0x") && *id != "0x0")
+        .ok_or_else(|| {
+            if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+                unsupported("the active window is a Wayland window")
+            } else {
+                failed("no window is active")
+            }
+        })?;
+    let properties = xprop(&[
+        "-id",
+        id,
+        "_NET_WM_NAME",
+        "WM_NAME",
+        "WM_CLASS",
+        "_NET_WM_PID",
+    ])
+    .await?;
+    let value = |name: &str| {
+        properties.lines().find_map(|line| {
+            let (key, value) = line.split_once(" = ")?;
+            key.starts_with(&format!("{name}("))
+                .then(|| value.to_string())
+        })
+    };
+    let title = value("_NET_WM_NAME")
+        .or_else(|| value("WM_NAME"))
+        .map(|title| unquote(&title));
+    // `WM_CLASS(STRING) = "navigator", "firefox"`: instance, then class.
+    let class = value("WM_CLASS")
+        .and_then(|class| class.rsplit_once(", ").map(|(_, class)| unquote(class)));
+    let process_name = match value("_NET_WM_PID").and_then(|pid| pid.trim().parse::<u32>().ok()) {
+        Some(pid) => tokio::fs::read_to_string(format!("/proc/{pid}/comm"))
+            .await
+            .ok()
+            .map(|comm| comm.trim().to_string()),
+        None => None,
+    };
+    Ok(ForegroundWindowInfo {
+        title,
+        app_name: class.or_else(|| process_name.clone()),
+        process_name,
+    })
+}
+
+#[cfg(target_os = "linux")]
+async fn xprop(args: &[&str]) -> CommandResult<String> {
+    let output = tokio::process::Command::new("xprop")
+        .args(args)
+        .stdin(std::process::Stdio::null())
+        .output()
+        .await
+        .map_err(|error| match error.kind() {
+            std::io::ErrorKind::NotFound => unsupported("xprop is not installed"),
+            _ => failed(error),
+        })?;
+    if !output.status.success() {
+        return Err(failed(format!("xprop exited with {}", output.status)));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// `"a \"b\""` as xprop prints it, back to `a "b"`.
+#[cfg(target_os = "linux")]
+fn unquote(value: &str) -> String {
+    let value = value.trim();
+    let inner = value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .unwrap_or(value);
+    inner.replace("\\\"", "\"").replace("\\\\", "\\")
+}
+
+#[cfg(not(any(windows, target_os = "macos", target_os = "linux")))]
+async fn read() -> CommandResult<ForegroundWindowInfo> {
+    Err(unsupported("not implemented for this platform"))
+}
+
+/// Wraps the window context the LLM layer adds to the system prompt.
+pub const CONTEXT_START: &str = "[window-context]";
+pub const CONTEXT_END: &str = "[/window-context]";
+
+/// `text` without any marked window context, for exports.
+pub fn strip_context(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find(CONTEXT_START) {
+        out.push_str(&rest[..start]);
+        match rest[start..].find(CONTEXT_END) {
+            Some(end) => rest = &rest[start + end + CONTEXT_END.len()..],
+            None => {
+                rest = "";
+                break;
+            }
+        }
+    }
+    out.push_str(rest);
+    out.trim().to_string()
+}
@@ -1,87 +1,539 @@
-use std::collections::HashSet;
-use std::sync::Mutex;
+use std::collections::{BTreeMap, HashSet, VecDeque};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
 
+use chrono::Local;
+use serde::Serialize;
 use serde_json::json;
-use tauri::{AppHandle, Emitter};
-use tauri_plugin_global_shortcut::GlobalShortcutExt;
+use tauri::{AppHandle, Manager, Runtime};
+use tauri_plugin_global_shortcut::{GlobalShortcut, GlobalShortcutExt, Shortcut};
 
-use crate::types::AppConfig;
+use crate::config::ConfigState;
+use crate::constants::DEFAULT_MAX_PENDING_CLIPS;
+use crate::events::{self, emit_event};
+use crate::private_session;
+use crate::session::{self, InteractionTrigger};
+use crate::transcription::TranscriptionManager;
+use crate::types::{AppConfig, StreamMode};
+
+// A dispatched press that never became a transcription, e.g. because the buffer
+// held too little audio, must not hold up the rest of the queue.
+const PENDING_STALL_TIMEOUT: Duration = Duration::from_secs(3);
+
+static APP: OnceLock<AppHandle> = OnceLock::new();
+
+/// Outcome of registering one hotkey during `reapply`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HotkeyResult {
+    /// `duration`, `toggle`, `stream` or `private-session`.
+    pub kind: &'static str,
+    pub accelerator: String,
+    /// Seconds of a duration hotkey.
+    pub seconds: Option<u32>,
+    pub registered: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HotkeyReapplyReport {
+    /// What triggered the re-apply: `command`, `resume` or `explorer-restart`.
+    pub reason: &'static str,
+    pub results: Vec<HotkeyResult>,
+}
+
+/// A duration press held back while a transcription was running.
+#[derive(Debug, Clone, Copy)]
+struct PendingPress {
+    seconds: u32,
+    pressed_at: i64,
+}
 
 #[derive(Default)]
 pub struct HotkeyManager {
-    duration_shortcuts: Mutex<Vec<String>>,
+    /// Registered `(seconds, accelerator)` pairs, kept so a failed replace can roll back.
+    duration_shortcuts: Mutex<Vec<(u32, String)>>,
     toggle_shortcut: Mutex<Option<String>>,
+    /// Only registered in stream mode.
+    stream_shortcut: Mutex<Option<String>>,
+    private_session_shortcut: Mutex<Option<String>>,
+    /// Every accelerator this manager ever registered. `reapply` unregisters all of
+    /// them, in case the plugin still holds one the lists above lost track of.
+    known: Mutex<HashSet<String>>,
+    /// Oldest first; drained one press per finished transcription.
+    pending: Mutex<VecDeque<PendingPress>>,
+    max_pending: AtomicUsize,
+    /// Bumped on every dispatch so a stale stall timer leaves the queue alone.
+    dispatches: AtomicU64,
 }
 
 impl HotkeyManager {
     pub fn new() -> Self {
-        Self::default()
+        Self {
+            max_pending: AtomicUsize::new(DEFAULT_MAX_PENDING_CLIPS as usize),
+            ..Self::default()
+        }
     }
 
     pub fn apply_config(&self, app: &AppHandle, config: &AppConfig) {
-        self.register_duration_hotkeys(app, config);
-        self.register_toggle_hotkey(app, config);
+        let max_pending = config.max_pending_clips.max(1) as usize;
+        self.max_pending.store(max_pending, Ordering::SeqCst);
+        self.pending.lock().unwrap().truncate(max_pending);
+        self.register(app, Shortcuts::of(config));
+    }
+
+    /// Drops every queued duration press and returns how many there were.
+    pub fn clear_pending(&self) -> usize {
+        let mut pending = self.pending.lock().unwrap();
+        let cleared = pending.len();
+        pending.clear();
+        if cleared > 0 {
+            log::info!(target: "hotkeys", "Cleared {cleared} queued duration presses");
+        }
+        cleared
+    }
+
+    /// Queues a press, replacing the newest pending one when the queue is full.
+    /// Returns the press's position and whether it replaced another.
+    fn enqueue(&self, seconds: u32) -> (usize, bool) {
+        let mut pending = self.pending.lock().unwrap();
+        let coalesced = pending.len() >= self.max_pending.load(Ordering::SeqCst).max(1);
+        if coalesced {
+            pending.pop_back();
+        }
+        pending.push_back(PendingPress {
+            seconds,
+            pressed_at: Local::now().timestamp_millis(),
+        });
+        (pending.len(), coalesced)
     }
 
-    fn register_duration_hotkeys(&self, app: &AppHandle, config: &AppConfig) {
+    /// Drops every hotkey, including orphaned ones, and registers the set from
+    /// `config` again. Recovers shortcuts the OS stopped delivering, e.g. after
+    /// Explorer restarts.
+    pub fn reapply(
+        &self,
+        app: &AppHandle,
+        config: &AppConfig,
+        reason: &'static str,
+    ) -> HotkeyReapplyReport {
+        let manager = app.global_shortcut();
+        let orphans: Vec<String> = self.known.lock().unwrap().iter().cloned().collect();
+        for accelerator in orphans {
+            let _ = manager.unregister(accelerator.as_str());
+        }
+        self.duration_shortcuts.lock().unwrap().clear();
+        self.toggle_shortcut.lock().unwrap().take();
+        self.stream_shortcut.lock().unwrap().take();
+        self.private_session_shortcut.lock().unwrap().take();
+
+        let results = self.register(app, Shortcuts::of(config));
+        let failed = results.iter().filter(|result| !result.registered).count();
+        log::info!(
+            target: "hotkeys",
+            "Hotkeys re-applied ({reason}): registered={} failed={failed}",
+            results.len() - failed
+        );
+        let report = HotkeyReapplyReport { reason, results };
+        let _ = emit_event(app, events::HOTKEYS_REAPPLIED, &report);
+        report
+    }
+
+    fn register(&self, app: &AppHandle, shortcuts: Shortcuts) -> Vec<HotkeyResult> {
+        let mut results = self.register_duration_hotkeys(app, shortcuts.durations);
+        results.extend(self.register_toggle_hotkey(app, shortcuts.toggle));
+        results.extend(self.register_stream_hotkey(app, shortcuts.stream));
+        results.extend(self.register_private_session_hotkey(app, shortcuts.private_session));
+        results
+    }
+
+    fn register_duration_hotkeys(
+        &self,
+        app: &AppHandle,
+        entries: Vec<(u32, String)>,
+    ) -> Vec<HotkeyResult> {
         let manager = app.global_shortcut();
         let mut registered = self.duration_shortcuts.lock().unwrap();
-        for accelerator in registered.drain(..) {
+        for (_, accelerator) in registered.drain(..) {
             let _ = manager.unregister(accelerator.as_str());
         }
 
-        let mut used = HashSet::new();
-        for duration in &config.durations {
-            if let Some(key) = config.duration_hotkeys.get(duration) {
-                if let Some(accelerator) = normalize_accelerator(key) {
-                    if !used.insert(accelerator.clone()) {
-                        continue;
-                    }
-                    let seconds = *duration;
-                    match manager.on_shortcut(accelerator.as_str(), move |app_handle, _, _| {
-                        let _ = app_handle.emit("hotkeys:duration", json!({ "sec": seconds }));
-                    }) {
-                        Ok(_) => registered.push(accelerator),
-                        Err(error) => {
-                            eprintln!(
-                                "[hotkeys] failed to register duration hotkey '{}': {error}",
-                                key
-                            );
-                        }
+        let mut results = Vec::new();
+        for (seconds, accelerator) in entries {
+            let error = match register_duration(manager, seconds, &accelerator) {
+                Ok(()) => {
+                    self.remember(&accelerator);
+                    registered.push((seconds, accelerator.clone()));
+                    None
+                }
+                Err(error) => {
+                    log::warn!(
+                        target: "hotkeys",
+                        "failed to register duration hotkey '{}': {error}",
+                        accelerator
+                    );
+                    Some(error.to_string())
+                }
+            };
+            results.push(HotkeyResult {
+                kind: "duration",
+                accelerator,
+                seconds: Some(seconds),
+                registered: error.is_none(),
+                error,
+            });
+        }
+        results
+    }
+
+    fn remember(&self, accelerator: &str) {
+        self.known.lock().unwrap().insert(accelerator.to_string());
+    }
+
+    /// Swaps in a new set of duration hotkeys, all or nothing: if any accelerator
+    /// cannot be registered, the previous set is restored and that accelerator returned.
+    pub fn replace_duration_hotkeys(
+        &self,
+        app: &AppHandle,
+        durations: &[u32],
+        hotkeys: &BTreeMap<u32, String>,
+    ) -> Result<(), String> {
+        let manager = app.global_shortcut();
+        let mut registered = self.duration_shortcuts.lock().unwrap();
+        for (_, accelerator) in registered.iter() {
+            let _ = manager.unregister(accelerator.as_str());
+        }
+
+        let mut next: Vec<(u32, String)> = Vec::new();
+        for (seconds, accelerator) in duration_entries(durations, hotkeys) {
+            if let Err(error) = register_duration(manager, seconds, &accelerator) {
+                log::warn!(
+                    target: "hotkeys",
+                    "failed to register duration hotkey '{accelerator}': {error}; rolling back"
+                );
+                for (_, done) in &next {
+                    let _ = manager.unregister(done.as_str());
+                }
+                for (seconds, previous) in registered.iter() {
+                    if let Err(error) = register_duration(manager, *seconds, previous) {
+                        log::warn!(
+                            target: "hotkeys",
+                            "failed to restore duration hotkey '{previous}': {error}"
+                        );
                     }
                 }
+                return Err(accelerator);
             }
+            self.remember(&accelerator);
+            next.push((seconds, accelerator));
         }
+        *registered = next;
+        Ok(())
     }
 
-    fn register_toggle_hotkey(&self, app: &AppHandle, config: &AppConfig) {
+    fn register_toggle_hotkey(
+        &self,
+        app: &AppHandle,
+        accelerator: Option<String>,
+    ) -> Option<HotkeyResult> {
         let manager = app.global_shortcut();
         let mut guard = self.toggle_shortcut.lock().unwrap();
         if let Some(existing) = guard.take() {
             let _ = manager.unregister(existing.as_str());
         }
-        let key = config.toggle_input_hotkey.trim();
-        if key.is_empty() {
-            return;
+        let accelerator = accelerator?;
+        let result = manager.on_shortcut(accelerator.as_str(), move |app_handle, _, _| {
+            on_toggle_pressed(app_handle);
+        });
+        let error = match result {
+            Ok(()) => {
+                self.remember(&accelerator);
+                *guard = Some(accelerator.clone());
+                None
+            }
+            Err(error) => {
+                log::warn!(
+                    target: "hotkeys",
+                    "failed to register toggle hotkey '{accelerator}': {error}"
+                );
+                Some(error.to_string())
+            }
+        };
+        Some(HotkeyResult {
+            kind: "toggle",
+            accelerator,
+            seconds: None,
+            registered: error.is_none(),
+            error,
+        })
+    }
+
+    /// Registers `accelerator`, from `stream_shortcut`, in place of the current
+    /// stream send hotkey. `None` when nothing was registered.
+    pub fn register_stream_hotkey(
+        &self,
+        app: &AppHandle,
+        accelerator: Option<String>,
+    ) -> Option<HotkeyResult> {
+        let manager = app.global_shortcut();
+        let mut guard = self.stream_shortcut.lock().unwrap();
+        if let Some(existing) = guard.take() {
+            let _ = manager.unregister(existing.as_str());
         }
-        if let Some(accelerator) = normalize_accelerator(key) {
-            match manager.on_shortcut(accelerator.as_str(), move |app_handle, _, _| {
-                let _ = app_handle.emit("hotkeys:toggle-input", json!({}));
-            }) {
-                Ok(_) => {
-                    *guard = Some(accelerator);
-                }
-                Err(error) => {
-                    eprintln!(
-                        "[hotkeys] failed to register toggle hotkey '{}': {error}",
-                        key
-                    );
-                }
+        let accelerator = accelerator?;
+        let result = manager.on_shortcut(accelerator.as_str(), move |app_handle, _, _| {
+            let _ = emit_event(app_handle, events::HOTKEYS_STREAM_SEND, ());
+        });
+        let error = match result {
+            Ok(()) => {
+                self.remember(&accelerator);
+                *guard = Some(accelerator.clone());
+                None
+            }
+            Err(error) => {
+                log::warn!(
+                    target: "hotkeys",
+                    "failed to register stream send hotkey '{accelerator}': {error}"
+                );
+                Some(error.to_string())
+            }
+        };
+        Some(HotkeyResult {
+            kind: "stream",
+            accelerator,
+            seconds: None,
+            registered: error.is_none(),
+            error,
+        })
+    }
+
+    fn register_private_session_hotkey(
+        &self,
+        app: &AppHandle,
+        accelerator: Option<String>,
+    ) -> Option<HotkeyResult> {
+        let manager = app.global_shortcut();
+        let mut guard = self.private_session_shortcut.lock().unwrap();
+        if let Some(existing) = guard.take() {
+            let _ = manager.unregister(existing.as_str());
+        }
+        let accelerator = accelerator?;
+        let result = manager.on_shortcut(accelerator.as_str(), move |app_handle, _, _| {
+            private_session::toggle(app_handle);
+        });
+        let error = match result {
+            Ok(()) => {
+                self.remember(&accelerator);
+                *guard = Some(accelerator.clone());
+                None
             }
+            Err(error) => {
+                log::warn!(
+                    target: "hotkeys",
+                    "failed to register private session hotkey '{accelerator}': {error}"
+                );
+                Some(error.to_string())
+            }
+        };
+        Some(HotkeyResult {
+            kind: "private-session",
+            accelerator,
+            seconds: None,
+            registered: error.is_none(),
+            error,
+        })
+    }
+}
+
+pub fn start(app: &AppHandle) {
+    let _ = APP.set(app.clone());
+}
+
+/// Called from the window procedure when Explorer restarts.
+#[cfg_attr(not(windows), allow(dead_code))]
+pub fn notify_shell_restarted() {
+    if let Some(app) = APP.get() {
+        reapply_in_background(app, "explorer-restart");
+    }
+}
+
+/// Re-applies hotkeys from the current config off the calling thread, for
+/// signals that arrive on the window procedure or the power watcher.
+pub fn reapply_in_background(app: &AppHandle, reason: &'static str) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let (Some(hotkeys), Some(config)) = (
+            app.try_state::<Arc<HotkeyManager>>(),
+            app.try_state::<Arc<ConfigState>>(),
+        ) else {
+            return;
+        };
+        let config = config.get().await;
+        hotkeys.reapply(&app, &config, reason);
+    });
+}
+
+/// The accelerators a config registers, by hotkey.
+#[derive(Debug, PartialEq)]
+struct Shortcuts {
+    durations: Vec<(u32, String)>,
+    toggle: Option<String>,
+    stream: Option<String>,
+    private_session: Option<String>,
+}
+
+impl Shortcuts {
+    fn of(config: &AppConfig) -> Self {
+        Self {
+            durations: duration_entries(&config.durations, &config.duration_hotkeys),
+            toggle: normalize_accelerator(&config.toggle_input_hotkey),
+            stream: stream_shortcut(config),
+            private_session: normalize_accelerator(&config.private_session_hotkey),
         }
     }
 }
 
+/// `stream_send_hotkey` in stream mode; nothing in base mode, where the
+/// duration hotkeys send instead.
+pub fn stream_shortcut(config: &AppConfig) -> Option<String> {
+    if config.stream_mode != StreamMode::Stream {
+        return None;
+    }
+    normalize_accelerator(&config.stream_send_hotkey)
+}
+
+/// Accelerators to register for `durations`, in list order, skipping empty keys and
+/// any accelerator already taken by an earlier duration.
+fn duration_entries(durations: &[u32], hotkeys: &BTreeMap<u32, String>) -> Vec<(u32, String)> {
+    let mut used = HashSet::new();
+    durations
+        .iter()
+        .filter_map(|duration| {
+            let accelerator = normalize_accelerator(hotkeys.get(duration)?)?;
+            used.insert(accelerator.clone())
+                .then_some((*duration, accelerator))
+        })
+        .collect()
+}
+
+fn register_duration<R: Runtime>(
+    manager: &GlobalShortcut<R>,
+    seconds: u32,
+    accelerator: &str,
+) -> Result<(), tauri_plugin_global_shortcut::Error> {
+    manager.on_shortcut(accelerator, move |app_handle, _, _| {
+        on_duration_pressed(app_handle, seconds);
+    })
+}
+
+/// Emits `hotkeys:toggle-input` as the toggle hotkey does.
+pub fn on_toggle_pressed<R: Runtime>(app: &AppHandle<R>) {
+    let ids = session::begin_interaction(app, InteractionTrigger::ToggleInput, None);
+    let _ = emit_event(
+        app,
+        events::HOTKEYS_TOGGLE_INPUT,
+        json!({
+            "sessionId": ids.as_ref().map(|ids| &ids.session_id),
+            "interactionId": ids.as_ref().map(|ids| &ids.interaction_id),
+        }),
+    );
+}
+
+/// Sends the press on, or queues it while a transcription is still running.
+pub fn on_duration_pressed<R: Runtime>(app: &AppHandle<R>, seconds: u32) {
+    let busy = is_transcribing(app);
+    let hotkeys = app.try_state::<Arc<HotkeyManager>>();
+    let (true, Some(hotkeys)) = (busy, hotkeys) else {
+        emit_duration(app, seconds, Local::now().timestamp_millis());
+        return;
+    };
+    let (position, coalesced) = hotkeys.enqueue(seconds);
+    log::info!(
+        target: "hotkeys",
+        "Queued {seconds}s press at position {position} (coalesced={coalesced})"
+    );
+    let _ = emit_event(
+        app,
+        events::HOTKEYS_DURATION_QUEUED,
+        json!({
+            "sec": seconds,
+            "position": position,
+            "coalesced": coalesced,
+        }),
+    );
+    // The transcription may have finished, and found the queue empty, between
+    // the check above and the push.
+    if !is_transcribing(app) {
+        dispatch_pending(app);
+    }
+}
+
+fn is_transcribing<R: Runtime>(app: &AppHandle<R>) -> bool {
+    app.try_state::<Arc<TranscriptionManager>>()
+        .is_some_and(|transcriber| transcriber.is_transcribing())
+}
+
+/// `pressedAt` is when the key went down, which for a queued press is earlier
+/// than the event.
+fn emit_duration<R: Runtime>(app: &AppHandle<R>, seconds: u32, pressed_at: i64) {
+    let ids = session::begin_interaction(
+        app,
+        InteractionTrigger::DurationHotkey,
+        Some(seconds as f64),
+    );
+    let _ = emit_event(
+        app,
+        events::HOTKEYS_DURATION,
+        json!({
+            "sec": seconds,
+            "pressedAt": pressed_at,
+            "sessionId": ids.as_ref().map(|ids| &ids.session_id),
+            "interactionId": ids.as_ref().map(|ids| &ids.interaction_id),
+        }),
+    );
+}
+
+/// Called by the transcription manager when its last request finishes.
+pub fn notify_transcription_idle() {
+    if let Some(app) = APP.get() {
+        dispatch_pending(app);
+    }
+}
+
+fn dispatch_pending<R: Runtime>(app: &AppHandle<R>) {
+    let Some(hotkeys) = app.try_state::<Arc<HotkeyManager>>() else {
+        return;
+    };
+    let Some(press) = hotkeys.pending.lock().unwrap().pop_front() else {
+        return;
+    };
+    let dispatch = hotkeys.dispatches.fetch_add(1, Ordering::SeqCst) + 1;
+    emit_duration(app, press.seconds, press.pressed_at);
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(PENDING_STALL_TIMEOUT).await;
+        let Some(hotkeys) = app.try_state::<Arc<HotkeyManager>>() else {
+            return;
+        };
+        if !is_transcribing(&app) && hotkeys.dispatches.load(Ordering::SeqCst) == dispatch {
+            dispatch_pending(&app);
+        }
+    });
+}
+
+/// Parses a stored hotkey the way it will be registered. `Ok(None)` for an empty key,
+/// `Err(())` when the global shortcut plugin would reject it.
+pub fn parse_hotkey(key: &str) -> Result<Option<Shortcut>, ()> {
+    match normalize_accelerator(key) {
+        None => Ok(None),
+        Some(accelerator) => Shortcut::from_str(&accelerator).map(Some).map_err(|_| ()),
+    }
+}
+
 fn normalize_accelerator(key: &str) -> Option<String> {
     let trimmed = key.trim();
     if trimmed.is_empty() {
@@ -95,3 +547,57 @@ fn normalize_accelerator(key: &str) -> Option<String> {
     }
     Some(accelerator)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(stream_mode: StreamMode) -> AppConfig {
+        AppConfig {
+            durations: vec![5, 10, 30],
+            duration_hotkeys: BTreeMap::from([
+                (5, "1".to_string()),
+                (10, "2".to_string()),
+                (30, " ".to_string()),
+            ]),
+            toggle_input_hotkey: "g".to_string(),
+            stream_send_hotkey: "Enter".to_string(),
+            private_session_hotkey: String::new(),
+            stream_mode,
+            ..AppConfig::default()
+        }
+    }
+
+    #[test]
+    fn stream_mode_registers_the_send_hotkey() {
+        assert_eq!(
+            Shortcuts::of(&config(StreamMode::Stream)),
+            Shortcuts {
+                durations: vec![(5, "Ctrl+1".to_string()), (10, "Ctrl+2".to_string())],
+                toggle: Some("Ctrl+G".to_string()),
+                stream: Some("Ctrl+Enter".to_string()),
+                private_session: None,
+            }
+        );
+    }
+
+    #[test]
+    fn base_mode_drops_the_send_hotkey() {
+        let base = Shortcuts::of(&config(StreamMode::Base));
+        assert_eq!(base.stream, None);
+        assert_eq!(
+            base,
+            Shortcuts {
+                stream: None,
+                ..Shortcuts::of(&config(StreamMode::Stream))
+            }
+        );
+    }
+
+    #[test]
+    fn an_empty_send_hotkey_registers_nothing_in_stream_mode() {
+        let mut config = config(StreamMode::Stream);
+        config.stream_send_hotkey = "  ".to_string();
+        assert_eq!(stream_shortcut(&config), None);
+    }
+}
@@ -0,0 +1,310 @@
+//! Names and plain descriptions for the HRESULTs core audio returns, and a short
+//! history of the WASAPI failures seen this run. Codes are plain `i32`s so the
+//! table is available on every platform, e.g. for decoding a pasted log line.
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use chrono::Local;
+use serde::Serialize;
+
+const MAX_RECENT_FAILURES: usize = 20;
+
+static RECENT: Mutex<VecDeque<HresultFailure>> = Mutex::new(VecDeque::new());
+
+const KNOWN: &[(u32, &str, &str)] = &[
+    (
+        0x8889_0001,
+        "AUDCLNT_E_NOT_INITIALIZED",
+        "The audio stream was used before it was initialized.",
+    ),
+    (
+        0x8889_0002,
+        "AUDCLNT_E_ALREADY_INITIALIZED",
+        "The audio stream is already initialized.",
+    ),
+    (
+        0x8889_0003,
+        "AUDCLNT_E_WRONG_ENDPOINT_TYPE",
+        "The device is the wrong kind (capture vs. playback) for this operation.",
+    ),
+    (
+        0x8889_0004,
+        "AUDCLNT_E_DEVICE_INVALIDATED",
+        "The audio device was unplugged, disabled or reconfigured.",
+    ),
+    (
+        0x8889_0005,
+        "AUDCLNT_E_NOT_STOPPED",
+        "The audio stream must be stopped first.",
+    ),
+    (
+        0x8889_0006,
+        "AUDCLNT_E_BUFFER_TOO_LARGE",
+        "More audio was requested than the buffer holds.",
+    ),
+    (
+        0x8889_0007,
+        "AUDCLNT_E_OUT_OF_ORDER",
+        "Buffer calls were made out of order.",
+    ),
+    (
+        0x8889_0008,
+        "AUDCLNT_E_UNSUPPORTED_FORMAT",
+        "The device does not support this audio format.",
+    ),
+    (
+        0x8889_0009,
+        "AUDCLNT_E_INVALID_SIZE",
+        "The buffer size is invalid.",
+    ),
+    (
+        0x8889_000A,
+        "AUDCLNT_E_DEVICE_IN_USE",
+        "Another application has the device in exclusive mode.",
+    ),
+    (
+        0x8889_000B,
+        "AUDCLNT_E_BUFFER_OPERATION_PENDING",
+        "A buffer operation is still pending.",
+    ),
+    (
+        0x8889_000C,
+        "AUDCLNT_E_THREAD_NOT_REGISTERED",
+        "The thread is not registered for audio.",
+    ),
+    (
+        0x8889_000E,
+        "AUDCLNT_E_EXCLUSIVE_MODE_NOT_ALLOWED",
+        "Exclusive mode is disabled for this device in the Sound control panel.",
+    ),
+    (
+        0x8889_000F,
+        "AUDCLNT_E_ENDPOINT_CREATE_FAILED",
+        "Windows could not create the audio endpoint; the driver may be faulty.",
+    ),
+    (
+        0x8889_0010,
+        "AUDCLNT_E_SERVICE_NOT_RUNNING",
+        "The Windows Audio service is not running.",
+    ),
+    (
+        0x8889_0011,
+        "AUDCLNT_E_EVENTHANDLE_NOT_EXPECTED",
+        "An event handle was set on a stream not created for event callbacks.",
+    ),
+    (
+        0x8889_0012,
+        "AUDCLNT_E_EXCLUSIVE_MODE_ONLY",
+        "The device only works in exclusive mode.",
+    ),
+    (
+        0x8889_0013,
+        "AUDCLNT_E_BUFDURATION_PERIOD_NOT_EQUAL",
+        "Buffer duration and period must match in this mode.",
+    ),
+    (
+        0x8889_0014,
+        "AUDCLNT_E_EVENTHANDLE_NOT_SET",
+        "The stream was started without its event handle.",
+    ),
+    (
+        0x8889_0015,
+        "AUDCLNT_E_INCORRECT_BUFFER_SIZE",
+        "The buffer size does not match the device period.",
+    ),
+    (
+        0x8889_0016,
+        "AUDCLNT_E_BUFFER_SIZE_ERROR",
+        "The requested buffer duration is out of range.",
+    ),
+    (
+        0x8889_0017,
+        "AUDCLNT_E_CPUUSAGE_EXCEEDED",
+        "Audio processing exceeded its CPU budget.",
+    ),
+    (
+        0x8889_0018,
+        "AUDCLNT_E_BUFFER_ERROR",
+        "The audio engine could not get a buffer.",
+    ),
+    (
+        0x8889_0019,
+        "AUDCLNT_E_BUFFER_SIZE_NOT_ALIGNED",
+        "The buffer size is not aligned to the device.",
+    ),
+    (
+        0x8889_0020,
+        "AUDCLNT_E_INVALID_DEVICE_PERIOD",
+        "The requested device period is not supported.",
+    ),
+    (
+        0x8889_0026,
+        "AUDCLNT_E_RESOURCES_INVALIDATED",
+        "Audio resources were lost, usually after a device or driver change.",
+    ),
+    (
+        0x0889_0001,
+        "AUDCLNT_S_BUFFER_EMPTY",
+        "No audio is available yet.",
+    ),
+    (
+        0x8000_4002,
+        "E_NOINTERFACE",
+        "The audio interface is not available on this system.",
+    ),
+    (0x8000_4003, "E_POINTER", "A required pointer was null."),
+    (
+        0x8000_4005,
+        "E_FAIL",
+        "The call failed without a specific reason.",
+    ),
+    (0x8000_FFFF, "E_UNEXPECTED", "The call failed unexpectedly."),
+    (
+        0x8001_0106,
+        "RPC_E_CHANGED_MODE",
+        "COM was already initialized on this thread in a different mode.",
+    ),
+    (
+        0x8004_0154,
+        "REGDB_E_CLASSNOTREG",
+        "The audio device enumerator is not registered.",
+    ),
+    (
+        0x8004_01F0,
+        "CO_E_NOTINITIALIZED",
+        "COM was not initialized on this thread.",
+    ),
+    (
+        0x8007_0005,
+        "E_ACCESSDENIED",
+        "Access to the audio device was denied; check the privacy settings.",
+    ),
+    (
+        0x8007_000E,
+        "E_OUTOFMEMORY",
+        "Not enough memory to open the audio stream.",
+    ),
+    (
+        0x8007_0057,
+        "E_INVALIDARG",
+        "An argument to the audio call was invalid.",
+    ),
+    (
+        0x8007_0490,
+        "E_NOTFOUND",
+        "No audio device was found; a default playback device may be missing.",
+    ),
+];
+
+/// One failed call, newest last in `recent()`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HresultFailure {
+    /// What was being done, e.g. `GetBuffer`.
+    pub context: String,
+    /// `0x88890004` style.
+    pub code: String,
+    pub name: Option<&'static str>,
+    pub description: Option<&'static str>,
+    pub timestamp: i64,
+}
+
+/// Symbolic name and description for a known code.
+pub fn lookup(code: i32) -> Option<(&'static str, &'static str)> {
+    KNOWN
+        .iter()
+        .find(|(known, _, _)| *known == code as u32)
+        .map(|(_, name, description)| (*name, *description))
+}
+
+/// `AUDCLNT_E_DEVICE_INVALIDATED (0x88890004): The audio device was ...`, or the
+/// bare hex code when it is not in the table.
+pub fn hresult_to_string(code: i32) -> String {
+    let hex = format!("0x{:08X}", code as u32);
+    match lookup(code) {
+        Some((name, description)) => format!("{name} ({hex}): {description}"),
+        None => format!("HRESULT {hex}"),
+    }
+}
+
+/// Keeps the failure for diagnostics and returns its decoded text.
+#[cfg_attr(not(windows), allow(dead_code))]
+pub fn record(context: &str, code: i32) -> String {
+    let known = lookup(code);
+    let failure = HresultFailure {
+        context: context.to_string(),
+        code: format!("0x{:08X}", code as u32),
+        name: known.map(|(name, _)| name),
+        description: known.map(|(_, description)| description),
+        timestamp: Local::now().timestamp_millis(),
+    };
+    let mut recent = RECENT.lock().unwrap();
+    if recent.len() == MAX_RECENT_FAILURES {
+        recent.pop_front();
+    }
+    recent.push_back(failure);
+    hresult_to_string(code)
+}
+
+pub fn recent() -> Vec<HresultFailure> {
+    RECENT.lock().unwrap().iter().cloned().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn common_core_audio_codes_have_names() {
+        for (code, name) in [
+            (0x8889_0001u32, "AUDCLNT_E_NOT_INITIALIZED"),
+            (0x8889_0004, "AUDCLNT_E_DEVICE_INVALIDATED"),
+            (0x8889_0008, "AUDCLNT_E_UNSUPPORTED_FORMAT"),
+            (0x8889_000A, "AUDCLNT_E_DEVICE_IN_USE"),
+            (0x8889_000E, "AUDCLNT_E_EXCLUSIVE_MODE_NOT_ALLOWED"),
+            (0x8889_0010, "AUDCLNT_E_SERVICE_NOT_RUNNING"),
+            (0x8889_0026, "AUDCLNT_E_RESOURCES_INVALIDATED"),
+            (0x0889_0001, "AUDCLNT_S_BUFFER_EMPTY"),
+            (0x8000_4005, "E_FAIL"),
+            (0x8001_0106, "RPC_E_CHANGED_MODE"),
+            (0x8004_01F0, "CO_E_NOTINITIALIZED"),
+            (0x8007_0005, "E_ACCESSDENIED"),
+            (0x8007_0490, "E_NOTFOUND"),
+        ] {
+            let (found, description) = lookup(code as i32).unwrap();
+            assert_eq!(found, name);
+            assert_eq!(
+                hresult_to_string(code as i32),
+                format!("{name} (0x{code:08X}): {description}")
+            );
+        }
+    }
+
+    #[test]
+    fn unknown_codes_fall_back_to_hex() {
+        assert_eq!(lookup(0x8889_00FFu32 as i32), None);
+        assert_eq!(
+            hresult_to_string(0x8889_00FFu32 as i32),
+            "HRESULT 0x888900FF"
+        );
+        assert_eq!(hresult_to_string(0), "HRESULT 0x00000000");
+    }
+
+    #[test]
+    fn only_the_latest_failures_are_kept() {
+        for attempt in 0..MAX_RECENT_FAILURES + 5 {
+            record(&format!("GetBuffer #{attempt}"), 0x8889_0004u32 as i32);
+        }
+        let recent = recent();
+        assert_eq!(recent.len(), MAX_RECENT_FAILURES);
+        assert_eq!(recent[0].context, "GetBuffer #5");
+        let last = recent.last().unwrap();
+        assert_eq!(
+            last.context,
+            format!("GetBuffer #{}", MAX_RECENT_FAILURES + 4)
+        );
+        assert_eq!(last.code, "0x88890004");
+        assert_eq!(last.name, Some("AUDCLNT_E_DEVICE_INVALIDATED"));
+    }
+}
@@ -0,0 +1,165 @@
+//! Shared reqwest clients. Building a client redoes TLS setup and starts with an
+//! empty connection pool, so commands reuse these instead of building their own.
+//! Remote clients go through the configured proxy and are rebuilt when it changes;
+//! clients for the local servers never use it.
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use anyhow::{anyhow, Context, Result};
+use reqwest::Client;
+
+use crate::types::AppConfig;
+
+// Whole-request limits; callers with their own limit set it per request.
+const HEALTH_TIMEOUT: Duration = Duration::from_secs(5);
+const API_TIMEOUT: Duration = Duration::from_secs(30);
+const UPLOAD_TIMEOUT: Duration = Duration::from_secs(300);
+
+struct RemoteClients {
+    proxy: Option<String>,
+    // Counts rebuilds, for the log.
+    generation: u64,
+    api: Client,
+    upload: Client,
+}
+
+pub struct HttpClients {
+    health: Client,
+    local: Client,
+    remote: Mutex<Option<RemoteClients>>,
+}
+
+impl HttpClients {
+    pub fn new() -> Result<Self> {
+        Ok(Self {
+            health: Client::builder().timeout(HEALTH_TIMEOUT).build()?,
+            local: Client::builder().timeout(UPLOAD_TIMEOUT).build()?,
+            remote: Mutex::new(None),
+        })
+    }
+
+    /// Short-timeout client for polling the local servers.
+    pub fn health(&self) -> Client {
+        self.health.clone()
+    }
+
+    /// Long-timeout client for the local transcription and LLM servers.
+    pub fn local(&self) -> Client {
+        self.local.clone()
+    }
+
+    /// Client for backend API and auth calls.
+    pub fn api(&self, config: &AppConfig) -> Result<Client> {
+        self.remote(config, |clients| clients.api.clone())
+    }
+
+    /// Long-timeout client for provider transcription and downloads.
+    pub fn upload(&self, config: &AppConfig) -> Result<Client> {
+        self.remote(config, |clients| clients.upload.clone())
+    }
+
+    /// Rebuilds the remote clients now if `config` changed what they depend on.
+    pub fn sync(&self, config: &AppConfig) {
+        if let Err(error) = self.remote(config, |_| ()) {
+            log::warn!(target: "http", "HTTP clients not rebuilt: {error:#}");
+        }
+    }
+
+    fn remote<T>(&self, config: &AppConfig, pick: impl FnOnce(&RemoteClients) -> T) -> Result<T> {
+        let mut guard = self
+            .remote
+            .lock()
+            .map_err(|_| anyhow!("HTTP client lock poisoned"))?;
+        let Some(generation) = rebuild_generation(guard.as_ref(), &config.http_proxy) else {
+            return Ok(pick(guard.as_ref().expect("clients to reuse")));
+        };
+        let clients = RemoteClients {
+            proxy: config.http_proxy.clone(),
+            generation,
+            api: remote_client(config, API_TIMEOUT)?,
+            upload: remote_client(config, UPLOAD_TIMEOUT)?,
+        };
+        log::info!(
+            target: "http",
+            "Remote HTTP clients built: generation={} proxy={}",
+            clients.generation,
+            config.http_proxy.is_some()
+        );
+        let picked = pick(&clients);
+        *guard = Some(clients);
+        Ok(picked)
+    }
+}
+
+/// The generation to build for `proxy`, or `None` when `current` already goes
+/// through it.
+fn rebuild_generation(current: Option<&RemoteClients>, proxy: &Option<String>) -> Option<u64> {
+    match current {
+        Some(clients) if clients.proxy == *proxy => None,
+        current => Some(current.map_or(0, |clients| clients.generation) + 1),
+    }
+}
+
+fn remote_client(config: &AppConfig, timeout: Duration) -> Result<Client> {
+    let mut builder = Client::builder().timeout(timeout);
+    if let Some(proxy) = config.http_proxy.as_deref() {
+        builder = builder.proxy(reqwest::Proxy::all(proxy).context("invalid proxy URL")?);
+    }
+    Ok(builder.build()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROXY: &str = "http://127.0.0.1:3128";
+
+    fn generation(clients: &HttpClients, proxy: Option<&str>) -> u64 {
+        let config = AppConfig {
+            http_proxy: proxy.map(str::to_string),
+            ..AppConfig::default()
+        };
+        clients
+            .remote(&config, |clients| clients.generation)
+            .unwrap()
+    }
+
+    #[test]
+    fn rebuilds_only_when_the_proxy_changes() {
+        let remote = |proxy: Option<&str>, generation| RemoteClients {
+            proxy: proxy.map(str::to_string),
+            generation,
+            api: Client::new(),
+            upload: Client::new(),
+        };
+        let proxy = Some(PROXY.to_string());
+        assert_eq!(rebuild_generation(None, &None), Some(1));
+        assert_eq!(rebuild_generation(None, &proxy), Some(1));
+        assert_eq!(rebuild_generation(Some(&remote(None, 1)), &None), None);
+        assert_eq!(
+            rebuild_generation(Some(&remote(Some(PROXY), 2)), &proxy),
+            None
+        );
+        assert_eq!(rebuild_generation(Some(&remote(None, 1)), &proxy), Some(2));
+        assert_eq!(
+            rebuild_generation(Some(&remote(Some(PROXY), 2)), &None),
+            Some(3)
+        );
+        let other = Some("http://10.0.0.1:8080".to_string());
+        assert_eq!(
+            rebuild_generation(Some(&remote(Some(PROXY), 2)), &other),
+            Some(3)
+        );
+    }
+
+    #[test]
+    fn reuses_the_remote_clients_until_the_proxy_changes() {
+        let clients = HttpClients::new().unwrap();
+        assert_eq!(generation(&clients, None), 1);
+        assert_eq!(generation(&clients, None), 1);
+        assert_eq!(generation(&clients, Some(PROXY)), 2);
+        assert_eq!(generation(&clients, Some(PROXY)), 2);
+        assert_eq!(generation(&clients, None), 3);
+    }
+}
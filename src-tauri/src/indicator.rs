@@ -0,0 +1,137 @@
+//! Small "REC" window shown while capture runs (`show_recording_indicator`). It is
+//! click-through, never takes focus and, like the main window in hide mode, is kept
+//! out of screen capture.
+
+use tauri::{
+    AppHandle, Manager, PhysicalPosition, WebviewUrl, WebviewWindow, WebviewWindowBuilder,
+};
+
+use crate::audio::AudioManager;
+use crate::types::{AppConfig, IndicatorCorner};
+
+const INDICATOR_LABEL: &str = "indicator";
+const INDICATOR_PAGE: &str = "indicator.html";
+const INDICATOR_WIDTH: f64 = 64.0;
+const INDICATOR_HEIGHT: f64 = 24.0;
+// Distance from the screen edges, in logical pixels.
+const INDICATOR_MARGIN: f64 = 12.0;
+
+/// Shows the indicator while capture is active and the preference is on, and
+/// closes it otherwise.
+pub fn sync(app: &AppHandle, config: &AppConfig) {
+    let capturing = app
+        .try_state::<std::sync::Arc<AudioManager>>()
+        .is_some_and(|manager| manager.status().active);
+    if capturing && config.show_recording_indicator {
+        show(app, config.recording_indicator_corner);
+    } else {
+        hide(app);
+    }
+}
+
+pub fn show(app: &AppHandle, corner: IndicatorCorner) {
+    let window = match app.get_webview_window(INDICATOR_LABEL) {
+        Some(window) => window,
+        None => match build(app) {
+            Ok(window) => window,
+            Err(error) => {
+                log::warn!(target: "indicator", "failed to create indicator: {error}");
+                return;
+            }
+        },
+    };
+    if let Err(error) = place(&window, corner) {
+        log::warn!(target: "indicator", "failed to position indicator: {error}");
+    }
+    if let Err(error) = window.show() {
+        log::warn!(target: "indicator", "failed to show indicator: {error}");
+    }
+}
+
+pub fn hide(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window(INDICATOR_LABEL) {
+        if let Err(error) = window.destroy() {
+            log::warn!(target: "indicator", "failed to close indicator: {error}");
+        }
+    }
+}
+
+fn build(app: &AppHandle) -> tauri::Result<WebviewWindow> {
+    let window =
+        WebviewWindowBuilder::new(app, INDICATOR_LABEL, WebviewUrl::App(INDICATOR_PAGE.into()))
+            .title("REC")
+            .inner_size(INDICATOR_WIDTH, INDICATOR_HEIGHT)
+            .resizable(false)
+            .decorations(false)
+            .transparent(true)
+            .shadow(false)
+            .always_on_top(true)
+            .visible_on_all_workspaces(true)
+            .skip_taskbar(true)
+            .focused(false)
+            .focusable(false)
+            .visible(false)
+            .build()?;
+    window.set_ignore_cursor_events(true)?;
+    exclude_from_capture(&window);
+    Ok(window)
+}
+
+/// Moves the window into `corner` of the primary monitor.
+fn place(window: &WebviewWindow, corner: IndicatorCorner) -> tauri::Result<()> {
+    let Some(monitor) = window.primary_monitor()? else {
+        return Ok(());
+    };
+    let scale = monitor.scale_factor();
+    let origin = monitor.position();
+    let screen = monitor.size();
+    let width = (INDICATOR_WIDTH * scale) as i32;
+    let height = (INDICATOR_HEIGHT * scale) as i32;
+    let margin = (INDICATOR_MARGIN * scale) as i32;
+    let left = origin.x + margin;
+    let right = origin.x + screen.width as i32 - width - margin;
+    let top = origin.y + margin;
+    let bottom = origin.y + screen.height as i32 - height - margin;
+    let (x, y) = match corner {
+        IndicatorCorner::TopLeft => (left, top),
+        IndicatorCorner::BottomLeft => (left, bottom),
+        IndicatorCorner::BottomRight => (right, bottom),
+        IndicatorCorner::TopRight | IndicatorCorner::Unknown => (right, top),
+    };
+    window.set_position(PhysicalPosition::new(x, y))
+}
+
+/// Same affinity the main window gets in hide mode, plus a no-activate style so
+/// showing the window never moves focus.
+#[cfg(windows)]
+fn exclude_from_capture(window: &WebviewWindow) {
+    use windows::Win32::Foundation::HWND;
+    use windows::Win32::UI::WindowsAndMessaging::{
+        GetWindowLongPtrW, SetWindowDisplayAffinity, SetWindowLongPtrW, GWL_EXSTYLE,
+        WDA_EXCLUDEFROMCAPTURE, WS_EX_NOACTIVATE, WS_EX_TOOLWINDOW,
+    };
+
+    let hwnd = match window.hwnd() {
+        Ok(hwnd) => HWND(hwnd.0),
+        Err(error) => {
+            log::warn!(target: "indicator", "no window handle: {error}");
+            return;
+        }
+    };
+    unsafe {
+        let ex_style = GetWindowLongPtrW(hwnd, GWL_EXSTYLE)
+            | WS_EX_NOACTIVATE.0 as isize
+            | WS_EX_TOOLWINDOW.0 as isize;
+        SetWindowLongPtrW(hwnd, GWL_EXSTYLE, ex_style);
+    }
+    if let Err(error) = unsafe { SetWindowDisplayAffinity(hwnd, WDA_EXCLUDEFROMCAPTURE) } {
+        log::warn!(target: "indicator", "SetWindowDisplayAffinity failed: {error}");
+    }
+}
+
+#[cfg(not(windows))]
+fn exclude_from_capture(window: &WebviewWindow) {
+    if let Err(error) = window.set_content_protected(true) {
+        log::warn!(target: "indicator", "failed to protect indicator from capture: {error}");
+    }
+}
@@ -0,0 +1,780 @@
+use std::net::{Ipv4Addr, SocketAddr};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use anyhow::{anyhow, bail, Context, Result};
+use base64::Engine as _;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha1::{Digest, Sha1};
+use tauri::{AppHandle, Manager};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{broadcast, mpsc, watch};
+
+use crate::audio::AudioManager;
+use crate::constants::{MAX_DURATION_SECONDS, MIN_DURATION_SECONDS};
+use crate::events::{self, emit_event};
+use crate::local_speech::FastWhisperManager;
+use crate::transcription::TranscriptionManager;
+use crate::types::{AppConfig, AudioInputType};
+
+const TOKEN_FILE_NAME: &str = "integration-token";
+const WEBSOCKET_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+const MAX_HANDSHAKE_BYTES: usize = 8 * 1024;
+const MAX_MESSAGE_BYTES: usize = 64 * 1024;
+// RFC 6455 caps control frame payloads at 125 bytes.
+const MAX_CONTROL_BYTES: usize = 125;
+const BROADCAST_CAPACITY: usize = 256;
+const STATUS_PATH: &str = "/status";
+
+const OPCODE_CONTINUATION: u8 = 0x0;
+const OPCODE_TEXT: u8 = 0x1;
+const OPCODE_CLOSE: u8 = 0x8;
+const OPCODE_PING: u8 = 0x9;
+const OPCODE_PONG: u8 = 0xA;
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+enum ClientCommand {
+    StartCapture,
+    StopCapture,
+    TriggerDuration { seconds: u32 },
+}
+
+/// Body of `GET /status`; keys are snake_case because overlay scripts poll it directly.
+#[derive(Debug, Serialize)]
+struct StatusSnapshot {
+    capturing: bool,
+    source: Option<AudioInputType>,
+    transcribing: bool,
+    local_speech_running: bool,
+    version: &'static str,
+}
+
+struct RunningServer {
+    port: u16,
+    shutdown: watch::Sender<bool>,
+}
+
+/// Loopback-only WebSocket feed of transcripts, answers, and capture state
+/// for external tools, plus a small authenticated command channel. The same
+/// port answers a plain `GET /status` for pollers that cannot speak WebSocket.
+pub struct IntegrationServer {
+    token: String,
+    events: broadcast::Sender<String>,
+    running: Mutex<Option<RunningServer>>,
+}
+
+impl IntegrationServer {
+    pub async fn load(config_dir: PathBuf) -> Arc<Self> {
+        let token = match load_or_create_token(&config_dir).await {
+            Ok(token) => token,
+            Err(error) => {
+                // Still usable for this run; the token just changes on restart.
+                log::warn!(target: "integration", "Failed to persist integration token: {error}");
+                generate_token()
+            }
+        };
+        let (events, _) = broadcast::channel(BROADCAST_CAPACITY);
+        Arc::new(Self {
+            token,
+            events,
+            running: Mutex::new(None),
+        })
+    }
+
+    pub fn token(&self) -> String {
+        self.token.clone()
+    }
+
+    /// Starts, stops, or rebinds the server to match the config.
+    pub fn apply_config(self: &Arc<Self>, app: &AppHandle, config: &AppConfig) {
+        let desired = config
+            .integration_server_enabled
+            .then_some(config.integration_server_port);
+        let mut running = self.running.lock().unwrap();
+        if running.as_ref().map(|server| server.port) == desired {
+            return;
+        }
+        if let Some(server) = running.take() {
+            let _ = server.shutdown.send(true);
+            log::info!(target: "integration", "Integration server stopped: port={}", server.port);
+        }
+        let Some(port) = desired else {
+            return;
+        };
+        let listener = match std::net::TcpListener::bind((Ipv4Addr::LOCALHOST, port))
+            .and_then(|listener| listener.set_nonblocking(true).map(|_| listener))
+        {
+            Ok(listener) => listener,
+            Err(error) => {
+                log::error!(target: "integration", "Failed to bind integration server on port {port}: {error}");
+                return;
+            }
+        };
+        let (shutdown, shutdown_rx) = watch::channel(false);
+        *running = Some(RunningServer { port, shutdown });
+        log::info!(target: "integration", "Integration server listening on ws://127.0.0.1:{port}");
+        tauri::async_runtime::spawn(accept_loop(
+            app.clone(),
+            self.clone(),
+            listener,
+            shutdown_rx,
+        ));
+    }
+
+    pub fn publish(&self, message: Value) {
+        // No receivers simply means no client is connected.
+        let _ = self.events.send(message.to_string());
+    }
+}
+
+pub fn publish_transcript(app: &AppHandle, text: &str, speaker: Option<&str>) {
+    publish(
+        app,
+        json!({ "type": "transcript", "text": text, "speaker": speaker }),
+    );
+}
+
+pub fn publish_answer(app: &AppHandle, text: &str) {
+    publish(app, json!({ "type": "answer", "text": text }));
+}
+
+pub fn publish_capture_state(app: &AppHandle, active: bool, source: Option<&str>) {
+    publish(
+        app,
+        json!({ "type": "capture", "active": active, "source": source }),
+    );
+}
+
+fn publish(app: &AppHandle, message: Value) {
+    if let Some(server) = app.try_state::<Arc<IntegrationServer>>() {
+        server.publish(message);
+    }
+}
+
+async fn accept_loop(
+    app: AppHandle,
+    server: Arc<IntegrationServer>,
+    listener: std::net::TcpListener,
+    mut shutdown: watch::Receiver<bool>,
+) {
+    let listener = match TcpListener::from_std(listener) {
+        Ok(listener) => listener,
+        Err(error) => {
+            log::error!(target: "integration", "Failed to start integration listener: {error}");
+            return;
+        }
+    };
+    loop {
+        tokio::select! {
+            _ = shutdown.changed() => return,
+            accepted = listener.accept() => match accepted {
+                Ok((stream, peer)) => {
+                    let app = app.clone();
+                    let server = server.clone();
+                    let shutdown = shutdown.clone();
+                    tauri::async_runtime::spawn(async move {
+                        if let Err(error) = handle_connection(app, server, stream, peer, shutdown).await {
+                            log::debug!(target: "integration", "Integration client {peer} closed: {error}");
+                        }
+                    });
+                }
+                Err(error) => {
+                    log::warn!(target: "integration", "Integration accept failed: {error}");
+                }
+            },
+        }
+    }
+}
+
+async fn handle_connection(
+    app: AppHandle,
+    server: Arc<IntegrationServer>,
+    mut stream: TcpStream,
+    peer: SocketAddr,
+    mut shutdown: watch::Receiver<bool>,
+) -> Result<()> {
+    if !peer.ip().is_loopback() {
+        bail!("refused non-loopback peer");
+    }
+    let head = read_request_head(&mut stream).await?;
+    if is_status_request(&head) {
+        return write_status(&app, &mut stream).await;
+    }
+    let key = match validate_handshake(&head, &server.token) {
+        Ok(key) => key,
+        Err((status, error)) => {
+            let response =
+                format!("HTTP/1.1 {status}\r\nConnection: close\r\nContent-Length: 0\r\n\r\n");
+            let _ = stream.write_all(response.as_bytes()).await;
+            return Err(error);
+        }
+    };
+    let response = format!(
+        "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+        accept_key(&key)
+    );
+    stream.write_all(response.as_bytes()).await?;
+    log::info!(target: "integration", "Integration client connected: {peer}");
+
+    let (mut reader, mut writer) = stream.into_split();
+    let mut events = server.events.subscribe();
+    let (reply_tx, mut reply_rx) = mpsc::unbounded_channel::<(u8, Vec<u8>)>();
+
+    let write_task = async move {
+        loop {
+            let (opcode, payload) = tokio::select! {
+                _ = shutdown.changed() => (OPCODE_CLOSE, Vec::new()),
+                reply = reply_rx.recv() => match reply {
+                    Some(reply) => reply,
+                    None => return Ok(()),
+                },
+                event = events.recv() => match event {
+                    Ok(text) => (OPCODE_TEXT, text.into_bytes()),
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        log::warn!(target: "integration", "Integration client lagged; dropped {skipped} messages");
+                        continue;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => (OPCODE_CLOSE, Vec::new()),
+                },
+            };
+            write_frame(&mut writer, opcode, &payload).await?;
+            if opcode == OPCODE_CLOSE {
+                return Ok::<(), anyhow::Error>(());
+            }
+        }
+    };
+
+    let read_task = async move {
+        loop {
+            match read_message(&mut reader, &reply_tx).await? {
+                Incoming::Text(text) => {
+                    let reply = handle_command(&app, &text);
+                    let _ = reply_tx.send((OPCODE_TEXT, reply.to_string().into_bytes()));
+                }
+                Incoming::Close => {
+                    let _ = reply_tx.send((OPCODE_CLOSE, Vec::new()));
+                    return Ok::<(), anyhow::Error>(());
+                }
+            }
+        }
+    };
+
+    tokio::pin!(write_task);
+    tokio::select! {
+        result = &mut write_task => result,
+        result = read_task => {
+            // Let queued replies, including the close frame, go out before the socket drops.
+            let _ = write_task.await;
+            result
+        }
+    }
+}
+
+fn is_status_request(head: &str) -> bool {
+    head.lines()
+        .next()
+        .and_then(|line| line.strip_prefix("GET "))
+        .and_then(|rest| rest.split_whitespace().next())
+        .map(|target| target.split('?').next() == Some(STATUS_PATH))
+        .unwrap_or(false)
+}
+
+async fn write_status(app: &AppHandle, stream: &mut TcpStream) -> Result<()> {
+    // Each manager is read through its own short accessor, so no lock is held
+    // while the snapshot is serialized and written.
+    let audio = app
+        .try_state::<Arc<AudioManager>>()
+        .map(|manager| manager.status());
+    let transcribing = app
+        .try_state::<Arc<TranscriptionManager>>()
+        .map(|manager| manager.is_transcribing())
+        .unwrap_or(false);
+    let local_speech_running = match app.try_state::<Arc<FastWhisperManager>>() {
+        Some(manager) => manager.get_status().await.running,
+        None => false,
+    };
+    let snapshot = StatusSnapshot {
+        capturing: audio.as_ref().map(|status| status.active).unwrap_or(false),
+        source: audio.and_then(|status| status.source),
+        transcribing,
+        local_speech_running,
+        version: env!("CARGO_PKG_VERSION"),
+    };
+    let body = serde_json::to_string(&snapshot)?;
+    let response = format!(
+        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nCache-Control: no-store\r\nConnection: close\r\nContent-Length: {}\r\n\r\n{}",
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    Ok(())
+}
+
+/// Parses a client command and checks its values against the limits the
+/// matching hotkeys and settings have.
+fn parse_command(text: &str) -> std::result::Result<ClientCommand, String> {
+    let command = serde_json::from_str::<ClientCommand>(text)
+        .map_err(|error| format!("Invalid command: {error}"))?;
+    if let ClientCommand::TriggerDuration { seconds } = command {
+        if !(MIN_DURATION_SECONDS..=MAX_DURATION_SECONDS).contains(&seconds) {
+            return Err(format!(
+                "Invalid command: seconds must be between {MIN_DURATION_SECONDS} and {MAX_DURATION_SECONDS}"
+            ));
+        }
+    }
+    Ok(command)
+}
+
+fn handle_command(app: &AppHandle, text: &str) -> Value {
+    let command = match parse_command(text) {
+        Ok(command) => command,
+        Err(message) => return json!({ "type": "error", "message": message }),
+    };
+    // Recording is driven by the webview, so commands are forwarded to it the same
+    // way global hotkeys are.
+    let (name, result) = match command {
+        ClientCommand::StartCapture => (
+            "startCapture",
+            emit_event(app, events::INTEGRATION_CAPTURE, json!({ "record": true })),
+        ),
+        ClientCommand::StopCapture => (
+            "stopCapture",
+            emit_event(app, events::INTEGRATION_CAPTURE, json!({ "record": false })),
+        ),
+        ClientCommand::TriggerDuration { seconds } => (
+            "triggerDuration",
+            emit_event(app, events::HOTKEYS_DURATION, json!({ "sec": seconds })),
+        ),
+    };
+    log::info!(target: "integration", "Integration command received: {name}");
+    match result {
+        Ok(()) => json!({ "type": "ack", "command": name }),
+        Err(error) => json!({ "type": "error", "command": name, "message": error.to_string() }),
+    }
+}
+
+async fn read_request_head(stream: &mut TcpStream) -> Result<String> {
+    let mut buffer = Vec::new();
+    let mut chunk = [0u8; 1024];
+    while !buffer.windows(4).any(|window| window == b"\r\n\r\n") {
+        if buffer.len() > MAX_HANDSHAKE_BYTES {
+            bail!("handshake too large");
+        }
+        let read = stream.read(&mut chunk).await?;
+        if read == 0 {
+            bail!("connection closed during handshake");
+        }
+        buffer.extend_from_slice(&chunk[..read]);
+    }
+    String::from_utf8(buffer).context("handshake is not UTF-8")
+}
+
+/// Returns the client key, or the HTTP status to reply with.
+fn validate_handshake(
+    head: &str,
+    expected_token: &str,
+) -> std::result::Result<String, (&'static str, anyhow::Error)> {
+    let mut lines = head.split("\r\n");
+    let target = lines
+        .next()
+        .and_then(|line| line.strip_prefix("GET "))
+        .and_then(|rest| rest.split_whitespace().next())
+        .ok_or(("400 Bad Request", anyhow!("not a GET request")))?;
+    let mut key = None;
+    let mut upgrade = false;
+    let mut bearer = None;
+    for line in lines {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        match name.trim().to_ascii_lowercase().as_str() {
+            "upgrade" => upgrade = value.eq_ignore_ascii_case("websocket"),
+            "sec-websocket-key" => key = Some(value.to_string()),
+            "authorization" => bearer = value.strip_prefix("Bearer ").map(str::to_string),
+            _ => {}
+        }
+    }
+    let (Some(key), true) = (key, upgrade) else {
+        return Err((
+            "400 Bad Request",
+            anyhow!("missing WebSocket upgrade headers"),
+        ));
+    };
+    let query_token = url::Url::parse(&format!("http://127.0.0.1{target}"))
+        .ok()
+        .and_then(|url| {
+            url.query_pairs()
+                .find(|(name, _)| name == "token")
+                .map(|(_, value)| value.into_owned())
+        });
+    let provided = bearer.or(query_token).unwrap_or_default();
+    if !constant_time_eq(provided.as_bytes(), expected_token.as_bytes()) {
+        return Err(("401 Unauthorized", anyhow!("invalid integration token")));
+    }
+    Ok(key)
+}
+
+fn accept_key(key: &str) -> String {
+    let mut hasher = Sha1::new();
+    hasher.update(key.as_bytes());
+    hasher.update(WEBSOCKET_GUID.as_bytes());
+    base64::engine::general_purpose::STANDARD.encode(hasher.finalize())
+}
+
+fn constant_time_eq(left: &[u8], right: &[u8]) -> bool {
+    left.len() == right.len()
+        && left
+            .iter()
+            .zip(right)
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
+#[derive(Debug, PartialEq)]
+enum Incoming {
+    Text(String),
+    Close,
+}
+
+/// Reads one complete client message, reassembling fragmented text frames.
+/// Pings, which may arrive between fragments, are answered through `replies`.
+async fn read_message<R: AsyncRead + Unpin>(
+    reader: &mut R,
+    replies: &mpsc::UnboundedSender<(u8, Vec<u8>)>,
+) -> Result<Incoming> {
+    let mut message = Vec::new();
+    let mut in_message = false;
+    loop {
+        let mut header = [0u8; 2];
+        reader.read_exact(&mut header).await?;
+        let fin = header[0] & 0x80 != 0;
+        let opcode = header[0] & 0x0F;
+        if header[1] & 0x80 == 0 {
+            bail!("client frames must be masked");
+        }
+        let length = match header[1] & 0x7F {
+            126 => reader.read_u16().await? as u64,
+            127 => reader.read_u64().await?,
+            length => length as u64,
+        };
+        let limit = if opcode & 0x8 != 0 {
+            if !fin {
+                bail!("control frames must not be fragmented");
+            }
+            MAX_CONTROL_BYTES
+        } else {
+            MAX_MESSAGE_BYTES - message.len()
+        };
+        let length = match usize::try_from(length) {
+            Ok(length) if length <= limit => length,
+            _ => bail!("message too large"),
+        };
+        let mut mask = [0u8; 4];
+        reader.read_exact(&mut mask).await?;
+        let mut payload = vec![0u8; length];
+        reader.read_exact(&mut payload).await?;
+        for (index, byte) in payload.iter_mut().enumerate() {
+            *byte ^= mask[index % 4];
+        }
+
+        match opcode {
+            OPCODE_TEXT | OPCODE_CONTINUATION => {
+                if (opcode == OPCODE_TEXT) == in_message {
+                    bail!(if in_message {
+                        "text frame inside a fragmented message"
+                    } else {
+                        "continuation frame without a message"
+                    });
+                }
+                in_message = !fin;
+                message.extend_from_slice(&payload);
+                if fin {
+                    return String::from_utf8(message)
+                        .map(Incoming::Text)
+                        .context("text frame is not UTF-8");
+                }
+            }
+            OPCODE_PING => {
+                let _ = replies.send((OPCODE_PONG, payload));
+            }
+            OPCODE_PONG => {}
+            OPCODE_CLOSE => return Ok(Incoming::Close),
+            other => bail!("unsupported frame opcode {other}"),
+        }
+    }
+}
+
+async fn write_frame<W: AsyncWrite + Unpin>(
+    writer: &mut W,
+    opcode: u8,
+    payload: &[u8],
+) -> Result<()> {
+    let mut frame = Vec::with_capacity(payload.len() + 10);
+    frame.push(0x80 | opcode);
+    match payload.len() {
+        length if length < 126 => frame.push(length as u8),
+        length if length <= u16::MAX as usize => {
+            frame.push(126);
+            frame.extend_from_slice(&(length as u16).to_be_bytes());
+        }
+        length => {
+            frame.push(127);
+            frame.extend_from_slice(&(length as u64).to_be_bytes());
+        }
+    }
+    frame.extend_from_slice(payload);
+    writer.write_all(&frame).await?;
+    Ok(())
+}
+
+async fn load_or_create_token(config_dir: &std::path::Path) -> Result<String> {
+    let path = config_dir.join(TOKEN_FILE_NAME);
+    if let Ok(existing) = tokio::fs::read_to_string(&path).await {
+        let existing = existing.trim();
+        if !existing.is_empty() {
+            return Ok(existing.to_string());
+        }
+    }
+    let token = generate_token();
+    tokio::fs::write(&path, &token)
+        .await
+        .context("write integration token")?;
+    Ok(token)
+}
+
+fn generate_token() -> String {
+    let mut bytes = [0u8; 24];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::duplex;
+
+    use super::*;
+
+    const TOKEN: &str = "secret-token";
+
+    /// A client frame; client frames are masked unless `mask` is `None`.
+    fn frame(fin: bool, opcode: u8, payload: &[u8], mask: Option<[u8; 4]>) -> Vec<u8> {
+        let mut frame = vec![if fin { 0x80 } else { 0 } | opcode];
+        let masked = if mask.is_some() { 0x80 } else { 0 };
+        match payload.len() {
+            length if length < 126 => frame.push(masked | length as u8),
+            length if length <= u16::MAX as usize => {
+                frame.push(masked | 126);
+                frame.extend_from_slice(&(length as u16).to_be_bytes());
+            }
+            length => {
+                frame.push(masked | 127);
+                frame.extend_from_slice(&(length as u64).to_be_bytes());
+            }
+        }
+        match mask {
+            Some(mask) => {
+                frame.extend_from_slice(&mask);
+                frame.extend(payload.iter().enumerate().map(|(i, b)| b ^ mask[i % 4]));
+            }
+            None => frame.extend_from_slice(payload),
+        }
+        frame
+    }
+
+    fn masked(fin: bool, opcode: u8, payload: &[u8]) -> Vec<u8> {
+        frame(fin, opcode, payload, Some([0x12, 0x34, 0x56, 0x78]))
+    }
+
+    /// Feeds `bytes` through an in-memory duplex and reads one message.
+    async fn read(bytes: Vec<u8>) -> (Result<Incoming>, Vec<(u8, Vec<u8>)>) {
+        let (mut client, mut server) = duplex(256 * 1024);
+        let writer = tokio::spawn(async move {
+            let _ = client.write_all(&bytes).await;
+            client
+        });
+        let (replies, mut replies_rx) = mpsc::unbounded_channel();
+        let result = read_message(&mut server, &replies).await;
+        drop(writer.await.unwrap());
+        drop(replies);
+        let mut sent = Vec::new();
+        while let Some(reply) = replies_rx.recv().await {
+            sent.push(reply);
+        }
+        (result, sent)
+    }
+
+    fn handshake(target: &str, extra: &str) -> String {
+        format!(
+            "GET {target} HTTP/1.1\r\nHost: 127.0.0.1\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n{extra}\r\n"
+        )
+    }
+
+    #[test]
+    fn accept_key_matches_the_rfc_example() {
+        assert_eq!(
+            accept_key("dGhlIHNhbXBsZSBub25jZQ=="),
+            "s3pPLMBiTxaQ9kYGzzhZRbK+xOo="
+        );
+    }
+
+    #[test]
+    fn handshake_takes_the_token_from_the_header_or_the_query() {
+        let bearer = handshake("/", &format!("Authorization: Bearer {TOKEN}\r\n"));
+        assert_eq!(
+            validate_handshake(&bearer, TOKEN).unwrap(),
+            "dGhlIHNhbXBsZSBub25jZQ=="
+        );
+        let query = handshake(&format!("/?token={TOKEN}"), "");
+        assert!(validate_handshake(&query, TOKEN).is_ok());
+    }
+
+    #[test]
+    fn handshake_rejects_wrong_or_missing_tokens_and_plain_requests() {
+        for head in [
+            handshake("/", ""),
+            handshake("/?token=secret", ""),
+            handshake("/", "Authorization: Bearer secret-token-2\r\n"),
+            handshake("/", &format!("Authorization: Basic {TOKEN}\r\n")),
+        ] {
+            let (status, _) = validate_handshake(&head, TOKEN).unwrap_err();
+            assert_eq!(status, "401 Unauthorized", "{head}");
+        }
+        let plain = format!("GET /?token={TOKEN} HTTP/1.1\r\nHost: 127.0.0.1\r\n\r\n");
+        assert_eq!(
+            validate_handshake(&plain, TOKEN).unwrap_err().0,
+            "400 Bad Request"
+        );
+        assert_eq!(
+            validate_handshake("POST / HTTP/1.1\r\n\r\n", TOKEN)
+                .unwrap_err()
+                .0,
+            "400 Bad Request"
+        );
+    }
+
+    #[test]
+    fn status_requests_are_told_apart() {
+        assert!(is_status_request("GET /status HTTP/1.1\r\n\r\n"));
+        assert!(is_status_request("GET /status?x=1 HTTP/1.1\r\n\r\n"));
+        assert!(!is_status_request("GET /statuses HTTP/1.1\r\n\r\n"));
+        assert!(!is_status_request("POST /status HTTP/1.1\r\n\r\n"));
+    }
+
+    #[tokio::test]
+    async fn masked_text_frames_are_unmasked() {
+        let (result, _) = read(masked(true, OPCODE_TEXT, br#"{"type":"startCapture"}"#)).await;
+        assert_eq!(
+            result.unwrap(),
+            Incoming::Text(r#"{"type":"startCapture"}"#.into())
+        );
+        let long = "x".repeat(70_000 - 10_000);
+        let (result, _) = read(masked(true, OPCODE_TEXT, long.as_bytes())).await;
+        assert_eq!(result.unwrap(), Incoming::Text(long));
+    }
+
+    #[tokio::test]
+    async fn unmasked_frames_are_refused() {
+        let (result, _) = read(frame(true, OPCODE_TEXT, b"hi", None)).await;
+        assert!(result.unwrap_err().to_string().contains("masked"));
+    }
+
+    #[tokio::test]
+    async fn oversized_frames_are_refused_before_reading_them() {
+        let big = vec![b'x'; MAX_MESSAGE_BYTES + 1];
+        let (result, _) = read(masked(true, OPCODE_TEXT, &big)).await;
+        assert!(result.unwrap_err().to_string().contains("too large"));
+
+        // A length near u64::MAX after a fragment must not wrap the size check.
+        let mut bytes = masked(false, OPCODE_TEXT, b"abc");
+        bytes.extend_from_slice(&[OPCODE_CONTINUATION | 0x80, 0x80 | 127]);
+        bytes.extend_from_slice(&(u64::MAX - 1).to_be_bytes());
+        let (result, _) = read(bytes).await;
+        assert!(result.unwrap_err().to_string().contains("too large"));
+
+        // Fragments add up against the same limit.
+        let mut bytes = masked(false, OPCODE_TEXT, &vec![b'x'; MAX_MESSAGE_BYTES - 10]);
+        bytes.extend(masked(true, OPCODE_CONTINUATION, &[b'x'; 11]));
+        let (result, _) = read(bytes).await;
+        assert!(result.unwrap_err().to_string().contains("too large"));
+    }
+
+    #[tokio::test]
+    async fn fragments_reassemble_around_an_interleaved_ping() {
+        let mut bytes = masked(false, OPCODE_TEXT, b"{\"type\":");
+        bytes.extend(masked(true, OPCODE_PING, b"are you there"));
+        bytes.extend(masked(false, OPCODE_CONTINUATION, b"\"stop"));
+        bytes.extend(masked(true, OPCODE_PONG, b""));
+        bytes.extend(masked(true, OPCODE_CONTINUATION, b"Capture\"}"));
+        let (result, replies) = read(bytes).await;
+        assert_eq!(
+            result.unwrap(),
+            Incoming::Text(r#"{"type":"stopCapture"}"#.into())
+        );
+        assert_eq!(replies, vec![(OPCODE_PONG, b"are you there".to_vec())]);
+    }
+
+    #[tokio::test]
+    async fn continuation_needs_a_message_and_text_needs_none() {
+        let (result, _) = read(masked(true, OPCODE_CONTINUATION, b"x")).await;
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("without a message"));
+
+        let mut bytes = masked(false, OPCODE_TEXT, b"a");
+        bytes.extend(masked(true, OPCODE_TEXT, b"b"));
+        let (result, _) = read(bytes).await;
+        assert!(result.unwrap_err().to_string().contains("inside"));
+    }
+
+    #[tokio::test]
+    async fn control_frames_must_be_small_and_whole() {
+        let (result, _) = read(masked(true, OPCODE_PING, &[0; 126])).await;
+        assert!(result.is_err());
+        let (result, _) = read(masked(false, OPCODE_PING, b"x")).await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn close_ends_the_message_stream() {
+        let mut bytes = masked(false, OPCODE_TEXT, b"half");
+        bytes.extend(masked(true, OPCODE_CLOSE, &1000u16.to_be_bytes()));
+        let (result, _) = read(bytes).await;
+        assert_eq!(result.unwrap(), Incoming::Close);
+    }
+
+    #[tokio::test]
+    async fn server_frames_use_the_shortest_length_encoding() {
+        for (length, header) in [(5, 2), (126, 4), (70_000, 10)] {
+            let (mut server, mut client) = duplex(256 * 1024);
+            let payload = vec![b'y'; length];
+            write_frame(&mut server, OPCODE_TEXT, &payload)
+                .await
+                .unwrap();
+            drop(server);
+            let mut bytes = Vec::new();
+            client.read_to_end(&mut bytes).await.unwrap();
+            assert_eq!(bytes.len(), header + length);
+            assert_eq!(bytes[0], 0x80 | OPCODE_TEXT);
+            assert_eq!(bytes[1] & 0x80, 0, "server frames are not masked");
+        }
+    }
+
+    #[test]
+    fn trigger_duration_uses_the_hotkey_bounds() {
+        assert!(matches!(
+            parse_command(r#"{"type":"triggerDuration","seconds":30}"#),
+            Ok(ClientCommand::TriggerDuration { seconds: 30 })
+        ));
+        for seconds in [0, MAX_DURATION_SECONDS + 1, u32::MAX] {
+            let text = format!(r#"{{"type":"triggerDuration","seconds":{seconds}}}"#);
+            assert!(parse_command(&text).is_err(), "{seconds}");
+        }
+        assert!(parse_command(r#"{"type":"reboot"}"#).is_err());
+    }
+}
@@ -0,0 +1,247 @@
+//! `api_key_test`: checks a provider key with the cheapest authenticated call
+//! (listing models) without saving it. Keys never reach the log, and Google gets
+//! its key in a header so it cannot show up in a URL inside an error message.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use once_cell::sync::Lazy;
+use reqwest::{Response, StatusCode};
+use serde::Serialize;
+use serde_json::{json, Value};
+use tauri::{AppHandle, Manager};
+
+use crate::config::ConfigState;
+use crate::constants::API_KEY_TEST_INTERVAL_SECS;
+use crate::errors::{CommandError, CommandResult, ErrorCode};
+use crate::http::HttpClients;
+use crate::types::AppConfig;
+
+const OPENAI_MODELS_URL: &str = "https://api.openai.com/v1/models";
+const GOOGLE_MODELS_URL: &str = "https://generativelanguage.googleapis.com/v1beta/models";
+const OPENAI_KEY_PREFIX: &str = "sk-";
+const GOOGLE_KEY_PREFIX: &str = "AIza";
+
+/// When each provider was last tested, for the local rate limit.
+static LAST_TEST: Lazy<Mutex<HashMap<Provider, Instant>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Provider {
+    OpenAi,
+    Google,
+}
+
+impl Provider {
+    fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "openai" => Some(Self::OpenAi),
+            "google" | "gemini" => Some(Self::Google),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::OpenAi => "OpenAI",
+            Self::Google => "Google",
+        }
+    }
+
+    fn stored_key(self, config: &AppConfig) -> Option<String> {
+        match self {
+            Self::OpenAi => config.openai_api_key.clone(),
+            Self::Google => config.google_api_key.clone(),
+        }
+    }
+
+    /// The other provider, when `key` has its prefix.
+    fn mismatch(self, key: &str) -> Option<Self> {
+        match self {
+            Self::OpenAi if key.starts_with(GOOGLE_KEY_PREFIX) => Some(Self::Google),
+            Self::Google if key.starts_with(OPENAI_KEY_PREFIX) => Some(Self::OpenAi),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyTestError {
+    Missing,
+    WrongProvider,
+    InvalidKey,
+    /// The key authenticated but may not list models, e.g. the API is not
+    /// enabled for its project.
+    Forbidden,
+    RateLimited,
+    Network,
+    Unexpected,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyOrgInfo {
+    /// OpenAI organization the key belongs to.
+    pub organization: Option<String>,
+    /// OpenAI project of a project key.
+    pub project: Option<String>,
+    /// Models in the first page of the listing.
+    pub model_count: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyTestResult {
+    pub valid: bool,
+    pub error_code: Option<KeyTestError>,
+    pub detail: String,
+    pub org_info: Option<KeyOrgInfo>,
+}
+
+impl KeyTestResult {
+    fn failed(code: KeyTestError, detail: impl Into<String>) -> Self {
+        Self {
+            valid: false,
+            error_code: Some(code),
+            detail: detail.into(),
+            org_info: None,
+        }
+    }
+}
+
+/// Tests `key`, or the stored key for `provider` when none is given.
+pub async fn test_key(
+    app: &AppHandle,
+    provider: &str,
+    key: Option<String>,
+) -> CommandResult<KeyTestResult> {
+    let Some(parsed) = Provider::parse(provider) else {
+        return Err(
+            CommandError::bare(ErrorCode::InvalidArgument, &[("name", "provider")])
+                .with_data(json!({ "provider": provider, "allowed": ["openai", "google"] })),
+        );
+    };
+    throttle(parsed)?;
+    let config = app.state::<Arc<ConfigState>>().get().await;
+    let key = key
+        .or_else(|| parsed.stored_key(&config))
+        .map(|key| key.trim().to_string())
+        .filter(|key| !key.is_empty());
+    let result = match key {
+        None => KeyTestResult::failed(
+            KeyTestError::Missing,
+            format!("No {} API key to test", parsed.name()),
+        ),
+        Some(key) => match parsed.mismatch(&key) {
+            Some(other) => KeyTestResult::failed(
+                KeyTestError::WrongProvider,
+                format!("This looks like a {} key", other.name()),
+            ),
+            None => probe(app, &config, parsed, &key).await?,
+        },
+    };
+    log::info!(
+        target: "key_test",
+        "{} key test: valid={} code={:?}",
+        parsed.name(),
+        result.valid,
+        result.error_code
+    );
+    Ok(result)
+}
+
+/// Claims the provider's test slot, or fails with how long to wait.
+fn throttle(provider: Provider) -> CommandResult<()> {
+    let interval = Duration::from_secs(API_KEY_TEST_INTERVAL_SECS);
+    let now = Instant::now();
+    let mut last = LAST_TEST.lock().unwrap();
+    if let Some(previous) = last.get(&provider) {
+        let elapsed = now.duration_since(*previous);
+        if elapsed < interval {
+            let wait = interval - elapsed;
+            let seconds = wait.as_secs_f64().ceil().to_string();
+            return Err(CommandError::bare(
+                ErrorCode::KeyTestTooSoon,
+                &[("provider", provider.name()), ("seconds", &seconds)],
+            )
+            .with_data(json!({ "retryAfterMs": wait.as_millis() as u64 })));
+        }
+    }
+    last.insert(provider, now);
+    Ok(())
+}
+
+async fn probe(
+    app: &AppHandle,
+    config: &AppConfig,
+    provider: Provider,
+    key: &str,
+) -> CommandResult<KeyTestResult> {
+    let client = app
+        .state::<Arc<HttpClients>>()
+        .api(config)
+        .map_err(|error| CommandError::new(ErrorCode::NetworkFailed, format!("{error:#}")))?;
+    let request = match provider {
+        Provider::OpenAi => client.get(OPENAI_MODELS_URL).bearer_auth(key),
+        Provider::Google => client.get(GOOGLE_MODELS_URL).header("x-goog-api-key", key),
+    };
+    Ok(match request.send().await {
+        Ok(response) => classify(provider, response).await,
+        Err(error) => KeyTestResult::failed(
+            KeyTestError::Network,
+            format!(
+                "{} could not be reached: {}",
+                provider.name(),
+                error.without_url()
+            ),
+        ),
+    })
+}
+
+async fn classify(provider: Provider, response: Response) -> KeyTestResult {
+    let status = response.status();
+    let header = |name: &str| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string)
+    };
+    let organization = header("openai-organization");
+    let project = header("openai-project");
+    let body: Value = response.json().await.unwrap_or(Value::Null);
+
+    if status.is_success() {
+        let models = match provider {
+            Provider::OpenAi => &body["data"],
+            Provider::Google => &body["models"],
+        };
+        return KeyTestResult {
+            valid: true,
+            error_code: None,
+            detail: format!("{} key accepted", provider.name()),
+            org_info: Some(KeyOrgInfo {
+                organization,
+                project,
+                model_count: models.as_array().map(Vec::len),
+            }),
+        };
+    }
+
+    // Both providers wrap errors as `{"error": {"message": ...}}`.
+    let message = body["error"]["message"]
+        .as_str()
+        .map(str::to_string)
+        .unwrap_or_else(|| status.to_string());
+    let code = match status {
+        StatusCode::UNAUTHORIZED => KeyTestError::InvalidKey,
+        // Google answers an unknown key with 400 API_KEY_INVALID.
+        StatusCode::BAD_REQUEST if provider == Provider::Google => KeyTestError::InvalidKey,
+        StatusCode::FORBIDDEN => KeyTestError::Forbidden,
+        StatusCode::TOO_MANY_REQUESTS => KeyTestError::RateLimited,
+        _ => KeyTestError::Unexpected,
+    };
+    KeyTestResult::failed(code, message)
+}
@@ -1,31 +1,157 @@
+use std::fmt;
 use std::fs;
 use std::fs::File;
 use std::future::Future;
-use std::io::Cursor;
 use std::path::{Path, PathBuf};
 use std::process::Stdio;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use anyhow::{anyhow, Result};
+use futures_util::StreamExt;
 use reqwest::StatusCode;
-use tauri::{AppHandle, Emitter, Manager};
-use tokio::io::{AsyncBufReadExt, BufReader};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{AppHandle, Manager};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 use tokio::process::Command;
-use tokio::sync::{mpsc, Mutex};
+use tokio::sync::{mpsc, Mutex, Notify};
 use tokio::task::spawn_blocking;
 use tokio::time::sleep;
 use zip::ZipArchive;
 
 use crate::constants::{
-    FAST_WHISPER_HEALTH_ENDPOINT, FAST_WHISPER_INSTALL_ENV_VAR, FAST_WHISPER_INSTALL_HINT_FILE,
-    FAST_WHISPER_PORT, FAST_WHISPER_REPO_ARCHIVE_URL, FAST_WHISPER_REPO_NAME, FAST_WHISPER_REPO_URL,
+    FAST_WHISPER_ARCHIVE_MAX_BYTES, FAST_WHISPER_HEALTH_ENDPOINT, FAST_WHISPER_INSTALL_ENV_VAR,
+    FAST_WHISPER_INSTALL_HINT_FILE, FAST_WHISPER_PORT, FAST_WHISPER_REPO_ARCHIVE_URL,
+    FAST_WHISPER_REPO_NAME, FAST_WHISPER_REPO_URL, FAST_WHISPER_STATUS_FILE,
+    FAST_WHISPER_VERSION_FILE,
 };
+use crate::config::ConfigState;
+use crate::error_bus;
+use crate::errors::ErrorCode;
+use crate::events::{self, emit_event};
+use crate::http::HttpClients;
 use crate::types::FastWhisperStatus;
 
 const HEALTH_TIMEOUT: Duration = Duration::from_secs(120);
 const HEALTH_INTERVAL: Duration = Duration::from_secs(2);
 const STOP_TIMEOUT: Duration = Duration::from_secs(30);
+// At most five `local-speech:status` events a second, apart from phase changes.
+const STATUS_EMIT_INTERVAL: Duration = Duration::from_millis(200);
+// Background health checks while watched; a server that is down is checked
+// less and less often, up to the backoff cap.
+const WATCH_INTERVAL: Duration = Duration::from_secs(10);
+const WATCH_MAX_BACKOFF: Duration = Duration::from_secs(300);
+const API_PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// One generation of the server's HTTP API and the releases that speak it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ServerApi {
+    /// Reported in the status as `apiVariant`.
+    pub variant: &'static str,
+    /// First release with this API, and the first one without it.
+    since: (u32, u32, u32),
+    until: (u32, u32, u32),
+    pub transcriptions_path: &'static str,
+    pub model_field: &'static str,
+    pub file_field: &'static str,
+    pub prompt_field: &'static str,
+    pub language_field: &'static str,
+}
+
+// Newest first. A server that reports no version gets the first entry whose
+// path it serves, or the first entry when it does not list its paths either.
+const SERVER_APIS: &[ServerApi] = &[ServerApi {
+    variant: "openai-v1",
+    since: (0, 1, 0),
+    until: (2, 0, 0),
+    transcriptions_path: "/v1/audio/transcriptions",
+    model_field: "model",
+    file_field: "file",
+    prompt_field: "prompt",
+    language_field: "language",
+}];
+
+/// The running server reports a version, or routes, that no entry in the
+/// compatibility table covers.
+#[derive(Debug, Clone)]
+pub struct IncompatibleLocalServer {
+    pub server_version: String,
+    /// Supported range, e.g. `>= 0.1.0, < 2.0.0`.
+    pub required: String,
+}
+
+impl fmt::Display for IncompatibleLocalServer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "local speech server {} is not supported ({} is needed); update it with local_speech_reinstall",
+            self.server_version, self.required
+        )
+    }
+}
+
+impl std::error::Error for IncompatibleLocalServer {}
+
+/// What the server said about itself on first contact.
+struct ServerProbe {
+    version: Option<String>,
+    /// Routes from its OpenAPI document, when it serves one.
+    paths: Option<Vec<String>>,
+}
+
+fn required_versions() -> String {
+    let since = SERVER_APIS.iter().map(|api| api.since).min().unwrap_or_default();
+    let until = SERVER_APIS.iter().map(|api| api.until).max().unwrap_or_default();
+    format!(
+        ">= {}.{}.{}, < {}.{}.{}",
+        since.0, since.1, since.2, until.0, until.1, until.2
+    )
+}
+
+/// `1.2.3` out of `v1.2.3-rc1`, `1.2` or `1`.
+fn parse_version(raw: &str) -> Option<(u32, u32, u32)> {
+    let core = raw
+        .trim()
+        .trim_start_matches(['v', 'V'])
+        .split(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .next()?;
+    let mut parts = core.split('.').map(|part| part.parse::<u32>().ok());
+    let major = parts.next()??;
+    let minor = parts.next().flatten().unwrap_or(0);
+    let patch = parts.next().flatten().unwrap_or(0);
+    Some((major, minor, patch))
+}
+
+/// `/version` answers with a JSON object, a JSON string or plain text.
+fn version_from_body(body: &str) -> Option<String> {
+    let version = match serde_json::from_str::<Value>(body) {
+        Ok(Value::Object(object)) => object.get("version")?.as_str()?.to_string(),
+        Ok(Value::String(version)) => version,
+        _ => body.trim().to_string(),
+    };
+    (!version.is_empty() && version.len() <= 64).then_some(version)
+}
+
+fn select_api(probe: &ServerProbe) -> Result<ServerApi, IncompatibleLocalServer> {
+    let version = probe.version.as_deref().and_then(parse_version);
+    SERVER_APIS
+        .iter()
+        .filter(|api| version.is_none_or(|version| api.since <= version && version < api.until))
+        .find(|api| {
+            probe.paths.as_ref().is_none_or(|paths| {
+                paths.iter().any(|path| path == api.transcriptions_path)
+            })
+        })
+        .copied()
+        .ok_or_else(|| IncompatibleLocalServer {
+            server_version: probe
+                .version
+                .clone()
+                .unwrap_or_else(|| "unknown".to_string()),
+            required: required_versions(),
+        })
+}
 #[cfg(windows)]
 const CREATE_NO_WINDOW: u32 = 0x0800_0000;
 
@@ -134,6 +260,44 @@ async fn write_install_hint(app: &AppHandle, path: &Path) -> Result<()> {
     Ok(())
 }
 
+async fn write_snapshot(path: &Path, snapshot: &StatusSnapshot) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    tokio::fs::write(path, serde_json::to_vec(snapshot)?).await?;
+    Ok(())
+}
+
+/// A downloaded file that is deleted once it is no longer needed.
+struct TempArchive(PathBuf);
+
+impl Drop for TempArchive {
+    fn drop(&mut self) {
+        if let Err(error) = fs::remove_file(&self.0) {
+            if error.kind() != std::io::ErrorKind::NotFound {
+                log::warn!(
+                    target: "local-speech",
+                    "failed to remove {}: {error}",
+                    self.0.display()
+                );
+            }
+        }
+    }
+}
+
+/// `main@<commit>`, or just the branch the archive URL names.
+fn repository_version(commit: Option<&str>) -> String {
+    let branch = FAST_WHISPER_REPO_ARCHIVE_URL
+        .rsplit('/')
+        .next()
+        .unwrap_or_default()
+        .trim_end_matches(".zip");
+    match commit {
+        Some(commit) => format!("{branch}@{commit}"),
+        None => branch.to_string(),
+    }
+}
+
 #[cfg(windows)]
 async fn persist_windows_env_var(path: PathBuf) -> Result<()> {
     use winreg::enums::{HKEY_CURRENT_USER, KEY_WRITE};
@@ -151,10 +315,97 @@ async fn persist_windows_env_var(path: PathBuf) -> Result<()> {
     Ok(())
 }
 
+/// Paces `local-speech:status`. pip prints hundreds of lines a second during an
+/// install; updates inside the interval are folded into one trailing emit.
+#[derive(Default)]
+struct StatusThrottle {
+    last_emit: Option<Instant>,
+    last_phase: String,
+    flush_scheduled: bool,
+}
+
+/// What `update_status` does with a new status.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StatusEmit {
+    Now,
+    /// Schedule a flush after this long.
+    Later(Duration),
+    /// A flush is already scheduled and sends this update too.
+    Pending,
+}
+
+impl StatusThrottle {
+    fn on_update(&mut self, phase: &str, now: Instant) -> StatusEmit {
+        match self.last_emit {
+            Some(last)
+                if self.last_phase == phase && now.duration_since(last) < STATUS_EMIT_INTERVAL =>
+            {
+                if self.flush_scheduled {
+                    return StatusEmit::Pending;
+                }
+                self.flush_scheduled = true;
+                StatusEmit::Later(STATUS_EMIT_INTERVAL - now.duration_since(last))
+            }
+            _ => {
+                self.emitted(phase, now);
+                StatusEmit::Now
+            }
+        }
+    }
+
+    fn on_flush(&mut self, phase: &str, now: Instant) {
+        self.flush_scheduled = false;
+        self.emitted(phase, now);
+    }
+
+    fn emitted(&mut self, phase: &str, now: Instant) {
+        self.last_emit = Some(now);
+        self.last_phase = phase.to_string();
+    }
+}
+
+/// Who asked for background health checks (`local_speech_watch`), and whether
+/// the poller task is alive.
+#[derive(Default)]
+struct HealthWatch {
+    watchers: usize,
+    running: bool,
+    shutdown: bool,
+}
+
+/// The part of `FastWhisperStatus` kept in `local-speech-status.json`, so a
+/// restart shows the last known state instead of "not installed".
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StatusSnapshot {
+    installed: bool,
+    phase: String,
+    last_success_at: Option<i64>,
+    install_dir: Option<String>,
+}
+
+impl StatusSnapshot {
+    fn of(status: &FastWhisperStatus) -> Self {
+        Self {
+            installed: status.installed,
+            phase: status.phase.clone(),
+            last_success_at: status.last_success_at,
+            install_dir: status.install_dir.clone(),
+        }
+    }
+}
+
 #[derive(Default)]
 pub struct FastWhisperManager {
     status: Mutex<FastWhisperStatus>,
     lock: Mutex<()>,
+    throttle: std::sync::Mutex<StatusThrottle>,
+    watch: std::sync::Mutex<HealthWatch>,
+    watch_wake: Notify,
+    // What was last written to the snapshot file.
+    snapshot: std::sync::Mutex<Option<StatusSnapshot>>,
+    // Negotiated on first contact after the server came up; cleared when it goes down.
+    api: Mutex<Option<ServerApi>>,
 }
 
 impl FastWhisperManager {
@@ -162,35 +413,239 @@ impl FastWhisperManager {
         Self {
             status: Mutex::new(FastWhisperStatus::new("Local server is not installed.")),
             lock: Mutex::new(()),
+            throttle: std::sync::Mutex::new(StatusThrottle::default()),
+            watch: std::sync::Mutex::new(HealthWatch::default()),
+            watch_wake: Notify::new(),
+            snapshot: std::sync::Mutex::new(None),
+            api: Mutex::new(None),
+        }
+    }
+
+    /// `path` on the server, at the host and port the health checks use.
+    pub fn server_url(path: &str) -> String {
+        format!("http://{}:{}{path}", Self::resolve_host(), Self::resolve_port())
+    }
+
+    /// The API the running server speaks. Asked for on first contact and then
+    /// cached until the server stops; a server that cannot be reached gets the
+    /// newest API, so the request that follows reports the real problem.
+    pub async fn server_api(&self, app: &AppHandle) -> Result<ServerApi, IncompatibleLocalServer> {
+        let mut cached = self.api.lock().await;
+        if let Some(api) = *cached {
+            return Ok(api);
+        }
+        let Some(probe) = self.probe_api(app).await else {
+            return Ok(SERVER_APIS[0]);
+        };
+        let selected = select_api(&probe);
+        if let Ok(api) = selected {
+            *cached = Some(api);
+        }
+        drop(cached);
+        match &selected {
+            Ok(api) => log::info!(
+                target: "local-speech",
+                "Server {} speaks {}",
+                probe.version.as_deref().unwrap_or("(no version)"),
+                api.variant
+            ),
+            Err(error) => log::warn!(target: "local-speech", "{error}"),
+        }
+        let variant = selected.as_ref().ok().map(|api| api.variant.to_string());
+        self.update_status(app, |status| {
+            status.server_version = probe.version.clone();
+            status.api_variant = variant.clone();
+        })
+        .await;
+        selected
+    }
+
+    /// Reads `/version` and the OpenAPI document; `None` when neither answers.
+    async fn probe_api(&self, app: &AppHandle) -> Option<ServerProbe> {
+        let client = app.state::<Arc<HttpClients>>().health();
+        let get = |path: &str| {
+            client
+                .get(Self::server_url(path))
+                .timeout(API_PROBE_TIMEOUT)
+                .send()
+        };
+        let mut reachable = false;
+        let mut version = None;
+        if let Ok(response) = get("/version").await {
+            reachable = true;
+            if response.status().is_success() {
+                version = version_from_body(&response.text().await.unwrap_or_default());
+            }
+        }
+        let mut paths = None;
+        if let Ok(response) = get("/openapi.json").await {
+            reachable = true;
+            if response.status().is_success() {
+                if let Ok(spec) = response.json::<Value>().await {
+                    version = version.or_else(|| {
+                        spec.pointer("/info/version")
+                            .and_then(Value::as_str)
+                            .map(str::to_string)
+                    });
+                    paths = spec
+                        .get("paths")
+                        .and_then(Value::as_object)
+                        .map(|paths| paths.keys().cloned().collect());
+                }
+            }
+        }
+        reachable.then_some(ServerProbe { version, paths })
+    }
+
+    /// Shows the status saved by the last session, marked `stale`, and checks
+    /// the real one in the background. Called once from setup.
+    pub fn restore(self: &Arc<Self>, app: &AppHandle) {
+        let path = self.install_root(app).join(FAST_WHISPER_STATUS_FILE);
+        let snapshot = fs::read_to_string(&path)
+            .ok()
+            .and_then(|contents| serde_json::from_str::<StatusSnapshot>(&contents).ok());
+        if let Some(snapshot) = snapshot {
+            if let Ok(mut status) = self.status.try_lock() {
+                status.installed = snapshot.installed;
+                status.last_success_at = snapshot.last_success_at;
+                status.install_dir = snapshot.install_dir.clone();
+                // An install or start cut short by the exit is not resumed.
+                let (phase, message) = match snapshot.phase.as_str() {
+                    "running" => ("running", "Server is running."),
+                    _ if snapshot.installed => ("idle", "Server is stopped."),
+                    _ => ("not-installed", "Local server is not installed."),
+                };
+                status.running = phase == "running";
+                status.phase = phase.into();
+                status.message = message.into();
+                status.stale = true;
+                log::info!(
+                    target: "local-speech",
+                    "Restored last known status: {} (installed={})",
+                    status.phase,
+                    status.installed
+                );
+            }
+            *self.snapshot.lock().unwrap() = Some(snapshot);
         }
+        let manager = self.clone();
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            manager.check_health(&app).await;
+        });
+    }
+
+    /// Counts a watcher in or out. While at least one is in, a background task
+    /// checks health and emits `local-speech:status` only when the server comes
+    /// up, goes down or appears/disappears on disk. Returns the watcher count.
+    pub fn watch(self: &Arc<Self>, app: &AppHandle, enable: bool) -> usize {
+        let mut watch = self.watch.lock().unwrap();
+        if watch.shutdown {
+            return 0;
+        }
+        if enable {
+            watch.watchers += 1;
+        } else {
+            watch.watchers = watch.watchers.saturating_sub(1);
+        }
+        if watch.watchers > 0 && !watch.running {
+            watch.running = true;
+            let manager = Arc::clone(self);
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move { manager.run_watch(app).await });
+        } else if watch.watchers == 0 {
+            self.watch_wake.notify_one();
+        }
+        watch.watchers
+    }
+
+    /// Ends the background checks for good; called when the app exits.
+    pub fn shutdown_watch(&self) {
+        let mut watch = self.watch.lock().unwrap();
+        watch.shutdown = true;
+        watch.watchers = 0;
+        self.watch_wake.notify_one();
+    }
+
+    async fn run_watch(self: Arc<Self>, app: AppHandle) {
+        log::info!(target: "local-speech", "Health watch started");
+        let mut delay = Duration::ZERO;
+        loop {
+            tokio::select! {
+                _ = sleep(delay) => {}
+                _ = self.watch_wake.notified() => {}
+            }
+            {
+                let mut watch = self.watch.lock().unwrap();
+                if watch.watchers == 0 || watch.shutdown {
+                    watch.running = false;
+                    break;
+                }
+            }
+            // An install, start or stop holds the lock and reports its own
+            // progress; checking now would only contend with it.
+            let Ok(_guard) = self.lock.try_lock() else {
+                delay = WATCH_INTERVAL;
+                continue;
+            };
+            let (installed, healthy) = self.probe_health(&app).await;
+            let current = self.get_status().await;
+            if current.installed != installed || current.running != healthy {
+                self.apply_health(&app, installed, healthy).await;
+            }
+            delay = if healthy {
+                WATCH_INTERVAL
+            } else {
+                (delay * 2).clamp(WATCH_INTERVAL, WATCH_MAX_BACKOFF)
+            };
+        }
+        log::info!(target: "local-speech", "Health watch stopped");
     }
 
     pub async fn get_status(&self) -> FastWhisperStatus {
         self.status.lock().await.clone()
     }
 
+    /// The ref recorded at install, `unknown` for installs older than the
+    /// marker, `None` when the repository is not there.
+    pub async fn installed_version(&self, app: &AppHandle) -> Option<String> {
+        let repo_dir = self.repo_path(app);
+        match tokio::fs::read_to_string(repo_dir.join(FAST_WHISPER_VERSION_FILE)).await {
+            Ok(version) => Some(version.trim().to_string()),
+            Err(_) if repo_dir.exists() => Some("unknown".to_string()),
+            Err(_) => None,
+        }
+    }
+
     pub async fn check_health(self: &Arc<Self>, app: &AppHandle) -> FastWhisperStatus {
+        let (repo_exists, is_healthy) = self.probe_health(app).await;
+        self.apply_health(app, repo_exists, is_healthy).await;
+        self.get_status().await
+    }
+
+    /// Whether the server is installed and answers its health endpoint.
+    async fn probe_health(&self, app: &AppHandle) -> (bool, bool) {
         let repo_exists = self.repo_path(app).exists();
         let health_url = self.health_endpoint();
         
         // Быстрая проверка здоровья сервера
-        let is_healthy = {
-            let client = reqwest::Client::builder()
-                .timeout(Duration::from_secs(2))
-                .build();
-            
-            if let Ok(client) = client {
-                client
-                    .get(&health_url)
-                    .send()
-                    .await
-                    .map(|response| response.status() == StatusCode::OK)
-                    .unwrap_or(false)
-            } else {
-                false
-            }
-        };
+        let is_healthy = app
+            .state::<Arc<HttpClients>>()
+            .health()
+            .get(&health_url)
+            .timeout(Duration::from_secs(2))
+            .send()
+            .await
+            .map(|response| response.status() == StatusCode::OK)
+            .unwrap_or(false);
+        (repo_exists, is_healthy)
+    }
 
+    async fn apply_health(&self, app: &AppHandle, repo_exists: bool, is_healthy: bool) {
+        if !is_healthy {
+            // A server that comes back may be a different release.
+            self.api.lock().await.take();
+        }
         self.update_status(app, |status| {
             status.installed = repo_exists;
             if is_healthy {
@@ -200,6 +655,8 @@ impl FastWhisperManager {
                 status.error = None;
             } else {
                 status.running = false;
+                status.server_version = None;
+                status.api_variant = None;
                 if repo_exists {
                     status.phase = "idle".into();
                     status.message = "Server is stopped.".into();
@@ -210,8 +667,6 @@ impl FastWhisperManager {
             }
         })
         .await;
-
-        self.get_status().await
     }
 
     pub async fn install_and_start(self: &Arc<Self>, app: &AppHandle) -> Result<FastWhisperStatus> {
@@ -341,13 +796,66 @@ impl FastWhisperManager {
         update(&mut guard);
         guard.install_dir = Some(install_dir.to_string_lossy().to_string());
         guard.updated_at = chrono::Utc::now().timestamp_millis();
-        let _ = app.emit("local-speech:status", guard.clone());
+        guard.stale = false;
+        let snapshot = StatusSnapshot::of(&guard);
+        let snapshot_changed = {
+            let mut saved = self.snapshot.lock().unwrap();
+            if saved.as_ref() == Some(&snapshot) {
+                false
+            } else {
+                *saved = Some(snapshot.clone());
+                true
+            }
+        };
+        let emit = self
+            .throttle
+            .lock()
+            .unwrap()
+            .on_update(&guard.phase, Instant::now());
+        match emit {
+            StatusEmit::Now => {
+                let _ = emit_event(app, events::LOCAL_SPEECH_STATUS, guard.clone());
+            }
+            // The scheduled flush sends whatever is current by then.
+            StatusEmit::Pending => {}
+            StatusEmit::Later(delay) => {
+                let app = app.clone();
+                tokio::spawn(async move {
+                    sleep(delay).await;
+                    if let Some(manager) = app.try_state::<Arc<FastWhisperManager>>() {
+                        manager.flush_status(&app).await;
+                    }
+                });
+            }
+        }
+        drop(guard);
+        if snapshot_changed {
+            let path = install_dir.join(FAST_WHISPER_STATUS_FILE);
+            if let Err(error) = write_snapshot(&path, &snapshot).await {
+                log::warn!(
+                    target: "local-speech",
+                    "failed to save status to {}: {error}",
+                    path.display()
+                );
+            }
+        }
+    }
+
+    /// Sends the status held back by `update_status`, so the last state always goes out.
+    async fn flush_status(&self, app: &AppHandle) {
+        let guard = self.status.lock().await;
+        self.throttle
+            .lock()
+            .unwrap()
+            .on_flush(&guard.phase, Instant::now());
+        let _ = emit_event(app, events::LOCAL_SPEECH_STATUS, guard.clone());
     }
 
     async fn ensure_repository(&self, app: &AppHandle, force: bool) -> Result<()> {
         let repo_dir = self.repo_path(app);
-        println!(
-            "[fast-fast-whisper] repository directory: {}",
+        log::info!(
+            target: "local-speech",
+            "repository directory: {}",
             repo_dir.display()
         );
         if force {
@@ -360,10 +868,27 @@ impl FastWhisperManager {
         }
         let install_root = self.install_root(app);
         tokio::fs::create_dir_all(&install_root).await?;
-        let _ = write_install_hint(app, &install_root).await;
+        // The hint and env var only help later launches find the install; report and go on.
+        if let Err(error) = write_install_hint(app, &install_root).await {
+            error_bus::publish(
+                app,
+                "local-speech",
+                ErrorCode::LocalSpeechFailed,
+                error,
+                true,
+            );
+        }
         #[cfg(windows)]
         {
-            let _ = persist_windows_env_var(install_root.clone()).await;
+            if let Err(error) = persist_windows_env_var(install_root.clone()).await {
+                error_bus::publish(
+                    app,
+                    "local-speech",
+                    ErrorCode::LocalSpeechFailed,
+                    error,
+                    true,
+                );
+            }
         }
         self.update_status(app, |state| {
             state.phase = "installing".into();
@@ -371,15 +896,22 @@ impl FastWhisperManager {
             state.message = format!("Downloading repository from {FAST_WHISPER_REPO_URL}…");
         })
         .await;
-        let archive = self.download_repository_archive().await?;
+        // Removed when this goes out of scope, including when the install is cancelled.
+        let archive =
+            TempArchive(install_root.join(format!("{FAST_WHISPER_REPO_NAME}.zip.partial")));
+        self.download_repository_archive(app, &archive.0).await?;
         self.update_status(app, |state| {
             state.message = "Extracting repository…".into();
         })
         .await;
         tokio::fs::create_dir_all(&repo_dir).await?;
         let repo_dir_for_extract = repo_dir.clone();
-        let extraction_result =
-            spawn_blocking(move || Self::extract_repository_archive(archive, repo_dir_for_extract)).await;
+        let archive_path = archive.0.clone();
+        let extraction_result = spawn_blocking(move || {
+            Self::extract_repository_archive(&archive_path, repo_dir_for_extract)
+        })
+        .await;
+        drop(archive);
         let extraction_result = match extraction_result {
             Ok(result) => result,
             Err(join_error) => {
@@ -387,9 +919,19 @@ impl FastWhisperManager {
                 return Err(anyhow!(join_error));
             }
         };
-        if let Err(error) = extraction_result {
-            let _ = tokio::fs::remove_dir_all(&repo_dir).await;
-            return Err(error);
+        let commit = match extraction_result {
+            Ok(commit) => commit,
+            Err(error) => {
+                let _ = tokio::fs::remove_dir_all(&repo_dir).await;
+                return Err(error);
+            }
+        };
+        let version = repository_version(commit.as_deref());
+        log::info!(target: "local-speech", "installed repository version {version}");
+        if let Err(error) =
+            tokio::fs::write(repo_dir.join(FAST_WHISPER_VERSION_FILE), &version).await
+        {
+            log::warn!(target: "local-speech", "failed to record repository version: {error}");
         }
         #[cfg(unix)]
         {
@@ -415,20 +957,59 @@ impl FastWhisperManager {
         Ok(())
     }
 
-    async fn download_repository_archive(&self) -> Result<Vec<u8>> {
-        let response = reqwest::get(FAST_WHISPER_REPO_ARCHIVE_URL).await?;
+    /// Streams the archive into `dest` rather than memory; it can run to hundreds
+    /// of megabytes.
+    async fn download_repository_archive(&self, app: &AppHandle, dest: &Path) -> Result<()> {
+        let config = app.state::<Arc<ConfigState>>().get().await;
+        let client = app.state::<Arc<HttpClients>>().upload(&config)?;
+        let response = client.get(FAST_WHISPER_REPO_ARCHIVE_URL).send().await?;
         let status = response.status();
         if !status.is_success() {
             return Err(anyhow!(
                 "Failed to download repository archive: HTTP {status}"
             ));
         }
-        Ok(response.bytes().await?.to_vec())
+        let expected = response.content_length();
+        if expected.is_some_and(|bytes| bytes > FAST_WHISPER_ARCHIVE_MAX_BYTES) {
+            return Err(anyhow!(
+                "Repository archive is larger than {FAST_WHISPER_ARCHIVE_MAX_BYTES} bytes"
+            ));
+        }
+
+        let mut file = tokio::fs::File::create(dest).await?;
+        let mut written = 0u64;
+        let mut stream = response.bytes_stream();
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk?;
+            written += chunk.len() as u64;
+            if written > FAST_WHISPER_ARCHIVE_MAX_BYTES {
+                return Err(anyhow!(
+                    "Repository archive is larger than {FAST_WHISPER_ARCHIVE_MAX_BYTES} bytes"
+                ));
+            }
+            file.write_all(&chunk).await?;
+        }
+        file.flush().await?;
+        if let Some(expected) = expected.filter(|expected| *expected != written) {
+            return Err(anyhow!(
+                "Repository archive is truncated: got {written} of {expected} bytes"
+            ));
+        }
+        log::info!(
+            target: "local-speech",
+            "downloaded repository archive: {written} bytes"
+        );
+        Ok(())
     }
 
-    fn extract_repository_archive(archive: Vec<u8>, target_dir: PathBuf) -> Result<()> {
-        let reader = Cursor::new(archive);
+    /// Returns the commit GitHub stores as the archive comment, when there is one.
+    fn extract_repository_archive(archive: &Path, target_dir: PathBuf) -> Result<Option<String>> {
+        // Entries are read from the file one at a time, never all at once.
+        let reader = std::io::BufReader::new(File::open(archive)?);
         let mut archive = ZipArchive::new(reader)?;
+        let commit = String::from_utf8_lossy(archive.comment())
+            .trim()
+            .to_string();
         for index in 0..archive.len() {
             let mut file = archive.by_index(index)?;
             let entry_path = match file.enclosed_name() {
@@ -461,7 +1042,8 @@ impl FastWhisperManager {
                 std::io::copy(&mut file, &mut outfile)?;
             }
         }
-        Ok(())
+        let is_commit = commit.len() == 40 && commit.chars().all(|ch| ch.is_ascii_hexdigit());
+        Ok(is_commit.then_some(commit))
     }
 
     #[cfg(windows)]
@@ -504,7 +1086,7 @@ impl FastWhisperManager {
             }
         };
 
-        let health_result = self.wait_for_health(true).await;
+        let health_result = self.wait_for_health(app, true).await;
         if let Err(error) = health_result {
             self.update_status(app, |state| {
                 state.phase = "error".into();
@@ -531,6 +1113,9 @@ impl FastWhisperManager {
             state.last_success_at = Some(chrono::Utc::now().timestamp_millis());
         })
         .await;
+        // First contact after the start: find out which API this release speaks.
+        self.api.lock().await.take();
+        let _ = self.server_api(app).await;
         Ok(self.get_status().await)
     }
 
@@ -540,7 +1125,8 @@ impl FastWhisperManager {
         }
         let (command, args) = self.stop_command(app);
         let _ = self.run_script(app, &command, &args, "stop").await;
-        let _ = self.wait_for_health(false).await;
+        let _ = self.wait_for_health(app, false).await;
+        self.api.lock().await.take();
         Ok(())
     }
 
@@ -591,6 +1177,8 @@ impl FastWhisperManager {
                 continue;
             }
             let message = trimmed.to_string();
+            // Every line reaches the log even when its status event is folded away.
+            log::info!(target: "local-speech", "{label}: {message}");
             self.update_status(app, |state| {
                 state.log_line = Some(message.clone());
                 if matches!(state.phase.as_str(), "installing" | "starting" | "reinstalling") {
@@ -608,8 +1196,8 @@ impl FastWhisperManager {
         Ok(())
     }
 
-    async fn wait_for_health(&self, expect_up: bool) -> Result<()> {
-        let client = reqwest::Client::builder().timeout(Duration::from_secs(5)).build()?;
+    async fn wait_for_health(&self, app: &AppHandle, expect_up: bool) -> Result<()> {
+        let client = app.state::<Arc<HttpClients>>().health();
         let started = Instant::now();
         let health_url = self.health_endpoint();
         loop {
@@ -775,3 +1363,165 @@ impl FastWhisperManager {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use zip::write::FileOptions;
+    use zip::ZipWriter;
+
+    use super::*;
+
+    /// A directory under the system temp dir, removed when dropped.
+    struct Scratch(PathBuf);
+
+    impl Scratch {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!(
+                "xexamai-{name}-{}-{}",
+                std::process::id(),
+                chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default()
+            ));
+            fs::create_dir_all(&dir).unwrap();
+            Self(dir)
+        }
+    }
+
+    impl Drop for Scratch {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn write_archive(path: &Path, comment: &str, entries: &[(&str, Option<&str>)]) {
+        let mut zip = ZipWriter::new(File::create(path).unwrap());
+        for (name, contents) in entries {
+            match contents {
+                Some(contents) => {
+                    zip.start_file(*name, FileOptions::default()).unwrap();
+                    zip.write_all(contents.as_bytes()).unwrap();
+                }
+                None => zip.add_directory(*name, FileOptions::default()).unwrap(),
+            }
+        }
+        zip.set_comment(comment);
+        zip.finish().unwrap();
+    }
+
+    #[test]
+    fn repository_archive_is_extracted_from_disk_without_its_top_folder() {
+        let scratch = Scratch::new("archive");
+        let archive = scratch.0.join("repo.zip");
+        let target = scratch.0.join("repo");
+        let commit = "0123456789abcdef0123456789abcdef01234567";
+        write_archive(
+            &archive,
+            commit,
+            &[
+                ("fast-fast-whisper-main/", None),
+                ("fast-fast-whisper-main/start.sh", Some("#!/bin/sh\n")),
+                ("fast-fast-whisper-main/app/", None),
+                ("fast-fast-whisper-main/app/server.py", Some("print('hi')\n")),
+                ("fast-fast-whisper-main/app/models/readme.txt", Some("models")),
+                ("../escape.txt", Some("outside")),
+            ],
+        );
+
+        let found =
+            FastWhisperManager::extract_repository_archive(&archive, target.clone()).unwrap();
+        assert_eq!(found.as_deref(), Some(commit));
+        assert_eq!(fs::read_to_string(target.join("start.sh")).unwrap(), "#!/bin/sh\n");
+        assert_eq!(
+            fs::read_to_string(target.join("app/server.py")).unwrap(),
+            "print('hi')\n"
+        );
+        assert_eq!(
+            fs::read_to_string(target.join("app/models/readme.txt")).unwrap(),
+            "models"
+        );
+        assert!(!scratch.0.join("escape.txt").exists());
+        assert!(!target.join("fast-fast-whisper-main").exists());
+    }
+
+    #[test]
+    fn archive_comments_that_are_not_commits_are_ignored() {
+        let scratch = Scratch::new("archive-comment");
+        let archive = scratch.0.join("repo.zip");
+        write_archive(&archive, "built by hand", &[("repo/readme.md", Some("#"))]);
+        let found =
+            FastWhisperManager::extract_repository_archive(&archive, scratch.0.join("repo"))
+                .unwrap();
+        assert_eq!(found, None);
+    }
+
+    #[test]
+    fn temp_archive_is_removed_when_dropped() {
+        let scratch = Scratch::new("temp-archive");
+        let path = scratch.0.join("download.zip");
+        fs::write(&path, b"partial").unwrap();
+        drop(TempArchive(path.clone()));
+        assert!(!path.exists());
+        // Already gone is fine too.
+        drop(TempArchive(path));
+    }
+
+    /// Runs `updates` through a throttle on a fake clock, firing scheduled flushes
+    /// when they fall due, and returns what was emitted.
+    fn throttled(updates: &[(Duration, &str, u32)]) -> Vec<(String, u32)> {
+        let start = Instant::now();
+        let mut throttle = StatusThrottle::default();
+        let mut flush_at = None;
+        let mut current = (String::new(), 0);
+        let mut emitted = Vec::new();
+        for &(at, phase, progress) in updates {
+            let now = start + at;
+            if let Some(due) = flush_at.filter(|due| *due <= now) {
+                throttle.on_flush(&current.0, due);
+                emitted.push(current.clone());
+                flush_at = None;
+            }
+            current = (phase.to_string(), progress);
+            match throttle.on_update(phase, now) {
+                StatusEmit::Now => emitted.push(current.clone()),
+                StatusEmit::Later(delay) => flush_at = Some(now + delay),
+                StatusEmit::Pending => {}
+            }
+        }
+        if let Some(due) = flush_at {
+            throttle.on_flush(&current.0, due);
+            emitted.push(current.clone());
+        }
+        emitted
+    }
+
+    #[test]
+    fn rapid_status_updates_are_paced_and_the_last_one_goes_out() {
+        let updates: Vec<(Duration, &str, u32)> = (0..1000)
+            .map(|index| (Duration::from_millis(index as u64), "installing", index))
+            .collect();
+        let emitted = throttled(&updates);
+        // One every 200 ms over the second, plus the trailing flush.
+        assert_eq!(emitted.len(), 6);
+        assert_eq!(emitted.last(), Some(&("installing".to_string(), 999)));
+    }
+
+    #[test]
+    fn phase_changes_are_emitted_at_once() {
+        let updates: Vec<(Duration, &str, u32)> = (0..1000)
+            .map(|index| {
+                let phase = if index < 500 { "installing" } else { "starting" };
+                (Duration::from_micros(index as u64 * 10), phase, index)
+            })
+            .collect();
+        let emitted = throttled(&updates);
+        assert_eq!(
+            emitted,
+            [
+                ("installing".to_string(), 0),
+                ("starting".to_string(), 500),
+                ("starting".to_string(), 999),
+            ]
+        );
+    }
+}
@@ -1,37 +1,107 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod api_client;
+mod artifacts;
 mod audio;
+mod auto_type;
+mod audio_monitor;
+mod audio_pipeline;
 mod app_log;
 mod auth;
+mod auth_store;
+mod bootstrap;
+mod calibration;
+mod cli;
+mod clipboard_stream;
+mod commands;
 mod config;
+mod config_lock;
 mod constants;
+mod crash;
+mod diagnostics;
+mod durations;
+mod error_bus;
+mod foreground;
+mod errors;
+mod events;
 mod hotkeys;
+mod hresult;
+mod http;
+mod indicator;
+mod integration;
+mod key_test;
 mod local_speech;
+mod mic_access;
+mod monitors;
 mod oauth;
+mod onboarding;
 mod ollama;
+mod perf;
+mod power;
+mod privacy;
+mod private_session;
+mod provider_quota;
+mod prompt_template;
+mod rate_limit;
+mod resample;
+mod resources;
+mod reveal;
+mod self_check;
+mod session;
+mod sounds;
+mod stream_mode;
+mod summarizer;
+mod system_resources;
+mod text_sanitize;
+mod thread_priority;
+mod time_stretch;
+mod transcript_log;
 mod transcription;
 mod tray;
 mod types;
 mod update;
+mod usage;
+mod versions;
+mod wake_word;
+mod waveform;
+mod window_size;
 
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
+use api_client::ApiClient;
+use artifacts::ArtifactVault;
 use audio::AudioManager;
 use auth::AuthQueue;
+use auth_store::AuthStore;
+use auto_type::AutoTyper;
+use clipboard_stream::ClipboardStream;
 use config::ConfigState;
 use constants::{
-    DEFAULT_WINDOW_HEIGHT, DEFAULT_WINDOW_MIN_HEIGHT, DEFAULT_WINDOW_MIN_WIDTH,
-    DEFAULT_WINDOW_WIDTH,
+    CONFIG_EXIT_FLUSH_TIMEOUT_MS, DEFAULT_WINDOW_HEIGHT, DEFAULT_WINDOW_MIN_HEIGHT,
+    DEFAULT_WINDOW_MIN_WIDTH, DEFAULT_WINDOW_WIDTH, MAX_WINDOW_SCALE, MIN_WINDOW_SCALE,
 };
 use hotkeys::HotkeyManager;
+use http::HttpClients;
+use errors::ErrorCode;
+use events::emit_event;
+use integration::IntegrationServer;
 use local_speech::FastWhisperManager;
 use once_cell::sync::Lazy;
+use provider_quota::ProviderQuotaState;
+use rate_limit::RateLimiter;
+use self_check::SelfCheck;
+use session::SessionLog;
 use tauri::LogicalSize;
-use tauri::{AppHandle, Emitter, Manager, State, WindowEvent};
+use tauri::{AppHandle, Manager, WindowEvent};
 use tauri_plugin_deep_link::DeepLinkExt;
-use tray::set_tray_visible;
-use types::{AppConfig, AuthDeepLinkPayload, FastWhisperStatus};
+use transcript_log::TranscriptLog;
+use tray::{set_tray_visible, LastResult};
+use types::{AppConfig, AudioInputType, WindowPrefs};
+use usage::UsageStore;
+
+// Bluetooth devices often connect a few seconds after login.
+const CAPTURE_AUTO_START_RETRY_DELAY: Duration = Duration::from_secs(5);
 
 static PENDING_DEEP_LINKS: Lazy<Mutex<Vec<String>>> = Lazy::new(|| Mutex::new(Vec::new()));
 
@@ -39,6 +109,13 @@ static PENDING_DEEP_LINKS: Lazy<Mutex<Vec<String>>> = Lazy::new(|| Mutex::new(Ve
 static ORIGINAL_WNDPROC_BY_HWND: Lazy<Mutex<std::collections::HashMap<isize, isize>>> =
     Lazy::new(|| Mutex::new(std::collections::HashMap::new()));
 
+#[cfg(target_os = "windows")]
+static TASKBAR_CREATED_MESSAGE: Lazy<u32> = Lazy::new(|| unsafe {
+    windows::Win32::UI::WindowsAndMessaging::RegisterWindowMessageW(windows::core::w!(
+        "TaskbarCreated"
+    ))
+});
+
 #[cfg(target_os = "windows")]
 fn is_resize_hit_test(hit_test_code: u16) -> bool {
     matches!(hit_test_code, 4 | 10 | 11 | 12 | 13 | 14 | 15 | 16 | 17)
@@ -54,9 +131,25 @@ unsafe extern "system" fn force_arrow_cursor_wndproc(
     use windows::Win32::Foundation::LRESULT;
     use windows::Win32::UI::WindowsAndMessaging::{
         CallWindowProcW, DefWindowProcW, LoadCursorW, SetCursor, SetWindowLongPtrW, GWLP_WNDPROC,
-        IDC_ARROW, WM_NCDESTROY, WM_SETCURSOR, WNDPROC,
+        IDC_ARROW, PBT_APMRESUMEAUTOMATIC, WM_DISPLAYCHANGE, WM_NCDESTROY, WM_POWERBROADCAST,
+        WM_SETCURSOR, WNDPROC,
     };
 
+    if msg == WM_POWERBROADCAST && wparam.0 == PBT_APMRESUMEAUTOMATIC as usize {
+        power::notify_resumed(power::ResumeSource::PowerBroadcast, None);
+    }
+
+    if msg == WM_DISPLAYCHANGE {
+        // Off the window thread: the query goes back through the event loop.
+        std::thread::spawn(monitors::notify_changed);
+    }
+
+    // Explorer broadcasts this after it restarts; hotkeys can stop firing then.
+    if msg != 0 && msg == *TASKBAR_CREATED_MESSAGE {
+        hotkeys::notify_shell_restarted();
+        tray::notify_shell_restarted();
+    }
+
     if msg == WM_SETCURSOR {
         let hit_test = (lparam.0 & 0xFFFF) as u16;
         if is_resize_hit_test(hit_test) {
@@ -125,8 +218,9 @@ fn install_force_default_cursor(window: &tauri::WebviewWindow) {
     if previous == 0 {
         let error = unsafe { GetLastError() };
         if error.0 != 0 {
-            eprintln!(
-                "[window] failed to install cursor override hook: {}",
+            log::warn!(
+                target: "window",
+                "failed to install cursor override hook: {}",
                 error.0
             );
             return;
@@ -138,392 +232,72 @@ fn install_force_default_cursor(window: &tauri::WebviewWindow) {
     }
 }
 
-#[tauri::command]
-async fn config_get(state: State<'_, Arc<ConfigState>>) -> Result<AppConfig, String> {
-    Ok(state.get().await)
-}
-
-#[tauri::command]
-async fn config_update(
-    app: tauri::AppHandle,
-    state: State<'_, Arc<ConfigState>>,
-    hotkeys: State<'_, Arc<HotkeyManager>>,
-    payload: serde_json::Value,
-) -> Result<AppConfig, String> {
-    let apply_window_size =
-        payload.get("windowWidth").is_some() || payload.get("windowHeight").is_some();
-    log::info!(
-        target: "config",
-        "config_update command: keys={:?}",
-        payload
-            .as_object()
-            .map(|value| value.keys().cloned().collect::<Vec<_>>())
-    );
-    let updated = state
-        .update(payload)
-        .await
-        .map_err(|error| error.to_string())?;
-    app.emit("config:updated", &updated)
-        .map_err(|error| error.to_string())?;
-    handle_config_effects(&app, &updated, hotkeys.inner().clone(), apply_window_size);
-    Ok(updated)
-}
-
-#[tauri::command]
-async fn config_reset(
-    app: tauri::AppHandle,
-    state: State<'_, Arc<ConfigState>>,
-    hotkeys: State<'_, Arc<HotkeyManager>>,
-) -> Result<AppConfig, String> {
-    let updated = state.reset().await.map_err(|error| error.to_string())?;
-    app.emit("config:updated", &updated)
-        .map_err(|error| error.to_string())?;
-    handle_config_effects(&app, &updated, hotkeys.inner().clone(), true);
-    Ok(updated)
-}
-
-#[tauri::command]
-async fn config_path(state: State<'_, Arc<ConfigState>>) -> Result<String, String> {
-    Ok(state.path().await.to_string_lossy().to_string())
-}
-
-#[tauri::command]
-async fn open_config_folder(
-    app: tauri::AppHandle,
-    state: State<'_, Arc<ConfigState>>,
-) -> Result<(), String> {
-    let dir = state.directory().await;
-    tauri_plugin_opener::OpenerExt::opener(&app)
-        .open_path(dir.to_string_lossy(), None::<String>)
-        .map_err(|error| error.to_string())
-}
-
-#[tauri::command]
-async fn app_log_path() -> Result<String, String> {
-    app_log::current_log_path().map(|path| path.to_string_lossy().to_string())
-}
-
-#[tauri::command]
-async fn open_app_logs_folder(app: tauri::AppHandle) -> Result<(), String> {
-    use tauri_plugin_opener::OpenerExt;
-    let dir = app_log::current_log_dir()?;
-    app.opener()
-        .open_path(dir.to_string_lossy(), None::<String>)
-        .map_err(|error| error.to_string())
-}
-
-#[tauri::command]
-async fn log_frontend(entry: serde_json::Value) -> Result<(), String> {
-    let level = entry
-        .get("level")
-        .and_then(|value| value.as_str())
-        .unwrap_or("info")
-        .trim()
-        .to_lowercase();
-    let category = entry
-        .get("category")
-        .and_then(|value| value.as_str())
-        .unwrap_or("renderer")
-        .trim()
-        .to_string();
-    let message = entry
-        .get("message")
-        .and_then(|value| value.as_str())
-        .unwrap_or("")
-        .trim()
-        .to_string();
-    let data = entry.get("data").cloned().unwrap_or(serde_json::Value::Null);
-    let data_text = if data.is_null() {
-        String::new()
-    } else {
-        let text = serde_json::to_string(&data).unwrap_or_else(|_| "<unserializable>".to_string());
-        format!(" data={}", truncate_log_value(&text, 4000))
-    };
-    let line = format!("[{category}] {message}{data_text}");
-
-    match level.as_str() {
-        "error" => log::error!(target: "frontend", "{line}"),
-        "warn" | "warning" => log::warn!(target: "frontend", "{line}"),
-        "debug" => log::debug!(target: "frontend", "{line}"),
-        _ => log::info!(target: "frontend", "{line}"),
-    }
-    Ok(())
-}
-
-fn truncate_log_value(value: &str, max_len: usize) -> String {
-    if value.chars().count() <= max_len {
-        return value.to_string();
-    }
-    let truncated = value.chars().take(max_len).collect::<String>();
-    format!("{truncated}...<truncated>")
-}
-
-#[tauri::command]
-async fn open_external_url(app: tauri::AppHandle, url: String) -> Result<(), String> {
-    use tauri_plugin_opener::OpenerExt;
-
-    let normalized = url.trim();
-    if normalized.is_empty() {
-        return Err("URL is empty".to_string());
-    }
-    if !(normalized.starts_with("https://") || normalized.starts_with("http://")) {
-        return Err("Only http(s) URLs are allowed".to_string());
-    }
-
-    app.opener()
-        .open_url(normalized.to_string(), None::<String>)
-        .map_err(|error| error.to_string())
-}
-
-#[tauri::command]
-async fn auth_consume_pending(
-    queue: State<'_, Arc<AuthQueue>>,
-) -> Result<Vec<AuthDeepLinkPayload>, String> {
-    Ok(queue.drain().await)
+fn capture_started(app: &AppHandle, config: &AppConfig, source: AudioInputType) {
+    privacy::check_on_capture(app, config);
+    indicator::sync(app, config);
+    integration::publish_capture_state(app, true, Some(source.as_str()));
+    sounds::play_event(app, sounds::CUE_RECORD_START);
 }
 
-#[tauri::command]
-async fn auth_get_methods(
-    config: State<'_, Arc<ConfigState>>,
-) -> Result<oauth::AuthMethods, String> {
-    let cfg = config.get().await;
-    log::info!(
-        target: "auth",
-        "auth_get_methods command: backend_domain={}",
-        cfg.backend_domain
-    );
-    oauth::load_auth_methods(Some(cfg.backend_domain.as_str()))
-        .await
-        .map_err(|error| {
-            log::error!(target: "auth", "auth_get_methods failed: {error}");
-            error.to_string()
-        })
-}
-
-#[tauri::command]
-async fn auth_start_oauth(
-    app: tauri::AppHandle,
-    config: State<'_, Arc<ConfigState>>,
-    queue: State<'_, Arc<AuthQueue>>,
-    provider: String,
-) -> Result<(), String> {
-    use tauri_plugin_opener::OpenerExt;
-    let cfg = config.get().await;
-    let provider = provider.trim().to_lowercase();
-    log::info!(
-        target: "auth",
-        "auth_start_oauth command: provider={} backend_domain={}",
-        provider,
-        cfg.backend_domain
-    );
-    if !oauth::is_supported_provider(&provider) {
-        log::warn!(target: "auth", "Unsupported OAuth provider requested: {provider}");
-        return Err(format!("Unsupported OAuth provider: {provider}"));
-    }
-    let methods = oauth::load_auth_methods(Some(cfg.backend_domain.as_str()))
-        .await
-        .map_err(|error| {
-            log::error!(target: "auth", "Failed to load auth methods before OAuth start: {error}");
-            error.to_string()
-        })?;
-    if !oauth::provider_is_allowed(&methods, &provider) {
-        log::warn!(
-            target: "auth",
-            "OAuth provider blocked: provider={} country={} allowed={:?}",
-            provider,
-            methods.country_code,
-            methods.allowed_oauth_providers
-        );
-        return Err("OAuth provider is not available for your region".to_string());
+/// Starts capture at launch when `auto_start_capture` is on. Runs in the background
+/// so setup never waits on device enumeration; a missing device gets one more try.
+fn auto_start_capture(app: &AppHandle, config: &AppConfig) {
+    if !config.auto_start_capture {
+        return;
     }
-    let oauth_state = queue.start_state(&provider).await;
-    let url =
-        oauth::build_oauth_start_url(&provider, Some(cfg.backend_domain.as_str()), &oauth_state)
-            .map_err(|error| error.to_string())?;
-    log::info!(target: "auth", "Opening OAuth URL: provider={provider}");
-    app.opener()
-        .open_url(url, None::<String>)
-        .map_err(|error| {
-            log::error!(target: "auth", "Failed to open OAuth URL: provider={} error={}", provider, error);
-            error.to_string()
-        })
-}
-
-#[tauri::command]
-async fn local_speech_get_status(
-    manager: State<'_, Arc<FastWhisperManager>>,
-) -> Result<FastWhisperStatus, String> {
-    Ok(manager.get_status().await)
-}
-
-#[tauri::command]
-async fn local_speech_check_health(
-    app: tauri::AppHandle,
-    manager: State<'_, Arc<FastWhisperManager>>,
-) -> Result<FastWhisperStatus, String> {
-    Ok(manager.check_health(&app).await)
-}
-
-#[tauri::command]
-async fn local_speech_install(
-    app: tauri::AppHandle,
-    manager: State<'_, Arc<FastWhisperManager>>,
-) -> Result<FastWhisperStatus, String> {
-    manager
-        .install_and_start(&app)
-        .await
-        .map_err(|error| error.to_string())
-}
-
-#[tauri::command]
-async fn local_speech_start(
-    app: tauri::AppHandle,
-    manager: State<'_, Arc<FastWhisperManager>>,
-) -> Result<FastWhisperStatus, String> {
-    manager
-        .start_existing(&app)
-        .await
-        .map_err(|error| error.to_string())
-}
-
-#[tauri::command]
-async fn local_speech_restart(
-    app: tauri::AppHandle,
-    manager: State<'_, Arc<FastWhisperManager>>,
-) -> Result<FastWhisperStatus, String> {
-    manager
-        .restart(&app)
-        .await
-        .map_err(|error| error.to_string())
-}
-
-#[tauri::command]
-async fn local_speech_reinstall(
-    app: tauri::AppHandle,
-    manager: State<'_, Arc<FastWhisperManager>>,
-) -> Result<FastWhisperStatus, String> {
-    manager
-        .reinstall(&app)
-        .await
-        .map_err(|error| error.to_string())
-}
-
-#[tauri::command]
-async fn local_speech_stop(
-    app: tauri::AppHandle,
-    manager: State<'_, Arc<FastWhisperManager>>,
-) -> Result<FastWhisperStatus, String> {
-    manager.stop(&app).await.map_err(|error| error.to_string())
-}
-
-#[tauri::command]
-async fn local_speech_check_model_downloaded(
-    app: tauri::AppHandle,
-    manager: State<'_, Arc<FastWhisperManager>>,
-    model: String,
-) -> Result<bool, String> {
-    manager
-        .is_model_downloaded(&app, &model)
-        .await
-        .map_err(|error| error.to_string())
-}
-
-#[tauri::command]
-async fn ollama_check_installed() -> Result<bool, String> {
-    crate::ollama::check_installed()
-        .await
-        .map_err(|error| error.to_string())
-}
-
-#[tauri::command]
-async fn ollama_list_models() -> Result<Vec<String>, String> {
-    crate::ollama::list_models()
-        .await
-        .map_err(|error| error.to_string())
-}
-
-#[tauri::command]
-async fn ollama_pull_model(model: String) -> Result<(), String> {
-    crate::ollama::pull_model(&model)
-        .await
-        .map_err(|error| error.to_string())
-}
-
-#[tauri::command]
-async fn ollama_warmup_model(model: String) -> Result<(), String> {
-    crate::ollama::warmup_model(&model)
-        .await
-        .map_err(|error| error.to_string())
-}
-
-#[tauri::command]
-async fn audio_list_devices(
-    manager: State<'_, Arc<AudioManager>>,
-) -> Result<Vec<audio::AudioDeviceInfo>, String> {
-    manager.list_devices().map_err(|e| e.to_string())
-}
-
-#[tauri::command]
-async fn audio_start_capture(
-    app: tauri::AppHandle,
-    manager: State<'_, Arc<AudioManager>>,
-    source: String,
-    device_id: Option<String>,
-) -> Result<(), String> {
-    manager
-        .start(app, &source, device_id)
-        .map_err(|e| e.to_string())
-}
-
-#[tauri::command]
-async fn audio_stop_capture(manager: State<'_, Arc<AudioManager>>) -> Result<(), String> {
-    manager.stop().map_err(|e| e.to_string())
-}
-
-#[tauri::command]
-async fn ollama_http_request(
-    url: String,
-    method: String,
-    headers: serde_json::Value,
-    body: Option<String>,
-    timeout_secs: Option<u64>,
-) -> Result<String, String> {
-    let client = reqwest::Client::builder()
-        .timeout(Duration::from_secs(timeout_secs.unwrap_or(600)))
-        .build()
-        .map_err(|e| e.to_string())?;
-
-    let mut request = match method.as_str() {
-        "GET" => client.get(&url),
-        "POST" => client.post(&url),
-        "PUT" => client.put(&url),
-        "DELETE" => client.delete(&url),
-        _ => return Err(format!("Unsupported method: {}", method)),
-    };
-
-    // Добавляем заголовки
-    if let serde_json::Value::Object(map) = headers {
-        for (key, value) in map {
-            if let Some(val_str) = value.as_str() {
-                request = request.header(&key, val_str);
+    let app = app.clone();
+    let config = config.clone();
+    tauri::async_runtime::spawn(async move {
+        let source = config.audio_input_type;
+        let manager = app.state::<Arc<AudioManager>>().inner().clone();
+        for attempt in 1..=2 {
+            if manager.status().active {
+                // Started by hand while the retry was waiting.
+                return;
+            }
+            let devices = audio::CaptureDevices::for_source(&config, source, None);
+            let start_app = app.clone();
+            let start_manager = manager.clone();
+            let result = tauri::async_runtime::spawn_blocking(move || {
+                start_manager.start(start_app, source, devices)
+            })
+            .await;
+            let error = match result {
+                Ok(Ok(())) => {
+                    log::info!(
+                        target: "audio",
+                        "Capture auto-started: source={} attempt={attempt}",
+                        source.as_str()
+                    );
+                    capture_started(&app, &config, source);
+                    let _ = emit_event(&app, events::AUDIO_AUTO_STARTED, source);
+                    return;
+                }
+                Ok(Err(error)) => error,
+                Err(error) => {
+                    log::error!(target: "audio", "Capture auto-start panicked: {error}");
+                    return;
+                }
+            };
+            if attempt == 1 && audio::is_device_not_found(&error) {
+                log::warn!(
+                    target: "audio",
+                    "Capture auto-start found no device, retrying: {error:#}"
+                );
+                tokio::time::sleep(CAPTURE_AUTO_START_RETRY_DELAY).await;
+                continue;
             }
+            log::warn!(target: "audio", "Capture auto-start failed: {error:#}");
+            error_bus::publish(
+                &app,
+                "audio",
+                ErrorCode::AudioCaptureFailed,
+                format!("{error:#}"),
+                true,
+            );
+            return;
         }
-    }
-
-    // Добавляем тело запроса
-    if let Some(body_str) = body {
-        request = request.body(body_str);
-    }
-
-    let response = request.send().await.map_err(|e| e.to_string())?;
-    let status = response.status();
-    let text = response.text().await.map_err(|e| e.to_string())?;
-
-    if !status.is_success() {
-        return Err(format!("HTTP {}: {}", status.as_u16(), text));
-    }
-
-    Ok(text)
+    });
 }
 
 fn handle_config_effects(
@@ -532,9 +306,31 @@ fn handle_config_effects(
     hotkeys: Arc<HotkeyManager>,
     apply_window_size: bool,
 ) {
+    app_log::set_level(&config.log_level);
+    errors::set_language(&config.ui_language);
+    perf::set_slow_command_ms(config.slow_command_ms);
+    thread_priority::apply_config(config);
     hotkeys.apply_config(app, config);
+    if let Some(integration) = app.try_state::<Arc<IntegrationServer>>() {
+        integration.apply_config(app, config);
+    }
+    if let Some(http) = app.try_state::<Arc<HttpClients>>() {
+        http.sync(config);
+    }
+    if let Some(audio) = app.try_state::<Arc<AudioManager>>() {
+        audio.apply_wake_words(app, config);
+        audio.apply_pre_roll(app, config);
+        audio::set_mic_gain(config.mic_gain);
+        audio::set_ducking(
+            config.ducking_enabled,
+            config.ducking_db,
+            config.vad_threshold_db,
+        );
+    }
+    indicator::sync(app, config);
+    tray::apply_theme(app, config.tray_icon_theme);
     if let Err(error) = apply_window_preferences(app, config, apply_window_size) {
-        eprintln!("[window] failed to apply preferences: {error}");
+        log::warn!(target: "window", "failed to apply preferences: {error}");
     }
 }
 
@@ -544,8 +340,6 @@ fn apply_window_preferences(
     apply_window_size: bool,
 ) -> Result<(), String> {
     if let Some(window) = app.get_webview_window("main") {
-        let scale = config.window_scale.clamp(0.5, 3.0);
-
         // Применяем размер окна БЕЗ масштабирования
         // Масштабирование контента происходит через CSS font-size на html
         if apply_window_size {
@@ -555,10 +349,13 @@ fn apply_window_preferences(
                 .max(DEFAULT_WINDOW_MIN_HEIGHT)
                 .min(4000) as f64;
 
-            // Используем базовый размер окна без масштабирования
-            window
-                .set_size(LogicalSize::new(base_width, base_height))
-                .map_err(|error| error.to_string())?;
+            // Используем базовый размер окна без масштабирования.
+            // A size saved from the user's own resize is already on the window.
+            if !window_size::has_size(&window, base_width, base_height) {
+                window
+                    .set_size(LogicalSize::new(base_width, base_height))
+                    .map_err(|error| error.to_string())?;
+            }
             window
                 .set_min_size(Some(LogicalSize::new(
                     DEFAULT_WINDOW_MIN_WIDTH as f64,
@@ -577,8 +374,20 @@ fn apply_window_preferences(
                 .map_err(|error| error.to_string())?;
         }
         set_tray_visible(!config.hide_app);
+        // Accessory apps have no Dock icon and are skipped by Cmd-Tab.
+        #[cfg(target_os = "macos")]
+        app.set_activation_policy(if config.hide_from_switcher {
+            tauri::ActivationPolicy::Accessory
+        } else {
+            tauri::ActivationPolicy::Regular
+        })
+        .map_err(|error| error.to_string())?;
 
         window.show().map_err(|error| error.to_string())?;
+        // A position left over from a display that is gone would leave it unreachable.
+        if let Ok(monitors) = monitors::list(app) {
+            monitors::keep_on_screen(&window, &monitors);
+        }
 
         // Применяем opacity и скрытие от записи экрана (Windows) после показа окна
         #[cfg(target_os = "windows")]
@@ -587,81 +396,138 @@ fn apply_window_preferences(
             let app_clone = app.clone();
             let opacity_value = config.window_opacity.clamp(10, 100);
             let hide_app_value = config.hide_app;
+            let hide_from_switcher_value = config.hide_from_switcher;
             std::thread::spawn(move || {
                 std::thread::sleep(std::time::Duration::from_millis(200));
                 if let Some(w) = app_clone.get_webview_window("main") {
-                    if let Ok(hwnd) = w.hwnd() {
-                        use windows::Win32::Foundation::HWND;
-                        use windows::Win32::UI::WindowsAndMessaging::{
-                            GetWindowLongPtrW, SetLayeredWindowAttributes,
-                            SetWindowDisplayAffinity, SetWindowLongPtrW, GWL_EXSTYLE, LWA_ALPHA,
-                            WDA_EXCLUDEFROMCAPTURE, WDA_NONE, WS_EX_LAYERED,
-                        };
-
-                        let hwnd_handle = HWND(hwnd.0);
-
-                        // Применяем opacity через SetLayeredWindowAttributes
-                        let alpha = ((opacity_value as f32 / 100.0) * 255.0) as u8;
-                        unsafe {
-                            // Устанавливаем WS_EX_LAYERED стиль
-                            let ex_style = GetWindowLongPtrW(hwnd_handle, GWL_EXSTYLE);
-                            let layered_flag = WS_EX_LAYERED.0 as isize;
-                            SetWindowLongPtrW(hwnd_handle, GWL_EXSTYLE, ex_style | layered_flag);
-                            // Устанавливаем opacity
-                            let _ = SetLayeredWindowAttributes(
-                                hwnd_handle,
-                                windows::Win32::Foundation::COLORREF(0),
-                                alpha,
-                                LWA_ALPHA,
-                            );
-                        }
-
-                        // Применяем скрытие от записи экрана
-                        unsafe {
-                            if hide_app_value {
-                                let _ =
-                                    SetWindowDisplayAffinity(hwnd_handle, WDA_EXCLUDEFROMCAPTURE);
-                            } else {
-                                let _ = SetWindowDisplayAffinity(hwnd_handle, WDA_NONE);
-                            }
-                        }
-                    }
+                    apply_native_window_style(
+                        &w,
+                        opacity_value,
+                        hide_app_value,
+                        hide_from_switcher_value,
+                    );
                 }
             });
         }
 
-        // Применяем scale через CSS переменную и font-size на html
-        // Это масштабирует все элементы, использующие rem единицы
-        let scale_script = format!(
-            r#"
-            (function() {{
-                const html = document.documentElement;
-                if (!html) return;
-                
-                // Устанавливаем CSS переменную для масштаба
-                html.style.setProperty('--app-scale', '{}');
-                
-                // Устанавливаем font-size на html для масштабирования через rem
-                // Базовый размер 16px, умножаем на scale
-                const baseFontSize = 16;
-                const scaledFontSize = baseFontSize * {};
-                html.style.fontSize = scaledFontSize + 'px';
-            }})();
-            "#,
-            scale, scale
-        );
-        // Применяем scale после небольшой задержки
-        let app_clone = app.clone();
-        std::thread::spawn(move || {
-            std::thread::sleep(std::time::Duration::from_millis(300));
-            if let Some(w) = app_clone.get_webview_window("main") {
-                let _ = w.eval(&scale_script);
-            }
-        });
+        // The webview applies the scale itself; on load it asks via
+        // `get_initial_window_prefs`, so an event sent before the DOM exists is harmless.
+        emit_event(app, events::WINDOW_SCALE_CHANGED, window_prefs(config))
+            .map_err(|error| error.to_string())?;
     }
     Ok(())
 }
 
+/// Ex-style, opacity and capture exclusion of the main window. Windows keeps
+/// them on the HWND, so a recreated one needs them again.
+#[cfg(target_os = "windows")]
+pub fn apply_native_window_style(
+    window: &tauri::WebviewWindow,
+    opacity: u32,
+    hide_app: bool,
+    hide_from_switcher: bool,
+) {
+    if let Ok(hwnd) = window.hwnd() {
+        use windows::Win32::Foundation::HWND;
+        use windows::Win32::UI::WindowsAndMessaging::{
+            GetWindowLongPtrW, SetLayeredWindowAttributes, SetWindowDisplayAffinity,
+            SetWindowLongPtrW, SetWindowPos, GWL_EXSTYLE, LWA_ALPHA, SWP_FRAMECHANGED,
+            SWP_NOACTIVATE, SWP_NOMOVE, SWP_NOSIZE, SWP_NOZORDER, WDA_EXCLUDEFROMCAPTURE,
+            WDA_NONE, WS_EX_APPWINDOW, WS_EX_LAYERED, WS_EX_TOOLWINDOW,
+        };
+
+        let hwnd_handle = HWND(hwnd.0);
+
+        // Применяем opacity через SetLayeredWindowAttributes
+        let alpha = ((opacity as f32 / 100.0) * 255.0) as u8;
+        unsafe {
+            // Устанавливаем WS_EX_LAYERED стиль
+            let mut ex_style =
+                GetWindowLongPtrW(hwnd_handle, GWL_EXSTYLE) | WS_EX_LAYERED.0 as isize;
+            // Tool windows are left out of Alt-Tab; WS_EX_APPWINDOW would
+            // force the window back in, so it only stays while visible in
+            // the taskbar.
+            let tool_flag = WS_EX_TOOLWINDOW.0 as isize;
+            let app_flag = WS_EX_APPWINDOW.0 as isize;
+            if hide_from_switcher {
+                ex_style = (ex_style | tool_flag) & !app_flag;
+            } else {
+                ex_style &= !tool_flag;
+                if !hide_app {
+                    ex_style |= app_flag;
+                }
+            }
+            SetWindowLongPtrW(hwnd_handle, GWL_EXSTYLE, ex_style);
+            // The shell only rereads the ex-style after a frame change.
+            if let Err(error) = SetWindowPos(
+                hwnd_handle,
+                None,
+                0,
+                0,
+                0,
+                0,
+                SWP_NOMOVE | SWP_NOSIZE | SWP_NOZORDER | SWP_NOACTIVATE | SWP_FRAMECHANGED,
+            ) {
+                log::warn!(
+                    target: "window",
+                    "SetWindowPos failed: hide_from_switcher={hide_from_switcher} error={error}"
+                );
+            }
+            // Устанавливаем opacity
+            let _ = SetLayeredWindowAttributes(
+                hwnd_handle,
+                windows::Win32::Foundation::COLORREF(0),
+                alpha,
+                LWA_ALPHA,
+            );
+        }
+
+        // Применяем скрытие от записи экрана
+        let affinity = if hide_app {
+            WDA_EXCLUDEFROMCAPTURE
+        } else {
+            WDA_NONE
+        };
+        // Some GPU drivers reject this; capture start re-checks and warns.
+        if let Err(error) = unsafe { SetWindowDisplayAffinity(hwnd_handle, affinity) } {
+            log::warn!(
+                target: "window",
+                "SetWindowDisplayAffinity failed: hide_app={hide_app} error={error}"
+            );
+        }
+    }
+}
+
+/// Re-applies what a restarted Explorer forgets about the main window: its taskbar
+/// entry, and on a visible window also the capture exclusion and ex-style. A
+/// hidden window stays hidden; those are applied again when it is shown.
+#[cfg_attr(not(windows), allow(dead_code))]
+pub fn reapply_shell_window_state(app: &AppHandle, config: &AppConfig) {
+    let Some(window) = app.get_webview_window("main") else {
+        return;
+    };
+    let visible = window.is_visible().unwrap_or(false);
+    if visible {
+        if let Err(error) = apply_window_preferences(app, config, false) {
+            log::warn!(target: "window", "failed to re-apply preferences: {error}");
+        }
+    }
+    #[cfg(not(target_os = "linux"))]
+    if !visible {
+        if let Err(error) = window.set_skip_taskbar(config.hide_app) {
+            log::warn!(target: "window", "failed to re-apply skip-taskbar: {error}");
+        }
+    }
+}
+
+fn window_prefs(config: &AppConfig) -> WindowPrefs {
+    WindowPrefs {
+        scale: config
+            .window_scale
+            .clamp(MIN_WINDOW_SCALE, MAX_WINDOW_SCALE),
+    }
+}
+
 pub fn show_main_window(app: &AppHandle) -> Result<(), String> {
     if let Some(window) = app.get_webview_window("main") {
         #[cfg(target_os = "windows")]
@@ -697,7 +563,22 @@ fn main() {
     if let Err(error) = app_log::init() {
         eprintln!("App logger initialization failed: {error}");
     }
+    crash::install_panic_hook();
     log::info!(target: "app", "Starting XEXAMAI");
+    let cli_args = cli::parse(std::env::args_os()).unwrap_or_else(|error| {
+        log::info!(target: "cli", "Ignoring command line: {}", error.kind());
+        cli::CliArgs::default()
+    });
+    if let Some(action) = cli_args.action {
+        log::info!(target: "cli", "No running instance to forward {action:?} to, ignoring it");
+    }
+    let headless = cli_args.headless;
+    if let Ok(namespace) = std::env::var("XEXAMAI_EVENT_NAMESPACE") {
+        match events::set_namespace(&namespace) {
+            Ok(()) => log::info!(target: "app", "Events namespaced under {:?}", events::namespace()),
+            Err(error) => log::warn!(target: "app", "Ignoring event namespace: {}", error.kind()),
+        }
+    }
 
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
@@ -706,7 +587,7 @@ fn main() {
         .plugin(tauri_plugin_http::init())
         .plugin(tauri_plugin_single_instance::init(|app, args, _cwd| {
             log::info!(target: "deep-link", "Single instance activation: args={:?}", args);
-            if let Some(url) = args.into_iter().find(|arg| arg.starts_with("xexamai://")) {
+            if let Some(url) = args.iter().find(|arg| arg.starts_with("xexamai://")).cloned() {
                 log::info!(target: "deep-link", "Single instance received deep link");
                 if let Some(state) = app.try_state::<Arc<AuthQueue>>() {
                     dispatch_deep_link(app, state.inner().clone(), url);
@@ -715,91 +596,145 @@ fn main() {
                     PENDING_DEEP_LINKS.lock().unwrap().push(url);
                 }
             } else {
-                let _ = show_main_window(app);
+                match cli::parse(&args) {
+                    Ok(cli::CliArgs {
+                        action: Some(action),
+                        ..
+                    }) => cli::run(app, action),
+                    _ => {
+                        let _ = show_main_window(app);
+                    }
+                }
             }
         }))
         .plugin(tauri_plugin_deep_link::init())
-        .setup(|app| {
+        .setup(move |app| {
             let app_handle = app.handle();
+            crash::attach(app_handle);
+            perf::attach(app_handle);
             ensure_deep_links_registered(&app_handle);
             let config_state = Arc::new(tauri::async_runtime::block_on(ConfigState::initialize(
                 &app_handle,
-            ))?);
+            )));
             let initial_config = tauri::async_runtime::block_on(config_state.get());
             log::info!(
                 target: "app",
-                "Config loaded: backend_domain={} transcription_mode={} llm_host={}",
+                "Config loaded: backend_domain={} transcription_mode={} llm_host={} llm_model={}",
                 initial_config.backend_domain,
                 initial_config.transcription_mode,
-                initial_config.llm_host
+                initial_config.llm_host,
+                initial_config.effective_llm_model()
             );
 
             let hotkeys = Arc::new(HotkeyManager::new());
             let fast_whisper = Arc::new(FastWhisperManager::new());
             let auth_queue = Arc::new(AuthQueue::new());
+            let auth_store = Arc::new(AuthStore::new());
             let audio_manager = Arc::new(AudioManager::new());
+            let http_clients = Arc::new(HttpClients::new()?);
+            let last_result = Arc::new(LastResult::new());
+            let usage_store = tauri::async_runtime::block_on(async {
+                UsageStore::load(config_state.directory().await).await
+            });
+            let integration_server = tauri::async_runtime::block_on(async {
+                IntegrationServer::load(config_state.directory().await).await
+            });
 
             app.manage(config_state.clone());
+            if !config_state.persistence_available() {
+                // The webview also sees this through `config_get` if it loads later.
+                config_state.emit_status();
+            }
             app.manage(hotkeys.clone());
             app.manage(fast_whisper.clone());
+            fast_whisper.restore(app_handle);
             app.manage(auth_queue.clone());
+            app.manage(auth_store.clone());
+            app.manage(Arc::new(ApiClient::new()));
+            app.manage(http_clients);
             app.manage(audio_manager.clone());
+            app.manage(last_result);
+            app.manage(usage_store);
+            app.manage(Arc::new(SessionLog::new()));
+            app.manage(Arc::new(ClipboardStream::new()));
+            app.manage(Arc::new(AutoTyper::new()));
+            app.manage(Arc::new(SelfCheck::new()));
+            app.manage(Arc::new(TranscriptLog::new()));
+            app.manage(Arc::new(ArtifactVault::new()));
+            app.manage(Arc::new(RateLimiter::new()));
+            app.manage(Arc::new(ProviderQuotaState::new()));
+            app.manage(integration_server);
+            app.manage(Arc::new(transcription::TranscriptionManager::new()));
+            app.manage(Arc::new(error_bus::ErrorBus::new()));
+            let summarizer = Arc::new(summarizer::Summarizer::new());
+            app.manage(summarizer.clone());
 
             tray::setup(&app_handle)?;
+            if headless {
+                // Closed before the config is applied, which would show it.
+                if let Some(main_window) = app.get_webview_window("main") {
+                    log::info!(target: "app", "Starting headless, main window closed");
+                    main_window.destroy()?;
+                }
+            }
             handle_config_effects(&app_handle, &initial_config, hotkeys, true);
+            auto_start_capture(app_handle, &initial_config);
             flush_pending_deep_links(&app_handle, auth_queue.clone());
             setup_deep_link_listener(&app_handle, auth_queue);
             update::start_update_poll(app_handle.clone());
+            power::start(app_handle);
+            monitors::start(app_handle);
+            privacy::start(app_handle);
+            summarizer.start(app_handle);
+            hotkeys::start(app_handle);
+            versions::log_at_startup(app_handle);
+            auth_store::start_refresh_loop(app_handle.clone(), auth_store);
+            audio::prune_stale_devices(app_handle.clone());
 
             if let Some(main_window) = app.get_webview_window("main") {
                 #[cfg(target_os = "windows")]
                 install_force_default_cursor(&main_window);
 
                 let app_handle = app_handle.clone();
-                main_window.on_window_event(move |event| {
-                    if let WindowEvent::CloseRequested { api, .. } = event {
+                main_window.on_window_event(move |event| match event {
+                    WindowEvent::CloseRequested { api, .. } => {
                         api.prevent_close();
                         app_handle.exit(0);
                     }
+                    WindowEvent::Resized(size) => window_size::on_resized(&app_handle, *size),
+                    _ => {}
                 });
             }
 
             Ok(())
         })
-        .invoke_handler(tauri::generate_handler![
-            config_get,
-            config_update,
-            config_reset,
-            config_path,
-            open_config_folder,
-            app_log_path,
-            open_app_logs_folder,
-            log_frontend,
-            open_external_url,
-            ollama_http_request,
-            auth_consume_pending,
-            auth_get_methods,
-            auth_start_oauth,
-            local_speech_get_status,
-            local_speech_check_health,
-            local_speech_install,
-            local_speech_start,
-            local_speech_restart,
-            local_speech_reinstall,
-            local_speech_stop,
-            local_speech_check_model_downloaded,
-            ollama_check_installed,
-            ollama_list_models,
-            ollama_pull_model,
-            ollama_warmup_model,
-            audio_list_devices,
-            audio_start_capture,
-            audio_stop_capture,
-            update::check_app_update,
-            transcription::transcribe_audio,
-        ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .invoke_handler(commands::handler())
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(move |app, event| {
+            if let tauri::RunEvent::ExitRequested {
+                code: None, api, ..
+            } = &event
+            {
+                // Headless runs live in the tray; closing the window from
+                // there does not quit.
+                if headless {
+                    api.prevent_exit();
+                }
+            }
+            if let tauri::RunEvent::Exit = event {
+                // A config change still waiting for its write retry goes out now.
+                if let Some(config) = app.try_state::<Arc<ConfigState>>() {
+                    tauri::async_runtime::block_on(
+                        config.flush(Duration::from_millis(CONFIG_EXIT_FLUSH_TIMEOUT_MS)),
+                    );
+                    config.release_lock();
+                }
+                if let Some(fast_whisper) = app.try_state::<Arc<FastWhisperManager>>() {
+                    fast_whisper.shutdown_watch();
+                }
+            }
+        });
 }
 
 fn flush_pending_deep_links(app: &AppHandle, queue: Arc<AuthQueue>) {
@@ -854,7 +789,53 @@ fn ensure_deep_links_registered(app: &AppHandle) {
         Ok(()) => log::info!(target: "deep-link", "Deep link schemes registered"),
         Err(error) => {
             log::error!(target: "deep-link", "Failed to register deep link schemes: {error}");
-            eprintln!("[deep-link] failed to register schemes: {error}");
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn prefs(window_scale: f32) -> WindowPrefs {
+        window_prefs(&AppConfig {
+            window_scale,
+            ..AppConfig::default()
+        })
+    }
+
+    #[test]
+    fn window_scale_is_clamped_to_its_bounds() {
+        let cases = [
+            (MIN_WINDOW_SCALE, MIN_WINDOW_SCALE),
+            (MIN_WINDOW_SCALE - 0.01, MIN_WINDOW_SCALE),
+            (0.0, MIN_WINDOW_SCALE),
+            (-2.0, MIN_WINDOW_SCALE),
+            (1.25, 1.25),
+            (MAX_WINDOW_SCALE, MAX_WINDOW_SCALE),
+            (MAX_WINDOW_SCALE + 0.01, MAX_WINDOW_SCALE),
+            (f32::INFINITY, MAX_WINDOW_SCALE),
+        ];
+        for (configured, applied) in cases {
+            assert_eq!(prefs(configured).scale, applied, "{configured}");
+        }
+    }
+
+    #[test]
+    fn scale_changed_payload_carries_the_clamped_scale() {
+        assert_eq!(
+            serde_json::to_value(prefs(MIN_WINDOW_SCALE / 2.0)).unwrap(),
+            json!({ "scale": MIN_WINDOW_SCALE })
+        );
+        assert_eq!(
+            serde_json::to_value(prefs(MAX_WINDOW_SCALE * 2.0)).unwrap(),
+            json!({ "scale": MAX_WINDOW_SCALE })
+        );
+        assert_eq!(
+            serde_json::to_value(prefs(1.5)).unwrap(),
+            json!({ "scale": 1.5 })
+        );
+    }
+}
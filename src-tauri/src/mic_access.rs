@@ -0,0 +1,123 @@
+//! Whether the OS lets the app use the microphone. With access switched off,
+//! Windows hands out no devices or silent streams and macOS silent ones, so a
+//! capture failure is checked against this before it is reported.
+
+use serde::Serialize;
+
+#[cfg(windows)]
+const SETTINGS_URL: Option<&str> = Some("ms-settings:privacy-microphone");
+#[cfg(target_os = "macos")]
+const SETTINGS_URL: Option<&str> =
+    Some("x-apple.systempreferences:com.apple.preference.security?Privacy_Microphone");
+#[cfg(not(any(windows, target_os = "macos")))]
+const SETTINGS_URL: Option<&str> = None;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+#[cfg_attr(not(any(windows, target_os = "macos")), allow(dead_code))]
+pub enum AccessState {
+    Allowed,
+    Denied,
+    /// Blocked by a device policy the user cannot change.
+    Restricted,
+    /// macOS has not asked yet; the first capture shows the prompt.
+    NotDetermined,
+    Unknown,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MicrophoneAccess {
+    pub state: AccessState,
+    /// The switch that is off: `system`, `apps` or `desktop-apps` on Windows.
+    pub blocked_by: Option<&'static str>,
+    /// OS settings page where access is granted.
+    pub settings_url: Option<&'static str>,
+}
+
+impl MicrophoneAccess {
+    pub fn denied(&self) -> bool {
+        matches!(self.state, AccessState::Denied | AccessState::Restricted)
+    }
+}
+
+pub fn check() -> MicrophoneAccess {
+    let (state, blocked_by) = platform_state();
+    if state != AccessState::Allowed {
+        log::info!(
+            target: "audio",
+            "Microphone access: {state:?} (blocked by {})",
+            blocked_by.unwrap_or("-")
+        );
+    }
+    MicrophoneAccess {
+        state,
+        blocked_by,
+        settings_url: SETTINGS_URL,
+    }
+}
+
+/// The ConsentStore switches, outermost first: "Microphone access" for the
+/// device, "Let apps access your microphone", then the desktop-apps switch,
+/// which is the one that covers this app.
+#[cfg(windows)]
+fn platform_state() -> (AccessState, Option<&'static str>) {
+    use winreg::enums::{HKEY_CURRENT_USER, HKEY_LOCAL_MACHINE};
+    use winreg::{RegKey, HKEY};
+
+    const CONSENT_KEY: &str = r"SOFTWARE\Microsoft\Windows\CurrentVersion\CapabilityAccessManager\ConsentStore\microphone";
+
+    let denied = |root: HKEY, path: &str| {
+        RegKey::predef(root)
+            .open_subkey(path)
+            .and_then(|key| key.get_value::<String, _>("Value"))
+            .is_ok_and(|value| value.eq_ignore_ascii_case("deny"))
+    };
+    let desktop_apps = format!(r"{CONSENT_KEY}\NonPackaged");
+    let blocked_by = if denied(HKEY_LOCAL_MACHINE, CONSENT_KEY) {
+        Some("system")
+    } else if denied(HKEY_CURRENT_USER, CONSENT_KEY) {
+        Some("apps")
+    } else if denied(HKEY_CURRENT_USER, &desktop_apps) {
+        Some("desktop-apps")
+    } else {
+        None
+    };
+    match blocked_by {
+        Some(_) => (AccessState::Denied, blocked_by),
+        None => (AccessState::Allowed, None),
+    }
+}
+
+/// `AVCaptureDevice authorizationStatusForMediaType:` for audio, which reads the
+/// TCC entry without prompting.
+#[cfg(target_os = "macos")]
+fn platform_state() -> (AccessState, Option<&'static str>) {
+    use objc2::msg_send;
+    use objc2::runtime::AnyClass;
+    use objc2_foundation::NSString;
+
+    #[link(name = "AVFoundation", kind = "framework")]
+    extern "C" {}
+
+    let Some(class) = AnyClass::get(c"AVCaptureDevice") else {
+        return (AccessState::Unknown, None);
+    };
+    // The value of `AVMediaTypeAudio`.
+    let media_type = NSString::from_str("soun");
+    let status: isize = unsafe { msg_send![class, authorizationStatusForMediaType: &*media_type] };
+    let state = match status {
+        0 => AccessState::NotDetermined,
+        1 => AccessState::Restricted,
+        2 => AccessState::Denied,
+        3 => AccessState::Allowed,
+        _ => AccessState::Unknown,
+    };
+    (state, None)
+}
+
+/// Linux has no OS-level switch for desktop apps.
+#[cfg(not(any(windows, target_os = "macos")))]
+fn platform_state() -> (AccessState, Option<&'static str>) {
+    (AccessState::Allowed, None)
+}
@@ -0,0 +1,314 @@
+//! Connected displays, for anything that places windows or captures the screen
+//! across monitors with different DPI. `monitors:changed` goes out when the set
+//! changes: plugging, unplugging, rearranging, rescaling or a moved taskbar.
+//!
+//! Windows reports a change through `WM_DISPLAYCHANGE` on the main window;
+//! a poll catches work-area changes and everything on the other platforms.
+
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use tauri::{AppHandle, Manager, PhysicalPosition, PhysicalSize, WebviewWindow};
+
+use crate::events::{self, emit_event};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(3);
+
+static APP: OnceLock<AppHandle> = OnceLock::new();
+static LAST: Mutex<Option<Vec<MonitorInfo>>> = Mutex::new(None);
+
+/// A rectangle in physical pixels on the virtual desktop.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScreenRect {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl ScreenRect {
+    fn right(&self) -> i64 {
+        self.x as i64 + self.width as i64
+    }
+
+    fn bottom(&self) -> i64 {
+        self.y as i64 + self.height as i64
+    }
+
+    fn overlap(&self, other: &ScreenRect) -> u64 {
+        let width = self.right().min(other.right()) - (self.x.max(other.x) as i64);
+        let height = self.bottom().min(other.bottom()) - (self.y.max(other.y) as i64);
+        if width <= 0 || height <= 0 {
+            return 0;
+        }
+        width as u64 * height as u64
+    }
+
+    /// Squared distance between the centres, to find the nearest display.
+    fn distance(&self, other: &ScreenRect) -> i64 {
+        let dx =
+            (self.x as i64 * 2 + self.width as i64) - (other.x as i64 * 2 + other.width as i64);
+        let dy =
+            (self.y as i64 * 2 + self.height as i64) - (other.y as i64 * 2 + other.height as i64);
+        dx * dx + dy * dy
+    }
+}
+
+/// One connected display. Positions and sizes are physical pixels.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MonitorInfo {
+    pub name: Option<String>,
+    pub position: PhysicalPosition<i32>,
+    pub size: PhysicalSize<u32>,
+    pub scale_factor: f64,
+    pub is_primary: bool,
+    /// The part not covered by taskbars and docks.
+    pub work_area: ScreenRect,
+}
+
+pub fn list(app: &AppHandle) -> Result<Vec<MonitorInfo>> {
+    let primary = app
+        .primary_monitor()
+        .context("query primary monitor")?
+        .map(|monitor| *monitor.position());
+    let monitors = app
+        .available_monitors()
+        .context("query monitors")?
+        .into_iter()
+        .map(|monitor| {
+            let work_area = monitor.work_area();
+            MonitorInfo {
+                name: monitor.name().cloned(),
+                position: *monitor.position(),
+                size: *monitor.size(),
+                scale_factor: monitor.scale_factor(),
+                is_primary: primary == Some(*monitor.position()),
+                work_area: ScreenRect {
+                    x: work_area.position.x,
+                    y: work_area.position.y,
+                    width: work_area.size.width,
+                    height: work_area.size.height,
+                },
+            }
+        })
+        .collect();
+    Ok(monitors)
+}
+
+pub fn start(app: &AppHandle) {
+    let _ = APP.set(app.clone());
+    *LAST.lock().unwrap() = list(app).ok();
+    thread::spawn(|| loop {
+        thread::sleep(POLL_INTERVAL);
+        notify_changed();
+    });
+}
+
+/// Re-reads the displays and emits `monitors:changed` if they differ from the
+/// last read; safe to call from any thread. A changed set also brings the main
+/// window back onto a display if it was left outside all of them.
+pub fn notify_changed() {
+    let Some(app) = APP.get() else {
+        return;
+    };
+    let monitors = match list(app) {
+        Ok(monitors) => monitors,
+        Err(error) => {
+            log::warn!(target: "window", "Monitor query failed: {error:#}");
+            return;
+        }
+    };
+    if !remember(&mut LAST.lock().unwrap(), &monitors) {
+        return;
+    }
+    log::info!(
+        target: "window",
+        "Displays changed: {}",
+        monitors
+            .iter()
+            .map(|monitor| format!(
+                "{}x{}@{}",
+                monitor.size.width, monitor.size.height, monitor.scale_factor
+            ))
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    let _ = emit_event(app, events::MONITORS_CHANGED, &monitors);
+    if let Some(window) = app.get_webview_window("main") {
+        keep_on_screen(&window, &monitors);
+    }
+}
+
+/// Stores `monitors` as the last read and tells whether they differ from it.
+fn remember(last: &mut Option<Vec<MonitorInfo>>, monitors: &[MonitorInfo]) -> bool {
+    if last.as_deref() == Some(monitors) {
+        return false;
+    }
+    *last = Some(monitors.to_vec());
+    true
+}
+
+/// Moves `window` onto the nearest display when part of it lies outside every
+/// work area, as after the display it was on went away.
+pub fn keep_on_screen(window: &WebviewWindow, monitors: &[MonitorInfo]) {
+    let (Ok(position), Ok(size)) = (window.outer_position(), window.outer_size()) else {
+        return;
+    };
+    if window.is_minimized().unwrap_or(false) || window.is_maximized().unwrap_or(false) {
+        return;
+    }
+    let frame = ScreenRect {
+        x: position.x,
+        y: position.y,
+        width: size.width,
+        height: size.height,
+    };
+    let work_areas: Vec<ScreenRect> = monitors.iter().map(|monitor| monitor.work_area).collect();
+    let Some((x, y)) = clamp_to_work_areas(&frame, &work_areas) else {
+        return;
+    };
+    log::info!(
+        target: "window",
+        "Moving main window from {},{} to {x},{y} to keep it on screen",
+        frame.x,
+        frame.y
+    );
+    if let Err(error) = window.set_position(PhysicalPosition::new(x, y)) {
+        log::warn!(target: "window", "failed to move window on screen: {error}");
+    }
+}
+
+/// Where `frame` has to go to lie within the union of `work_areas`, or `None`
+/// when it already does. The target is the work area it overlaps most, or the
+/// nearest one; a frame larger than it is aligned to its top-left corner.
+pub fn clamp_to_work_areas(frame: &ScreenRect, work_areas: &[ScreenRect]) -> Option<(i32, i32)> {
+    // Work areas of different displays never overlap, so the parts add up.
+    let covered: u64 = work_areas.iter().map(|area| frame.overlap(area)).sum();
+    if work_areas.is_empty() || covered >= frame.width as u64 * frame.height as u64 {
+        return None;
+    }
+    let target = work_areas
+        .iter()
+        .max_by_key(|area| (frame.overlap(area), -frame.distance(area)))?;
+    let x = (frame.x as i64)
+        .min(target.right() - frame.width as i64)
+        .max(target.x as i64);
+    let y = (frame.y as i64)
+        .min(target.bottom() - frame.height as i64)
+        .max(target.y as i64);
+    let moved = (x as i32, y as i32);
+    (moved != (frame.x, frame.y)).then_some(moved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(x: i32, y: i32, width: u32, height: u32) -> ScreenRect {
+        ScreenRect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    fn monitor(bounds: ScreenRect, scale_factor: f64, taskbar: u32) -> MonitorInfo {
+        MonitorInfo {
+            name: None,
+            position: PhysicalPosition::new(bounds.x, bounds.y),
+            size: PhysicalSize::new(bounds.width, bounds.height),
+            scale_factor,
+            is_primary: bounds.x == 0 && bounds.y == 0,
+            work_area: ScreenRect {
+                height: bounds.height - taskbar,
+                ..bounds
+            },
+        }
+    }
+
+    /// A 1080p screen left of a 4K primary with a taskbar, and a 1440p one right of it.
+    fn three_displays() -> Vec<MonitorInfo> {
+        vec![
+            monitor(rect(-1920, 0, 1920, 1080), 1.0, 0),
+            monitor(rect(0, 0, 3840, 2160), 1.5, 72),
+            monitor(rect(3840, 0, 2560, 1440), 1.25, 0),
+        ]
+    }
+
+    fn clamp(frame: ScreenRect, monitors: &[MonitorInfo]) -> Option<(i32, i32)> {
+        let work_areas: Vec<ScreenRect> =
+            monitors.iter().map(|monitor| monitor.work_area).collect();
+        clamp_to_work_areas(&frame, &work_areas)
+    }
+
+    #[test]
+    fn windows_inside_the_work_areas_stay() {
+        let displays = three_displays();
+        assert_eq!(clamp(rect(200, 200, 800, 600), &displays), None);
+        assert_eq!(clamp(rect(-1700, 100, 800, 600), &displays), None);
+        // Straddling two displays is fine as long as the union covers it.
+        assert_eq!(clamp(rect(3500, 100, 800, 600), &displays), None);
+    }
+
+    #[test]
+    fn windows_under_a_taskbar_or_off_an_edge_are_pulled_in() {
+        let displays = three_displays();
+        assert_eq!(
+            clamp(rect(1000, 2000, 800, 600), &displays),
+            Some((1000, 2088 - 600))
+        );
+        assert_eq!(
+            clamp(rect(-1000, 900, 800, 400), &displays),
+            Some((-1000, 680))
+        );
+        assert_eq!(
+            clamp(rect(6000, 1000, 800, 600), &displays),
+            Some((6400 - 800, 1440 - 600))
+        );
+    }
+
+    #[test]
+    fn windows_left_on_an_unplugged_display_go_to_the_nearest_one() {
+        let mut displays = three_displays();
+        let frame = rect(-1500, 300, 800, 600);
+        assert_eq!(clamp(frame, &displays), None);
+        displays.remove(0);
+        assert_eq!(clamp(frame, &displays), Some((0, 300)));
+        assert_eq!(
+            clamp(rect(9000, 300, 800, 600), &displays),
+            Some((5600, 300))
+        );
+    }
+
+    #[test]
+    fn windows_larger_than_the_target_align_to_its_top_left() {
+        let displays = three_displays();
+        assert_eq!(clamp(rect(100, 100, 4000, 3000), &displays), Some((0, 0)));
+    }
+
+    #[test]
+    fn no_displays_leave_the_window_alone() {
+        assert_eq!(clamp(rect(100, 100, 800, 600), &[]), None);
+    }
+
+    #[test]
+    fn only_a_different_set_counts_as_a_change() {
+        let mut last = None;
+        let mut displays = three_displays();
+        assert!(remember(&mut last, &displays));
+        assert!(!remember(&mut last, &displays));
+        displays[2].scale_factor = 1.5;
+        assert!(remember(&mut last, &displays));
+        displays[1].work_area.height = 2100;
+        assert!(remember(&mut last, &displays));
+        displays.pop();
+        assert!(remember(&mut last, &displays));
+        assert_eq!(last, Some(displays));
+    }
+}
@@ -1,13 +1,30 @@
+use std::net::Ipv4Addr;
+use std::sync::Arc;
 use std::time::Duration;
 
 use anyhow::{anyhow, Result};
 use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
 
+use crate::auth::{self, AuthQueue};
 use crate::constants::{
     BACKEND_DOMAIN_RU, DEFAULT_BACKEND_DOMAIN, OAUTH_APP_NAME, OAUTH_SCHEME, SITE_BASE_URL,
 };
+use crate::types::AuthTokensPayload;
 
 const AUTH_METHODS_TIMEOUT_MS: u64 = 10_000;
+const TOKEN_REFRESH_TIMEOUT_MS: u64 = 15_000;
+const LOOPBACK_CALLBACK_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+const LOOPBACK_MAX_REQUEST_BYTES: usize = 64 * 1024;
+// Browsers open idle preconnect sockets; they are dropped after this long.
+const LOOPBACK_READ_TIMEOUT: Duration = Duration::from_secs(10);
+const LOOPBACK_DONE_PAGE: &str =
+    "<!doctype html><html><head><meta charset=\"utf-8\"><title>XexamAI</title></head>\
+<body style=\"font-family:sans-serif;text-align:center;padding-top:4rem\">\
+<h2>Sign-in complete</h2><p>You can close this tab and return to XexamAI.</p></body></html>";
 const SUPPORTED_OAUTH_PROVIDERS: &[&str] = &["google", "github", "discord", "yandex"];
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -58,7 +75,7 @@ fn resolve_site_base_by_domain(backend_domain: Option<&str>) -> String {
     format!("https://{resolved_domain}")
 }
 
-fn resolve_auth_api_base(backend_domain: Option<&str>) -> String {
+pub(crate) fn resolve_api_base(backend_domain: Option<&str>) -> String {
     normalize_base(env("XEXAMAI_AUTH_API_BASE_URL"))
         .or_else(|| normalize_base(env("XEXAMAI_API_BASE_URL")))
         .or_else(|| normalize_base(env("API_BASE_URL")))
@@ -98,8 +115,11 @@ fn normalize_auth_methods(mut methods: AuthMethods) -> AuthMethods {
     methods
 }
 
-pub async fn load_auth_methods(backend_domain: Option<&str>) -> Result<AuthMethods> {
-    let base = resolve_auth_api_base(backend_domain);
+pub async fn load_auth_methods(
+    client: &reqwest::Client,
+    backend_domain: Option<&str>,
+) -> Result<AuthMethods> {
+    let base = resolve_api_base(backend_domain);
     let url = format!("{}/auth/methods/", base.trim_end_matches('/'));
     log::info!(
         target: "auth",
@@ -107,11 +127,9 @@ pub async fn load_auth_methods(backend_domain: Option<&str>) -> Result<AuthMetho
         url,
         backend_domain
     );
-    let client = reqwest::Client::builder()
-        .timeout(Duration::from_millis(AUTH_METHODS_TIMEOUT_MS))
-        .build()?;
     let response = client
         .get(url)
+        .timeout(Duration::from_millis(AUTH_METHODS_TIMEOUT_MS))
         .header(reqwest::header::ACCEPT, "application/json")
         .send()
         .await?;
@@ -140,17 +158,42 @@ pub async fn load_auth_methods(backend_domain: Option<&str>) -> Result<AuthMetho
     Ok(methods)
 }
 
+pub async fn refresh_tokens(
+    client: &reqwest::Client,
+    backend_domain: Option<&str>,
+    refresh: &str,
+) -> Result<AuthTokensPayload> {
+    let base = resolve_api_base(backend_domain);
+    let url = format!("{}/auth/token/refresh/", base.trim_end_matches('/'));
+    log::info!(target: "auth", "Refreshing access token: url={url}");
+    let response = client
+        .post(url)
+        .timeout(Duration::from_millis(TOKEN_REFRESH_TIMEOUT_MS))
+        .header(reqwest::header::ACCEPT, "application/json")
+        .json(&serde_json::json!({ "refresh": refresh }))
+        .send()
+        .await?;
+    let status = response.status();
+    if !status.is_success() {
+        log::warn!(target: "auth", "Token refresh failed: HTTP {}", status.as_u16());
+        return Err(anyhow!(
+            "Failed to refresh access token: HTTP {}",
+            status.as_u16()
+        ));
+    }
+    Ok(response.json::<AuthTokensPayload>().await?)
+}
+
 fn desktop_oauth_redirect_uri() -> String {
     format!("{OAUTH_SCHEME}://auth/callback")
 }
 
-fn with_desktop_oauth_query(mut url: url::Url, state: &str) -> String {
+fn with_desktop_oauth_query(mut url: url::Url, state: &str, redirect_uri: &str) -> String {
     let existing_query: Vec<(String, String)> = url
         .query_pairs()
         .filter(|(key, _)| key != "app_auth" && key != "redirect_uri" && key != "state")
         .map(|(key, value)| (key.into_owned(), value.into_owned()))
         .collect();
-    let redirect_uri = desktop_oauth_redirect_uri();
     url.set_query(None);
     {
         let mut query = url.query_pairs_mut();
@@ -158,30 +201,40 @@ fn with_desktop_oauth_query(mut url: url::Url, state: &str) -> String {
             query.append_pair(&key, &value);
         }
         query.append_pair("app_auth", OAUTH_APP_NAME);
-        query.append_pair("redirect_uri", &redirect_uri);
+        query.append_pair("redirect_uri", redirect_uri);
         query.append_pair("state", state);
     }
     url.to_string()
 }
 
+/// Whether the OAuth callback should go through a loopback listener instead of the
+/// `xexamai://` scheme. Linux desktops frequently never register the scheme handler.
+pub fn prefers_loopback_redirect() -> bool {
+    match env("XEXAMAI_OAUTH_LOOPBACK") {
+        Some(value) => matches!(value.trim(), "1" | "true" | "yes"),
+        None => cfg!(target_os = "linux"),
+    }
+}
+
 pub fn build_oauth_start_url(
     provider: &str,
     backend_domain: Option<&str>,
     state: &str,
+    redirect_uri: Option<&str>,
 ) -> Result<String> {
     let provider_lower = provider.to_lowercase();
+    let redirect_uri = redirect_uri
+        .map(str::to_string)
+        .unwrap_or_else(desktop_oauth_redirect_uri);
     let key = format!("OAUTH_PROVIDER_URL_{}", provider_lower.to_uppercase());
     if let Some(override_url) = env(&key) {
-        let url = with_desktop_oauth_query(
-            url::Url::parse(&override_url)?,
-            state,
-        );
+        let url = with_desktop_oauth_query(url::Url::parse(&override_url)?, state, &redirect_uri);
         log::info!(
             target: "auth",
             "Built OAuth start URL from provider override: provider={} key={} redirect_uri={}",
             provider_lower,
             key,
-            desktop_oauth_redirect_uri()
+            redirect_uri
         );
         return Ok(url);
     }
@@ -198,13 +251,121 @@ pub fn build_oauth_start_url(
         });
     let mut url = url::Url::parse(&base)?;
     url.set_path(&format!("/auth/oauth/{}/start", provider_lower));
-    let result = with_desktop_oauth_query(url, state);
+    let result = with_desktop_oauth_query(url, state, &redirect_uri);
     log::info!(
         target: "auth",
         "Built OAuth start URL: provider={} base={} redirect_uri={}",
         provider_lower,
         base,
-        desktop_oauth_redirect_uri()
+        redirect_uri
     );
     Ok(result)
 }
+
+/// Binds an ephemeral loopback port for the OAuth callback and returns its redirect URI.
+pub async fn bind_loopback_listener() -> Result<(TcpListener, String)> {
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await?;
+    let port = listener.local_addr()?.port();
+    Ok((listener, format!("http://127.0.0.1:{port}/callback")))
+}
+
+pub fn spawn_loopback_callback(app: AppHandle, queue: Arc<AuthQueue>, listener: TcpListener) {
+    tauri::async_runtime::spawn(async move {
+        match tokio::time::timeout(
+            LOOPBACK_CALLBACK_TIMEOUT,
+            accept_loopback_callback(listener),
+        )
+        .await
+        {
+            Ok(Ok(url)) => auth::handle_loopback_callback(app, queue, url).await,
+            Ok(Err(error)) => {
+                log::error!(target: "auth", "Loopback OAuth listener failed: {error}");
+            }
+            Err(_) => {
+                log::warn!(target: "auth", "Loopback OAuth listener timed out");
+            }
+        }
+    });
+}
+
+/// Serves every connection on its own task, so an idle socket cannot hold up
+/// the real callback, and returns the first request that carries the payload.
+async fn accept_loopback_callback(listener: TcpListener) -> Result<url::Url> {
+    let (found_tx, mut found_rx) = mpsc::channel(1);
+    loop {
+        tokio::select! {
+            Some(url) = found_rx.recv() => return Ok(url),
+            accepted = listener.accept() => {
+                let (stream, peer) = accepted?;
+                if !peer.ip().is_loopback() {
+                    log::warn!(target: "auth", "Rejected non-loopback OAuth callback: peer={peer}");
+                    continue;
+                }
+                tauri::async_runtime::spawn(serve_loopback_connection(stream, found_tx.clone()));
+            }
+        }
+    }
+}
+
+async fn serve_loopback_connection(mut stream: TcpStream, found: mpsc::Sender<url::Url>) {
+    let target =
+        match tokio::time::timeout(LOOPBACK_READ_TIMEOUT, read_request_target(&mut stream)).await {
+            Ok(Ok(target)) => target,
+            Ok(Err(error)) => {
+                log::warn!(target: "auth", "Malformed loopback OAuth request: {error}");
+                let _ = write_response(&mut stream, "400 Bad Request", "Bad request").await;
+                return;
+            }
+            Err(_) => {
+                log::debug!(target: "auth", "Dropped an idle loopback OAuth connection");
+                return;
+            }
+        };
+    if !target.starts_with("/callback") {
+        // Browsers probe for /favicon.ico and similar before the real callback.
+        let _ = write_response(&mut stream, "404 Not Found", "Not found").await;
+        return;
+    }
+    let url = match url::Url::parse(&format!("http://127.0.0.1{target}")) {
+        Ok(url) if auth::is_loopback_callback(&url) => url,
+        _ => {
+            log::warn!(target: "auth", "Loopback OAuth request without a payload or state ignored");
+            let _ = write_response(&mut stream, "400 Bad Request", "Missing sign-in payload").await;
+            return;
+        }
+    };
+    let _ = write_response(&mut stream, "200 OK", LOOPBACK_DONE_PAGE).await;
+    let _ = found.send(url).await;
+}
+
+async fn read_request_target(stream: &mut TcpStream) -> Result<String> {
+    let mut buffer = Vec::new();
+    let mut chunk = [0u8; 4096];
+    while !buffer.windows(4).any(|window| window == b"\r\n\r\n") {
+        let read = stream.read(&mut chunk).await?;
+        if read == 0 {
+            break;
+        }
+        buffer.extend_from_slice(&chunk[..read]);
+        if buffer.len() > LOOPBACK_MAX_REQUEST_BYTES {
+            return Err(anyhow!("Request is too large"));
+        }
+    }
+    let head = String::from_utf8_lossy(&buffer);
+    let request_line = head.lines().next().unwrap_or_default();
+    let mut parts = request_line.split_whitespace();
+    match (parts.next(), parts.next()) {
+        (Some("GET"), Some(target)) => Ok(target.to_string()),
+        _ => Err(anyhow!("Unsupported request: {request_line}")),
+    }
+}
+
+async fn write_response(stream: &mut TcpStream, status: &str, body: &str) -> Result<()> {
+    let response = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.flush().await?;
+    Ok(())
+}
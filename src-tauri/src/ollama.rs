@@ -1,12 +1,75 @@
 use anyhow::{anyhow, Result};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
 use std::io;
 use std::process::Stdio;
+use std::time::Duration;
 use tokio::process::Command;
 
+use crate::system_resources::SystemResources;
+
 #[cfg(windows)]
 const CREATE_NO_WINDOW: u32 = 0x0800_0000;
 
 const LIST_JSON_FLAG: &str = "--json";
+const TAGS_URL: &str = "http://127.0.0.1:11434/api/tags";
+const VERSION_URL: &str = "http://127.0.0.1:11434/api/version";
+// A debug report should not wait on a server that is not running.
+const VERSION_TIMEOUT: Duration = Duration::from_millis(1500);
+// Weights of the default 4-bit builds, per billion parameters.
+const ESTIMATED_BYTES_PER_BILLION_PARAMS: f64 = 0.65e9;
+// Context cache and runtime buffers on top of the weights.
+const MEMORY_OVERHEAD: f64 = 1.2;
+
+/// How a model compares with the memory of this machine.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ModelFit {
+    /// Fits in VRAM, or in free RAM on machines without a usable GPU.
+    Fit,
+    /// Only fits split across VRAM and RAM; it runs, slowly.
+    Tight,
+    WontFit,
+    /// Size or memory could not be determined.
+    Unknown,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelRecommendation {
+    pub name: String,
+    pub installed: bool,
+    /// Download size: from Ollama when installed, estimated from the name otherwise.
+    pub size_bytes: Option<u64>,
+    pub required_bytes: Option<u64>,
+    pub fit: ModelFit,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PullOutcome {
+    /// Set when the model likely exceeds memory; also sent as `ollama:memory-warning`
+    /// before the download starts.
+    pub memory_warning: Option<ModelRecommendation>,
+}
+
+#[derive(Deserialize)]
+struct TagsResponse {
+    #[serde(default)]
+    models: Vec<TaggedModel>,
+}
+
+#[derive(Deserialize)]
+struct VersionResponse {
+    version: String,
+}
+
+#[derive(Deserialize)]
+struct TaggedModel {
+    name: String,
+    #[serde(default)]
+    size: Option<u64>,
+}
 
 fn normalize_model_name(model: &str) -> String {
     model.trim().to_lowercase()
@@ -166,3 +229,132 @@ pub async fn warmup_model(model: &str) -> Result<()> {
         ))
     }
 }
+
+/// Classifies the installed models plus `candidates` (names that may not be pulled
+/// yet) against `resources`. Installed models are listed first, smallest first.
+pub async fn recommend_models(
+    client: &Client,
+    candidates: &[String],
+    resources: &SystemResources,
+) -> Vec<ModelRecommendation> {
+    let installed = match installed_sizes(client).await {
+        Ok(models) => models,
+        Err(error) => {
+            log::warn!(target: "ollama", "Installed models unavailable: {error:#}");
+            Vec::new()
+        }
+    };
+    let mut out: Vec<ModelRecommendation> = installed
+        .into_iter()
+        .map(|(name, size)| recommendation(name, true, size, resources))
+        .collect();
+    for candidate in candidates {
+        let name = normalize_model_name(candidate);
+        if name.is_empty() || out.iter().any(|known| known.name == name) {
+            continue;
+        }
+        let size = estimate_size(&name);
+        out.push(recommendation(name, false, size, resources));
+    }
+    out.sort_by_key(|model| (!model.installed, model.size_bytes.unwrap_or(u64::MAX)));
+    out
+}
+
+/// The recommendation for one model, using the installed size when there is one.
+pub async fn check_model(
+    client: &Client,
+    model: &str,
+    resources: &SystemResources,
+) -> ModelRecommendation {
+    let name = normalize_model_name(model);
+    let installed = installed_sizes(client)
+        .await
+        .ok()
+        .and_then(|models| models.into_iter().find(|(known, _)| *known == name));
+    match installed {
+        Some((name, size)) => recommendation(name, true, size, resources),
+        None => {
+            let size = estimate_size(&name);
+            recommendation(name, false, size, resources)
+        }
+    }
+}
+
+/// Number of installed models, read from the running server; fails when it is
+/// not reachable.
+pub async fn installed_models(client: &Client) -> Result<usize> {
+    Ok(installed_sizes(client).await?.len())
+}
+
+/// Version reported by the running server.
+pub async fn server_version(client: &Client) -> Result<String> {
+    let response: VersionResponse = client
+        .get(VERSION_URL)
+        .timeout(VERSION_TIMEOUT)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    Ok(response.version)
+}
+
+async fn installed_sizes(client: &Client) -> Result<Vec<(String, Option<u64>)>> {
+    let tags: TagsResponse = client
+        .get(TAGS_URL)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    Ok(tags
+        .models
+        .into_iter()
+        .map(|model| (normalize_model_name(&model.name), model.size))
+        .collect())
+}
+
+fn recommendation(
+    name: String,
+    installed: bool,
+    size_bytes: Option<u64>,
+    resources: &SystemResources,
+) -> ModelRecommendation {
+    let required_bytes = size_bytes.map(|size| (size as f64 * MEMORY_OVERHEAD) as u64);
+    ModelRecommendation {
+        name,
+        installed,
+        size_bytes,
+        required_bytes,
+        fit: required_bytes.map_or(ModelFit::Unknown, |required| classify(required, resources)),
+    }
+}
+
+fn classify(required: u64, resources: &SystemResources) -> ModelFit {
+    let Some(available_ram) = resources.available_ram_bytes else {
+        return ModelFit::Unknown;
+    };
+    let unified = resources.gpus.iter().any(|gpu| gpu.unified_memory);
+    let (fast, total) = match resources.gpu_memory_bytes() {
+        // Unified memory is already part of the RAM figure.
+        Some(gpu) if unified => (gpu.min(available_ram), available_ram),
+        Some(vram) => (vram, vram + available_ram),
+        None => (available_ram, available_ram),
+    };
+    if required <= fast {
+        ModelFit::Fit
+    } else if required <= total {
+        ModelFit::Tight
+    } else {
+        ModelFit::WontFit
+    }
+}
+
+/// Size from the parameter count in the tag, e.g. `llama3.1:70b` or `qwen2.5:0.5b`.
+fn estimate_size(name: &str) -> Option<u64> {
+    let tag = name.rsplit_once(':').map_or(name, |(_, tag)| tag);
+    tag.split(['-', '_'])
+        .find_map(|part| part.strip_suffix('b')?.parse::<f64>().ok())
+        .filter(|billions| *billions > 0.0)
+        .map(|billions| (billions * ESTIMATED_BYTES_PER_BILLION_PARAMS) as u64)
+}
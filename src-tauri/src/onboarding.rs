@@ -0,0 +1,201 @@
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use serde_json::json;
+use tauri::{AppHandle, Manager};
+
+use crate::audio::AudioManager;
+use crate::auth_store::AuthStore;
+use crate::config::ConfigState;
+use crate::local_speech::FastWhisperManager;
+use crate::ollama;
+use crate::types::{AppConfig, LlmHost, TranscriptionMode};
+
+const STEP_API_KEY: &str = "apiKey";
+const STEP_MICROPHONE: &str = "microphone";
+const STEP_LOCAL_SPEECH: &str = "localSpeech";
+const STEP_OLLAMA: &str = "ollama";
+const STEP_WELCOME: &str = "welcome";
+
+const KNOWN_STEPS: &[&str] = &[
+    STEP_API_KEY,
+    STEP_MICROPHONE,
+    STEP_LOCAL_SPEECH,
+    STEP_OLLAMA,
+    STEP_WELCOME,
+];
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OnboardingCheck {
+    pub id: &'static str,
+    pub label: &'static str,
+    /// False when the current modes do not depend on this check.
+    pub required: bool,
+    pub passed: bool,
+    /// Set once the user finished or skipped the step in the setup flow.
+    pub completed: bool,
+    pub hint: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OnboardingStatus {
+    pub checks: Vec<OnboardingCheck>,
+    pub completed_steps: Vec<String>,
+    /// True when every required check passes or was explicitly completed.
+    pub ready: bool,
+}
+
+pub async fn status(app: &AppHandle) -> Result<OnboardingStatus> {
+    let config = app.state::<Arc<ConfigState>>().get().await;
+    let completed = |id: &str| {
+        config
+            .onboarding_completed_steps
+            .iter()
+            .any(|step| step == id)
+    };
+
+    let mut checks = vec![
+        api_key_check(app, &config).await,
+        microphone_check(app),
+        local_speech_check(app, &config).await,
+        ollama_check(&config).await,
+    ];
+    for check in &mut checks {
+        check.completed = completed(check.id);
+    }
+    let ready = checks
+        .iter()
+        .all(|check| !check.required || check.passed || check.completed);
+
+    Ok(OnboardingStatus {
+        checks,
+        completed_steps: config.onboarding_completed_steps.clone(),
+        ready,
+    })
+}
+
+pub async fn complete_step(app: &AppHandle, step: &str) -> Result<OnboardingStatus> {
+    let step = step.trim();
+    if !KNOWN_STEPS.contains(&step) {
+        return Err(anyhow!("Unknown onboarding step: {step}"));
+    }
+    let config_state = app.state::<Arc<ConfigState>>();
+    let mut steps = config_state.get().await.onboarding_completed_steps;
+    if !steps.iter().any(|existing| existing == step) {
+        steps.push(step.to_string());
+        let mut partial = json!({ "onboardingCompletedSteps": steps });
+        if step == STEP_WELCOME {
+            partial["welcomeModalDismissed"] = json!(true);
+        }
+        config_state.update(partial, "onboarding").await?;
+        log::info!(target: "onboarding", "Onboarding step completed: {step}");
+    }
+    status(app).await
+}
+
+async fn api_key_check(app: &AppHandle, config: &AppConfig) -> OnboardingCheck {
+    let has_key = [&config.openai_api_key, &config.google_api_key]
+        .iter()
+        .any(|key| key.as_deref().is_some_and(|key| !key.trim().is_empty()));
+    let signed_in = match app.try_state::<Arc<AuthStore>>() {
+        Some(store) => !store.list(None).await.is_empty(),
+        None => false,
+    };
+    let passed = has_key || signed_in;
+    OnboardingCheck {
+        id: STEP_API_KEY,
+        label: "API key or account",
+        required: config.transcription_mode == TranscriptionMode::Api
+            || config.llm_host == LlmHost::Api,
+        passed,
+        completed: false,
+        hint: (!passed).then(|| {
+            "Add an OpenAI or Google API key in Settings, or sign in to use the hosted models."
+                .to_string()
+        }),
+    }
+}
+
+fn microphone_check(app: &AppHandle) -> OnboardingCheck {
+    let (passed, hint) = match app
+        .state::<Arc<AudioManager>>()
+        .list_devices()
+        .map(|devices| devices.iter().any(|device| device.kind == "mic"))
+    {
+        Ok(true) => (true, None),
+        Ok(false) => (
+            false,
+            Some("No microphone was found. Connect one and check the OS privacy settings.".into()),
+        ),
+        Err(error) => (
+            false,
+            Some(format!("Audio devices could not be listed: {error}")),
+        ),
+    };
+    OnboardingCheck {
+        id: STEP_MICROPHONE,
+        label: "Microphone",
+        required: true,
+        passed,
+        completed: false,
+        hint,
+    }
+}
+
+async fn local_speech_check(app: &AppHandle, config: &AppConfig) -> OnboardingCheck {
+    let required = config.transcription_mode == TranscriptionMode::Local;
+    // Only probe the server when it matters; the health check makes a network call.
+    let status = if required {
+        let manager = app.state::<Arc<FastWhisperManager>>().inner().clone();
+        Some(manager.check_health(app).await)
+    } else {
+        None
+    };
+    let passed = status
+        .as_ref()
+        .is_some_and(|status| status.installed && status.running);
+    let hint = match &status {
+        Some(status) if !status.installed => {
+            Some("Install the local speech server from Settings → Local speech.".to_string())
+        }
+        Some(status) if !status.running => {
+            Some("Start the local speech server from Settings → Local speech.".to_string())
+        }
+        _ => None,
+    };
+    OnboardingCheck {
+        id: STEP_LOCAL_SPEECH,
+        label: "Local speech server",
+        required,
+        passed,
+        completed: false,
+        hint,
+    }
+}
+
+async fn ollama_check(config: &AppConfig) -> OnboardingCheck {
+    let required = config.llm_host == LlmHost::Local;
+    let (passed, hint) = if required {
+        match ollama::check_installed().await {
+            Ok(true) => (true, None),
+            Ok(false) => (
+                false,
+                Some("Install Ollama from https://ollama.com and make sure it is in PATH.".into()),
+            ),
+            Err(error) => (false, Some(format!("Ollama check failed: {error}"))),
+        }
+    } else {
+        (false, None)
+    };
+    OnboardingCheck {
+        id: STEP_OLLAMA,
+        label: "Ollama",
+        required,
+        passed,
+        completed: false,
+        hint,
+    }
+}
@@ -0,0 +1,200 @@
+//! Per-command timings. Commands are declared with [`command`] instead of
+//! `#[tauri::command]`, which runs each body inside [`track`] under the
+//! function's name. `track` records a `tracing` span and event under the
+//! `commands` target, keeps the last few durations of each command for
+//! `perf_command_stats`, and emits `perf:slow-command` when a call takes longer
+//! than `slowCommandMs`.
+//!
+//! The timing wraps the body rather than the invoke handler: Tauri runs async
+//! commands on a spawned task and answers the webview from there, so the
+//! handler only ever sees the dispatch.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::future::Future;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tauri::AppHandle;
+use tracing::Instrument;
+
+use crate::constants::DEFAULT_SLOW_COMMAND_MS;
+use crate::errors::CommandResult;
+use crate::events::{self, emit_event};
+
+/// `#[tauri::command]` for an async command, timed through [`track`].
+pub use xexamai_macros::command;
+
+// Percentiles are taken over this many of the latest calls of each command.
+const SAMPLES_PER_COMMAND: usize = 200;
+
+static APP: OnceLock<AppHandle> = OnceLock::new();
+static SLOW_COMMAND_MS: AtomicU32 = AtomicU32::new(DEFAULT_SLOW_COMMAND_MS);
+static STATS: Mutex<BTreeMap<&'static str, Samples>> = Mutex::new(BTreeMap::new());
+
+#[derive(Default)]
+struct Samples {
+    count: u64,
+    failures: u64,
+    recent: VecDeque<Duration>,
+}
+
+/// Returned by `perf_command_stats`, one per command called since startup.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandStats {
+    pub command: &'static str,
+    pub count: u64,
+    pub failures: u64,
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+}
+
+/// Emitted as `perf:slow-command`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SlowCommand {
+    pub command: &'static str,
+    pub duration_ms: f64,
+    pub threshold_ms: u32,
+    pub ok: bool,
+}
+
+pub(crate) fn attach(app: &AppHandle) {
+    let _ = APP.set(app.clone());
+}
+
+pub fn set_slow_command_ms(threshold_ms: u32) {
+    SLOW_COMMAND_MS.store(threshold_ms, Ordering::Relaxed);
+}
+
+/// Runs the body of `command` and records how long it took and whether it failed.
+pub async fn track<T>(
+    command: &'static str,
+    body: impl Future<Output = CommandResult<T>>,
+) -> CommandResult<T> {
+    let span = tracing::debug_span!(target: "commands", "command", name = command);
+    let started = Instant::now();
+    let result = body.instrument(span).await;
+    record(command, started.elapsed(), result.is_ok());
+    result
+}
+
+fn record(command: &'static str, elapsed: Duration, ok: bool) {
+    let duration_ms = elapsed.as_secs_f64() * 1000.0;
+    tracing::debug!(target: "commands", command, duration_ms, ok, "command finished");
+    {
+        let mut stats = STATS.lock().unwrap();
+        let samples = stats.entry(command).or_default();
+        samples.count += 1;
+        if !ok {
+            samples.failures += 1;
+        }
+        if samples.recent.len() == SAMPLES_PER_COMMAND {
+            samples.recent.pop_front();
+        }
+        samples.recent.push_back(elapsed);
+    }
+    let threshold_ms = SLOW_COMMAND_MS.load(Ordering::Relaxed);
+    if duration_ms < threshold_ms as f64 {
+        return;
+    }
+    tracing::warn!(
+        target: "commands",
+        command,
+        duration_ms,
+        threshold_ms,
+        ok,
+        "slow command"
+    );
+    if let Some(app) = APP.get() {
+        let _ = emit_event(
+            app,
+            events::PERF_SLOW_COMMAND,
+            SlowCommand {
+                command,
+                duration_ms,
+                threshold_ms,
+                ok,
+            },
+        );
+    }
+}
+
+/// Count, failures and p50/p95 of the recent calls, per command, by name.
+pub fn command_stats() -> Vec<CommandStats> {
+    let stats = STATS.lock().unwrap();
+    stats
+        .iter()
+        .map(|(command, samples)| {
+            let mut recent: Vec<Duration> = samples.recent.iter().copied().collect();
+            recent.sort_unstable();
+            CommandStats {
+                command,
+                count: samples.count,
+                failures: samples.failures,
+                p50_ms: percentile_ms(&recent, 0.5),
+                p95_ms: percentile_ms(&recent, 0.95),
+            }
+        })
+        .collect()
+}
+
+/// Nearest-rank percentile of `sorted`, in milliseconds.
+fn percentile_ms(sorted: &[Duration], share: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let index = ((sorted.len() as f64 * share).ceil() as usize).clamp(1, sorted.len()) - 1;
+    sorted[index].as_secs_f64() * 1000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::errors::{CommandError, ErrorCode};
+
+    use super::*;
+
+    fn stats_of(command: &str) -> CommandStats {
+        command_stats()
+            .into_iter()
+            .find(|stats| stats.command == command)
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn track_counts_calls_and_failures() {
+        assert_eq!(track("perf_test_ok", async { Ok(7) }).await.unwrap(), 7);
+        let failed: CommandResult<()> = track("perf_test_ok", async {
+            Err(CommandError::new(ErrorCode::InvalidArgument, "bad"))
+        })
+        .await;
+        assert!(failed.is_err());
+        let stats = stats_of("perf_test_ok");
+        assert_eq!((stats.count, stats.failures), (2, 1));
+        assert!(stats.p50_ms <= stats.p95_ms);
+    }
+
+    #[test]
+    fn only_the_latest_calls_count_toward_percentiles() {
+        for _ in 0..SAMPLES_PER_COMMAND {
+            record("perf_test_window", Duration::from_secs(1), true);
+        }
+        for _ in 0..SAMPLES_PER_COMMAND {
+            record("perf_test_window", Duration::from_millis(2), true);
+        }
+        let stats = stats_of("perf_test_window");
+        assert_eq!(stats.count, 2 * SAMPLES_PER_COMMAND as u64);
+        assert_eq!((stats.p50_ms, stats.p95_ms), (2.0, 2.0));
+    }
+
+    #[test]
+    fn percentiles_use_the_nearest_rank() {
+        let sorted: Vec<Duration> = (1..=20).map(Duration::from_millis).collect();
+        assert_eq!(percentile_ms(&sorted, 0.5), 10.0);
+        assert_eq!(percentile_ms(&sorted, 0.95), 19.0);
+        assert_eq!(percentile_ms(&sorted[..1], 0.95), 1.0);
+        assert_eq!(percentile_ms(&[], 0.5), 0.0);
+    }
+}
@@ -0,0 +1,169 @@
+//! Notices resume from sleep and revalidates what does not survive it: capture
+//! streams, the local Whisper server, the token refresh timer and global hotkeys.
+//!
+//! Windows reports resume through `WM_POWERBROADCAST` on the main window. Elsewhere
+//! the monotonic clock stops while suspended, so a wall clock that ran ahead of it
+//! between two polls means the machine slept.
+
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+use crate::audio::AudioManager;
+use crate::auth_store::AuthStore;
+use crate::error_bus;
+use crate::errors::ErrorCode;
+use crate::events::{self, emit_event};
+use crate::hotkeys;
+use crate::local_speech::FastWhisperManager;
+
+#[cfg(not(windows))]
+const CLOCK_POLL_INTERVAL: Duration = Duration::from_secs(5);
+#[cfg(not(windows))]
+const SLEEP_GAP_THRESHOLD: Duration = Duration::from_secs(15);
+// Windows can deliver more than one resume notification per wake.
+const RESUME_DEBOUNCE: Duration = Duration::from_secs(10);
+
+static APP: OnceLock<AppHandle> = OnceLock::new();
+static LAST_RESUME: Mutex<Option<Instant>> = Mutex::new(None);
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ResumeSource {
+    #[cfg_attr(not(windows), allow(dead_code))]
+    PowerBroadcast,
+    ClockGap,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResumedPayload {
+    pub source: ResumeSource,
+    /// How long the machine slept, when the detector can tell.
+    pub slept_seconds: Option<u64>,
+    pub audio_restarted: bool,
+    pub local_speech_running: Option<bool>,
+    /// Recovery steps that failed, by name.
+    pub failed: Vec<String>,
+}
+
+pub fn start(app: &AppHandle) {
+    let _ = APP.set(app.clone());
+    #[cfg(not(windows))]
+    start_clock_watch();
+}
+
+/// Entry point for the platform notifications; safe to call from any thread.
+pub fn notify_resumed(source: ResumeSource, slept: Option<Duration>) {
+    let Some(app) = APP.get().cloned() else {
+        return;
+    };
+    {
+        let mut last = LAST_RESUME.lock().unwrap();
+        if last.is_some_and(|at| at.elapsed() < RESUME_DEBOUNCE) {
+            return;
+        }
+        *last = Some(Instant::now());
+    }
+    log::info!(
+        target: "power",
+        "System resumed: source={source:?} slept={:?}",
+        slept.map(|gap| gap.as_secs())
+    );
+    tauri::async_runtime::spawn(revalidate(app, source, slept));
+}
+
+/// Runs every recovery step in its own task so a failure or panic in one does not
+/// keep the others from running.
+async fn revalidate(app: AppHandle, source: ResumeSource, slept: Option<Duration>) {
+    let audio_app = app.clone();
+    let audio = tauri::async_runtime::spawn_blocking(move || {
+        let manager = audio_app.state::<Arc<AudioManager>>();
+        manager.restart_if_active(&audio_app).inspect_err(|error| {
+            error_bus::publish(
+                &audio_app,
+                "audio",
+                ErrorCode::AudioDeviceFailed,
+                error,
+                true,
+            );
+        })
+    });
+    let speech_app = app.clone();
+    let speech = tauri::async_runtime::spawn(async move {
+        let manager = speech_app
+            .state::<Arc<FastWhisperManager>>()
+            .inner()
+            .clone();
+        manager.check_health(&speech_app).await.running
+    });
+    let auth_app = app.clone();
+    let auth = tauri::async_runtime::spawn(async move {
+        auth_app.state::<Arc<AuthStore>>().recheck_expiry();
+    });
+    // Reports through `hotkeys:reapplied` on its own.
+    hotkeys::reapply_in_background(&app, "resume");
+
+    let mut failed = Vec::new();
+    let audio_restarted = match audio.await {
+        Ok(Ok(restarted)) => restarted,
+        Ok(Err(error)) => {
+            log::warn!(target: "power", "Capture restart after resume failed: {error:#}");
+            failed.push("audio".to_string());
+            false
+        }
+        Err(error) => {
+            log::error!(target: "power", "Capture restart after resume panicked: {error}");
+            failed.push("audio".to_string());
+            false
+        }
+    };
+    let local_speech_running = match speech.await {
+        Ok(running) => Some(running),
+        Err(error) => {
+            log::error!(target: "power", "Local server check after resume failed: {error}");
+            failed.push("localSpeech".to_string());
+            None
+        }
+    };
+    if let Err(error) = auth.await {
+        log::error!(target: "power", "Token expiry recheck after resume failed: {error}");
+        failed.push("auth".to_string());
+    }
+
+    let _ = emit_event(
+        &app,
+        events::SYSTEM_RESUMED,
+        ResumedPayload {
+            source,
+            slept_seconds: slept.map(|gap| gap.as_secs()),
+            audio_restarted,
+            local_speech_running,
+            failed,
+        },
+    );
+}
+
+#[cfg(not(windows))]
+fn start_clock_watch() {
+    std::thread::spawn(|| {
+        crate::crash::guard("power-watch", || {
+            let mut wall = std::time::SystemTime::now();
+            let mut mono = Instant::now();
+            loop {
+                std::thread::sleep(CLOCK_POLL_INTERVAL);
+                let now_wall = std::time::SystemTime::now();
+                let now_mono = Instant::now();
+                let wall_elapsed = now_wall.duration_since(wall).unwrap_or_default();
+                let gap = wall_elapsed.saturating_sub(now_mono - mono);
+                if gap > SLEEP_GAP_THRESHOLD {
+                    notify_resumed(ResumeSource::ClockGap, Some(gap));
+                }
+                wall = now_wall;
+                mono = now_mono;
+            }
+        })
+    });
+}
@@ -0,0 +1,180 @@
+//! Warns when capture starts while the window can still show up in screen shares,
+//! and keeps the exclusion on when WebView2 recreates the window.
+//!
+//! A GPU driver reset or theme change can give the main window a new HWND, which
+//! starts without the display affinity, ex-style and opacity set on the old one.
+//! A slow poll notices the new handle, applies them again and reports the result
+//! as `privacy:reapplied`, or `privacy:lost` when the exclusion did not take.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use serde::Serialize;
+use tauri::AppHandle;
+
+use crate::events::{self, emit_event};
+use crate::types::AppConfig;
+
+// New HWNDs seen since startup.
+static WINDOW_RECREATIONS: AtomicU64 = AtomicU64::new(0);
+
+/// What `capture_protection_status` reports, and the payload of
+/// `privacy:reapplied` and `privacy:lost`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CaptureProtectionStatus {
+    pub hide_app: bool,
+    /// What Windows reports for the main window; `None` where it cannot be checked.
+    pub excluded_from_capture: Option<bool>,
+    pub affinity_error: Option<String>,
+    /// How often the main window got a new HWND since startup.
+    pub window_recreations: u64,
+}
+
+pub fn status(app: &AppHandle, config: &AppConfig) -> CaptureProtectionStatus {
+    let (excluded_from_capture, affinity_error) = capture_exclusion(app);
+    CaptureProtectionStatus {
+        hide_app: config.hide_app,
+        excluded_from_capture,
+        affinity_error,
+        window_recreations: WINDOW_RECREATIONS.load(Ordering::Relaxed),
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrivacyWarning {
+    pub hide_app: bool,
+    /// What Windows reports for the main window; `None` where it cannot be checked.
+    pub excluded_from_capture: Option<bool>,
+    pub affinity_error: Option<String>,
+    /// The user wants a notification with an action to turn hide mode on.
+    pub notify: bool,
+}
+
+/// Emits `privacy:warning` when hide mode is off, or on but not in effect.
+pub fn check_on_capture(app: &AppHandle, config: &AppConfig) {
+    let (excluded_from_capture, affinity_error) = capture_exclusion(app);
+    if config.hide_app && excluded_from_capture != Some(false) {
+        return;
+    }
+    log::warn!(
+        target: "privacy",
+        "Capture started while the window is visible to screen capture: hide_app={} excluded={excluded_from_capture:?} error={}",
+        config.hide_app,
+        affinity_error.as_deref().unwrap_or("")
+    );
+    let _ = emit_event(
+        app,
+        events::PRIVACY_WARNING,
+        PrivacyWarning {
+            hide_app: config.hide_app,
+            excluded_from_capture,
+            affinity_error,
+            notify: config.warn_when_not_hidden,
+        },
+    );
+}
+
+/// Reads the display affinity back, since SetWindowDisplayAffinity can succeed or
+/// fail silently depending on the GPU driver.
+#[cfg(windows)]
+fn capture_exclusion(app: &AppHandle) -> (Option<bool>, Option<String>) {
+    use tauri::Manager;
+    use windows::Win32::Foundation::HWND;
+    use windows::Win32::UI::WindowsAndMessaging::{
+        GetWindowDisplayAffinity, WDA_EXCLUDEFROMCAPTURE,
+    };
+
+    let Some(window) = app.get_webview_window("main") else {
+        return (None, None);
+    };
+    let hwnd = match window.hwnd() {
+        Ok(hwnd) => HWND(hwnd.0),
+        Err(error) => return (None, Some(error.to_string())),
+    };
+    let mut affinity = 0u32;
+    match unsafe { GetWindowDisplayAffinity(hwnd, &mut affinity) } {
+        Ok(()) => (Some(affinity == WDA_EXCLUDEFROMCAPTURE.0), None),
+        Err(error) => (None, Some(error.to_string())),
+    }
+}
+
+#[cfg(not(windows))]
+fn capture_exclusion(_app: &AppHandle) -> (Option<bool>, Option<String>) {
+    (None, None)
+}
+
+/// Starts watching the main window's HWND. Only Windows recreates it.
+pub fn start(app: &AppHandle) {
+    #[cfg(windows)]
+    start_hwnd_watch(app.clone());
+    #[cfg(not(windows))]
+    let _ = app;
+}
+
+#[cfg(windows)]
+fn start_hwnd_watch(app: AppHandle) {
+    use std::time::Duration;
+
+    const HWND_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+    std::thread::spawn(move || {
+        let mut last = main_hwnd(&app);
+        loop {
+            std::thread::sleep(HWND_POLL_INTERVAL);
+            let current = main_hwnd(&app);
+            if current.is_none() || current == last {
+                continue;
+            }
+            let previous = std::mem::replace(&mut last, current);
+            // The first handle is the one `apply_window_preferences` styles.
+            if previous.is_some() {
+                WINDOW_RECREATIONS.fetch_add(1, Ordering::Relaxed);
+                reapply(&app);
+            }
+        }
+    });
+}
+
+#[cfg(windows)]
+fn main_hwnd(app: &AppHandle) -> Option<isize> {
+    use tauri::Manager;
+
+    let window = app.get_webview_window("main")?;
+    window.hwnd().ok().map(|hwnd| hwnd.0 as isize)
+}
+
+#[cfg(windows)]
+fn reapply(app: &AppHandle) {
+    use std::sync::Arc;
+    use tauri::Manager;
+
+    use crate::config::ConfigState;
+
+    let Some(window) = app.get_webview_window("main") else {
+        return;
+    };
+    let config = tauri::async_runtime::block_on(app.state::<Arc<ConfigState>>().get());
+    if let Err(error) = window.set_skip_taskbar(config.hide_app) {
+        log::warn!(target: "privacy", "failed to re-apply skip-taskbar: {error}");
+    }
+    crate::apply_native_window_style(
+        &window,
+        config.window_opacity.clamp(10, 100),
+        config.hide_app,
+        config.hide_from_switcher,
+    );
+    let status = status(app, &config);
+    if config.hide_app && status.excluded_from_capture != Some(true) {
+        log::warn!(
+            target: "privacy",
+            "Main window recreated and capture exclusion did not take: excluded={:?} error={}",
+            status.excluded_from_capture,
+            status.affinity_error.as_deref().unwrap_or("")
+        );
+        let _ = emit_event(app, events::PRIVACY_LOST, &status);
+    } else {
+        log::info!(target: "privacy", "Main window recreated; window style re-applied");
+        let _ = emit_event(app, events::PRIVACY_REAPPLIED, &status);
+    }
+}
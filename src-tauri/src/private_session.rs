@@ -0,0 +1,68 @@
+//! Private sessions: while one is on, nothing from it reaches disk. Debug WAVs,
+//! the plain-text transcript log, session history and clipboard mirroring are
+//! skipped, the pre-roll capture stops, frontend log lines lose their data,
+//! and prompt edits stay in memory (`ConfigState::hold_prompts`). The flag is never persisted, so every launch
+//! starts with it off.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use anyhow::Result;
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+use crate::audio;
+use crate::config::ConfigState;
+use crate::events::{self, emit_event};
+use crate::tray;
+
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrivateSessionChanged {
+    pub enabled: bool,
+}
+
+pub fn is_active() -> bool {
+    ACTIVE.load(Ordering::SeqCst)
+}
+
+/// Turns the private session on or off and emits `privacy:private-session`.
+/// Turning it off drops the prompt edits made during it and writes nothing else.
+pub async fn set(app: &AppHandle, enabled: bool) -> Result<bool> {
+    if ACTIVE.swap(enabled, Ordering::SeqCst) == enabled {
+        return Ok(enabled);
+    }
+    let config = app.state::<Arc<ConfigState>>();
+    if enabled {
+        config.hold_prompts().await;
+    } else if let Err(error) = config.release_prompts().await {
+        ACTIVE.store(true, Ordering::SeqCst);
+        return Err(error);
+    }
+    log::info!(
+        target: "privacy",
+        "Private session {}",
+        if enabled { "started" } else { "ended" }
+    );
+    tray::set_private_session(enabled);
+    // The pre-roll stops for the session and comes back after it.
+    audio::refresh_pre_roll(app);
+    let _ = emit_event(
+        app,
+        events::PRIVACY_PRIVATE_SESSION,
+        PrivateSessionChanged { enabled },
+    );
+    Ok(enabled)
+}
+
+/// For the hotkey: flips the private session off the calling thread.
+pub fn toggle(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(error) = set(&app, !is_active()).await {
+            log::warn!(target: "privacy", "Private session toggle failed: {error:#}");
+        }
+    });
+}
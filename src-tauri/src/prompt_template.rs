@@ -0,0 +1,324 @@
+//! `{{name}}` placeholders in the LLM prompt, filled here so every provider path
+//! sends the same text.
+//!
+//! Rules: a name is letters, digits and `_`, not starting with a digit, with
+//! optional spaces inside the braces. Single braces are plain text, so JSON
+//! examples need no escaping. `\{{` is a literal `{{`. In a longer run of braces
+//! the placeholder takes the innermost pair, so `{{{name}}}` is the value wrapped
+//! in single braces. Unclosed `{{` and invalid names are errors. Unknown names
+//! are left as written, with a warning, so prompts saved with a variable that
+//! was never defined here (`{{position}}`) still go out.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use chrono::Local;
+use serde::Serialize;
+
+use crate::foreground;
+use crate::types::AppConfig;
+
+pub const TRANSCRIPT: &str = "transcript";
+pub const SCREEN_TEXT: &str = "screen_text";
+pub const WINDOW_TITLE: &str = "window_title";
+pub const DATETIME: &str = "datetime";
+
+/// Always defined; custom variables cannot reuse these names.
+pub const BUILTIN_VARIABLES: &[&str] = &[TRANSCRIPT, SCREEN_TEXT, WINDOW_TITLE, DATETIME];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TemplateError {
+    Unclosed { offset: usize },
+    InvalidName { name: String, offset: usize },
+}
+
+impl TemplateError {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Unclosed { .. } => "unclosed",
+            Self::InvalidName { .. } => "invalid_name",
+        }
+    }
+
+    /// Character offset of the opening `{{`.
+    pub fn offset(&self) -> usize {
+        match self {
+            Self::Unclosed { offset } | Self::InvalidName { offset, .. } => *offset,
+        }
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        match self {
+            Self::Unclosed { .. } => None,
+            Self::InvalidName { name, .. } => Some(name),
+        }
+    }
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unclosed { offset } => write!(f, "`{{{{` at {offset} is never closed"),
+            Self::InvalidName { name, offset } => {
+                write!(f, "`{name}` at {offset} is not a valid variable name")
+            }
+        }
+    }
+}
+
+impl std::error::Error for TemplateError {}
+
+enum Segment<'a> {
+    Text(&'a str),
+    /// `raw` is the placeholder as written, braces included.
+    Variable {
+        name: &'a str,
+        raw: &'a str,
+    },
+}
+
+pub fn is_variable_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|first| first.is_ascii_alphabetic() || first == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn char_offset(template: &str, byte_offset: usize) -> usize {
+    template[..byte_offset].chars().count()
+}
+
+fn parse(template: &str) -> Result<Vec<Segment<'_>>, TemplateError> {
+    let bytes = template.as_bytes();
+    let mut segments = Vec::new();
+    let mut text_start = 0;
+    let mut index = 0;
+    // Braces and backslashes are ASCII, so every index sliced at is a char boundary.
+    while index < bytes.len() {
+        if bytes[index] == b'\\' && bytes[index + 1..].starts_with(b"{{") {
+            segments.push(Segment::Text(&template[text_start..index]));
+            segments.push(Segment::Text("{{"));
+            index += 3;
+            text_start = index;
+            continue;
+        }
+        if !bytes[index..].starts_with(b"{{") {
+            index += 1;
+            continue;
+        }
+        let run = bytes[index..].iter().take_while(|b| **b == b'{').count();
+        let open = index + run - 2;
+        let Some(length) = template[open + 2..].find("}}") else {
+            return Err(TemplateError::Unclosed {
+                offset: char_offset(template, open),
+            });
+        };
+        let close = open + 2 + length;
+        let name = template[open + 2..close].trim();
+        if !is_variable_name(name) {
+            return Err(TemplateError::InvalidName {
+                name: name.to_string(),
+                offset: char_offset(template, open),
+            });
+        }
+        segments.push(Segment::Text(&template[text_start..open]));
+        segments.push(Segment::Variable {
+            name,
+            raw: &template[open..close + 2],
+        });
+        index = close + 2;
+        text_start = index;
+    }
+    segments.push(Segment::Text(&template[text_start..]));
+    Ok(segments)
+}
+
+/// Names used in `template`, in order of first use.
+pub fn referenced(template: &str) -> Result<Vec<String>, TemplateError> {
+    let mut names: Vec<String> = Vec::new();
+    for segment in parse(template)? {
+        if let Segment::Variable { name, .. } = segment {
+            if !names.iter().any(|known| known == name) {
+                names.push(name.to_string());
+            }
+        }
+    }
+    Ok(names)
+}
+
+/// Names used in `template` that `values` has no value for.
+pub fn unknown(
+    template: &str,
+    values: &HashMap<String, String>,
+) -> Result<Vec<String>, TemplateError> {
+    let mut names = referenced(template)?;
+    names.retain(|name| !values.contains_key(name));
+    Ok(names)
+}
+
+/// `template` with its variables filled. Placeholders without a value stay as
+/// written.
+pub fn render(template: &str, values: &HashMap<String, String>) -> Result<String, TemplateError> {
+    let mut output = String::with_capacity(template.len());
+    let mut unknown: Vec<&str> = Vec::new();
+    for segment in parse(template)? {
+        match segment {
+            Segment::Text(text) => output.push_str(text),
+            Segment::Variable { name, raw } => match values.get(name) {
+                Some(value) => output.push_str(value),
+                None => {
+                    output.push_str(raw);
+                    if !unknown.contains(&name) {
+                        unknown.push(name);
+                    }
+                }
+            },
+        }
+    }
+    if !unknown.is_empty() {
+        log::warn!(
+            target: "prompt",
+            "Prompt uses variables without a value, sent as written: {}",
+            unknown.join(", ")
+        );
+    }
+    Ok(output)
+}
+
+/// Values for `template`: every built-in (empty unless the backend can fill it),
+/// the custom `promptVariables`, then `supplied`, which wins. The window is only
+/// read when the template uses `window_title`.
+pub async fn values(
+    config: &AppConfig,
+    template: &str,
+    supplied: HashMap<String, String>,
+) -> Result<HashMap<String, String>, TemplateError> {
+    let used = referenced(template)?;
+    let mut values: HashMap<String, String> = BUILTIN_VARIABLES
+        .iter()
+        .map(|name| (name.to_string(), String::new()))
+        .collect();
+    values.insert(
+        DATETIME.to_string(),
+        Local::now().format("%Y-%m-%d %H:%M").to_string(),
+    );
+    if used.iter().any(|name| name == WINDOW_TITLE) && !supplied.contains_key(WINDOW_TITLE) {
+        // macOS gives no titles without Screen Recording, so the app name stands in.
+        let title = foreground::window_info()
+            .await
+            .ok()
+            .and_then(|info| info.title.or(info.app_name))
+            .unwrap_or_default();
+        values.insert(WINDOW_TITLE.to_string(), title);
+    }
+    values.extend(config.prompt_variables.clone());
+    values.extend(supplied);
+    Ok(values)
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptPreview {
+    pub text: String,
+    /// Names the template uses, in order of first use.
+    pub variables: Vec<String>,
+    /// Used names without a value; `render` leaves them as written.
+    pub unknown: Vec<String>,
+}
+
+pub async fn preview(
+    config: &AppConfig,
+    template: &str,
+    supplied: HashMap<String, String>,
+) -> Result<PromptPreview, TemplateError> {
+    let values = values(config, template, supplied).await?;
+    Ok(PromptPreview {
+        text: render(template, &values)?,
+        variables: referenced(template)?,
+        unknown: unknown(template, &values)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn variables_are_filled_with_optional_inner_spaces() {
+        let values = values(&[("transcript", "hi"), ("role", "a tutor")]);
+        assert_eq!(
+            render("As {{role}}, answer: {{ transcript }}", &values).unwrap(),
+            "As a tutor, answer: hi"
+        );
+        assert_eq!(
+            referenced("{{role}} {{ transcript }} {{role}}").unwrap(),
+            ["role", "transcript"]
+        );
+    }
+
+    #[test]
+    fn a_run_of_braces_keeps_the_outer_ones() {
+        let values = values(&[("x", "v")]);
+        assert_eq!(render("{{{x}}}", &values).unwrap(), "{v}");
+        assert_eq!(render("{{{{x}}}}", &values).unwrap(), "{{v}}");
+        assert_eq!(
+            render(r#"{"a": {"b": 1}}"#, &values).unwrap(),
+            r#"{"a": {"b": 1}}"#
+        );
+    }
+
+    #[test]
+    fn escaped_braces_are_literal() {
+        let values = values(&[("x", "v")]);
+        assert_eq!(render(r"\{{x}} is {{x}}", &values).unwrap(), "{{x}} is v");
+        assert_eq!(render(r"\{{", &values).unwrap(), "{{");
+        assert_eq!(referenced(r"\{{x}}").unwrap(), Vec::<String>::new());
+    }
+
+    #[test]
+    fn unclosed_braces_are_errors() {
+        let error = render("Hi {{name", &HashMap::new()).unwrap_err();
+        assert_eq!(error, TemplateError::Unclosed { offset: 3 });
+        assert_eq!(error.kind(), "unclosed");
+        // Offsets count characters, not bytes.
+        let error = render("Привет {{", &HashMap::new()).unwrap_err();
+        assert_eq!(error.offset(), 7);
+    }
+
+    #[test]
+    fn invalid_names_are_errors() {
+        for (template, name) in [
+            ("{{1st}}", "1st"),
+            ("{{two words}}", "two words"),
+            ("{{}}", ""),
+            ("{{ na-me }}", "na-me"),
+        ] {
+            let error = render(template, &HashMap::new()).unwrap_err();
+            assert_eq!(error.kind(), "invalid_name", "{template}");
+            assert_eq!(error.name(), Some(name), "{template}");
+            assert_eq!(error.offset(), 0);
+        }
+    }
+
+    #[test]
+    fn unknown_names_are_sent_as_written() {
+        let values = values(&[("transcript", "hi")]);
+        let template = "Interview for {{position}} in {{ language }}: {{transcript}}";
+        assert_eq!(
+            render(template, &values).unwrap(),
+            "Interview for {{position}} in {{ language }}: hi"
+        );
+        assert_eq!(
+            unknown(template, &values).unwrap(),
+            ["position", "language"]
+        );
+    }
+}
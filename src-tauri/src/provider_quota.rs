@@ -0,0 +1,379 @@
+//! What the providers say about their own limits. OpenAI sends
+//! `x-ratelimit-remaining-*` and `x-ratelimit-reset-*` on every answer and
+//! `retry-after` on a 429; the rate limiter holds requests back while these say
+//! the next one would be refused. Transcription records them from its responses
+//! and the renderer reports the ones from its chat calls.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use reqwest::header::HeaderMap;
+use serde::Serialize;
+
+/// A reset this far out or further is not waited for; the request goes out
+/// and fails or succeeds on its own. Parsed durations are capped at it, so
+/// garbled headers cannot overflow the clock.
+const MAX_QUOTA_WAIT: Duration = Duration::from_secs(60);
+
+/// Header names, lower case, that `parse` reads.
+pub const QUOTA_HEADERS: &[&str] = &[
+    "x-ratelimit-limit-requests",
+    "x-ratelimit-remaining-requests",
+    "x-ratelimit-reset-requests",
+    "x-ratelimit-limit-tokens",
+    "x-ratelimit-remaining-tokens",
+    "x-ratelimit-reset-tokens",
+    "retry-after",
+    "retry-after-ms",
+];
+
+/// The quota headers of one response.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct QuotaHeaders {
+    pub limit_requests: Option<u64>,
+    pub remaining_requests: Option<u64>,
+    pub requests_reset: Option<Duration>,
+    pub limit_tokens: Option<u64>,
+    pub remaining_tokens: Option<u64>,
+    pub tokens_reset: Option<Duration>,
+    pub retry_after: Option<Duration>,
+}
+
+impl QuotaHeaders {
+    /// Reads the headers through `get`, which looks one up by its lower-case
+    /// name. `None` when the response carries none of them.
+    pub fn parse<'a>(get: impl Fn(&str) -> Option<&'a str>) -> Option<Self> {
+        let number = |name: &str| get(name).and_then(|value| value.trim().parse().ok());
+        let reset = |name: &str| get(name).and_then(parse_reset).map(capped);
+        let retry_after = get("retry-after-ms")
+            .and_then(|value| value.trim().parse::<f64>().ok())
+            .and_then(|ms| seconds(ms / 1000.0))
+            .or_else(|| get("retry-after").and_then(|value| parse_retry_after(value, Utc::now())))
+            .map(capped);
+        let headers = Self {
+            limit_requests: number("x-ratelimit-limit-requests"),
+            remaining_requests: number("x-ratelimit-remaining-requests"),
+            requests_reset: reset("x-ratelimit-reset-requests"),
+            limit_tokens: number("x-ratelimit-limit-tokens"),
+            remaining_tokens: number("x-ratelimit-remaining-tokens"),
+            tokens_reset: reset("x-ratelimit-reset-tokens"),
+            retry_after,
+        };
+        (headers != Self::default()).then_some(headers)
+    }
+
+    pub fn from_header_map(headers: &HeaderMap) -> Option<Self> {
+        Self::parse(|name| headers.get(name).and_then(|value| value.to_str().ok()))
+    }
+}
+
+fn capped(wait: Duration) -> Duration {
+    wait.min(MAX_QUOTA_WAIT)
+}
+
+/// `seconds` as a duration; `None` when negative or not a number, and
+/// `Duration::MAX` when too large to represent.
+fn seconds(seconds: f64) -> Option<Duration> {
+    if seconds.is_nan() || seconds < 0.0 {
+        return None;
+    }
+    Some(Duration::try_from_secs_f64(seconds).unwrap_or(Duration::MAX))
+}
+
+/// OpenAI's reset format: unit-suffixed parts such as `1s`, `6m0s`, `20ms` or
+/// `1h2m3.5s`. A bare number is taken as seconds.
+fn parse_reset(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(number) = value.parse::<f64>() {
+        return seconds(number);
+    }
+    let mut total = 0.0f64;
+    let mut rest = value;
+    while !rest.is_empty() {
+        let number_len = rest
+            .find(|ch: char| !(ch.is_ascii_digit() || ch == '.'))
+            .unwrap_or(rest.len());
+        let amount: f64 = rest[..number_len].parse().ok()?;
+        rest = &rest[number_len..];
+        let unit_len = rest
+            .find(|ch: char| ch.is_ascii_digit() || ch == '.')
+            .unwrap_or(rest.len());
+        let scale = match &rest[..unit_len] {
+            "h" => 3600.0,
+            "m" => 60.0,
+            "s" => 1.0,
+            "ms" => 0.001,
+            _ => return None,
+        };
+        total += amount * scale;
+        rest = &rest[unit_len..];
+    }
+    if value.is_empty() {
+        return None;
+    }
+    seconds(total)
+}
+
+/// `Retry-After` is either whole seconds or an HTTP date.
+fn parse_retry_after(value: &str, now: DateTime<Utc>) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let at = DateTime::parse_from_rfc2822(value).ok()?;
+    Some((at.with_timezone(&Utc) - now).to_std().unwrap_or_default())
+}
+
+struct Quota {
+    headers: QuotaHeaders,
+    received_at: Instant,
+    updated_at: DateTime<Utc>,
+}
+
+impl Quota {
+    fn remaining(&self, after: Option<Duration>, now: Instant) -> Option<Duration> {
+        after.map(|after| match self.received_at.checked_add(after) {
+            Some(at) => at.saturating_duration_since(now),
+            None => after,
+        })
+    }
+
+    /// How long the next request should wait, and why.
+    fn wait(&self, now: Instant) -> Option<(Duration, &'static str)> {
+        let headers = &self.headers;
+        let mut waits = vec![(self.remaining(headers.retry_after, now), "retry-after")];
+        if headers.remaining_requests == Some(0) {
+            waits.push((self.remaining(headers.requests_reset, now), "requests"));
+        }
+        if headers.remaining_tokens == Some(0) {
+            waits.push((self.remaining(headers.tokens_reset, now), "tokens"));
+        }
+        waits
+            .into_iter()
+            .filter_map(|(wait, reason)| wait.map(|wait| (wait, reason)))
+            .filter(|(wait, _)| !wait.is_zero() && *wait < MAX_QUOTA_WAIT)
+            .max_by_key(|(wait, _)| *wait)
+    }
+}
+
+/// The provider's view of its limits after its last answer.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderQuotaStatus {
+    /// Usage provider key, e.g. `openai`.
+    pub provider: String,
+    pub limit_requests: Option<u64>,
+    pub remaining_requests: Option<u64>,
+    /// Until the request window resets; 0 once it has, after which
+    /// `remaining_requests` is out of date.
+    pub requests_reset_ms: Option<u64>,
+    pub limit_tokens: Option<u64>,
+    pub remaining_tokens: Option<u64>,
+    pub tokens_reset_ms: Option<u64>,
+    /// Left of the last `retry-after`.
+    pub retry_after_ms: Option<u64>,
+    /// How long the next request will be held back; 0 when it goes straight out.
+    pub wait_ms: u64,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// The last quota headers per provider.
+pub struct ProviderQuotaState {
+    quotas: Mutex<HashMap<String, Quota>>,
+}
+
+impl ProviderQuotaState {
+    pub fn new() -> Self {
+        Self {
+            quotas: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Keeps `headers` for `provider`; answers without quota headers change nothing.
+    pub fn record(&self, provider: &str, headers: Option<QuotaHeaders>) {
+        let Some(headers) = headers else {
+            return;
+        };
+        if headers.remaining_requests == Some(0) || headers.retry_after.is_some() {
+            log::info!(
+                target: "rate_limit",
+                "{provider} quota exhausted: requests reset in {:?}, retry after {:?}",
+                headers.requests_reset,
+                headers.retry_after
+            );
+        }
+        self.quotas.lock().unwrap().insert(
+            provider.to_string(),
+            Quota {
+                headers,
+                received_at: Instant::now(),
+                updated_at: Utc::now(),
+            },
+        );
+    }
+
+    /// How long a request to `provider` should wait for its quota, and which
+    /// limit it waits for.
+    pub fn wait(&self, provider: &str, now: Instant) -> Option<(Duration, &'static str)> {
+        self.quotas.lock().unwrap().get(provider)?.wait(now)
+    }
+
+    pub fn status(&self) -> Vec<ProviderQuotaStatus> {
+        let now = Instant::now();
+        let millis = |wait: Option<Duration>| wait.map(|wait| wait.as_millis() as u64);
+        let mut status: Vec<ProviderQuotaStatus> = self
+            .quotas
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(provider, quota)| ProviderQuotaStatus {
+                provider: provider.clone(),
+                limit_requests: quota.headers.limit_requests,
+                remaining_requests: quota.headers.remaining_requests,
+                requests_reset_ms: millis(quota.remaining(quota.headers.requests_reset, now)),
+                limit_tokens: quota.headers.limit_tokens,
+                remaining_tokens: quota.headers.remaining_tokens,
+                tokens_reset_ms: millis(quota.remaining(quota.headers.tokens_reset, now)),
+                retry_after_ms: millis(quota.remaining(quota.headers.retry_after, now)),
+                wait_ms: millis(quota.wait(now).map(|(wait, _)| wait)).unwrap_or(0),
+                updated_at: quota.updated_at,
+            })
+            .collect();
+        status.sort_by(|a, b| a.provider.cmp(&b.provider));
+        status
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(headers: &[(&str, &'static str)]) -> Option<QuotaHeaders> {
+        let headers: HashMap<String, &'static str> = headers
+            .iter()
+            .map(|(name, value)| (name.to_string(), *value))
+            .collect();
+        QuotaHeaders::parse(|name| headers.get(name).copied())
+    }
+
+    #[test]
+    fn reads_openai_reset_strings() {
+        assert_eq!(parse_reset("6m0s"), Some(Duration::from_secs(360)));
+        assert_eq!(parse_reset("1.5s"), Some(Duration::from_millis(1500)));
+        assert_eq!(parse_reset("20ms"), Some(Duration::from_millis(20)));
+        assert_eq!(
+            parse_reset("1h2m3.5s"),
+            Some(Duration::from_millis(3_723_500))
+        );
+        assert_eq!(parse_reset("2"), Some(Duration::from_secs(2)));
+        assert_eq!(parse_reset(""), None);
+        assert_eq!(parse_reset("5 minutes"), None);
+        assert_eq!(parse_reset("-1"), None);
+    }
+
+    #[test]
+    fn reads_retry_after_as_seconds_or_a_date() {
+        let now = DateTime::parse_from_rfc2822("Wed, 21 Oct 2015 07:28:00 GMT")
+            .unwrap()
+            .with_timezone(&Utc);
+        assert_eq!(
+            parse_retry_after("120", now),
+            Some(Duration::from_secs(120))
+        );
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:28:30 GMT", now),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:27:00 GMT", now),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(parse_retry_after("soon", now), None);
+    }
+
+    #[test]
+    fn retry_after_ms_takes_precedence() {
+        let headers = parse(&[("retry-after-ms", "250"), ("retry-after", "30")]).unwrap();
+        assert_eq!(headers.retry_after, Some(Duration::from_millis(250)));
+        let headers = parse(&[("retry-after-ms", "soon"), ("retry-after", "30")]).unwrap();
+        assert_eq!(headers.retry_after, Some(Duration::from_secs(30)));
+    }
+
+    #[test]
+    fn reads_the_rate_limit_headers() {
+        let headers = parse(&[
+            ("x-ratelimit-limit-requests", "500"),
+            ("x-ratelimit-remaining-requests", "0"),
+            ("x-ratelimit-reset-requests", "1.5s"),
+            ("x-ratelimit-remaining-tokens", "9000"),
+        ])
+        .unwrap();
+        assert_eq!(headers.limit_requests, Some(500));
+        assert_eq!(headers.remaining_requests, Some(0));
+        assert_eq!(headers.requests_reset, Some(Duration::from_millis(1500)));
+        assert_eq!(headers.remaining_tokens, Some(9000));
+        assert_eq!(headers.tokens_reset, None);
+        assert_eq!(parse(&[("content-type", "application/json")]), None);
+    }
+
+    #[test]
+    fn huge_values_are_capped_instead_of_panicking() {
+        let headers = parse(&[
+            ("retry-after-ms", "1e300"),
+            ("x-ratelimit-reset-requests", "99999999999999999999h"),
+            ("x-ratelimit-reset-tokens", "1e400"),
+        ])
+        .unwrap();
+        assert_eq!(headers.retry_after, Some(MAX_QUOTA_WAIT));
+        assert_eq!(headers.requests_reset, Some(MAX_QUOTA_WAIT));
+        assert_eq!(headers.tokens_reset, Some(MAX_QUOTA_WAIT));
+
+        let headers = parse(&[("retry-after", "18446744073709551615")]).unwrap();
+        assert_eq!(headers.retry_after, Some(MAX_QUOTA_WAIT));
+        assert_eq!(
+            parse_retry_after("18446744073709551615", Utc::now()),
+            Some(Duration::from_secs(u64::MAX))
+        );
+        assert_eq!(parse_reset("1e300"), Some(Duration::MAX));
+    }
+
+    #[test]
+    fn waits_for_near_resets_only() {
+        let received_at = Instant::now();
+        let quota = |headers: QuotaHeaders| Quota {
+            headers,
+            received_at,
+            updated_at: Utc::now(),
+        };
+
+        let near = quota(QuotaHeaders {
+            remaining_requests: Some(0),
+            requests_reset: Some(Duration::from_secs(2)),
+            retry_after: Some(Duration::from_secs(1)),
+            ..QuotaHeaders::default()
+        });
+        assert_eq!(
+            near.wait(received_at),
+            Some((Duration::from_secs(2), "requests"))
+        );
+        assert_eq!(near.wait(received_at + Duration::from_secs(3)), None);
+
+        let far = quota(QuotaHeaders {
+            retry_after: Some(MAX_QUOTA_WAIT),
+            ..QuotaHeaders::default()
+        });
+        assert_eq!(far.wait(received_at), None);
+
+        let unbounded = quota(QuotaHeaders {
+            retry_after: Some(Duration::MAX),
+            ..QuotaHeaders::default()
+        });
+        assert_eq!(
+            unbounded.remaining(Some(Duration::MAX), received_at),
+            Some(Duration::MAX)
+        );
+        assert_eq!(unbounded.wait(received_at), None);
+    }
+}
@@ -0,0 +1,149 @@
+//! Client-side pacing for the remote providers (`provider_rate_limits`). A call
+//! over budget waits for its slot instead of going out and coming back as a 429,
+//! and transcription and chat draw from the same budget per provider. Calls also
+//! wait while the provider's own quota headers say the next one would be refused.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+use crate::events::{self, emit_event};
+use crate::provider_quota::ProviderQuotaState;
+use crate::types::AppConfig;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ThrottleNotice {
+    /// Usage provider key, e.g. `openai`.
+    pub provider: String,
+    /// `transcription` or `llm`.
+    pub kind: &'static str,
+    pub delay_ms: u64,
+    /// `budget` for the configured rpm, or the provider limit that ran out:
+    /// `retry-after`, `requests` or `tokens`.
+    pub reason: &'static str,
+}
+
+/// Next free request slot per provider.
+pub struct RateLimiter {
+    next_slot: Mutex<HashMap<String, Instant>>,
+}
+
+impl RateLimiter {
+    pub fn new() -> Self {
+        Self {
+            next_slot: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns once `provider` has budget and quota for one more request,
+    /// announcing the wait first when there is one. Providers without a limit or
+    /// quota headers pass straight through.
+    pub async fn acquire(
+        &self,
+        app: &AppHandle,
+        config: &AppConfig,
+        provider: &str,
+        kind: &'static str,
+    ) {
+        let now = Instant::now();
+        let budget = config
+            .provider_rate_limits
+            .rpm_for(provider)
+            .map(|rpm| (self.reserve(provider, rpm, now), "budget"));
+        let quota = app
+            .try_state::<Arc<ProviderQuotaState>>()
+            .and_then(|quota| quota.wait(provider, now));
+        let Some((delay, reason)) = budget
+            .into_iter()
+            .chain(quota)
+            .max_by_key(|(delay, _)| *delay)
+        else {
+            return;
+        };
+        if delay.is_zero() {
+            return;
+        }
+        log::info!(
+            target: "rate_limit",
+            "{provider} {kind} request waits {} ms ({reason})",
+            delay.as_millis()
+        );
+        let _ = emit_event(
+            app,
+            events::TRANSCRIPTION_THROTTLED,
+            ThrottleNotice {
+                provider: provider.to_string(),
+                kind,
+                delay_ms: delay.as_millis() as u64,
+                reason,
+            },
+        );
+        tokio::time::sleep(delay).await;
+    }
+
+    /// Books the first free slot at or after `now` and returns the wait until it.
+    /// Slots are `60 / rpm` seconds apart, i.e. a bucket that holds one token, so a
+    /// burst is spread out rather than allowed through and then refused.
+    fn reserve(&self, provider: &str, rpm: u32, now: Instant) -> Duration {
+        let interval = Duration::from_secs(60) / rpm.max(1);
+        let mut slots = self.next_slot.lock().unwrap();
+        let slot = slots
+            .get(provider)
+            .copied()
+            .filter(|slot| *slot > now)
+            .unwrap_or(now);
+        slots.insert(provider.to_string(), slot + interval);
+        slot - now
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECOND: Duration = Duration::from_secs(1);
+
+    #[test]
+    fn a_burst_is_spread_over_the_budget() {
+        let limiter = RateLimiter::new();
+        let start = Instant::now();
+        let waits: Vec<Duration> = (0..4)
+            .map(|_| limiter.reserve("openai", 3, start))
+            .collect();
+        assert_eq!(waits, [0, 20, 40, 60].map(|seconds| seconds * SECOND));
+    }
+
+    #[test]
+    fn calls_after_a_pause_wait_only_for_the_remaining_interval() {
+        let limiter = RateLimiter::new();
+        let start = Instant::now();
+        assert_eq!(limiter.reserve("openai", 3, start), Duration::ZERO);
+        assert_eq!(
+            limiter.reserve("openai", 3, start + 5 * SECOND),
+            15 * SECOND
+        );
+        // Its slot was at 20 s, so the next one is free from 40 s on.
+        assert_eq!(
+            limiter.reserve("openai", 3, start + 50 * SECOND),
+            Duration::ZERO
+        );
+        assert_eq!(
+            limiter.reserve("openai", 3, start + 55 * SECOND),
+            15 * SECOND
+        );
+    }
+
+    #[test]
+    fn providers_have_separate_budgets() {
+        let limiter = RateLimiter::new();
+        let start = Instant::now();
+        assert_eq!(limiter.reserve("openai", 60, start), Duration::ZERO);
+        assert_eq!(limiter.reserve("google", 60, start), Duration::ZERO);
+        assert_eq!(limiter.reserve("openai", 60, start), SECOND);
+        assert_eq!(limiter.reserve("google", 60, start), SECOND);
+    }
+}
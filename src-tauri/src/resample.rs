@@ -0,0 +1,393 @@
+//! Sample-rate conversion for WAV payloads sent to transcription providers.
+
+use std::f64::consts::PI;
+use std::fmt;
+use std::io::{self, Cursor, Read, Seek, SeekFrom};
+
+use anyhow::Result;
+
+const WAVE_FORMAT_PCM: u16 = 1;
+const WAVE_FORMAT_IEEE_FLOAT: u16 = 3;
+const WAVE_FORMAT_EXTENSIBLE: u16 = 0xFFFE;
+
+/// Zero crossings of the sinc kernel on each side, at the output rate.
+const ZERO_CROSSINGS: usize = 16;
+/// Pass band as a fraction of the output Nyquist; the rest is transition band.
+const CUTOFF_SCALE: f64 = 0.95;
+
+/// Layout of a RIFF/WAVE payload, from its `fmt ` chunk and the header of its
+/// `data` chunk.
+#[derive(Debug, Clone, Copy)]
+pub struct WavHeader {
+    /// Format tag, with `WAVE_FORMAT_EXTENSIBLE` resolved to its sub-format.
+    pub format: u16,
+    pub channels: usize,
+    pub sample_rate: u32,
+    pub byte_rate: u32,
+    pub bits: u16,
+    /// Offset of the first sample.
+    pub data_offset: u64,
+    /// Bytes of audio actually present, which may be less than the header says.
+    pub data_len: u64,
+}
+
+impl WavHeader {
+    pub fn duration_seconds(&self) -> Option<f64> {
+        (self.byte_rate > 0).then(|| self.data_len as f64 / self.byte_rate as f64)
+    }
+
+    pub fn bytes_per_sample(&self) -> usize {
+        self.bits as usize / 8
+    }
+
+    /// Decodes one sample of this encoding to `-1.0..=1.0`. Integer PCM of 8, 16,
+    /// 24 or 32 bits and 32-bit float are supported.
+    pub fn sample_decoder(&self) -> Result<fn(&[u8]) -> f32, WavError> {
+        let decode: fn(&[u8]) -> f32 = match (self.format, self.bits) {
+            (WAVE_FORMAT_PCM, 8) => |bytes| (bytes[0] as f32 - 128.0) / 128.0,
+            (WAVE_FORMAT_PCM, 16) => {
+                |bytes| i16::from_le_bytes([bytes[0], bytes[1]]) as f32 / 32768.0
+            }
+            (WAVE_FORMAT_PCM, 24) => |bytes| {
+                (i32::from_le_bytes([0, bytes[0], bytes[1], bytes[2]]) >> 8) as f32 / 8_388_608.0
+            },
+            (WAVE_FORMAT_PCM, 32) => {
+                |bytes| i32::from_le_bytes(bytes[..4].try_into().unwrap()) as f32 / 2_147_483_648.0
+            }
+            (WAVE_FORMAT_IEEE_FLOAT, 32) => {
+                |bytes| f32::from_le_bytes(bytes[..4].try_into().unwrap()).clamp(-1.0, 1.0)
+            }
+            (format, bits) => return Err(WavError::Unsupported { format, bits }),
+        };
+        Ok(decode)
+    }
+}
+
+#[derive(Debug)]
+pub enum WavError {
+    NotWav,
+    Malformed(&'static str),
+    Unsupported { format: u16, bits: u16 },
+    Io(io::Error),
+}
+
+impl fmt::Display for WavError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotWav => f.write_str("not a RIFF/WAVE file"),
+            Self::Malformed(reason) => write!(f, "malformed WAV file: {reason}"),
+            Self::Unsupported { format, bits } => {
+                write!(f, "unsupported WAV encoding (format {format}, {bits} bits)")
+            }
+            Self::Io(error) => write!(f, "WAV file could not be read: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for WavError {}
+
+impl From<io::Error> for WavError {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+pub struct WavPcm {
+    pub channels: usize,
+    pub sample_rate: u32,
+    /// Interleaved samples.
+    pub samples: Vec<i16>,
+}
+
+/// Re-encodes a WAV payload at `target_rate` as 16-bit PCM. Returns `None` when the
+/// payload is not a WAV we can read or is already at or below the target rate, so
+/// callers can send the original bytes unchanged.
+pub fn downsample_wav(bytes: &[u8], target_rate: u32) -> Result<Option<Vec<u8>>> {
+    if !is_wav(bytes) {
+        return Ok(None);
+    }
+    let wav = parse_wav(bytes)?;
+    if target_rate == 0 || wav.sample_rate <= target_rate {
+        return Ok(None);
+    }
+    let samples = resample(&wav.samples, wav.channels, wav.sample_rate, target_rate);
+    Ok(Some(encode_wav(&samples, wav.channels, target_rate)))
+}
+
+/// Polyphase windowed-sinc conversion of interleaved samples from `from_rate` to
+/// `to_rate`. The output holds `ceil(frames * to_rate / from_rate)` frames, so the
+/// duration is preserved to within one output sample.
+fn resample(samples: &[i16], channels: usize, from_rate: u32, to_rate: u32) -> Vec<i16> {
+    let channels = channels.max(1);
+    let frames = samples.len() / channels;
+    let divisor = gcd(from_rate, to_rate) as usize;
+    // Output frame n sits at input position n * step / phases.
+    let phases = to_rate as usize / divisor;
+    let step = from_rate as usize / divisor;
+    let cutoff = (to_rate as f64 / from_rate as f64).min(1.0) * CUTOFF_SCALE;
+    let half_width = (ZERO_CROSSINGS as f64 / cutoff).ceil() as isize;
+    let table = build_filter_table(phases, half_width, cutoff);
+
+    let out_frames = (frames * phases).div_ceil(step);
+    let mut out = Vec::with_capacity(out_frames * channels);
+    for n in 0..out_frames {
+        let position = n * step;
+        let center = (position / phases) as isize;
+        let taps = &table[position % phases];
+        for channel in 0..channels {
+            let mut acc = 0.0f64;
+            for (offset, coefficient) in (-half_width + 1..=half_width).zip(taps) {
+                let index = center + offset;
+                if index >= 0 && (index as usize) < frames {
+                    acc += samples[index as usize * channels + channel] as f64 * coefficient;
+                }
+            }
+            out.push(acc.round().clamp(i16::MIN as f64, i16::MAX as f64) as i16);
+        }
+    }
+    out
+}
+
+/// One Blackman-windowed sinc per phase, each normalized to unity DC gain.
+fn build_filter_table(phases: usize, half_width: isize, cutoff: f64) -> Vec<Vec<f64>> {
+    (0..phases)
+        .map(|phase| {
+            let fraction = phase as f64 / phases as f64;
+            let mut taps: Vec<f64> = (-half_width + 1..=half_width)
+                .map(|offset| {
+                    let x = offset as f64 - fraction;
+                    sinc(cutoff * x) * blackman(x / half_width as f64)
+                })
+                .collect();
+            let sum: f64 = taps.iter().sum();
+            if sum.abs() > f64::EPSILON {
+                taps.iter_mut().for_each(|tap| *tap /= sum);
+            }
+            taps
+        })
+        .collect()
+}
+
+fn sinc(x: f64) -> f64 {
+    if x.abs() < 1e-12 {
+        1.0
+    } else {
+        (PI * x).sin() / (PI * x)
+    }
+}
+
+/// Blackman window over `-1.0..=1.0`.
+fn blackman(t: f64) -> f64 {
+    if t.abs() >= 1.0 {
+        return 0.0;
+    }
+    let phase = PI * (t + 1.0);
+    0.42 - 0.5 * phase.cos() + 0.08 * (2.0 * phase).cos()
+}
+
+fn gcd(mut a: u32, mut b: u32) -> u32 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a.max(1)
+}
+
+/// Whether `bytes` start with a RIFF/WAVE header.
+pub fn is_wav(bytes: &[u8]) -> bool {
+    bytes.len() >= 12 && &bytes[0..4] == b"RIFF" && &bytes[8..12] == b"WAVE"
+}
+
+pub fn wav_header(bytes: &[u8]) -> Result<WavHeader, WavError> {
+    read_wav_header(&mut Cursor::new(bytes))
+}
+
+/// Walks the RIFF chunks up to `data` and leaves `reader` at its first sample.
+pub fn read_wav_header<R: Read + Seek>(reader: &mut R) -> Result<WavHeader, WavError> {
+    let file_len = reader.seek(SeekFrom::End(0))?;
+    reader.seek(SeekFrom::Start(0))?;
+    let mut riff = [0u8; 12];
+    if reader.read_exact(&mut riff).is_err() || !is_wav(&riff) {
+        return Err(WavError::NotWav);
+    }
+    let mut format = None;
+    loop {
+        let mut header = [0u8; 8];
+        if reader.read_exact(&mut header).is_err() {
+            return Err(WavError::Malformed("no data chunk"));
+        }
+        let size = u32::from_le_bytes(header[4..8].try_into().unwrap()) as u64;
+        let body = reader.stream_position()?;
+        match &header[0..4] {
+            b"fmt " => {
+                if size < 16 {
+                    return Err(WavError::Malformed("format chunk is truncated"));
+                }
+                let mut chunk = vec![0u8; size.min(64) as usize];
+                reader
+                    .read_exact(&mut chunk)
+                    .map_err(|_| WavError::Malformed("format chunk is truncated"))?;
+                let mut tag = u16::from_le_bytes([chunk[0], chunk[1]]);
+                if tag == WAVE_FORMAT_EXTENSIBLE && chunk.len() >= 26 {
+                    tag = u16::from_le_bytes([chunk[24], chunk[25]]);
+                }
+                format = Some((
+                    tag,
+                    u16::from_le_bytes([chunk[2], chunk[3]]) as usize,
+                    u32::from_le_bytes(chunk[4..8].try_into().unwrap()),
+                    u32::from_le_bytes(chunk[8..12].try_into().unwrap()),
+                    u16::from_le_bytes([chunk[14], chunk[15]]),
+                ));
+            }
+            b"data" => {
+                let (format, channels, sample_rate, byte_rate, bits) =
+                    format.ok_or(WavError::Malformed("data chunk before its format"))?;
+                if channels == 0 || sample_rate == 0 {
+                    return Err(WavError::Malformed("no channels or a zero sample rate"));
+                }
+                reader.seek(SeekFrom::Start(body))?;
+                return Ok(WavHeader {
+                    format,
+                    channels,
+                    sample_rate,
+                    byte_rate,
+                    bits,
+                    data_offset: body,
+                    data_len: size.min(file_len.saturating_sub(body)),
+                });
+            }
+            _ => {}
+        }
+        reader.seek(SeekFrom::Start(body + size + size % 2))?;
+    }
+}
+
+pub fn parse_wav(bytes: &[u8]) -> Result<WavPcm> {
+    let header = wav_header(bytes)?;
+    let start = header.data_offset as usize;
+    let data = &bytes[start..start + header.data_len as usize];
+    let decode = header.sample_decoder()?;
+    let samples = data
+        .chunks_exact(header.bytes_per_sample())
+        .map(|sample| (decode(sample) * 32768.0).round().clamp(-32768.0, 32767.0) as i16)
+        .collect();
+    Ok(WavPcm {
+        channels: header.channels,
+        sample_rate: header.sample_rate,
+        samples,
+    })
+}
+
+pub fn encode_wav(samples: &[i16], channels: usize, sample_rate: u32) -> Vec<u8> {
+    let channels = channels as u16;
+    let block_align = channels * 2;
+    let data_len = (samples.len() * 2) as u32;
+    let mut out = Vec::with_capacity(44 + samples.len() * 2);
+    out.extend_from_slice(b"RIFF");
+    out.extend_from_slice(&(36 + data_len).to_le_bytes());
+    out.extend_from_slice(b"WAVE");
+    out.extend_from_slice(b"fmt ");
+    out.extend_from_slice(&16u32.to_le_bytes());
+    out.extend_from_slice(&WAVE_FORMAT_PCM.to_le_bytes());
+    out.extend_from_slice(&channels.to_le_bytes());
+    out.extend_from_slice(&sample_rate.to_le_bytes());
+    out.extend_from_slice(&(sample_rate * block_align as u32).to_le_bytes());
+    out.extend_from_slice(&block_align.to_le_bytes());
+    out.extend_from_slice(&16u16.to_le_bytes());
+    out.extend_from_slice(b"data");
+    out.extend_from_slice(&data_len.to_le_bytes());
+    for sample in samples {
+        out.extend_from_slice(&sample.to_le_bytes());
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tone(frames: usize, channels: usize, sample_rate: u32) -> Vec<i16> {
+        (0..frames)
+            .flat_map(|frame| {
+                let t = frame as f64 / sample_rate as f64;
+                let value = ((2.0 * PI * 440.0 * t).sin() * 10_000.0) as i16;
+                std::iter::repeat_n(value, channels)
+            })
+            .collect()
+    }
+
+    fn with_format(wav: &mut [u8], format: u16, bits: u16) {
+        wav[20..22].copy_from_slice(&format.to_le_bytes());
+        wav[34..36].copy_from_slice(&bits.to_le_bytes());
+    }
+
+    #[test]
+    fn downsampling_preserves_duration_within_a_sample() {
+        for (from_rate, to_rate, frames) in [
+            (44_100, 16_000, 44_100),
+            (44_100, 16_000, 12_345),
+            (48_000, 16_000, 48_001),
+            (48_000, 24_000, 7),
+        ] {
+            let input = encode_wav(&tone(frames, 2, from_rate), 2, from_rate);
+            let output = downsample_wav(&input, to_rate).unwrap().unwrap();
+            let wav = parse_wav(&output).unwrap();
+            assert_eq!(wav.sample_rate, to_rate);
+            assert_eq!(wav.channels, 2);
+            let out_frames = wav.samples.len() / 2;
+            assert_eq!(
+                out_frames,
+                (frames as u64 * to_rate as u64).div_ceil(from_rate as u64) as usize
+            );
+            let input_seconds = frames as f64 / from_rate as f64;
+            let output_seconds = out_frames as f64 / to_rate as f64;
+            assert!((input_seconds - output_seconds).abs() <= 1.0 / to_rate as f64);
+            assert_eq!(
+                wav_header(&output).unwrap().duration_seconds(),
+                Some(output_seconds)
+            );
+        }
+    }
+
+    #[test]
+    fn payloads_at_or_below_the_target_rate_are_left_alone() {
+        let wav = encode_wav(&tone(1600, 1, 16_000), 1, 16_000);
+        assert!(downsample_wav(&wav, 16_000).unwrap().is_none());
+        assert!(downsample_wav(&wav, 24_000).unwrap().is_none());
+        assert!(downsample_wav(b"OggS not a wav", 16_000).unwrap().is_none());
+    }
+
+    #[test]
+    fn unsupported_bit_depths_are_rejected() {
+        let mut wav = encode_wav(&tone(441, 1, 44_100), 1, 44_100);
+        with_format(&mut wav, WAVE_FORMAT_PCM, 12);
+        let error = downsample_wav(&wav, 16_000).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "unsupported WAV encoding (format 1, 12 bits)"
+        );
+        with_format(&mut wav, WAVE_FORMAT_IEEE_FLOAT, 64);
+        assert!(parse_wav(&wav).is_err());
+    }
+
+    #[test]
+    fn every_supported_depth_decodes_to_the_same_pcm() {
+        let cases: [(u16, u16, &[u8]); 5] = [
+            (WAVE_FORMAT_PCM, 8, &[192]),
+            (WAVE_FORMAT_PCM, 16, &0x4000i16.to_le_bytes()),
+            (WAVE_FORMAT_PCM, 24, &[0, 0, 0x40]),
+            (WAVE_FORMAT_PCM, 32, &0x4000_0000i32.to_le_bytes()),
+            (WAVE_FORMAT_IEEE_FLOAT, 32, &0.5f32.to_le_bytes()),
+        ];
+        for (format, bits, sample) in cases {
+            let mut wav = encode_wav(&[], 1, 16_000);
+            with_format(&mut wav, format, bits);
+            wav[40..44].copy_from_slice(&(sample.len() as u32).to_le_bytes());
+            wav.extend_from_slice(sample);
+            assert_eq!(
+                parse_wav(&wav).unwrap().samples,
+                [16_384],
+                "{format}/{bits}"
+            );
+        }
+    }
+}
@@ -1,8 +1,54 @@
+use std::path::PathBuf;
+
 use tauri::{path::BaseDirectory, AppHandle, Manager};
 
-pub fn resolve_sound_path(app: &AppHandle, sound_name: &str) -> Option<String> {
+pub const SOUNDS_DIR_NAME: &str = "sounds";
+
+/// Root of user sound themes: `app_local_data_dir()/sounds/<theme>/<cue file>`.
+pub fn user_sounds_dir(app: &AppHandle) -> Option<PathBuf> {
+    app.path()
+        .app_local_data_dir()
+        .ok()
+        .map(|dir| dir.join(SOUNDS_DIR_NAME))
+}
+
+/// Directory holding the sounds shipped with the app, if it can be found.
+pub fn bundled_sounds_dir(app: &AppHandle) -> Option<PathBuf> {
+    let mut candidates = Vec::new();
+    if let Ok(current_dir) = std::env::current_dir() {
+        candidates.push(current_dir.join("resources").join(SOUNDS_DIR_NAME));
+    }
+    if let Ok(resource_path) = app.path().resolve(SOUNDS_DIR_NAME, BaseDirectory::Resource) {
+        candidates.push(resource_path);
+    }
+    candidates.into_iter().find(|dir| dir.is_dir())
+}
+
+/// Theme and cue names are single path components; anything else is ignored.
+pub fn is_plain_name(name: &str) -> bool {
+    !name.is_empty() && name != "." && name != ".." && !name.contains(['/', '\\'])
+}
+
+pub fn resolve_sound_path(
+    app: &AppHandle,
+    sound_name: &str,
+    theme: Option<&str>,
+) -> Option<String> {
+    if !is_plain_name(sound_name) {
+        return None;
+    }
     let relative = format!("sounds/{}", sound_name);
-    
+
+    // Пользовательская тема имеет приоритет над встроенными звуками
+    if let (Some(theme), Some(user_dir)) =
+        (theme.filter(|t| is_plain_name(t)), user_sounds_dir(app))
+    {
+        let themed_path = user_dir.join(theme).join(sound_name);
+        if themed_path.is_file() {
+            return Some(themed_path.to_string_lossy().to_string());
+        }
+    }
+
     // В dev режиме пробуем через current_dir
     if let Ok(current_dir) = std::env::current_dir() {
         let alt_path = current_dir.join("resources").join("sounds").join(sound_name);
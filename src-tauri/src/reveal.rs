@@ -0,0 +1,103 @@
+//! Showing our own files in the OS file manager. The webview names the file, so
+//! only paths under the app's directories are accepted.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use serde_json::json;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_opener::OpenerExt;
+
+use crate::app_log;
+use crate::config::ConfigState;
+use crate::errors::{CommandError, CommandResult, ErrorCode};
+use crate::transcript_log;
+
+/// Selects `path` in Explorer, Finder or the file manager behind the
+/// FileManager1 D-Bus interface, falling back to opening its folder.
+pub async fn reveal(app: &AppHandle, path: &str) -> CommandResult<()> {
+    let path = path.trim();
+    if path.is_empty() {
+        return Err(CommandError::bare(
+            ErrorCode::InvalidArgument,
+            &[("name", "path")],
+        ));
+    }
+    let Ok(resolved) = std::fs::canonicalize(path) else {
+        return Err(
+            CommandError::bare(ErrorCode::PathNotFound, &[("path", path)])
+                .with_data(json!({ "path": path })),
+        );
+    };
+    let roots = allowed_roots(app).await;
+    if !is_within(&resolved, &roots) {
+        log::warn!(target: "reveal", "Refused to reveal {}", resolved.display());
+        return Err(
+            CommandError::bare(ErrorCode::PathNotAllowed, &[("path", path)])
+                .with_data(json!({ "path": path })),
+        );
+    }
+    app.opener().reveal_item_in_dir(&resolved).map_err(|error| {
+        CommandError::with_params(ErrorCode::OpenFailed, &[("target", path)], error)
+    })
+}
+
+/// Folders whose files may be revealed: config, app data, logs and crash
+/// reports, and the configured transcript folder. Missing ones are skipped.
+async fn allowed_roots(app: &AppHandle) -> Vec<PathBuf> {
+    let mut roots = Vec::new();
+    let config = app.state::<Arc<ConfigState>>();
+    roots.push(config.directory().await);
+    roots.extend(app.path().app_config_dir().ok());
+    roots.extend(app.path().app_data_dir().ok());
+    roots.extend(app.path().app_local_data_dir().ok());
+    // Crash reports sit next to the log folder.
+    if let Ok(log_dir) = app_log::current_log_dir() {
+        roots.extend(log_dir.parent().map(Path::to_path_buf));
+        roots.push(log_dir);
+    }
+    roots.extend(transcript_log::directory(app, &config.get().await).ok());
+    roots
+        .iter()
+        .filter_map(|root| std::fs::canonicalize(root).ok())
+        .collect()
+}
+
+/// Whether `path` is inside one of `roots`. Both sides must already be
+/// canonical, so `..` and symlinks cannot step out; the comparison is by
+/// component, so `/data/app-evil` is not inside `/data/app`.
+fn is_within(path: &Path, roots: &[PathBuf]) -> bool {
+    roots.iter().any(|root| path.starts_with(root))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn only_paths_under_a_root_are_within() {
+        let base = std::env::temp_dir().join(format!("xexamai-reveal-{}", std::process::id()));
+        for dir in ["app/recordings", "app-evil", "other"] {
+            fs::create_dir_all(base.join(dir)).unwrap();
+        }
+        fs::write(base.join("app/recordings/clip.wav"), b"").unwrap();
+        fs::write(base.join("app-evil/clip.wav"), b"").unwrap();
+        let canonical = |relative: &str| fs::canonicalize(base.join(relative)).unwrap();
+        let roots = [canonical("app")];
+
+        assert!(is_within(&canonical("app/recordings/clip.wav"), &roots));
+        assert!(is_within(&canonical("app"), &roots));
+        // Shares the root's name as a string prefix, but is a sibling.
+        assert!(!is_within(&canonical("app-evil/clip.wav"), &roots));
+        assert!(!is_within(&canonical("app-evil"), &roots));
+        // `..` is resolved before the check, so it cannot climb out of the root.
+        assert!(!is_within(&canonical("app/../app-evil/clip.wav"), &roots));
+        assert!(!is_within(&canonical("app/recordings/../../other"), &roots));
+        assert!(!is_within(&canonical("app/.."), &roots));
+        assert!(!is_within(&canonical("app/recordings/clip.wav"), &[]));
+
+        let _ = fs::remove_dir_all(&base);
+    }
+}
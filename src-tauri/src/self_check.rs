@@ -0,0 +1,478 @@
+//! One-shot health report for support: config, API key, audio, the local servers,
+//! the deep-link scheme and disk space, checked concurrently. Reports are cached
+//! briefly so a settings page that polls does not hit the providers every time.
+
+use std::future::Future;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_deep_link::DeepLinkExt;
+use tokio::sync::Mutex;
+
+use crate::audio::AudioManager;
+use crate::auth_store::AuthStore;
+use crate::config::ConfigState;
+use crate::constants::OAUTH_SCHEME;
+use crate::events::{self, emit_event};
+use crate::http::HttpClients;
+use crate::local_speech::FastWhisperManager;
+use crate::ollama;
+use crate::types::{AppConfig, LlmHost, TranscriptionMode};
+
+const CACHE_TTL: Duration = Duration::from_secs(60);
+const OPENAI_MODELS_URL: &str = "https://api.openai.com/v1/models";
+const GOOGLE_MODELS_URL: &str = "https://generativelanguage.googleapis.com/v1beta/models";
+const PROBE_FILE: &str = ".self-check";
+const LOW_DISK_BYTES: u64 = 1024 * 1024 * 1024;
+const CRITICAL_DISK_BYTES: u64 = 100 * 1024 * 1024;
+const CHECK_COUNT: usize = 7;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CheckStatus {
+    Ok,
+    Warning,
+    Failed,
+    /// Not applicable to the current settings or platform.
+    Skipped,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SelfCheckItem {
+    pub id: &'static str,
+    pub label: &'static str,
+    pub status: CheckStatus,
+    pub detail: String,
+    pub hint: Option<String>,
+    pub duration_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SelfCheckReport {
+    pub checks: Vec<SelfCheckItem>,
+    /// False when any check failed; warnings and skips do not count.
+    pub healthy: bool,
+    pub checked_at: i64,
+    /// True when this report was served from the cache.
+    pub cached: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SelfCheckProgress {
+    pub check: SelfCheckItem,
+    pub completed: usize,
+    pub total: usize,
+}
+
+struct Outcome {
+    status: CheckStatus,
+    detail: String,
+    hint: Option<String>,
+}
+
+impl Outcome {
+    fn ok(detail: impl Into<String>) -> Self {
+        Self {
+            status: CheckStatus::Ok,
+            detail: detail.into(),
+            hint: None,
+        }
+    }
+
+    fn skipped(detail: impl Into<String>) -> Self {
+        Self {
+            status: CheckStatus::Skipped,
+            detail: detail.into(),
+            hint: None,
+        }
+    }
+
+    fn warning(detail: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self {
+            status: CheckStatus::Warning,
+            detail: detail.into(),
+            hint: Some(hint.into()),
+        }
+    }
+
+    fn failed(detail: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self {
+            status: CheckStatus::Failed,
+            detail: detail.into(),
+            hint: Some(hint.into()),
+        }
+    }
+}
+
+/// Last report, held behind an async lock so concurrent callers share one run.
+pub struct SelfCheck {
+    last: Mutex<Option<(Instant, SelfCheckReport)>>,
+}
+
+impl SelfCheck {
+    pub fn new() -> Self {
+        Self {
+            last: Mutex::new(None),
+        }
+    }
+
+    /// The cached report when it is younger than `CACHE_TTL` and `force` is off,
+    /// otherwise a fresh run.
+    pub async fn run(&self, app: &AppHandle, force: bool) -> SelfCheckReport {
+        let mut last = self.last.lock().await;
+        if !force {
+            if let Some((at, report)) = last.as_ref() {
+                if at.elapsed() < CACHE_TTL {
+                    return SelfCheckReport {
+                        cached: true,
+                        ..report.clone()
+                    };
+                }
+            }
+        }
+        let report = run_checks(app).await;
+        let failed = report
+            .checks
+            .iter()
+            .filter(|check| check.status == CheckStatus::Failed)
+            .count();
+        log::info!(
+            target: "self-check",
+            "Self-check finished: checks={} failed={failed}",
+            report.checks.len()
+        );
+        *last = Some((Instant::now(), report.clone()));
+        report
+    }
+}
+
+async fn run_checks(app: &AppHandle) -> SelfCheckReport {
+    let config = app.state::<Arc<ConfigState>>().get().await;
+    let completed = AtomicUsize::new(0);
+    let progress = |item: &SelfCheckItem| {
+        let done = completed.fetch_add(1, Ordering::Relaxed) + 1;
+        let _ = emit_event(
+            app,
+            events::SELF_CHECK_PROGRESS,
+            SelfCheckProgress {
+                check: item.clone(),
+                completed: done,
+                total: CHECK_COUNT,
+            },
+        );
+    };
+
+    let (config_item, api_key, audio, local_speech, ollama_item, deep_link, disk) = tokio::join!(
+        timed("config", "Config file", &progress, config_check(app)),
+        timed("apiKey", "API key", &progress, api_key_check(app, &config)),
+        timed("audio", "Audio devices", &progress, audio_check(app)),
+        timed(
+            "localSpeech",
+            "Local speech server",
+            &progress,
+            local_speech_check(app, &config)
+        ),
+        timed("ollama", "Ollama", &progress, ollama_check(app, &config)),
+        timed("deepLink", "Sign-in links", &progress, deep_link_check(app)),
+        timed("disk", "Disk space", &progress, disk_check(app)),
+    );
+    let checks = vec![
+        config_item,
+        api_key,
+        audio,
+        local_speech,
+        ollama_item,
+        deep_link,
+        disk,
+    ];
+    SelfCheckReport {
+        healthy: checks
+            .iter()
+            .all(|check| check.status != CheckStatus::Failed),
+        checks,
+        checked_at: Utc::now().timestamp_millis(),
+        cached: false,
+    }
+}
+
+async fn timed(
+    id: &'static str,
+    label: &'static str,
+    progress: &impl Fn(&SelfCheckItem),
+    check: impl Future<Output = Outcome>,
+) -> SelfCheckItem {
+    let started = Instant::now();
+    let outcome = check.await;
+    let item = SelfCheckItem {
+        id,
+        label,
+        status: outcome.status,
+        detail: outcome.detail,
+        hint: outcome.hint,
+        duration_ms: started.elapsed().as_millis() as u64,
+    };
+    progress(&item);
+    item
+}
+
+async fn config_check(app: &AppHandle) -> Outcome {
+    let state = app.state::<Arc<ConfigState>>();
+    let status = state.persistence_status();
+    if let Some(error) = status.error {
+        return Outcome::failed(
+            format!("Settings are not being saved: {error}"),
+            format!(
+                "Make {} writable, then use Retry in the settings banner.",
+                status.path
+            ),
+        );
+    }
+    let path = state.path().await;
+    match tokio::fs::read_to_string(&path).await {
+        Ok(_) => {}
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => {}
+        Err(error) => {
+            return Outcome::failed(
+                format!("{} could not be read: {error}", path.display()),
+                "Check the file permissions of the config file.",
+            )
+        }
+    }
+    let probe = state.directory().await.join(PROBE_FILE);
+    let written = tokio::fs::write(&probe, b"ok").await;
+    let _ = tokio::fs::remove_file(&probe).await;
+    match written {
+        Ok(()) => Outcome::ok(format!("{} is readable and writable", path.display())),
+        Err(error) => Outcome::failed(
+            format!("The config directory is not writable: {error}"),
+            "Check the permissions of the config directory or free up space.",
+        ),
+    }
+}
+
+async fn api_key_check(app: &AppHandle, config: &AppConfig) -> Outcome {
+    let needed =
+        config.transcription_mode == TranscriptionMode::Api || config.llm_host == LlmHost::Api;
+    let key = |key: &Option<String>| {
+        key.as_deref()
+            .map(str::trim)
+            .filter(|key| !key.is_empty())
+            .map(str::to_string)
+    };
+    let (provider, result) = match (key(&config.openai_api_key), key(&config.google_api_key)) {
+        (Some(openai), _) => ("OpenAI", openai_models(app, config, &openai).await),
+        (None, Some(google)) => ("Google", google_models(app, config, &google).await),
+        (None, None) => {
+            let signed_in = match app.try_state::<Arc<AuthStore>>() {
+                Some(store) => !store.list(None).await.is_empty(),
+                None => false,
+            };
+            return if signed_in {
+                Outcome::ok("No API key; signed in to an account")
+            } else if needed {
+                Outcome::failed(
+                    "No API key and no signed-in account",
+                    "Add an OpenAI or Google API key in Settings, or sign in.",
+                )
+            } else {
+                Outcome::skipped("Not needed in local mode")
+            };
+        }
+    };
+    match result {
+        Ok(()) => Outcome::ok(format!("{provider} key accepted")),
+        Err(error) => Outcome::failed(
+            format!("{provider} rejected the request: {error:#}"),
+            format!("Check the {provider} API key, its billing status and the proxy setting."),
+        ),
+    }
+}
+
+async fn openai_models(app: &AppHandle, config: &AppConfig, key: &str) -> Result<()> {
+    app.state::<Arc<HttpClients>>()
+        .api(config)?
+        .get(OPENAI_MODELS_URL)
+        .bearer_auth(key)
+        .send()
+        .await?
+        .error_for_status()?;
+    Ok(())
+}
+
+async fn google_models(app: &AppHandle, config: &AppConfig, key: &str) -> Result<()> {
+    app.state::<Arc<HttpClients>>()
+        .api(config)?
+        .get(GOOGLE_MODELS_URL)
+        .query(&[("key", key)])
+        .send()
+        .await?
+        .error_for_status()?;
+    Ok(())
+}
+
+async fn audio_check(app: &AppHandle) -> Outcome {
+    let manager = app.state::<Arc<AudioManager>>().inner().clone();
+    let devices = tokio::task::spawn_blocking(move || manager.list_devices())
+        .await
+        .map_err(|error| anyhow!(error))
+        .and_then(|result| result);
+    match devices {
+        Ok(devices) => {
+            let mics = devices.iter().filter(|device| device.kind == "mic").count();
+            let system = devices.len() - mics;
+            if mics == 0 {
+                Outcome::failed(
+                    format!("No microphone found ({system} system devices)"),
+                    "Connect a microphone and allow access in the OS privacy settings.",
+                )
+            } else {
+                Outcome::ok(format!("{mics} microphones, {system} system devices"))
+            }
+        }
+        Err(error) => Outcome::failed(
+            format!("Audio devices could not be listed: {error:#}"),
+            "Check that an audio service is running and the app has microphone access.",
+        ),
+    }
+}
+
+async fn local_speech_check(app: &AppHandle, config: &AppConfig) -> Outcome {
+    let manager = app.state::<Arc<FastWhisperManager>>().inner().clone();
+    let status = manager.check_health(app).await;
+    let required = config.transcription_mode == TranscriptionMode::Local;
+    match (status.installed, status.running) {
+        (_, true) => Outcome::ok("Server is running"),
+        (false, false) if !required => {
+            Outcome::skipped("Not installed; not used by the current mode")
+        }
+        (true, false) if !required => {
+            Outcome::skipped("Installed but stopped; not used by the current mode")
+        }
+        (false, false) => Outcome::failed(
+            "Server is not installed",
+            "Install the local speech server from Settings → Local speech.",
+        ),
+        (true, false) => Outcome::failed(
+            status.error.unwrap_or(status.message),
+            "Start the local speech server from Settings → Local speech.",
+        ),
+    }
+}
+
+async fn ollama_check(app: &AppHandle, config: &AppConfig) -> Outcome {
+    let required = config.llm_host == LlmHost::Local;
+    let client = app.state::<Arc<HttpClients>>().health();
+    match ollama::installed_models(&client).await {
+        Ok(0) => Outcome::warning(
+            "Ollama is running but has no models",
+            "Pull a model from Settings → AI.",
+        ),
+        Ok(count) => Outcome::ok(format!("Ollama is running with {count} models")),
+        Err(_) if !required => Outcome::skipped("Not reachable; not used by the current mode"),
+        Err(error) => Outcome::failed(
+            format!("Ollama is not reachable: {error:#}"),
+            "Install Ollama from https://ollama.com and make sure it is running.",
+        ),
+    }
+}
+
+async fn deep_link_check(app: &AppHandle) -> Outcome {
+    match app.deep_link().is_registered(OAUTH_SCHEME) {
+        Ok(true) => Outcome::ok(format!("{OAUTH_SCHEME}:// opens this app")),
+        Ok(false) => Outcome::warning(
+            format!("{OAUTH_SCHEME}:// is not registered to this app"),
+            "Restart the app so it can register the scheme; browser sign-in needs it.",
+        ),
+        Err(tauri_plugin_deep_link::Error::UnsupportedPlatform) => {
+            Outcome::skipped("Cannot be checked on this platform")
+        }
+        Err(error) => Outcome::warning(
+            format!("Registration could not be read: {error}"),
+            "Restart the app so it can register the scheme; browser sign-in needs it.",
+        ),
+    }
+}
+
+async fn disk_check(app: &AppHandle) -> Outcome {
+    let dir = match app.path().app_local_data_dir() {
+        Ok(dir) => dir,
+        Err(error) => {
+            return Outcome::failed(
+                format!("Data directory could not be resolved: {error}"),
+                "Check that the user profile directory exists.",
+            )
+        }
+    };
+    // The directory may not exist before the first download.
+    let existing = dir
+        .ancestors()
+        .find(|path| path.exists())
+        .unwrap_or(&dir)
+        .to_path_buf();
+    match available_bytes(&existing).await {
+        Ok(free) => {
+            let detail = format!("{:.1} GB free at {}", free as f64 / 1e9, dir.display());
+            if free < CRITICAL_DISK_BYTES {
+                Outcome::failed(
+                    detail,
+                    "Free up disk space; recordings and models cannot be saved.",
+                )
+            } else if free < LOW_DISK_BYTES {
+                Outcome::warning(
+                    detail,
+                    "Free up disk space before downloading local models.",
+                )
+            } else {
+                Outcome::ok(detail)
+            }
+        }
+        Err(error) => Outcome::skipped(format!("Free space unknown: {error:#}")),
+    }
+}
+
+#[cfg(windows)]
+async fn available_bytes(path: &Path) -> Result<u64> {
+    use windows::core::HSTRING;
+    use windows::Win32::Storage::FileSystem::GetDiskFreeSpaceExW;
+
+    let mut free = 0u64;
+    unsafe {
+        GetDiskFreeSpaceExW(
+            &HSTRING::from(path.as_os_str()),
+            Some(&mut free),
+            None,
+            None,
+        )?;
+    }
+    Ok(free)
+}
+
+#[cfg(not(windows))]
+async fn available_bytes(path: &Path) -> Result<u64> {
+    let output = tokio::process::Command::new("df")
+        .arg("-Pk")
+        .arg(path)
+        .output()
+        .await?;
+    if !output.status.success() {
+        return Err(anyhow!("df exited with {}", output.status));
+    }
+    // POSIX format: "Filesystem 1024-blocks Used Available Capacity Mounted on".
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let kib: u64 = stdout
+        .lines()
+        .nth(1)
+        .and_then(|line| line.split_whitespace().nth(3))
+        .and_then(|value| value.parse().ok())
+        .ok_or_else(|| anyhow!("unexpected df output"))?;
+    Ok(kib * 1024)
+}
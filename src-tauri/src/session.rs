@@ -0,0 +1,450 @@
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use tauri::{AppHandle, Manager, Runtime};
+use tokio::fs;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+use crate::events::{self, emit_event};
+use crate::foreground;
+use crate::private_session;
+use crate::types::AudioInputType;
+
+const SESSIONS_DIR_NAME: &str = "sessions";
+// Cleared sessions kept so their timeline can still be opened after a reload.
+const MAX_ARCHIVED_SESSIONS: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SessionEntryKind {
+    Transcript,
+    Answer,
+    Summary,
+    /// The transcript before it, in `translateTo`.
+    Translation,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionEntry {
+    pub timestamp: DateTime<Local>,
+    pub kind: SessionEntryKind,
+    pub speaker: Option<String>,
+    pub text: String,
+    pub interaction_id: Option<String>,
+}
+
+/// What started an interaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum InteractionTrigger {
+    DurationHotkey,
+    ToggleInput,
+    /// A configured wake word was heard in the captured audio.
+    WakeWord,
+    /// The periodic summary of the transcripts since the previous one.
+    AutoSummary,
+    /// Started from the UI, or by a transcript/answer that arrived without an id.
+    Manual,
+}
+
+/// One hotkey press (or UI action) and everything it produced, in order.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Interaction {
+    pub id: String,
+    pub trigger: InteractionTrigger,
+    pub started_at: DateTime<Local>,
+    /// Requested window for duration hotkeys, replaced by the transcribed clip length.
+    pub clip_seconds: Option<f64>,
+    pub entries: Vec<SessionEntry>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionTimeline {
+    pub session_id: String,
+    pub started_at: DateTime<Local>,
+    pub interactions: Vec<Interaction>,
+}
+
+/// Ids carried by hotkey events, transcription and answers to tie them together.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InteractionIds {
+    pub session_id: String,
+    pub interaction_id: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Markdown,
+    Json,
+}
+
+impl ExportFormat {
+    pub fn parse(value: &str) -> Result<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "md" | "markdown" => Ok(Self::Markdown),
+            "json" => Ok(Self::Json),
+            other => Err(anyhow!("Unsupported export format: {other}")),
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            Self::Markdown => "md",
+            Self::Json => "json",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SessionExportedPayload {
+    path: String,
+    format: &'static str,
+    entries: usize,
+}
+
+struct SessionState {
+    id: String,
+    started_at: DateTime<Local>,
+    entries: Vec<SessionEntry>,
+    // Entries are kept once, in `entries`; these carry only the metadata.
+    interactions: Vec<Interaction>,
+}
+
+impl SessionState {
+    fn new() -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            started_at: Local::now(),
+            entries: Vec::new(),
+            interactions: Vec::new(),
+        }
+    }
+
+    fn begin(
+        &mut self,
+        id: String,
+        trigger: InteractionTrigger,
+        clip_seconds: Option<f64>,
+    ) -> &mut Interaction {
+        self.interactions.push(Interaction {
+            id,
+            trigger,
+            started_at: Local::now(),
+            clip_seconds,
+            entries: Vec::new(),
+        });
+        self.interactions.last_mut().unwrap()
+    }
+
+    /// The interaction for `id`; unknown or missing ids start a manual one so every
+    /// entry lands on the timeline.
+    fn interaction(&mut self, id: Option<&str>) -> &mut Interaction {
+        let id = id.map(str::trim).filter(|id| !id.is_empty());
+        match id.and_then(|id| self.interactions.iter().position(|i| i.id == id)) {
+            Some(index) => &mut self.interactions[index],
+            None => {
+                let id = id.map_or_else(|| Uuid::new_v4().to_string(), str::to_string);
+                self.begin(id, InteractionTrigger::Manual, None)
+            }
+        }
+    }
+
+    fn timeline(&self) -> SessionTimeline {
+        let interactions = self
+            .interactions
+            .iter()
+            .map(|interaction| Interaction {
+                entries: self
+                    .entries
+                    .iter()
+                    .filter(|entry| entry.interaction_id.as_deref() == Some(&interaction.id))
+                    .cloned()
+                    .collect(),
+                ..interaction.clone()
+            })
+            .collect();
+        SessionTimeline {
+            session_id: self.id.clone(),
+            started_at: self.started_at,
+            interactions,
+        }
+    }
+}
+
+/// In-memory log of what was transcribed and answered since the last session boundary.
+pub struct SessionLog {
+    // Held only for short, non-async sections so hotkey callbacks can use it directly.
+    state: std::sync::Mutex<SessionState>,
+    archived: std::sync::Mutex<VecDeque<SessionState>>,
+    // Serializes exports so two requests never interleave writes to the same file.
+    export_lock: Mutex<()>,
+}
+
+impl SessionLog {
+    pub fn new() -> Self {
+        Self {
+            state: std::sync::Mutex::new(SessionState::new()),
+            archived: std::sync::Mutex::new(VecDeque::new()),
+            export_lock: Mutex::new(()),
+        }
+    }
+
+    pub fn begin_interaction(
+        &self,
+        trigger: InteractionTrigger,
+        clip_seconds: Option<f64>,
+    ) -> InteractionIds {
+        let mut state = self.state.lock().unwrap();
+        let interaction_id = Uuid::new_v4().to_string();
+        state.begin(interaction_id.clone(), trigger, clip_seconds);
+        InteractionIds {
+            session_id: state.id.clone(),
+            interaction_id,
+        }
+    }
+
+    /// Appends an entry to `interaction_id` and returns the id it was filed under.
+    /// Nothing is kept during a private session, so no export can carry it later.
+    pub fn record(
+        &self,
+        kind: SessionEntryKind,
+        speaker: Option<String>,
+        text: &str,
+        interaction_id: Option<&str>,
+    ) -> Option<String> {
+        let text = text.trim();
+        if text.is_empty() || private_session::is_active() {
+            return None;
+        }
+        let mut state = self.state.lock().unwrap();
+        let interaction_id = state.interaction(interaction_id).id.clone();
+        state.entries.push(SessionEntry {
+            timestamp: Local::now(),
+            kind,
+            speaker,
+            text: text.to_string(),
+            interaction_id: Some(interaction_id.clone()),
+        });
+        Some(interaction_id)
+    }
+
+    /// Files a rolling summary as an interaction of its own. Like `record`,
+    /// nothing is kept during a private session.
+    pub fn record_summary(&self, text: &str) -> bool {
+        let text = text.trim();
+        if text.is_empty() || private_session::is_active() {
+            return false;
+        }
+        let mut state = self.state.lock().unwrap();
+        let interaction_id = Uuid::new_v4().to_string();
+        state.begin(
+            interaction_id.clone(),
+            InteractionTrigger::AutoSummary,
+            None,
+        );
+        state.entries.push(SessionEntry {
+            timestamp: Local::now(),
+            kind: SessionEntryKind::Summary,
+            speaker: None,
+            text: text.to_string(),
+            interaction_id: Some(interaction_id),
+        });
+        true
+    }
+
+    /// Transcripts of the current session recorded after `since`, with the id
+    /// of that session. A point in an earlier session leaves out nothing.
+    pub fn transcripts_since(
+        &self,
+        since: Option<&(String, DateTime<Local>)>,
+    ) -> (String, Vec<SessionEntry>) {
+        let state = self.state.lock().unwrap();
+        let since = since
+            .filter(|(session_id, _)| *session_id == state.id)
+            .map(|(_, timestamp)| *timestamp);
+        let entries = state
+            .entries
+            .iter()
+            .filter(|entry| entry.kind == SessionEntryKind::Transcript)
+            .filter(|entry| since.is_none_or(|since| entry.timestamp > since))
+            .cloned()
+            .collect();
+        (state.id.clone(), entries)
+    }
+
+    /// Stores the length of the clip that was actually transcribed.
+    pub fn record_clip(&self, interaction_id: Option<&str>, seconds: f64) -> String {
+        let mut state = self.state.lock().unwrap();
+        let interaction = state.interaction(interaction_id);
+        interaction.clip_seconds = Some(seconds);
+        interaction.id.clone()
+    }
+
+    /// Interactions of the current session, or of a recently cleared one by id,
+    /// oldest first.
+    pub fn timeline(&self, session_id: Option<&str>) -> Option<SessionTimeline> {
+        let state = self.state.lock().unwrap();
+        if session_id.is_none_or(|id| id == state.id) {
+            return Some(state.timeline());
+        }
+        self.archived
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|archived| Some(archived.id.as_str()) == session_id)
+            .map(SessionState::timeline)
+    }
+
+    pub fn clear(&self) {
+        let previous = std::mem::replace(&mut *self.state.lock().unwrap(), SessionState::new());
+        if !previous.interactions.is_empty() {
+            let mut archived = self.archived.lock().unwrap();
+            if archived.len() == MAX_ARCHIVED_SESSIONS {
+                archived.pop_front();
+            }
+            archived.push_back(previous);
+        }
+        log::info!(target: "session", "Session cleared");
+    }
+
+    pub async fn export(
+        &self,
+        app: &AppHandle,
+        format: ExportFormat,
+        path: Option<String>,
+    ) -> Result<PathBuf> {
+        let _export = self.export_lock.lock().await;
+        let (started_at, entries) = {
+            let state = self.state.lock().unwrap();
+            (state.started_at, state.entries.clone())
+        };
+        // An answer that quotes its prompt must not carry the window context out.
+        let entries: Vec<SessionEntry> = entries
+            .into_iter()
+            .map(|entry| SessionEntry {
+                text: foreground::strip_context(&entry.text),
+                ..entry
+            })
+            .collect();
+        if entries.is_empty() {
+            return Err(anyhow!("Nothing to export: the session is empty"));
+        }
+
+        let target = match path.map(|value| value.trim().to_string()) {
+            Some(value) if !value.is_empty() => PathBuf::from(value),
+            _ => default_export_path(app, started_at, format)?,
+        };
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)
+                .await
+                .context("create export directory")?;
+        }
+        let contents = match format {
+            ExportFormat::Markdown => render_markdown(started_at, &entries),
+            ExportFormat::Json => serde_json::to_string_pretty(&entries)?,
+        };
+        // Write next to the target and rename so readers never see a half-written file.
+        let partial = target.with_extension(format!("{}.partial", format.extension()));
+        fs::write(&partial, contents)
+            .await
+            .context("write session export")?;
+        fs::rename(&partial, &target)
+            .await
+            .context("finalize session export")?;
+
+        log::info!(
+            target: "session",
+            "Session exported: path={} entries={}",
+            target.display(),
+            entries.len()
+        );
+        let _ = emit_event(
+            app,
+            events::SESSION_EXPORTED,
+            SessionExportedPayload {
+                path: target.to_string_lossy().to_string(),
+                format: format.extension(),
+                entries: entries.len(),
+            },
+        );
+        Ok(target)
+    }
+}
+
+fn default_export_path(
+    app: &AppHandle,
+    started_at: DateTime<Local>,
+    format: ExportFormat,
+) -> Result<PathBuf> {
+    let dir = app
+        .path()
+        .app_local_data_dir()
+        .map_err(|error| anyhow!("Failed to resolve app data directory: {error}"))?
+        .join(SESSIONS_DIR_NAME);
+    Ok(dir.join(format!(
+        "session-{}.{}",
+        started_at.format("%Y%m%d-%H%M%S"),
+        format.extension()
+    )))
+}
+
+fn render_markdown(started_at: DateTime<Local>, entries: &[SessionEntry]) -> String {
+    let mut out = format!(
+        "# XexamAI session — {}\n\n",
+        started_at.format("%Y-%m-%d %H:%M")
+    );
+    for entry in entries {
+        let time = entry.timestamp.format("%H:%M:%S");
+        match entry.kind {
+            SessionEntryKind::Transcript => {
+                let speaker = entry.speaker.as_deref().unwrap_or("Transcript");
+                out.push_str(&format!("### {time} — {speaker}\n\n{}\n\n", entry.text));
+            }
+            SessionEntryKind::Summary => {
+                out.push_str(&format!("**Summary ({time})**\n\n{}\n\n", entry.text));
+            }
+            SessionEntryKind::Translation => {
+                out.push_str(&format!("*Translation:* {}\n\n", entry.text));
+            }
+            SessionEntryKind::Answer => {
+                out.push_str(&format!("**Answer ({time})**\n\n"));
+                for line in entry.text.lines() {
+                    out.push_str("> ");
+                    out.push_str(line);
+                    out.push('\n');
+                }
+                out.push('\n');
+            }
+        }
+    }
+    out
+}
+
+/// Starts an interaction on the managed log; `None` before setup has run.
+pub fn begin_interaction<R: Runtime>(
+    app: &AppHandle<R>,
+    trigger: InteractionTrigger,
+    clip_seconds: Option<f64>,
+) -> Option<InteractionIds> {
+    app.try_state::<Arc<SessionLog>>()
+        .map(|log| log.begin_interaction(trigger, clip_seconds))
+}
+
+pub fn speaker_for_input(audio_input_type: AudioInputType) -> Option<String> {
+    match audio_input_type {
+        AudioInputType::Microphone => Some("Microphone".to_string()),
+        AudioInputType::System => Some("System audio".to_string()),
+        AudioInputType::Mixed => Some("Microphone + system".to_string()),
+        AudioInputType::Unknown => None,
+    }
+}
@@ -0,0 +1,258 @@
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Context, Result};
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::{SampleFormat, StreamConfig};
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+use crate::config::ConfigState;
+use crate::resample;
+use crate::resources::{self, is_plain_name};
+
+pub const CUE_RECORD_START: &str = "recordStart";
+pub const CUE_RECORD_STOP: &str = "recordStop";
+pub const CUE_TRANSCRIPT_READY: &str = "transcriptReady";
+pub const CUE_ERROR: &str = "error";
+
+pub const CUE_EVENTS: &[&str] = &[
+    CUE_RECORD_START,
+    CUE_RECORD_STOP,
+    CUE_TRANSCRIPT_READY,
+    CUE_ERROR,
+];
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SoundCueInfo {
+    pub name: String,
+    /// `None` for sounds bundled with the app.
+    pub theme: Option<String>,
+    pub path: String,
+    pub valid: bool,
+    pub duration_ms: Option<u64>,
+    pub warning: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SoundsListing {
+    pub themes: Vec<String>,
+    pub active_theme: Option<String>,
+    pub events: Vec<&'static str>,
+    pub cues: Vec<SoundCueInfo>,
+}
+
+struct DecodedSound {
+    channels: usize,
+    sample_rate: u32,
+    /// Interleaved samples in `-1.0..=1.0`.
+    samples: Vec<f32>,
+}
+
+impl DecodedSound {
+    fn duration(&self) -> Duration {
+        let frames = self.samples.len() / self.channels.max(1);
+        Duration::from_secs_f64(frames as f64 / self.sample_rate.max(1) as f64)
+    }
+}
+
+/// Lists bundled cues and every user theme, probing each file with the decoder
+/// so broken files show up as warnings here instead of failing silently on play.
+pub async fn list(app: &AppHandle) -> Result<SoundsListing> {
+    let config = app.state::<Arc<ConfigState>>().get().await;
+    let bundled_dir = resources::bundled_sounds_dir(app);
+    let user_dir = resources::user_sounds_dir(app);
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut cues = Vec::new();
+        if let Some(dir) = bundled_dir {
+            cues.extend(probe_dir(&dir, None));
+        }
+        let mut themes = Vec::new();
+        if let Some(user_dir) = user_dir {
+            if let Ok(entries) = std::fs::read_dir(&user_dir) {
+                for entry in entries.filter_map(|entry| entry.ok()) {
+                    let path = entry.path();
+                    let name = entry.file_name().to_string_lossy().to_string();
+                    if path.is_dir() && is_plain_name(&name) {
+                        cues.extend(probe_dir(&path, Some(&name)));
+                        themes.push(name);
+                    }
+                }
+            }
+        }
+        themes.sort();
+        for cue in cues.iter().filter(|cue| !cue.valid) {
+            log::warn!(
+                target: "sounds",
+                "Sound cue cannot be played: path={} reason={}",
+                cue.path,
+                cue.warning.as_deref().unwrap_or("unknown")
+            );
+        }
+        SoundsListing {
+            themes,
+            active_theme: config.sound_theme,
+            events: CUE_EVENTS.to_vec(),
+            cues,
+        }
+    })
+    .await
+    .map_err(|error| anyhow!("Failed to list sounds: {error}"))
+}
+
+/// Plays the cue mapped to `event` in `soundCues`, if any. Never blocks the caller.
+pub fn play_event(app: &AppHandle, event: &'static str) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let config = app.state::<Arc<ConfigState>>().get().await;
+        let Some(cue) = config.sound_cues.get(event).filter(|cue| !cue.is_empty()) else {
+            return;
+        };
+        let Some(path) = resources::resolve_sound_path(&app, cue, config.sound_theme.as_deref())
+        else {
+            log::warn!(target: "sounds", "Sound cue not found: event={event} cue={cue}");
+            return;
+        };
+        let result =
+            tauri::async_runtime::spawn_blocking(move || play_file(Path::new(&path))).await;
+        match result {
+            Ok(Ok(())) => {}
+            Ok(Err(error)) => {
+                log::warn!(target: "sounds", "Failed to play sound cue: event={event} error={error}");
+            }
+            Err(error) => {
+                log::warn!(target: "sounds", "Sound playback task failed: {error}");
+            }
+        }
+    });
+}
+
+fn probe_dir(dir: &Path, theme: Option<&str>) -> Vec<SoundCueInfo> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut cues: Vec<SoundCueInfo> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_file())
+        .map(|entry| {
+            let path = entry.path();
+            let probe = std::fs::read(&path)
+                .context("read sound file")
+                .and_then(|bytes| decode_wav(&bytes));
+            let (valid, duration_ms, warning) = match probe {
+                Ok(sound) => (true, Some(sound.duration().as_millis() as u64), None),
+                Err(error) => (false, None, Some(error.to_string())),
+            };
+            SoundCueInfo {
+                name: entry.file_name().to_string_lossy().to_string(),
+                theme: theme.map(str::to_string),
+                path: path.to_string_lossy().to_string(),
+                valid,
+                duration_ms,
+                warning,
+            }
+        })
+        .collect();
+    cues.sort_by(|a, b| a.name.cmp(&b.name));
+    cues
+}
+
+fn play_file(path: &Path) -> Result<()> {
+    let bytes = std::fs::read(path).context("read sound file")?;
+    let sound = decode_wav(&bytes)?;
+    let device = cpal::default_host()
+        .default_output_device()
+        .ok_or_else(|| anyhow!("No output device available"))?;
+    let supported = device.default_output_config()?;
+    let sample_format = supported.sample_format();
+    let config: StreamConfig = supported.into();
+    let frames = resample(&sound, config.channels as usize, config.sample_rate.0);
+    let duration = Duration::from_secs_f64(
+        frames.len() as f64 / config.channels.max(1) as f64 / config.sample_rate.0.max(1) as f64,
+    );
+    let frames = Arc::new(frames);
+    let position = Arc::new(AtomicUsize::new(0));
+    let next_sample = move || {
+        let index = position.fetch_add(1, Ordering::Relaxed);
+        frames.get(index).copied().unwrap_or(0.0)
+    };
+
+    let err_fn = |err| {
+        log::warn!(target: "sounds", "output stream error: {}", err);
+    };
+    let stream = match sample_format {
+        SampleFormat::F32 => device.build_output_stream(
+            &config,
+            move |data: &mut [f32], _| data.iter_mut().for_each(|s| *s = next_sample()),
+            err_fn,
+            None,
+        )?,
+        SampleFormat::I16 => device.build_output_stream(
+            &config,
+            move |data: &mut [i16], _| {
+                data.iter_mut()
+                    .for_each(|s| *s = (next_sample() * i16::MAX as f32) as i16)
+            },
+            err_fn,
+            None,
+        )?,
+        SampleFormat::U16 => device.build_output_stream(
+            &config,
+            move |data: &mut [u16], _| {
+                data.iter_mut()
+                    .for_each(|s| *s = ((next_sample() * 32767.0) as i32 + 32768) as u16)
+            },
+            err_fn,
+            None,
+        )?,
+        other => bail!("Unsupported output sample format: {other:?}"),
+    };
+    stream.play()?;
+    // The stream stops when dropped, so keep it alive for the cue plus a short tail.
+    std::thread::sleep(duration + Duration::from_millis(100));
+    Ok(())
+}
+
+/// Nearest-neighbour conversion to the device layout; cues are short UI sounds.
+fn resample(sound: &DecodedSound, channels: usize, sample_rate: u32) -> Vec<f32> {
+    let channels = channels.max(1);
+    let source_frames = sound.samples.len() / sound.channels;
+    let target_frames =
+        (source_frames as u64 * sample_rate as u64 / sound.sample_rate as u64) as usize;
+    let mut out = Vec::with_capacity(target_frames * channels);
+    for frame in 0..target_frames {
+        let source_frame = ((frame as u64 * sound.sample_rate as u64 / sample_rate as u64)
+            as usize)
+            .min(source_frames.saturating_sub(1));
+        for channel in 0..channels {
+            let source_channel = channel.min(sound.channels - 1);
+            out.push(sound.samples[source_frame * sound.channels + source_channel]);
+        }
+    }
+    out
+}
+
+/// Decodes a RIFF/WAVE cue with the transcription WAV reader.
+fn decode_wav(bytes: &[u8]) -> Result<DecodedSound> {
+    if !resample::is_wav(bytes) {
+        bail!("Not a WAV file; only RIFF/WAVE cues are supported");
+    }
+    let wav = resample::parse_wav(bytes)?;
+    if wav.samples.is_empty() {
+        bail!("WAV file contains no audio");
+    }
+    Ok(DecodedSound {
+        channels: wav.channels,
+        sample_rate: wav.sample_rate,
+        samples: wav
+            .samples
+            .iter()
+            .map(|&sample| sample as f32 / 32768.0)
+            .collect(),
+    })
+}
@@ -0,0 +1,68 @@
+//! Switching between base and stream mode. The mode decides which hotkeys are
+//! registered, so the switch goes through here rather than a plain config edit.
+
+use std::sync::Arc;
+
+use serde::Serialize;
+use serde_json::json;
+use tauri::{AppHandle, Manager};
+
+use crate::config::{ConfigSnapshot, ConfigState};
+use crate::errors::{CommandError, CommandResult, ErrorCode};
+use crate::events::{self, emit_event};
+use crate::hotkeys::{stream_shortcut, HotkeyManager, HotkeyResult};
+use crate::types::StreamMode;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StreamModeChanged {
+    pub mode: StreamMode,
+    /// Label of the window that switched.
+    pub origin: String,
+    pub revision: u64,
+    /// The stream send hotkey, when stream mode registered one.
+    pub hotkey: Option<HotkeyResult>,
+}
+
+pub async fn set_stream_mode(
+    app: &AppHandle,
+    mode: &str,
+    origin: &str,
+) -> CommandResult<ConfigSnapshot> {
+    let mode_value = StreamMode::parse(mode);
+    if mode_value == StreamMode::Unknown {
+        return Err(
+            CommandError::bare(ErrorCode::InvalidArgument, &[("name", "mode")]).with_data(json!({
+                "mode": mode,
+                "allowed": [StreamMode::Base, StreamMode::Stream],
+            })),
+        );
+    }
+    let config = app.state::<Arc<ConfigState>>();
+    if config.get().await.stream_mode == mode_value {
+        return Ok(config.snapshot().await);
+    }
+    let updated = config
+        .update(json!({ "streamMode": mode_value }), origin)
+        .await
+        .map_err(|error| CommandError::new(ErrorCode::ConfigFailed, error))?;
+    let hotkey = app
+        .state::<Arc<HotkeyManager>>()
+        .register_stream_hotkey(app, stream_shortcut(&updated.config));
+    log::info!(
+        target: "stream",
+        "Stream mode set to {mode_value} (hotkey registered={:?})",
+        hotkey.as_ref().map(|result| result.registered)
+    );
+    let _ = emit_event(
+        app,
+        events::STREAM_MODE_CHANGED,
+        StreamModeChanged {
+            mode: mode_value,
+            origin: origin.to_string(),
+            revision: updated.revision,
+            hotkey,
+        },
+    );
+    Ok(updated)
+}
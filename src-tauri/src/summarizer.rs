@@ -0,0 +1,214 @@
+//! Rolling summaries of the session while capture runs. Every
+//! `autoSummarizeEveryMin` minutes of capture, the transcripts recorded since
+//! the last summary go to the webview as `session:summary-request`; it runs them
+//! through the LLM with `summaryPrompt` and reports back through
+//! `session_summary_complete`, which files the summary and emits
+//! `session:summary`.
+//!
+//! One request is in flight at a time. Stopping capture or turning the feature
+//! off cancels it through `session:summary-cancel`.
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+use uuid::Uuid;
+
+use crate::audio::AudioManager;
+use crate::config::ConfigState;
+use crate::events::{self, emit_event};
+use crate::session::SessionLog;
+
+const TICK_INTERVAL: Duration = Duration::from_secs(15);
+// A webview that reloaded mid-request never answers; the next cycle takes over.
+const PENDING_TIMEOUT: Duration = Duration::from_secs(180);
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SummaryRequestPayload {
+    request_id: String,
+    prompt: String,
+    text: String,
+    covering_from: DateTime<Local>,
+    covering_to: DateTime<Local>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SummaryCancelPayload {
+    request_id: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionSummary {
+    pub text: String,
+    pub covering_from: DateTime<Local>,
+    pub covering_to: DateTime<Local>,
+}
+
+struct PendingSummary {
+    request_id: String,
+    session_id: String,
+    covering_from: DateTime<Local>,
+    covering_to: DateTime<Local>,
+    sent_at: Instant,
+}
+
+#[derive(Default)]
+struct SummaryState {
+    /// Start of the current interval; `None` while capture is stopped.
+    interval_started: Option<Instant>,
+    /// Session and timestamp of the last transcript already summarized.
+    covered: Option<(String, DateTime<Local>)>,
+    pending: Option<PendingSummary>,
+}
+
+#[derive(Default)]
+pub struct Summarizer {
+    state: Mutex<SummaryState>,
+}
+
+impl Summarizer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Runs a cycle check every few seconds for the lifetime of the app.
+    pub fn start(self: &Arc<Self>, app: &AppHandle) {
+        let summarizer = self.clone();
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            loop {
+                tokio::time::sleep(TICK_INTERVAL).await;
+                summarizer.tick(&app).await;
+            }
+        });
+    }
+
+    async fn tick(&self, app: &AppHandle) {
+        let config = app.state::<Arc<ConfigState>>().get().await;
+        let active = app.state::<Arc<AudioManager>>().status().active;
+        let every = Duration::from_secs(config.auto_summarize_every_min as u64 * 60);
+        let mut state = self.state.lock().unwrap();
+        if !active || every.is_zero() {
+            state.interval_started = None;
+            if let Some(pending) = state.pending.take() {
+                log::info!(target: "summary", "Summary cancelled: capture stopped or summaries off");
+                cancel(app, pending);
+            }
+            return;
+        }
+        let started = *state.interval_started.get_or_insert_with(Instant::now);
+        if let Some(pending) = &state.pending {
+            if pending.sent_at.elapsed() < PENDING_TIMEOUT {
+                return;
+            }
+            log::warn!(target: "summary", "Summary request timed out");
+            if let Some(pending) = state.pending.take() {
+                cancel(app, pending);
+            }
+        }
+        if started.elapsed() < every {
+            return;
+        }
+        state.interval_started = Some(Instant::now());
+
+        let session = app.state::<Arc<SessionLog>>();
+        let (session_id, entries) = session.transcripts_since(state.covered.as_ref());
+        let (Some(first), Some(last)) = (entries.first(), entries.last()) else {
+            log::info!(target: "summary", "Summary skipped: no new transcripts");
+            return;
+        };
+        let text = entries
+            .iter()
+            .map(|entry| {
+                let time = entry.timestamp.format("%H:%M:%S");
+                match &entry.speaker {
+                    Some(speaker) => format!("[{time}] {speaker}: {}", entry.text),
+                    None => format!("[{time}] {}", entry.text),
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        let pending = PendingSummary {
+            request_id: Uuid::new_v4().to_string(),
+            session_id,
+            covering_from: first.timestamp,
+            covering_to: last.timestamp,
+            sent_at: Instant::now(),
+        };
+        log::info!(
+            target: "summary",
+            "Summary requested: request_id={} transcripts={}",
+            pending.request_id,
+            entries.len()
+        );
+        let _ = emit_event(
+            app,
+            events::SESSION_SUMMARY_REQUEST,
+            SummaryRequestPayload {
+                request_id: pending.request_id.clone(),
+                prompt: config.summary_prompt.clone(),
+                text,
+                covering_from: pending.covering_from,
+                covering_to: pending.covering_to,
+            },
+        );
+        state.pending = Some(pending);
+    }
+
+    /// Takes the webview's answer to `request_id`. A failed request leaves its
+    /// transcripts for the next cycle; answers to cancelled requests are dropped.
+    pub fn complete(
+        &self,
+        app: &AppHandle,
+        request_id: &str,
+        text: Option<String>,
+        error: Option<String>,
+    ) -> Option<SessionSummary> {
+        let pending = {
+            let mut state = self.state.lock().unwrap();
+            if state
+                .pending
+                .as_ref()
+                .is_none_or(|pending| pending.request_id != request_id)
+            {
+                log::info!(target: "summary", "Stale summary ignored: request_id={request_id}");
+                return None;
+            }
+            state.pending.take()?
+        };
+        let text = text.map(|text| text.trim().to_string()).unwrap_or_default();
+        if text.is_empty() {
+            log::warn!(
+                target: "summary",
+                "Summary failed: request_id={request_id} error={}",
+                error.as_deref().unwrap_or("empty answer")
+            );
+            return None;
+        }
+        self.state.lock().unwrap().covered = Some((pending.session_id, pending.covering_to));
+        app.state::<Arc<SessionLog>>().record_summary(&text);
+        let summary = SessionSummary {
+            text,
+            covering_from: pending.covering_from,
+            covering_to: pending.covering_to,
+        };
+        log::info!(target: "summary", "Summary recorded: request_id={request_id}");
+        let _ = emit_event(app, events::SESSION_SUMMARY, &summary);
+        Some(summary)
+    }
+}
+
+fn cancel(app: &AppHandle, pending: PendingSummary) {
+    let _ = emit_event(
+        app,
+        events::SESSION_SUMMARY_CANCEL,
+        SummaryCancelPayload {
+            request_id: pending.request_id,
+        },
+    );
+}
@@ -0,0 +1,194 @@
+//! Memory and GPU figures used to tell whether a local model will run. Read from the
+//! OS directly and from `nvidia-smi` when it is installed; every field is optional
+//! because any of the probes can be missing.
+
+use std::process::Stdio;
+
+use serde::Serialize;
+use tokio::process::Command;
+
+#[cfg(windows)]
+const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GpuInfo {
+    pub name: String,
+    pub vram_total_bytes: Option<u64>,
+    pub vram_free_bytes: Option<u64>,
+    /// Apple Silicon: the GPU shares system RAM instead of having its own.
+    pub unified_memory: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SystemResources {
+    pub total_ram_bytes: Option<u64>,
+    pub available_ram_bytes: Option<u64>,
+    pub gpus: Vec<GpuInfo>,
+}
+
+impl SystemResources {
+    /// VRAM of the largest GPU, or the RAM a unified-memory GPU may use.
+    pub fn gpu_memory_bytes(&self) -> Option<u64> {
+        self.gpus
+            .iter()
+            .filter_map(|gpu| {
+                if gpu.unified_memory {
+                    // macOS lets the GPU wire about three quarters of RAM.
+                    self.total_ram_bytes.map(|total| total / 4 * 3)
+                } else {
+                    gpu.vram_total_bytes
+                }
+            })
+            .max()
+    }
+}
+
+pub async fn probe() -> SystemResources {
+    let (total_ram_bytes, available_ram_bytes) = memory().await;
+    let mut gpus = nvidia_gpus().await;
+    if gpus.is_empty() {
+        gpus.extend(apple_gpu());
+    }
+    SystemResources {
+        total_ram_bytes,
+        available_ram_bytes,
+        gpus,
+    }
+}
+
+#[cfg(target_os = "linux")]
+async fn memory() -> (Option<u64>, Option<u64>) {
+    let Ok(meminfo) = tokio::fs::read_to_string("/proc/meminfo").await else {
+        return (None, None);
+    };
+    // Values are in KiB: "MemTotal:       16318512 kB".
+    let field = |key: &str| {
+        meminfo.lines().find_map(|line| {
+            let value = line.strip_prefix(key)?.trim_start_matches(':').trim();
+            let kib: u64 = value.trim_end_matches("kB").trim().parse().ok()?;
+            Some(kib * 1024)
+        })
+    };
+    (field("MemTotal"), field("MemAvailable"))
+}
+
+#[cfg(target_os = "macos")]
+async fn memory() -> (Option<u64>, Option<u64>) {
+    let total = run("sysctl", &["-n", "hw.memsize"])
+        .await
+        .and_then(|out| out.trim().parse().ok());
+    // Free, inactive and speculative pages can all be handed out without swapping.
+    let available = run("vm_stat", &[]).await.and_then(|out| {
+        let page_size: u64 = out
+            .lines()
+            .next()?
+            .split("page size of ")
+            .nth(1)?
+            .split_whitespace()
+            .next()?
+            .parse()
+            .ok()?;
+        let pages = |key: &str| -> u64 {
+            out.lines()
+                .find_map(|line| line.strip_prefix(key))
+                .and_then(|value| value.trim().trim_end_matches('.').parse().ok())
+                .unwrap_or(0)
+        };
+        Some(
+            (pages("Pages free:") + pages("Pages inactive:") + pages("Pages speculative:"))
+                * page_size,
+        )
+    });
+    (total, available)
+}
+
+#[cfg(windows)]
+async fn memory() -> (Option<u64>, Option<u64>) {
+    use windows::Win32::System::SystemInformation::{GlobalMemoryStatusEx, MEMORYSTATUSEX};
+
+    let mut status = MEMORYSTATUSEX {
+        dwLength: std::mem::size_of::<MEMORYSTATUSEX>() as u32,
+        ..Default::default()
+    };
+    match unsafe { GlobalMemoryStatusEx(&mut status) } {
+        Ok(()) => (Some(status.ullTotalPhys), Some(status.ullAvailPhys)),
+        Err(error) => {
+            log::warn!(target: "resources", "GlobalMemoryStatusEx failed: {error}");
+            (None, None)
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+async fn memory() -> (Option<u64>, Option<u64>) {
+    (None, None)
+}
+
+/// One entry per NVIDIA GPU; empty when `nvidia-smi` is missing or fails.
+async fn nvidia_gpus() -> Vec<GpuInfo> {
+    let Some(out) = run(
+        "nvidia-smi",
+        &[
+            "--query-gpu=name,memory.total,memory.free",
+            "--format=csv,noheader,nounits",
+        ],
+    )
+    .await
+    else {
+        return Vec::new();
+    };
+    // Memory is reported in MiB: "NVIDIA GeForce RTX 3060, 12288, 11623".
+    let mib = |value: Option<&str>| {
+        value
+            .and_then(|value| value.trim().parse::<u64>().ok())
+            .map(|mib| mib * 1024 * 1024)
+    };
+    out.lines()
+        .filter_map(|line| {
+            let mut fields = line.split(',');
+            let name = fields.next()?.trim().to_string();
+            (!name.is_empty()).then(|| GpuInfo {
+                name,
+                vram_total_bytes: mib(fields.next()),
+                vram_free_bytes: mib(fields.next()),
+                unified_memory: false,
+            })
+        })
+        .collect()
+}
+
+fn apple_gpu() -> Option<GpuInfo> {
+    (cfg!(target_os = "macos") && cfg!(target_arch = "aarch64")).then(|| GpuInfo {
+        name: "Apple Silicon GPU".to_string(),
+        unified_memory: true,
+        ..GpuInfo::default()
+    })
+}
+
+/// Stdout of a successful run, or `None` when the tool is missing or fails.
+async fn run(program: &str, args: &[&str]) -> Option<String> {
+    let mut cmd = Command::new(program);
+    cmd.args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null());
+    #[cfg(windows)]
+    {
+        cmd.creation_flags(CREATE_NO_WINDOW);
+    }
+    match cmd.output().await {
+        Ok(output) if output.status.success() => {
+            Some(String::from_utf8_lossy(&output.stdout).into_owned())
+        }
+        Ok(output) => {
+            log::debug!(target: "resources", "{program} exited with {}", output.status);
+            None
+        }
+        Err(error) => {
+            log::debug!(target: "resources", "{program} not available: {error}");
+            None
+        }
+    }
+}
@@ -0,0 +1,162 @@
+//! Cleanup for text pasted from documents. Google Docs and Word carry a BOM and
+//! zero-width characters along, macOS pastes accented letters decomposed, and
+//! Windows line ends arrive as `\r\n`. None of it is visible in the settings,
+//! but it breaks prompt echo detection and some provider schemas.
+
+use crate::constants::MAX_PROMPT_CHARS;
+
+/// Dropped wherever they appear: BOM, zero-width space/non-joiner/joiner and
+/// word joiner.
+const INVISIBLE: &[char] = &['\u{feff}', '\u{200b}', '\u{200c}', '\u{200d}', '\u{2060}'];
+
+/// Canonical compositions for the combining marks pasted text actually carries,
+/// over Latin and Cyrillic letters: `(mark, bases, composed)`, where the n-th
+/// base plus the mark composes to the n-th composed letter.
+const COMPOSITIONS: &[(char, &str, &str)] = &[
+    ('\u{300}', "AEINOUWYaeinouwyЕИеи", "ÀÈÌǸÒÙẀỲàèìǹòùẁỳЀЍѐѝ"),
+    (
+        '\u{301}',
+        "ACEGIKLMNOPRSUWYZacegiklmnoprsuwyzГКгк",
+        "ÁĆÉǴÍḰĹḾŃÓṔŔŚÚẂÝŹáćéǵíḱĺḿńóṕŕśúẃýźЃЌѓќ",
+    ),
+    (
+        '\u{302}',
+        "ACEGHIJOSUWYZaceghijosuwyz",
+        "ÂĈÊĜĤÎĴÔŜÛŴŶẐâĉêĝĥîĵôŝûŵŷẑ",
+    ),
+    ('\u{303}', "AEINOUVYaeinouvy", "ÃẼĨÑÕŨṼỸãẽĩñõũṽỹ"),
+    (
+        '\u{306}',
+        "AEGIOUaegiouАЕЖИУаежиу",
+        "ĂĔĞĬŎŬăĕğĭŏŭӐӖӁЙЎӑӗӂйў",
+    ),
+    (
+        '\u{308}',
+        "AEHIOUWXYaehiotuwxyІАЕЖЗИОУЧЫЭаежзиоучыэі",
+        "ÄËḦÏÖÜẄẌŸäëḧïöẗüẅẍÿЇӒЁӜӞӤӦӰӴӸӬӓёӝӟӥӧӱӵӹӭї",
+    ),
+    ('\u{30a}', "AUauwy", "ÅŮåůẘẙ"),
+    (
+        '\u{30c}',
+        "ACDEGHIKLNORSTUZacdeghijklnorstuz",
+        "ǍČĎĚǦȞǏǨĽŇǑŘŠŤǓŽǎčďěǧȟǐǰǩľňǒřšťǔž",
+    ),
+    (
+        '\u{327}',
+        "CDEGHKLNRSTcdeghklnrst",
+        "ÇḐȨĢḨĶĻŅŖŞŢçḑȩģḩķļņŗşţ",
+    ),
+];
+
+/// Strips invisible characters, composes decomposed letters and turns every
+/// line end into `\n`.
+pub fn sanitize(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match ch {
+            _ if INVISIBLE.contains(&ch) => {}
+            '\r' => {
+                chars.next_if_eq(&'\n');
+                out.push('\n');
+            }
+            '\u{2028}' | '\u{2029}' => out.push('\n'),
+            _ => match out.chars().next_back().and_then(|base| compose(base, ch)) {
+                Some(composed) => {
+                    out.pop();
+                    out.push(composed);
+                }
+                None => out.push(ch),
+            },
+        }
+    }
+    out
+}
+
+/// `sanitize`, then cut to `MAX_PROMPT_CHARS` with a warning naming `field`.
+pub fn sanitize_prompt(field: &str, text: &str) -> String {
+    let mut text = sanitize(text);
+    if let Some((cut, _)) = text.char_indices().nth(MAX_PROMPT_CHARS) {
+        log::warn!(
+            target: "config",
+            "{field} is longer than {MAX_PROMPT_CHARS} characters; the rest was dropped"
+        );
+        text.truncate(cut);
+    }
+    text
+}
+
+fn compose(base: char, mark: char) -> Option<char> {
+    let (_, bases, composed) = COMPOSITIONS.iter().find(|(m, ..)| *m == mark)?;
+    let index = bases.chars().position(|c| c == base)?;
+    composed.chars().nth(index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invisible_characters_are_dropped() {
+        assert_eq!(
+            sanitize("\u{feff}Hello\u{200b} wor\u{200d}ld\u{2060}"),
+            "Hello world"
+        );
+        assert_eq!(sanitize("\u{200c}\u{feff}"), "");
+    }
+
+    #[test]
+    fn every_line_end_becomes_a_newline() {
+        assert_eq!(
+            sanitize("a\r\nb\rc\nd\u{2028}e\u{2029}f"),
+            "a\nb\nc\nd\ne\nf"
+        );
+        assert_eq!(sanitize("a\r\n\r\nb"), "a\n\nb");
+        assert_eq!(sanitize("a\n\rb"), "a\n\nb");
+    }
+
+    #[test]
+    fn decomposed_letters_are_composed() {
+        assert_eq!(
+            sanitize("Cafe\u{301} Ёлка Zu\u{308}rich"),
+            "Café Ёлка Zürich"
+        );
+        assert_eq!(sanitize("Е\u{308}ж и\u{306}"), "Ёж й");
+        // A mark with no composition stays as it came.
+        assert_eq!(sanitize("q\u{301}"), "q\u{301}");
+        assert_eq!(sanitize("\u{301}a"), "\u{301}a");
+    }
+
+    #[test]
+    fn pasted_document_text_comes_out_clean() {
+        let pasted =
+            "\u{feff}You are\u{200b} an interviewer.\r\nAsk about re\u{301}sume\u{301}s.\r\n";
+        assert_eq!(
+            sanitize(pasted),
+            "You are an interviewer.\nAsk about résumés.\n"
+        );
+        assert_eq!(sanitize(&sanitize(pasted)), sanitize(pasted));
+    }
+
+    #[test]
+    fn composition_table_rows_line_up() {
+        for (mark, bases, composed) in COMPOSITIONS {
+            assert_eq!(
+                bases.chars().count(),
+                composed.chars().count(),
+                "row for U+{:04X}",
+                *mark as u32
+            );
+        }
+    }
+
+    #[test]
+    fn prompts_are_capped_by_characters() {
+        let long = "é".repeat(MAX_PROMPT_CHARS + 1);
+        assert_eq!(
+            sanitize_prompt("llmPrompt", &long).chars().count(),
+            MAX_PROMPT_CHARS
+        );
+        assert_eq!(sanitize_prompt("llmPrompt", "short\r\n"), "short\n");
+    }
+}
@@ -0,0 +1,165 @@
+//! Best-effort priority boost for the audio threads, so capture and mixing keep
+//! up while the LLM streams and the webview renders.
+//!
+//! Windows raises the thread to above normal and can also register the WASAPI
+//! thread with MMCSS as "Pro Audio". Elsewhere the thread asks for round-robin
+//! scheduling and, on Linux, falls back to a lower nice value; both usually need
+//! privileges, so failing is expected. What each running thread got is kept for
+//! `CaptureStatus`.
+
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use serde::Serialize;
+
+use crate::types::AppConfig;
+
+#[cfg(target_os = "linux")]
+const NICE_BOOST: libc::c_int = -5;
+
+static ENABLED: AtomicBool = AtomicBool::new(true);
+static MMCSS: AtomicBool = AtomicBool::new(false);
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+static BOOSTS: Mutex<BTreeMap<u64, ThreadBoost>> = Mutex::new(BTreeMap::new());
+
+/// What one audio thread got.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ThreadBoost {
+    pub thread: &'static str,
+    pub applied: bool,
+    /// `above-normal`, `round-robin` or `nice`; `None` when nothing applied.
+    pub method: Option<&'static str>,
+    /// Registered with MMCSS as "Pro Audio"; Windows only.
+    pub mmcss: bool,
+    /// Why the boost or the MMCSS registration failed, or that it is turned off.
+    pub error: Option<String>,
+}
+
+/// Keeps the boost record, and on Windows the MMCSS registration, for as long
+/// as the thread holds it.
+pub struct BoostGuard {
+    id: u64,
+    #[cfg(windows)]
+    mmcss: Option<windows::Win32::Foundation::HANDLE>,
+}
+
+impl Drop for BoostGuard {
+    fn drop(&mut self) {
+        #[cfg(windows)]
+        if let Some(handle) = self.mmcss.take() {
+            let _ = unsafe {
+                windows::Win32::System::Threading::AvRevertMmThreadCharacteristics(handle)
+            };
+        }
+        BOOSTS.lock().unwrap().remove(&self.id);
+    }
+}
+
+/// Follows `audioPriorityBoost` and `audioMmcss`; threads started afterwards use it.
+pub fn apply_config(config: &AppConfig) {
+    ENABLED.store(config.audio_priority_boost, Ordering::Relaxed);
+    MMCSS.store(config.audio_mmcss, Ordering::Relaxed);
+}
+
+/// Boosts the calling thread. `mmcss` marks the thread that talks to WASAPI,
+/// which is also registered with MMCSS when the config asks for it.
+pub fn boost(thread: &'static str, mmcss: bool) -> BoostGuard {
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    let mut record = ThreadBoost {
+        thread,
+        applied: false,
+        method: None,
+        mmcss: false,
+        error: None,
+    };
+    #[cfg_attr(not(windows), allow(unused_mut))]
+    let mut guard = BoostGuard {
+        id,
+        #[cfg(windows)]
+        mmcss: None,
+    };
+    if !ENABLED.load(Ordering::Relaxed) {
+        record.error = Some("Turned off in settings".to_string());
+    } else {
+        match raise() {
+            Ok(method) => {
+                record.applied = true;
+                record.method = Some(method);
+            }
+            Err(error) => {
+                log::info!(target: "audio", "Priority boost for {thread} not applied: {error}");
+                record.error = Some(error);
+            }
+        }
+        #[cfg(windows)]
+        if mmcss && MMCSS.load(Ordering::Relaxed) {
+            match register_mmcss() {
+                Ok(handle) => {
+                    guard.mmcss = Some(handle);
+                    record.mmcss = true;
+                }
+                Err(error) => {
+                    log::info!(target: "audio", "MMCSS registration for {thread} failed: {error}");
+                    record.error.get_or_insert(error);
+                }
+            }
+        }
+        #[cfg(not(windows))]
+        let _ = mmcss;
+    }
+    BOOSTS.lock().unwrap().insert(id, record);
+    guard
+}
+
+/// The running audio threads, oldest first.
+pub fn current() -> Vec<ThreadBoost> {
+    BOOSTS.lock().unwrap().values().cloned().collect()
+}
+
+#[cfg(windows)]
+fn raise() -> Result<&'static str, String> {
+    use windows::Win32::System::Threading::{
+        GetCurrentThread, SetThreadPriority, THREAD_PRIORITY_ABOVE_NORMAL,
+    };
+    unsafe { SetThreadPriority(GetCurrentThread(), THREAD_PRIORITY_ABOVE_NORMAL) }
+        .map(|()| "above-normal")
+        .map_err(|error| error.to_string())
+}
+
+#[cfg(windows)]
+fn register_mmcss() -> Result<windows::Win32::Foundation::HANDLE, String> {
+    use windows::Win32::System::Threading::AvSetMmThreadCharacteristicsW;
+    let mut task_index = 0u32;
+    unsafe { AvSetMmThreadCharacteristicsW(windows::core::w!("Pro Audio"), &mut task_index) }
+        .map_err(|error| error.to_string())
+}
+
+#[cfg(unix)]
+fn raise() -> Result<&'static str, String> {
+    // SAFETY: only the calling thread's own scheduling is changed; `sched_param`
+    // is zeroed first because macOS has private padding in it.
+    unsafe {
+        let mut param: libc::sched_param = std::mem::zeroed();
+        param.sched_priority = libc::sched_get_priority_min(libc::SCHED_RR);
+        let code = libc::pthread_setschedparam(libc::pthread_self(), libc::SCHED_RR, &param);
+        if code == 0 {
+            return Ok("round-robin");
+        }
+        // Linux applies a nice value to the single thread its id names.
+        #[cfg(target_os = "linux")]
+        {
+            let tid = libc::syscall(libc::SYS_gettid) as libc::id_t;
+            if libc::setpriority(libc::PRIO_PROCESS, tid, NICE_BOOST) == 0 {
+                return Ok("nice");
+            }
+        }
+        Err(std::io::Error::from_raw_os_error(code).to_string())
+    }
+}
+
+#[cfg(not(any(windows, unix)))]
+fn raise() -> Result<&'static str, String> {
+    Err("Not supported on this platform".to_string())
+}
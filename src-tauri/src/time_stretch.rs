@@ -0,0 +1,222 @@
+//! Speeding up WAV payloads before transcription, which shortens what the
+//! provider processes and bills. Whisper-style models cope with 1.5x nearly as
+//! well as with real time.
+//!
+//! WSOLA keeps the pitch: 30 ms frames are read from the input `factor` times
+//! faster than they are written, each shifted by up to 10 ms to where it best
+//! continues the previous frame, and overlap-added with a Hann window. The
+//! cheaper alternative only relabels the sample rate, so the voice is pitched up
+//! by the same factor.
+
+use std::f32::consts::PI;
+
+use anyhow::Result;
+
+use crate::resample::{encode_wav, is_wav, parse_wav};
+
+const FRAME_MS: u32 = 30;
+const TOLERANCE_MS: u32 = 10;
+/// The similarity search reads every n-th sample; it only has to find the
+/// alignment to within a sample or two.
+const CORRELATION_STRIDE: usize = 2;
+
+/// Returns the payload played `factor` times faster, or `None` when the factor
+/// is 1 or the payload is not a WAV we can read, so callers send it unchanged.
+/// `relabel` picks the rate relabelling over WSOLA.
+pub fn speed_up_wav(bytes: &[u8], factor: f32, relabel: bool) -> Result<Option<Vec<u8>>> {
+    if factor <= 1.0 || !is_wav(bytes) {
+        return Ok(None);
+    }
+    let wav = parse_wav(bytes)?;
+    if relabel {
+        let rate = (wav.sample_rate as f32 * factor).round() as u32;
+        return Ok(Some(encode_wav(&wav.samples, wav.channels, rate)));
+    }
+    let samples = wsola(&wav.samples, wav.channels, wav.sample_rate, factor);
+    Ok(Some(encode_wav(&samples, wav.channels, wav.sample_rate)))
+}
+
+/// Time-compresses interleaved samples by `factor` without changing the pitch.
+/// The output holds `ceil(frames / factor)` frames.
+fn wsola(samples: &[i16], channels: usize, sample_rate: u32, factor: f32) -> Vec<i16> {
+    let channels = channels.max(1);
+    let frames = samples.len() / channels;
+    // Even, so two windows at half a frame apart sum to exactly one.
+    let frame_len = ((sample_rate * FRAME_MS / 1000) as usize).max(4) & !1;
+    let hop_out = frame_len / 2;
+    let tolerance = (sample_rate * TOLERANCE_MS / 1000) as usize;
+    let out_frames = (frames as f32 / factor).ceil() as usize;
+    if frames < frame_len {
+        return samples.to_vec();
+    }
+
+    // The similarity search runs on a mono mix. Both it and the input get a
+    // frame of silence at the end, so the last output frames have input to read.
+    let padded_frames = frames + frame_len + tolerance;
+    let mut mono: Vec<f32> = samples
+        .chunks_exact(channels)
+        .map(|frame| frame.iter().map(|&sample| sample as f32).sum::<f32>() / channels as f32)
+        .collect();
+    mono.resize(padded_frames, 0.0);
+    let sample_at = |frame: usize, channel: usize| -> f32 {
+        samples
+            .get(frame * channels + channel)
+            .map_or(0.0, |&sample| sample as f32)
+    };
+    let window: Vec<f32> = (0..frame_len)
+        .map(|i| 0.5 - 0.5 * (2.0 * PI * i as f32 / frame_len as f32).cos())
+        .collect();
+
+    let mut out = vec![0.0f32; (out_frames + frame_len) * channels];
+    let mut start = 0usize;
+    let mut k = 0usize;
+    while k * hop_out < out_frames {
+        if k > 0 {
+            let target = (k as f32 * hop_out as f32 * factor).round() as usize;
+            start = best_start(&mono, start + hop_out, target, tolerance, frame_len);
+        }
+        let out_at = k * hop_out;
+        for i in 0..frame_len {
+            // The first frame has no predecessor to fade in against.
+            let weight = if k == 0 && i < hop_out {
+                1.0
+            } else {
+                window[i]
+            };
+            for channel in 0..channels {
+                out[(out_at + i) * channels + channel] += sample_at(start + i, channel) * weight;
+            }
+        }
+        k += 1;
+    }
+    out.truncate(out_frames * channels);
+    out.into_iter()
+        .map(|sample| sample.round().clamp(i16::MIN as f32, i16::MAX as f32) as i16)
+        .collect()
+}
+
+/// The frame start within `tolerance` of `target` whose first half best
+/// matches the input that naturally follows the previous frame, at `natural`.
+fn best_start(
+    mono: &[f32],
+    natural: usize,
+    target: usize,
+    tolerance: usize,
+    frame_len: usize,
+) -> usize {
+    let last = mono.len().saturating_sub(frame_len);
+    let low = target.saturating_sub(tolerance).min(last);
+    let high = (target + tolerance).min(last);
+    let natural = natural.min(last);
+    let overlap = frame_len / 2;
+    let reference = &mono[natural..natural + overlap];
+    let mut best = (target.min(last), f32::MIN);
+    for candidate in low..=high {
+        let segment = &mono[candidate..candidate + overlap];
+        let (mut dot, mut energy) = (0.0f32, 0.0f32);
+        for i in (0..overlap).step_by(CORRELATION_STRIDE) {
+            dot += reference[i] * segment[i];
+            energy += segment[i] * segment[i];
+        }
+        let score = dot / energy.sqrt().max(1.0);
+        if score > best.1 {
+            best = (candidate, score);
+        }
+    }
+    best.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resample::wav_header;
+
+    const RATE: u32 = 16_000;
+
+    /// Two seconds of a gliding tone, roughly the pitch range of speech.
+    fn clip(channels: usize) -> Vec<u8> {
+        let frames = RATE as usize * 2;
+        let samples: Vec<i16> = (0..frames)
+            .flat_map(|frame| {
+                let t = frame as f32 / RATE as f32;
+                let sample = ((2.0 * PI * (150.0 + 100.0 * t) * t).sin() * 8000.0) as i16;
+                std::iter::repeat_n(sample, channels)
+            })
+            .collect();
+        encode_wav(&samples, channels, RATE)
+    }
+
+    fn u32_at(bytes: &[u8], offset: usize) -> u32 {
+        u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+    }
+
+    /// The header parses and its RIFF and data sizes match the bytes.
+    fn assert_valid(bytes: &[u8], channels: usize, sample_rate: u32) -> f64 {
+        let header = wav_header(bytes).unwrap();
+        assert_eq!(
+            (header.channels, header.sample_rate, header.bits),
+            (channels, sample_rate, 16)
+        );
+        assert_eq!(header.byte_rate, sample_rate * channels as u32 * 2);
+        assert_eq!(u32_at(bytes, 4) as usize, bytes.len() - 8);
+        let data_offset = header.data_offset as usize;
+        assert_eq!(&bytes[data_offset - 8..data_offset - 4], b"data");
+        assert_eq!(
+            u32_at(bytes, data_offset - 4) as usize,
+            bytes.len() - data_offset
+        );
+        assert_eq!(header.data_len as usize % (channels * 2), 0);
+        header.duration_seconds().unwrap()
+    }
+
+    #[test]
+    fn output_lasts_input_over_factor() {
+        for channels in [1, 2] {
+            let input = clip(channels);
+            let seconds = assert_valid(&input, channels, RATE);
+            for factor in [1.25, 1.6, 2.0] {
+                let wsola = speed_up_wav(&input, factor, false).unwrap().unwrap();
+                let duration = assert_valid(&wsola, channels, RATE);
+                assert!(
+                    (duration - seconds / factor as f64).abs() < 0.001,
+                    "wsola {channels}ch x{factor}: {duration}s"
+                );
+
+                let relabeled = speed_up_wav(&input, factor, true).unwrap().unwrap();
+                let rate = (RATE as f32 * factor).round() as u32;
+                let duration = assert_valid(&relabeled, channels, rate);
+                assert!(
+                    (duration - seconds / factor as f64).abs() < 0.001,
+                    "relabel {channels}ch x{factor}: {duration}s"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn leaves_real_time_and_foreign_payloads_alone() {
+        let input = clip(1);
+        assert_eq!(speed_up_wav(&input, 1.0, false).unwrap(), None);
+        assert_eq!(speed_up_wav(&input, 0.8, true).unwrap(), None);
+        assert_eq!(speed_up_wav(b"OggS not a wav", 1.5, false).unwrap(), None);
+    }
+
+    #[test]
+    fn wsola_keeps_the_level() {
+        let input = clip(1);
+        let output = speed_up_wav(&input, 1.6, false).unwrap().unwrap();
+        let peak = |bytes: &[u8]| {
+            parse_wav(bytes)
+                .unwrap()
+                .samples
+                .iter()
+                .map(|s| s.unsigned_abs())
+                .max()
+        };
+        let (before, after) = (peak(&input).unwrap(), peak(&output).unwrap());
+        assert!(
+            after > before * 3 / 4 && after <= before + before / 4,
+            "{before} -> {after}"
+        );
+    }
+}
@@ -0,0 +1,91 @@
+//! Plain-text transcript log (`plain_text_log_enabled`): one line per finished
+//! transcription in `transcripts-YYYY-MM-DD.txt`, so the text survives a crash.
+//! A new file starts every day; nothing is rewritten except by the one-off
+//! encryption of old logs, which pauses appends while it runs.
+
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Result};
+use chrono::Local;
+use tauri::{AppHandle, Manager};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::{Mutex, MutexGuard};
+
+use crate::artifacts::ArtifactVault;
+use crate::private_session;
+use crate::types::AppConfig;
+
+const TRANSCRIPTS_DIR_NAME: &str = "transcripts";
+
+/// Serializes appends so lines from concurrent transcriptions never interleave.
+pub struct TranscriptLog {
+    write_lock: Mutex<()>,
+}
+
+impl TranscriptLog {
+    pub fn new() -> Self {
+        Self {
+            write_lock: Mutex::new(()),
+        }
+    }
+
+    /// Appends `text` as one timestamped line when the log is enabled and no
+    /// private session is on.
+    pub async fn append(
+        &self,
+        app: &AppHandle,
+        config: &AppConfig,
+        mode: &str,
+        audio_seconds: f64,
+        text: &str,
+    ) -> Result<()> {
+        if !config.plain_text_log_enabled || private_session::is_active() || text.trim().is_empty()
+        {
+            return Ok(());
+        }
+        let now = Local::now();
+        let dir = directory(app, config)?;
+        let path = dir.join(format!("transcripts-{}.txt", now.format("%Y-%m-%d")));
+        // Keeps the one-line-per-transcript shape for multi-line answers.
+        let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        let mut line = format!(
+            "[{}] [{mode} {audio_seconds:.1}s] {text}",
+            now.format("%Y-%m-%d %H:%M:%S")
+        );
+        if config.encrypt_local_artifacts {
+            line = app
+                .state::<std::sync::Arc<ArtifactVault>>()
+                .seal_line(&line)
+                .await?;
+        }
+        line.push('\n');
+
+        let _guard = self.write_lock.lock().await;
+        tokio::fs::create_dir_all(&dir).await?;
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .await?;
+        file.write_all(line.as_bytes()).await?;
+        file.flush().await?;
+        Ok(())
+    }
+
+    /// Holds off appends until the guard is dropped.
+    pub async fn pause(&self) -> MutexGuard<'_, ()> {
+        self.write_lock.lock().await
+    }
+}
+
+/// The configured folder, or `transcripts` in the app data dir.
+pub fn directory(app: &AppHandle, config: &AppConfig) -> Result<PathBuf> {
+    if let Some(dir) = config.plain_text_log_dir.as_deref() {
+        return Ok(PathBuf::from(dir));
+    }
+    Ok(app
+        .path()
+        .app_local_data_dir()
+        .map_err(|error| anyhow!("Failed to resolve app data directory: {error}"))?
+        .join(TRANSCRIPTS_DIR_NAME))
+}
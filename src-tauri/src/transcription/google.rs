@@ -0,0 +1,274 @@
+use std::collections::HashSet;
+use std::sync::Mutex;
+
+use base64::Engine as _;
+use futures_util::future::BoxFuture;
+use reqwest::{Client, StatusCode};
+use serde_json::Value;
+
+use super::provider::{
+    check_status, reject_prompt_echo, transcript, ProviderContext, TranscriptionError,
+    TranscriptionProvider,
+};
+use super::{TranscriptionRequest, TranscriptionResponse};
+use crate::constants::GOOGLE_TRANSCRIPTION_MAX_BYTES;
+use crate::types::GoogleTranscriptionFormat;
+
+const MODELS_URL: &str = "https://generativelanguage.googleapis.com/v1beta/models";
+const DEFAULT_MODEL: &str = "gemini-2.0-flash-exp";
+const DEFAULT_INSTRUCTION: &str = "Transcribe verbatim in the original spoken language. Do not translate, summarise, or answer questions.";
+// Longest first-line lead-in that is still treated as a preamble, not speech.
+const MAX_PREAMBLE_CHARS: usize = 60;
+const PREAMBLE_OPENERS: &[&str] = &[
+    "here",
+    "sure",
+    "okay",
+    "ok,",
+    "below",
+    "the transcri",
+    "transcri",
+];
+
+/// Gemini, prompted to transcribe the inline audio.
+#[derive(Default)]
+pub struct GoogleProvider {
+    /// Models that rejected the JSON response schema; they get the text request
+    /// straight away for the rest of the run.
+    plain_text_models: Mutex<HashSet<String>>,
+}
+
+impl GoogleProvider {
+    async fn send(
+        &self,
+        client: &Client,
+        url: &str,
+        body: &Value,
+        ctx: &mut ProviderContext<'_>,
+    ) -> Result<reqwest::Response, TranscriptionError> {
+        let body = serde_json::to_vec(body).map_err(|error| {
+            TranscriptionError::Request(format!("Failed to encode request: {error}"))
+        })?;
+        let response = client
+            .post(url)
+            .header("Content-Type", "application/json")
+            .body(ctx.clock.body(body))
+            .send()
+            .await?;
+        ctx.headers_received(&response);
+        check_status(self.name(), response).await
+    }
+}
+
+impl TranscriptionProvider for GoogleProvider {
+    fn name(&self) -> &'static str {
+        "Google"
+    }
+
+    fn max_payload_bytes(&self) -> Option<usize> {
+        Some(GOOGLE_TRANSCRIPTION_MAX_BYTES)
+    }
+
+    fn transcribe<'a>(
+        &'a self,
+        request: TranscriptionRequest,
+        ctx: &'a mut ProviderContext<'_>,
+    ) -> BoxFuture<'a, Result<TranscriptionResponse, TranscriptionError>> {
+        Box::pin(async move {
+            let api_key = request
+                .api_key
+                .ok_or(TranscriptionError::MissingKey(self.name()))?;
+            let model = request.model.unwrap_or_else(|| DEFAULT_MODEL.to_string());
+            let client = ctx.upload_client()?;
+            let url = format!("{MODELS_URL}/{model}:generateContent?key={api_key}");
+
+            let audio_base64 =
+                base64::engine::general_purpose::STANDARD.encode(&request.audio_data);
+            let mut body = serde_json::json!({
+                "contents": [{
+                    "parts": [{
+                        "inline_data": {
+                            "mime_type": request.mime_type,
+                            "data": audio_base64
+                        }
+                    }]
+                }],
+                "systemInstruction": {
+                    "parts": [{
+                        "text": request.prompt.unwrap_or_else(|| DEFAULT_INSTRUCTION.to_string())
+                    }]
+                },
+                "generationConfig": {
+                    "temperature": 0.0
+                }
+            });
+            let json = ctx.config.google_transcription_format == GoogleTranscriptionFormat::Json
+                && !self.plain_text_models.lock().unwrap().contains(&model);
+            if json {
+                body["generationConfig"]["responseMimeType"] = "application/json".into();
+                body["generationConfig"]["responseSchema"] = serde_json::json!({
+                    "type": "OBJECT",
+                    "properties": { "text": { "type": "STRING" } },
+                    "required": ["text"]
+                });
+            }
+
+            let response = match self.send(&client, &url, &body, ctx).await {
+                Err(TranscriptionError::Status {
+                    status: StatusCode::BAD_REQUEST,
+                    body: error,
+                    ..
+                }) if json && rejects_schema(&error) => {
+                    log::info!(
+                        target: "transcription",
+                        "{model} rejected the JSON response schema, retrying as plain text"
+                    );
+                    self.plain_text_models.lock().unwrap().insert(model.clone());
+                    if let Some(config) = body["generationConfig"].as_object_mut() {
+                        config.remove("responseMimeType");
+                        config.remove("responseSchema");
+                    }
+                    self.send(&client, &url, &body, ctx).await?
+                }
+                result => result?,
+            };
+
+            let data: Value = response.json().await?;
+            let text = candidate_text(&data).ok_or_else(|| {
+                TranscriptionError::Response("No text in Google response".to_string())
+            })?;
+            Ok(transcript(reject_prompt_echo(parse_transcript(&text))?))
+        })
+    }
+}
+
+/// Whether a 400 body complains about JSON mode rather than the audio.
+fn rejects_schema(body: &str) -> bool {
+    let body = body.to_lowercase();
+    [
+        "response_schema",
+        "responseschema",
+        "response_mime_type",
+        "responsemimetype",
+        "json mode",
+    ]
+    .iter()
+    .any(|marker| body.contains(marker))
+}
+
+/// The text parts of the first candidate, joined; long answers can be split.
+fn candidate_text(data: &Value) -> Option<String> {
+    let parts = data.pointer("/candidates/0/content/parts")?.as_array()?;
+    let texts: Vec<&str> = parts
+        .iter()
+        .filter_map(|part| part.get("text").and_then(Value::as_str))
+        .collect();
+    (!texts.is_empty()).then(|| texts.concat())
+}
+
+/// Pulls the transcript out of what Gemini returned: the `text` field in JSON
+/// mode, otherwise the reply minus code fences and a "Here is the
+/// transcription:" lead-in. Models in text mode sometimes answer with the JSON
+/// object anyway, so both shapes are accepted either way.
+fn parse_transcript(raw: &str) -> String {
+    // The fence can sit on either side of the lead-in.
+    let text = strip_code_fence(strip_preamble(strip_code_fence(raw.trim())));
+    if let Ok(value) = serde_json::from_str::<Value>(text) {
+        if let Some(inner) = value.get("text").and_then(Value::as_str) {
+            return inner.trim().to_string();
+        }
+    }
+    text.to_string()
+}
+
+fn strip_code_fence(text: &str) -> &str {
+    let Some(rest) = text.strip_prefix("```") else {
+        return text;
+    };
+    // The info string (`json`, `text`) runs to the end of the opening line.
+    let body = match rest.split_once('\n') {
+        Some((_, body)) => body,
+        None => rest.trim_start_matches(|c: char| c.is_ascii_alphanumeric()),
+    };
+    let body = body.trim_end();
+    body.strip_suffix("```").unwrap_or(body).trim()
+}
+
+fn strip_preamble(text: &str) -> &str {
+    let first_line = text.lines().next().unwrap_or_default();
+    let Some(colon) = first_line.find(':') else {
+        return text;
+    };
+    let lead = first_line[..colon].trim().to_lowercase();
+    let is_preamble = lead.len() <= MAX_PREAMBLE_CHARS
+        && lead.contains("transcri")
+        && PREAMBLE_OPENERS
+            .iter()
+            .any(|opener| lead.starts_with(opener));
+    if is_preamble {
+        text[colon + 1..].trim()
+    } else {
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn messy_replies_reduce_to_the_transcript() {
+        for (raw, expected) in [
+            ("Hello there.", "Hello there."),
+            ("  Hello there.\n", "Hello there."),
+            (r#"{"text": " Hello there. "}"#, "Hello there."),
+            ("```json\n{\"text\": \"Hello there.\"}\n```", "Hello there."),
+            ("```\nHello there.\n```", "Hello there."),
+            ("```text\nHello there.```", "Hello there."),
+            ("Here is the transcription:\nHello there.", "Hello there."),
+            ("Sure! Here's the transcript: Hello there.", "Hello there."),
+            ("Transcription:\n\nHello there.", "Hello there."),
+            (
+                "Here is the transcription:\n```\nHello there.\n```",
+                "Hello there.",
+            ),
+            (
+                "```\nHere is the transcription: Hello there.\n```",
+                "Hello there.",
+            ),
+            (
+                "Okay, the transcription is below:\n{\"text\": \"Hello there.\"}",
+                "Hello there.",
+            ),
+            ("Привет, как дела?", "Привет, как дела?"),
+        ] {
+            assert_eq!(parse_transcript(raw), expected, "{raw:?}");
+        }
+    }
+
+    #[test]
+    fn speech_that_looks_like_a_lead_in_is_kept() {
+        for raw in [
+            "Note: the meeting moved to Friday.",
+            "Here we go: first item on the agenda.",
+            "We discussed the transcription budget: it is too high.",
+            "Here is what the transcription service charged us last month in total: ten dollars.",
+            r#"{"answer": "not a transcript"}"#,
+        ] {
+            assert_eq!(parse_transcript(raw), raw, "{raw:?}");
+        }
+    }
+
+    #[test]
+    fn split_candidate_parts_are_joined() {
+        let data = json!({
+            "candidates": [{
+                "content": { "parts": [{ "text": "{\"text\": \"Hel" }, { "text": "lo\"}" }] }
+            }]
+        });
+        let text = candidate_text(&data).unwrap();
+        assert_eq!(parse_transcript(&text), "Hello");
+        assert_eq!(candidate_text(&json!({ "candidates": [] })), None);
+    }
+}
@@ -0,0 +1,233 @@
+//! Finds the spoken language before the real transcription, so the matching
+//! `languageProfiles` entry can pick the prompt and model. Only the first
+//! seconds of the clip are looked at: either the local server's tiny Whisper
+//! model reports the language, or the clip's provider transcribes them as a
+//! draft and `whatlang` reads the text.
+
+use std::sync::Arc;
+use std::time::Instant;
+
+use anyhow::{anyhow, Context, Result};
+use reqwest::multipart;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use super::provider::TranscriptionProvider;
+use super::{run_provider, RequestClock, TranscriptionRequest};
+use crate::http::HttpClients;
+use crate::local_speech::FastWhisperManager;
+use crate::rate_limit::RateLimiter;
+use crate::resample::{encode_wav, parse_wav};
+use crate::types::{AppConfig, LanguageDetection};
+use crate::usage::{self, UsageEvent, UsageStore};
+
+const DETECTION_SECONDS: usize = 5;
+const WHISPER_MODEL: &str = "tiny";
+
+/// Whisper reports full names where the server follows OpenAI; the rest are
+/// already ISO 639-1 codes. Paired with the ISO 639-3 codes `whatlang` uses.
+const LANGUAGES: &[(&str, &str, &str)] = &[
+    ("en", "eng", "english"),
+    ("ru", "rus", "russian"),
+    ("uk", "ukr", "ukrainian"),
+    ("be", "bel", "belarusian"),
+    ("kk", "kaz", "kazakh"),
+    ("de", "deu", "german"),
+    ("fr", "fra", "french"),
+    ("es", "spa", "spanish"),
+    ("it", "ita", "italian"),
+    ("pt", "por", "portuguese"),
+    ("pl", "pol", "polish"),
+    ("nl", "nld", "dutch"),
+    ("tr", "tur", "turkish"),
+    ("zh", "cmn", "chinese"),
+    ("ja", "jpn", "japanese"),
+    ("ko", "kor", "korean"),
+];
+
+/// `TranscriptionResponse.language`: what detection found and whether a
+/// profile was applied for it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DetectedLanguage {
+    /// ISO 639-1 code.
+    pub language: String,
+    /// 0 to 1, where the detector reports one.
+    pub confidence: Option<f64>,
+    /// `whisper` or `draft`.
+    pub method: String,
+    pub profile_applied: bool,
+    pub elapsed_ms: u64,
+}
+
+/// Runs the detection `languageDetection` asks for. `None` when it is off, the
+/// language is already known, or nothing was found; a failed detection is only
+/// logged and the clip is transcribed as configured.
+pub(super) async fn detect(
+    app: &AppHandle,
+    config: &AppConfig,
+    backend: &dyn TranscriptionProvider,
+    request: &TranscriptionRequest,
+    quota_key: &str,
+) -> Option<DetectedLanguage> {
+    if request.language.is_some() {
+        return None;
+    }
+    let started = Instant::now();
+    let method = config.language_detection;
+    let detected = match method {
+        LanguageDetection::Whisper => detect_with_whisper(app, request).await,
+        LanguageDetection::Draft => {
+            detect_from_draft(app, config, backend, request, quota_key).await
+        }
+        LanguageDetection::Off | LanguageDetection::Unknown => return None,
+    };
+    match detected {
+        Ok(Some((language, confidence))) => {
+            let elapsed_ms = started.elapsed().as_millis() as u64;
+            log::info!(
+                target: "transcription",
+                "Detected language {language} ({method}, confidence {confidence:?}) in {elapsed_ms} ms"
+            );
+            Some(DetectedLanguage {
+                language,
+                confidence,
+                method: method.as_str().to_string(),
+                profile_applied: false,
+                elapsed_ms,
+            })
+        }
+        Ok(None) => {
+            log::info!(target: "transcription", "No language detected ({method})");
+            None
+        }
+        Err(error) => {
+            log::warn!(target: "transcription", "Language detection ({method}) failed: {error:#}");
+            None
+        }
+    }
+}
+
+/// The first `DETECTION_SECONDS` of a WAV payload; other formats are used whole.
+fn head(audio: &[u8]) -> Vec<u8> {
+    let Ok(wav) = parse_wav(audio) else {
+        return audio.to_vec();
+    };
+    let keep = DETECTION_SECONDS * wav.sample_rate as usize * wav.channels.max(1);
+    if wav.samples.len() <= keep {
+        return audio.to_vec();
+    }
+    encode_wav(&wav.samples[..keep], wav.channels, wav.sample_rate)
+}
+
+async fn detect_with_whisper(
+    app: &AppHandle,
+    request: &TranscriptionRequest,
+) -> Result<Option<(String, Option<f64>)>> {
+    let api = app
+        .state::<Arc<FastWhisperManager>>()
+        .server_api(app)
+        .await?;
+    let file = multipart::Part::bytes(head(&request.audio_data))
+        .file_name(request.filename.clone())
+        .mime_str(&request.mime_type)?;
+    let form = multipart::Form::new()
+        .text(api.model_field, WHISPER_MODEL)
+        .text("response_format", "verbose_json")
+        .part(api.file_field, file);
+    let response = app
+        .state::<Arc<HttpClients>>()
+        .local()
+        .post(FastWhisperManager::server_url(api.transcriptions_path))
+        .multipart(form)
+        .send()
+        .await
+        .context("local Whisper server is not reachable")?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(anyhow!("local Whisper server answered {status}"));
+    }
+    let data: serde_json::Value = response.json().await?;
+    let language = data
+        .get("language")
+        .and_then(|value| value.as_str())
+        .and_then(language_code);
+    let confidence = data
+        .get("language_probability")
+        .and_then(|value| value.as_f64());
+    Ok(language.map(|language| (language, confidence)))
+}
+
+/// Transcribes the head of the clip with its own provider and guesses the
+/// language from the text. The draft counts towards the rate limit and usage.
+async fn detect_from_draft(
+    app: &AppHandle,
+    config: &AppConfig,
+    backend: &dyn TranscriptionProvider,
+    request: &TranscriptionRequest,
+    quota_key: &str,
+) -> Result<Option<(String, Option<f64>)>> {
+    let audio_data = head(&request.audio_data);
+    let audio_seconds = usage::wav_duration_seconds(&audio_data).unwrap_or(0.0);
+    let draft = TranscriptionRequest {
+        mode: request.mode,
+        model: request.model.clone(),
+        api_key: request.api_key.clone(),
+        audio_data,
+        mime_type: request.mime_type.clone(),
+        filename: request.filename.clone(),
+        prompt: None,
+        interaction_id: None,
+        language: None,
+    };
+    app.state::<Arc<RateLimiter>>()
+        .acquire(app, config, quota_key, "transcription")
+        .await;
+    let mut clock = RequestClock::new();
+    let result = run_provider(app, config, backend, draft, quota_key, &mut clock).await;
+    if let Some(usage) = app.try_state::<Arc<UsageStore>>() {
+        usage.record(UsageEvent::Transcription {
+            provider: quota_key.to_string(),
+            audio_seconds,
+            latency_ms: clock.finish().total_ms,
+            ok: result.is_ok(),
+        });
+    }
+    let text = result.map_err(|failure| anyhow!("{}", failure.error))?.text;
+    Ok(guess_from_text(&text).map(|(language, confidence)| (language, Some(confidence))))
+}
+
+/// ISO 639-1 code and confidence `whatlang` gives `text`, for the languages
+/// it shares with `LANGUAGES`.
+pub(super) fn guess_from_text(text: &str) -> Option<(String, f64)> {
+    let info = whatlang::detect(text)?;
+    LANGUAGES
+        .iter()
+        .find(|(_, iso3, _)| *iso3 == info.lang().code())
+        .map(|(code, _, _)| (code.to_string(), info.confidence()))
+}
+
+/// English name of an ISO 639-1 code in `LANGUAGES`.
+pub(super) fn language_name(code: &str) -> Option<&'static str> {
+    LANGUAGES
+        .iter()
+        .find(|(iso1, _, _)| *iso1 == code)
+        .map(|(_, _, name)| *name)
+}
+
+/// ISO 639-1 code for a code or an English name, as Whisper reports them and
+/// as `translateTo` may be written. Region subtags (`pt-BR`) are dropped.
+pub(super) fn language_code(reported: &str) -> Option<String> {
+    let reported = reported.trim().to_lowercase();
+    if reported.is_empty() {
+        return None;
+    }
+    let primary = reported.split(['-', '_']).next().unwrap_or_default();
+    if primary.len() == 2 {
+        return Some(primary.to_string());
+    }
+    LANGUAGES
+        .iter()
+        .find(|(_, _, name)| *name == reported)
+        .map(|(code, _, _)| code.to_string())
+}
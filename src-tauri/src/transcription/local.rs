@@ -0,0 +1,77 @@
+use std::sync::Arc;
+
+use futures_util::future::BoxFuture;
+use reqwest::multipart;
+use tauri::Manager;
+
+use super::provider::{
+    audio_part, check_status, reject_prompt_echo, text_field, transcript, ProviderContext,
+    TranscriptionError, TranscriptionProvider, WHISPER_PROMPT_TOKENS,
+};
+use super::{TranscriptionRequest, TranscriptionResponse};
+use crate::local_speech::FastWhisperManager;
+use crate::types::AppConfig;
+
+const DEFAULT_MODEL: &str = "large-v3";
+
+/// The bundled FastWhisper server.
+pub struct LocalProvider;
+
+impl TranscriptionProvider for LocalProvider {
+    fn name(&self) -> &'static str {
+        "Local Whisper"
+    }
+
+    fn max_payload_bytes(&self) -> Option<usize> {
+        None
+    }
+
+    fn max_prompt_tokens(&self) -> Option<usize> {
+        Some(WHISPER_PROMPT_TOKENS)
+    }
+
+    fn sends_prompt(&self, config: &AppConfig) -> bool {
+        config.send_prompt_to_local
+    }
+
+    fn transcribe<'a>(
+        &'a self,
+        request: TranscriptionRequest,
+        ctx: &'a mut ProviderContext<'_>,
+    ) -> BoxFuture<'a, Result<TranscriptionResponse, TranscriptionError>> {
+        Box::pin(async move {
+            let model = request.model.unwrap_or_else(|| DEFAULT_MODEL.to_string());
+            let client = ctx.local_client();
+            let api = ctx
+                .app
+                .state::<Arc<FastWhisperManager>>()
+                .server_api(ctx.app)
+                .await?;
+
+            let file = audio_part(
+                ctx.clock,
+                request.audio_data,
+                request.filename,
+                &request.mime_type,
+            )?;
+            let mut form = multipart::Form::new().text(api.model_field, model);
+            if let Some(prompt) = request.prompt {
+                form = form.text(api.prompt_field, prompt);
+            }
+            if let Some(language) = request.language {
+                form = form.text(api.language_field, language);
+            }
+            let form = form.part(api.file_field, file);
+
+            let response = client
+                .post(FastWhisperManager::server_url(api.transcriptions_path))
+                .multipart(form)
+                .send()
+                .await?;
+            ctx.headers_received(&response);
+            let response = check_status(self.name(), response).await?;
+            let text = reject_prompt_echo(text_field(response).await?)?;
+            Ok(transcript(text))
+        })
+    }
+}
@@ -0,0 +1,172 @@
+//! Offline stand-in for the real providers, for tests and frontend work without
+//! network. Only reachable when `AppConfig::mock_providers_enabled` is true.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use anyhow::anyhow;
+use futures_util::future::BoxFuture;
+use reqwest::StatusCode;
+
+use super::provider::{transcript, ProviderContext, TranscriptionError, TranscriptionProvider};
+use super::{TranscriptionRequest, TranscriptionResponse};
+use crate::constants::OPENAI_TRANSCRIPTION_MAX_BYTES;
+use crate::types::MockProviderSettings;
+use crate::usage;
+
+#[derive(Default)]
+pub struct MockProvider {
+    requests: AtomicU64,
+}
+
+impl TranscriptionProvider for MockProvider {
+    fn name(&self) -> &'static str {
+        "Mock"
+    }
+
+    // The OpenAI ceiling, so payload validation behaves as it does for real uploads.
+    fn max_payload_bytes(&self) -> Option<usize> {
+        Some(OPENAI_TRANSCRIPTION_MAX_BYTES)
+    }
+
+    fn transcribe<'a>(
+        &'a self,
+        request: TranscriptionRequest,
+        ctx: &'a mut ProviderContext<'_>,
+    ) -> BoxFuture<'a, Result<TranscriptionResponse, TranscriptionError>> {
+        Box::pin(async move {
+            let settings = ctx.config.mock_providers.clone();
+            let number = self.requests.fetch_add(1, Ordering::SeqCst) + 1;
+            if settings.latency_ms > 0 {
+                tokio::time::sleep(Duration::from_millis(settings.latency_ms)).await;
+            }
+            answer(number, &request, &settings)
+        })
+    }
+}
+
+/// What request `number` gets once its latency has passed.
+fn answer(
+    number: u64,
+    request: &TranscriptionRequest,
+    settings: &MockProviderSettings,
+) -> Result<TranscriptionResponse, TranscriptionError> {
+    if should_fail(number, settings.failure_percent) {
+        return Err(injected_failure(settings.failure_status));
+    }
+    let seconds = usage::wav_duration_seconds(&request.audio_data).unwrap_or(0.0);
+    Ok(transcript(format!(
+        "Mock transcript {number}: {seconds:.1} s of audio, {} bytes.",
+        request.audio_data.len()
+    )))
+}
+
+/// Spreads failures evenly instead of drawing them at random: at 25 % every fourth
+/// request fails, so a test run is repeatable.
+fn should_fail(number: u64, percent: u8) -> bool {
+    let percent = u64::from(percent.min(100));
+    number * percent / 100 != (number - 1) * percent / 100
+}
+
+fn injected_failure(status: Option<u16>) -> TranscriptionError {
+    match status.and_then(|code| StatusCode::from_u16(code).ok()) {
+        Some(status) => TranscriptionError::Status {
+            provider: "Mock",
+            status,
+            body: "Injected failure".to_string(),
+        },
+        None => TranscriptionError::Transport(anyhow!("Injected network failure")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{check_payload_size, provider_error};
+    use super::*;
+    use crate::errors::ErrorCode;
+    use crate::resample::encode_wav;
+    use crate::types::TranscriptionMode;
+
+    fn request(audio_data: Vec<u8>) -> TranscriptionRequest {
+        TranscriptionRequest {
+            mode: TranscriptionMode::Mock,
+            model: None,
+            api_key: None,
+            audio_data,
+            mime_type: "audio/wav".into(),
+            filename: "clip.wav".into(),
+            prompt: None,
+            interaction_id: None,
+            language: None,
+        }
+    }
+
+    fn failing(failure_status: Option<u16>) -> MockProviderSettings {
+        MockProviderSettings {
+            failure_percent: 50,
+            failure_status,
+            ..MockProviderSettings::default()
+        }
+    }
+
+    #[test]
+    fn answers_with_a_transcript_of_the_clip() {
+        let request = request(encode_wav(&[0; 16_000], 1, 16_000));
+        check_payload_size(&MockProvider::default(), &request).unwrap();
+        let response = answer(3, &request, &MockProviderSettings::default()).unwrap();
+        assert_eq!(
+            response.text,
+            format!(
+                "Mock transcript 3: 1.0 s of audio, {} bytes.",
+                request.audio_data.len()
+            )
+        );
+    }
+
+    #[test]
+    fn fails_the_configured_share_with_the_configured_status() {
+        let request = request(encode_wav(&[0; 160], 1, 16_000));
+        let failed: Vec<u64> = (1..=6)
+            .filter(|&number| answer(number, &request, &failing(Some(429))).is_err())
+            .collect();
+        assert_eq!(failed, [2, 4, 6]);
+
+        let error = answer(2, &request, &failing(Some(429))).unwrap_err();
+        assert!(matches!(
+            error,
+            TranscriptionError::Status { provider: "Mock", status, .. }
+                if status == StatusCode::TOO_MANY_REQUESTS
+        ));
+        let error = provider_error(error);
+        assert_eq!(error.code, ErrorCode::TranscriptionFailed);
+        assert_eq!(
+            error.detail.as_deref(),
+            Some("Mock error: 429 Too Many Requests - Injected failure")
+        );
+    }
+
+    #[test]
+    fn fails_as_a_network_error_without_a_valid_status() {
+        let request = request(encode_wav(&[0; 160], 1, 16_000));
+        for status in [None, Some(1000)] {
+            let error = answer(2, &request, &failing(status)).unwrap_err();
+            assert!(matches!(error, TranscriptionError::Transport(_)));
+            let error = provider_error(error);
+            assert_eq!(error.code, ErrorCode::TranscriptionFailed);
+            assert_eq!(error.detail.as_deref(), Some("Injected network failure"));
+        }
+    }
+
+    #[test]
+    fn refuses_clips_over_the_upload_limit() {
+        let provider = MockProvider::default();
+        check_payload_size(&provider, &request(vec![0; OPENAI_TRANSCRIPTION_MAX_BYTES])).unwrap();
+        let error = check_payload_size(
+            &provider,
+            &request(vec![0; OPENAI_TRANSCRIPTION_MAX_BYTES + 1]),
+        )
+        .unwrap_err();
+        assert_eq!(error.code, ErrorCode::PayloadTooLarge);
+        assert!(error.message.contains("Mock"), "{}", error.message);
+    }
+}
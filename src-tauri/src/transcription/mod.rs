@@ -0,0 +1,871 @@
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, State};
+use tokio::fs;
+use tokio::sync::Semaphore;
+use chrono::Local;
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex, OnceLock};
+use crate::artifacts::{self, ArtifactVault};
+use crate::config::ConfigState;
+use crate::constants::{MIN_STT_SPEEDUP_FACTOR, STT_SPEEDUP_QUALITY_LIMIT};
+use crate::errors::{CommandError, CommandResult, ErrorCode};
+use crate::events::{self, emit_event};
+use crate::hotkeys;
+use crate::http::HttpClients;
+use crate::integration;
+use crate::perf;
+use crate::private_session;
+use crate::provider_quota::ProviderQuotaState;
+use crate::rate_limit::RateLimiter;
+use crate::resample;
+use crate::session::{self, SessionEntryKind, SessionLog};
+use crate::sounds;
+use crate::time_stretch;
+use crate::transcript_log::TranscriptLog;
+use crate::tray::LastResult;
+use crate::types::{AppConfig, TranscriptionMode};
+use crate::usage::{self, UsageEvent, UsageStore};
+
+mod google;
+mod language;
+mod local;
+mod mock;
+mod openai;
+mod provider;
+mod translate;
+
+use language::DetectedLanguage;
+use provider::{
+    prepare_prompt, ProviderContext, ProviderRegistry, TranscriptionError, TranscriptionProvider,
+};
+
+/// The snake_case aliases keep callers written before the camelCase rename working.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TranscriptionRequest {
+    pub mode: TranscriptionMode,
+    pub model: Option<String>,
+    #[serde(alias = "api_key")]
+    pub api_key: Option<String>,
+    #[serde(alias = "audio_data")]
+    pub audio_data: Vec<u8>,
+    #[serde(alias = "mime_type")]
+    pub mime_type: String,
+    pub filename: String,
+    pub prompt: Option<String>,
+    /// Id from the hotkey event that cut this clip, if any.
+    #[serde(default, alias = "interaction_id")]
+    pub interaction_id: Option<String>,
+    /// Spoken language as an ISO 639-1 code; `transcriptionLanguage` or the
+    /// detected one when unset.
+    #[serde(default)]
+    pub language: Option<String>,
+}
+
+// Server-side processing time OpenAI reports on every API response.
+const OPENAI_PROCESSING_MS_HEADER: &str = "openai-processing-ms";
+const UPLOAD_CHUNK_BYTES: usize = 64 * 1024;
+// Clips processed at once; the rest wait their turn, batch items included.
+const MAX_CONCURRENT_TRANSCRIPTIONS: usize = 2;
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TranscriptionResponse {
+    pub text: String,
+    #[serde(default)]
+    pub timings: TranscriptionTimings,
+    /// Session interaction the transcript was filed under.
+    #[serde(default, alias = "interaction_id")]
+    pub interaction_id: Option<String>,
+    /// Speed-up applied before upload; provider timestamps times this are real time.
+    #[serde(default)]
+    pub speedup_factor: Option<f32>,
+    /// What language detection found; `None` when it did not run.
+    #[serde(default)]
+    pub language: Option<DetectedLanguage>,
+    /// `text` in `translateTo`, when translation is on and the text needed it.
+    #[serde(default)]
+    pub translated_text: Option<String>,
+    /// The translation was attempted and failed; `text` is still good.
+    #[serde(default)]
+    pub translation_failed: bool,
+}
+
+/// `transcription:speedup-quality` payload.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SpeedupQuality {
+    factor: f32,
+    limit: f32,
+}
+
+/// Where the time of one transcription went, in milliseconds.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TranscriptionTimings {
+    /// From the command call until the request is sent: debug copy, encoding, form.
+    #[serde(alias = "prepare_ms")]
+    pub prepare_ms: u64,
+    /// Until the last byte of the body was handed to the connection.
+    #[serde(alias = "upload_ms")]
+    pub upload_ms: u64,
+    /// Provider processing up to the response headers.
+    #[serde(alias = "provider_ms")]
+    pub provider_ms: u64,
+    #[serde(alias = "total_ms")]
+    pub total_ms: u64,
+}
+
+/// Timestamps collected across one provider request.
+struct RequestClock {
+    started: Instant,
+    sent: Option<Instant>,
+    uploaded: Arc<OnceLock<Instant>>,
+    first_byte: Option<Instant>,
+    reported_provider_ms: Option<u64>,
+}
+
+impl RequestClock {
+    fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            sent: None,
+            uploaded: Arc::new(OnceLock::new()),
+            first_byte: None,
+            reported_provider_ms: None,
+        }
+    }
+
+    /// Wraps the upload so the clock sees when its last chunk leaves.
+    fn body(&mut self, bytes: Vec<u8>) -> reqwest::Body {
+        let uploaded = self.uploaded.clone();
+        let total = bytes.len();
+        let chunks: Vec<Vec<u8>> = bytes
+            .chunks(UPLOAD_CHUNK_BYTES)
+            .map(<[u8]>::to_vec)
+            .collect();
+        let stream = futures_util::stream::iter(chunks.into_iter().scan(0, move |sent, chunk| {
+            *sent += chunk.len();
+            if *sent >= total {
+                let _ = uploaded.set(Instant::now());
+            }
+            Some(Ok::<_, std::io::Error>(chunk))
+        }));
+        self.sent = Some(Instant::now());
+        reqwest::Body::wrap_stream(stream)
+    }
+
+    fn headers_received(&mut self, response: &reqwest::Response) {
+        self.first_byte = Some(Instant::now());
+        self.reported_provider_ms = response
+            .headers()
+            .get(OPENAI_PROCESSING_MS_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.trim().parse().ok());
+    }
+
+    fn finish(&self) -> TranscriptionTimings {
+        let now = Instant::now();
+        let sent = self.sent.unwrap_or(now);
+        let first_byte = self.first_byte.unwrap_or(now);
+        let uploaded = self
+            .uploaded
+            .get()
+            .copied()
+            .unwrap_or(sent)
+            .clamp(sent, first_byte.max(sent));
+        let ms = |duration: Duration| duration.as_millis() as u64;
+        TranscriptionTimings {
+            prepare_ms: ms(sent - self.started),
+            upload_ms: ms(uploaded - sent),
+            provider_ms: self
+                .reported_provider_ms
+                .unwrap_or_else(|| ms(first_byte.saturating_duration_since(uploaded))),
+            total_ms: ms(now - self.started),
+        }
+    }
+}
+
+/// Holds the providers, limits how many clips run at once and counts requests that
+/// are still waiting on one.
+pub struct TranscriptionManager {
+    in_flight: AtomicUsize,
+    providers: ProviderRegistry,
+    queue: Semaphore,
+    // Provider and prompt pairs already reported as truncated.
+    truncation_warned: Mutex<HashSet<(&'static str, String)>>,
+    // Speed-up factor last reported as past the quality limit.
+    speedup_warned: Mutex<Option<f32>>,
+}
+
+impl TranscriptionManager {
+    pub fn new() -> Self {
+        Self {
+            in_flight: AtomicUsize::new(0),
+            providers: ProviderRegistry::with_defaults(),
+            queue: Semaphore::new(MAX_CONCURRENT_TRANSCRIPTIONS),
+            truncation_warned: Mutex::new(HashSet::new()),
+            speedup_warned: Mutex::new(None),
+        }
+    }
+
+    pub fn is_transcribing(&self) -> bool {
+        self.in_flight.load(Ordering::SeqCst) > 0
+    }
+
+    fn begin(self: &Arc<Self>) -> InFlightGuard {
+        self.in_flight.fetch_add(1, Ordering::SeqCst);
+        InFlightGuard(self.clone())
+    }
+
+    /// Reports a factor past the quality limit once, until the factor changes.
+    fn warn_speedup_quality(&self, app: &AppHandle, factor: f32) {
+        if self.speedup_warned.lock().unwrap().replace(factor) == Some(factor) {
+            return;
+        }
+        log::warn!(
+            target: "transcription",
+            "Speed-up {factor}x is above {STT_SPEEDUP_QUALITY_LIMIT}x; expect missed words"
+        );
+        let _ = emit_event(
+            app,
+            events::TRANSCRIPTION_SPEEDUP_QUALITY,
+            SpeedupQuality {
+                factor,
+                limit: STT_SPEEDUP_QUALITY_LIMIT,
+            },
+        );
+    }
+}
+
+struct InFlightGuard(Arc<TranscriptionManager>);
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        if self.0.in_flight.fetch_sub(1, Ordering::SeqCst) == 1 {
+            hotkeys::notify_transcription_idle();
+        }
+    }
+}
+
+async fn save_audio_debug(
+    app: &AppHandle,
+    config: &AppConfig,
+    audio_data: &[u8],
+    mode: &str,
+    filename: &str,
+) {
+    if !config.save_recorder_files || private_session::is_active() {
+        return;
+    }
+    
+    if let Ok(debug_dir) = artifacts::debug_audio_dir(app) {
+        if let Err(_) = fs::create_dir_all(&debug_dir).await {
+            return;
+        }
+        
+        let timestamp = Local::now().format("%Y%m%d_%H%M%S_%3f");
+        let debug_filename = format!("{}_{}_{}", timestamp, mode, filename);
+        let debug_path = debug_dir.join(&debug_filename);
+        let sealed;
+        let contents = if config.encrypt_local_artifacts {
+            match app.state::<Arc<ArtifactVault>>().seal(audio_data).await {
+                Ok(data) => {
+                    sealed = data;
+                    &sealed[..]
+                }
+                Err(error) => {
+                    log::warn!(target: "transcription", "Audio file not saved, encryption failed: {error:#}");
+                    return;
+                }
+            }
+        } else {
+            audio_data
+        };
+        
+        if let Err(e) = fs::write(&debug_path, contents).await {
+            log::warn!(target: "transcription", "Failed to save audio file: {}", e);
+        } else {
+            let path_str = debug_path.to_string_lossy().to_string();
+            log::info!(target: "transcription", "Saved audio file: {} ({} bytes)", 
+                debug_path.display(), audio_data.len());
+            // Emit to frontend DevTools
+            let _ = emit_event(app, events::TRANSCRIPTION_DEBUG_SAVED, serde_json::json!({
+                "path": path_str,
+                "size": audio_data.len(),
+                "mode": mode,
+                "filename": filename
+            }));
+        }
+    }
+}
+
+/// Rejects payloads the provider would refuse, before spending time on the upload.
+fn check_payload_size(
+    provider: &dyn TranscriptionProvider,
+    request: &TranscriptionRequest,
+) -> CommandResult<()> {
+    let size = request.audio_data.len();
+    let Some(limit) = provider.max_payload_bytes().filter(|limit| size > *limit) else {
+        return Ok(());
+    };
+    let provider = provider.name();
+    log::warn!(
+        target: "transcription",
+        "Payload too large for {provider}: size={size} limit={limit}"
+    );
+    Err(CommandError::bare(
+        ErrorCode::PayloadTooLarge,
+        &[
+            ("provider", provider),
+            ("size", &format_megabytes(size)),
+            ("limit", &format_megabytes(limit)),
+        ],
+    )
+    .with_data(serde_json::json!({ "size": size, "limit": limit, "provider": provider })))
+}
+
+fn format_megabytes(bytes: usize) -> String {
+    format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+}
+
+/// Resamples WAV payloads off the async runtime; anything it cannot read is sent as is.
+async fn downsample_for_transcription(audio: Vec<u8>, target_rate: u32) -> Result<Vec<u8>> {
+    tauri::async_runtime::spawn_blocking(move || {
+        match resample::downsample_wav(&audio, target_rate) {
+            Ok(Some(converted)) => {
+                log::debug!(
+                    target: "transcription",
+                    "Downsampled audio to {target_rate} Hz: {} -> {} bytes",
+                    audio.len(),
+                    converted.len()
+                );
+                converted
+            }
+            Ok(None) => audio,
+            Err(error) => {
+                log::warn!(target: "transcription", "Sending audio at its original rate: {error}");
+                audio
+            }
+        }
+    })
+    .await
+    .map_err(|error| anyhow!("Audio resampling task failed: {error}"))
+}
+
+/// Applies `sttSpeedupFactor` off the async runtime and returns the factor when
+/// the payload was sped up; anything it cannot read is sent at real speed.
+async fn speed_up_for_transcription(
+    audio: Vec<u8>,
+    factor: f32,
+    relabel: bool,
+) -> Result<(Vec<u8>, Option<f32>)> {
+    if factor <= MIN_STT_SPEEDUP_FACTOR {
+        return Ok((audio, None));
+    }
+    tauri::async_runtime::spawn_blocking(move || {
+        let started = Instant::now();
+        match time_stretch::speed_up_wav(&audio, factor, relabel) {
+            Ok(Some(converted)) => {
+                log::debug!(
+                    target: "transcription",
+                    "Sped up audio {factor}x ({}) in {} ms: {} -> {} bytes",
+                    if relabel { "relabel" } else { "wsola" },
+                    started.elapsed().as_millis(),
+                    audio.len(),
+                    converted.len()
+                );
+                (converted, Some(factor))
+            }
+            Ok(None) => (audio, None),
+            Err(error) => {
+                log::warn!(target: "transcription", "Sending audio at real speed: {error}");
+                (audio, None)
+            }
+        }
+    })
+    .await
+    .map_err(|error| anyhow!("Audio speed-up task failed: {error}"))
+}
+
+/// Outcome of one `transcribe_batch` item; results keep the order of the requests.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchItemResult {
+    pub index: usize,
+    pub response: Option<TranscriptionResponse>,
+    pub error: Option<CommandError>,
+    /// Not sent because an earlier item failed authentication.
+    pub skipped: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BatchProgress<'a> {
+    batch_id: &'a str,
+    index: usize,
+    total: usize,
+    /// `started`, `done`, `failed` or `skipped`.
+    state: &'static str,
+}
+
+/// A failed clip, and whether it failed on credentials the other clips share.
+struct Failure {
+    error: CommandError,
+    auth: bool,
+}
+
+impl From<CommandError> for Failure {
+    fn from(error: CommandError) -> Self {
+        Self { error, auth: false }
+    }
+}
+
+#[perf::command]
+pub async fn transcribe_audio(
+    app: AppHandle,
+    state: State<'_, Arc<ConfigState>>,
+    transcriber: State<'_, Arc<TranscriptionManager>>,
+    request: TranscriptionRequest,
+) -> CommandResult<TranscriptionResponse> {
+    let config = state.get().await;
+    let _slot = transcriber.queue.acquire().await.ok();
+    transcribe_one(&app, &config, transcriber.inner(), request)
+        .await
+        .map_err(|failure| failure.error)
+}
+
+/// Transcribes clips through the same queue as `transcribe_audio`. Progress goes out
+/// per item; after an authentication failure the items not yet sent are skipped.
+#[perf::command]
+pub async fn transcribe_batch(
+    app: AppHandle,
+    state: State<'_, Arc<ConfigState>>,
+    transcriber: State<'_, Arc<TranscriptionManager>>,
+    requests: Vec<TranscriptionRequest>,
+    batch_id: Option<String>,
+) -> CommandResult<Vec<BatchItemResult>> {
+    let config = state.get().await;
+    let batch_id = batch_id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    let total = requests.len();
+    let auth_failed = AtomicBool::new(false);
+    log::info!(target: "transcription", "Batch {batch_id}: {total} clips");
+
+    let items = requests.into_iter().enumerate().map(|(index, request)| {
+        let (app, config, batch_id, auth_failed) = (&app, &config, &batch_id, &auth_failed);
+        let transcriber = transcriber.inner();
+        async move {
+            let progress = |state| {
+                let _ = emit_event(
+                    app,
+                    events::TRANSCRIPTION_BATCH_PROGRESS,
+                    BatchProgress {
+                        batch_id,
+                        index,
+                        total,
+                        state,
+                    },
+                );
+            };
+            let _slot = transcriber.queue.acquire().await.ok();
+            if auth_failed.load(Ordering::SeqCst) {
+                progress("skipped");
+                return BatchItemResult {
+                    index,
+                    response: None,
+                    error: None,
+                    skipped: true,
+                };
+            }
+            progress("started");
+            match transcribe_one(app, config, transcriber, request).await {
+                Ok(response) => {
+                    progress("done");
+                    BatchItemResult {
+                        index,
+                        response: Some(response),
+                        error: None,
+                        skipped: false,
+                    }
+                }
+                Err(failure) => {
+                    if failure.auth {
+                        auth_failed.store(true, Ordering::SeqCst);
+                    }
+                    progress("failed");
+                    BatchItemResult {
+                        index,
+                        response: None,
+                        error: Some(failure.error),
+                        skipped: false,
+                    }
+                }
+            }
+        }
+    });
+    Ok(futures_util::future::join_all(items).await)
+}
+
+/// The whole pipeline for one clip; the caller holds a queue slot.
+async fn transcribe_one(
+    app: &AppHandle,
+    config: &AppConfig,
+    transcriber: &Arc<TranscriptionManager>,
+    mut request: TranscriptionRequest,
+) -> Result<TranscriptionResponse, Failure> {
+    let _in_flight = transcriber.begin();
+    let mut clock = RequestClock::new();
+
+    // Debug copies keep the captured rate only when asked to, for A/B listening.
+    let original_rate_copy = config.recorder_files_original_rate;
+    if original_rate_copy {
+        save_audio_debug(
+            app,
+            config,
+            &request.audio_data,
+            request.mode.as_str(),
+            &request.filename,
+        )
+        .await;
+    }
+    request.audio_data =
+        downsample_for_transcription(request.audio_data, config.transcription_sample_rate)
+            .await
+            .map_err(|error| CommandError::new(ErrorCode::TranscriptionFailed, error))?;
+    // Real time, for the session and transcript log; usage counts what is uploaded.
+    let audio_seconds = usage::wav_duration_seconds(&request.audio_data).unwrap_or(0.0);
+    if config.stt_speedup_factor > STT_SPEEDUP_QUALITY_LIMIT {
+        transcriber.warn_speedup_quality(app, config.stt_speedup_factor);
+    }
+    let (audio_data, speedup_factor) = speed_up_for_transcription(
+        request.audio_data,
+        config.stt_speedup_factor,
+        config.stt_speedup_relabel,
+    )
+    .await
+    .map_err(|error| CommandError::new(ErrorCode::TranscriptionFailed, error))?;
+    request.audio_data = audio_data;
+    let uploaded_seconds = audio_seconds / speedup_factor.unwrap_or(1.0) as f64;
+    if !original_rate_copy {
+        save_audio_debug(
+            app,
+            config,
+            &request.audio_data,
+            request.mode.as_str(),
+            &request.filename,
+        )
+        .await;
+    }
+    
+    let provider = match request.mode {
+        TranscriptionMode::Api => "openai",
+        other => other.as_str(),
+    }
+    .to_string();
+    let interaction_id = request.interaction_id.take();
+    // A known language skips detection.
+    request.language = request
+        .language
+        .take()
+        .or_else(|| config.transcription_language.clone());
+    // The wait counts towards `prepare_ms`.
+    app.state::<Arc<RateLimiter>>()
+        .acquire(app, config, &provider, "transcription")
+        .await;
+
+    let backend = transcriber
+        .providers
+        .get(request.mode.as_str())
+        .filter(|_| request.mode != TranscriptionMode::Mock || config.mock_providers_enabled());
+    let mut detected = match backend {
+        Some(backend) => language::detect(app, config, backend, &request, &provider).await,
+        None => None,
+    };
+    if let Some(detected) = &mut detected {
+        detected.profile_applied = config.language_profile(Some(&detected.language)).is_some();
+        request.language = Some(detected.language.clone());
+    }
+    if let Some(model) = config
+        .language_profile(request.language.as_deref())
+        .and_then(|profile| profile.model.clone())
+        .filter(|model| !model.trim().is_empty())
+    {
+        request.model = Some(model);
+    }
+    let source_language = request.language.clone();
+    let mut result = match backend {
+        Some(backend) => run_provider(app, config, backend, request, &provider, &mut clock).await,
+        None => Err(CommandError::bare(
+            ErrorCode::TranscriptionModeUnknown,
+            &[("mode", request.mode.as_str())],
+        )
+        .into()),
+    };
+    
+    if let Ok(response) = &mut result {
+        response.timings = clock.finish();
+        response.speedup_factor = speedup_factor;
+        response.language = detected;
+        let source = source_language
+            .as_deref()
+            .or_else(|| response.language.as_ref().map(|d| d.language.as_str()));
+        match translate::translate(app, config, &response.text, source).await {
+            translate::Outcome::Translated(text) => response.translated_text = Some(text),
+            translate::Outcome::Failed => response.translation_failed = true,
+            translate::Outcome::Skipped => {}
+        }
+        let timings = &response.timings;
+        log::info!(
+            target: "transcription",
+            "Transcription timings: provider={provider} prepare_ms={} upload_ms={} provider_ms={} total_ms={}",
+            timings.prepare_ms,
+            timings.upload_ms,
+            timings.provider_ms,
+            timings.total_ms
+        );
+        if let Some(last_result) = app.try_state::<Arc<LastResult>>() {
+            last_result.set(app, &response.text);
+        }
+        let speaker = session::speaker_for_input(config.audio_input_type);
+        integration::publish_transcript(app, &response.text, speaker.as_deref());
+        if let Some(session_log) = app.try_state::<Arc<SessionLog>>() {
+            let id = session_log.record_clip(interaction_id.as_deref(), audio_seconds);
+            session_log.record(
+                SessionEntryKind::Transcript,
+                speaker,
+                &response.text,
+                Some(&id),
+            );
+            if let Some(translated) = &response.translated_text {
+                session_log.record(SessionEntryKind::Translation, None, translated, Some(&id));
+            }
+            response.interaction_id = Some(id);
+        }
+        if let Some(transcript_log) = app.try_state::<Arc<TranscriptLog>>() {
+            if let Err(error) = transcript_log
+                .append(app, config, &provider, audio_seconds, &response.text)
+                .await
+            {
+                log::warn!(target: "transcription", "Transcript not written to the text log: {error:#}");
+            }
+        }
+    }
+    sounds::play_event(
+        app,
+        if result.is_ok() {
+            sounds::CUE_TRANSCRIPT_READY
+        } else {
+            sounds::CUE_ERROR
+        },
+    );
+    if let Some(usage) = app.try_state::<Arc<UsageStore>>() {
+        usage.record(UsageEvent::Transcription {
+            provider,
+            audio_seconds: uploaded_seconds,
+            latency_ms: result
+                .as_ref()
+                .map(|response| response.timings.total_ms)
+                .unwrap_or(0),
+            ok: result.is_ok(),
+        });
+    }
+    
+    result
+}
+
+/// Provider messages stay in `detail`; the headline is the localized code text.
+fn provider_error(error: TranscriptionError) -> CommandError {
+    match &error {
+        TranscriptionError::IncompatibleLocalServer(incompatible) => CommandError::with_params(
+            ErrorCode::LocalSpeechIncompatible,
+            &[
+                ("version", &incompatible.server_version),
+                ("required", &incompatible.required),
+            ],
+            &error,
+        )
+        .with_data(serde_json::json!({
+            "serverVersion": incompatible.server_version,
+            "required": incompatible.required,
+            "updateCommand": "local_speech_reinstall",
+        })),
+        _ => CommandError::new(ErrorCode::TranscriptionFailed, error),
+    }
+}
+
+/// Validation and error mapping shared by every provider.
+async fn run_provider(
+    app: &AppHandle,
+    config: &AppConfig,
+    provider: &dyn TranscriptionProvider,
+    mut request: TranscriptionRequest,
+    quota_key: &str,
+    clock: &mut RequestClock,
+) -> Result<TranscriptionResponse, Failure> {
+    check_payload_size(provider, &request)?;
+    let (prompt, truncation) = prepare_prompt(
+        provider,
+        config,
+        request.mode,
+        request.language.as_deref(),
+        request.prompt,
+    );
+    if let Some(truncation) = truncation {
+        let first = app
+            .state::<Arc<TranscriptionManager>>()
+            .truncation_warned
+            .lock()
+            .unwrap()
+            .insert((truncation.provider, prompt.clone().unwrap_or_default()));
+        if first {
+            log::warn!(
+                target: "transcription",
+                "Prompt shortened for {}: ~{} tokens, limit {}",
+                truncation.provider,
+                truncation.estimated_tokens,
+                truncation.limit_tokens
+            );
+            let _ = emit_event(app, events::TRANSCRIPTION_PROMPT_TRUNCATED, &truncation);
+        }
+    }
+    request.prompt = prompt;
+    log::debug!(
+        target: "transcription",
+        "Sending to {}: bytes={} streaming={}",
+        provider.name(),
+        request.audio_data.len(),
+        provider.supports_streaming()
+    );
+    let http = app.state::<Arc<HttpClients>>();
+    let quota = app.state::<Arc<ProviderQuotaState>>();
+    let mut ctx = ProviderContext {
+        app,
+        http: &http,
+        config,
+        clock,
+        quota: &quota,
+        quota_key,
+    };
+    provider
+        .transcribe(request, &mut ctx)
+        .await
+        .map_err(|error| Failure {
+            auth: error.is_auth_failure(),
+            error: provider_error(error),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn transcription_request_wire_format_is_camel_case() {
+        let request = TranscriptionRequest {
+            mode: TranscriptionMode::Api,
+            model: Some("whisper-1".into()),
+            api_key: None,
+            audio_data: vec![1, 2],
+            mime_type: "audio/wav".into(),
+            filename: "clip.wav".into(),
+            prompt: None,
+            interaction_id: Some("i-1".into()),
+            language: Some("en".into()),
+        };
+        assert_eq!(
+            serde_json::to_value(&request).unwrap(),
+            json!({
+                "mode": "api",
+                "model": "whisper-1",
+                "apiKey": null,
+                "audioData": [1, 2],
+                "mimeType": "audio/wav",
+                "filename": "clip.wav",
+                "prompt": null,
+                "interactionId": "i-1",
+                "language": "en",
+            })
+        );
+    }
+
+    #[test]
+    fn transcription_request_accepts_snake_case_fields() {
+        let request: TranscriptionRequest = serde_json::from_value(json!({
+            "mode": "openai",
+            "model": null,
+            "api_key": "sk-test",
+            "audio_data": [7],
+            "mime_type": "audio/webm",
+            "filename": "clip.webm",
+            "prompt": null,
+            "interaction_id": "i-2",
+        }))
+        .unwrap();
+        assert_eq!(request.mode, TranscriptionMode::Api);
+        assert_eq!(request.api_key.as_deref(), Some("sk-test"));
+        assert_eq!(request.audio_data, [7]);
+        assert_eq!(request.mime_type, "audio/webm");
+        assert_eq!(request.interaction_id.as_deref(), Some("i-2"));
+        assert_eq!(request.language, None);
+    }
+
+    #[test]
+    fn transcription_response_wire_format_is_camel_case() {
+        let response = TranscriptionResponse {
+            text: "hello".into(),
+            timings: TranscriptionTimings {
+                prepare_ms: 1,
+                upload_ms: 2,
+                provider_ms: 3,
+                total_ms: 6,
+            },
+            interaction_id: Some("i-1".into()),
+            speedup_factor: Some(1.5),
+            language: Some(DetectedLanguage {
+                language: "en".into(),
+                confidence: Some(0.5),
+                method: "whisper".into(),
+                profile_applied: true,
+                elapsed_ms: 40,
+            }),
+            translated_text: None,
+            translation_failed: false,
+        };
+        assert_eq!(
+            serde_json::to_value(&response).unwrap(),
+            json!({
+                "text": "hello",
+                "timings": { "prepareMs": 1, "uploadMs": 2, "providerMs": 3, "totalMs": 6 },
+                "interactionId": "i-1",
+                "speedupFactor": 1.5,
+                "language": {
+                    "language": "en",
+                    "confidence": 0.5,
+                    "method": "whisper",
+                    "profileApplied": true,
+                    "elapsedMs": 40,
+                },
+                "translatedText": null,
+                "translationFailed": false,
+            })
+        );
+    }
+
+    #[test]
+    fn transcription_response_accepts_snake_case_fields() {
+        let response: TranscriptionResponse = serde_json::from_value(json!({
+            "text": "hello",
+            "timings": { "prepare_ms": 1, "upload_ms": 2, "provider_ms": 3, "total_ms": 6 },
+            "interaction_id": "i-1",
+        }))
+        .unwrap();
+        assert_eq!(response.interaction_id.as_deref(), Some("i-1"));
+        assert_eq!(response.timings.total_ms, 6);
+        assert_eq!(response.timings.upload_ms, 2);
+    }
+}
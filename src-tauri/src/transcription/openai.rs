@@ -0,0 +1,67 @@
+use futures_util::future::BoxFuture;
+use reqwest::multipart;
+
+use super::provider::{
+    audio_part, check_status, text_field, transcript, ProviderContext, TranscriptionError,
+    TranscriptionProvider, WHISPER_PROMPT_TOKENS,
+};
+use super::{TranscriptionRequest, TranscriptionResponse};
+use crate::constants::OPENAI_TRANSCRIPTION_MAX_BYTES;
+
+const TRANSCRIPTIONS_URL: &str = "https://api.openai.com/v1/audio/transcriptions";
+const DEFAULT_MODEL: &str = "whisper-1";
+
+pub struct OpenAiProvider;
+
+impl TranscriptionProvider for OpenAiProvider {
+    fn name(&self) -> &'static str {
+        "OpenAI"
+    }
+
+    fn max_payload_bytes(&self) -> Option<usize> {
+        Some(OPENAI_TRANSCRIPTION_MAX_BYTES)
+    }
+
+    fn max_prompt_tokens(&self) -> Option<usize> {
+        Some(WHISPER_PROMPT_TOKENS)
+    }
+
+    fn transcribe<'a>(
+        &'a self,
+        request: TranscriptionRequest,
+        ctx: &'a mut ProviderContext<'_>,
+    ) -> BoxFuture<'a, Result<TranscriptionResponse, TranscriptionError>> {
+        Box::pin(async move {
+            let api_key = request
+                .api_key
+                .ok_or(TranscriptionError::MissingKey(self.name()))?;
+            let model = request.model.unwrap_or_else(|| DEFAULT_MODEL.to_string());
+            let client = ctx.upload_client()?;
+
+            let file = audio_part(
+                ctx.clock,
+                request.audio_data,
+                request.filename,
+                &request.mime_type,
+            )?;
+            let mut form = multipart::Form::new().text("model", model);
+            if let Some(prompt) = request.prompt {
+                form = form.text("prompt", prompt);
+            }
+            if let Some(language) = request.language {
+                form = form.text("language", language);
+            }
+            let form = form.part("file", file);
+
+            let response = client
+                .post(TRANSCRIPTIONS_URL)
+                .header("Authorization", format!("Bearer {api_key}"))
+                .multipart(form)
+                .send()
+                .await?;
+            ctx.headers_received(&response);
+            let response = check_status(self.name(), response).await?;
+            Ok(transcript(text_field(response).await?))
+        })
+    }
+}
@@ -0,0 +1,456 @@
+//! The seam between `transcribe_audio` and the speech-to-text backends. Each backend
+//! implements `TranscriptionProvider` and is registered under its mode string.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use futures_util::future::BoxFuture;
+use reqwest::{multipart, Client, StatusCode};
+use serde::Serialize;
+use tauri::AppHandle;
+
+use super::google::GoogleProvider;
+use super::local::LocalProvider;
+use super::mock::MockProvider;
+use super::openai::OpenAiProvider;
+use super::{RequestClock, TranscriptionRequest, TranscriptionResponse, TranscriptionTimings};
+use crate::http::HttpClients;
+use crate::local_speech::IncompatibleLocalServer;
+use crate::provider_quota::{ProviderQuotaState, QuotaHeaders};
+use crate::types::{AppConfig, TranscriptionMode};
+
+// Some models answer with the instruction itself when the clip has no speech.
+const PROMPT_ECHO_MARKER: &str = "transcribe verbatim";
+// Whisper conditions on at most this many prompt tokens.
+pub const WHISPER_PROMPT_TOKENS: usize = 224;
+
+#[derive(Debug)]
+pub enum TranscriptionError {
+    /// The request carries no API key for the named provider.
+    MissingKey(&'static str),
+    /// The request lacks something else the provider needs.
+    Request(String),
+    /// No client could be built, or the provider never answered.
+    Transport(anyhow::Error),
+    /// The provider answered with an error status.
+    Status {
+        provider: &'static str,
+        status: StatusCode,
+        body: String,
+    },
+    /// The answer came back without a usable transcript.
+    Response(String),
+    /// The local server is a release whose API this build does not speak.
+    IncompatibleLocalServer(IncompatibleLocalServer),
+}
+
+impl fmt::Display for TranscriptionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingKey(provider) => write!(f, "{provider} API key is required"),
+            Self::Request(message) | Self::Response(message) => f.write_str(message),
+            Self::Transport(error) => write!(f, "{error:#}"),
+            Self::Status {
+                provider,
+                status,
+                body,
+            } => write!(f, "{provider} error: {status} - {body}"),
+            Self::IncompatibleLocalServer(error) => error.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for TranscriptionError {}
+
+impl TranscriptionError {
+    /// Every other request with the same credentials would fail the same way.
+    pub fn is_auth_failure(&self) -> bool {
+        match self {
+            Self::MissingKey(_) => true,
+            Self::Status { status, .. } => {
+                matches!(*status, StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN)
+            }
+            _ => false,
+        }
+    }
+}
+
+impl From<reqwest::Error> for TranscriptionError {
+    fn from(error: reqwest::Error) -> Self {
+        Self::Transport(error.into())
+    }
+}
+
+impl From<IncompatibleLocalServer> for TranscriptionError {
+    fn from(error: IncompatibleLocalServer) -> Self {
+        Self::IncompatibleLocalServer(error)
+    }
+}
+
+impl From<anyhow::Error> for TranscriptionError {
+    fn from(error: anyhow::Error) -> Self {
+        Self::Transport(error)
+    }
+}
+
+/// What a provider gets besides the request.
+pub struct ProviderContext<'a> {
+    pub app: &'a AppHandle,
+    pub http: &'a HttpClients,
+    pub config: &'a AppConfig,
+    pub clock: &'a mut RequestClock,
+    pub quota: &'a ProviderQuotaState,
+    /// Usage provider key the quota headers are kept under, e.g. `openai`.
+    pub quota_key: &'a str,
+}
+
+impl ProviderContext<'_> {
+    /// Proxied long-timeout client for remote providers.
+    pub fn upload_client(&self) -> Result<Client, TranscriptionError> {
+        Ok(self.http.upload(self.config)?)
+    }
+
+    /// Direct client for the servers on this machine.
+    pub fn local_client(&self) -> Client {
+        self.http.local()
+    }
+
+    /// Call once the response headers are in: stops the upload clock and keeps
+    /// the provider's quota headers for the rate limiter.
+    pub fn headers_received(&mut self, response: &reqwest::Response) {
+        self.clock.headers_received(response);
+        self.quota.record(
+            self.quota_key,
+            QuotaHeaders::from_header_map(response.headers()),
+        );
+    }
+}
+
+pub trait TranscriptionProvider: Send + Sync {
+    /// Name shown to the user, e.g. in payload limit errors.
+    fn name(&self) -> &'static str;
+
+    /// Largest upload the provider accepts; `None` when it has no limit.
+    fn max_payload_bytes(&self) -> Option<usize>;
+
+    /// Whether the provider can return partial text while audio is still arriving.
+    fn supports_streaming(&self) -> bool {
+        false
+    }
+
+    /// Prompt tokens the provider uses; it cuts longer prompts itself, often
+    /// mid-word. `None` when prompts are not capped.
+    fn max_prompt_tokens(&self) -> Option<usize> {
+        None
+    }
+
+    /// Whether the prompt is sent at all under `config`.
+    fn sends_prompt(&self, _config: &AppConfig) -> bool {
+        true
+    }
+
+    fn transcribe<'a>(
+        &'a self,
+        request: TranscriptionRequest,
+        ctx: &'a mut ProviderContext<'_>,
+    ) -> BoxFuture<'a, Result<TranscriptionResponse, TranscriptionError>>;
+}
+
+/// Providers keyed by the transcription mode string they serve.
+pub struct ProviderRegistry {
+    providers: HashMap<&'static str, Box<dyn TranscriptionProvider>>,
+}
+
+impl ProviderRegistry {
+    pub fn with_defaults() -> Self {
+        let mut registry = Self {
+            providers: HashMap::new(),
+        };
+        registry.register(TranscriptionMode::Api.as_str(), OpenAiProvider);
+        registry.register(TranscriptionMode::Local.as_str(), LocalProvider);
+        registry.register(
+            TranscriptionMode::Google.as_str(),
+            GoogleProvider::default(),
+        );
+        registry.register(TranscriptionMode::Mock.as_str(), MockProvider::default());
+        registry
+    }
+
+    pub fn register(&mut self, mode: &'static str, provider: impl TranscriptionProvider + 'static) {
+        self.providers.insert(mode, Box::new(provider));
+    }
+
+    pub fn get(&self, mode: &str) -> Option<&dyn TranscriptionProvider> {
+        self.providers.get(mode).map(|provider| provider.as_ref())
+    }
+}
+
+/// A prompt shortened to fit a provider's limit.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptTruncation {
+    pub provider: &'static str,
+    pub estimated_tokens: usize,
+    pub limit_tokens: usize,
+}
+
+/// The prompt `provider` should get: the profile prompt for `language` or the
+/// configured override for `mode` if any, dropped when the provider is not sent
+/// prompts, and cut at a word boundary to the provider's limit.
+pub fn prepare_prompt(
+    provider: &dyn TranscriptionProvider,
+    config: &AppConfig,
+    mode: TranscriptionMode,
+    language: Option<&str>,
+    prompt: Option<String>,
+) -> (Option<String>, Option<PromptTruncation>) {
+    if !provider.sends_prompt(config) {
+        return (None, None);
+    }
+    let prompt = config
+        .language_profile(language)
+        .and_then(|profile| profile.prompt.as_deref())
+        .filter(|prompt| !prompt.trim().is_empty())
+        .or_else(|| config.transcription_prompt_overrides.for_mode(mode))
+        .map(str::to_string)
+        .or(prompt)
+        .filter(|prompt| !prompt.trim().is_empty());
+    let (Some(prompt), Some(limit)) = (prompt.clone(), provider.max_prompt_tokens()) else {
+        return (prompt, None);
+    };
+    let estimated_tokens = estimate_tokens(&prompt);
+    if estimated_tokens <= limit {
+        return (Some(prompt), None);
+    }
+    let mut kept = String::new();
+    for word in prompt.split_whitespace() {
+        let next = if kept.is_empty() {
+            word.to_string()
+        } else {
+            format!("{kept} {word}")
+        };
+        if estimate_tokens(&next) > limit {
+            break;
+        }
+        kept = next;
+    }
+    let truncation = PromptTruncation {
+        provider: provider.name(),
+        estimated_tokens,
+        limit_tokens: limit,
+    };
+    (Some(kept).filter(|kept| !kept.is_empty()), Some(truncation))
+}
+
+/// Rough BPE count: about four characters per token for ASCII text and two for
+/// other scripts, erring on the long side.
+fn estimate_tokens(text: &str) -> usize {
+    let ascii = text.chars().filter(char::is_ascii).count();
+    let other = text.chars().count() - ascii;
+    ascii.div_ceil(4) + other.div_ceil(2)
+}
+
+/// Multipart file part whose upload progress the request clock follows.
+pub fn audio_part(
+    clock: &mut RequestClock,
+    audio: Vec<u8>,
+    filename: String,
+    mime_type: &str,
+) -> Result<multipart::Part, TranscriptionError> {
+    let length = audio.len() as u64;
+    Ok(
+        multipart::Part::stream_with_length(clock.body(audio), length)
+            .file_name(filename)
+            .mime_str(mime_type)?,
+    )
+}
+
+/// Passes successful answers through and turns the rest into `Status` errors.
+pub async fn check_status(
+    provider: &'static str,
+    response: reqwest::Response,
+) -> Result<reqwest::Response, TranscriptionError> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    let body = response
+        .text()
+        .await
+        .unwrap_or_else(|_| "Unknown error".to_string());
+    Err(TranscriptionError::Status {
+        provider,
+        status,
+        body,
+    })
+}
+
+/// Reads the top-level `text` field the OpenAI-compatible endpoints return.
+pub async fn text_field(response: reqwest::Response) -> Result<String, TranscriptionError> {
+    let data: serde_json::Value = response.json().await?;
+    data.get("text")
+        .and_then(|value| value.as_str())
+        .map(str::to_string)
+        .ok_or_else(|| TranscriptionError::Response("No text field in response".to_string()))
+}
+
+/// Rejects a transcript that is the instruction read back instead of speech.
+pub fn reject_prompt_echo(text: String) -> Result<String, TranscriptionError> {
+    if text.to_lowercase().contains(PROMPT_ECHO_MARKER) {
+        return Err(TranscriptionError::Response(
+            "Received prompt text instead of transcription".to_string(),
+        ));
+    }
+    Ok(text)
+}
+
+pub fn transcript(text: String) -> TranscriptionResponse {
+    TranscriptionResponse {
+        text,
+        timings: TranscriptionTimings::default(),
+        interaction_id: None,
+        speedup_factor: None,
+        language: None,
+        translated_text: None,
+        translation_failed: false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::anyhow;
+    use serde_json::json;
+
+    use super::super::{check_payload_size, provider_error};
+    use super::*;
+    use crate::errors::ErrorCode;
+
+    /// A provider that only has a name and a payload limit.
+    struct Stub(Option<usize>);
+
+    impl TranscriptionProvider for Stub {
+        fn name(&self) -> &'static str {
+            "Stub"
+        }
+
+        fn max_payload_bytes(&self) -> Option<usize> {
+            self.0
+        }
+
+        fn transcribe<'a>(
+            &'a self,
+            _request: TranscriptionRequest,
+            _ctx: &'a mut ProviderContext<'_>,
+        ) -> BoxFuture<'a, Result<TranscriptionResponse, TranscriptionError>> {
+            unreachable!("only the checks around transcribe run")
+        }
+    }
+
+    fn request(size: usize) -> TranscriptionRequest {
+        TranscriptionRequest {
+            mode: TranscriptionMode::Api,
+            model: None,
+            api_key: None,
+            audio_data: vec![0; size],
+            mime_type: "audio/wav".into(),
+            filename: "clip.wav".into(),
+            prompt: None,
+            interaction_id: None,
+            language: None,
+        }
+    }
+
+    #[test]
+    fn payload_size_is_checked_against_the_provider_limit() {
+        const MB: usize = 1024 * 1024;
+        let cases = [
+            (None, 100 * MB, None),
+            (Some(MB), 0, None),
+            (Some(MB), MB, None),
+            (
+                Some(MB),
+                MB + 1,
+                Some("The recording is too large for Stub (1.0 MB, limit 1.0 MB)."),
+            ),
+            (
+                Some(25 * MB),
+                30 * MB,
+                Some("The recording is too large for Stub (30.0 MB, limit 25.0 MB)."),
+            ),
+        ];
+        for (limit, size, refused) in cases {
+            let result = check_payload_size(&Stub(limit), &request(size));
+            match refused {
+                None => assert!(result.is_ok(), "{limit:?} {size}"),
+                Some(headline) => {
+                    let error = result.unwrap_err();
+                    assert_eq!(error.code, ErrorCode::PayloadTooLarge);
+                    assert!(error.message.starts_with(headline), "{}", error.message);
+                    assert_eq!(error.detail, None);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn provider_errors_map_to_command_errors() {
+        let status = |status| TranscriptionError::Status {
+            provider: "Stub",
+            status,
+            body: "nope".into(),
+        };
+        let cases = [
+            (
+                TranscriptionError::MissingKey("Stub"),
+                "Stub API key is required",
+            ),
+            (TranscriptionError::Request("no model".into()), "no model"),
+            (
+                TranscriptionError::Transport(anyhow!("connection reset")),
+                "connection reset",
+            ),
+            (
+                status(StatusCode::UNAUTHORIZED),
+                "Stub error: 401 Unauthorized - nope",
+            ),
+            (
+                status(StatusCode::INTERNAL_SERVER_ERROR),
+                "Stub error: 500 Internal Server Error - nope",
+            ),
+            (
+                TranscriptionError::Response("empty transcript".into()),
+                "empty transcript",
+            ),
+        ];
+        for (error, detail) in cases {
+            let error = provider_error(error);
+            assert_eq!(error.code, ErrorCode::TranscriptionFailed, "{detail}");
+            assert_eq!(error.detail.as_deref(), Some(detail));
+            assert_eq!(error.data, None);
+        }
+    }
+
+    #[test]
+    fn an_incompatible_local_server_asks_for_a_reinstall() {
+        let error = provider_error(TranscriptionError::IncompatibleLocalServer(
+            IncompatibleLocalServer {
+                server_version: "2.1.0".into(),
+                required: ">= 0.1.0, < 2.0.0".into(),
+            },
+        ));
+        assert_eq!(error.code, ErrorCode::LocalSpeechIncompatible);
+        assert!(
+            error.message.contains("2.1.0") && error.message.contains(">= 0.1.0, < 2.0.0"),
+            "{}",
+            error.message
+        );
+        assert_eq!(
+            error.data,
+            Some(json!({
+                "serverVersion": "2.1.0",
+                "required": ">= 0.1.0, < 2.0.0",
+                "updateCommand": "local_speech_reinstall",
+            }))
+        );
+    }
+}
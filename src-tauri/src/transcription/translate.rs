@@ -0,0 +1,229 @@
+//! Optional post-step that translates a finished transcript into `translateTo`
+//! through the configured LLM host. The transcript never depends on it: a
+//! failed translation is logged and flagged on the response.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, Context, Result};
+use serde_json::{json, Value};
+use tauri::{AppHandle, Manager};
+
+use super::language;
+use crate::http::HttpClients;
+use crate::rate_limit::RateLimiter;
+use crate::types::{AppConfig, LlmHost};
+use crate::usage::{UsageEvent, UsageStore};
+
+const OPENAI_CHAT_URL: &str = "https://api.openai.com/v1/chat/completions";
+const GEMINI_MODELS_URL: &str = "https://generativelanguage.googleapis.com/v1beta/models";
+const OLLAMA_CHAT_URL: &str = "http://127.0.0.1:11434/api/chat";
+// Shorter transcripts are left alone: little to gain, and too short to tell
+// their language apart from the target.
+const MIN_WORDS: usize = 5;
+
+pub(super) enum Outcome {
+    /// Not configured, too short, or already in the target language.
+    Skipped,
+    Translated(String),
+    Failed,
+}
+
+/// Translates `text` when `translateTo` is set and differs from `source`, the
+/// declared or detected language; without one the text itself is looked at.
+pub(super) async fn translate(
+    app: &AppHandle,
+    config: &AppConfig,
+    text: &str,
+    source: Option<&str>,
+) -> Outcome {
+    let Some(target) = config.translate_to.as_deref() else {
+        return Outcome::Skipped;
+    };
+    if text.split_whitespace().count() < MIN_WORDS {
+        return Outcome::Skipped;
+    }
+    let target_code = language::language_code(target);
+    let source_code = source
+        .and_then(language::language_code)
+        .or_else(|| language::guess_from_text(text).map(|(code, _)| code));
+    if target_code.is_some() && target_code == source_code {
+        return Outcome::Skipped;
+    }
+    let target_name = target_code
+        .as_deref()
+        .and_then(language::language_name)
+        .unwrap_or(target);
+    let model = config
+        .translation_model
+        .as_deref()
+        .unwrap_or(config.effective_llm_model());
+    match request(app, config, model, target_name, text).await {
+        Ok(translated) if !translated.is_empty() => {
+            log::info!(
+                target: "transcription",
+                "Translated transcript to {target_name} with {model}: {} chars",
+                translated.chars().count()
+            );
+            Outcome::Translated(translated)
+        }
+        Ok(_) => {
+            log::warn!(target: "transcription", "Translation to {target_name} came back empty");
+            Outcome::Failed
+        }
+        Err(error) => {
+            log::warn!(target: "transcription", "Translation to {target_name} failed: {error:#}");
+            Outcome::Failed
+        }
+    }
+}
+
+async fn request(
+    app: &AppHandle,
+    config: &AppConfig,
+    model: &str,
+    target: &str,
+    text: &str,
+) -> Result<String> {
+    let instruction = format!(
+        "Translate the user's text into {target}. Reply with the translation only. \
+         Keep technical terms, names and code as they are."
+    );
+    let timeout = Duration::from_millis(config.api_llm_timeout_ms as u64);
+    let http = app.state::<Arc<HttpClients>>();
+    if config.llm_host == LlmHost::Local {
+        let body = json!({
+            "model": model,
+            "messages": [
+                {"role": "system", "content": instruction},
+                {"role": "user", "content": text},
+            ],
+            "stream": false,
+            "options": {"temperature": 0},
+        });
+        let data = send(
+            http.local()
+                .post(OLLAMA_CHAT_URL)
+                .json(&body)
+                .timeout(timeout),
+        )
+        .await?;
+        return Ok(string_at(&data, "/message/content"));
+    }
+    if model.starts_with("winky-") {
+        return Err(anyhow!(
+            "{model} runs on the backend and cannot translate here; set translationModel"
+        ));
+    }
+    let client = http.api(config)?;
+    let (provider, data) = if model.starts_with("gemini") {
+        let key = config
+            .google_api_key
+            .as_deref()
+            .context("no Google API key configured")?;
+        let body = json!({
+            "systemInstruction": {"parts": [{"text": instruction}]},
+            "contents": [{"role": "user", "parts": [{"text": text}]}],
+            "generationConfig": {"temperature": 0},
+        });
+        let url = format!("{GEMINI_MODELS_URL}/{model}:generateContent?key={key}");
+        budget(app, config, "google").await;
+        let result = send(client.post(url).json(&body).timeout(timeout)).await;
+        ("google", result)
+    } else {
+        let key = config
+            .openai_api_key
+            .as_deref()
+            .context("no OpenAI API key configured")?;
+        let mut body = json!({
+            "model": model,
+            "messages": [
+                {"role": "system", "content": instruction},
+                {"role": "user", "content": text},
+            ],
+        });
+        // The gpt-5 family only takes the default temperature.
+        if !model.starts_with("gpt-5") {
+            body["temperature"] = json!(0);
+        }
+        budget(app, config, "openai").await;
+        let result = send(
+            client
+                .post(OPENAI_CHAT_URL)
+                .bearer_auth(key)
+                .json(&body)
+                .timeout(timeout),
+        )
+        .await;
+        ("openai", result)
+    };
+    let tokens = |pointer: &str| {
+        data.as_ref()
+            .ok()
+            .and_then(|data| data.pointer(pointer))
+            .and_then(Value::as_u64)
+            .unwrap_or(0)
+    };
+    let (tokens_in, tokens_out) = if provider == "google" {
+        (
+            tokens("/usageMetadata/promptTokenCount"),
+            tokens("/usageMetadata/candidatesTokenCount"),
+        )
+    } else {
+        (
+            tokens("/usage/prompt_tokens"),
+            tokens("/usage/completion_tokens"),
+        )
+    };
+    if let Some(usage) = app.try_state::<Arc<UsageStore>>() {
+        usage.record(UsageEvent::Llm {
+            provider: provider.to_string(),
+            tokens_in,
+            tokens_out,
+            ok: data.is_ok(),
+        });
+    }
+    let data = data?;
+    Ok(if provider == "google" {
+        data.pointer("/candidates/0/content/parts")
+            .and_then(Value::as_array)
+            .map(|parts| {
+                parts
+                    .iter()
+                    .filter_map(|part| part.get("text").and_then(Value::as_str))
+                    .collect::<String>()
+            })
+            .unwrap_or_default()
+            .trim()
+            .to_string()
+    } else {
+        string_at(&data, "/choices/0/message/content")
+    })
+}
+
+async fn budget(app: &AppHandle, config: &AppConfig, provider: &str) {
+    app.state::<Arc<RateLimiter>>()
+        .acquire(app, config, provider, "llm")
+        .await;
+}
+
+async fn send(request: reqwest::RequestBuilder) -> Result<Value> {
+    let response = request.send().await?;
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(anyhow!(
+            "{status}: {}",
+            body.chars().take(300).collect::<String>()
+        ));
+    }
+    Ok(response.json().await?)
+}
+
+fn string_at(data: &Value, pointer: &str) -> String {
+    data.pointer(pointer)
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .trim()
+        .to_string()
+}
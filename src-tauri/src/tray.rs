@@ -1,36 +1,262 @@
 use once_cell::sync::OnceCell;
-use std::sync::Mutex;
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use tauri::{
     image::Image,
-    menu::{MenuBuilder, MenuItemBuilder},
+    menu::{
+        CheckMenuItem, CheckMenuItemBuilder, MenuBuilder, MenuItemBuilder, Submenu, SubmenuBuilder,
+    },
     tray::{TrayIcon, TrayIconBuilder},
-    AppHandle, Manager,
+    AppHandle, Manager, Wry,
 };
+use tauri_plugin_clipboard_manager::ClipboardExt;
 
-use crate::show_main_window;
+use crate::config::ConfigState;
+use crate::error_bus;
+use crate::errors::ErrorCode;
+use crate::events::{self, emit_event};
+use crate::private_session;
+use crate::types::TrayIconTheme;
+use crate::{reapply_shell_window_state, show_main_window};
 
-fn load_image_from_path(path: &std::path::Path) -> Option<Image<'static>> {
-    let img = image::open(path).ok()?;
+fn load_image_from_path(app: &AppHandle, path: &std::path::Path) -> Option<Image<'static>> {
+    let img = match image::open(path) {
+        Ok(img) => img,
+        Err(error) => {
+            let detail = format!("{}: {error}", path.display());
+            error_bus::publish(app, "tray", ErrorCode::TrayFailed, detail, true);
+            return None;
+        }
+    };
     let rgba = img.to_rgba8();
     let (width, height) = rgba.dimensions();
     let pixels = rgba.into_raw();
     Some(Image::new_owned(pixels, width, height))
 }
 
+// Last resort for installs where no icon file resolves, such as portable builds
+// run from a network share; without one the Windows 11 tray shows nothing.
+const EMBEDDED_ICON: &[u8] = include_bytes!("../icons/tray-fallback.png");
+const LIGHT_THEME_ICON: &[u8] = include_bytes!("../icons/tray-light.png");
+const DARK_THEME_ICON: &[u8] = include_bytes!("../icons/tray-dark.png");
+
+/// Where the tray icon was loaded from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum IconSource {
+    /// The monochrome variant for `trayIconTheme` light or dark.
+    Theme,
+    WindowDefault,
+    Resource,
+    Embedded,
+}
+
+impl IconSource {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Theme => "theme",
+            Self::WindowDefault => "window-default",
+            Self::Resource => "resource",
+            Self::Embedded => "embedded",
+        }
+    }
+}
+
+fn decode_embedded(bytes: &[u8]) -> Option<Image<'static>> {
+    let rgba = image::load_from_memory(bytes).ok()?.to_rgba8();
+    let (width, height) = rgba.dimensions();
+    Some(Image::new_owned(rgba.into_raw(), width, height))
+}
+
+/// Icon files next to the sources in development, then in the resource dir.
+fn load_resource_icon(app: &AppHandle) -> Option<Image<'static>> {
+    let mut dirs = Vec::new();
+    if let Ok(current_dir) = std::env::current_dir() {
+        dirs.push(current_dir.join("src-tauri").join("icons"));
+    }
+    if let Ok(resource_dir) = app.path().resource_dir() {
+        dirs.push(resource_dir.join("icons"));
+    }
+    dirs.iter()
+        .flat_map(|dir| ["icon.ico", "icon.png"].map(|name| dir.join(name)))
+        .filter(|path| path.exists())
+        .find_map(|path| load_image_from_path(app, &path))
+}
+
+/// The tray icon for `theme`: its variant, else the app icon from the first
+/// source that has one. `None` only if the embedded icon fails to decode.
+fn resolve_icon(app: &AppHandle, theme: TrayIconTheme) -> Option<(Image<'static>, IconSource)> {
+    pick_icon(
+        theme,
+        || {
+            app.default_window_icon()
+                .map(|icon| Image::new_owned(icon.rgba().to_vec(), icon.width(), icon.height()))
+        },
+        || load_resource_icon(app),
+    )
+}
+
+/// `resolve_icon` with the app's sources passed in; each is only asked when
+/// the ones before it had nothing.
+fn pick_icon(
+    theme: TrayIconTheme,
+    window_default: impl FnOnce() -> Option<Image<'static>>,
+    resource: impl FnOnce() -> Option<Image<'static>>,
+) -> Option<(Image<'static>, IconSource)> {
+    let themed = match theme {
+        TrayIconTheme::Light => decode_embedded(LIGHT_THEME_ICON),
+        TrayIconTheme::Dark => decode_embedded(DARK_THEME_ICON),
+        _ => None,
+    };
+    if let Some(icon) = themed {
+        return Some((icon, IconSource::Theme));
+    }
+    if let Some(icon) = window_default() {
+        return Some((icon, IconSource::WindowDefault));
+    }
+    if let Some(icon) = resource() {
+        return Some((icon, IconSource::Resource));
+    }
+    log::warn!(target: "tray", "No icon file resolved, using the embedded icon");
+    decode_embedded(EMBEDDED_ICON).map(|icon| (icon, IconSource::Embedded))
+}
+
 const MENU_SHOW: &str = "show";
 const MENU_HIDE: &str = "hide";
 const MENU_QUIT: &str = "quit";
+const MENU_LAST_RESULT: &str = "last-result";
+const MENU_COPY_LAST_RESULT: &str = "copy-last-result";
+const MENU_PRIVATE_SESSION: &str = "private-session";
 
+const LAST_RESULT_EMPTY_TEXT: &str = "Пока нет расшифровок";
+const PRIVATE_SESSION_TEXT: &str = "Приватная сессия";
+const TOOLTIP: &str = "xexamai";
+const PRIVATE_TOOLTIP: &str = "xexamai — приватная сессия";
+const LAST_RESULT_SNIPPET_CHARS: usize = 60;
+const LAST_RESULT_REFRESH_DEBOUNCE: Duration = Duration::from_millis(300);
+
+static APP: OnceCell<AppHandle> = OnceCell::new();
 static TRAY_ICON: OnceCell<Mutex<Option<TrayIcon>>> = OnceCell::new();
+// Replaced along with the icon when the tray is rebuilt.
+static LAST_RESULT_MENU: Mutex<Option<Submenu<Wry>>> = Mutex::new(None);
+static PRIVATE_SESSION_ITEM: Mutex<Option<CheckMenuItem<Wry>>> = Mutex::new(None);
+static ICON_THEME: Mutex<TrayIconTheme> = Mutex::new(TrayIconTheme::Auto);
+
+/// Most recent transcription, shown in the tray menu without opening the window.
+#[derive(Default)]
+pub struct LastResult {
+    text: Mutex<Option<String>>,
+    revision: AtomicU64,
+}
 
-fn store_tray_icon(icon: TrayIcon) {
+impl LastResult {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self) -> Option<String> {
+        self.text.lock().ok().and_then(|guard| guard.clone())
+    }
+
+    pub fn set(self: &Arc<Self>, app: &AppHandle, text: &str) {
+        let trimmed = text.trim();
+        if trimmed.is_empty() {
+            return;
+        }
+        if let Ok(mut guard) = self.text.lock() {
+            *guard = Some(trimmed.to_string());
+        }
+        // Rapid consecutive transcriptions only refresh the menu once they settle.
+        let revision = self.revision.fetch_add(1, Ordering::SeqCst) + 1;
+        let state = Arc::clone(self);
+        let app_handle = app.clone();
+        tauri::async_runtime::spawn(async move {
+            tokio::time::sleep(LAST_RESULT_REFRESH_DEBOUNCE).await;
+            if state.revision.load(Ordering::SeqCst) != revision {
+                return;
+            }
+            let snapshot = state.get();
+            let _ = app_handle.run_on_main_thread(move || {
+                refresh_last_result_menu(snapshot.as_deref());
+            });
+        });
+    }
+}
+
+fn last_result_snippet(text: &str) -> String {
+    let collapsed = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if collapsed.chars().count() <= LAST_RESULT_SNIPPET_CHARS {
+        return collapsed;
+    }
+    let truncated = collapsed
+        .chars()
+        .take(LAST_RESULT_SNIPPET_CHARS)
+        .collect::<String>();
+    format!("{}…", truncated.trim_end())
+}
+
+fn refresh_last_result_menu(text: Option<&str>) {
+    let guard = LAST_RESULT_MENU.lock().unwrap();
+    let Some(submenu) = guard.as_ref() else {
+        return;
+    };
+    let (label, enabled) = match text {
+        Some(value) => (last_result_snippet(value), true),
+        None => (LAST_RESULT_EMPTY_TEXT.to_string(), false),
+    };
+    if let Err(error) = submenu.set_text(label) {
+        log::warn!(target: "tray", "failed to update last result: {error}");
+    }
+    if let Err(error) = submenu.set_enabled(enabled) {
+        log::warn!(target: "tray", "failed to toggle last result: {error}");
+    }
+}
+
+/// Ticks the private session item and marks the tooltip, so the tray shows
+/// the session is private while the window is hidden.
+pub fn set_private_session(enabled: bool) {
+    let Some(app) = APP.get() else {
+        return;
+    };
+    let _ = app.run_on_main_thread(move || {
+        if let Some(item) = PRIVATE_SESSION_ITEM.lock().unwrap().as_ref() {
+            if let Err(error) = item.set_checked(enabled) {
+                log::warn!(target: "tray", "failed to tick private session: {error}");
+            }
+        }
+        if let Some(tray) = TRAY_ICON
+            .get()
+            .and_then(|mutex| mutex.lock().ok())
+            .and_then(|guard| guard.clone())
+        {
+            let tooltip = if enabled { PRIVATE_TOOLTIP } else { TOOLTIP };
+            if let Err(error) = tray.set_tooltip(Some(tooltip)) {
+                log::warn!(target: "tray", "failed to set tooltip: {error}");
+            }
+        }
+    });
+}
+
+fn copy_last_result(app: &AppHandle) {
+    let Some(text) = app
+        .try_state::<Arc<LastResult>>()
+        .and_then(|state| state.get())
+    else {
+        return;
+    };
+    if let Err(error) = app.clipboard().write_text(text) {
+        log::warn!(target: "tray", "failed to copy last result: {error}");
+    }
+}
+
+fn store_tray_icon(icon: TrayIcon) -> Option<TrayIcon> {
     TRAY_ICON
         .get_or_init(|| Mutex::new(None))
         .lock()
         .ok()
-        .map(|mut guard| {
-            *guard = Some(icon);
-        });
+        .and_then(|mut guard| guard.replace(icon))
 }
 
 pub fn set_tray_visible(visible: bool) {
@@ -38,64 +264,117 @@ pub fn set_tray_visible(visible: bool) {
         if let Ok(guard) = mutex.lock() {
             if let Some(tray) = guard.as_ref() {
                 if let Err(error) = tray.set_visible(visible) {
-                    eprintln!("[tray] failed to set visibility: {error}");
+                    log::warn!(target: "tray", "failed to set visibility: {error}");
                 }
             }
         }
     }
 }
 
+fn current_theme() -> TrayIconTheme {
+    *ICON_THEME.lock().unwrap()
+}
+
+/// Follows `trayIconTheme`, swapping the icon when it changed.
+pub fn apply_theme(app: &AppHandle, theme: TrayIconTheme) {
+    let previous = std::mem::replace(&mut *ICON_THEME.lock().unwrap(), theme);
+    if previous != theme {
+        if let Err(error) = reload_icon(app) {
+            log::warn!(target: "tray", "Failed to swap the tray icon: {error}");
+        }
+    }
+}
+
+/// Resolves the icon again and puts it on the existing tray.
+pub fn reload_icon(app: &AppHandle) -> tauri::Result<IconSource> {
+    let (icon, source) = resolve_icon(app, current_theme())
+        .ok_or_else(|| tauri::Error::AssetNotFound("tray icon".into()))?;
+    if let Some(tray) = TRAY_ICON
+        .get()
+        .and_then(|mutex| mutex.lock().ok())
+        .and_then(|guard| guard.clone())
+    {
+        tray.set_icon(Some(icon))?;
+    }
+    log::info!(target: "tray", "Tray icon reloaded from {}", source.as_str());
+    Ok(source)
+}
+
+/// Called from the main window procedure on `TaskbarCreated`. A restarted
+/// Explorer starts with an empty notification area, and with `hide_app` on the
+/// tray icon is the only way back into the app, so the icon and its menu are
+/// built again and the taskbar state of the window is re-applied.
+#[cfg_attr(not(windows), allow(dead_code))]
+pub fn notify_shell_restarted() {
+    let Some(app) = APP.get() else {
+        return;
+    };
+    let result = setup(app);
+    match &result {
+        Ok(()) => log::info!(target: "tray", "Rebuilt tray icon after Explorer restart"),
+        Err(error) => {
+            error_bus::publish(app, "tray", ErrorCode::TrayFailed, error.to_string(), true);
+        }
+    }
+    let _ = emit_event(
+        app,
+        events::TRAY_REBUILT,
+        serde_json::json!({ "ok": result.is_ok() }),
+    );
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let Some(state) = app.try_state::<Arc<ConfigState>>() else {
+            return;
+        };
+        let config = state.get().await;
+        let _ = app.clone().run_on_main_thread(move || {
+            set_tray_visible(!config.hide_app);
+            reapply_shell_window_state(&app, &config);
+        });
+    });
+}
+
+/// Builds the tray icon and menu, replacing any previous icon.
 pub fn setup(app: &AppHandle) -> tauri::Result<()> {
+    let _ = APP.set(app.clone());
+    let last_result_menu = SubmenuBuilder::with_id(app, MENU_LAST_RESULT, LAST_RESULT_EMPTY_TEXT)
+        .item(
+            &MenuItemBuilder::with_id(MENU_COPY_LAST_RESULT, "Скопировать полный текст")
+                .build(app)?,
+        )
+        .enabled(false)
+        .build()?;
+    let private_session_item =
+        CheckMenuItemBuilder::with_id(MENU_PRIVATE_SESSION, PRIVATE_SESSION_TEXT)
+            .checked(private_session::is_active())
+            .build(app)?;
+
     let menu = MenuBuilder::new(app)
+        .item(&last_result_menu)
+        .item(&private_session_item)
+        .separator()
         .item(&MenuItemBuilder::with_id(MENU_SHOW, "Показать окно").build(app)?)
         .item(&MenuItemBuilder::with_id(MENU_HIDE, "Скрыть окно").build(app)?)
         .item(&MenuItemBuilder::with_id(MENU_QUIT, "Выход").build(app)?)
         .build()?;
 
-    let loaded_icon: Option<Image<'static>> = if let Some(default_icon) = app.default_window_icon()
-    {
-        let rgba_data = default_icon.rgba();
-        let width = default_icon.width();
-        let height = default_icon.height();
-        Some(Image::new_owned(rgba_data.to_vec(), width, height))
+    let mut builder = TrayIconBuilder::new().tooltip(if private_session::is_active() {
+        PRIVATE_TOOLTIP
     } else {
-        let mut found_icon = None;
-        if let Ok(current_dir) = std::env::current_dir() {
-            for candidate in ["icon.ico", "icon.png"] {
-                let path = current_dir.join("src-tauri").join("icons").join(candidate);
-                if path.exists() {
-                    found_icon = load_image_from_path(&path);
-                }
-                if found_icon.is_some() {
-                    break;
-                }
-            }
-        }
-        if found_icon.is_none() {
-            found_icon = app
-                .path()
-                .resource_dir()
-                .ok()
-                .and_then(|dir| {
-                    let ico = dir.join("icons").join("icon.ico");
-                    if ico.exists() {
-                        load_image_from_path(&ico)
-                    } else {
-                        let png = dir.join("icons").join("icon.png");
-                        if png.exists() {
-                            load_image_from_path(&png)
-                        } else {
-                            None
-                        }
-                    }
-                });
-        }
-        found_icon
-    };
-
-    let mut builder = TrayIconBuilder::new();
-    if let Some(icon) = loaded_icon {
+        TOOLTIP
+    });
+    if let Some((icon, source)) = resolve_icon(app, current_theme()) {
+        log::info!(target: "tray", "Tray icon source: {}", source.as_str());
         builder = builder.icon(icon);
+    } else {
+        error_bus::publish(
+            app,
+            "tray",
+            ErrorCode::TrayFailed,
+            "no icon could be loaded",
+            true,
+        );
     }
 
     let tray_icon = builder
@@ -103,16 +382,18 @@ pub fn setup(app: &AppHandle) -> tauri::Result<()> {
         .on_menu_event(|app, event| match event.id().as_ref() {
             MENU_SHOW => {
                 if let Err(error) = show_main_window(app) {
-                    eprintln!("Failed to show window from tray: {error}");
+                    log::warn!(target: "tray", "Failed to show window from tray: {error}");
                 }
             }
             MENU_HIDE => {
                 if let Some(window) = app.get_webview_window("main") {
                     if let Err(error) = window.hide() {
-                        eprintln!("Failed to hide window from tray: {error}");
+                        log::warn!(target: "tray", "Failed to hide window from tray: {error}");
                     }
                 }
             }
+            MENU_COPY_LAST_RESULT => copy_last_result(app),
+            MENU_PRIVATE_SESSION => private_session::toggle(app),
             MENU_QUIT => {
                 app.exit(0);
             }
@@ -120,7 +401,86 @@ pub fn setup(app: &AppHandle) -> tauri::Result<()> {
         })
         .build(app)?;
 
-    store_tray_icon(tray_icon);
+    if let Some(previous) = store_tray_icon(tray_icon) {
+        app.remove_tray_by_id(previous.id());
+    }
+    *LAST_RESULT_MENU.lock().unwrap() = Some(last_result_menu);
+    *PRIVATE_SESSION_ITEM.lock().unwrap() = Some(private_session_item);
+    let initial_result = app
+        .try_state::<Arc<LastResult>>()
+        .and_then(|state| state.get());
+    refresh_last_result_menu(initial_result.as_deref());
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn blank(size: u32) -> Image<'static> {
+        Image::new_owned(vec![0; (size * size * 4) as usize], size, size)
+    }
+
+    fn unused() -> Option<Image<'static>> {
+        panic!("an earlier source already had an icon")
+    }
+
+    fn picked(
+        theme: TrayIconTheme,
+        window_default: impl FnOnce() -> Option<Image<'static>>,
+        resource: impl FnOnce() -> Option<Image<'static>>,
+    ) -> (IconSource, u32) {
+        let (icon, source) = pick_icon(theme, window_default, resource).unwrap();
+        (source, icon.width())
+    }
+
+    #[test]
+    fn falls_back_from_the_window_icon_to_files_to_the_embedded_icon() {
+        let auto = TrayIconTheme::Auto;
+        assert_eq!(
+            picked(auto, || Some(blank(1)), unused),
+            (IconSource::WindowDefault, 1)
+        );
+        assert_eq!(
+            picked(auto, || None, || Some(blank(2))),
+            (IconSource::Resource, 2)
+        );
+        let embedded = decode_embedded(EMBEDDED_ICON).unwrap();
+        assert_eq!(
+            picked(auto, || None, || None),
+            (IconSource::Embedded, embedded.width())
+        );
+    }
+
+    #[test]
+    fn light_and_dark_themes_use_their_variant() {
+        for (theme, bytes) in [
+            (TrayIconTheme::Light, LIGHT_THEME_ICON),
+            (TrayIconTheme::Dark, DARK_THEME_ICON),
+        ] {
+            let (icon, source) = pick_icon(theme, unused, unused).unwrap();
+            assert_eq!(source, IconSource::Theme);
+            assert_eq!(icon.rgba(), decode_embedded(bytes).unwrap().rgba());
+        }
+        assert_ne!(
+            decode_embedded(LIGHT_THEME_ICON).unwrap().rgba(),
+            decode_embedded(DARK_THEME_ICON).unwrap().rgba()
+        );
+    }
+
+    #[test]
+    fn source_names_match_the_wire_format() {
+        for source in [
+            IconSource::Theme,
+            IconSource::WindowDefault,
+            IconSource::Resource,
+            IconSource::Embedded,
+        ] {
+            assert_eq!(
+                serde_json::to_value(source).unwrap(),
+                serde_json::json!(source.as_str())
+            );
+        }
+    }
+}
@@ -1,21 +1,202 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
 
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
 use crate::constants::{
-    BACKEND_DOMAIN_RU, DEFAULT_API_LLM_TIMEOUT_MS, DEFAULT_API_STT_TIMEOUT_MS,
-    DEFAULT_AUDIO_INPUT_TYPE, DEFAULT_BACKEND_DOMAIN, DEFAULT_DURATIONS, DEFAULT_LLM_HOST,
-    DEFAULT_LLM_PROMPT, DEFAULT_LOCAL_DEVICE, DEFAULT_LOCAL_LLM_MODEL, DEFAULT_LOCAL_WHISPER_MODEL,
-    DEFAULT_OPENAI_MODEL, DEFAULT_OPENAI_TRANSCRIPTION_MODEL, DEFAULT_SCREEN_PROCESSING_TIMEOUT_MS,
-    DEFAULT_SCREEN_PROMPT, DEFAULT_SCREEN_PROVIDER, DEFAULT_STREAM_SEND_HOTKEY,
-    DEFAULT_TOGGLE_INPUT_HOTKEY, DEFAULT_TRANSCRIPTION_MODE, DEFAULT_TRANSCRIPTION_PROMPT,
-    DEFAULT_WINDOW_HEIGHT, DEFAULT_WINDOW_MIN_HEIGHT, DEFAULT_WINDOW_MIN_WIDTH,
-    DEFAULT_WINDOW_OPACITY, DEFAULT_WINDOW_SCALE, DEFAULT_WINDOW_WIDTH,
+    BACKEND_DOMAIN_RU, DEFAULT_AGC_TARGET_DB, DEFAULT_API_LLM_TIMEOUT_MS,
+    DEFAULT_API_STT_TIMEOUT_MS, DEFAULT_AUDIO_INPUT_TYPE, DEFAULT_AUTO_TYPE_WPM,
+    DEFAULT_BACKEND_DOMAIN, DEFAULT_DUCKING_DB, DEFAULT_DURATIONS,
+    DEFAULT_GOOGLE_TRANSCRIPTION_FORMAT, DEFAULT_INDICATOR_CORNER, DEFAULT_INTEGRATION_SERVER_PORT,
+    DEFAULT_LANGUAGE_DETECTION, DEFAULT_LLM_HOST, DEFAULT_LLM_PROMPT, DEFAULT_LOCAL_DEVICE,
+    DEFAULT_LOCAL_LLM_MODEL, DEFAULT_LOCAL_WHISPER_MODEL, DEFAULT_LOG_LEVEL,
+    DEFAULT_MAX_PENDING_CLIPS, DEFAULT_MIC_GAIN, DEFAULT_OPENAI_MODEL,
+    DEFAULT_OPENAI_TRANSCRIPTION_MODEL, DEFAULT_PRE_ROLL_SOURCE,
+    DEFAULT_SCREEN_PROCESSING_TIMEOUT_MS, DEFAULT_SCREEN_PROMPT, DEFAULT_SCREEN_PROVIDER,
+    DEFAULT_SLOW_COMMAND_MS, DEFAULT_STREAM_MODE, DEFAULT_STREAM_SEND_HOTKEY,
+    DEFAULT_SUMMARY_PROMPT, DEFAULT_TOGGLE_INPUT_HOTKEY, DEFAULT_TRANSCRIPTION_MODE,
+    DEFAULT_TRANSCRIPTION_PROMPT, DEFAULT_TRANSCRIPTION_SAMPLE_RATE, DEFAULT_TRAY_ICON_THEME,
+    DEFAULT_UI_LANGUAGE, DEFAULT_VAD_THRESHOLD_DB, DEFAULT_WINDOW_HEIGHT,
+    DEFAULT_WINDOW_MIN_HEIGHT, DEFAULT_WINDOW_MIN_WIDTH, DEFAULT_WINDOW_OPACITY,
+    DEFAULT_WINDOW_SCALE, DEFAULT_WINDOW_WIDTH, MAX_AGC_TARGET_DB, MAX_AUTO_SUMMARIZE_MINUTES,
+    MAX_AUTO_TYPE_WPM, MAX_DUCKING_DB, MAX_DURATION_SECONDS, MAX_LLM_CONTEXT,
+    MAX_LLM_STOP_SEQUENCES, MAX_LLM_TEMPERATURE, MAX_MIC_GAIN, MAX_PENDING_CLIPS_LIMIT,
+    MAX_PRE_ROLL_SECONDS, MAX_STT_SPEEDUP_FACTOR, MAX_VAD_THRESHOLD_DB, MAX_WINDOW_SCALE,
+    MIN_AGC_TARGET_DB, MIN_AUTO_TYPE_WPM, MIN_DUCKING_DB, MIN_DURATION_SECONDS, MIN_LLM_CONTEXT,
+    MIN_MIC_GAIN, MIN_STT_SPEEDUP_FACTOR, MIN_VAD_THRESHOLD_DB, MIN_WINDOW_SCALE,
+    MOCK_PROVIDERS_ENV_VAR, SUPPORTED_UI_LANGUAGES, TRANSCRIPTION_SAMPLE_RATES,
 };
+use crate::prompt_template;
+use crate::text_sanitize;
 
-const VALID_LOCAL_DEVICES: &[&str] = &["auto", "cpu", "cuda", "metal", "gpu"];
+const VALID_LOG_LEVELS: &[&str] = &["error", "warn", "info", "debug", "trace"];
+
+/// Declares a string-valued config enum. Input is trimmed and lowercased, legacy
+/// spellings map onto their current variant, and anything else becomes `Unknown`
+/// so a value written by a newer build does not fail the whole config load.
+macro_rules! config_enum {
+    (
+        $(#[$meta:meta])*
+        pub enum $name:ident {
+            $($variant:ident => $value:literal $(| $legacy:literal)*,)+
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub enum $name {
+            $($variant,)+
+            Unknown,
+        }
+
+        impl $name {
+            /// Every accepted spelling, current and legacy, with its variant.
+            #[cfg(test)]
+            const SPELLINGS: &'static [(&'static str, Self)] =
+                &[$(($value, Self::$variant), $(($legacy, Self::$variant),)*)+];
+
+            pub fn as_str(self) -> &'static str {
+                match self {
+                    $(Self::$variant => $value,)+
+                    Self::Unknown => "unknown",
+                }
+            }
+
+            pub fn parse(value: &str) -> Self {
+                match value.trim().to_lowercase().as_str() {
+                    $($value $(| $legacy)* => Self::$variant,)+
+                    _ => Self::Unknown,
+                }
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: serde::Serializer,
+            {
+                serializer.serialize_str(self.as_str())
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                Ok(Self::parse(&String::deserialize(deserializer)?))
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+    };
+}
+
+config_enum! {
+    /// Where transcription runs. `Google` is only valid per request, not in config;
+    /// `Mock` only while mock providers are enabled.
+    pub enum TranscriptionMode {
+        Api => "api" | "openai" | "cloud",
+        Local => "local" | "whisper" | "fast-whisper" | "faster-whisper",
+        Google => "google" | "gemini",
+        Mock => "mock",
+    }
+}
+
+config_enum! {
+    pub enum LlmHost {
+        Api => "api" | "openai" | "cloud",
+        Local => "local" | "ollama",
+    }
+}
+
+config_enum! {
+    /// `mic` is what the capture commands have always sent.
+    pub enum AudioInputType {
+        Microphone => "microphone" | "mic",
+        System => "system" | "loopback",
+        Mixed => "mixed" | "both",
+    }
+}
+
+config_enum! {
+    pub enum LocalDevice {
+        Auto => "auto",
+        Cpu => "cpu",
+        Cuda => "cuda",
+        Metal => "metal" | "mps",
+        Gpu => "gpu",
+    }
+}
+
+config_enum! {
+    /// Screen corner of the recording indicator.
+    pub enum IndicatorCorner {
+        TopLeft => "topleft" | "top-left",
+        TopRight => "topright" | "top-right",
+        BottomLeft => "bottomleft" | "bottom-left",
+        BottomRight => "bottomright" | "bottom-right",
+    }
+}
+
+config_enum! {
+    /// Tray icon variant. `Auto` keeps the full-color app icon; `Light` and
+    /// `Dark` are monochrome icons for a light or dark taskbar.
+    pub enum TrayIconTheme {
+        Auto => "auto",
+        Light => "light",
+        Dark => "dark",
+    }
+}
+
+config_enum! {
+    /// `Base` sends clips on the duration hotkeys; `Stream` transcribes live and
+    /// sends the accumulated text on `stream_send_hotkey`.
+    pub enum StreamMode {
+        Base => "base",
+        Stream => "stream" | "streaming",
+    }
+}
+
+config_enum! {
+    /// How Gemini is asked to return transcripts: a `{"text": ...}` object, or
+    /// free text for models that reject a response schema.
+    pub enum GoogleTranscriptionFormat {
+        Json => "json",
+        Text => "text" | "plain",
+    }
+}
+
+config_enum! {
+    /// How the spoken language is found before transcribing, to pick its
+    /// `language_profiles` entry. `Whisper` asks the local server's tiny model;
+    /// `Draft` transcribes the start of the clip and guesses from the text.
+    pub enum LanguageDetection {
+        Off => "off" | "none",
+        Whisper => "whisper" | "local",
+        Draft => "draft" | "text",
+    }
+}
+
+config_enum! {
+    /// What the pre-roll capture records while capture is stopped.
+    pub enum PreRollSource {
+        Mic => "mic" | "microphone",
+        Mixed => "mixed" | "loopback",
+    }
+}
+
+config_enum! {
+    pub enum ScreenProcessingProvider {
+        Openai => "openai" | "gpt",
+        Google => "google" | "gemini",
+    }
+}
 
 fn default_durations() -> Vec<u32> {
     DEFAULT_DURATIONS.to_vec()
@@ -34,16 +215,32 @@ fn default_toggle_hotkey() -> String {
     DEFAULT_TOGGLE_INPUT_HOTKEY.to_string()
 }
 
-fn default_audio_input_type() -> String {
-    DEFAULT_AUDIO_INPUT_TYPE.to_string()
+fn default_audio_input_type() -> AudioInputType {
+    AudioInputType::parse(DEFAULT_AUDIO_INPUT_TYPE)
 }
 
-fn default_transcription_model() -> String {
-    DEFAULT_OPENAI_TRANSCRIPTION_MODEL.to_string()
+fn default_indicator_corner() -> IndicatorCorner {
+    IndicatorCorner::parse(DEFAULT_INDICATOR_CORNER)
 }
 
-fn default_llm_model() -> String {
-    DEFAULT_OPENAI_MODEL.to_string()
+fn default_tray_icon_theme() -> TrayIconTheme {
+    TrayIconTheme::parse(DEFAULT_TRAY_ICON_THEME)
+}
+
+fn default_pre_roll_source() -> PreRollSource {
+    PreRollSource::parse(DEFAULT_PRE_ROLL_SOURCE)
+}
+
+fn default_google_transcription_format() -> GoogleTranscriptionFormat {
+    GoogleTranscriptionFormat::parse(DEFAULT_GOOGLE_TRANSCRIPTION_FORMAT)
+}
+
+fn default_language_detection() -> LanguageDetection {
+    LanguageDetection::parse(DEFAULT_LANGUAGE_DETECTION)
+}
+
+fn default_transcription_model() -> String {
+    DEFAULT_OPENAI_TRANSCRIPTION_MODEL.to_string()
 }
 
 fn default_api_llm_model() -> String {
@@ -58,20 +255,20 @@ fn default_llm_prompt() -> String {
     DEFAULT_LLM_PROMPT.to_string()
 }
 
-fn default_transcription_mode() -> String {
-    DEFAULT_TRANSCRIPTION_MODE.to_string()
+fn default_transcription_mode() -> TranscriptionMode {
+    TranscriptionMode::parse(DEFAULT_TRANSCRIPTION_MODE)
 }
 
-fn default_llm_host() -> String {
-    DEFAULT_LLM_HOST.to_string()
+fn default_llm_host() -> LlmHost {
+    LlmHost::parse(DEFAULT_LLM_HOST)
 }
 
 fn default_local_whisper_model() -> String {
     DEFAULT_LOCAL_WHISPER_MODEL.to_string()
 }
 
-fn default_local_device() -> String {
-    DEFAULT_LOCAL_DEVICE.to_string()
+fn default_local_device() -> LocalDevice {
+    LocalDevice::parse(DEFAULT_LOCAL_DEVICE)
 }
 
 fn default_window_scale() -> f32 {
@@ -82,8 +279,16 @@ fn default_stream_hotkey() -> String {
     DEFAULT_STREAM_SEND_HOTKEY.to_string()
 }
 
-fn default_screen_model() -> String {
-    DEFAULT_SCREEN_PROVIDER.to_string()
+fn default_stream_mode() -> StreamMode {
+    StreamMode::parse(DEFAULT_STREAM_MODE)
+}
+
+fn default_screen_model() -> ScreenProcessingProvider {
+    ScreenProcessingProvider::parse(DEFAULT_SCREEN_PROVIDER)
+}
+
+fn default_summary_prompt() -> String {
+    DEFAULT_SUMMARY_PROMPT.to_string()
 }
 
 fn default_screen_prompt() -> String {
@@ -94,6 +299,22 @@ fn default_backend_domain() -> String {
     DEFAULT_BACKEND_DOMAIN.to_string()
 }
 
+fn default_log_level() -> String {
+    DEFAULT_LOG_LEVEL.to_string()
+}
+
+fn default_ui_language() -> String {
+    DEFAULT_UI_LANGUAGE.to_string()
+}
+
+fn default_transcription_sample_rate() -> u32 {
+    DEFAULT_TRANSCRIPTION_SAMPLE_RATE
+}
+
+fn default_stt_speedup_factor() -> f32 {
+    MIN_STT_SPEEDUP_FACTOR
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct AppConfig {
@@ -107,38 +328,166 @@ pub struct AppConfig {
     pub durations: Vec<u32>,
     #[serde(default = "default_duration_hotkeys")]
     pub duration_hotkeys: BTreeMap<u32, String>,
+    /// Duration presses held back while a transcription runs. Once full, a new
+    /// press replaces the newest pending one, so `1` keeps only the latest.
+    #[serde(default = "default_max_pending_clips")]
+    pub max_pending_clips: u32,
     #[serde(default = "default_toggle_hotkey")]
     pub toggle_input_hotkey: String,
+    /// Input device for microphone mode; `None` is the system default.
+    #[serde(default)]
+    pub mic_device_id: Option<String>,
+    /// Microphone mixed with system audio in mixed mode; falls back to `mic_device_id`.
     #[serde(default)]
+    pub mixed_mic_device_id: Option<String>,
+    /// Loopback/monitor device for system and mixed modes where WASAPI is unavailable.
+    #[serde(default)]
+    pub system_loopback_device_id: Option<String>,
+    /// Pre-split single device field, read once and moved into `mic_device_id`.
+    #[serde(default, skip_serializing)]
     pub audio_input_device_id: Option<String>,
     #[serde(default = "default_audio_input_type")]
-    pub audio_input_type: String,
+    pub audio_input_type: AudioInputType,
+    /// Start capturing with the saved input type and devices when the app launches.
+    #[serde(default)]
+    pub auto_start_capture: bool,
+    /// Seconds kept from a capture that runs while recording is stopped, and put
+    /// ahead of the audio once it starts. 0 turns the pre-roll off.
+    #[serde(default)]
+    pub pre_roll_seconds: u32,
+    #[serde(default = "default_pre_roll_source")]
+    pub pre_roll_source: PreRollSource,
+    /// Linear gain on the microphone before it is mixed and encoded.
+    #[serde(default = "default_mic_gain")]
+    pub mic_gain: f32,
+    /// Audio below this level, in dBFS after `mic_gain`, counts as silence.
+    #[serde(default = "default_vad_threshold_db")]
+    pub vad_threshold_db: f32,
+    /// Speech level, in dBFS, that gain adjustments aim for.
+    #[serde(default = "default_agc_target_db")]
+    pub agc_target_db: f32,
+    /// In a mixed capture, lowers the system audio by `ducking_db` while the mic
+    /// is above `vad_threshold_db`.
+    #[serde(default)]
+    pub ducking_enabled: bool,
+    #[serde(default = "default_ducking_db")]
+    pub ducking_db: f32,
+    /// Phrases spotted in captured audio through the local Whisper server. Detection
+    /// is off while the list is empty.
+    #[serde(default)]
+    pub wake_words: Vec<String>,
+    /// What a spotted wake word does besides emitting `audio:wake-word`.
+    #[serde(default)]
+    pub wake_word_action: Option<WakeWordAction>,
+    /// Run the capture, mixing and encoder threads above normal priority.
+    #[serde(default = "default_audio_priority_boost")]
+    pub audio_priority_boost: bool,
+    /// Also register the WASAPI loopback thread with MMCSS as "Pro Audio".
+    /// Windows only, and only with `audio_priority_boost`.
+    #[serde(default)]
+    pub audio_mmcss: bool,
     #[serde(default = "default_transcription_model")]
     pub transcription_model: String,
     #[serde(default = "default_transcription_prompt")]
     pub transcription_prompt: String,
-    #[serde(default = "default_llm_model")]
-    pub llm_model: String,
+    /// Replaces the prompt for one provider; unset entries use the request's prompt.
+    #[serde(default)]
+    pub transcription_prompt_overrides: TranscriptionPromptOverrides,
+    /// Whisper models on the local server tend to hallucinate the prompt into quiet
+    /// clips, so it is left out unless this is on.
+    #[serde(default)]
+    pub send_prompt_to_local: bool,
+    #[serde(default = "default_google_transcription_format")]
+    pub google_transcription_format: GoogleTranscriptionFormat,
+    /// Spoken language as an ISO 639-1 code, sent to the Whisper providers.
+    /// While set, no detection runs.
+    #[serde(default)]
+    pub transcription_language: Option<String>,
+    #[serde(default = "default_language_detection")]
+    pub language_detection: LanguageDetection,
+    /// Language transcripts are translated into after transcription, as a code
+    /// or an English name; `None` turns translation off.
+    #[serde(default)]
+    pub translate_to: Option<String>,
+    /// Model for those translations in place of the chat model, on the same host.
+    #[serde(default)]
+    pub translation_model: Option<String>,
+    /// Prompt and model per spoken language, keyed by ISO 639-1 code.
+    #[serde(default)]
+    pub language_profiles: BTreeMap<String, LanguageProfile>,
+    /// Requests per minute allowed per provider, shared by transcription and chat.
+    #[serde(default)]
+    pub provider_rate_limits: ProviderRateLimits,
+    /// Appends every finished transcript to `transcripts-YYYY-MM-DD.txt`.
+    #[serde(default)]
+    pub plain_text_log_enabled: bool,
+    /// Folder for the plain-text transcript log; unset uses `transcripts` in the
+    /// app data dir.
+    #[serde(default)]
+    pub plain_text_log_dir: Option<String>,
+    /// Write-only alias for the model of the active host. Older configs persisted it,
+    /// and `config_update` still accepts it; `normalize` moves it into `api_llm_model`
+    /// or `local_llm_model`. Read the model through `effective_llm_model`.
+    #[serde(default, skip_serializing)]
+    pub llm_model: Option<String>,
     #[serde(default = "default_api_llm_model")]
     pub api_llm_model: String,
     #[serde(default = "default_local_llm_model")]
     pub local_llm_model: String,
     #[serde(default = "default_llm_prompt")]
     pub llm_prompt: String,
+    /// Custom `{{name}}` values for the LLM prompt, next to the built-in ones.
+    #[serde(default)]
+    pub prompt_variables: BTreeMap<String, String>,
+    /// Add the title and app of the window in front to the system prompt.
+    #[serde(default)]
+    pub include_window_context: bool,
+    /// Minutes between rolling summaries of the session while capture runs; 0 is off.
+    #[serde(default)]
+    pub auto_summarize_every_min: u32,
+    /// System prompt for those summaries, used in place of `llm_prompt`.
+    #[serde(default = "default_summary_prompt")]
+    pub summary_prompt: String,
+    /// Generation options sent with every LLM request; unset ones keep the
+    /// provider's defaults.
+    #[serde(default)]
+    pub local_llm_options: LocalLlmOptions,
+    /// Copy the answer to the clipboard while it streams, for clipboard sync tools.
+    #[serde(default)]
+    pub llm_stream_to_clipboard: bool,
+    /// Allows `type_text` to send keystrokes to the focused app. Off by default since
+    /// a stray call types into whatever has focus.
+    #[serde(default)]
+    pub enable_auto_type: bool,
+    #[serde(default = "default_auto_type_wpm")]
+    pub auto_type_wpm: u32,
     #[serde(default = "default_transcription_mode")]
-    pub transcription_mode: String,
+    pub transcription_mode: TranscriptionMode,
     #[serde(default = "default_llm_host")]
-    pub llm_host: String,
+    pub llm_host: LlmHost,
     #[serde(default = "default_local_whisper_model")]
     pub local_whisper_model: String,
     #[serde(default = "default_local_device")]
-    pub local_device: String,
+    pub local_device: LocalDevice,
     #[serde(default)]
     pub window_opacity: u32,
     #[serde(default)]
     pub always_on_top: bool,
     #[serde(default = "default_hide_app")]
     pub hide_app: bool,
+    /// Notify when capture starts while the window is visible to screen capture.
+    #[serde(default = "default_warn_when_not_hidden")]
+    pub warn_when_not_hidden: bool,
+    /// Keep the window out of Alt-Tab and the task switcher.
+    #[serde(default)]
+    pub hide_from_switcher: bool,
+    /// Small "REC" window shown while capture runs, even when the main window is hidden.
+    #[serde(default)]
+    pub show_recording_indicator: bool,
+    #[serde(default = "default_indicator_corner")]
+    pub recording_indicator_corner: IndicatorCorner,
+    #[serde(default = "default_tray_icon_theme")]
+    pub tray_icon_theme: TrayIconTheme,
     #[serde(default)]
     pub welcome_modal_dismissed: bool,
     #[serde(default = "default_window_width")]
@@ -153,20 +502,241 @@ pub struct AppConfig {
     pub api_llm_timeout_ms: u32,
     #[serde(default = "default_screen_timeout")]
     pub screen_processing_timeout_ms: u32,
+    /// Commands that take longer are logged and reported as `perf:slow-command`.
+    #[serde(default = "default_slow_command_ms")]
+    pub slow_command_ms: u32,
     #[serde(default = "default_stream_hotkey")]
     pub stream_send_hotkey: String,
+    /// Turns the private session on and off; empty for none.
+    #[serde(default)]
+    pub private_session_hotkey: String,
+    #[serde(default = "default_stream_mode")]
+    pub stream_mode: StreamMode,
     #[serde(default = "default_screen_model")]
-    pub screen_processing_model: String,
+    pub screen_processing_model: ScreenProcessingProvider,
     #[serde(default = "default_screen_prompt")]
     pub screen_processing_prompt: String,
     #[serde(default)]
     pub save_recorder_files: bool,
+    /// Encrypts debug recordings and the transcript log with a keychain-held key.
+    #[serde(default)]
+    pub encrypt_local_artifacts: bool,
+    #[serde(default)]
+    pub active_account: Option<String>,
+    #[serde(default)]
+    pub http_proxy: Option<String>,
+    #[serde(default)]
+    pub api_debug_logging: bool,
+    #[serde(default = "default_log_level")]
+    pub log_level: String,
+    #[serde(default = "default_check_updates_on_startup")]
+    pub check_updates_on_startup: bool,
+    #[serde(default)]
+    pub integration_server_enabled: bool,
+    #[serde(default = "default_integration_server_port")]
+    pub integration_server_port: u16,
+    #[serde(default)]
+    pub onboarding_completed_steps: Vec<String>,
+    #[serde(default)]
+    pub sound_theme: Option<String>,
+    #[serde(default)]
+    pub sound_cues: BTreeMap<String, String>,
+    #[serde(default = "default_ui_language")]
+    pub ui_language: String,
+    /// WAV payloads above this rate are downsampled before transcription.
+    #[serde(default = "default_transcription_sample_rate")]
+    pub transcription_sample_rate: u32,
+    /// WAV payloads are played this much faster before transcription, 1.0–2.0.
+    #[serde(default = "default_stt_speedup_factor")]
+    pub stt_speedup_factor: f32,
+    /// Speed up by relabelling the sample rate, which raises the pitch, instead
+    /// of the pitch-preserving WSOLA stretch.
+    #[serde(default)]
+    pub stt_speedup_relabel: bool,
+    /// Debug copies (`saveRecorderFiles`) keep the captured rate instead of the sent one.
+    #[serde(default)]
+    pub recorder_files_original_rate: bool,
+    /// Hidden test switches; only written to the file once something is set.
+    #[serde(default, skip_serializing_if = "MockProviderSettings::is_default")]
+    pub mock_providers: MockProviderSettings,
+}
+
+/// Ollama's generation `options`. The OpenAI provider maps the ones it has an
+/// equivalent for and ignores `num_ctx`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LocalLlmOptions {
+    pub temperature: Option<f32>,
+    pub top_p: Option<f32>,
+    /// Context window in tokens.
+    pub num_ctx: Option<u32>,
+    /// Most tokens to generate; -1 is unlimited.
+    pub num_predict: Option<i32>,
+    pub stop: Vec<String>,
+    pub seed: Option<i64>,
+}
+
+impl LocalLlmOptions {
+    /// Drops values out of range with a warning, so a bad entry falls back to
+    /// the provider default instead of failing every request.
+    fn normalize(&mut self) {
+        fn keep<T: Copy + fmt::Display>(
+            field: &str,
+            value: &mut Option<T>,
+            valid: impl Fn(T) -> bool,
+        ) {
+            if let Some(current) = *value {
+                if !valid(current) {
+                    log::warn!(target: "config", "localLlmOptions.{field} = {current} is out of range; ignored");
+                    *value = None;
+                }
+            }
+        }
+        keep("temperature", &mut self.temperature, |value| {
+            (0.0..=MAX_LLM_TEMPERATURE).contains(&value)
+        });
+        keep("topP", &mut self.top_p, |value| {
+            (0.0..=1.0).contains(&value)
+        });
+        keep("numCtx", &mut self.num_ctx, |value| {
+            (MIN_LLM_CONTEXT..=MAX_LLM_CONTEXT).contains(&value)
+        });
+        keep("numPredict", &mut self.num_predict, |value| {
+            value == -1 || value > 0
+        });
+        let mut seen = HashSet::new();
+        self.stop
+            .retain(|stop| !stop.is_empty() && seen.insert(stop.clone()));
+        if self.stop.len() > MAX_LLM_STOP_SEQUENCES {
+            log::warn!(
+                target: "config",
+                "localLlmOptions.stop has more than {MAX_LLM_STOP_SEQUENCES} entries; the rest were dropped"
+            );
+            self.stop.truncate(MAX_LLM_STOP_SEQUENCES);
+        }
+    }
+}
+
+/// Per-provider transcription prompts, keyed like the provider settings.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TranscriptionPromptOverrides {
+    pub openai: Option<String>,
+    pub google: Option<String>,
+    pub local: Option<String>,
+}
+
+impl TranscriptionPromptOverrides {
+    pub fn for_mode(&self, mode: TranscriptionMode) -> Option<&str> {
+        match mode {
+            TranscriptionMode::Api => self.openai.as_deref(),
+            TranscriptionMode::Google => self.google.as_deref(),
+            TranscriptionMode::Local => self.local.as_deref(),
+            TranscriptionMode::Mock | TranscriptionMode::Unknown => None,
+        }
+    }
+}
+
+/// Transcription settings for one spoken language.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LanguageProfile {
+    /// Wins over the request's prompt and the per-provider override.
+    pub prompt: Option<String>,
+    /// Replaces the requested model of whichever provider transcribes.
+    pub model: Option<String>,
+}
+
+impl LanguageProfile {
+    /// Neither field set, which is how the settings patch removes a profile.
+    pub fn is_empty(&self) -> bool {
+        [&self.prompt, &self.model]
+            .iter()
+            .all(|value| value.as_deref().is_none_or(|value| value.trim().is_empty()))
+    }
+}
+
+/// Request budgets for the remote providers; unset means unlimited.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ProviderRateLimits {
+    pub openai: Option<ProviderRateLimit>,
+    pub google: Option<ProviderRateLimit>,
+}
+
+impl ProviderRateLimits {
+    /// Budget for a usage provider key (`openai`, `google`, ...).
+    pub fn rpm_for(&self, provider: &str) -> Option<u32> {
+        let limit = match provider {
+            "openai" => self.openai.as_ref(),
+            "google" => self.google.as_ref(),
+            _ => None,
+        };
+        limit.map(|limit| limit.rpm)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderRateLimit {
+    pub rpm: u32,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct WakeWordAction {
+    /// Seconds of audio to send for an answer, as if that duration hotkey was pressed.
+    pub duration: Option<u32>,
+}
+
+/// Settings for the `mock` transcription provider used by tests and offline work.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct MockProviderSettings {
+    /// Makes the `mock` mode selectable, like the `XEXAMAI_MOCK_PROVIDERS` variable.
+    pub enabled: bool,
+    /// Delay before every answer.
+    pub latency_ms: u64,
+    /// Share of requests that fail, 0-100, spread evenly.
+    pub failure_percent: u8,
+    /// HTTP status the injected failures carry; without one they fail as network errors.
+    pub failure_status: Option<u16>,
+}
+
+impl MockProviderSettings {
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
 }
 
 fn default_window_width() -> u32 {
     DEFAULT_WINDOW_WIDTH
 }
 
+fn default_auto_type_wpm() -> u32 {
+    DEFAULT_AUTO_TYPE_WPM
+}
+
+fn default_max_pending_clips() -> u32 {
+    DEFAULT_MAX_PENDING_CLIPS
+}
+
+fn default_mic_gain() -> f32 {
+    DEFAULT_MIC_GAIN
+}
+
+fn default_vad_threshold_db() -> f32 {
+    DEFAULT_VAD_THRESHOLD_DB
+}
+
+fn default_agc_target_db() -> f32 {
+    DEFAULT_AGC_TARGET_DB
+}
+
+fn default_ducking_db() -> f32 {
+    DEFAULT_DUCKING_DB
+}
+
 fn default_window_height() -> u32 {
     DEFAULT_WINDOW_HEIGHT
 }
@@ -175,6 +745,22 @@ fn default_hide_app() -> bool {
     true
 }
 
+fn default_warn_when_not_hidden() -> bool {
+    true
+}
+
+fn default_check_updates_on_startup() -> bool {
+    true
+}
+
+fn default_audio_priority_boost() -> bool {
+    true
+}
+
+fn default_integration_server_port() -> u16 {
+    DEFAULT_INTEGRATION_SERVER_PORT
+}
+
 fn default_transcription_prompt() -> String {
     DEFAULT_TRANSCRIPTION_PROMPT.to_string()
 }
@@ -191,6 +777,10 @@ fn default_screen_timeout() -> u32 {
     DEFAULT_SCREEN_PROCESSING_TIMEOUT_MS
 }
 
+fn default_slow_command_ms() -> u32 {
+    DEFAULT_SLOW_COMMAND_MS
+}
+
 impl Default for AppConfig {
     fn default() -> Self {
         let mut cfg = Self {
@@ -199,15 +789,50 @@ impl Default for AppConfig {
             google_api_key: None,
             durations: default_durations(),
             duration_hotkeys: default_duration_hotkeys(),
+            max_pending_clips: default_max_pending_clips(),
             toggle_input_hotkey: default_toggle_hotkey(),
+            mic_device_id: None,
+            mixed_mic_device_id: None,
+            system_loopback_device_id: None,
             audio_input_device_id: None,
             audio_input_type: default_audio_input_type(),
+            auto_start_capture: false,
+            pre_roll_seconds: 0,
+            pre_roll_source: default_pre_roll_source(),
+            mic_gain: default_mic_gain(),
+            vad_threshold_db: default_vad_threshold_db(),
+            agc_target_db: default_agc_target_db(),
+            ducking_enabled: false,
+            ducking_db: default_ducking_db(),
+            wake_words: Vec::new(),
+            wake_word_action: None,
+            audio_priority_boost: default_audio_priority_boost(),
+            audio_mmcss: false,
             transcription_model: default_transcription_model(),
             transcription_prompt: default_transcription_prompt(),
-            llm_model: default_llm_model(),
+            transcription_prompt_overrides: TranscriptionPromptOverrides::default(),
+            send_prompt_to_local: false,
+            google_transcription_format: default_google_transcription_format(),
+            transcription_language: None,
+            translate_to: None,
+            translation_model: None,
+            language_detection: default_language_detection(),
+            language_profiles: BTreeMap::new(),
+            provider_rate_limits: ProviderRateLimits::default(),
+            plain_text_log_enabled: false,
+            plain_text_log_dir: None,
+            llm_model: None,
             api_llm_model: default_api_llm_model(),
             local_llm_model: default_local_llm_model(),
             llm_prompt: default_llm_prompt(),
+            prompt_variables: BTreeMap::new(),
+            include_window_context: false,
+            auto_summarize_every_min: 0,
+            summary_prompt: default_summary_prompt(),
+            local_llm_options: LocalLlmOptions::default(),
+            llm_stream_to_clipboard: false,
+            enable_auto_type: false,
+            auto_type_wpm: default_auto_type_wpm(),
             transcription_mode: default_transcription_mode(),
             llm_host: default_llm_host(),
             local_whisper_model: default_local_whisper_model(),
@@ -215,6 +840,11 @@ impl Default for AppConfig {
             window_opacity: DEFAULT_WINDOW_OPACITY,
             always_on_top: false,
             hide_app: true,
+            warn_when_not_hidden: true,
+            hide_from_switcher: false,
+            show_recording_indicator: false,
+            recording_indicator_corner: default_indicator_corner(),
+            tray_icon_theme: default_tray_icon_theme(),
             welcome_modal_dismissed: false,
             window_width: DEFAULT_WINDOW_WIDTH,
             window_height: DEFAULT_WINDOW_HEIGHT,
@@ -222,10 +852,30 @@ impl Default for AppConfig {
             api_stt_timeout_ms: DEFAULT_API_STT_TIMEOUT_MS,
             api_llm_timeout_ms: DEFAULT_API_LLM_TIMEOUT_MS,
             screen_processing_timeout_ms: DEFAULT_SCREEN_PROCESSING_TIMEOUT_MS,
+            slow_command_ms: DEFAULT_SLOW_COMMAND_MS,
             stream_send_hotkey: default_stream_hotkey(),
+            private_session_hotkey: String::new(),
+            stream_mode: default_stream_mode(),
             screen_processing_model: default_screen_model(),
             screen_processing_prompt: default_screen_prompt(),
             save_recorder_files: false,
+            encrypt_local_artifacts: false,
+            active_account: None,
+            http_proxy: None,
+            api_debug_logging: false,
+            log_level: default_log_level(),
+            check_updates_on_startup: true,
+            integration_server_enabled: false,
+            integration_server_port: DEFAULT_INTEGRATION_SERVER_PORT,
+            onboarding_completed_steps: Vec::new(),
+            sound_theme: None,
+            sound_cues: BTreeMap::new(),
+            ui_language: default_ui_language(),
+            transcription_sample_rate: DEFAULT_TRANSCRIPTION_SAMPLE_RATE,
+            stt_speedup_factor: MIN_STT_SPEEDUP_FACTOR,
+            stt_speedup_relabel: false,
+            recorder_files_original_rate: false,
+            mock_providers: MockProviderSettings::default(),
         };
         cfg.normalize();
         cfg
@@ -233,7 +883,36 @@ impl Default for AppConfig {
 }
 
 impl AppConfig {
+    /// The chat model for the configured host.
+    pub fn effective_llm_model(&self) -> &str {
+        match self.llm_host {
+            LlmHost::Local => &self.local_llm_model,
+            _ => &self.api_llm_model,
+        }
+    }
+
+    /// Whether the `mock` providers may be selected.
+    pub fn mock_providers_enabled(&self) -> bool {
+        self.mock_providers.enabled
+            || std::env::var_os(MOCK_PROVIDERS_ENV_VAR)
+                .is_some_and(|value| !value.is_empty() && value != "0")
+    }
+
+    /// The `language_profiles` entry for `language`.
+    pub fn language_profile(&self, language: Option<&str>) -> Option<&LanguageProfile> {
+        self.language_profiles.get(language?)
+    }
+
     pub fn normalize(&mut self) {
+        for (field, prompt) in [
+            ("llmPrompt", &mut self.llm_prompt),
+            ("transcriptionPrompt", &mut self.transcription_prompt),
+            ("screenProcessingPrompt", &mut self.screen_processing_prompt),
+            ("summaryPrompt", &mut self.summary_prompt),
+        ] {
+            *prompt = text_sanitize::sanitize_prompt(field, prompt);
+        }
+
         if self.backend_domain != DEFAULT_BACKEND_DOMAIN && self.backend_domain != BACKEND_DOMAIN_RU
         {
             self.backend_domain = default_backend_domain();
@@ -245,16 +924,50 @@ impl AppConfig {
         self.durations.sort_unstable();
         self.durations.dedup();
 
-        ensure_duration_hotkeys(&mut self.duration_hotkeys, &self.durations);
+        ensure_duration_hotkeys(
+            &mut self.duration_hotkeys,
+            &self.durations,
+            &self.toggle_input_hotkey,
+        );
         if self.toggle_input_hotkey.trim().is_empty() {
             self.toggle_input_hotkey = DEFAULT_TOGGLE_INPUT_HOTKEY.to_string();
         }
 
-        if !matches!(
-            self.audio_input_type.as_str(),
-            "microphone" | "system" | "mixed"
-        ) {
-            self.audio_input_type = DEFAULT_AUDIO_INPUT_TYPE.to_string();
+        if self.audio_input_type == AudioInputType::Unknown {
+            self.audio_input_type = default_audio_input_type();
+        }
+        if self.recording_indicator_corner == IndicatorCorner::Unknown {
+            self.recording_indicator_corner = default_indicator_corner();
+        }
+        if self.tray_icon_theme == TrayIconTheme::Unknown {
+            self.tray_icon_theme = default_tray_icon_theme();
+        }
+        if let Some(legacy) = self.audio_input_device_id.take() {
+            self.mic_device_id = Some(legacy);
+        }
+        for device_id in [
+            &mut self.mic_device_id,
+            &mut self.mixed_mic_device_id,
+            &mut self.system_loopback_device_id,
+        ] {
+            *device_id = device_id
+                .take()
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty());
+        }
+        let mut seen = HashSet::new();
+        self.wake_words = self
+            .wake_words
+            .iter()
+            .map(|word| word.trim().to_string())
+            .filter(|word| !word.is_empty() && seen.insert(word.to_lowercase()))
+            .collect();
+        if let Some(duration) = self
+            .wake_word_action
+            .as_mut()
+            .and_then(|action| action.duration.as_mut())
+        {
+            *duration = (*duration).clamp(MIN_DURATION_SECONDS, MAX_DURATION_SECONDS);
         }
 
         if self.transcription_model.trim().is_empty() {
@@ -263,8 +976,61 @@ impl AppConfig {
         if self.transcription_prompt.trim().is_empty() {
             self.transcription_prompt = DEFAULT_TRANSCRIPTION_PROMPT.to_string();
         }
-        if self.llm_model.trim().is_empty() {
-            self.llm_model = DEFAULT_OPENAI_MODEL.to_string();
+        let overrides = &mut self.transcription_prompt_overrides;
+        for prompt in [
+            &mut overrides.openai,
+            &mut overrides.google,
+            &mut overrides.local,
+        ] {
+            *prompt = prompt
+                .take()
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty());
+        }
+        self.plain_text_log_dir = self
+            .plain_text_log_dir
+            .take()
+            .map(|dir| dir.trim().to_string())
+            .filter(|dir| !dir.is_empty());
+        let limits = &mut self.provider_rate_limits;
+        for limit in [&mut limits.openai, &mut limits.google] {
+            *limit = limit.take().filter(|limit| limit.rpm > 0);
+        }
+        if self.google_transcription_format == GoogleTranscriptionFormat::Unknown {
+            self.google_transcription_format = default_google_transcription_format();
+        }
+        self.transcription_language = self
+            .transcription_language
+            .take()
+            .map(|language| language.trim().to_lowercase())
+            .filter(|language| !language.is_empty() && language != "auto");
+        for value in [&mut self.translate_to, &mut self.translation_model] {
+            *value = value
+                .take()
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty());
+        }
+        if self.language_detection == LanguageDetection::Unknown {
+            self.language_detection = default_language_detection();
+        }
+        self.language_profiles = std::mem::take(&mut self.language_profiles)
+            .into_iter()
+            .map(|(language, profile)| (language.trim().to_lowercase(), profile))
+            .filter(|(language, profile)| !language.is_empty() && !profile.is_empty())
+            .collect();
+        if self.llm_host == LlmHost::Unknown {
+            self.llm_host = default_llm_host();
+        }
+        // Runs after the host is settled so a patch that switches host and sets the
+        // model together lands on the new host.
+        if let Some(model) = self.llm_model.take() {
+            let model = model.trim();
+            if !model.is_empty() {
+                match self.llm_host {
+                    LlmHost::Local => self.local_llm_model = model.to_string(),
+                    _ => self.api_llm_model = model.to_string(),
+                }
+            }
         }
         if self.api_llm_model.trim().is_empty() {
             self.api_llm_model = DEFAULT_OPENAI_MODEL.to_string();
@@ -275,25 +1041,77 @@ impl AppConfig {
         if self.llm_prompt.trim().is_empty() {
             self.llm_prompt = DEFAULT_LLM_PROMPT.to_string();
         }
-        if !matches!(self.transcription_mode.as_str(), "api" | "local") {
-            self.transcription_mode = DEFAULT_TRANSCRIPTION_MODE.to_string();
+        if self.summary_prompt.trim().is_empty() {
+            self.summary_prompt = DEFAULT_SUMMARY_PROMPT.to_string();
         }
-        if !matches!(self.llm_host.as_str(), "api" | "local") {
-            self.llm_host = DEFAULT_LLM_HOST.to_string();
+        self.auto_summarize_every_min = self
+            .auto_summarize_every_min
+            .min(MAX_AUTO_SUMMARIZE_MINUTES);
+        self.local_llm_options.normalize();
+        self.prompt_variables = std::mem::take(&mut self.prompt_variables)
+            .into_iter()
+            .map(|(name, value)| (name.trim().to_string(), value))
+            .filter(|(name, _)| {
+                prompt_template::is_variable_name(name)
+                    && !prompt_template::BUILTIN_VARIABLES.contains(&name.as_str())
+            })
+            .collect();
+        self.auto_type_wpm = self
+            .auto_type_wpm
+            .clamp(MIN_AUTO_TYPE_WPM, MAX_AUTO_TYPE_WPM);
+        self.max_pending_clips = self.max_pending_clips.clamp(1, MAX_PENDING_CLIPS_LIMIT);
+        self.pre_roll_seconds = self.pre_roll_seconds.min(MAX_PRE_ROLL_SECONDS);
+        if self.pre_roll_source == PreRollSource::Unknown {
+            self.pre_roll_source = default_pre_roll_source();
         }
-        if self.llm_host == "api" {
-            self.llm_model = self.api_llm_model.clone();
-        } else {
-            self.llm_model = self.local_llm_model.clone();
+        let levels = [
+            (
+                &mut self.mic_gain,
+                DEFAULT_MIC_GAIN,
+                MIN_MIC_GAIN,
+                MAX_MIC_GAIN,
+            ),
+            (
+                &mut self.vad_threshold_db,
+                DEFAULT_VAD_THRESHOLD_DB,
+                MIN_VAD_THRESHOLD_DB,
+                MAX_VAD_THRESHOLD_DB,
+            ),
+            (
+                &mut self.agc_target_db,
+                DEFAULT_AGC_TARGET_DB,
+                MIN_AGC_TARGET_DB,
+                MAX_AGC_TARGET_DB,
+            ),
+            (
+                &mut self.ducking_db,
+                DEFAULT_DUCKING_DB,
+                MIN_DUCKING_DB,
+                MAX_DUCKING_DB,
+            ),
+        ];
+        for (value, default, min, max) in levels {
+            *value = if value.is_finite() {
+                value.clamp(min, max)
+            } else {
+                default
+            };
+        }
+        let mock_allowed =
+            self.transcription_mode == TranscriptionMode::Mock && self.mock_providers_enabled();
+        if !mock_allowed
+            && !matches!(
+                self.transcription_mode,
+                TranscriptionMode::Api | TranscriptionMode::Local
+            )
+        {
+            self.transcription_mode = default_transcription_mode();
         }
         if self.local_whisper_model.trim().is_empty() {
             self.local_whisper_model = DEFAULT_LOCAL_WHISPER_MODEL.to_string();
         }
-        let normalized_device = self.local_device.trim().to_lowercase();
-        if VALID_LOCAL_DEVICES.contains(&normalized_device.as_str()) {
-            self.local_device = normalized_device;
-        } else {
-            self.local_device = DEFAULT_LOCAL_DEVICE.to_string();
+        if self.local_device == LocalDevice::Unknown {
+            self.local_device = default_local_device();
         }
 
         if self.window_opacity == 0 {
@@ -306,7 +1124,7 @@ impl AppConfig {
         if !self.window_scale.is_finite() {
             self.window_scale = DEFAULT_WINDOW_SCALE;
         }
-        self.window_scale = self.window_scale.clamp(0.5, 3.0);
+        self.window_scale = self.window_scale.clamp(MIN_WINDOW_SCALE, MAX_WINDOW_SCALE);
 
         if self.api_stt_timeout_ms == 0 {
             self.api_stt_timeout_ms = DEFAULT_API_STT_TIMEOUT_MS;
@@ -317,29 +1135,102 @@ impl AppConfig {
         if self.screen_processing_timeout_ms == 0 {
             self.screen_processing_timeout_ms = DEFAULT_SCREEN_PROCESSING_TIMEOUT_MS;
         }
+        if self.slow_command_ms == 0 {
+            self.slow_command_ms = DEFAULT_SLOW_COMMAND_MS;
+        }
 
         if self.stream_send_hotkey.trim().is_empty() {
             self.stream_send_hotkey = DEFAULT_STREAM_SEND_HOTKEY.to_string();
         }
+        if self.stream_mode == StreamMode::Unknown {
+            self.stream_mode = default_stream_mode();
+        }
 
-        if self.screen_processing_model != "openai" && self.screen_processing_model != "google" {
-            self.screen_processing_model = DEFAULT_SCREEN_PROVIDER.to_string();
+        if self.screen_processing_model == ScreenProcessingProvider::Unknown {
+            self.screen_processing_model = default_screen_model();
         }
         if self.screen_processing_prompt.trim().is_empty() {
             self.screen_processing_prompt = DEFAULT_SCREEN_PROMPT.to_string();
         }
+
+        let log_level = self.log_level.trim().to_lowercase();
+        self.log_level = if VALID_LOG_LEVELS.contains(&log_level.as_str()) {
+            log_level
+        } else {
+            DEFAULT_LOG_LEVEL.to_string()
+        };
+
+        self.http_proxy = self
+            .http_proxy
+            .take()
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty());
+
+        if self.integration_server_port < 1024 {
+            self.integration_server_port = DEFAULT_INTEGRATION_SERVER_PORT;
+        }
+
+        self.onboarding_completed_steps
+            .retain(|step| !step.trim().is_empty());
+        self.onboarding_completed_steps.dedup();
+
+        self.sound_theme = self
+            .sound_theme
+            .take()
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty());
+        self.sound_cues
+            .retain(|event, cue| !event.trim().is_empty() && !cue.trim().is_empty());
+
+        let ui_language = self.ui_language.trim().to_lowercase();
+        self.ui_language = if SUPPORTED_UI_LANGUAGES.contains(&ui_language.as_str()) {
+            ui_language
+        } else {
+            default_ui_language()
+        };
+
+        if !TRANSCRIPTION_SAMPLE_RATES.contains(&self.transcription_sample_rate) {
+            self.transcription_sample_rate = DEFAULT_TRANSCRIPTION_SAMPLE_RATE;
+        }
+        self.stt_speedup_factor = if self.stt_speedup_factor.is_finite() {
+            self.stt_speedup_factor
+                .clamp(MIN_STT_SPEEDUP_FACTOR, MAX_STT_SPEEDUP_FACTOR)
+        } else {
+            MIN_STT_SPEEDUP_FACTOR
+        };
     }
 }
 
-fn ensure_duration_hotkeys(map: &mut BTreeMap<u32, String>, durations: &[u32]) {
+/// Drops hotkeys of removed durations and gives new durations a free key: first the
+/// keys just freed (so editing 15 into 20 keeps its key), then unused digits. Once
+/// those run out a duration simply has no hotkey; an empty key means "none" on purpose.
+fn ensure_duration_hotkeys(map: &mut BTreeMap<u32, String>, durations: &[u32], toggle: &str) {
     if map.is_empty() {
         *map = default_duration_hotkeys();
-        return;
     }
-    let mut fallback_iter = (b'1'..=b'9').map(|b| (b as char).to_string());
+    let mut freed = Vec::new();
+    map.retain(|duration, key| {
+        let keep = durations.contains(duration);
+        if !keep && !key.trim().is_empty() {
+            freed.push(key.clone());
+        }
+        keep
+    });
+    let mut used: HashSet<String> = map
+        .values()
+        .chain(std::iter::once(&toggle.to_string()))
+        .map(|key| key.trim().to_lowercase())
+        .collect();
+    let mut spare = freed
+        .into_iter()
+        .chain((b'1'..=b'9').map(|b| (b as char).to_string()));
     for duration in durations {
-        map.entry(*duration)
-            .or_insert_with(|| fallback_iter.next().unwrap_or_else(|| duration.to_string()));
+        if map.contains_key(duration) {
+            continue;
+        }
+        if let Some(key) = spare.find(|key| used.insert(key.trim().to_lowercase())) {
+            map.insert(*duration, key);
+        }
     }
 }
 
@@ -349,6 +1240,8 @@ pub struct AuthTokensPayload {
     pub access: String,
     #[serde(default)]
     pub refresh: Option<String>,
+    #[serde(default, alias = "expires_in")]
+    pub expires_in: Option<i64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -384,6 +1277,14 @@ pub struct FastWhisperStatus {
     #[serde(default)]
     pub install_dir: Option<String>,
     pub updated_at: i64,
+    /// Restored from the last session and not yet confirmed by a health check.
+    #[serde(default)]
+    pub stale: bool,
+    /// What the running server reported on first contact, and the API picked for it.
+    #[serde(default)]
+    pub server_version: Option<String>,
+    #[serde(default)]
+    pub api_variant: Option<String>,
 }
 
 impl FastWhisperStatus {
@@ -399,6 +1300,148 @@ impl FastWhisperStatus {
             log_line: None,
             install_dir: None,
             updated_at: Utc::now().timestamp_millis(),
+            stale: false,
+            server_version: None,
+            api_variant: None,
+        }
+    }
+}
+
+/// Window preferences the webview applies itself, at load and on `window:scale-changed`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WindowPrefs {
+    pub scale: f32,
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::constants::MAX_PROMPT_CHARS;
+
+    macro_rules! for_each_config_enum {
+        ($check:ident) => {
+            $check!(TranscriptionMode);
+            $check!(LlmHost);
+            $check!(AudioInputType);
+            $check!(LocalDevice);
+            $check!(IndicatorCorner);
+            $check!(TrayIconTheme);
+            $check!(StreamMode);
+            $check!(GoogleTranscriptionFormat);
+            $check!(LanguageDetection);
+            $check!(PreRollSource);
+            $check!(ScreenProcessingProvider);
+        };
+    }
+
+    #[test]
+    fn config_enums_accept_every_spelling_and_serialize_canonically() {
+        macro_rules! check {
+            ($name:ident) => {
+                for &(spelling, variant) in $name::SPELLINGS {
+                    let loaded: $name = serde_json::from_value(json!(spelling)).unwrap();
+                    assert_eq!(loaded, variant, "{} {spelling:?}", stringify!($name));
+                    let padded = format!("  {}  ", spelling.to_uppercase());
+                    assert_eq!($name::parse(&padded), variant);
+
+                    let saved = serde_json::to_value(loaded).unwrap();
+                    assert_eq!(saved, json!(variant.as_str()));
+                    let reloaded: $name = serde_json::from_value(saved).unwrap();
+                    assert_eq!(reloaded, variant);
+                }
+                let unknown: $name = serde_json::from_value(json!("from-a-newer-build")).unwrap();
+                assert_eq!(unknown, $name::Unknown);
+                assert_eq!(serde_json::to_value(unknown).unwrap(), json!("unknown"));
+            };
+        }
+        for_each_config_enum!(check);
+    }
+
+    /// What `config_update` does with a flat patch: merge, load, normalize.
+    fn updated(config: &AppConfig, patch: Value) -> AppConfig {
+        let mut value = serde_json::to_value(config).unwrap();
+        for (key, field) in patch.as_object().unwrap() {
+            value[key] = field.clone();
         }
+        let mut next: AppConfig = serde_json::from_value(value).unwrap();
+        next.normalize();
+        next
+    }
+
+    #[test]
+    fn llm_model_updates_the_model_of_the_active_host() {
+        let config = AppConfig::default();
+        let next = updated(&config, json!({ "llmModel": " gpt-4o " }));
+        assert_eq!(next.api_llm_model, "gpt-4o");
+        assert_eq!(next.local_llm_model, DEFAULT_LOCAL_LLM_MODEL);
+        assert_eq!(next.effective_llm_model(), "gpt-4o");
+        assert_eq!(next.llm_model, None);
+        assert!(serde_json::to_value(&next)
+            .unwrap()
+            .get("llmModel")
+            .is_none());
+
+        // Another save keeps it rather than reverting to a derived value.
+        let again = updated(&next, json!({ "llmPrompt": "Answer briefly." }));
+        assert_eq!(again.effective_llm_model(), "gpt-4o");
+
+        let local = updated(&config, json!({ "llmHost": "local" }));
+        let local = updated(&local, json!({ "llmModel": "llama3" }));
+        assert_eq!(local.local_llm_model, "llama3");
+        assert_eq!(local.api_llm_model, DEFAULT_OPENAI_MODEL);
+    }
+
+    #[test]
+    fn llm_model_survives_host_switches() {
+        let config = updated(&AppConfig::default(), json!({ "llmModel": "gpt-4o" }));
+        let local = updated(&config, json!({ "llmHost": "local" }));
+        assert_eq!(local.effective_llm_model(), DEFAULT_LOCAL_LLM_MODEL);
+        let back = updated(&local, json!({ "llmHost": "api" }));
+        assert_eq!(back.effective_llm_model(), "gpt-4o");
+
+        // Switching host and model in one patch sets the model on the new host.
+        let switched = updated(
+            &config,
+            json!({ "llmHost": "ollama", "llmModel": "llama3" }),
+        );
+        assert_eq!(switched.llm_host, LlmHost::Local);
+        assert_eq!(switched.local_llm_model, "llama3");
+        assert_eq!(switched.api_llm_model, "gpt-4o");
+    }
+
+    #[test]
+    fn per_host_models_update_in_isolation() {
+        let config = AppConfig::default();
+        let next = updated(&config, json!({ "apiLlmModel": "gpt-4o" }));
+        assert_eq!(next.api_llm_model, "gpt-4o");
+        assert_eq!(next.local_llm_model, DEFAULT_LOCAL_LLM_MODEL);
+        let next = updated(&next, json!({ "localLlmModel": "llama3" }));
+        assert_eq!(next.api_llm_model, "gpt-4o");
+        assert_eq!(next.local_llm_model, "llama3");
+        assert_eq!(next.effective_llm_model(), "gpt-4o");
+        let next = updated(&next, json!({ "llmModel": "  ", "apiLlmModel": "" }));
+        assert_eq!(next.api_llm_model, DEFAULT_OPENAI_MODEL);
+        assert_eq!(next.local_llm_model, "llama3");
+    }
+
+    #[test]
+    fn normalize_cleans_pasted_prompts() {
+        let next = updated(
+            &AppConfig::default(),
+            json!({
+                "llmPrompt": "\u{feff}Answer\u{200b} briefly.\r\nBe exact.",
+                "transcriptionPrompt": "Cafe\u{301}\rnames",
+                "screenProcessingPrompt": "x".repeat(MAX_PROMPT_CHARS + 10),
+            }),
+        );
+        assert_eq!(next.llm_prompt, "Answer briefly.\nBe exact.");
+        assert_eq!(next.transcription_prompt, "Café\nnames");
+        assert_eq!(
+            next.screen_processing_prompt.chars().count(),
+            MAX_PROMPT_CHARS
+        );
     }
 }
@@ -1,9 +1,16 @@
+//! Update checks. The latest GitHub release is the single source of "is there
+//! a newer version": the hourly poll refreshes it, `updates_check` reads it
+//! from the 12-hour cache, and only when it is newer is the update manifest
+//! loaded to download and install the build. The manifest is also the
+//! fallback when GitHub cannot be reached.
+
 use std::{
     collections::HashMap,
     fs,
     io::Read,
     path::{Path, PathBuf},
-    time::Duration,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
 };
 
 #[cfg(target_os = "windows")]
@@ -14,19 +21,24 @@ use futures_util::StreamExt;
 use reqwest::StatusCode;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
-use tauri::{AppHandle, Emitter, Manager};
+use tauri::{AppHandle, Manager};
 use tokio::io::AsyncWriteExt;
 use url::Url;
 
+use crate::config::ConfigState;
 use crate::constants::{
-    UPDATE_CHECK_INTERVAL_SECS, UPDATE_INITIAL_CHECK_DELAY_SECS, UPDATE_MANIFEST_URL,
+    GITHUB_RELEASES_URL, RELEASE_CHECK_CACHE_SECS, UPDATE_CHECK_INTERVAL_SECS,
+    UPDATE_INITIAL_CHECK_DELAY_SECS, UPDATE_MANIFEST_URL,
 };
+use crate::errors::{CommandError, CommandResult, ErrorCode};
+use crate::events::{self, emit_event};
+use crate::http::HttpClients;
+use crate::perf;
 
-const UPDATE_AVAILABLE_EVENT: &str = "update-available";
-const UPDATE_PROGRESS_EVENT: &str = "update-download-progress";
-const UPDATE_STARTED_EVENT: &str = "update-started";
-const UPDATE_ERROR_EVENT: &str = "update-error";
 const UPDATE_REQUEST_TIMEOUT_SECS: u64 = 60;
+const RELEASE_REQUEST_TIMEOUT_SECS: u64 = 15;
+
+static RELEASE_CACHE: Mutex<Option<(Instant, ReleaseInfo)>> = Mutex::new(None);
 
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -66,6 +78,24 @@ pub struct UpdateCheckResult {
     pub version: Option<String>,
 }
 
+#[derive(Debug, Deserialize)]
+struct GithubRelease {
+    tag_name: String,
+    html_url: String,
+    #[serde(default)]
+    body: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReleaseInfo {
+    pub current: String,
+    pub latest: String,
+    pub url: String,
+    pub notes: Option<String>,
+    pub is_newer: bool,
+}
+
 #[derive(Debug, Serialize, Clone)]
 #[serde(rename_all = "camelCase")]
 struct UpdateAvailablePayload {
@@ -95,17 +125,101 @@ struct UpdateErrorPayload {
     message: String,
 }
 
-#[tauri::command]
-pub(crate) async fn check_app_update(app: AppHandle) -> std::result::Result<UpdateCheckResult, String> {
+#[perf::command]
+pub(crate) async fn check_app_update(app: AppHandle) -> CommandResult<UpdateCheckResult> {
     log::info!(target: "update", "Manual update check requested");
     check_for_updates(&app, true)
         .await
         .map_err(|error| {
             log::error!(target: "update", "Manual update check failed: {error}");
-            error.to_string()
+            CommandError::new(ErrorCode::UpdateCheckFailed, error)
         })
 }
 
+/// Reports the latest GitHub release; results are cached for 12 hours unless `force` is set.
+#[perf::command]
+pub(crate) async fn updates_check(
+    app: AppHandle,
+    force: Option<bool>,
+) -> CommandResult<ReleaseInfo> {
+    check_latest_release(&app, force.unwrap_or(false))
+        .await
+        .map_err(|error| {
+            log::warn!(target: "update", "GitHub release check failed: {error}");
+            CommandError::new(ErrorCode::UpdateCheckFailed, error)
+        })
+}
+
+/// Emits `updates:available` once after startup when `checkUpdatesOnStartup`
+/// is on; the release normally comes from the cache the poll just filled.
+async fn report_release_on_startup(app: &AppHandle) {
+    let enabled = match app.try_state::<Arc<ConfigState>>() {
+        Some(config) => config.get().await.check_updates_on_startup,
+        None => false,
+    };
+    if !enabled {
+        return;
+    }
+    match check_latest_release(app, false).await {
+        Ok(release) if release.is_newer => {
+            log::info!(target: "update", "Newer release available: {}", release.latest);
+            let _ = emit_event(app, events::UPDATES_AVAILABLE, &release);
+        }
+        Ok(_) => {}
+        Err(error) => log::warn!(target: "update", "GitHub release check failed: {error}"),
+    }
+}
+
+/// The latest GitHub release, from the cache unless `force` is set or it is stale.
+async fn check_latest_release(app: &AppHandle, force: bool) -> Result<ReleaseInfo> {
+    if !force {
+        let cached = RELEASE_CACHE
+            .lock()
+            .map_err(|_| anyhow!("Release cache lock poisoned"))?
+            .clone();
+        if let Some((checked_at, release)) = cached {
+            if checked_at.elapsed() < Duration::from_secs(RELEASE_CHECK_CACHE_SECS) {
+                return Ok(release);
+            }
+        }
+    }
+
+    log::info!(target: "update", "Checking GitHub releases: url={GITHUB_RELEASES_URL}");
+    let client = remote_client(app).await?;
+    let response = client
+        .get(GITHUB_RELEASES_URL)
+        .timeout(Duration::from_secs(RELEASE_REQUEST_TIMEOUT_SECS))
+        .header(reqwest::header::ACCEPT, "application/vnd.github+json")
+        .header(
+            reqwest::header::USER_AGENT,
+            format!("xexamai/{}", app.package_info().version),
+        )
+        .send()
+        .await
+        .context("Failed to request GitHub releases")?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(anyhow!(
+            "Failed to load GitHub releases: HTTP {}",
+            status.as_u16()
+        ));
+    }
+    let latest = response.json::<GithubRelease>().await?;
+
+    let current = app.package_info().version.to_string();
+    let release = ReleaseInfo {
+        is_newer: is_newer_version(&latest.tag_name, &current),
+        latest: normalize_version(&latest.tag_name),
+        url: latest.html_url,
+        notes: latest.body.filter(|body| !body.trim().is_empty()),
+        current,
+    };
+    if let Ok(mut cache) = RELEASE_CACHE.lock() {
+        *cache = Some((Instant::now(), release.clone()));
+    }
+    Ok(release)
+}
+
 pub(crate) fn start_update_poll(app: AppHandle) {
     log::info!(
         target: "update",
@@ -115,15 +229,24 @@ pub(crate) fn start_update_poll(app: AppHandle) {
     );
     tauri::async_runtime::spawn(async move {
         tokio::time::sleep(Duration::from_secs(UPDATE_INITIAL_CHECK_DELAY_SECS)).await;
+        let mut first = true;
         loop {
             if let Err(error) = check_for_updates(&app, false).await {
-                eprintln!("[update] check failed: {error}");
+                log::warn!(target: "update", "check failed: {error}");
+            }
+            if std::mem::take(&mut first) {
+                report_release_on_startup(&app).await;
             }
             tokio::time::sleep(Duration::from_secs(UPDATE_CHECK_INTERVAL_SECS)).await;
         }
     });
 }
 
+async fn remote_client(app: &AppHandle) -> Result<reqwest::Client> {
+    let config = app.state::<Arc<ConfigState>>().get().await;
+    app.state::<Arc<HttpClients>>().upload(&config)
+}
+
 async fn check_for_updates(app: &AppHandle, emit_manifest_errors: bool) -> Result<UpdateCheckResult> {
     if cfg!(debug_assertions) && std::env::var("XEXAMAI_ENABLE_DEBUG_UPDATER").is_err() {
         log::debug!(
@@ -136,11 +259,28 @@ async fn check_for_updates(app: &AppHandle, emit_manifest_errors: bool) -> Resul
         });
     }
 
+    match check_latest_release(app, true).await {
+        Ok(release) if !release.is_newer => {
+            log::info!(target: "update", "No update available: latest={}", release.latest);
+            return Ok(UpdateCheckResult {
+                update_available: false,
+                version: Some(release.latest),
+            });
+        }
+        Ok(release) => {
+            log::info!(target: "update", "Newer release found: {}", release.latest);
+        }
+        Err(error) => {
+            log::warn!(
+                target: "update",
+                "GitHub release check failed, using the manifest: {error}"
+            );
+        }
+    }
+
     let manifest_url = resolve_manifest_url();
     log::info!(target: "update", "Checking updates: manifest_url={manifest_url}");
-    let client = reqwest::Client::builder()
-        .timeout(Duration::from_secs(UPDATE_REQUEST_TIMEOUT_SECS))
-        .build()?;
+    let client = remote_client(app).await?;
     let manifest = match load_manifest(&client, &manifest_url).await {
         Ok(Some(manifest)) => manifest,
         Ok(None) => {
@@ -183,8 +323,9 @@ async fn check_for_updates(app: &AppHandle, emit_manifest_errors: bool) -> Resul
         }
     };
 
-    let _ = app.emit(
-        UPDATE_AVAILABLE_EVENT,
+    let _ = emit_event(
+        app,
+        events::UPDATE_AVAILABLE,
         UpdateAvailablePayload {
             version: candidate.version.clone(),
             current_version,
@@ -223,8 +364,9 @@ async fn check_for_updates(app: &AppHandle, emit_manifest_errors: bool) -> Resul
         installer_path.to_string_lossy()
     );
 
-    let _ = app.emit(
-        UPDATE_STARTED_EVENT,
+    let _ = emit_event(
+        app,
+        events::UPDATE_STARTED,
         UpdateStartedPayload {
             version: candidate.version.clone(),
             file_name: candidate.file_name.clone(),
@@ -257,6 +399,7 @@ async fn load_manifest(client: &reqwest::Client, manifest_url: &str) -> Result<O
     log::info!(target: "update", "Requesting update manifest");
     let response = client
         .get(manifest_url)
+        .timeout(Duration::from_secs(UPDATE_REQUEST_TIMEOUT_SECS))
         .header(reqwest::header::ACCEPT, "application/json")
         .send()
         .await
@@ -398,6 +541,7 @@ async fn download_with_progress(
     );
     let response = client
         .get(file_url)
+        .timeout(Duration::from_secs(UPDATE_REQUEST_TIMEOUT_SECS))
         .send()
         .await
         .with_context(|| format!("Failed to download update: {file_url}"))?;
@@ -427,8 +571,9 @@ async fn download_with_progress(
             .unwrap_or(0)
             .min(100);
         if percent > last_percent || total.is_none() {
-            let _ = app.emit(
-                UPDATE_PROGRESS_EVENT,
+            let _ = emit_event(
+                app,
+                events::UPDATE_DOWNLOAD_PROGRESS,
                 UpdateProgressPayload {
                     percent,
                     downloaded_bytes: downloaded,
@@ -440,8 +585,9 @@ async fn download_with_progress(
     }
 
     file.flush().await?;
-    let _ = app.emit(
-        UPDATE_PROGRESS_EVENT,
+    let _ = emit_event(
+        app,
+        events::UPDATE_DOWNLOAD_PROGRESS,
         UpdateProgressPayload {
             percent: 100,
             downloaded_bytes: downloaded,
@@ -520,7 +666,7 @@ fn parse_version_numbers(value: &str) -> Option<Vec<u64>> {
 
 fn emit_update_error(app: &AppHandle, message: String) {
     log::error!(target: "update", "Update error: {message}");
-    let _ = app.emit(UPDATE_ERROR_EVENT, UpdateErrorPayload { message });
+    let _ = emit_event(app, events::UPDATE_ERROR, UpdateErrorPayload { message });
 }
 
 #[cfg(target_os = "windows")]
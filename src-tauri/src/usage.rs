@@ -0,0 +1,234 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use chrono::{Duration as ChronoDuration, Local};
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+use tokio::sync::{Mutex, Notify};
+
+use crate::resample;
+
+const USAGE_FILE_NAME: &str = "stats.json";
+// Coalesces bursts of requests into a single write.
+const PERSIST_DEBOUNCE: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageCounters {
+    #[serde(default)]
+    pub requests: u64,
+    #[serde(default)]
+    pub failures: u64,
+    #[serde(default)]
+    pub audio_seconds: f64,
+    #[serde(default)]
+    pub tokens_in: u64,
+    #[serde(default)]
+    pub tokens_out: u64,
+    /// Sum of end-to-end transcription time over successful requests.
+    #[serde(default)]
+    pub latency_ms: u64,
+}
+
+impl UsageCounters {
+    fn add(&mut self, other: &UsageCounters) {
+        self.requests += other.requests;
+        self.failures += other.failures;
+        self.audio_seconds += other.audio_seconds;
+        self.tokens_in += other.tokens_in;
+        self.tokens_out += other.tokens_out;
+        self.latency_ms += other.latency_ms;
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageDay {
+    pub date: String,
+    pub providers: BTreeMap<String, UsageCounters>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageStats {
+    pub range: String,
+    pub total: UsageCounters,
+    pub by_provider: BTreeMap<String, UsageCounters>,
+    pub days: Vec<UsageDay>,
+}
+
+pub enum UsageEvent {
+    Transcription {
+        provider: String,
+        audio_seconds: f64,
+        latency_ms: u64,
+        ok: bool,
+    },
+    Llm {
+        provider: String,
+        tokens_in: u64,
+        tokens_out: u64,
+        ok: bool,
+    },
+}
+
+/// Per-day, per-provider request accounting persisted next to the config.
+pub struct UsageStore {
+    days: Mutex<BTreeMap<String, BTreeMap<String, UsageCounters>>>,
+    path: PathBuf,
+    dirty: Notify,
+}
+
+impl UsageStore {
+    pub async fn load(config_dir: PathBuf) -> Arc<Self> {
+        let path = config_dir.join(USAGE_FILE_NAME);
+        let days = match fs::read(&path).await {
+            Ok(bytes) => serde_json::from_slice(&bytes).unwrap_or_else(|error| {
+                log::warn!(target: "usage", "Ignoring unreadable usage stats: {error}");
+                BTreeMap::new()
+            }),
+            Err(_) => BTreeMap::new(),
+        };
+        let store = Arc::new(Self {
+            days: Mutex::new(days),
+            path,
+            dirty: Notify::new(),
+        });
+        spawn_writer(store.clone());
+        store
+    }
+
+    /// Fire-and-forget: the caller never waits for the counters or the disk write.
+    pub fn record(self: &Arc<Self>, event: UsageEvent) {
+        let store = self.clone();
+        tauri::async_runtime::spawn(async move {
+            let (provider, delta) = match event {
+                UsageEvent::Transcription {
+                    provider,
+                    audio_seconds,
+                    latency_ms,
+                    ok,
+                } => (
+                    provider,
+                    UsageCounters {
+                        requests: 1,
+                        failures: u64::from(!ok),
+                        audio_seconds: if ok { audio_seconds.max(0.0) } else { 0.0 },
+                        latency_ms: if ok { latency_ms } else { 0 },
+                        ..UsageCounters::default()
+                    },
+                ),
+                UsageEvent::Llm {
+                    provider,
+                    tokens_in,
+                    tokens_out,
+                    ok,
+                } => (
+                    provider,
+                    UsageCounters {
+                        requests: 1,
+                        failures: u64::from(!ok),
+                        tokens_in,
+                        tokens_out,
+                        ..UsageCounters::default()
+                    },
+                ),
+            };
+            let today = Local::now().format("%Y-%m-%d").to_string();
+            store
+                .days
+                .lock()
+                .await
+                .entry(today)
+                .or_default()
+                .entry(provider.trim().to_lowercase())
+                .or_default()
+                .add(&delta);
+            store.dirty.notify_one();
+        });
+    }
+
+    /// Aggregates for `today`, `7d`, `30d`, or `all` (the default).
+    pub async fn stats(&self, range: &str) -> UsageStats {
+        let range = range.trim().to_lowercase();
+        let since = match range.as_str() {
+            "today" => Some(0),
+            "7d" | "week" => Some(6),
+            "30d" | "month" => Some(29),
+            _ => None,
+        }
+        .map(|days_back| {
+            (Local::now() - ChronoDuration::days(days_back))
+                .format("%Y-%m-%d")
+                .to_string()
+        });
+
+        let guard = self.days.lock().await;
+        let mut total = UsageCounters::default();
+        let mut by_provider: BTreeMap<String, UsageCounters> = BTreeMap::new();
+        let mut days = Vec::new();
+        for (date, providers) in guard.iter() {
+            if since.as_ref().is_some_and(|since| date < since) {
+                continue;
+            }
+            for (provider, counters) in providers {
+                total.add(counters);
+                by_provider
+                    .entry(provider.clone())
+                    .or_default()
+                    .add(counters);
+            }
+            days.push(UsageDay {
+                date: date.clone(),
+                providers: providers.clone(),
+            });
+        }
+        UsageStats {
+            range: if since.is_some() {
+                range
+            } else {
+                "all".to_string()
+            },
+            total,
+            by_provider,
+            days,
+        }
+    }
+
+    pub async fn reset(&self) -> Result<()> {
+        let mut guard = self.days.lock().await;
+        guard.clear();
+        persist(&self.path, &guard).await
+    }
+}
+
+fn spawn_writer(store: Arc<UsageStore>) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            store.dirty.notified().await;
+            tokio::time::sleep(PERSIST_DEBOUNCE).await;
+            let snapshot = store.days.lock().await.clone();
+            if let Err(error) = persist(&store.path, &snapshot).await {
+                log::warn!(target: "usage", "Failed to persist usage stats: {error}");
+            }
+        }
+    });
+}
+
+async fn persist(
+    path: &Path,
+    days: &BTreeMap<String, BTreeMap<String, UsageCounters>>,
+) -> Result<()> {
+    let serialized = serde_json::to_string_pretty(days).context("serialize usage stats")?;
+    fs::write(path, serialized)
+        .await
+        .context("write usage stats")
+}
+
+/// Duration of a RIFF/WAVE payload; other containers are not measured.
+pub fn wav_duration_seconds(bytes: &[u8]) -> Option<f64> {
+    resample::wav_header(bytes).ok()?.duration_seconds()
+}
@@ -0,0 +1,89 @@
+//! Versions of the app and the components it talks to, for debug reports. The
+//! last snapshot is kept so the panic hook and the log header can use it
+//! without waiting on the network.
+
+use std::sync::{Arc, Mutex};
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+use crate::app_log;
+use crate::http::HttpClients;
+use crate::local_speech::FastWhisperManager;
+use crate::ollama;
+
+static LAST: Mutex<Option<AppVersions>> = Mutex::new(None);
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppVersions {
+    pub app: String,
+    pub tauri: &'static str,
+    pub webview: Option<String>,
+    /// Branch and commit of the installed fast-whisper repository.
+    pub fast_whisper: Option<String>,
+    /// `None` when the Ollama server is not running.
+    pub ollama: Option<String>,
+    pub os: &'static str,
+    pub arch: &'static str,
+}
+
+impl AppVersions {
+    /// One line for log headers and crash reports.
+    pub fn summary(&self) -> String {
+        let missing = "none".to_string();
+        format!(
+            "app {} | tauri {} | webview {} | fast-whisper {} | ollama {} | {} {}",
+            self.app,
+            self.tauri,
+            self.webview.as_ref().unwrap_or(&missing),
+            self.fast_whisper.as_ref().unwrap_or(&missing),
+            self.ollama.as_ref().unwrap_or(&missing),
+            self.os,
+            self.arch
+        )
+    }
+}
+
+/// Looks every version up again and remembers the result.
+pub async fn collect(app: &AppHandle) -> AppVersions {
+    let fast_whisper = match app.try_state::<Arc<FastWhisperManager>>() {
+        Some(manager) => manager.installed_version(app).await,
+        None => None,
+    };
+    let client = app.state::<Arc<HttpClients>>().health();
+    let ollama = ollama::server_version(&client).await.ok();
+    let versions = AppVersions {
+        app: app.package_info().version.to_string(),
+        tauri: tauri::VERSION,
+        webview: tauri::webview_version().ok(),
+        fast_whisper,
+        ollama,
+        os: std::env::consts::OS,
+        arch: std::env::consts::ARCH,
+    };
+    *LAST.lock().unwrap() = Some(versions.clone());
+    versions
+}
+
+/// Collects the versions once at startup and puts them at the top of the log.
+pub fn log_at_startup(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let versions = collect(&app).await;
+        app_log::set_header(format!("Versions: {}", versions.summary()));
+    });
+}
+
+/// The last snapshot, or the parts known without looking anything up.
+pub fn summary() -> String {
+    if let Some(versions) = LAST.try_lock().ok().and_then(|last| last.clone()) {
+        return versions.summary();
+    }
+    format!(
+        "app {} | {} {}",
+        env!("CARGO_PKG_VERSION"),
+        std::env::consts::OS,
+        std::env::consts::ARCH
+    )
+}
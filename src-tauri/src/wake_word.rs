@@ -0,0 +1,249 @@
+//! Spots configured wake words in the captured mix. The encoder thread hands over
+//! mono windows of a few seconds; a single worker runs them through the local
+//! Whisper server and drops windows that arrive while it is busy, so detection
+//! never costs more than one small transcription at a time.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Result};
+use reqwest::multipart;
+use serde::Serialize;
+use serde_json::json;
+use tauri::{AppHandle, Manager};
+use tokio::sync::mpsc;
+
+use crate::audio_pipeline;
+use crate::events::{self, emit_event};
+use crate::http::HttpClients;
+use crate::local_speech::FastWhisperManager;
+use crate::resample;
+use crate::session::{self, InteractionTrigger};
+use crate::types::AppConfig;
+
+const MODEL: &str = "tiny";
+const SAMPLE_RATE: u32 = 16_000;
+const WINDOW: Duration = Duration::from_secs(3);
+// Kept from the end of each window so a word cut at the boundary is heard whole once.
+const OVERLAP: Duration = Duration::from_secs(1);
+// The overlap can hear the same word twice.
+const COOLDOWN: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WakeWordPayload {
+    pub word: String,
+}
+
+struct Settings {
+    /// Configured words, and the same words normalized for matching.
+    words: Vec<(String, String)>,
+    duration: Option<u32>,
+    /// Windows quieter than this RMS, from `vad_threshold_db`, are not worth a transcription.
+    min_rms: f32,
+}
+
+#[derive(Default)]
+struct Window {
+    sample_rate: u32,
+    samples: Vec<i16>,
+}
+
+struct Clip {
+    sample_rate: u32,
+    samples: Vec<i16>,
+}
+
+/// Wake word settings plus the worker that checks windows against them. Both
+/// exist only while at least one word is configured.
+pub struct WakeWordDetector {
+    enabled: AtomicBool,
+    settings: Arc<Mutex<Option<Arc<Settings>>>>,
+    window: Mutex<Window>,
+    worker: Mutex<Option<mpsc::Sender<Clip>>>,
+}
+
+impl WakeWordDetector {
+    pub fn new() -> Self {
+        Self {
+            enabled: AtomicBool::new(false),
+            settings: Arc::new(Mutex::new(None)),
+            window: Mutex::new(Window::default()),
+            worker: Mutex::new(None),
+        }
+    }
+
+    /// Starts the worker when words are configured and stops it when they are gone.
+    pub fn apply_config(&self, app: &AppHandle, config: &AppConfig) {
+        let settings = (!config.wake_words.is_empty()).then(|| Settings {
+            words: config
+                .wake_words
+                .iter()
+                .map(|word| (word.clone(), normalize(word)))
+                .filter(|(_, normalized)| !normalized.is_empty())
+                .collect(),
+            duration: config
+                .wake_word_action
+                .as_ref()
+                .and_then(|action| action.duration),
+            min_rms: 10f32.powf(config.vad_threshold_db / 20.0),
+        });
+        let enabled = settings.is_some();
+        *self.settings.lock().unwrap() = settings.map(Arc::new);
+        let mut worker = self.worker.lock().unwrap();
+        if enabled && worker.is_none() {
+            log::info!(
+                target: "wake-word",
+                "Wake word detection on: words={}",
+                config.wake_words.len()
+            );
+            *worker = Some(spawn_worker(app.clone(), self.settings.clone()));
+        } else if !enabled && worker.take().is_some() {
+            log::info!(target: "wake-word", "Wake word detection off");
+            *self.window.lock().unwrap() = Window::default();
+        }
+        self.enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Called by the encoder thread for every mixed chunk; a no-op while disabled.
+    pub fn feed(&self, samples: &[i16], sample_rate: u32, channels: u16) {
+        if !self.enabled.load(Ordering::Relaxed) || sample_rate == 0 {
+            return;
+        }
+        let mut window = self.window.lock().unwrap();
+        if window.sample_rate != sample_rate {
+            window.sample_rate = sample_rate;
+            window.samples.clear();
+        }
+        let channels = channels.max(1) as usize;
+        window.samples.extend(
+            samples.chunks_exact(channels).map(|frame| {
+                (frame.iter().map(|&s| s as i32).sum::<i32>() / channels as i32) as i16
+            }),
+        );
+
+        let full = (sample_rate as f64 * WINDOW.as_secs_f64()) as usize;
+        if window.samples.len() < full {
+            return;
+        }
+        let keep = (sample_rate as f64 * OVERLAP.as_secs_f64()) as usize;
+        let clip = Clip {
+            sample_rate,
+            samples: window.samples.clone(),
+        };
+        let start = window.samples.len() - keep;
+        window.samples.drain(..start);
+        drop(window);
+        if let Some(worker) = self.worker.lock().unwrap().as_ref() {
+            // A busy worker means the previous window is still being checked.
+            let _ = worker.try_send(clip);
+        }
+    }
+}
+
+fn spawn_worker(app: AppHandle, settings: Arc<Mutex<Option<Arc<Settings>>>>) -> mpsc::Sender<Clip> {
+    let (tx, mut rx) = mpsc::channel::<Clip>(1);
+    tauri::async_runtime::spawn(async move {
+        let mut last_hit: Option<Instant> = None;
+        while let Some(clip) = rx.recv().await {
+            let Some(current) = settings.lock().unwrap().clone() else {
+                continue;
+            };
+            if audio_pipeline::rms(&clip.samples) < current.min_rms
+                || last_hit.is_some_and(|at| at.elapsed() < COOLDOWN)
+            {
+                continue;
+            }
+            let text = match transcribe(&app, clip).await {
+                Ok(Some(text)) => normalize(&text),
+                Ok(None) => continue,
+                Err(error) => {
+                    log::debug!(target: "wake-word", "Window not checked: {error:#}");
+                    continue;
+                }
+            };
+            let Some((word, _)) = current
+                .words
+                .iter()
+                .find(|(_, normalized)| contains_phrase(&text, normalized))
+            else {
+                continue;
+            };
+            last_hit = Some(Instant::now());
+            log::info!(target: "wake-word", "Wake word heard: {word}");
+            let _ = emit_event(
+                &app,
+                events::AUDIO_WAKE_WORD,
+                WakeWordPayload { word: word.clone() },
+            );
+            if let Some(seconds) = current.duration {
+                trigger_clip(&app, seconds);
+            }
+        }
+    });
+    tx
+}
+
+/// Transcribes `clip` with the small local model. `None` while the local server
+/// is not running, since starting it is up to the user.
+async fn transcribe(app: &AppHandle, clip: Clip) -> Result<Option<String>> {
+    let Some(server) = app.try_state::<Arc<FastWhisperManager>>() else {
+        return Ok(None);
+    };
+    if !server.get_status().await.running {
+        return Ok(None);
+    }
+    let wav = resample::encode_wav(&clip.samples, 1, clip.sample_rate);
+    let wav = resample::downsample_wav(&wav, SAMPLE_RATE)?.unwrap_or(wav);
+    let api = server.server_api(app).await?;
+    let file = multipart::Part::bytes(wav)
+        .file_name("wake-word.wav")
+        .mime_str("audio/wav")?;
+    let form = multipart::Form::new()
+        .text(api.model_field, MODEL)
+        .part(api.file_field, file);
+    let response = app
+        .state::<Arc<HttpClients>>()
+        .local()
+        .post(FastWhisperManager::server_url(api.transcriptions_path))
+        .multipart(form)
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        bail!("local server returned {}", response.status());
+    }
+    let body: serde_json::Value = response.json().await?;
+    Ok(body
+        .get("text")
+        .and_then(|text| text.as_str())
+        .map(str::to_string))
+}
+
+/// Asks the webview for a clip the same way a duration hotkey does.
+fn trigger_clip(app: &AppHandle, seconds: u32) {
+    let ids = session::begin_interaction(app, InteractionTrigger::WakeWord, Some(seconds as f64));
+    let _ = emit_event(
+        app,
+        events::HOTKEYS_DURATION,
+        json!({
+            "sec": seconds,
+            "sessionId": ids.as_ref().map(|ids| &ids.session_id),
+            "interactionId": ids.as_ref().map(|ids| &ids.interaction_id),
+        }),
+    );
+}
+
+/// Lowercase words separated by single spaces, without punctuation.
+fn normalize(text: &str) -> String {
+    text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Whole-word match, so "max" is not heard in "maximum".
+fn contains_phrase(text: &str, phrase: &str) -> bool {
+    format!(" {text} ").contains(&format!(" {phrase} "))
+}
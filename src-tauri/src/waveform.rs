@@ -0,0 +1,340 @@
+//! Waveform thumbnails of saved WAV recordings for the history list, so the
+//! webview gets a few hundred points instead of the PCM.
+//!
+//! Plain files are read in blocks without loading them whole; encrypted ones
+//! have to be decrypted in memory first. Results for plain files are cached as
+//! JSON in `waveform_cache`, keyed by file name and checked against the size and
+//! modification time of the recording. Encrypted recordings are never cached.
+
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufReader, Cursor, Read, Seek};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::UNIX_EPOCH;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::artifacts::{self, ArtifactVault};
+use crate::resample::{self, WavError};
+use crate::types::AppConfig;
+
+const CACHE_DIR_NAME: &str = "waveform_cache";
+const READ_BLOCK_BYTES: usize = 64 * 1024;
+pub const DEFAULT_BUCKETS: u32 = 200;
+const MAX_BUCKETS: u32 = 4000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WaveformMode {
+    /// One RMS value per bucket, 0.0-1.0.
+    #[default]
+    Rms,
+    /// A min and a max per bucket, interleaved, -1.0-1.0.
+    Peaks,
+}
+
+impl WaveformMode {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Rms => "rms",
+            Self::Peaks => "peaks",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum WaveformError {
+    /// Outside the recordings folders, or gone.
+    NotFound(String),
+    Io(io::Error),
+    /// Encrypted with a key that is not available.
+    Decrypt(String),
+    NotWav,
+    Malformed(&'static str),
+    Unsupported {
+        format: u16,
+        bits: u16,
+    },
+    Empty,
+}
+
+impl WaveformError {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::NotFound(_) => "not_found",
+            Self::Io(_) => "io",
+            Self::Decrypt(_) => "decrypt",
+            Self::NotWav => "not_wav",
+            Self::Malformed(_) => "malformed",
+            Self::Unsupported { .. } => "unsupported",
+            Self::Empty => "empty",
+        }
+    }
+}
+
+impl fmt::Display for WaveformError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotFound(reason) | Self::Decrypt(reason) => f.write_str(reason),
+            Self::Io(error) => write!(f, "recording could not be read: {error}"),
+            Self::NotWav => f.write_str("not a RIFF/WAVE file"),
+            Self::Malformed(reason) => write!(f, "malformed WAV file: {reason}"),
+            Self::Unsupported { format, bits } => {
+                write!(f, "unsupported WAV encoding (format {format}, {bits} bits)")
+            }
+            Self::Empty => f.write_str("WAV file contains no audio"),
+        }
+    }
+}
+
+impl std::error::Error for WaveformError {}
+
+impl From<io::Error> for WaveformError {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+impl From<WavError> for WaveformError {
+    fn from(error: WavError) -> Self {
+        match error {
+            WavError::NotWav => Self::NotWav,
+            WavError::Malformed(reason) => Self::Malformed(reason),
+            WavError::Unsupported { format, bits } => Self::Unsupported { format, bits },
+            WavError::Io(error) => Self::Io(error),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CachedWaveform {
+    source_len: u64,
+    source_modified_ms: u64,
+    values: Vec<f32>,
+}
+
+/// `buckets` points (two per bucket for peaks) for the recording at
+/// `path_or_id`: a path inside the recordings folders, or the file name of a
+/// debug recording.
+pub async fn generate(
+    app: &AppHandle,
+    config: &AppConfig,
+    path_or_id: &str,
+    buckets: u32,
+    mode: WaveformMode,
+) -> Result<Vec<f32>, WaveformError> {
+    let buckets = buckets.clamp(1, MAX_BUCKETS) as usize;
+    let path = resolve(app, config, path_or_id)?;
+
+    if artifacts::is_sealed(&path).await? {
+        let plain = app
+            .state::<Arc<ArtifactVault>>()
+            .read(&path)
+            .await
+            .map_err(|error| WaveformError::Decrypt(format!("{error:#}")))?;
+        return run_blocking(move || compute(&mut Cursor::new(plain), buckets, mode)).await;
+    }
+
+    let metadata = tokio::fs::metadata(&path).await?;
+    let source_len = metadata.len();
+    let source_modified_ms = metadata
+        .modified()
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map(|elapsed| elapsed.as_millis() as u64)
+        .unwrap_or_default();
+    let cache_path = cache_path(app, &path, buckets, mode);
+    if let Some(cache_path) = &cache_path {
+        if let Some(cached) = read_cache(cache_path).await {
+            if cached.source_len == source_len && cached.source_modified_ms == source_modified_ms {
+                return Ok(cached.values);
+            }
+        }
+    }
+
+    let source = path.clone();
+    let values = run_blocking(move || {
+        let mut reader = BufReader::with_capacity(READ_BLOCK_BYTES, File::open(&source)?);
+        compute(&mut reader, buckets, mode)
+    })
+    .await?;
+
+    if let Some(cache_path) = cache_path {
+        let cached = CachedWaveform {
+            source_len,
+            source_modified_ms,
+            values,
+        };
+        write_cache(&cache_path, &cached).await;
+        return Ok(cached.values);
+    }
+    Ok(values)
+}
+
+fn resolve(
+    app: &AppHandle,
+    config: &AppConfig,
+    path_or_id: &str,
+) -> Result<PathBuf, WaveformError> {
+    let path_or_id = path_or_id.trim();
+    let is_id = !path_or_id.is_empty()
+        && !path_or_id.contains(['/', '\\'])
+        && path_or_id != ".."
+        && path_or_id != ".";
+    let candidate = if is_id {
+        artifacts::debug_audio_dir(app)
+            .map_err(|error| WaveformError::NotFound(error.to_string()))?
+            .join(path_or_id)
+    } else {
+        PathBuf::from(path_or_id)
+    };
+    artifacts::resolve_artifact(app, config, &candidate.to_string_lossy())
+        .map_err(|error| WaveformError::NotFound(error.to_string()))
+}
+
+async fn run_blocking(
+    job: impl FnOnce() -> Result<Vec<f32>, WaveformError> + Send + 'static,
+) -> Result<Vec<f32>, WaveformError> {
+    tokio::task::spawn_blocking(job)
+        .await
+        .map_err(|error| WaveformError::Io(io::Error::other(error)))?
+}
+
+fn cache_path(
+    app: &AppHandle,
+    source: &Path,
+    buckets: usize,
+    mode: WaveformMode,
+) -> Option<PathBuf> {
+    let name = source.file_name()?.to_string_lossy();
+    let dir = app.path().app_local_data_dir().ok()?.join(CACHE_DIR_NAME);
+    Some(dir.join(format!("{name}.{buckets}.{}.json", mode.as_str())))
+}
+
+async fn read_cache(path: &Path) -> Option<CachedWaveform> {
+    let bytes = tokio::fs::read(path).await.ok()?;
+    serde_json::from_slice(&bytes).ok()
+}
+
+/// Best effort: a cache that cannot be written only costs a recomputation.
+async fn write_cache(path: &Path, cached: &CachedWaveform) {
+    let result = async {
+        if let Some(dir) = path.parent() {
+            tokio::fs::create_dir_all(dir).await?;
+        }
+        tokio::fs::write(path, serde_json::to_vec(cached)?).await?;
+        anyhow::Ok(())
+    };
+    if let Err(error) = result.await {
+        log::debug!(target: "waveform", "Waveform not cached at {}: {error:#}", path.display());
+    }
+}
+
+fn compute<R: Read + Seek>(
+    reader: &mut R,
+    buckets: usize,
+    mode: WaveformMode,
+) -> Result<Vec<f32>, WaveformError> {
+    let data = resample::read_wav_header(reader)?;
+    let decode = data.sample_decoder()?;
+    let sample_bytes = data.bytes_per_sample();
+    let frame_bytes = sample_bytes * data.channels;
+    let total_frames = data.data_len / frame_bytes as u64;
+    if total_frames == 0 {
+        return Err(WaveformError::Empty);
+    }
+
+    let mut accumulator = Buckets::new(buckets, total_frames, mode);
+    // Whole frames per read, so none straddles two blocks.
+    let mut block = vec![0u8; (READ_BLOCK_BYTES / frame_bytes).max(1) * frame_bytes];
+    let mut remaining = total_frames * frame_bytes as u64;
+    while remaining > 0 {
+        let want = (block.len() as u64).min(remaining) as usize;
+        reader.read_exact(&mut block[..want])?;
+        remaining -= want as u64;
+        for frame in block[..want].chunks_exact(frame_bytes) {
+            let sum: f32 = frame.chunks_exact(sample_bytes).map(decode).sum();
+            accumulator.push(sum / data.channels as f32);
+        }
+    }
+    Ok(accumulator.finish())
+}
+
+/// Folds frames into `count` equal spans of the recording.
+struct Buckets {
+    count: usize,
+    total_frames: u64,
+    mode: WaveformMode,
+    frame: u64,
+    current: usize,
+    min: f32,
+    max: f32,
+    sum_squares: f64,
+    frames_in_bucket: u64,
+    values: Vec<f32>,
+}
+
+impl Buckets {
+    fn new(count: usize, total_frames: u64, mode: WaveformMode) -> Self {
+        let per_bucket = match mode {
+            WaveformMode::Rms => 1,
+            WaveformMode::Peaks => 2,
+        };
+        Self {
+            count,
+            total_frames,
+            mode,
+            frame: 0,
+            current: 0,
+            min: 0.0,
+            max: 0.0,
+            sum_squares: 0.0,
+            frames_in_bucket: 0,
+            values: Vec::with_capacity(count * per_bucket),
+        }
+    }
+
+    fn push(&mut self, value: f32) {
+        let bucket = (self.frame * self.count as u64 / self.total_frames) as usize;
+        while self.current < bucket {
+            self.flush();
+        }
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+        self.sum_squares += (value as f64) * (value as f64);
+        self.frames_in_bucket += 1;
+        self.frame += 1;
+    }
+
+    fn flush(&mut self) {
+        match self.mode {
+            WaveformMode::Rms => {
+                let rms = if self.frames_in_bucket == 0 {
+                    0.0
+                } else {
+                    (self.sum_squares / self.frames_in_bucket as f64).sqrt() as f32
+                };
+                self.values.push(rms);
+            }
+            WaveformMode::Peaks => {
+                self.values.push(self.min);
+                self.values.push(self.max);
+            }
+        }
+        self.current += 1;
+        self.min = 0.0;
+        self.max = 0.0;
+        self.sum_squares = 0.0;
+        self.frames_in_bucket = 0;
+    }
+
+    fn finish(mut self) -> Vec<f32> {
+        while self.current < self.count {
+            self.flush();
+        }
+        self.values
+    }
+}
@@ -0,0 +1,68 @@
+//! Saving the main window size as the user resizes it. A drag fires a resize
+//! event per frame, so the size is saved once the events stop, under its own
+//! origin so nothing sets it back on the window.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde_json::json;
+use tauri::{AppHandle, Manager, PhysicalSize, WebviewWindow};
+
+use crate::config::ConfigState;
+use crate::constants::WINDOW_RESIZE_SAVE_DEBOUNCE_MS;
+
+/// `config:updated` origin of a size saved from a resize.
+pub const RESIZE_ORIGIN: &str = "resize";
+
+/// Bumped by every resize event; a pending save only runs if it is still the
+/// latest one when its delay ends.
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// Call on each `WindowEvent::Resized` of the main window.
+pub fn on_resized(app: &AppHandle, size: PhysicalSize<u32>) {
+    let Some(window) = app.get_webview_window("main") else {
+        return;
+    };
+    // Minimizing reports a zero size and maximizing is not a size to keep.
+    if size.width == 0 || size.height == 0 || window.is_maximized().unwrap_or(false) {
+        return;
+    }
+    let Ok(scale) = window.scale_factor() else {
+        return;
+    };
+    let logical = size.to_logical::<f64>(scale);
+    let (width, height) = (logical.width.round() as u32, logical.height.round() as u32);
+    let generation = GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(WINDOW_RESIZE_SAVE_DEBOUNCE_MS)).await;
+        if GENERATION.load(Ordering::SeqCst) != generation {
+            return;
+        }
+        save(&app, width, height).await;
+    });
+}
+
+async fn save(app: &AppHandle, width: u32, height: u32) {
+    let config = app.state::<Arc<ConfigState>>();
+    let current = config.get().await;
+    if current.window_width == width && current.window_height == height {
+        return;
+    }
+    log::info!(target: "window", "Saving window size {width}x{height}");
+    let partial = json!({ "windowWidth": width, "windowHeight": height });
+    if let Err(error) = config.update(partial, RESIZE_ORIGIN).await {
+        log::warn!(target: "window", "Failed to save window size: {error:#}");
+    }
+}
+
+/// Whether the window already has this logical size, as after the user's own
+/// resize; setting it again would fight a drag still in progress.
+pub fn has_size(window: &WebviewWindow, width: f64, height: f64) -> bool {
+    let (Ok(size), Ok(scale)) = (window.inner_size(), window.scale_factor()) else {
+        return false;
+    };
+    let logical = size.to_logical::<f64>(scale);
+    (logical.width - width).abs() < 1.0 && (logical.height - height).abs() < 1.0
+}
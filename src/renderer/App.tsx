@@ -16,6 +16,14 @@ import {BetaFeedbackWidget} from './components/feedback/BetaFeedbackWidget';
 import {muiTheme} from './mui/config.mui';
 import {setCurrentUser} from './utils/featureAccess';
 import {listen, UnlistenFn} from '@tauri-apps/api/event';
+import type {
+    CaptureProtectionStatus,
+    ConfigLockHolder,
+    ConfigPersisted,
+    ConfigPersistenceStatus,
+    PrivacyWarning,
+    SystemResumed,
+} from '@shared/ipc';
 
 type UpdateAvailablePayload = {
     version: string;
@@ -39,6 +47,8 @@ type UpdateErrorPayload = {
 };
 
 const UPDATE_TOAST_ID = 'xexamai-update';
+const PRIVACY_TOAST_ID = 'xexamai-privacy';
+const PERSISTENCE_TOAST_ID = 'xexamai-persistence';
 
 function formatBytes(bytes: number): string {
     if (!Number.isFinite(bytes) || bytes <= 0) {
@@ -126,6 +136,179 @@ function useUpdateNotifications() {
     }, []);
 }
 
+async function enableHideMode() {
+    try {
+        // Hide mode needs always-on-top, same as the settings toggle.
+        await window.api.settings.setAlwaysOnTop(true);
+        await window.api.settings.setHideApp(true);
+        toast.dismiss(PRIVACY_TOAST_ID);
+    } catch (error) {
+        console.warn('[privacy] failed to enable hide mode', error);
+    }
+}
+
+function usePrivacyWarnings() {
+    useEffect(() => {
+        const unlisteners: UnlistenFn[] = [];
+        let cancelled = false;
+        const keep = (fn: UnlistenFn) => {
+            if (cancelled) {
+                fn();
+                return;
+            }
+            unlisteners.push(fn);
+        };
+
+        // The window was recreated and hiding from capture did not come back with it.
+        void listen<CaptureProtectionStatus>('privacy:lost', () => {
+            toast.warning('The window was recreated and is no longer hidden from screen capture. It may be visible in screen shares.', {
+                toastId: PRIVACY_TOAST_ID,
+                autoClose: false,
+            });
+        }).then(keep);
+        void listen<CaptureProtectionStatus>('privacy:reapplied', () => {
+            toast.dismiss(PRIVACY_TOAST_ID);
+        }).then(keep);
+
+        void listen<PrivacyWarning>('privacy:warning', (event) => {
+            const warning = event.payload;
+            if (!warning.notify) return;
+            if (warning.hideApp) {
+                // Hide mode is on but the driver did not apply it; there is nothing to toggle.
+                toast.warning('Hiding from screen capture did not take effect on this system. The window may be visible in screen shares.', {
+                    toastId: PRIVACY_TOAST_ID,
+                    autoClose: 10000,
+                });
+                return;
+            }
+            toast.warning(
+                <div>
+                    <div>Recording started while the app is visible to screen capture.</div>
+                    <button type="button" className="btn btn-sm" onClick={() => void enableHideMode()}>
+                        Hide from capture
+                    </button>
+                </div>,
+                {toastId: PRIVACY_TOAST_ID, autoClose: 10000},
+            );
+        }).then(keep);
+
+        return () => {
+            cancelled = true;
+            unlisteners.forEach((fn) => fn());
+        };
+    }, []);
+}
+
+async function retryPersistence() {
+    try {
+        const status = await window.api.settings.retryPersistence();
+        if (status.persistenceAvailable) {
+            toast.dismiss(PERSISTENCE_TOAST_ID);
+            toast.success('Settings are saved to disk again.');
+        }
+    } catch (error) {
+        console.warn('[config] retrying persistence failed', error);
+        toast.update(PERSISTENCE_TOAST_ID, {
+            render: persistenceToast(`Still unable to save settings: ${String(error)}`),
+        });
+    }
+}
+
+function persistenceToast(message: string) {
+    return (
+        <div>
+            <div>{message}</div>
+            <button type="button" className="btn btn-sm" onClick={() => void retryPersistence()}>
+                Retry
+            </button>
+        </div>
+    );
+}
+
+function showReadonlyWarning(lockedBy?: ConfigLockHolder | null) {
+    if (toast.isActive(PERSISTENCE_TOAST_ID)) return;
+    // Retry takes the config over once the other copy has exited.
+    const message = lockedBy
+        ? `Settings are in use by another running xexamai (PID ${lockedBy.pid}); changes here will not be saved.`
+        : 'Settings cannot be saved; changes will be lost on restart.';
+    toast.warning(persistenceToast(message), {
+        toastId: PERSISTENCE_TOAST_ID,
+        autoClose: false,
+    });
+}
+
+function useConfigPersistence() {
+    useEffect(() => {
+        let unlisten: UnlistenFn | null = null;
+        let cancelled = false;
+
+        // The startup event may fire before this listener exists.
+        window.api.settings.get()
+            .then((settings) => {
+                if (!cancelled && settings.persistenceAvailable === false) showReadonlyWarning();
+            })
+            .catch(() => {});
+
+        void listen<ConfigPersistenceStatus>('config:readonly', (event) => {
+            if (event.payload.persistenceAvailable) {
+                toast.dismiss(PERSISTENCE_TOAST_ID);
+            } else {
+                showReadonlyWarning(event.payload.lockedBy);
+            }
+        }).then((fn) => {
+            if (cancelled) {
+                fn();
+                return;
+            }
+            unlisten = fn;
+        });
+
+        let unlistenPersisted: UnlistenFn | null = null;
+        void listen<ConfigPersisted>('config:persisted', (event) => {
+            console.info('[config] delayed write succeeded', event.payload);
+        }).then((fn) => {
+            if (cancelled) {
+                fn();
+                return;
+            }
+            unlistenPersisted = fn;
+        });
+
+        return () => {
+            cancelled = true;
+            unlisten?.();
+            unlistenPersisted?.();
+        };
+    }, []);
+}
+
+function useSystemResume() {
+    useEffect(() => {
+        let unlisten: UnlistenFn | null = null;
+        let cancelled = false;
+
+        void listen<SystemResumed>('system:resumed', (event) => {
+            const {audioRestarted, failed} = event.payload;
+            if (failed.includes('audio')) {
+                toast.error('Recording stopped while the computer was asleep and could not be restarted.', {autoClose: 8000});
+            } else if (audioRestarted) {
+                toast.info('Recording was restarted after sleep.');
+            }
+        }).then((fn) => {
+            if (cancelled) {
+                fn();
+                return;
+            }
+            unlisten = fn;
+        });
+
+        return () => {
+            cancelled = true;
+            unlisten?.();
+        };
+    }, []);
+}
+
 function AuthenticatedApp() {
     const initializedRef = useRef(false);
     const [activeTab, setActiveTab] = useState<'main' | 'settings' | 'profile'>('main');
@@ -401,6 +584,9 @@ function AppContent() {
 
 export function App() {
     useUpdateNotifications();
+    usePrivacyWarnings();
+    useConfigPersistence();
+    useSystemResume();
 
     return (
         <ThemeProvider theme={muiTheme}>
@@ -1,7 +1,7 @@
 import {AudioInputType, audioSessionState} from './internalState';
 import {setStatus} from '../../ui/status';
 import {state as appState} from '../../state/appState';
-import {startRecording, stopRecording} from './recorder';
+import {AudioSourceKind, switchAudioSource} from '../../services/nativeAudio';
 
 export type SwitchAudioResult = { success: boolean; error?: string };
 
@@ -18,30 +18,23 @@ export async function switchAudioInput(newType: AudioInputType): Promise<SwitchA
         return {success: true};
     }
 
-    // While recording we need to restart capture with the new type
+    // The native side hands the running capture over to the new source, so the
+    // ring buffer keeps filling without a gap.
     try {
         try {
             await window.api.settings.setAudioInputType(newType);
         } catch {
         }
 
-        // Stop the current capture completely
-        await stopRecording();
-
-        // Small delay to let resources clean up
-        await new Promise(resolve => setTimeout(resolve, 150));
-
-        // Update the input type
+        const source: AudioSourceKind = newType === 'microphone' ? 'mic' : newType;
+        await switchAudioSource(source);
         audioSessionState.currentAudioInputType = newType;
 
-        // Start capture with the new type
-        await startRecording();
-
         return {success: true};
     } catch (error) {
         console.error('Error switching audio input', error);
+        // The previous source is still recording.
         setStatus('Failed to switch audio input', 'error');
-        // Restore the previous type on error
         audioSessionState.currentAudioInputType = previousType;
         return {success: false, error: error instanceof Error ? error.message : String(error)};
     }
@@ -6,14 +6,27 @@ import {ensureWave, hideWave, showWave} from '../../ui/waveform';
 import {state as appState} from '../../state/appState';
 import {logger} from '../../utils/logger';
 import {audioSessionState} from './internalState';
-import {AudioSourceKind, onAudioChunk, startAudioCapture, stopAudioCapture} from '../../services/nativeAudio';
-import {settingsStore} from '../../state/settingsStore';
+import {
+    AudioSourceKind,
+    onAudioChunk,
+    onAudioPartialStart,
+    startAudioCapture,
+    stopAudioCapture,
+} from '../../services/nativeAudio';
 import {setStatus} from '../../ui/status';
 
 let audioUnsubscribe: (() => void) | null = null;
 
-export async function startRecording(): Promise<void> {
-    logger.info('recording', 'Starting recording');
+// Mixed mode keeps recording with whichever input opened; tell the user which one is missing.
+onAudioPartialStart(({missing}) => {
+    const names = missing.map(({source}) => (source === 'mic' ? 'microphone' : 'system audio'));
+    logger.warn('audioSession', 'Capture started without some sources', {missing});
+    setStatus(`Recording without ${names.join(' and ')}.`, 'ready');
+});
+
+/** `attach` picks up a capture the native side already runs instead of starting one. */
+export async function startRecording({attach = false}: { attach?: boolean } = {}): Promise<void> {
+    logger.info('recording', 'Starting recording', {attach});
 
     cleanupRecorder();
     await cleanupAudioGraph();
@@ -28,11 +41,11 @@ export async function startRecording(): Promise<void> {
     audioSessionState.visualizer.startFromLevels(audioSessionState.waveCanvas, {bars: 72, smoothing: 0.75});
 
     // Use native Rust capture for all modes (WASAPI loopback for system and mixed)
-    await startNativeRecording();
+    await startNativeRecording(attach);
 }
 
 
-async function startNativeRecording(): Promise<void> {
+async function startNativeRecording(attach: boolean): Promise<void> {
     const inputType = audioSessionState.currentAudioInputType;
 
     // Initialize shared buffer regardless of mode
@@ -63,8 +76,9 @@ async function startNativeRecording(): Promise<void> {
             }
             audioSessionState.pcmRing.push(chunk.samples, frames, chunk.sampleRate);
             audioSessionState.rmsLevel = chunk.rms;
+            // Read per chunk: a source switch keeps this listener.
             const visualizerLevel =
-                inputType === 'system'
+                audioSessionState.currentAudioInputType === 'system'
                     ? chunk.rms * 0.1
                     : chunk.rms;
             audioSessionState.visualizer?.ingestLevel(visualizerLevel);
@@ -74,6 +88,11 @@ async function startNativeRecording(): Promise<void> {
         }
     });
 
+    if (attach) {
+        logger.info('audioSession', 'Attached to running native capture', {inputType});
+        return;
+    }
+
     // Ensure listener is registered before starting capture
     await new Promise(resolve => setTimeout(resolve, 50));
 
@@ -84,37 +103,12 @@ async function startNativeRecording(): Promise<void> {
                 ? 'mixed'
                 : 'mic';
 
-    let deviceId: string | undefined;
-    if (source === 'mic' || source === 'mixed') {
-        try {
-            const settings = settingsStore.get();
-            deviceId = settings.audioInputDeviceId || undefined;
-        } catch {
-            try {
-                const settings = await settingsStore.load();
-                deviceId = settings.audioInputDeviceId || undefined;
-            } catch {
-            }
-        }
-    }
-
     try {
-        logger.info('audioSession', 'Starting native audio capture', {source, deviceId, inputType});
-        await startAudioCapture(source, deviceId);
+        // The native side picks the saved device for this input type.
+        logger.info('audioSession', 'Starting native audio capture', {source, inputType});
+        await startAudioCapture(source);
         logger.info('audioSession', 'Native audio capture started successfully', {source, inputType});
     } catch (error) {
-        if (source === 'mixed') {
-            logger.warn('audioSession', 'Mixed capture failed, retrying with microphone only', {
-                error: error instanceof Error ? error.message : String(error),
-                deviceId,
-            });
-            try {
-                await stopAudioCapture();
-            } catch {
-            }
-            await fallbackToMicrophone(deviceId, error);
-            return;
-        }
         logger.error('recording', 'Failed to start native capture', {error});
         const description =
             error instanceof Error
@@ -125,22 +119,6 @@ async function startNativeRecording(): Promise<void> {
     }
 }
 
-async function fallbackToMicrophone(deviceId: string | undefined, originalError: unknown): Promise<void> {
-    audioSessionState.currentAudioInputType = 'microphone';
-    settingsStore.patch({audioInputType: 'microphone'});
-    try {
-        await window.api.settings.setAudioInputType('microphone');
-    } catch {
-    }
-
-    await startAudioCapture('mic', deviceId);
-    logger.info('audioSession', 'Microphone fallback started after mixed capture failure', {
-        deviceId,
-        originalError: originalError instanceof Error ? originalError.message : String(originalError),
-    });
-    setStatus('System audio unavailable. Switched to microphone only.', 'ready');
-}
-
 export async function stopRecording(): Promise<void> {
     logger.info('recording', 'Stopping recording');
 
@@ -197,8 +197,17 @@ export class StreamController {
         }
     }
 
-    async handleAskWindow(seconds: number): Promise<void> {
-        logger.info('ui', 'Handle ask window', {seconds});
+    /** Picks up a capture that was started natively, e.g. by `autoStartCapture` on launch. */
+    async attachRunningCapture(inputType: 'microphone' | 'system' | 'mixed'): Promise<void> {
+        setAudioInputType(inputType);
+        await this.updateToggleButtonLabel(inputType);
+        await startAudioRecording({attach: true});
+        await this.updateStreamModeVisibility('base');
+    }
+
+    /** `interactionId` comes from the duration hotkey event and doubles as the request id. */
+    async handleAskWindow(seconds: number, interactionId?: string): Promise<void> {
+        logger.info('ui', 'Handle ask window', {seconds, interactionId});
 
         if (state.isProcessing) {
             await this.stopActiveOperation();
@@ -248,7 +257,7 @@ export class StreamController {
             return;
         }
 
-        const requestId = `ask-window-${seconds}-` + Date.now();
+        const requestId = interactionId ?? `ask-window-${seconds}-` + Date.now();
         logger.info('ui', 'Sending audio for transcription', {
             size: audioBuffer.byteLength,
             seconds,
@@ -267,6 +276,7 @@ export class StreamController {
                 mime: 'audio/wav',
                 filename: `last_${seconds}s.wav`,
                 audioSeconds: seconds,
+                interactionId: requestId,
             });
 
             if (opId !== this.activeOpId) {
@@ -665,7 +675,7 @@ export class StreamController {
         }
 
         const model = normalizeOllamaModelName(
-            settings.localLlmModel || settings.llmModel || LOCAL_LLM_MODELS[0] || 'gpt-oss:20b'
+            settings.localLlmModel || LOCAL_LLM_MODELS[0] || 'gpt-oss:20b'
         );
 
         try {
@@ -0,0 +1,18 @@
+import type {WindowPrefs} from '@shared/ipc';
+
+const BASE_FONT_SIZE = 16;
+
+// rem-based sizes follow the html font size, so this scales the whole UI.
+function applyWindowScale({scale}: WindowPrefs): void {
+    const html = document.documentElement;
+    html.style.setProperty('--app-scale', String(scale));
+    html.style.fontSize = `${BASE_FONT_SIZE * scale}px`;
+}
+
+export function setupWindowScale(): void {
+    window.api.window.onScaleChanged(applyWindowScale);
+    window.api.window
+        .getInitialPrefs()
+        .then(applyWindowScale)
+        .catch((error) => console.warn('[window] failed to load window prefs', error));
+}
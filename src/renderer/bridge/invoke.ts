@@ -0,0 +1,38 @@
+import {invoke as tauriInvoke, InvokeArgs} from '@tauri-apps/api/core';
+import type {CommandErrorCode, CommandErrorPayload} from '@shared/ipc';
+
+/**
+ * Native command failure. `message` is already localized to `uiLanguage`;
+ * the raw provider or OS error, if any, is in `detail`.
+ */
+export class CommandError extends Error {
+    readonly code: CommandErrorCode;
+    readonly detail: string | null;
+    readonly data: Record<string, unknown> | null;
+
+    constructor(payload: CommandErrorPayload) {
+        super(payload.message);
+        this.name = 'CommandError';
+        this.code = payload.code;
+        this.detail = payload.detail ?? null;
+        this.data = payload.data ?? null;
+    }
+}
+
+const isCommandErrorPayload = (value: unknown): value is CommandErrorPayload =>
+    typeof value === 'object'
+    && value !== null
+    && typeof (value as CommandErrorPayload).code === 'string'
+    && typeof (value as CommandErrorPayload).message === 'string';
+
+/** Drop-in for the core `invoke` that rejects with `CommandError` for coded failures. */
+export async function invoke<T>(cmd: string, args?: InvokeArgs): Promise<T> {
+    try {
+        return await tauriInvoke<T>(cmd, args);
+    } catch (error) {
+        if (isCommandErrorPayload(error)) {
+            throw new CommandError(error);
+        }
+        throw error;
+    }
+}
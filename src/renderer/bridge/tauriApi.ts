@@ -1,12 +1,59 @@
-import {invoke} from '@tauri-apps/api/core';
+import {invoke} from './invoke';
 import {getCurrentWindow, LogicalPosition, LogicalSize,} from '@tauri-apps/api/window';
 import {
+    ApiKeyProvider,
+    ApiKeyTestResult,
+    AppBootstrap,
+    AppSettings,
+    AppVersions,
+    ArtifactsEncryptProgress,
+    ArtifactsEncryptSummary,
     AssistantAPI,
+    AudioCalibration,
+    AudioCaptureStatus,
+    AudioDeviceInfo,
+    AudioDiagnostics,
+    AudioValidation,
     AuthDeepLinkPayload,
     AuthMethodsResponse,
+    AutoTypeOutcome,
+    BackgroundError,
+    CaptureProtectionStatus,
+    CommandStats,
+    ConfigPersistenceStatus,
+    DurationHotkeyPayload,
+    DurationQueuedPayload,
     FastWhisperStatus,
+    ForegroundWindowInfo,
+    HotkeyReapplyReport,
+    IndicatorCorner,
+    LanguageDetection,
+    LanguageProfile,
+    LocalLlmOptions,
+    MonitorInfo,
+    OllamaModelRecommendation,
+    OllamaPullOutcome,
+    OnboardingStatus,
+    PreRollSource,
+    PromptPreview,
+    ProviderQuotaStatus,
+    ProviderRateLimits,
     ScreenProcessRequest,
     ScreenProcessResponse,
+    SelfCheckProgress,
+    SelfCheckReport,
+    SessionTimeline,
+    SlowCommand,
+    SoundsListing,
+    StreamMode,
+    StreamModeChanged,
+    SystemResources,
+    TranscriptionPromptOverrides,
+    TrayIconSource,
+    TrayIconTheme,
+    WakeWordAction,
+    WaveformMode,
+    WindowPrefs,
 } from '@shared/ipc';
 import {listen, UnlistenFn} from '@tauri-apps/api/event';
 import {
@@ -28,8 +75,19 @@ import {
 
 const currentWindow = getCurrentWindow();
 
+// Each input type remembers its own device.
+const AUDIO_DEVICE_KEYS = {
+    microphone: 'micDeviceId',
+    mixed: 'mixedMicDeviceId',
+    system: 'systemLoopbackDeviceId',
+} as const;
+
 async function patchSettings(payload: Record<string, unknown>) {
-    await invoke('config_update', {payload});
+    const saved = await invoke<AppSettings>('config_update', {payload});
+    if (saved.persisted === false) {
+        // The change is active; the native side keeps retrying the write.
+        console.warn('[config] change not written to disk yet', saved.warning);
+    }
 }
 
 const makeSettingSetter =
@@ -38,6 +96,51 @@ const makeSettingSetter =
             await patchSettings({[key]: value});
         };
 
+// Past this age the first reads fetch fresh values instead of using the bootstrap.
+const BOOTSTRAP_MAX_AGE_MS = 5000;
+
+let bootstrapRequest: Promise<AppBootstrap | null> | null = null;
+let bootstrapLoadedAt = 0;
+const bootstrapTaken = new Set<keyof AppBootstrap>();
+
+function loadBootstrap(): Promise<AppBootstrap | null> {
+    if (!bootstrapRequest) {
+        bootstrapRequest = invoke<AppBootstrap>('app_bootstrap')
+            .then((bootstrap) => {
+                bootstrapLoadedAt = Date.now();
+                return bootstrap;
+            })
+            .catch((error) => {
+                console.warn('[bootstrap] falling back to individual calls', error);
+                return null;
+            });
+    }
+    return bootstrapRequest;
+}
+
+// The first read of each part comes from the bootstrap; later reads, failed
+// parts and a stale bootstrap go through `fallback`.
+async function fromBootstrap<K extends keyof AppBootstrap>(
+    key: K,
+    fallback: () => Promise<NonNullable<AppBootstrap[K]['value']>>,
+): Promise<NonNullable<AppBootstrap[K]['value']>> {
+    if (bootstrapTaken.has(key)) {
+        return fallback();
+    }
+    bootstrapTaken.add(key);
+    const bootstrap = await loadBootstrap();
+    const section = bootstrap?.[key];
+    // Drained auth payloads exist only here, so they are used however old.
+    const fresh = key === 'pendingAuth' || Date.now() - bootstrapLoadedAt <= BOOTSTRAP_MAX_AGE_MS;
+    if (section && section.value !== null && fresh) {
+        return section.value as NonNullable<AppBootstrap[K]['value']>;
+    }
+    if (section?.error) {
+        console.warn(`[bootstrap] ${key} unavailable`, section.error);
+    }
+    return fallback();
+}
+
 async function replaceListener<T>(
     current: UnlistenFn | null,
     event: string,
@@ -62,8 +165,11 @@ function clearListener(current: UnlistenFn | null): null {
     return null;
 }
 
+let artifactsEncryptUnlisten: UnlistenFn | null = null;
+let streamModeUnlisten: UnlistenFn | null = null;
+
 const settingsApi: AssistantAPI['settings'] = {
-    get: () => invoke('config_get'),
+    get: () => fromBootstrap('config', () => invoke<AppSettings>('config_get')),
     setOpenaiApiKey: makeSettingSetter<string>('openaiApiKey'),
     setWindowOpacity: async (opacity: number) => {
         await patchSettings({windowOpacity: opacity});
@@ -80,36 +186,70 @@ const settingsApi: AssistantAPI['settings'] = {
         await patchSettings({hideApp});
         // Screen recording exclusion is applied in Rust via SetWindowDisplayAffinity
     },
+    setWarnWhenNotHidden: makeSettingSetter<boolean>('warnWhenNotHidden'),
+    setHideFromSwitcher: makeSettingSetter<boolean>('hideFromSwitcher'),
+    setShowRecordingIndicator: makeSettingSetter<boolean>('showRecordingIndicator'),
+    setRecordingIndicatorCorner: makeSettingSetter<IndicatorCorner>('recordingIndicatorCorner'),
+    setTrayIconTheme: makeSettingSetter<TrayIconTheme>('trayIconTheme'),
+    reloadTrayIcon: () => invoke<TrayIconSource>('tray_reload_icon'),
     setWindowSize: async (size) => {
         const width = Math.max(size.width, 400);
         const height = Math.max(size.height, 500);
+        // config_update resizes the window; drags are saved natively.
         await patchSettings({windowWidth: width, windowHeight: height});
-        try {
-            await currentWindow.setSize(new LogicalSize(width, height));
-        } catch {
-        }
     },
     setWindowScale: async (scale) => {
         await patchSettings({windowScale: scale});
-        // Scale is applied in Rust by resizing the window and adjusting CSS zoom
+        // Applied by windowScale.ts when Rust emits window:scale-changed
     },
-    setDurations: makeSettingSetter('durations'),
-    setDurationHotkeys: makeSettingSetter('durationHotkeys'),
-    setAudioInputDevice: makeSettingSetter('audioInputDeviceId'),
+    setDurations: (list) => invoke<AppSettings>('durations_set', {list}),
+    setDurationHotkey: (duration, key) => invoke<AppSettings>('duration_hotkey_set', {duration, key}),
+    setAudioInputDevice: (type, deviceId) => patchSettings({[AUDIO_DEVICE_KEYS[type]]: deviceId || null}),
     setToggleInputHotkey: makeSettingSetter('toggleInputHotkey'),
+    setMaxPendingClips: makeSettingSetter<number>('maxPendingClips'),
     setAudioInputType: makeSettingSetter('audioInputType'),
+    setAutoStartCapture: makeSettingSetter<boolean>('autoStartCapture'),
+    setPreRollSeconds: makeSettingSetter<number>('preRollSeconds'),
+    setPreRollSource: makeSettingSetter<PreRollSource>('preRollSource'),
+    setMicGain: makeSettingSetter<number>('micGain'),
+    setVadThresholdDb: makeSettingSetter<number>('vadThresholdDb'),
+    setAgcTargetDb: makeSettingSetter<number>('agcTargetDb'),
+    setDuckingEnabled: makeSettingSetter<boolean>('duckingEnabled'),
+    setDuckingDb: makeSettingSetter<number>('duckingDb'),
+    setWakeWords: makeSettingSetter<string[]>('wakeWords'),
+    setWakeWordAction: makeSettingSetter<WakeWordAction | null>('wakeWordAction'),
+    setAudioPriorityBoost: makeSettingSetter<boolean>('audioPriorityBoost'),
+    setAudioMmcss: makeSettingSetter<boolean>('audioMmcss'),
     setTranscriptionModel: makeSettingSetter('transcriptionModel'),
     setTranscriptionPrompt: makeSettingSetter('transcriptionPrompt'),
+    setTranscriptionPromptOverrides: makeSettingSetter<TranscriptionPromptOverrides>('transcriptionPromptOverrides'),
+    setSendPromptToLocal: makeSettingSetter<boolean>('sendPromptToLocal'),
+    setGoogleTranscriptionFormat: makeSettingSetter<'json' | 'text'>('googleTranscriptionFormat'),
+    setTranscriptionLanguage: makeSettingSetter<string | null>('transcriptionLanguage'),
+    setLanguageDetection: makeSettingSetter<LanguageDetection>('languageDetection'),
+    setLanguageProfiles: makeSettingSetter<Record<string, LanguageProfile>>('languageProfiles'),
+    setSttSpeedupFactor: makeSettingSetter<number>('sttSpeedupFactor'),
+    setSttSpeedupRelabel: makeSettingSetter<boolean>('sttSpeedupRelabel'),
+    setProviderRateLimits: makeSettingSetter<ProviderRateLimits>('providerRateLimits'),
+    getProviderQuotaStatus: () => invoke<ProviderQuotaStatus[]>('provider_quota_status'),
+    // Without a host the native side applies the model to the active one.
     setLlmModel: async (model, host) => {
-        const payload: Record<string, unknown> = {llmModel: model};
         if (host === 'local') {
-            payload.localLlmModel = model;
+            await patchSettings({localLlmModel: model});
         } else if (host === 'api') {
-            payload.apiLlmModel = model;
+            await patchSettings({apiLlmModel: model});
+        } else {
+            await patchSettings({llmModel: model});
         }
-        await patchSettings(payload);
     },
     setLlmPrompt: makeSettingSetter('llmPrompt'),
+    setPromptVariables: makeSettingSetter<Record<string, string>>('promptVariables'),
+    previewPrompt: (template: string, sampleVars?: Record<string, string>) =>
+        invoke<PromptPreview>('prompt_preview', {template, sampleVars}),
+    sanitizeText: (text: string) => invoke<string>('text_sanitize', {text}),
+    setLlmStreamToClipboard: makeSettingSetter<boolean>('llmStreamToClipboard'),
+    setEnableAutoType: makeSettingSetter<boolean>('enableAutoType'),
+    setAutoTypeWpm: makeSettingSetter<number>('autoTypeWpm'),
     setTranscriptionMode: makeSettingSetter('transcriptionMode'),
     setLlmHost: makeSettingSetter('llmHost'),
     setLocalWhisperModel: makeSettingSetter('localWhisperModel'),
@@ -134,25 +274,97 @@ const settingsApi: AssistantAPI['settings'] = {
         await invoke('open_config_folder');
     },
     openLogsFolder: async () => {
-        await invoke('open_app_logs_folder');
+        await invoke('logs_open_folder');
+    },
+    openTranscriptLogFolder: async () => {
+        await invoke('transcript_log_open_folder');
+    },
+    setPlainTextLogEnabled: makeSettingSetter<boolean>('plainTextLogEnabled'),
+    setPlainTextLogDir: makeSettingSetter<string | null>('plainTextLogDir'),
+    setEncryptLocalArtifacts: makeSettingSetter<boolean>('encryptLocalArtifacts'),
+    encryptExistingArtifacts: () => invoke<ArtifactsEncryptSummary>('artifacts_encrypt_existing'),
+    onArtifactsEncryptProgress: (cb) => {
+        void (async () => {
+            artifactsEncryptUnlisten = await replaceListener<ArtifactsEncryptProgress>(
+                artifactsEncryptUnlisten,
+                'artifacts:encrypt-progress',
+                (event) => cb(event.payload)
+            );
+        })();
+    },
+    offArtifactsEncryptProgress: () => {
+        artifactsEncryptUnlisten = clearListener(artifactsEncryptUnlisten);
     },
+    readDebugFile: (path: string) => invoke<string>('debug_file_read', {path}),
+    getAudioWaveform: (pathOrId: string, buckets?: number, mode?: WaveformMode) =>
+        invoke<number[]>('audio_waveform', {pathOrId, buckets, mode}),
     getLogPath: () => invoke<string>('app_log_path'),
+    revealInFileManager: (path: string) => invoke('reveal_in_file_manager', {path}),
+    getRecentLogs: (lines: number) => invoke<string[]>('logs_get_recent', {lines}),
+    getVersions: () => invoke<AppVersions>('app_versions'),
+    exportDiagnosticsBundle: () => invoke<string>('diagnostics_export_bundle'),
+    getBootstrap: () => invoke<AppBootstrap>('app_bootstrap'),
+    listSounds: () => invoke<SoundsListing>('sounds_list'),
     setScreenProcessingModel: makeSettingSetter('screenProcessingModel'),
     setScreenProcessingPrompt: makeSettingSetter('screenProcessingPrompt'),
     setScreenProcessingTimeoutMs: makeSettingSetter('screenProcessingTimeoutMs'),
+    setSlowCommandMs: makeSettingSetter<number>('slowCommandMs'),
     setWelcomeModalDismissed: makeSettingSetter('welcomeModalDismissed'),
     setGoogleApiKey: makeSettingSetter('googleApiKey'),
+    setIncludeWindowContext: makeSettingSetter<boolean>('includeWindowContext'),
+    setAutoSummarizeEveryMin: makeSettingSetter<number>('autoSummarizeEveryMin'),
+    setSummaryPrompt: makeSettingSetter<string>('summaryPrompt'),
+    setLocalLlmOptions: makeSettingSetter<LocalLlmOptions>('localLlmOptions'),
+    setTranslateTo: makeSettingSetter<string | null>('translateTo'),
+    setTranslationModel: makeSettingSetter<string | null>('translationModel'),
+    getForegroundWindowInfo: () => invoke<ForegroundWindowInfo>('foreground_window_info'),
+    listMonitors: () => invoke<MonitorInfo[]>('monitors_list'),
+    getCaptureProtectionStatus: () => invoke<CaptureProtectionStatus>('capture_protection_status'),
+    testApiKey: (provider: ApiKeyProvider, key?: string) =>
+        invoke<ApiKeyTestResult>('api_key_test', {provider, key: key ?? null}),
     setStreamSendHotkey: makeSettingSetter<string>('streamSendHotkey'),
+    setPrivateSessionHotkey: makeSettingSetter<string>('privateSessionHotkey'),
+    setPrivateSession: (enabled: boolean) => invoke<boolean>('private_session_set', {enabled}),
+    getPrivateSession: () => fromBootstrap('privateSession', () => invoke<boolean>('private_session_get')),
+    setStreamMode: (mode: StreamMode) => invoke<AppSettings>('stream_mode_set', {mode}),
+    onStreamModeChanged: (cb) => {
+        void (async () => {
+            streamModeUnlisten = await replaceListener<StreamModeChanged>(
+                streamModeUnlisten,
+                'stream:mode-changed',
+                (event) => cb(event.payload)
+            );
+        })();
+    },
+    offStreamModeChanged: () => {
+        streamModeUnlisten = clearListener(streamModeUnlisten);
+    },
     setBackendDomain: makeSettingSetter('backendDomain'),
+    retryPersistence: () => invoke<ConfigPersistenceStatus>('config_retry_persistence'),
+    forceConfigTakeover: () => invoke<ConfigPersistenceStatus>('config_force_takeover'),
 };
 
 const audioApi: AssistantAPI['audio'] = {
-    listDevices: () => invoke('audio_list_devices'),
+    listDevices: () => fromBootstrap('audioDevices', () => invoke<AudioDeviceInfo[]>('audio_list_devices')),
     startCapture: (source: 'mic' | 'system' | 'mixed', deviceId?: string) =>
         invoke('audio_start_capture', {source, deviceId}),
     stopCapture: () => invoke('audio_stop_capture'),
+    switchSource: (source: 'mic' | 'system' | 'mixed', deviceId?: string) =>
+        invoke('audio_switch_source', {source, deviceId}),
+    ack: (seq: number) => invoke('audio_ack', {seq}),
+    setMonitor: (enabled: boolean, gain: number) => invoke('audio_set_monitor', {enabled, gain}),
+    getStatus: () => fromBootstrap('captureStatus', () => invoke<AudioCaptureStatus>('audio_get_status')),
+    runDiagnostics: () => invoke<AudioDiagnostics>('audio_run_diagnostics'),
+    validateSetup: (source, deviceId, loopbackDeviceId) =>
+        invoke<AudioValidation>('audio_validate_setup', {source, deviceId, loopbackDeviceId}),
+    calibrate: (durationSecs, apply) => invoke<AudioCalibration>('audio_calibrate', {durationSecs, apply}),
+    openPrivacySettings: () => invoke('audio_open_privacy_settings'),
+    showIndicator: () => invoke('indicator_show'),
+    hideIndicator: () => invoke('indicator_hide'),
 };
 
+let scaleUnlisten: UnlistenFn | null = null;
+
 const windowApi: AssistantAPI['window'] = {
     minimize: () => currentWindow.minimize(),
     close: () => currentWindow.close(),
@@ -172,6 +384,19 @@ const windowApi: AssistantAPI['window'] = {
         await currentWindow.setPosition(new LogicalPosition(bounds.x, bounds.y));
         await currentWindow.setSize(new LogicalSize(bounds.width, bounds.height));
     },
+    getInitialPrefs: () => invoke<WindowPrefs>('get_initial_window_prefs'),
+    onScaleChanged: (cb) => {
+        void (async () => {
+            scaleUnlisten = await replaceListener<WindowPrefs>(
+                scaleUnlisten,
+                'window:scale-changed',
+                (event) => cb(event.payload)
+            );
+        })();
+    },
+    offScaleChanged: () => {
+        scaleUnlisten = clearListener(scaleUnlisten);
+    },
 };
 
 const assistantApi: AssistantAPI['assistant'] = {
@@ -180,6 +405,8 @@ const assistantApi: AssistantAPI['assistant'] = {
     transcribeOnly: assistantTranscribeOnly,
     askChat: assistantAskChat,
     stopStream: assistantStopStream,
+    typeText: (text, wpm) => invoke<AutoTypeOutcome>('type_text', {text, wpm}),
+    abortTyping: () => invoke<void>('type_text_abort'),
     onStreamTranscript: (cb) => assistantOnStreamTranscript(cb),
     onStreamDelta: (cb) => assistantOnStreamDelta(cb),
     onStreamDone: (cb) => assistantOnStreamDone(cb),
@@ -192,11 +419,13 @@ const assistantApi: AssistantAPI['assistant'] = {
 
 let durationUnlisten: UnlistenFn | null = null;
 let toggleUnlisten: UnlistenFn | null = null;
+let durationQueuedUnlisten: UnlistenFn | null = null;
+let streamSendUnlisten: UnlistenFn | null = null;
 
 const hotkeysApi: AssistantAPI['hotkeys'] = {
     onDuration: (cb) => {
         void (async () => {
-            durationUnlisten = await replaceListener<{ sec: number }>(
+            durationUnlisten = await replaceListener<DurationHotkeyPayload>(
                 durationUnlisten,
                 'hotkeys:duration',
                 (event) => cb(event, event.payload)
@@ -218,6 +447,110 @@ const hotkeysApi: AssistantAPI['hotkeys'] = {
     offToggleInput: () => {
         toggleUnlisten = clearListener(toggleUnlisten);
     },
+    onDurationQueued: (cb) => {
+        void (async () => {
+            durationQueuedUnlisten = await replaceListener<DurationQueuedPayload>(
+                durationQueuedUnlisten,
+                'hotkeys:duration-queued',
+                (event) => cb(event.payload)
+            );
+        })();
+    },
+    offDurationQueued: () => {
+        durationQueuedUnlisten = clearListener(durationQueuedUnlisten);
+    },
+    onStreamSend: (cb) => {
+        void (async () => {
+            streamSendUnlisten = await replaceListener(
+                streamSendUnlisten,
+                'hotkeys:stream-send',
+                () => cb()
+            );
+        })();
+    },
+    offStreamSend: () => {
+        streamSendUnlisten = clearListener(streamSendUnlisten);
+    },
+    clearPending: () => invoke<number>('hotkeys_clear_pending'),
+    reapply: () => invoke<HotkeyReapplyReport>('hotkeys_reapply'),
+};
+
+let selfCheckProgressUnlisten: UnlistenFn | null = null;
+
+const onboardingApi: AssistantAPI['onboarding'] = {
+    getStatus: () => invoke<OnboardingStatus>('onboarding_status'),
+    completeStep: (step) => invoke<OnboardingStatus>('onboarding_complete_step', {step}),
+    selfCheck: (force) => invoke<SelfCheckReport>('app_self_check', {force}),
+    onSelfCheckProgress: (cb) => {
+        void (async () => {
+            selfCheckProgressUnlisten = await replaceListener<SelfCheckProgress>(
+                selfCheckProgressUnlisten,
+                'self-check:progress',
+                (event) => cb(event.payload)
+            );
+        })();
+    },
+    offSelfCheckProgress: () => {
+        selfCheckProgressUnlisten = clearListener(selfCheckProgressUnlisten);
+    },
+};
+
+let integrationCaptureUnlisten: UnlistenFn | null = null;
+
+const integrationApi: AssistantAPI['integration'] = {
+    getToken: () => invoke<string>('integration_get_token'),
+    onCaptureCommand: (cb) => {
+        void (async () => {
+            integrationCaptureUnlisten = await replaceListener<{ record: boolean }>(
+                integrationCaptureUnlisten,
+                'integration:capture',
+                (event) => cb(event.payload)
+            );
+        })();
+    },
+    offCaptureCommand: () => {
+        integrationCaptureUnlisten = clearListener(integrationCaptureUnlisten);
+    },
+};
+
+let errorUnlisten: UnlistenFn | null = null;
+
+const sessionApi: AssistantAPI['session'] = {
+    timeline: (sessionId?: string) => invoke<SessionTimeline>('session_timeline', {sessionId}),
+};
+
+const errorsApi: AssistantAPI['errors'] = {
+    getRecent: () => invoke<BackgroundError[]>('errors_get_recent'),
+    onError: (cb) => {
+        void (async () => {
+            errorUnlisten = await replaceListener<BackgroundError>(
+                errorUnlisten,
+                'app:error',
+                (event) => cb(event.payload)
+            );
+        })();
+    },
+    offError: () => {
+        errorUnlisten = clearListener(errorUnlisten);
+    },
+};
+
+let slowCommandUnlisten: UnlistenFn | null = null;
+
+const perfApi: AssistantAPI['perf'] = {
+    getCommandStats: () => invoke<CommandStats[]>('perf_command_stats'),
+    onSlowCommand: (cb) => {
+        void (async () => {
+            slowCommandUnlisten = await replaceListener<SlowCommand>(
+                slowCommandUnlisten,
+                'perf:slow-command',
+                (event) => cb(event.payload)
+            );
+        })();
+    },
+    offSlowCommand: () => {
+        slowCommandUnlisten = clearListener(slowCommandUnlisten);
+    },
 };
 
 const loopbackApi: AssistantAPI['loopback'] = {
@@ -267,7 +600,9 @@ const authApi: AssistantAPI['auth'] = {
         };
     },
     consumePendingOAuthPayloads: async () => {
-        const payloads = await invoke<AuthDeepLinkPayload[]>('auth_consume_pending');
+        const payloads = await fromBootstrap('pendingAuth', () =>
+            invoke<AuthDeepLinkPayload[]>('auth_consume_pending'),
+        );
         payloads.forEach(dispatchAuthPayload);
         return payloads;
     },
@@ -278,7 +613,7 @@ const mediaApi: AssistantAPI['media'] = {
 };
 
 const localSpeechApi: AssistantAPI['localSpeech'] = {
-    getStatus: () => invoke<FastWhisperStatus>('local_speech_get_status'),
+    getStatus: () => fromBootstrap('localSpeech', () => invoke<FastWhisperStatus>('local_speech_get_status')),
     checkHealth: () => invoke<FastWhisperStatus>('local_speech_check_health'),
     install: () => invoke<FastWhisperStatus>('local_speech_install'),
     start: () => invoke<FastWhisperStatus>('local_speech_start'),
@@ -287,18 +622,27 @@ const localSpeechApi: AssistantAPI['localSpeech'] = {
     stop: () => invoke<FastWhisperStatus>('local_speech_stop'),
     checkModelDownloaded: (model: string) =>
         invoke<boolean>('local_speech_check_model_downloaded', {model}),
+    watch: (enable: boolean) => invoke<number>('local_speech_watch', {enable}),
 };
 
 const ollamaApi: AssistantAPI['ollama'] = {
-    checkInstalled: () => invoke<boolean>('ollama_check_installed'),
+    checkInstalled: () => fromBootstrap('ollamaInstalled', () => invoke<boolean>('ollama_check_installed')),
     listModels: () => invoke<string[]>('ollama_list_models'),
-    pullModel: (model: string) => invoke('ollama_pull_model', {model}),
+    pullModel: (model: string) => invoke<OllamaPullOutcome>('ollama_pull_model', {model}),
     warmupModel: (model: string) => invoke('ollama_warmup_model', {model}),
+    recommendModels: (candidates?: string[]) =>
+        invoke<OllamaModelRecommendation[]>('ollama_recommend_models', {candidates}),
+    systemResources: () => invoke<SystemResources>('system_resources'),
 };
 
 const api: AssistantAPI = {
     assistant: assistantApi,
     hotkeys: hotkeysApi,
+    onboarding: onboardingApi,
+    integration: integrationApi,
+    session: sessionApi,
+    errors: errorsApi,
+    perf: perfApi,
     settings: settingsApi,
     window: windowApi,
     loopback: loopbackApi,
@@ -250,6 +250,7 @@ export const AiSettings = () => {
         let mounted = true;
 
         void refreshLocalStatus(true);
+        void window.api?.localSpeech?.watch(true).catch(() => undefined);
 
         (async () => {
             try {
@@ -290,6 +291,7 @@ export const AiSettings = () => {
             }
             window.removeEventListener('focus', handleVisibility);
             document.removeEventListener('visibilitychange', handleVisibility);
+            void window.api?.localSpeech?.watch(false).catch(() => undefined);
         };
     }, [refreshLocalStatus]);
 
@@ -556,10 +558,12 @@ export const AiSettings = () => {
         }
         try {
             await window.api.settings.setLlmHost(host);
+            // Persist the fallback too, otherwise the host keeps its stored model.
+            await window.api.settings.setLlmModel(targetModel, host);
             if (host === 'local') {
-                patchLocal({llmHost: host, llmModel: targetModel, localLlmModel: targetModel});
+                patchLocal({llmHost: host, localLlmModel: targetModel});
             } else {
-                patchLocal({llmHost: host, llmModel: targetModel, apiLlmModel: targetModel});
+                patchLocal({llmHost: host, apiLlmModel: targetModel});
             }
         } catch (error) {
             logger.error('settings', 'Failed to set LLM host', {error});
@@ -586,11 +590,7 @@ export const AiSettings = () => {
         }
         try {
             await window.api.settings.setLlmModel(model, 'api');
-            const isApiHost = settings.llmHost !== 'local';
-            patchLocal({
-                llmModel: isApiHost ? model : settings.llmModel,
-                apiLlmModel: model,
-            });
+            patchLocal({apiLlmModel: model});
         } catch (error) {
             logger.error('settings', 'Failed to set LLM model', {error});
             showMessage('Failed to update LLM model', 'error');
@@ -600,11 +600,7 @@ export const AiSettings = () => {
     const handleLocalLlmModelChange = async (model: string) => {
         try {
             await window.api.settings.setLlmModel(model, 'local');
-            const isLocalHost = settings.llmHost === 'local';
-            patchLocal({
-                llmModel: isLocalHost ? model : settings.llmModel,
-                localLlmModel: model,
-            });
+            patchLocal({localLlmModel: model});
         } catch (error) {
             logger.error('settings', 'Failed to set local LLM model', {error});
             showMessage('Failed to update local LLM model', 'error');
@@ -788,8 +784,8 @@ export const AiSettings = () => {
 
     const apiTranscribeModel = settings.transcriptionModel ?? DEFAULT_API_TRANSCRIBE_MODEL;
     const localTranscribeModel = settings.localWhisperModel ?? DEFAULT_LOCAL_TRANSCRIBE_MODEL;
-    const apiLlmModel = settings.apiLlmModel ?? settings.llmModel ?? DEFAULT_API_LLM_MODEL;
-    const localLlmModel = settings.localLlmModel ?? settings.llmModel ?? DEFAULT_LOCAL_LLM_MODEL;
+    const apiLlmModel = settings.apiLlmModel ?? DEFAULT_API_LLM_MODEL;
+    const localLlmModel = settings.localLlmModel ?? DEFAULT_LOCAL_LLM_MODEL;
 
     const transcribeOptions = useMemo(() => {
         if (settings.transcriptionMode === 'local') {
@@ -869,12 +865,15 @@ export const AiSettings = () => {
         localLogLineRaw && localLogLineRaw.length > 180
             ? `...${localLogLineRaw.slice(-180)}`
             : localLogLineRaw;
+    // A restored status keeps its buttons and badge; only the line says it is unconfirmed.
     const localMessage =
-        localStatus?.running && !localBusyPhase
-            ? ''
-            : localBusyPhase && localLogLine
-                ? localLogLine
-                : (localStatus?.phase === 'idle' ? '' : localStatus?.message || 'Checking server status...');
+        localStatus?.stale && !localBusyPhase
+            ? 'Checking server status...'
+            : localStatus?.running && !localBusyPhase
+                ? ''
+                : localBusyPhase && localLogLine
+                    ? localLogLine
+                    : (localStatus?.phase === 'idle' ? '' : localStatus?.message || 'Checking server status...');
 
     useEffect(() => {
         if (settings.transcriptionMode !== 'api') return;
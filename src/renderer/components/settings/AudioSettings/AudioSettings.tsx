@@ -21,6 +21,7 @@ export const AudioSettings = () => {
     const {settings, patchLocal} = useSettingsContext();
     const [devices, setDevices] = useState<AudioDeviceInfo[]>([]);
     const [loading, setLoading] = useState(false);
+    const [testing, setTesting] = useState(false);
 
     useEffect(() => {
         void loadDevices();
@@ -56,10 +57,14 @@ export const AudioSettings = () => {
         }
     };
 
+    const inputType = settings.audioInputType ?? 'microphone';
+    // System mode captures the default output through WASAPI, so only mic devices are picked here.
+    const deviceKey = inputType === 'mixed' ? 'mixedMicDeviceId' : 'micDeviceId';
+
     const handleDeviceChange = async (deviceId: string) => {
         try {
-            await window.api.settings.setAudioInputDevice(deviceId);
-            patchLocal({audioInputDeviceId: deviceId});
+            await window.api.settings.setAudioInputDevice(inputType, deviceId);
+            patchLocal({[deviceKey]: deviceId || null});
         } catch (error) {
             logger.error('settings', 'Failed to set audio input device', {error});
             showMessage('Failed to update audio input device', 'error');
@@ -68,14 +73,34 @@ export const AudioSettings = () => {
 
     const deviceOptions = [{value: '', label: 'Default device'}, ...devices.map((device) => ({
         value: device.id,
-        label: device.name
+        // Ids tell apart devices that share a name.
+        label: device.qualityHint === 'narrowband' ? `${device.id} (low quality, telephone band)` : device.id
     }))];
-    const currentDeviceId = settings.audioInputDeviceId ?? '';
+    const currentDeviceId = settings[deviceKey] ?? (inputType === 'mixed' ? settings.micDeviceId : null) ?? '';
     const renderDeviceLabel = (value: string) => {
         if (!value) return 'Default device';
         return deviceOptions.find((option) => option.value === value)?.label ?? 'Default device';
     };
 
+    const testSetup = async () => {
+        setTesting(true);
+        try {
+            const result = await window.api.audio.validateSetup(inputType, currentDeviceId || undefined);
+            const summary = result.sources
+                .map((source) => source.ok
+                    ? `${source.source}: ${source.levelDb?.toFixed(0)} dB at ${source.sampleRate} Hz`
+                    : `${source.source}: ${source.error}`)
+                .join('; ');
+            const ok = result.sources.every((source) => source.ok);
+            toast[ok ? 'success' : 'error'](summary);
+        } catch (error) {
+            logger.error('settings', 'Failed to test audio setup', {error});
+            showMessage('Failed to test audio setup', 'error');
+        } finally {
+            setTesting(false);
+        }
+    };
+
     return (
         <div className="audio-settings">
             <section className="settings-card card">
@@ -96,6 +121,11 @@ export const AudioSettings = () => {
                         ))}
                     </TextField>
                 </div>
+                <div className="audio-settings__actions">
+                    <button type="button" className="btn btn-sm" onClick={testSetup} disabled={testing}>
+                        {testing ? 'Testing…' : 'Test'}
+                    </button>
+                </div>
 
                 {settings.audioInputType === 'system' ? null : (
                     <div className="settings-field">
@@ -13,12 +13,25 @@ import {
     setBackendDomain as applyBackendDomain,
 } from '@shared/appUrls';
 import {authClient} from '../../../services/authClient';
+import type {IndicatorCorner, TrayIconTheme} from '@shared/ipc';
 import './GeneralSettings.scss';
 
 const MIN_WINDOW_WIDTH = 400;
 const MIN_WINDOW_HEIGHT = 500;
 const DEFAULT_WINDOW_WIDTH = 420;
 const DEFAULT_WINDOW_HEIGHT = 780;
+const INDICATOR_CORNERS: { value: IndicatorCorner; label: string }[] = [
+    {value: 'topleft', label: 'Top left'},
+    {value: 'topright', label: 'Top right'},
+    {value: 'bottomleft', label: 'Bottom left'},
+    {value: 'bottomright', label: 'Bottom right'},
+];
+
+const TRAY_ICON_THEMES: { value: TrayIconTheme; label: string }[] = [
+    {value: 'auto', label: 'App icon'},
+    {value: 'light', label: 'Dark icon for a light taskbar'},
+    {value: 'dark', label: 'Light icon for a dark taskbar'},
+];
 
 const baseCheckboxIcon = (
     <span className="winky-checkbox__control">
@@ -45,6 +58,13 @@ export const GeneralSettings = () => {
     const [windowScale, setWindowScale] = useState(settings.windowScale ?? 1);
     const [windowWidth, setWindowWidth] = useState(settings.windowWidth ?? DEFAULT_WINDOW_WIDTH);
     const [windowHeight, setWindowHeight] = useState(settings.windowHeight ?? DEFAULT_WINDOW_HEIGHT);
+    const [wakeWords, setWakeWords] = useState((settings.wakeWords ?? []).join(', '));
+    const [wakeWordDuration, setWakeWordDuration] = useState(
+        settings.wakeWordAction?.duration ? String(settings.wakeWordAction.duration) : ''
+    );
+    const [autoTypeWpm, setAutoTypeWpm] = useState(String(settings.autoTypeWpm ?? 300));
+    const [plainTextLogDir, setPlainTextLogDir] = useState(settings.plainTextLogDir ?? '');
+    const [encryptProgress, setEncryptProgress] = useState<string | null>(null);
     const [backendDomain, setBackendDomainState] = useState<BackendDomain>(
         settings.backendDomain ?? getBackendDomain()
     );
@@ -103,6 +123,179 @@ export const GeneralSettings = () => {
         }
     };
 
+    const toggleWarnWhenNotHidden = async (value: boolean) => {
+        try {
+            await window.api.settings.setWarnWhenNotHidden(value);
+            patchLocal({warnWhenNotHidden: value});
+        } catch (error) {
+            logger.error('settings', 'Failed to update privacy warning', {error});
+        }
+    };
+
+    const toggleAutoStartCapture = async (value: boolean) => {
+        try {
+            await window.api.settings.setAutoStartCapture(value);
+            patchLocal({autoStartCapture: value});
+        } catch (error) {
+            logger.error('settings', 'Failed to update capture auto-start', {error});
+        }
+    };
+
+    const toggleStreamToClipboard = async (value: boolean) => {
+        try {
+            await window.api.settings.setLlmStreamToClipboard(value);
+            patchLocal({llmStreamToClipboard: value});
+        } catch (error) {
+            logger.error('settings', 'Failed to update clipboard streaming', {error});
+        }
+    };
+
+    const toggleAutoType = async (value: boolean) => {
+        try {
+            await window.api.settings.setEnableAutoType(value);
+            patchLocal({enableAutoType: value});
+        } catch (error) {
+            logger.error('settings', 'Failed to update auto-type', {error});
+        }
+    };
+
+    const saveAutoTypeWpm = async () => {
+        const wpm = Math.round(Number(autoTypeWpm));
+        if (!Number.isFinite(wpm) || wpm <= 0) {
+            setAutoTypeWpm(String(settings.autoTypeWpm ?? 300));
+            return;
+        }
+        try {
+            await window.api.settings.setAutoTypeWpm(wpm);
+            patchLocal({autoTypeWpm: wpm});
+        } catch (error) {
+            logger.error('settings', 'Failed to update typing speed', {error});
+        }
+    };
+
+    const saveWakeWords = async () => {
+        const words = wakeWords.split(',').map((word) => word.trim()).filter(Boolean);
+        try {
+            await window.api.settings.setWakeWords(words);
+            patchLocal({wakeWords: words});
+        } catch (error) {
+            logger.error('settings', 'Failed to update wake words', {error});
+        }
+    };
+
+    const saveWakeWordDuration = async () => {
+        const seconds = Math.round(Number(wakeWordDuration));
+        const action = seconds > 0 ? {duration: seconds} : null;
+        try {
+            await window.api.settings.setWakeWordAction(action);
+            patchLocal({wakeWordAction: action});
+        } catch (error) {
+            logger.error('settings', 'Failed to update wake word action', {error});
+        }
+    };
+
+    const togglePlainTextLog = async (value: boolean) => {
+        try {
+            await window.api.settings.setPlainTextLogEnabled(value);
+            patchLocal({plainTextLogEnabled: value});
+        } catch (error) {
+            logger.error('settings', 'Failed to update transcript log', {error});
+        }
+    };
+
+    const savePlainTextLogDir = async () => {
+        const dir = plainTextLogDir.trim() || null;
+        try {
+            await window.api.settings.setPlainTextLogDir(dir);
+            patchLocal({plainTextLogDir: dir});
+        } catch (error) {
+            logger.error('settings', 'Failed to update transcript log folder', {error});
+        }
+    };
+
+    const toggleEncryptLocalArtifacts = async (value: boolean) => {
+        try {
+            await window.api.settings.setEncryptLocalArtifacts(value);
+            patchLocal({encryptLocalArtifacts: value});
+        } catch (error) {
+            logger.error('settings', 'Failed to update artifact encryption', {error});
+        }
+    };
+
+    const encryptExistingArtifacts = async () => {
+        setEncryptProgress('Starting…');
+        window.api.settings.onArtifactsEncryptProgress(({done, total}) => {
+            setEncryptProgress(`${done} / ${total}`);
+        });
+        try {
+            const summary = await window.api.settings.encryptExistingArtifacts();
+            showMessage(
+                `Encrypted ${summary.encrypted} files` + (summary.failed ? `, ${summary.failed} failed` : ''),
+                summary.failed ? 'error' : 'success'
+            );
+        } catch (error) {
+            logger.error('settings', 'Failed to encrypt existing files', {error});
+            showMessage('Unable to encrypt existing files', 'error');
+        } finally {
+            window.api.settings.offArtifactsEncryptProgress();
+            setEncryptProgress(null);
+        }
+    };
+
+    const openTranscriptLogFolder = async () => {
+        try {
+            await window.api.settings.openTranscriptLogFolder();
+        } catch (error) {
+            logger.error('settings', 'Failed to open transcript log folder', {error});
+            showMessage('Unable to open transcript log folder', 'error');
+        }
+    };
+
+    const toggleHideFromSwitcher = async (value: boolean) => {
+        try {
+            await window.api.settings.setHideFromSwitcher(value);
+            patchLocal({hideFromSwitcher: value});
+        } catch (error) {
+            logger.error('settings', 'Failed to update task switcher visibility', {error});
+        }
+    };
+
+    const toggleIncludeWindowContext = async (value: boolean) => {
+        try {
+            await window.api.settings.setIncludeWindowContext(value);
+            patchLocal({includeWindowContext: value});
+        } catch (error) {
+            logger.error('settings', 'Failed to update window context', {error});
+        }
+    };
+
+    const toggleRecordingIndicator = async (value: boolean) => {
+        try {
+            await window.api.settings.setShowRecordingIndicator(value);
+            patchLocal({showRecordingIndicator: value});
+        } catch (error) {
+            logger.error('settings', 'Failed to update recording indicator', {error});
+        }
+    };
+
+    const changeIndicatorCorner = async (corner: IndicatorCorner) => {
+        try {
+            await window.api.settings.setRecordingIndicatorCorner(corner);
+            patchLocal({recordingIndicatorCorner: corner});
+        } catch (error) {
+            logger.error('settings', 'Failed to update recording indicator corner', {error});
+        }
+    };
+
+    const changeTrayIconTheme = async (theme: TrayIconTheme) => {
+        try {
+            await window.api.settings.setTrayIconTheme(theme);
+            patchLocal({trayIconTheme: theme});
+        } catch (error) {
+            logger.error('settings', 'Failed to update tray icon theme', {error});
+        }
+    };
+
     const updateOpacity = (value: number) => {
         // Only update local state for immediate UI feedback
         setWindowOpacity(value);
@@ -188,7 +381,7 @@ export const GeneralSettings = () => {
         try {
             const logPath = await window.api.settings.getLogPath();
             logger.info('settings', 'Opening logs folder', {logPath});
-            await window.api.settings.openLogsFolder();
+            await window.api.settings.revealInFileManager(logPath);
         } catch (error) {
             logger.error('settings', 'Failed to open logs folder', {error});
             showMessage('Unable to open logs folder', 'error');
@@ -245,6 +438,143 @@ export const GeneralSettings = () => {
                         }
                         label="Hide app from screen recording"
                     />
+                    <FormControlLabel
+                        control={
+                            <Checkbox
+                                size="small"
+                                checked={settings.warnWhenNotHidden ?? true}
+                                onChange={(event) => toggleWarnWhenNotHidden(event.target.checked)}
+                                icon={baseCheckboxIcon}
+                                checkedIcon={checkedCheckboxIcon}
+                                disableRipple
+                            />
+                        }
+                        label="Warn when recording while visible to screen capture"
+                    />
+                    <FormControlLabel
+                        control={
+                            <Checkbox
+                                size="small"
+                                checked={Boolean(settings.hideFromSwitcher)}
+                                onChange={(event) => toggleHideFromSwitcher(event.target.checked)}
+                                icon={baseCheckboxIcon}
+                                checkedIcon={checkedCheckboxIcon}
+                                disableRipple
+                            />
+                        }
+                        label="Hide from Alt-Tab and task switcher"
+                    />
+                    <FormControlLabel
+                        control={
+                            <Checkbox
+                                size="small"
+                                checked={Boolean(settings.includeWindowContext)}
+                                onChange={(event) => toggleIncludeWindowContext(event.target.checked)}
+                                icon={baseCheckboxIcon}
+                                checkedIcon={checkedCheckboxIcon}
+                                disableRipple
+                            />
+                        }
+                        label="Tell the AI which app and window are in front"
+                    />
+                    <TextField
+                        select
+                        size="small"
+                        label="Tray icon"
+                        value={settings.trayIconTheme ?? 'auto'}
+                        onChange={(event) => {
+                            void changeTrayIconTheme(event.target.value as TrayIconTheme);
+                        }}
+                    >
+                        {TRAY_ICON_THEMES.map(({value, label}) => (
+                            <MenuItem key={value} value={value}>
+                                {label}
+                            </MenuItem>
+                        ))}
+                    </TextField>
+                    <FormControlLabel
+                        control={
+                            <Checkbox
+                                size="small"
+                                checked={Boolean(settings.showRecordingIndicator)}
+                                onChange={(event) => toggleRecordingIndicator(event.target.checked)}
+                                icon={baseCheckboxIcon}
+                                checkedIcon={checkedCheckboxIcon}
+                                disableRipple
+                            />
+                        }
+                        label="Show a REC indicator while recording"
+                    />
+                    {settings.showRecordingIndicator && (
+                        <TextField
+                            select
+                            size="small"
+                            label="Indicator corner"
+                            value={settings.recordingIndicatorCorner ?? 'topright'}
+                            onChange={(event) => {
+                                void changeIndicatorCorner(event.target.value as IndicatorCorner);
+                            }}
+                        >
+                            {INDICATOR_CORNERS.map(({value, label}) => (
+                                <MenuItem key={value} value={value}>
+                                    {label}
+                                </MenuItem>
+                            ))}
+                        </TextField>
+                    )}
+                    <FormControlLabel
+                        control={
+                            <Checkbox
+                                size="small"
+                                checked={Boolean(settings.autoStartCapture)}
+                                onChange={(event) => toggleAutoStartCapture(event.target.checked)}
+                                icon={baseCheckboxIcon}
+                                checkedIcon={checkedCheckboxIcon}
+                                disableRipple
+                            />
+                        }
+                        label="Start audio capture when the app launches"
+                    />
+                    <FormControlLabel
+                        control={
+                            <Checkbox
+                                size="small"
+                                checked={Boolean(settings.llmStreamToClipboard)}
+                                onChange={(event) => toggleStreamToClipboard(event.target.checked)}
+                                icon={baseCheckboxIcon}
+                                checkedIcon={checkedCheckboxIcon}
+                                disableRipple
+                            />
+                        }
+                        label="Copy answers to the clipboard as they stream"
+                    />
+                    <FormControlLabel
+                        control={
+                            <Checkbox
+                                size="small"
+                                checked={Boolean(settings.enableAutoType)}
+                                onChange={(event) => toggleAutoType(event.target.checked)}
+                                icon={baseCheckboxIcon}
+                                checkedIcon={checkedCheckboxIcon}
+                                disableRipple
+                            />
+                        }
+                        label="Allow typing answers into the focused app (Esc stops typing)"
+                    />
+                    {settings.enableAutoType ? (
+                        <div className="settings-field">
+                            <TextField
+                                label="Typing speed, words per minute"
+                                type="number"
+                                size="small"
+                                value={autoTypeWpm}
+                                onChange={(event) => setAutoTypeWpm(event.target.value)}
+                                onBlur={() => void saveAutoTypeWpm()}
+                                inputProps={{min: 10, max: 2000}}
+                                fullWidth
+                            />
+                        </div>
+                    ) : null}
                 </div>
 
                 <div className="settings-slider -mt-2">
@@ -303,6 +633,33 @@ export const GeneralSettings = () => {
                 </div>
             </section>
 
+            <section className="settings-card card">
+                <h3 className="settings-card__title">Wake words</h3>
+                <div className="settings-field">
+                    <TextField
+                        label="Words or phrases, comma separated"
+                        size="small"
+                        value={wakeWords}
+                        onChange={(event) => setWakeWords(event.target.value)}
+                        onBlur={() => void saveWakeWords()}
+                        helperText="Heard through the local Whisper server while capture runs"
+                        fullWidth
+                    />
+                </div>
+                <div className="settings-field">
+                    <TextField
+                        label="Seconds to send when heard (empty to only notify)"
+                        type="number"
+                        size="small"
+                        value={wakeWordDuration}
+                        onChange={(event) => setWakeWordDuration(event.target.value)}
+                        onBlur={() => void saveWakeWordDuration()}
+                        inputProps={{min: 1, max: 600}}
+                        fullWidth
+                    />
+                </div>
+            </section>
+
             <section className="settings-card card">
                 <h3 className="settings-card__title">Window size on startup</h3>
                 <div className="settings-window-size">
@@ -336,6 +693,57 @@ export const GeneralSettings = () => {
                 </button>
             </section>
 
+            <section className="settings-card card">
+                <h3 className="settings-card__title">Transcript log</h3>
+                <FormControlLabel
+                    control={
+                        <Checkbox
+                            size="small"
+                            checked={Boolean(settings.plainTextLogEnabled)}
+                            onChange={(event) => togglePlainTextLog(event.target.checked)}
+                            icon={baseCheckboxIcon}
+                            checkedIcon={checkedCheckboxIcon}
+                            disableRipple
+                        />
+                    }
+                    label="Append every transcript to a daily text file"
+                />
+                <div className="settings-field">
+                    <TextField
+                        label="Folder (empty for the app data folder)"
+                        size="small"
+                        value={plainTextLogDir}
+                        onChange={(event) => setPlainTextLogDir(event.target.value)}
+                        onBlur={() => void savePlainTextLogDir()}
+                        fullWidth
+                    />
+                </div>
+                <button type="button" className="btn btn-sm" onClick={openTranscriptLogFolder}>
+                    Open transcript folder
+                </button>
+                <FormControlLabel
+                    control={
+                        <Checkbox
+                            size="small"
+                            checked={Boolean(settings.encryptLocalArtifacts)}
+                            onChange={(event) => toggleEncryptLocalArtifacts(event.target.checked)}
+                            icon={baseCheckboxIcon}
+                            checkedIcon={checkedCheckboxIcon}
+                            disableRipple
+                        />
+                    }
+                    label="Encrypt saved recordings and transcripts"
+                />
+                <button
+                    type="button"
+                    className="btn btn-sm"
+                    onClick={encryptExistingArtifacts}
+                    disabled={encryptProgress !== null}
+                >
+                    {encryptProgress ? `Encrypting ${encryptProgress}` : 'Encrypt existing files'}
+                </button>
+            </section>
+
             <section className="settings-card card">
                 <h3 className="settings-card__title">Logs</h3>
                 <button type="button" className="btn btn-sm" onClick={openLogsFolder}>
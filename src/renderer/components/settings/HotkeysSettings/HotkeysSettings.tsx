@@ -8,7 +8,7 @@ import {logger} from '../../../utils/logger';
 import {emitSettingsChange} from '../../../utils/settingsEvents';
 import './HotkeysSettings.scss';
 
-const clampDuration = (duration: number) => Math.max(1, Math.min(300, duration));
+const clampDuration = (duration: number) => Math.max(1, Math.min(600, duration));
 
 export const HotkeysSettings = () => {
     const {settings, patchLocal} = useSettingsContext();
@@ -16,6 +16,7 @@ export const HotkeysSettings = () => {
     const [durationHotkeys, setDurationHotkeys] = useState<Record<number, string>>(settings.durationHotkeys ?? {});
     const [toggleHotkey, setToggleHotkey] = useState(settings.toggleInputHotkey ?? 'g');
     const [streamSendHotkey, setStreamSendHotkey] = useState(settings.streamSendHotkey ?? '~');
+    const [maxPendingClips, setMaxPendingClips] = useState(String(settings.maxPendingClips ?? 1));
 
     const durations = useMemo(() => [...(settings.durations ?? [])].sort((a, b) => a - b), [settings.durations]);
 
@@ -23,15 +24,21 @@ export const HotkeysSettings = () => {
         toast[tone](text);
     };
 
+    const errorText = (error: unknown, fallback: string) =>
+        error instanceof Error && error.message ? error.message : fallback;
+
     const updateDurations = async (next: number[]) => {
         try {
-            await window.api.settings.setDurations(next);
-            patchLocal({durations: next});
-            emitSettingsChange('durations', next);
+            // The native side re-keys hotkeys of removed or added durations.
+            const updated = await window.api.settings.setDurations(next);
+            patchLocal({durations: updated.durations, durationHotkeys: updated.durationHotkeys});
+            setDurationHotkeys(updated.durationHotkeys ?? {});
+            emitSettingsChange('durations', updated.durations);
+            emitSettingsChange('durationHotkeys', updated.durationHotkeys);
             showMessage('Durations saved');
         } catch (error) {
             logger.error('settings', 'Failed to save durations', {error});
-            showMessage('Failed to save durations', 'error');
+            showMessage(errorText(error, 'Failed to save durations'), 'error');
         }
     };
 
@@ -54,26 +61,6 @@ export const HotkeysSettings = () => {
     const removeDuration = async (duration: number) => {
         const next = durations.filter((value) => value !== duration);
         await updateDurations(next);
-        const hotkeys = {...durationHotkeys};
-        delete hotkeys[duration];
-        setDurationHotkeys(hotkeys);
-        await saveDurationHotkeys(hotkeys, false);
-    };
-
-    const saveDurationHotkeys = async (map: Record<number, string>, toast = true) => {
-        try {
-            await (window.api.settings as any).setDurationHotkeys(map);
-            patchLocal({durationHotkeys: map});
-            emitSettingsChange('durationHotkeys', map);
-            if (toast) {
-                showMessage('Duration hotkeys saved');
-            }
-        } catch (error) {
-            logger.error('settings', 'Failed to save duration hotkeys', {error});
-            if (toast) {
-                showMessage('Failed to save duration hotkeys', 'error');
-            }
-        }
     };
 
     const saveHotkeyForDuration = async (duration: number) => {
@@ -83,9 +70,16 @@ export const HotkeysSettings = () => {
             return;
         }
         const char = value[0].toLowerCase();
-        const map = {...durationHotkeys, [duration]: char};
-        setDurationHotkeys(map);
-        await saveDurationHotkeys(map);
+        try {
+            const updated = await window.api.settings.setDurationHotkey(duration, char);
+            patchLocal({durationHotkeys: updated.durationHotkeys});
+            setDurationHotkeys(updated.durationHotkeys ?? {});
+            emitSettingsChange('durationHotkeys', updated.durationHotkeys);
+            showMessage('Duration hotkeys saved');
+        } catch (error) {
+            logger.error('settings', 'Failed to save duration hotkey', {error});
+            showMessage(errorText(error, 'Failed to save duration hotkeys'), 'error');
+        }
     };
 
     const saveToggleHotkey = async () => {
@@ -122,6 +116,44 @@ export const HotkeysSettings = () => {
         }
     };
 
+    const saveMaxPendingClips = async () => {
+        const value = Math.max(1, Math.min(10, Math.round(Number(maxPendingClips) || 1)));
+        try {
+            await window.api.settings.setMaxPendingClips(value);
+            patchLocal({maxPendingClips: value});
+            setMaxPendingClips(String(value));
+            showMessage('Queue size saved');
+        } catch (error) {
+            logger.error('settings', 'Failed to save queue size', {error});
+            showMessage(errorText(error, 'Failed to save queue size'), 'error');
+        }
+    };
+
+    const clearPendingClips = async () => {
+        try {
+            const cleared = await window.api.hotkeys.clearPending();
+            showMessage(cleared ? `Dropped ${cleared} queued requests` : 'Nothing queued');
+        } catch (error) {
+            logger.error('settings', 'Failed to clear queued requests', {error});
+            showMessage(errorText(error, 'Failed to clear queued requests'), 'error');
+        }
+    };
+
+    const reapplyHotkeys = async () => {
+        try {
+            const report = await window.api.hotkeys.reapply();
+            const failed = report.results.filter((result) => !result.registered);
+            if (failed.length) {
+                showMessage(`Not registered: ${failed.map((result) => result.accelerator).join(', ')}`, 'error');
+            } else {
+                showMessage(`Re-registered ${report.results.length} hotkeys`);
+            }
+        } catch (error) {
+            logger.error('settings', 'Failed to re-apply hotkeys', {error});
+            showMessage(errorText(error, 'Failed to re-apply hotkeys'), 'error');
+        }
+    };
+
     return (
         <div className="hotkeys-settings">
 
@@ -170,6 +202,29 @@ export const HotkeysSettings = () => {
                 </div>
             </section>
 
+            <section className="settings-card card">
+                <h3 className="settings-card__title">Presses during a transcription</h3>
+                <div className="hotkeys-duration-add">
+                    <TextField
+                        type="number"
+                        value={maxPendingClips}
+                        sx={{maxWidth: 100}}
+                        onChange={(event) => setMaxPendingClips(event.target.value)}
+                        inputProps={{min: 1, max: 10}}
+                        size="small"
+                    />
+                    <button type="button" className="btn btn-sm" onClick={saveMaxPendingClips}>
+                        Save
+                    </button>
+                    <button type="button" className="btn btn-sm" onClick={clearPendingClips}>
+                        Clear queue
+                    </button>
+                </div>
+                <div className="hotkeys-helper">
+                    Duration requests kept until the running one finishes; when full, the newest press replaces the last.
+                </div>
+            </section>
+
             <section className="settings-card card">
                 <h3 className="settings-card__title">Hotkey: toggle audio input</h3>
                 <div className="hotkeys-input-row">
@@ -207,6 +262,14 @@ export const HotkeysSettings = () => {
                 </div>
                 <div className="hotkeys-helper">Single character for sending text from stream results.</div>
             </section>
+
+            <section className="settings-card card">
+                <h3 className="settings-card__title">Hotkeys not responding?</h3>
+                <button type="button" className="btn btn-sm" onClick={reapplyHotkeys}>
+                    Re-register hotkeys
+                </button>
+                <div className="hotkeys-helper">Happens after Explorer restarts; this is also done automatically on wake.</div>
+            </section>
         </div>
     );
 };
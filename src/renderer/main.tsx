@@ -2,6 +2,7 @@ import {createRoot} from 'react-dom/client';
 import App from './App';
 import './styles.css';
 import './bridge/tauriApi';
+import {setupWindowScale} from './app/windowScale';
 
 const rootElement = document.getElementById('root');
 
@@ -9,4 +10,5 @@ if (!rootElement) {
     throw new Error('Root element #root not found');
 }
 
+setupWindowScale();
 createRoot(rootElement).render(<App/>);
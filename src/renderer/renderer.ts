@@ -1,10 +1,12 @@
-import {initControls, updateDurations} from './ui/controls';
+import {adoptRecording, initControls, updateDurations} from './ui/controls';
 import {listen} from '@tauri-apps/api/event';
 import {initStatus, setStatus} from './ui/status';
 import {
+    appendChatMessage,
     CHAT_RETRY_EVENT_NAME,
     createNewChat,
     initOutputs,
+    setPrivateSession,
     subscribeChatSessions,
     switchChat,
     type ChatSessionSummary
@@ -21,6 +23,15 @@ import {hideStopButton, registerStopButton} from './ui/stopButton';
 import {state} from './state/appState';
 import {checkOllamaModelDownloaded} from './services/ollama';
 import {normalizeLocalWhisperModel} from './services/localSpeechModels';
+import {onCaptureAutoStarted} from './services/nativeAudio';
+import {assistantStopStream, assistantSummarize} from './services/nativeAssistant';
+import {invoke} from './bridge/invoke';
+import type {
+    DurationHotkeyPayload,
+    PrivateSessionChanged,
+    SessionSummary,
+    SessionSummaryRequest,
+} from '@shared/ipc';
 
 function renderChatSessionsList(
     listElement: HTMLElement | null,
@@ -114,7 +125,7 @@ async function preloadLocalModelsIfNeeded() {
             // Check local LLM model when local host is selected
             if (settings.llmHost === 'local') {
                 try {
-                    const llmModel = settings.localLlmModel || settings.llmModel || 'gpt-oss:20b';
+                    const llmModel = settings.localLlmModel || 'gpt-oss:20b';
                     const downloaded = await checkOllamaModelDownloaded(llmModel, {force: true});
                     console.info('[renderer] Local LLM model checked:', {
                         model: llmModel,
@@ -146,6 +157,68 @@ async function setupTranscriptionDebugListener() {
     }
 }
 
+// Chat messages from a private session must not reach local storage.
+async function followPrivateSession() {
+    try {
+        await listen<PrivateSessionChanged>('privacy:private-session', (event) => {
+            setPrivateSession(event.payload.enabled);
+        });
+        setPrivateSession(await window.api.settings.getPrivateSession());
+    } catch (error) {
+        console.warn('[privacy] Failed to follow the private session:', error);
+    }
+}
+
+const formatSummaryTime = (value: string) =>
+    new Date(value).toLocaleTimeString([], {hour: '2-digit', minute: '2-digit'});
+
+// The backend decides when a rolling summary is due; the LLM call runs here
+// and the result goes back through `session_summary_complete`.
+async function followSessionSummaries() {
+    try {
+        await listen<SessionSummaryRequest>('session:summary-request', async (event) => {
+            const {requestId, prompt, text} = event.payload;
+            try {
+                const summary = await assistantSummarize(requestId, prompt, text);
+                await invoke('session_summary_complete', {requestId, text: summary});
+            } catch (error) {
+                const message = error instanceof Error ? error.message : String(error);
+                await invoke('session_summary_complete', {requestId, error: message}).catch(() => {
+                });
+            }
+        });
+        await listen<{ requestId: string }>('session:summary-cancel', (event) => {
+            assistantStopStream({requestId: event.payload.requestId}).catch(() => {
+            });
+        });
+        await listen<SessionSummary>('session:summary', (event) => {
+            const {text, coveringFrom, coveringTo} = event.payload;
+            const range = `${formatSummaryTime(coveringFrom)}–${formatSummaryTime(coveringTo)}`;
+            appendChatMessage('assistant', `Summary ${range}\n\n${text}`);
+        });
+    } catch (error) {
+        console.warn('[summary] Failed to follow session summaries:', error);
+    }
+}
+
+// The backend checks the local speech server in the background while local
+// transcription is selected, and pushes `local-speech:status` on changes.
+function followLocalSpeechHealth() {
+    if (!window.api?.localSpeech) return;
+    let watching = false;
+    const sync = (mode: string | undefined) => {
+        const wanted = mode === 'local';
+        if (wanted === watching) return;
+        watching = wanted;
+        window.api.localSpeech.watch(wanted).catch((error) => {
+            console.warn('[renderer] Failed to toggle local speech health watch:', error);
+        });
+    };
+    settingsStore.load().then((settings) => sync(settings.transcriptionMode)).catch(() => {
+    });
+    settingsStore.subscribe((settings) => sync(settings.transcriptionMode));
+}
+
 export async function initializeRenderer() {
     // Setup transcription debug listener (optional)
     setupTranscriptionDebugListener().catch(() => {
@@ -167,6 +240,9 @@ export async function initializeRenderer() {
         return;
     }
     console.info('[renderer] Preload bridge ready for use');
+    await followPrivateSession();
+    await followSessionSummaries();
+    followLocalSpeechHealth();
 
     // Automatically check model availability after the bridge initializes to ensure the API is ready
     preloadLocalModelsIfNeeded().catch((error) => {
@@ -281,17 +357,33 @@ export async function initializeRenderer() {
     } catch {
     }
 
-    window.api.hotkeys.onDuration((_e: unknown, payload: { sec: number }) => {
+    window.api.hotkeys.onDuration((_e: unknown, payload: DurationHotkeyPayload) => {
         try {
-            streamController.handleAskWindow(payload.sec);
+            streamController.handleAskWindow(payload.sec, payload.interactionId ?? undefined);
         } catch {
         }
     });
 
+    window.api.hotkeys.onDurationQueued(({sec, position, coalesced}) => {
+        setStatus(coalesced ? `Last ${sec}s replaces queued request` : `Last ${sec}s queued (#${position})`, 'processing');
+    });
+
     window.api.hotkeys.onToggleInput(async () => {
         await streamController.handleHotkeyToggleRequest();
     });
 
+    window.api.hotkeys.onStreamSend(async () => {
+        await streamController.handleStreamTextSend();
+    });
+
+    void onCaptureAutoStarted((inputType) => {
+        void adoptRecording(() => streamController.attachRunningCapture(inputType));
+    });
+
+    window.api.integration.onCaptureCommand(async ({record}) => {
+        await streamController.handleRecordToggle(record);
+    });
+
     window.addEventListener('xexamai:settings-changed' as any, async (ev: any) => {
         try {
             const {key, value} = ev?.detail || {};
@@ -1,4 +1,5 @@
 import {settingsStore} from '../state/settingsStore';
+import {DeltaAssembler} from './streamText';
 
 type StreamOptions = {
     streamMode?: 'base' | 'stream';
@@ -7,6 +8,7 @@ type StreamOptions = {
 export class GoogleStreamingService {
     private transcriptCallback: ((text: string) => void) | null = null;
     private errorCallback: ((error: string) => void) | null = null;
+    private assembler = new DeltaAssembler();
     private audioContext: AudioContext | null = null;
     private processor: ScriptProcessorNode | null = null;
     private source: MediaStreamAudioSourceNode | null = null;
@@ -40,7 +42,9 @@ export class GoogleStreamingService {
                 const plainText = message?.text;
                 const text = inputTx || outputTx || plainText;
                 if (text && typeof text === 'string') {
-                    this.transcriptCallback?.(text);
+                    for (const piece of this.assembler.push(text)) {
+                        this.transcriptCallback?.(piece);
+                    }
                 }
             } catch {
             }
@@ -77,6 +81,9 @@ export class GoogleStreamingService {
     }
 
     async stop(): Promise<void> {
+        for (const piece of this.assembler.flush()) {
+            this.transcriptCallback?.(piece);
+        }
         try {
             await window.api.google.stopLive?.();
         } catch {
@@ -1,12 +1,16 @@
+import {invoke} from '../bridge/invoke';
 import {logger} from '../utils/logger';
+import type {LocalLlmOptions} from '@shared/ipc';
+import {charBoundary, toWellFormed} from './streamText';
 
 export const MAX_LOG_PREVIEW_LENGTH = 400;
 
 export const previewText = (text: string | undefined, maxLength: number = MAX_LOG_PREVIEW_LENGTH): string => {
     const normalized = (text ?? '').toString();
     if (!normalized) return '';
-    if (normalized.length <= maxLength) return normalized;
-    return `${normalized.slice(0, maxLength)}… (${normalized.length} chars)`;
+    if (normalized.length <= maxLength) return toWellFormed(normalized);
+    const cut = toWellFormed(normalized.slice(0, charBoundary(normalized, maxLength)));
+    return `${cut}… (${normalized.length} chars)`;
 };
 
 export const buildLogPayload = (details: Record<string, unknown> = {}) => {
@@ -36,3 +40,72 @@ export const logRequest = (
     }
     logger.info('network', message, payload);
 };
+
+/** `localLlmOptions` in Ollama's `options` shape, without the unset ones. */
+export const buildOllamaOptions = (options: LocalLlmOptions | undefined): Record<string, unknown> => {
+    const result: Record<string, unknown> = {};
+    if (!options) return result;
+    if (options.temperature != null) result.temperature = options.temperature;
+    if (options.topP != null) result.top_p = options.topP;
+    if (options.numCtx != null) result.num_ctx = options.numCtx;
+    if (options.numPredict != null) result.num_predict = options.numPredict;
+    if (options.stop?.length) result.stop = options.stop;
+    if (options.seed != null) result.seed = options.seed;
+    return result;
+};
+
+/**
+ * Copies the options OpenAI has an equivalent for onto a chat completion body.
+ * Models without sampling controls get only `seed` and `max_completion_tokens`.
+ */
+export const applyOpenAiOptions = (
+    body: Record<string, unknown>,
+    options: LocalLlmOptions | undefined,
+    sampling: boolean
+) => {
+    if (!options) return;
+    const ignored: string[] = [];
+    if (options.temperature != null) {
+        if (sampling) body.temperature = options.temperature;
+        else ignored.push('temperature');
+    }
+    if (options.topP != null) {
+        if (sampling) body.top_p = options.topP;
+        else ignored.push('topP');
+    }
+    if (options.stop?.length) {
+        if (sampling) body.stop = options.stop;
+        else ignored.push('stop');
+    }
+    if (options.numPredict != null && options.numPredict > 0) {
+        body.max_completion_tokens = options.numPredict;
+    }
+    if (options.seed != null) body.seed = options.seed;
+    if (options.numCtx != null) ignored.push('numCtx');
+    if (ignored.length) {
+        logger.debug('network', 'llm:openai: options without an equivalent ignored', {ignored, model: body.model});
+    }
+};
+
+export const recordLlmUsage = (provider: string, usage: any, ok: boolean) => {
+    void invoke('usage_record_llm', {
+        provider,
+        tokensIn: usage?.prompt_tokens ?? usage?.input_tokens ?? null,
+        tokensOut: usage?.completion_tokens ?? usage?.output_tokens ?? null,
+        ok,
+    }).catch(() => undefined);
+};
+
+export const streamAnswerToClipboard = (delta: string, requestId?: string) => {
+    if (!delta) return;
+    void invoke('llm_clipboard_delta', {requestId, delta}).catch(() => undefined);
+};
+
+export const finishAnswerInClipboard = (full: string, requestId?: string) => {
+    void invoke('llm_clipboard_done', {requestId, full}).catch(() => undefined);
+};
+
+export const recordSessionAnswer = (text: string, interactionId?: string) => {
+    if (!text.trim()) return;
+    void invoke('session_record_answer', {text, interactionId}).catch(() => undefined);
+};
@@ -1,13 +1,15 @@
-import {invoke} from '@tauri-apps/api/core';
+import {invoke} from '../bridge/invoke';
 import {
     AskChatRequest,
     AppSettings,
     AssistantResponse,
     ChatHistoryMessage,
+    ForegroundWindowInfo,
     ProcessAudioArgs,
     ScreenProcessRequest,
     ScreenProcessResponse,
     StopStreamRequest,
+    TranscriptionResult,
 } from '@shared/ipc';
 import {
     GEMINI_LLM_MODELS,
@@ -19,8 +21,18 @@ import {
     WINKY_LLM_MODELS,
     WINKY_TRANSCRIBE_MODELS,
 } from '@shared/constants';
-import {logRequest, previewText} from './nativeAssistant.helpers';
+import {
+    applyOpenAiOptions,
+    buildOllamaOptions,
+    finishAnswerInClipboard,
+    logRequest,
+    previewText,
+    recordLlmUsage,
+    recordSessionAnswer,
+    streamAnswerToClipboard,
+} from './nativeAssistant.helpers';
 import {fetchWithTimeout, ollamaAxios} from './nativeAssistant.network';
+import {DeltaAssembler, toWellFormed} from './streamText';
 import {getSiteBaseUrl, getWsBaseUrl} from '@shared/appUrls';
 import {AuthError, authClient} from './authClient';
 import {uploadMediaFile} from './mediaClient';
@@ -118,7 +130,53 @@ const normalizeChatHistory = (history?: ChatHistoryMessage[]): ChatHistoryMessag
         .slice(-MAX_HISTORY_MESSAGES);
 };
 
+// One per streaming request and event kind, until the request ends.
+const deltaAssemblers = new Map<string, DeltaAssembler>();
+
 function emit<K extends keyof StreamEventPayloads>(key: K, payload: StreamEventPayloads[K]) {
+    if (key === 'delta' || key === 'transcript') {
+        const {delta, requestId} = payload as StreamEventPayloads['delta'];
+        const id = `${key}:${requestId ?? ''}`;
+        let assembler = deltaAssemblers.get(id);
+        if (!assembler) {
+            assembler = new DeltaAssembler();
+            deltaAssemblers.set(id, assembler);
+        }
+        // An empty transcript delta resets the view, so it still goes out.
+        const pieces = assembler.push(delta);
+        for (const piece of delta ? pieces : ['']) {
+            dispatch(key, {requestId, delta: piece} as StreamEventPayloads[K]);
+        }
+        return;
+    }
+    if (key === 'done' || key === 'error') {
+        const {requestId} = payload as StreamEventPayloads['done'];
+        for (const kind of ['delta', 'transcript'] as const) {
+            const id = `${kind}:${requestId ?? ''}`;
+            const rest = deltaAssemblers.get(id)?.flush() ?? [];
+            deltaAssemblers.delete(id);
+            for (const piece of rest) {
+                dispatch(kind, {requestId, delta: piece});
+            }
+        }
+    }
+    if (key === 'done') {
+        const done = payload as StreamEventPayloads['done'];
+        payload = {...done, full: toWellFormed(done.full)} as StreamEventPayloads[K];
+    }
+    dispatch(key, payload);
+}
+
+function dispatch<K extends keyof StreamEventPayloads>(key: K, payload: StreamEventPayloads[K]) {
+    if (key === 'delta') {
+        const {delta, requestId} = payload as StreamEventPayloads['delta'];
+        streamAnswerToClipboard(delta, requestId);
+    }
+    if (key === 'done') {
+        const {full, requestId} = payload as StreamEventPayloads['done'];
+        recordSessionAnswer(full, requestId);
+        finishAnswerInClipboard(full, requestId);
+    }
     const listeners = streamEvents[key];
     for (const listener of listeners) {
         try {
@@ -133,6 +191,70 @@ async function loadSettings(): Promise<AppSettings> {
     return invoke<AppSettings>('config_get');
 }
 
+// Same markers as foreground.rs, which strips them from session exports.
+const WINDOW_CONTEXT_START = '[window-context]';
+const WINDOW_CONTEXT_END = '[/window-context]';
+
+// Settings for an answer. The backend fills the prompt's `{{name}}` placeholders,
+// so a bad template fails the request with `prompt_template_invalid`.
+async function loadLlmSettings(transcript: string): Promise<AppSettings> {
+    const settings = await loadSettings();
+    const template = settings.llmPrompt || '';
+    const llmPrompt = template.includes('{{')
+        ? await invoke<string>('prompt_render', {vars: {transcript}})
+        : template;
+    return withWindowContext({...settings, llmPrompt});
+}
+
+// With includeWindowContext, the window in front is appended to the system
+// prompt between the markers above.
+async function withWindowContext(settings: AppSettings): Promise<AppSettings> {
+    if (!settings.includeWindowContext) {
+        return settings;
+    }
+    try {
+        const info = await invoke<ForegroundWindowInfo>('foreground_window_info');
+        const lines = [
+            info.appName ? `App: ${info.appName}` : '',
+            info.title ? `Window title: ${info.title}` : '',
+        ].filter(Boolean);
+        if (!lines.length) {
+            return settings;
+        }
+        const context = [
+            WINDOW_CONTEXT_START,
+            'The user is looking at this window:',
+            ...lines,
+            WINDOW_CONTEXT_END,
+        ].join('\n');
+        const base = (settings.llmPrompt || '').trim();
+        return {...settings, llmPrompt: base ? `${base}\n\n${context}` : context};
+    } catch (error) {
+        logRequest('window-context', 'error', {error: String(error)});
+        return settings;
+    }
+}
+
+// Waits for the provider's request budget, which transcription draws from too.
+function awaitProviderBudget(provider: 'openai' | 'google'): Promise<void> {
+    return invoke<void>('provider_rate_limit_acquire', {provider});
+}
+
+const QUOTA_HEADER_PREFIXES = ['x-ratelimit-', 'retry-after'];
+
+// Hands the provider's quota headers to the rate limiter, which holds the next
+// call back while they say it would be refused.
+function reportProviderQuota(provider: 'openai' | 'google', response: Response): void {
+    const headers: Record<string, string> = {};
+    response.headers.forEach((value, name) => {
+        if (QUOTA_HEADER_PREFIXES.some((prefix) => name.toLowerCase().startsWith(prefix))) {
+            headers[name] = value;
+        }
+    });
+    if (Object.keys(headers).length === 0) return;
+    void invoke<void>('provider_quota_report', {provider, headers}).catch(() => undefined);
+}
+
 function ensureOpenAiKey(settings: AppSettings): string {
     const key = settings.openaiApiKey?.trim();
     if (!key) {
@@ -220,8 +342,8 @@ function resolveLlmTarget(settings: AppSettings): LlmTarget {
     const host: LlmTarget['host'] = settings.llmHost === 'local' ? 'local' : 'api';
     const model =
         host === 'local'
-            ? settings.localLlmModel || settings.llmModel || DEFAULT_LOCAL_LLM
-            : settings.apiLlmModel || settings.llmModel || DEFAULT_API_LLM;
+            ? settings.localLlmModel || DEFAULT_LOCAL_LLM
+            : settings.apiLlmModel || DEFAULT_API_LLM;
 
     return {host, model};
 }
@@ -231,7 +353,8 @@ async function transcribeWithOpenAi(
     mime: string,
     filename: string,
     settings: AppSettings,
-    model?: string
+    model?: string,
+    interactionId?: string
 ): Promise<string> {
     const apiKey = ensureOpenAiKey(settings);
     const resolvedModel = model || settings.transcriptionModel || DEFAULT_API_TRANSCRIBE;
@@ -240,15 +363,16 @@ async function transcribeWithOpenAi(
     logRequest('transcribe:openai', 'start', {model: resolvedModel, mime});
 
     try {
-        const result = await invoke<{ text: string }>('transcribe_audio', {
+        const result = await invoke<TranscriptionResult>('transcribe_audio', {
             request: {
                 mode: 'api',
                 model: resolvedModel,
-                api_key: apiKey,
-                audio_data: Array.from(new Uint8Array(buffer)),
-                mime_type: mime || 'audio/wav',
+                apiKey,
+                audioData: Array.from(new Uint8Array(buffer)),
+                mimeType: mime || 'audio/wav',
                 filename,
                 prompt: prompt || undefined,
+                interactionId,
             },
         });
 
@@ -256,6 +380,7 @@ async function transcribeWithOpenAi(
         logRequest('transcribe:openai', 'ok', {
             model: resolvedModel,
             textPreview: previewText(text),
+            timings: result.timings,
         });
         return text;
     } catch (error: any) {
@@ -344,11 +469,42 @@ const appendStreamingDelta = (current: string, nextChunk: string): { full: strin
     return {full: current + nextChunk, delta: nextChunk};
 };
 
+/** Offline provider; the native side only accepts it while mock providers are enabled. */
+async function transcribeWithMock(
+    buffer: ArrayBuffer,
+    mime: string,
+    filename: string,
+    interactionId?: string
+): Promise<string> {
+    logRequest('transcribe:mock', 'start', {mime, bufferSize: buffer.byteLength});
+    try {
+        const result = await invoke<TranscriptionResult>('transcribe_audio', {
+            request: {
+                mode: 'mock',
+                model: undefined,
+                apiKey: undefined,
+                audioData: Array.from(new Uint8Array(buffer)),
+                mimeType: mime || 'audio/wav',
+                filename,
+                prompt: undefined,
+                interactionId,
+            },
+        });
+        const text = result.text || '';
+        logRequest('transcribe:mock', 'ok', {textPreview: previewText(text), timings: result.timings});
+        return text;
+    } catch (error: any) {
+        logRequest('transcribe:mock', 'error', {error: error.message || String(error)});
+        throw new Error(error.message || 'Transcription failed');
+    }
+}
+
 async function transcribeWithLocal(
     buffer: ArrayBuffer,
     mime: string,
     filename: string,
-    settings: AppSettings
+    settings: AppSettings,
+    interactionId?: string
 ): Promise<string> {
     // Validate buffer size
     if (buffer.byteLength < 1000) {
@@ -372,15 +528,17 @@ async function transcribeWithLocal(
     logRequest('transcribe:local', 'start', {model, mime, bufferSize: buffer.byteLength});
 
     try {
-        const result = await invoke<{ text: string }>('transcribe_audio', {
+        const result = await invoke<TranscriptionResult>('transcribe_audio', {
             request: {
                 mode: 'local',
                 model,
-                api_key: undefined,
-                audio_data: Array.from(new Uint8Array(buffer)),
-                mime_type: mime || 'audio/wav',
+                apiKey: undefined,
+                audioData: Array.from(new Uint8Array(buffer)),
+                mimeType: mime || 'audio/wav',
                 filename,
-                prompt: undefined, // Don't send prompt to FastWhisper
+                // The backend drops this unless sendPromptToLocal is on.
+                prompt: settings.transcriptionPrompt?.trim() || undefined,
+                interactionId,
             },
         });
 
@@ -416,6 +574,7 @@ async function transcribeWithLocal(
         logRequest('transcribe:local', 'ok', {
             model,
             textPreview: previewText(text),
+            timings: result.timings,
         });
         return text;
     } catch (error: any) {
@@ -428,7 +587,8 @@ async function transcribeWithGoogle(
     buffer: ArrayBuffer,
     mime: string,
     settings: AppSettings,
-    model?: string
+    model?: string,
+    interactionId?: string
 ): Promise<string> {
     const key = ensureGoogleKey(settings);
     const resolvedModel = model || settings.transcriptionModel || DEFAULT_API_TRANSCRIBE;
@@ -459,15 +619,16 @@ async function transcribeWithGoogle(
     });
 
     try {
-        const result = await invoke<{ text: string }>('transcribe_audio', {
+        const result = await invoke<TranscriptionResult>('transcribe_audio', {
             request: {
                 mode: 'google',
                 model: resolvedModel,
-                api_key: key,
-                audio_data: Array.from(new Uint8Array(buffer)),
-                mime_type: mime || 'audio/wav',
+                apiKey: key,
+                audioData: Array.from(new Uint8Array(buffer)),
+                mimeType: mime || 'audio/wav',
                 filename: 'audio.wav',
                 prompt: prompt || undefined,
+                interactionId,
             },
         });
 
@@ -484,6 +645,7 @@ async function transcribeWithGoogle(
         logRequest('transcribe:google', 'ok', {
             model: resolvedModel,
             textPreview: previewText(text),
+            timings: result.timings,
         });
         return text;
     } catch (error: any) {
@@ -500,8 +662,9 @@ async function chatCompletion(
     signal?: AbortSignal
 ): Promise<string> {
     const apiKey = ensureOpenAiKey(settings);
+    await awaitProviderBudget('openai');
     const url = `${OPENAI_BASE}/v1/chat/completions`;
-    const resolvedModel = model || settings.llmModel || settings.apiLlmModel || DEFAULT_API_LLM;
+    const resolvedModel = model || settings.apiLlmModel || DEFAULT_API_LLM;
     const systemPrompt = (settings.llmPrompt || '').trim();
     const normalizedHistory = normalizeChatHistory(history);
     const messages: Array<{ role: string; content: string }> = [];
@@ -520,6 +683,7 @@ async function chatCompletion(
     if (supportsCustomTemperature(resolvedModel)) {
         body.temperature = 0.3;
     }
+    applyOpenAiOptions(body, settings.localLlmOptions, supportsCustomTemperature(resolvedModel));
     logRequest('llm:openai', 'start', {model: body.model, promptPreview: previewText(prompt)});
     let logged = false;
     try {
@@ -536,9 +700,11 @@ async function chatCompletion(
             },
             settings.apiLlmTimeoutMs
         );
+        reportProviderQuota('openai', response);
         const data = await response.json().catch(async () => ({text: await response.text()}));
         if (!response.ok) {
             logRequest('llm:openai', 'error', {status: response.status, data});
+            recordLlmUsage('openai', null, false);
             logged = true;
             const message = typeof data === 'string'
                 ? data
@@ -552,6 +718,7 @@ async function chatCompletion(
             promptPreview: previewText(prompt),
             responsePreview: previewText(content),
         });
+        recordLlmUsage('openai', data?.usage, true);
         return content;
     } catch (error) {
         const aborted = signal?.aborted || (error instanceof DOMException && error.name === 'AbortError');
@@ -658,7 +825,8 @@ async function chatWithGemini(
     signal?: AbortSignal
 ): Promise<string> {
     const accessToken = ensureGoogleKey(settings);
-    const resolvedModel = model || settings.llmModel || settings.apiLlmModel || GEMINI_LLM_MODELS[0] || 'gemini-3.0-pro';
+    await awaitProviderBudget('google');
+    const resolvedModel = model || settings.apiLlmModel || GEMINI_LLM_MODELS[0] || 'gemini-3.0-pro';
     const url = `https://generativelanguage.googleapis.com/v1beta/models/${resolvedModel}:generateContent?key=${accessToken}`;
     logRequest('llm:gemini', 'start', {model: resolvedModel, promptPreview: previewText(prompt)});
     const body = buildGeminiBody(prompt, settings, history);
@@ -674,6 +842,7 @@ async function chatWithGemini(
             },
             settings.apiLlmTimeoutMs
         );
+        reportProviderQuota('google', response);
         const data = await response.json().catch(async () => ({text: await response.text()}));
         if (!response.ok) {
             logRequest('llm:gemini', 'error', {status: response.status, data});
@@ -738,9 +907,11 @@ async function chatWithOllama(
     logRequest('llm:ollama', 'start', {model: resolvedModel, promptPreview: previewText(prompt)});
     let logged = false;
     try {
-        const response = await ollamaAxios.post('/v1/chat/completions', {
+        const response = await ollamaAxios.post('/api/chat', {
             model: resolvedModel,
             messages,
+            stream: false,
+            options: buildOllamaOptions(settings.localLlmOptions),
         }, {
             timeout: Math.max(settings.apiLlmTimeoutMs || 0, 600_000),
             signal,
@@ -972,7 +1143,8 @@ async function streamGeminiChatCompletion(
     controller: AbortController
 ): Promise<void> {
     const accessToken = ensureGoogleKey(settings);
-    const resolvedModel = model || settings.llmModel || settings.apiLlmModel || GEMINI_LLM_MODELS[0] || 'gemini-3.0-pro';
+    await awaitProviderBudget('google');
+    const resolvedModel = model || settings.apiLlmModel || GEMINI_LLM_MODELS[0] || 'gemini-3.0-pro';
     const url =
         `https://generativelanguage.googleapis.com/v1beta/models/${resolvedModel}:streamGenerateContent?alt=sse&key=${accessToken}`;
     const body = buildGeminiBody(prompt, settings, history);
@@ -987,6 +1159,7 @@ async function streamGeminiChatCompletion(
         },
         settings.apiLlmTimeoutMs
     );
+    reportProviderQuota('google', response);
     if (!response.ok) {
         const data = await response.json().catch(async () => ({text: await response.text()}));
         const message = typeof data === 'string'
@@ -1057,7 +1230,7 @@ async function streamOllamaChatCompletion(
     history: ChatHistoryMessage[] | undefined,
     controller: AbortController
 ): Promise<void> {
-    const model = settings.localLlmModel || settings.llmModel || DEFAULT_LOCAL_LLM;
+    const model = settings.localLlmModel || DEFAULT_LOCAL_LLM;
     const systemPrompt = (settings.llmPrompt || '').trim();
     const normalizedHistory = normalizeChatHistory(history);
     const messages = [
@@ -1068,11 +1241,16 @@ async function streamOllamaChatCompletion(
     logRequest('llm:ollama:stream', 'start', {requestId, model, promptPreview: previewText(prompt)});
 
     const response = await fetchWithTimeout(
-        'http://localhost:11434/v1/chat/completions',
+        'http://localhost:11434/api/chat',
         {
             method: 'POST',
             headers: {'Content-Type': 'application/json'},
-            body: JSON.stringify({model, messages, stream: true}),
+            body: JSON.stringify({
+                model,
+                messages,
+                stream: true,
+                options: buildOllamaOptions(settings.localLlmOptions),
+            }),
             signal: controller.signal,
         },
         Math.max(settings.apiLlmTimeoutMs || 0, 600_000)
@@ -1202,6 +1380,7 @@ async function streamChatCompletion(
     }
 
     const apiKey = ensureOpenAiKey(settings);
+    await awaitProviderBudget('openai');
     const url = `${OPENAI_BASE}/v1/chat/completions`;
     const systemPrompt = (settings.llmPrompt || '').trim();
     const normalizedHistory = normalizeChatHistory(history);
@@ -1222,6 +1401,7 @@ async function streamChatCompletion(
     if (supportsCustomTemperature(model)) {
         body.temperature = 0.3;
     }
+    applyOpenAiOptions(body, settings.localLlmOptions, supportsCustomTemperature(model));
     const response = await fetchWithTimeout(
         url,
         {
@@ -1235,6 +1415,7 @@ async function streamChatCompletion(
         },
         settings.apiLlmTimeoutMs
     );
+    reportProviderQuota('openai', response);
     if (!response.ok || !response.body) {
         logRequest('llm:stream', 'error', {requestId, status: response.status});
         const text = await response.text();
@@ -1292,7 +1473,7 @@ async function streamChatCompletion(
     });
 }
 
-type TranscriptionModeValue = 'api' | 'local';
+type TranscriptionModeValue = 'api' | 'local' | 'mock';
 
 type TranscriptionRunOptions = {
     settings: AppSettings;
@@ -1300,6 +1481,7 @@ type TranscriptionRunOptions = {
     mime: string;
     filename: string;
     stream?: boolean;
+    interactionId?: string;
 };
 
 type TranscriptionRunResult = {
@@ -1316,11 +1498,16 @@ const buildTranscriptionLogContext = (
 ) => (stream ? {mode, model, mime, stream: true} : {mode, model, mime});
 
 const resolveTranscriptionTarget = (settings: AppSettings): { mode: TranscriptionModeValue; model: string } => {
-    const mode: TranscriptionModeValue = settings.transcriptionMode === 'local' ? 'local' : 'api';
+    const mode: TranscriptionModeValue =
+        settings.transcriptionMode === 'local' || settings.transcriptionMode === 'mock'
+            ? settings.transcriptionMode
+            : 'api';
     const model =
-        mode === 'local'
-            ? settings.localWhisperModel || DEFAULT_LOCAL_TRANSCRIBE
-            : settings.transcriptionModel || DEFAULT_API_TRANSCRIBE;
+        mode === 'mock'
+            ? 'mock'
+            : mode === 'local'
+                ? settings.localWhisperModel || DEFAULT_LOCAL_TRANSCRIBE
+                : settings.transcriptionModel || DEFAULT_API_TRANSCRIBE;
     return {mode, model};
 };
 
@@ -1330,6 +1517,7 @@ async function transcribeAudioBuffer({
                                          mime,
                                          filename,
                                          stream = false,
+                                         interactionId,
                                      }: TranscriptionRunOptions): Promise<TranscriptionRunResult> {
     const {mode: transcriptionMode, model: transcriptionModel} = resolveTranscriptionTarget(settings);
 
@@ -1337,16 +1525,19 @@ async function transcribeAudioBuffer({
     logRequest('transcribe', 'start', logPayload);
 
     const text = await (async () => {
+        if (transcriptionMode === 'mock') {
+            return transcribeWithMock(buffer, mime, filename, interactionId);
+        }
         if (transcriptionMode === 'local') {
-            return transcribeWithLocal(buffer, mime, filename, settings);
+            return transcribeWithLocal(buffer, mime, filename, settings, interactionId);
         }
         if (WINKY_TRANSCRIBE_SET.has(transcriptionModel)) {
             return transcribeWithWinky(buffer, mime, settings);
         }
         if (GOOGLE_TRANSCRIBE_SET.has(transcriptionModel)) {
-            return transcribeWithGoogle(buffer, mime, settings, transcriptionModel);
+            return transcribeWithGoogle(buffer, mime, settings, transcriptionModel, interactionId);
         }
-        return transcribeWithOpenAi(buffer, mime, filename, settings, transcriptionModel);
+        return transcribeWithOpenAi(buffer, mime, filename, settings, transcriptionModel, interactionId);
     })();
 
     logRequest('transcribe', 'ok', {
@@ -1358,17 +1549,20 @@ async function transcribeAudioBuffer({
 }
 
 export async function assistantProcessAudio(args: ProcessAudioArgs): Promise<AssistantResponse> {
-    const settings = await loadSettings();
+    const transcriptionSettings = await loadSettings();
     const buffer = args.arrayBuffer;
     if (buffer.byteLength === 0) {
         return {ok: false, error: 'Empty audio'};
     }
+    const interactionId = args.interactionId ?? args.requestId;
     const {text, mode: transcriptionMode, model: transcriptionModel} = await transcribeAudioBuffer({
-        settings,
+        settings: transcriptionSettings,
         buffer,
         mime: args.mime,
         filename: args.filename || 'lastN.webm',
+        interactionId,
     });
+    const settings = await loadLlmSettings(text);
 
     const {host: llmHost, model: llmModel} = resolveLlmTarget(settings);
     logRequest('llm:select', 'start', {
@@ -1377,7 +1571,6 @@ export async function assistantProcessAudio(args: ProcessAudioArgs): Promise<Ass
         rawHost: settings.llmHost,
         rawApiModel: settings.apiLlmModel,
         rawLocalModel: settings.localLlmModel,
-        rawLlmModel: settings.llmModel,
     });
     let answer = '';
     if (text) {
@@ -1399,6 +1592,7 @@ export async function assistantProcessAudio(args: ProcessAudioArgs): Promise<Ass
         textPreview: previewText(text),
         answerPreview: previewText(answer),
     });
+    recordSessionAnswer(answer, interactionId);
     return {ok: true, text, answer};
 }
 
@@ -1416,12 +1610,13 @@ export async function assistantTranscribeOnly(args: ProcessAudioArgs): Promise<{
         buffer,
         mime: args.mime,
         filename: args.filename || 'lastN.webm',
+        interactionId: args.interactionId,
     });
     return {ok: true, text};
 }
 
 export async function assistantProcessAudioStream(args: ProcessAudioArgs): Promise<AssistantResponse> {
-    const settings = await loadSettings();
+    const transcriptionSettings = await loadSettings();
     const buffer = args.arrayBuffer;
     if (buffer.byteLength === 0) {
         return {ok: false, error: 'Empty audio'};
@@ -1429,12 +1624,14 @@ export async function assistantProcessAudioStream(args: ProcessAudioArgs): Promi
     const requestId = args.requestId || crypto.randomUUID();
     emit('transcript', {requestId, delta: ''});
     const {text} = await transcribeAudioBuffer({
-        settings,
+        settings: transcriptionSettings,
         buffer,
         mime: args.mime,
         filename: args.filename || 'lastN.webm',
         stream: true,
+        interactionId: args.interactionId ?? requestId,
     });
+    const settings = await loadLlmSettings(text);
     emit('transcript', {requestId, delta: text});
     const {host: llmHost, model: llmModel} = resolveLlmTarget(settings);
     logRequest('llm:stream', 'start', {
@@ -1444,7 +1641,6 @@ export async function assistantProcessAudioStream(args: ProcessAudioArgs): Promi
         rawHost: settings.llmHost,
         rawApiModel: settings.apiLlmModel,
         rawLocalModel: settings.localLlmModel,
-        rawLlmModel: settings.llmModel,
         promptPreview: previewText(text),
     });
 
@@ -1455,7 +1651,7 @@ export async function assistantProcessAudioStream(args: ProcessAudioArgs): Promi
 }
 
 export async function assistantAskChat(args: AskChatRequest) {
-    const settings = await loadSettings();
+    const settings = await loadLlmSettings(args.text);
     const requestId = args.requestId || crypto.randomUUID();
     const history = normalizeChatHistory(args.history);
     runWithActiveStream(requestId, (controller) =>
@@ -1472,6 +1668,28 @@ export async function assistantStopStream(args: StopStreamRequest): Promise<void
     }
 }
 
+// Rolling summary for `session:summary-request`. It is tracked like a stream,
+// so assistantStopStream with the same id cancels it.
+export async function assistantSummarize(requestId: string, summaryPrompt: string, text: string): Promise<string> {
+    const settings = {...(await loadSettings()), llmPrompt: summaryPrompt};
+    const {host: llmHost, model: llmModel} = resolveLlmTarget(settings);
+    const controller = new AbortController();
+    activeStreams.set(requestId, controller);
+    logRequest('llm:summary', 'start', {requestId, host: llmHost, model: llmModel});
+    try {
+        if (llmHost === 'local') {
+            return await chatWithOllama(text, settings, llmModel, undefined, controller.signal);
+        } else if (WINKY_LLM_SET.has(llmModel)) {
+            return await chatWithWinky(text, settings, llmModel, undefined, controller);
+        } else if (GEMINI_LLM_SET.has(llmModel)) {
+            return await chatWithGemini(text, settings, llmModel, undefined, controller.signal);
+        }
+        return await chatCompletion(text, settings, llmModel, undefined, controller.signal);
+    } finally {
+        activeStreams.delete(requestId);
+    }
+}
+
 export function assistantOnStreamTranscript(cb: StreamListener<{ requestId?: string; delta: string }>) {
     addStreamListener('transcript', cb);
 }
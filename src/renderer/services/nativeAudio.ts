@@ -1,22 +1,49 @@
 // noinspection JSUnusedGlobalSymbols
 
 import {listen, UnlistenFn} from '@tauri-apps/api/event';
-import type {AudioDeviceInfo} from '@shared/ipc';
+import type {AudioBackpressure, AudioDeviceInfo, AudioPartialStart, AudioSourceChanged} from '@shared/ipc';
 
 export type AudioSourceKind = 'mic' | 'system' | 'mixed';
+export type AudioInputKind = 'microphone' | 'system' | 'mixed';
 
 export type AudioChunk = {
+    /** Numbered at capture; unbroken across source switches. */
+    seq: number;
+    /** Host clock, Unix ms, when the chunk was captured. */
+    captureTimestampMs: number;
+    durationMs: number;
+    /** Chunks dropped natively right before this one, from the gap in `seq`. */
+    droppedBefore: number;
+    /** Recorded by the pre-roll before capture started; these arrive first. */
+    preRoll: boolean;
     sampleRate: number;
     channels: number;
     samples: Float32Array[];
     rms: number;
+    /** How far system audio was ducked under the user's voice, in dB; 0 when not. */
+    duckingDb: number;
 };
 
 type ChunkListener = (chunk: AudioChunk) => void;
+type LevelListener = (rms: number, duckingDb: number) => void;
+type BackpressureListener = (state: AudioBackpressure) => void;
+type PartialStartListener = (payload: AudioPartialStart) => void;
+type SourceChangedListener = (payload: AudioSourceChanged) => void;
+type AutoStartListener = (inputType: AudioInputKind) => void;
+
+// Acknowledge every few chunks; the native side throttles once acks lag too far.
+const ACK_EVERY_CHUNKS = 10;
 
 let chunkUnlisten: UnlistenFn | null = null;
+let flowUnlisten: UnlistenFn[] = [];
 const listeners = new Set<ChunkListener>();
+const levelListeners = new Set<LevelListener>();
+const backpressureListeners = new Set<BackpressureListener>();
+const partialStartListeners = new Set<PartialStartListener>();
+const sourceChangedListeners = new Set<SourceChangedListener>();
 let chunkCounter = 0;
+let lastSeq = 0;
+let lastAckedSeq = 0;
 
 export async function listAudioDevices(): Promise<AudioDeviceInfo[]> {
     return (await window.api?.audio?.listDevices?.()) ?? [];
@@ -29,6 +56,13 @@ export async function startAudioCapture(source: AudioSourceKind, deviceId?: stri
     await window.api?.audio?.startCapture?.(source, deviceId);
 }
 
+/** Keeps the chunk listener; the same stream continues from the new source. */
+export async function switchAudioSource(source: AudioSourceKind, deviceId?: string): Promise<void> {
+    console.log('[nativeAudio] switchSource', {source, deviceId});
+    await ensureListener();
+    await window.api?.audio?.switchSource?.(source, deviceId);
+}
+
 export async function stopAudioCapture(): Promise<void> {
     console.log('[nativeAudio] stopCapture');
     await window.api?.audio?.stopCapture?.();
@@ -36,7 +70,11 @@ export async function stopAudioCapture(): Promise<void> {
         await chunkUnlisten();
         chunkUnlisten = null;
     }
+    await Promise.all(flowUnlisten.map((unlisten) => unlisten()));
+    flowUnlisten = [];
     listeners.clear();
+    lastSeq = 0;
+    lastAckedSeq = 0;
 }
 
 export function onAudioChunk(cb: ChunkListener): () => void {
@@ -47,18 +85,96 @@ export function onAudioChunk(cb: ChunkListener): () => void {
     };
 }
 
+/** Level-only updates sent instead of chunks while the webview is behind. */
+export function onAudioLevel(cb: LevelListener): () => void {
+    levelListeners.add(cb);
+    return () => {
+        levelListeners.delete(cb);
+    };
+}
+
+export function onAudioBackpressure(cb: BackpressureListener): () => void {
+    backpressureListeners.add(cb);
+    return () => {
+        backpressureListeners.delete(cb);
+    };
+}
+
+/** Mixed captures that started with only some of their inputs. */
+export function onAudioPartialStart(cb: PartialStartListener): () => void {
+    partialStartListeners.add(cb);
+    return () => {
+        partialStartListeners.delete(cb);
+    };
+}
+
+export function onAudioSourceChanged(cb: SourceChangedListener): () => void {
+    sourceChangedListeners.add(cb);
+    return () => {
+        sourceChangedListeners.delete(cb);
+    };
+}
+
+/**
+ * Capture the native side started on launch. The webview usually loads after that,
+ * so a capture that is already running when this is called is reported as well.
+ */
+export async function onCaptureAutoStarted(cb: AutoStartListener): Promise<UnlistenFn> {
+    const unlisten = await listen<AudioInputKind>('audio:auto-started', (event) => cb(event.payload));
+    const status = await window.api?.audio?.getStatus?.();
+    if (status?.active && status.source) {
+        cb(status.source);
+    }
+    return unlisten;
+}
+
 async function ensureListener(): Promise<void> {
     if (chunkUnlisten) return;
     console.log('[nativeAudio] ensuring listener for audio:chunk');
+    flowUnlisten = await Promise.all([
+        listen<{ rms: number; duckingDb?: number }>('audio:level', (event) => {
+            levelListeners.forEach((fn) => fn(event.payload.rms, event.payload.duckingDb ?? 0));
+        }),
+        listen<AudioBackpressure>('audio:backpressure', (event) => {
+            if (event.payload.active) {
+                console.warn('[nativeAudio] webview is behind, audio chunks are buffered natively', event.payload);
+            }
+            backpressureListeners.forEach((fn) => fn(event.payload));
+        }),
+        listen<AudioPartialStart>('audio:partial-start', (event) => {
+            console.warn('[nativeAudio] capture started without some sources', event.payload);
+            partialStartListeners.forEach((fn) => fn(event.payload));
+        }),
+        listen<AudioSourceChanged>('audio:source-changed', (event) => {
+            sourceChangedListeners.forEach((fn) => fn(event.payload));
+        }),
+    ]);
     chunkUnlisten = await listen<{
-        sample_rate: number;
+        seq: number;
+        captureTimestampMs: number;
+        durationMs: number;
+        sampleRate: number;
         channels: number;
-        data_base64: string;
+        preRoll?: boolean;
+        duckingDb?: number;
+        dataBase64: string;
     }>('audio:chunk', (event) => {
         const payload = event.payload;
-        if (!payload || !payload.data_base64) return;
+        if (!payload || !payload.dataBase64) return;
+        // A new capture numbers from 1 again.
+        if (payload.seq <= lastSeq) {
+            lastSeq = 0;
+            lastAckedSeq = 0;
+        }
+        const droppedBefore = lastSeq > 0 ? payload.seq - lastSeq - 1 : 0;
+        lastSeq = payload.seq;
+        // Dropped chunks leave gaps, so count from the last ack instead of using seq % n.
+        if (payload.seq - lastAckedSeq >= ACK_EVERY_CHUNKS) {
+            lastAckedSeq = payload.seq;
+            void window.api?.audio?.ack?.(payload.seq);
+        }
         try {
-            const bytes = Uint8Array.from(atob(payload.data_base64), (c) => c.charCodeAt(0));
+            const bytes = Uint8Array.from(atob(payload.dataBase64), (c) => c.charCodeAt(0));
             if (bytes.length === 0) return;
 
             // Data is interleaved i16: [L, R, L, R, ...] for stereo
@@ -91,10 +207,16 @@ async function ensureListener(): Promise<void> {
 
             const rms = Math.sqrt(totalSum / Math.max(1, totalSamples));
             const chunk: AudioChunk = {
-                sampleRate: payload.sample_rate || 48000,
+                seq: payload.seq,
+                captureTimestampMs: payload.captureTimestampMs,
+                durationMs: payload.durationMs,
+                droppedBefore,
+                preRoll: payload.preRoll ?? false,
+                sampleRate: payload.sampleRate || 48000,
                 channels,
                 samples: perChannel,
                 rms,
+                duckingDb: payload.duckingDb ?? 0,
             };
             chunkCounter += 1;
             listeners.forEach((fn) => {
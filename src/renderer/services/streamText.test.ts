@@ -0,0 +1,93 @@
+import assert from 'node:assert/strict';
+import {test} from 'node:test';
+
+import {
+    DeltaAssembler,
+    MAX_EVENT_DELTA_CHARS,
+    charBoundary,
+    splitOnCharBoundaries,
+    toWellFormed,
+} from './streamText';
+
+const LONE_SURROGATE = /[\uD800-\uDBFF](?![\uDC00-\uDFFF])|(?<![\uD800-\uDBFF])[\uDC00-\uDFFF]/;
+
+const SAMPLE = 'Hi 👋🏽 — naïve café, 𝔘𝔫𝔦𝔠𝔬𝔡𝔢 and 家族 👨‍👩‍👧!';
+
+/** Streams `deltas` through one assembler and returns every piece it emitted. */
+const stream = (deltas: string[]): string[] => {
+    const assembler = new DeltaAssembler();
+    return [...deltas.flatMap((delta) => assembler.push(delta)), ...assembler.flush()];
+};
+
+const assertPieces = (pieces: string[], expected: string, label: string) => {
+    assert.equal(pieces.join(''), expected, label);
+    for (const piece of pieces) {
+        assert.ok(!LONE_SURROGATE.test(piece), `${label}: lone surrogate in ${JSON.stringify(piece)}`);
+        assert.ok(piece.length <= MAX_EVENT_DELTA_CHARS, `${label}: piece of ${piece.length}`);
+    }
+};
+
+test('toWellFormed keeps pairs and replaces stray halves', () => {
+    assert.equal(toWellFormed(SAMPLE), SAMPLE);
+    assert.equal(toWellFormed('a\uD83Db'), 'a�b');
+    assert.equal(toWellFormed('\uDE00a\uD83D'), '�a�');
+    assert.equal(toWellFormed('\uDE00\uD83D'), '��');
+});
+
+test('charBoundary never lands inside a pair', () => {
+    const text = 'a😀b';
+    assert.deepEqual(
+        [0, 1, 2, 3, 4, 5].map((index) => charBoundary(text, index)),
+        [0, 1, 1, 3, 4, 4],
+    );
+});
+
+test('every single split point reassembles the text', () => {
+    for (let cut = 0; cut <= SAMPLE.length; cut += 1) {
+        assertPieces(stream([SAMPLE.slice(0, cut), SAMPLE.slice(cut)]), SAMPLE, `cut at ${cut}`);
+    }
+});
+
+test('every pair of split points reassembles the text', () => {
+    for (let first = 0; first <= SAMPLE.length; first += 1) {
+        for (let second = first; second <= SAMPLE.length; second += 1) {
+            const deltas = [SAMPLE.slice(0, first), SAMPLE.slice(first, second), SAMPLE.slice(second)];
+            assertPieces(stream(deltas), SAMPLE, `cuts at ${first} and ${second}`);
+        }
+    }
+});
+
+test('one unit per delta reassembles the text', () => {
+    assertPieces(stream(SAMPLE.split('')), SAMPLE, 'unit by unit');
+});
+
+test('stray halves become U+FFFD without losing the rest', () => {
+    assert.deepEqual(stream(['a\uD83D', 'b']), ['a', '�b']);
+    assert.deepEqual(stream(['a', '\uDE00b']), ['a', '�b']);
+    assert.deepEqual(stream(['end\uD83D']), ['end', '�']);
+});
+
+test('long deltas are split at 4096 units without cutting a character', () => {
+    const plain = 'x'.repeat(MAX_EVENT_DELTA_CHARS * 2);
+    assert.deepEqual(
+        splitOnCharBoundaries(plain).map((piece) => piece.length),
+        [MAX_EVENT_DELTA_CHARS, MAX_EVENT_DELTA_CHARS],
+    );
+
+    // A pair straddling the limit moves whole into the next piece.
+    const straddling = `${'x'.repeat(MAX_EVENT_DELTA_CHARS - 1)}😀${'y'.repeat(10)}`;
+    const pieces = splitOnCharBoundaries(straddling);
+    assert.deepEqual(
+        pieces.map((piece) => piece.length),
+        [MAX_EVENT_DELTA_CHARS - 1, 12],
+    );
+    assertPieces(pieces, straddling, 'straddling');
+});
+
+test('split points around the 4096 limit reassemble the text', () => {
+    const text = `${'x'.repeat(MAX_EVENT_DELTA_CHARS - 3)}${SAMPLE}${'😀'.repeat(MAX_EVENT_DELTA_CHARS)}`;
+    for (let cut = MAX_EVENT_DELTA_CHARS - 8; cut <= MAX_EVENT_DELTA_CHARS + SAMPLE.length; cut += 1) {
+        assertPieces(stream([text.slice(0, cut), text.slice(cut)]), text, `cut at ${cut}`);
+    }
+    assertPieces(stream([text]), text, 'whole');
+});
@@ -0,0 +1,75 @@
+// Streamed text arrives in pieces cut wherever the provider flushed, which for
+// emoji and other astral characters can fall between the two halves of a
+// surrogate pair. A lone half breaks JSON on the way to the backend and some
+// listeners throw on it, so deltas go through a DeltaAssembler before they are
+// emitted.
+
+/** Largest delta handed to listeners at once; longer ones are split. */
+export const MAX_EVENT_DELTA_CHARS = 4096;
+
+const isHighSurrogate = (code: number) => code >= 0xd800 && code <= 0xdbff;
+const isLowSurrogate = (code: number) => code >= 0xdc00 && code <= 0xdfff;
+
+/** Replaces unpaired surrogate halves with U+FFFD. */
+export const toWellFormed = (text: string): string => {
+    let result = '';
+    let start = 0;
+    for (let index = 0; index < text.length; index += 1) {
+        const code = text.charCodeAt(index);
+        if (isHighSurrogate(code) && isLowSurrogate(text.charCodeAt(index + 1))) {
+            index += 1;
+            continue;
+        }
+        if (isHighSurrogate(code) || isLowSurrogate(code)) {
+            result += `${text.slice(start, index)}\uFFFD`;
+            start = index + 1;
+        }
+    }
+    return start === 0 ? text : result + text.slice(start);
+};
+
+/** Index at or before `index` that does not fall inside a surrogate pair. */
+export const charBoundary = (text: string, index: number): number => {
+    if (index <= 0 || index >= text.length) return Math.max(0, Math.min(index, text.length));
+    const before = text.charCodeAt(index - 1);
+    return isHighSurrogate(before) && isLowSurrogate(text.charCodeAt(index)) ? index - 1 : index;
+};
+
+/** Splits `text` into pieces of at most `max` UTF-16 units without cutting a character. */
+export const splitOnCharBoundaries = (text: string, max: number = MAX_EVENT_DELTA_CHARS): string[] => {
+    const pieces: string[] = [];
+    let start = 0;
+    while (text.length - start > max) {
+        const end = charBoundary(text, start + max);
+        pieces.push(text.slice(start, end));
+        start = end;
+    }
+    if (start < text.length) pieces.push(text.slice(start));
+    return pieces;
+};
+
+/**
+ * Reassembles one stream of deltas: a trailing high surrogate waits for the
+ * next delta, stray halves become U+FFFD and every piece returned is complete
+ * text of at most `MAX_EVENT_DELTA_CHARS`.
+ */
+export class DeltaAssembler {
+    private pending = '';
+
+    push(delta: string): string[] {
+        let text = this.pending + delta;
+        this.pending = '';
+        if (text && isHighSurrogate(text.charCodeAt(text.length - 1))) {
+            this.pending = text.slice(-1);
+            text = text.slice(0, -1);
+        }
+        return splitOnCharBoundaries(toWellFormed(text));
+    }
+
+    /** What is still held back, once the stream has ended. */
+    flush(): string[] {
+        const rest = this.pending;
+        this.pending = '';
+        return rest ? [toWellFormed(rest)] : [];
+    }
+}
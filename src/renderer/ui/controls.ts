@@ -129,6 +129,27 @@ function initTextInput(onTextSend?: (text: string) => Promise<void> | void) {
     updateSendButtonState();
 }
 
+/** Shows a capture the native side started as if the record button had started it. */
+export async function adoptRecording(attach: () => Promise<void>): Promise<void> {
+    if (state.isRecording) return;
+    const btnRecord = document.getElementById('btnRecord') as HTMLButtonElement | null;
+    setRecording(true);
+    try {
+        await attach();
+    } catch (error) {
+        logger.error('ui', 'Failed to attach to running capture', {error});
+        setRecording(false);
+        updateButtonsState();
+        return;
+    }
+    if (btnRecord) {
+        btnRecord.textContent = 'Stop';
+        btnRecord.dataset['state'] = 'rec';
+    }
+    updateButtonsState();
+    setStatus('Recording...', 'recording');
+}
+
 export function initControls({onRecordToggle, durations, onDurationChange, onTextSend}: ControlsInitArgs) {
     const btnRecord = document.getElementById('btnRecord') as HTMLButtonElement | null;
     const sendLastContainer = document.getElementById('send-last-container') as HTMLDivElement | null;
@@ -12,6 +12,8 @@ type ChatMessage = {
     text: string;
     pending?: boolean;
     retryText?: string;
+    /** Added during a private session; never written to storage. */
+    ephemeral?: boolean;
 };
 
 type ChatSession = {
@@ -42,6 +44,7 @@ const CHAT_STORAGE_KEY = 'xexamai.chat.sessions.v1';
 let sessionsHydrated = false;
 let chatSessions: ChatSession[] = [];
 let activeChatId: string | null = null;
+let privateSession = false;
 
 const chatSessionListeners = new Set<ChatSessionListener>();
 export const CHAT_RETRY_EVENT_NAME = 'xexamai:chat-retry';
@@ -205,10 +208,12 @@ function persistSessions(): void {
             activeChatId,
             sessions: chatSessions.map((session) => ({
                 ...session,
-                messages: session.messages.map((message) => ({
-                    ...message,
-                    pending: false,
-                })),
+                messages: session.messages
+                    .filter((message) => !message.ephemeral)
+                    .map((message) => ({
+                        ...message,
+                        pending: false,
+                    })),
             })),
         };
         window.localStorage?.setItem(CHAT_STORAGE_KEY, JSON.stringify(payload));
@@ -312,8 +317,10 @@ function summarizeTitleFromMessage(text: string): string | null {
     return `${firstLine.slice(0, 48).trimEnd()}...`;
 }
 
-function maybeUpdateSessionTitle(session: ChatSession, role: ChatRole, text: string): void {
-    if (role !== 'user') return;
+function maybeUpdateSessionTitle(session: ChatSession, message: ChatMessage): void {
+    const {role, text} = message;
+    // The title is stored with the chat, so a private message must not become it.
+    if (role !== 'user' || message.ephemeral) return;
     if (!session.title.startsWith('New chat')) return;
     const title = summarizeTitleFromMessage(text);
     if (!title) return;
@@ -441,6 +448,11 @@ export function subscribeChatSessions(listener: ChatSessionListener): () => void
     };
 }
 
+/** Messages added while this is on stay in memory only. */
+export function setPrivateSession(enabled: boolean): void {
+    privateSession = enabled;
+}
+
 export function getActiveChatId(): string {
     return getActiveSession().id;
 }
@@ -487,10 +499,11 @@ export function appendChatMessage(
         text: text || '',
         pending: options?.pending ?? false,
         retryText: options?.retryText?.trim() || undefined,
+        ephemeral: privateSession || undefined,
     };
     session.messages.push(entry);
     session.updatedAt = Date.now();
-    maybeUpdateSessionTitle(session, role, entry.text);
+    maybeUpdateSessionTitle(session, entry);
     persistSessions();
     renderChat();
     notifyChatSessionsChanged();
@@ -518,7 +531,7 @@ export function updateChatMessage(
         };
         session.messages[idx] = next;
         session.updatedAt = Date.now();
-        maybeUpdateSessionTitle(session, next.role, next.text);
+        maybeUpdateSessionTitle(session, next);
         persistSessions();
         renderChat();
         notifyChatSessionsChanged();
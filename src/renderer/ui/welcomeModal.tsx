@@ -1,5 +1,5 @@
 import {MouseEvent, useEffect, useMemo, useState} from 'react';
-import {invoke} from '@tauri-apps/api/core';
+import {invoke} from '../bridge/invoke';
 import {
     Box,
     Button,
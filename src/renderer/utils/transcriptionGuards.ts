@@ -21,6 +21,10 @@ export const ensureTranscriptionReady = async (): Promise<boolean> => {
     const mode = settings.transcriptionMode || 'api';
     const apiModel = settings.transcriptionModel || DEFAULT_API_MODEL;
 
+    if (mode === 'mock') {
+        return true;
+    }
+
     if (mode === 'api') {
         if (WINKY_TRANSCRIBE_SET.has(apiModel)) {
             if (!authClient.hasTokens()) {
@@ -75,7 +79,7 @@ export const ensureTranscriptionReady = async (): Promise<boolean> => {
     }
 
     if (settings.llmHost === 'local') {
-        const llmModel = settings.localLlmModel || settings.llmModel || 'gpt-oss:20b';
+        const llmModel = settings.localLlmModel || 'gpt-oss:20b';
         try {
             const downloaded = await checkOllamaModelDownloaded(llmModel, {force: true});
             if (!downloaded) {
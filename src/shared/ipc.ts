@@ -19,7 +19,7 @@ export type AssistantResponse = {
 
 export type WhisperModel = 'tiny' | 'base' | 'small' | 'medium' | 'large' | 'large-v2' | 'large-v3';
 
-export type TranscriptionMode = 'api' | 'local';
+export type TranscriptionMode = 'api' | 'local' | 'mock';
 
 export type LlmHost = 'api' | 'local';
 
@@ -31,22 +31,85 @@ export type AppSettings = {
     durations: number[];
     durationHotkeys?: Record<number, string>;
     toggleInputHotkey?: string;
+    /** Duration presses kept while a transcription runs (1-10); the newest replaces the last when full. */
+    maxPendingClips?: number;
     openaiApiKey?: string;
     windowOpacity?: number;
     alwaysOnTop?: boolean;
     hideApp?: boolean;
+    warnWhenNotHidden?: boolean;
+    hideFromSwitcher?: boolean;
+    showRecordingIndicator?: boolean;
+    recordingIndicatorCorner?: IndicatorCorner;
+    trayIconTheme?: TrayIconTheme;
     welcomeModalDismissed?: boolean;
     windowWidth?: number;
     windowHeight?: number;
     windowScale?: number;
-    audioInputDeviceId?: string;
+    micDeviceId?: string | null;
+    mixedMicDeviceId?: string | null;
+    systemLoopbackDeviceId?: string | null;
     audioInputType?: 'microphone' | 'system' | 'mixed';
+    autoStartCapture?: boolean;
+    /** Seconds kept while capture is stopped and put ahead of the audio once it starts, 0–10; 0 is off. */
+    preRollSeconds?: number;
+    preRollSource?: PreRollSource;
+    /** Linear microphone gain, 0.25–8; `audio.calibrate` recommends one. */
+    micGain?: number;
+    /** Level in dBFS, after `micGain`, below which audio counts as silence; -80 to -10. */
+    vadThresholdDb?: number;
+    /** Speech level in dBFS that gain adjustments aim for; -40 to -6. */
+    agcTargetDb?: number;
+    /** Mixed capture only: system audio drops by `duckingDb` while the mic is above `vadThresholdDb`. */
+    duckingEnabled?: boolean;
+    /** 1 to 40. */
+    duckingDb?: number;
+    wakeWords?: string[];
+    wakeWordAction?: WakeWordAction | null;
+    audioPriorityBoost?: boolean;
+    /** Windows only; registers the WASAPI thread with MMCSS as "Pro Audio". */
+    audioMmcss?: boolean;
     transcriptionModel?: string;
     transcriptionPrompt?: string;
-    llmModel?: string;
+    transcriptionPromptOverrides?: TranscriptionPromptOverrides;
+    sendPromptToLocal?: boolean;
+    /** `json` asks Gemini for a `{text}` object; `text` is the free-text request. */
+    googleTranscriptionFormat?: 'json' | 'text';
+    /** ISO 639-1 code sent to the Whisper providers; while set, no detection runs. */
+    transcriptionLanguage?: string | null;
+    languageDetection?: LanguageDetection;
+    /** Keyed by ISO 639-1 code. */
+    languageProfiles?: Record<string, LanguageProfile>;
+    /** WAV clips are played this much faster before transcription, 1.0–2.0. */
+    sttSpeedupFactor?: number;
+    /** Speed up by relabelling the sample rate (pitched up) instead of WSOLA. */
+    sttSpeedupRelabel?: boolean;
+    providerRateLimits?: ProviderRateLimits;
+    plainTextLogEnabled?: boolean;
+    plainTextLogDir?: string | null;
+    /** Encrypts debug recordings and the transcript log with a keychain-held key. */
+    encryptLocalArtifacts?: boolean;
     apiLlmModel?: string;
     localLlmModel?: string;
+    /** May use `{{name}}` placeholders; see `PromptPreview`. */
     llmPrompt?: string;
+    /** Custom `{{name}}` values for `llmPrompt`; built-in names are dropped. */
+    promptVariables?: Record<string, string>;
+    /** Append the app and title of the window in front to the system prompt. */
+    includeWindowContext?: boolean;
+    /** Minutes between rolling summaries while capture runs; 0 turns them off. */
+    autoSummarizeEveryMin?: number;
+    /** System prompt for those summaries. */
+    summaryPrompt?: string;
+    /** Generation options for every LLM request; see `LocalLlmOptions`. */
+    localLlmOptions?: LocalLlmOptions;
+    /** Language transcripts are translated into afterwards, e.g. `en`; unset turns it off. */
+    translateTo?: string | null;
+    /** Model for that translation; the answer model when unset. */
+    translationModel?: string | null;
+    llmStreamToClipboard?: boolean;
+    enableAutoType?: boolean;
+    autoTypeWpm?: number;
     transcriptionMode?: TranscriptionMode;
     llmHost?: LlmHost;
     localWhisperModel?: WhisperModel;
@@ -54,11 +117,24 @@ export type AppSettings = {
     apiSttTimeoutMs?: number;
     apiLlmTimeoutMs?: number;
     screenProcessingTimeoutMs?: number;
+    /** Commands slower than this are logged and reported through `perf:slow-command`. */
+    slowCommandMs?: number;
     googleApiKey?: string;
     streamSendHotkey?: string;
+    /** Turns the private session on and off; empty for none. */
+    privateSessionHotkey?: string;
+    streamMode?: StreamMode;
     screenProcessingModel?: ScreenProcessingProvider;
     screenProcessingPrompt?: string;
     backendDomain?: BackendDomain;
+    // Read-only: false while the config dir is unwritable and changes stay in memory.
+    persistenceAvailable?: boolean;
+    // Read-only: false while the last change failed to reach disk and is being retried.
+    persisted?: boolean;
+    // Read-only: why that write failed.
+    warning?: string | null;
+    // Read-only: bumped by every change; `config_update` accepts it as `expectedRevision`.
+    revision?: number;
 };
 
 export const DEFAULT_LLM_PROMPT =
@@ -76,17 +152,18 @@ export const DefaultSettings: AppSettings = {
     audioInputType: 'microphone',
     transcriptionMode: 'api',
     llmHost: 'api',
-    llmModel: 'gpt-4.1-nano',
     apiLlmModel: 'gpt-4.1-nano',
     localLlmModel: 'gpt-oss:20b',
     localWhisperModel: 'base',
     localDevice: 'cpu',
     streamSendHotkey: '~',
+    streamMode: 'base',
     screenProcessingModel: 'openai',
     screenProcessingPrompt: DEFAULT_SCREEN_PROMPT,
     apiSttTimeoutMs: 150000,
     apiLlmTimeoutMs: 150000,
     screenProcessingTimeoutMs: 150000,
+    slowCommandMs: 1000,
     backendDomain: 'xlartas.com',
 };
 
@@ -145,6 +222,7 @@ export type ProcessAudioArgs = {
     mime: string;
     filename?: string;
     requestId?: string;
+    interactionId?: string;
 };
 
 export type TranscribeOnlyRequest = {
@@ -159,6 +237,7 @@ export type TranscribeOnlyArgs = {
     mime: string;
     filename?: string;
     audioSeconds?: number;
+    interactionId?: string;
 };
 
 export type AskChatRequest = {
@@ -211,7 +290,9 @@ export type AudioDeviceInfo = {
     name: string;
     kind: 'mic' | 'system' | 'other';
     channels: number;
-    sample_rate: number;
+    sampleRate: number;
+    /** Set for hands-free headset profiles and other 8/16 kHz inputs. */
+    qualityHint?: 'narrowband';
 };
 
 export type LogEntry = {
@@ -263,6 +344,8 @@ export type AssistantAPI = {
         }>;
         askChat: (args: AskChatRequest) => Promise<void>;
         stopStream: (args: StopStreamRequest) => Promise<void>;
+        typeText: (text: string, wpm?: number) => Promise<AutoTypeOutcome>;
+        abortTyping: () => Promise<void>;
         onStreamTranscript: (cb: (e: unknown, payload: { requestId?: string; delta: string }) => void) => void;
         onStreamDelta: (cb: (e: unknown, payload: { requestId?: string; delta: string }) => void) => void;
         onStreamDone: (cb: (e: unknown, payload: { requestId?: string; full: string }) => void) => void;
@@ -273,10 +356,45 @@ export type AssistantAPI = {
         offStreamError: () => void;
     };
     hotkeys: {
-        onDuration: (cb: (e: unknown, payload: { sec: number }) => void) => void;
+        onDuration: (cb: (e: unknown, payload: DurationHotkeyPayload) => void) => void;
         offDuration: () => void;
         onToggleInput: (cb: () => void) => void;
         offToggleInput: () => void;
+        onDurationQueued: (cb: (payload: DurationQueuedPayload) => void) => void;
+        offDurationQueued: () => void;
+        /** The stream send hotkey, registered only in stream mode. */
+        onStreamSend: (cb: () => void) => void;
+        offStreamSend: () => void;
+        /** Drops presses still waiting for a transcription; resolves to how many. */
+        clearPending: () => Promise<number>;
+        reapply: () => Promise<HotkeyReapplyReport>;
+    };
+    onboarding: {
+        getStatus: () => Promise<OnboardingStatus>;
+        completeStep: (step: OnboardingStepId) => Promise<OnboardingStatus>;
+        selfCheck: (force?: boolean) => Promise<SelfCheckReport>;
+        onSelfCheckProgress: (cb: (payload: SelfCheckProgress) => void) => void;
+        offSelfCheckProgress: () => void;
+    };
+    integration: {
+        getToken: () => Promise<string>;
+        onCaptureCommand: (cb: (payload: { record: boolean }) => void) => void;
+        offCaptureCommand: () => void;
+    };
+    session: {
+        timeline: (sessionId?: string) => Promise<SessionTimeline>;
+    };
+    errors: {
+        getRecent: () => Promise<BackgroundError[]>;
+        onError: (cb: (payload: BackgroundError) => void) => void;
+        offError: () => void;
+    };
+    perf: {
+        /** Call count, failures and p50/p95 over the recent calls of every command used since startup. */
+        getCommandStats: () => Promise<CommandStats[]>;
+        /** Commands that took longer than `slowCommandMs`. */
+        onSlowCommand: (cb: (payload: SlowCommand) => void) => void;
+        offSlowCommand: () => void;
     };
     settings: {
         get: () => Promise<AppSettings>;
@@ -284,15 +402,50 @@ export type AssistantAPI = {
         setWindowOpacity: (opacity: number) => Promise<void>;
         setAlwaysOnTop: (alwaysOnTop: boolean) => Promise<void>;
         setWindowSize: (size: { width: number; height: number }) => Promise<void>;
-        setDurations: (durations: number[]) => Promise<void>;
-        setDurationHotkeys: (map: Record<number, string>) => Promise<void>;
-        setAudioInputDevice: (deviceId: string) => Promise<void>;
+        setDurations: (durations: number[]) => Promise<AppSettings>;
+        setDurationHotkey: (duration: number, key: string) => Promise<AppSettings>;
+        setAudioInputDevice: (type: 'microphone' | 'system' | 'mixed', deviceId: string) => Promise<void>;
         setToggleInputHotkey: (key: string) => Promise<void>;
+        setMaxPendingClips: (count: number) => Promise<void>;
         setAudioInputType: (type: 'microphone' | 'system' | 'mixed') => Promise<void>;
+        setAutoStartCapture: (enabled: boolean) => Promise<void>;
+        setPreRollSeconds: (seconds: number) => Promise<void>;
+        setPreRollSource: (source: PreRollSource) => Promise<void>;
+        setMicGain: (gain: number) => Promise<void>;
+        setVadThresholdDb: (db: number) => Promise<void>;
+        setAgcTargetDb: (db: number) => Promise<void>;
+        setDuckingEnabled: (enabled: boolean) => Promise<void>;
+        setDuckingDb: (db: number) => Promise<void>;
+        setWakeWords: (words: string[]) => Promise<void>;
+        setWakeWordAction: (action: WakeWordAction | null) => Promise<void>;
+        /** Applies to capture threads started afterwards. */
+        setAudioPriorityBoost: (enabled: boolean) => Promise<void>;
+        setAudioMmcss: (enabled: boolean) => Promise<void>;
         setTranscriptionModel: (model: string) => Promise<void>;
         setTranscriptionPrompt: (prompt: string) => Promise<void>;
+        setTranscriptionPromptOverrides: (overrides: TranscriptionPromptOverrides) => Promise<void>;
+        setSendPromptToLocal: (enabled: boolean) => Promise<void>;
+        setGoogleTranscriptionFormat: (format: 'json' | 'text') => Promise<void>;
+        /** `null` or `auto` turns detection back on. */
+        setTranscriptionLanguage: (language: string | null) => Promise<void>;
+        setLanguageDetection: (detection: LanguageDetection) => Promise<void>;
+        /** Merged into the saved profiles; a profile with both fields `null` is removed. */
+        setLanguageProfiles: (profiles: Record<string, LanguageProfile>) => Promise<void>;
+        setSttSpeedupFactor: (factor: number) => Promise<void>;
+        setSttSpeedupRelabel: (enabled: boolean) => Promise<void>;
+        setProviderRateLimits: (limits: ProviderRateLimits) => Promise<void>;
+        /** Limits the providers reported on their last answers, e.g. for "2 requests left, resets in 14 s". */
+        getProviderQuotaStatus: () => Promise<ProviderQuotaStatus[]>;
         setLlmModel: (model: string, host?: 'api' | 'local') => Promise<void>;
         setLlmPrompt: (prompt: string) => Promise<void>;
+        setPromptVariables: (variables: Record<string, string>) => Promise<void>;
+        /** Renders a template as the backend would; fails with `prompt_template_invalid`. */
+        previewPrompt: (template: string, sampleVars?: Record<string, string>) => Promise<PromptPreview>;
+        /** Strips BOM/zero-width characters, composes accented letters and unifies line ends. */
+        sanitizeText: (text: string) => Promise<string>;
+        setLlmStreamToClipboard: (enabled: boolean) => Promise<void>;
+        setEnableAutoType: (enabled: boolean) => Promise<void>;
+        setAutoTypeWpm: (wpm: number) => Promise<void>;
         setTranscriptionMode: (mode: TranscriptionMode) => Promise<void>;
         setLlmHost: (host: LlmHost) => Promise<void>;
         setLocalWhisperModel: (model: WhisperModel) => Promise<void>;
@@ -302,22 +455,84 @@ export type AssistantAPI = {
         getAudioDevices: () => Promise<AudioDevice[]>;
         openConfigFolder: () => Promise<void>;
         openLogsFolder: () => Promise<void>;
+        openTranscriptLogFolder: () => Promise<void>;
+        setPlainTextLogEnabled: (enabled: boolean) => Promise<void>;
+        setPlainTextLogDir: (dir: string | null) => Promise<void>;
+        setEncryptLocalArtifacts: (enabled: boolean) => Promise<void>;
+        /** Encrypts recordings and transcript logs saved while the option was off. */
+        encryptExistingArtifacts: () => Promise<ArtifactsEncryptSummary>;
+        onArtifactsEncryptProgress: (cb: (progress: ArtifactsEncryptProgress) => void) => void;
+        offArtifactsEncryptProgress: () => void;
+        /** Decrypted contents of a debug recording or transcript log, base64-encoded. */
+        readDebugFile: (path: string) => Promise<string>;
+        /**
+         * Waveform thumbnail of a saved WAV recording, by path or debug file name:
+         * `buckets` RMS values (default 200), or a min/max pair per bucket for `peaks`.
+         * Fails with `waveform_failed`, whose data carries the `kind`.
+         */
+        getAudioWaveform: (pathOrId: string, buckets?: number, mode?: WaveformMode) => Promise<number[]>;
+        getRecentLogs: (lines: number) => Promise<string[]>;
+        /** For debug reports; also written at the top of the log and into crash reports. */
+        getVersions: () => Promise<AppVersions>;
+        /** Zips logs, crash reports, versions and the config without secrets into Downloads; resolves to its path. */
+        exportDiagnosticsBundle: () => Promise<string>;
+        /** Startup state in one call; the first read of each part by the other getters uses it. */
+        getBootstrap: () => Promise<AppBootstrap>;
+        listSounds: () => Promise<SoundsListing>;
         getLogPath: () => Promise<string>;
+        /** Selects the file in the OS file manager; only files in the app folders are accepted. */
+        revealInFileManager: (path: string) => Promise<void>;
         setScreenProcessingModel: (provider: ScreenProcessingProvider) => Promise<void>;
         setScreenProcessingPrompt: (prompt: string) => Promise<void>;
         setScreenProcessingTimeoutMs: (timeoutMs: number) => Promise<void>;
+        setSlowCommandMs: (thresholdMs: number) => Promise<void>;
         setWelcomeModalDismissed: (dismissed: boolean) => Promise<void>;
         setGoogleApiKey: (key: string) => Promise<void>;
+        setIncludeWindowContext: (enabled: boolean) => Promise<void>;
+        setAutoSummarizeEveryMin: (minutes: number) => Promise<void>;
+        setSummaryPrompt: (prompt: string) => Promise<void>;
+        setLocalLlmOptions: (options: LocalLlmOptions) => Promise<void>;
+        setTranslateTo: (language: string | null) => Promise<void>;
+        setTranslationModel: (model: string | null) => Promise<void>;
+        getForegroundWindowInfo: () => Promise<ForegroundWindowInfo>;
+        listMonitors: () => Promise<MonitorInfo[]>;
+        getCaptureProtectionStatus: () => Promise<CaptureProtectionStatus>;
+        /** Checks `key`, or the saved one, with a models listing; nothing is saved. */
+        testApiKey: (provider: ApiKeyProvider, key?: string) => Promise<ApiKeyTestResult>;
         setStreamSendHotkey: (key: string) => Promise<void>;
+        setPrivateSessionHotkey: (key: string) => Promise<void>;
+        /**
+         * While on, nothing from the session is written to disk. Turning it off drops the
+         * prompt edits made during it; every window gets `privacy:private-session`.
+         */
+        setPrivateSession: (enabled: boolean) => Promise<boolean>;
+        getPrivateSession: () => Promise<boolean>;
+        /** Also swaps the hotkeys; every window gets `stream:mode-changed`. */
+        setStreamMode: (mode: StreamMode) => Promise<AppSettings>;
+        onStreamModeChanged: (cb: (payload: StreamModeChanged) => void) => void;
+        offStreamModeChanged: () => void;
         setWindowScale: (scale: number) => Promise<void>;
         setHideApp: (hideApp: boolean) => Promise<void>;
+        setWarnWhenNotHidden: (enabled: boolean) => Promise<void>;
+        setHideFromSwitcher: (enabled: boolean) => Promise<void>;
+        setShowRecordingIndicator: (enabled: boolean) => Promise<void>;
+        setRecordingIndicatorCorner: (corner: IndicatorCorner) => Promise<void>;
+        setTrayIconTheme: (theme: TrayIconTheme) => Promise<void>;
+        /** Loads the tray icon again and reports where it came from. */
+        reloadTrayIcon: () => Promise<TrayIconSource>;
         setBackendDomain: (domain: BackendDomain) => Promise<void>;
+        retryPersistence: () => Promise<ConfigPersistenceStatus>;
+        /** Takes the config over from another copy once it has exited; fails with `config_locked` while it runs. */
+        forceConfigTakeover: () => Promise<ConfigPersistenceStatus>;
     };
     window: {
         minimize: () => Promise<void>;
         close: () => Promise<void>;
         getBounds: () => Promise<{ x: number; y: number; width: number; height: number }>;
         setBounds: (bounds: { x: number; y: number; width: number; height: number }) => Promise<void>;
+        getInitialPrefs: () => Promise<WindowPrefs>;
+        onScaleChanged: (cb: (prefs: WindowPrefs) => void) => void;
+        offScaleChanged: () => void;
     };
     loopback: {
         enable: () => Promise<{ success: boolean; error?: string }>;
@@ -357,17 +572,50 @@ export type AssistantAPI = {
         reinstall: () => Promise<FastWhisperStatus>;
         stop: () => Promise<FastWhisperStatus>;
         checkModelDownloaded: (model: string) => Promise<boolean>;
+        /** Counts the caller in or out of background health checks; resolves to the watcher count. */
+        watch: (enable: boolean) => Promise<number>;
     };
     ollama: {
         checkInstalled: () => Promise<boolean>;
         listModels: () => Promise<string[]>;
-        pullModel: (model: string) => Promise<void>;
+        pullModel: (model: string) => Promise<OllamaPullOutcome>;
         warmupModel: (model: string) => Promise<void>;
+        /** Installed models plus `candidates`, classified against this machine's memory. */
+        recommendModels: (candidates?: string[]) => Promise<OllamaModelRecommendation[]>;
+        systemResources: () => Promise<SystemResources>;
     };
     audio: {
         listDevices: () => Promise<AudioDeviceInfo[]>;
         startCapture: (source: 'mic' | 'system' | 'mixed', deviceId?: string) => Promise<void>;
         stopCapture: () => Promise<void>;
+        /**
+         * Moves a running capture to another source without a gap; chunk numbering
+         * carries on. On failure the old source keeps recording. Starts capture if
+         * none runs.
+         */
+        switchSource: (source: 'mic' | 'system' | 'mixed', deviceId?: string) => Promise<void>;
+        ack: (seq: number) => Promise<void>;
+        setMonitor: (enabled: boolean, gain: number) => Promise<void>;
+        getStatus: () => Promise<AudioCaptureStatus>;
+        /** Capture status plus the last 20 decoded WASAPI failures. */
+        runDiagnostics: () => Promise<AudioDiagnostics>;
+        /** Opens the devices, listens ~400 ms and closes them; safe while a capture runs. */
+        validateSetup: (
+            source: 'microphone' | 'system' | 'mixed',
+            deviceId?: string,
+            loopbackDeviceId?: string,
+        ) => Promise<AudioValidation>;
+        /**
+         * Records the microphone while the user stays silent and then speaks,
+         * announcing each phase as `audio:calibration`. `apply` saves the levels.
+         * Fails with `audio_calibration_failed`, whose data carries the `kind`.
+         */
+        calibrate: (durationSecs?: number, apply?: boolean) => Promise<AudioCalibration>;
+        /** Opens the OS page where microphone access is granted. */
+        openPrivacySettings: () => Promise<void>;
+        /** Shows the "REC" indicator window whether or not capture runs. */
+        showIndicator: () => Promise<void>;
+        hideIndicator: () => Promise<void>;
     };
     log: (entry: LogEntry) => Promise<void>;
 };
@@ -382,4 +630,745 @@ export type FastWhisperStatus = {
     lastSuccessAt?: number | null;
     logLine?: string | null;
     updatedAt: number;
+    /** Restored from the last session; a health check is still confirming it. */
+    stale?: boolean;
+    /** Version the running server reported, and the API negotiated for it. */
+    serverVersion?: string | null;
+    apiVariant?: string | null;
+};
+
+export type OnboardingStepId = 'apiKey' | 'microphone' | 'localSpeech' | 'ollama' | 'welcome';
+
+export type OnboardingCheck = {
+    id: Exclude<OnboardingStepId, 'welcome'>;
+    label: string;
+    required: boolean;
+    passed: boolean;
+    completed: boolean;
+    hint?: string | null;
+};
+
+export type OnboardingStatus = {
+    checks: OnboardingCheck[];
+    completedSteps: string[];
+    ready: boolean;
+};
+
+export type SelfCheckStatus = 'ok' | 'warning' | 'failed' | 'skipped';
+
+export type SelfCheckItem = {
+    id: 'config' | 'apiKey' | 'audio' | 'localSpeech' | 'ollama' | 'deepLink' | 'disk';
+    label: string;
+    status: SelfCheckStatus;
+    detail: string;
+    hint?: string | null;
+    durationMs: number;
+};
+
+export type SelfCheckReport = {
+    checks: SelfCheckItem[];
+    healthy: boolean;
+    checkedAt: number;
+    cached: boolean;
+};
+
+export type ArtifactsEncryptProgress = {
+    done: number;
+    total: number;
+    path: string;
+};
+
+export type ArtifactsEncryptSummary = {
+    encrypted: number;
+    alreadyEncrypted: number;
+    failed: number;
+};
+
+export type SelfCheckProgress = {
+    check: SelfCheckItem;
+    completed: number;
+    total: number;
+};
+
+export type SoundCueInfo = {
+    name: string;
+    theme?: string | null;
+    path: string;
+    valid: boolean;
+    durationMs?: number | null;
+    warning?: string | null;
+};
+
+export type SoundsListing = {
+    themes: string[];
+    activeTheme?: string | null;
+    events: string[];
+    cues: SoundCueInfo[];
+};
+
+export type CommandErrorCode =
+    | 'internal'
+    | 'invalid_argument'
+    | 'config_failed'
+    | 'config_conflict'
+    | 'config_locked'
+    | 'open_failed'
+    | 'url_not_allowed'
+    | 'path_not_found'
+    | 'path_not_allowed'
+    | 'logs_unavailable'
+    | 'auth_unknown_account'
+    | 'auth_failed'
+    | 'auth_provider_unsupported'
+    | 'auth_provider_unavailable'
+    | 'network_failed'
+    | 'key_test_too_soon'
+    | 'local_speech_failed'
+    | 'ollama_failed'
+    | 'audio_device_failed'
+    | 'audio_capture_failed'
+    | 'microphone_access_denied'
+    | 'transcription_failed'
+    | 'transcription_mode_unknown'
+    | 'payload_too_large'
+    | 'update_check_failed'
+    | 'usage_failed'
+    | 'session_export_failed'
+    | 'session_not_found'
+    | 'onboarding_failed'
+    | 'sounds_failed'
+    | 'tray_failed'
+    | 'duration_out_of_range'
+    | 'duration_duplicate'
+    | 'duration_not_found'
+    | 'too_many_durations'
+    | 'hotkey_invalid'
+    | 'hotkey_conflict'
+    | 'hotkey_registration_failed'
+    | 'monitor_feedback'
+    | 'monitor_failed'
+    | 'auto_type_disabled'
+    | 'auto_type_busy'
+    | 'auto_type_unsupported'
+    | 'auto_type_failed'
+    | 'artifacts_failed'
+    | 'window_info_unsupported'
+    | 'window_info_failed'
+    | 'displays_unavailable'
+    | 'prompt_template_invalid'
+    | 'diagnostics_export_failed'
+    | 'waveform_failed'
+    | 'audio_calibration_failed'
+    | 'local_speech_incompatible';
+
+export type HotkeyResult = {
+    kind: 'duration' | 'toggle' | 'stream' | 'private-session';
+    accelerator: string;
+    seconds?: number | null;
+    registered: boolean;
+    error?: string | null;
+};
+
+export type HotkeyReapplyReport = {
+    reason: 'command' | 'resume' | 'explorer-restart';
+    results: HotkeyResult[];
+};
+
+/** A rectangle in physical pixels on the virtual desktop. */
+export type ScreenRect = {
+    x: number;
+    y: number;
+    width: number;
+    height: number;
+};
+
+/**
+ * One connected display, in physical pixels. The full list is emitted as
+ * `monitors:changed` whenever displays are added, removed, moved or rescaled.
+ */
+export type MonitorInfo = {
+    name: string | null;
+    position: { x: number; y: number };
+    size: { width: number; height: number };
+    scaleFactor: number;
+    isPrimary: boolean;
+    /** The part not covered by taskbars and docks. */
+    workArea: ScreenRect;
+};
+
+export type ForegroundWindowInfo = {
+    /** Not available on macOS without the Screen Recording permission. */
+    title?: string | null;
+    processName?: string | null;
+    appName?: string | null;
+};
+
+/**
+ * Built-in variables: `transcript`, `screen_text`, `window_title`, `datetime`.
+ * `\{{` is a literal `{{`; in `{{{name}}}` the value keeps the outer braces.
+ */
+export type PromptPreview = {
+    text: string;
+    /** Names the template uses, in order of first use. */
+    variables: string[];
+    /** Used names without a value; they are sent as written. */
+    unknown: string[];
+};
+
+/** Data of a `prompt_template_invalid` error; `offset` counts characters. */
+export type PromptTemplateErrorData = {
+    kind: 'unclosed' | 'invalid_name';
+    name: string | null;
+    offset: number;
+};
+
+export type ApiKeyProvider = 'openai' | 'google';
+
+export type ApiKeyTestResult = {
+    valid: boolean;
+    errorCode?:
+        | 'missing'
+        | 'wrong_provider'
+        | 'invalid_key'
+        | 'forbidden'
+        | 'rate_limited'
+        | 'network'
+        | 'unexpected'
+        | null;
+    detail: string;
+    orgInfo?: {
+        organization?: string | null;
+        project?: string | null;
+        modelCount?: number | null;
+    } | null;
+};
+
+export type AutoTypeOutcome = {
+    typedChars: number;
+    totalChars: number;
+    aborted: boolean;
+};
+
+export type CommandErrorPayload = {
+    code: CommandErrorCode;
+    message: string;
+    detail?: string | null;
+    data?: Record<string, unknown> | null;
+};
+
+export type TranscriptionTimings = {
+    prepareMs: number;
+    uploadMs: number;
+    providerMs: number;
+    totalMs: number;
+};
+
+export type TranscriptionResult = {
+    text: string;
+    timings?: TranscriptionTimings;
+    interactionId?: string | null;
+    /** Speed-up applied before upload; provider timestamps times this are real time. */
+    speedupFactor?: number | null;
+    language?: DetectedLanguage | null;
+    /** The transcript in `translateTo`, when it was translated. */
+    translatedText?: string | null;
+    /** Translation was attempted and failed; `text` is still valid. */
+    translationFailed?: boolean;
+};
+
+export type DetectedLanguage = {
+    /** ISO 639-1 code. */
+    language: string;
+    confidence: number | null;
+    method: 'whisper' | 'draft';
+    profileApplied: boolean;
+    elapsedMs: number;
+};
+
+/** Emitted as `transcription:speedup-quality` when the speed-up is past `limit`. */
+export type SpeedupQuality = {
+    factor: number;
+    limit: number;
+};
+
+/** Emitted as `diagnostics:bundle-ready` after `exportDiagnosticsBundle` wrote the zip. */
+export type DiagnosticsBundleReady = {
+    path: string;
+    files: number;
+    sizeBytes: number;
+};
+
+/** Emitted as `privacy:private-session` when the private session is turned on or off. */
+export type PrivateSessionChanged = {
+    enabled: boolean;
+};
+
+export type AppVersions = {
+    app: string;
+    tauri: string;
+    webview: string | null;
+    /** Branch and commit of the installed fast-whisper repository, `unknown` for older installs. */
+    fastWhisper: string | null;
+    /** Null when the Ollama server is not running. */
+    ollama: string | null;
+    os: string;
+    arch: string;
+};
+
+/** One part of `AppBootstrap`: the value, or why it could not be loaded. */
+export type BootstrapSection<T> = {
+    value: T | null;
+    error: string | null;
+};
+
+/** Startup state gathered in one `app_bootstrap` call. */
+export type AppBootstrap = {
+    config: BootstrapSection<AppSettings>;
+    audioDevices: BootstrapSection<AudioDeviceInfo[]>;
+    captureStatus: BootstrapSection<AudioCaptureStatus>;
+    localSpeech: BootstrapSection<FastWhisperStatus>;
+    /** Already drained on the native side. */
+    pendingAuth: BootstrapSection<AuthDeepLinkPayload[]>;
+    ollamaInstalled: BootstrapSection<boolean>;
+    privateSession: BootstrapSection<boolean>;
+};
+
+export type CommandStats = {
+    command: string;
+    count: number;
+    failures: number;
+    p50Ms: number;
+    p95Ms: number;
+};
+
+export type SlowCommand = {
+    command: string;
+    durationMs: number;
+    thresholdMs: number;
+    ok: boolean;
+};
+
+export type BackgroundError = {
+    component: string;
+    code: CommandErrorCode;
+    message: string;
+    detail?: string | null;
+    recoverable: boolean;
+    timestamp: number;
+};
+
+export type AudioBackpressure = {
+    active: boolean;
+    bufferedChunks: number;
+    droppedChunks: number;
+};
+
+export type CaptureSource = 'mic' | 'system';
+
+export type MissingCaptureSource = {
+    source: CaptureSource;
+    reason: string;
+};
+
+/** A mixed capture that started without some of its inputs. */
+export type AudioPartialStart = {
+    source: 'mic' | 'system' | 'mixed';
+    active: CaptureSource[];
+    missing: MissingCaptureSource[];
+};
+
+/** Emitted as `audio:source-changed` after `switchSource` moved a running capture. */
+export type AudioSourceChanged = {
+    previous: 'microphone' | 'system' | 'mixed' | null;
+    source: 'microphone' | 'system' | 'mixed';
+    active: CaptureSource[];
+    missing: MissingCaptureSource[];
+};
+
+export type InteractionIds = {
+    sessionId: string | null;
+    interactionId: string | null;
+};
+
+export type DurationHotkeyPayload = InteractionIds & {
+    sec: number;
+    /** Epoch ms of the key press; earlier than the event for a press that waited in the queue. */
+    pressedAt?: number;
+};
+
+/** Emitted as `hotkeys:duration-queued` when a press waits for the running transcription. */
+export type DurationQueuedPayload = {
+    sec: number;
+    /** 1-based place in the queue. */
+    position: number;
+    /** The queue was full and this press replaced the newest one. */
+    coalesced: boolean;
+};
+
+export type InteractionTrigger = 'duration-hotkey' | 'toggle-input' | 'wake-word' | 'auto-summary' | 'manual';
+
+export type GpuInfo = {
+    name: string;
+    vramTotalBytes: number | null;
+    vramFreeBytes: number | null;
+    /** Apple Silicon: the GPU shares system RAM. */
+    unifiedMemory: boolean;
+};
+
+export type SystemResources = {
+    totalRamBytes: number | null;
+    availableRamBytes: number | null;
+    gpus: GpuInfo[];
+};
+
+export type OllamaModelFit = 'fit' | 'tight' | 'wont-fit' | 'unknown';
+
+export type OllamaModelRecommendation = {
+    name: string;
+    installed: boolean;
+    /** From Ollama when installed, estimated from the parameter count otherwise. */
+    sizeBytes: number | null;
+    requiredBytes: number | null;
+    fit: OllamaModelFit;
+};
+
+/** `memoryWarning` is also emitted as `ollama:memory-warning` when the pull starts. */
+export type OllamaPullOutcome = {
+    memoryWarning: OllamaModelRecommendation | null;
+};
+
+export type TranscriptionPromptOverrides = {
+    openai?: string | null;
+    google?: string | null;
+    local?: string | null;
+};
+
+/** What the pre-roll records while capture is stopped: the microphone, or system audio too. */
+export type PreRollSource = 'mic' | 'mixed';
+
+/**
+ * Found before transcribing to pick a `languageProfiles` entry. `whisper` asks the
+ * local server's tiny model; `draft` transcribes the first 5 s and guesses from the text.
+ */
+export type LanguageDetection = 'off' | 'whisper' | 'draft';
+
+/** Transcription settings for one spoken language. */
+export type LanguageProfile = {
+    /** Wins over the request's prompt and the per-provider override. */
+    prompt?: string | null;
+    /** Replaces the requested model of whichever provider transcribes. */
+    model?: string | null;
+};
+
+export type PromptTruncation = {
+    provider: string;
+    estimatedTokens: number;
+    limitTokens: number;
+};
+
+/**
+ * Ollama's generation `options`; unset ones keep the model defaults. Values out
+ * of range are dropped when the config is saved. The OpenAI provider maps
+ * `numPredict` to `max_tokens` and ignores `numCtx`.
+ */
+export type LocalLlmOptions = {
+    /** 0-2. */
+    temperature?: number | null;
+    /** 0-1. */
+    topP?: number | null;
+    /** Context window in tokens, 256-262144. */
+    numCtx?: number | null;
+    /** Most tokens to generate; -1 is unlimited. */
+    numPredict?: number | null;
+    /** Up to four sequences. */
+    stop?: string[];
+    seed?: number | null;
+};
+
+/** Requests per minute, shared by transcription and chat; unset is unlimited. */
+export type ProviderRateLimits = {
+    openai?: { rpm: number } | null;
+    google?: { rpm: number } | null;
+};
+
+/** Emitted as `transcription:throttled` when a call waits for its provider budget. */
+export type ProviderThrottleNotice = {
+    provider: string;
+    kind: 'transcription' | 'llm';
+    delayMs: number;
+    /** `budget` for the configured rpm, otherwise the provider limit that ran out. */
+    reason: 'budget' | 'retry-after' | 'requests' | 'tokens';
+};
+
+/** From the provider's `x-ratelimit-*` and `retry-after` headers; unsent headers are null. */
+export type ProviderQuotaStatus = {
+    provider: string;
+    limitRequests: number | null;
+    remainingRequests: number | null;
+    /** 0 once the window has reset, after which `remainingRequests` is out of date. */
+    requestsResetMs: number | null;
+    limitTokens: number | null;
+    remainingTokens: number | null;
+    tokensResetMs: number | null;
+    retryAfterMs: number | null;
+    /** How long the next request will be held back. */
+    waitMs: number;
+    updatedAt: string;
+};
+
+/** Emitted as `tray:rebuilt` after the tray icon is rebuilt for a restarted Explorer. */
+export type TrayRebuilt = {
+    ok: boolean;
+};
+
+export type IndicatorCorner = 'topleft' | 'topright' | 'bottomleft' | 'bottomright';
+
+/** `auto` keeps the full-color app icon; `light` and `dark` suit a light or dark taskbar. */
+export type TrayIconTheme = 'auto' | 'light' | 'dark';
+
+export type TrayIconSource = 'theme' | 'window-default' | 'resource' | 'embedded';
+
+/** `base` sends clips on the duration hotkeys; `stream` sends live text on `streamSendHotkey`. */
+export type StreamMode = 'base' | 'stream';
+
+/** Emitted as `stream:mode-changed` after `setStreamMode` switches modes. */
+export type StreamModeChanged = {
+    mode: StreamMode;
+    /** Label of the window that switched. */
+    origin: string;
+    revision: number;
+    /** Registration of the stream send hotkey, when stream mode has one. */
+    hotkey?: HotkeyResult | null;
+};
+
+export type WakeWordAction = {
+    /** Seconds to send for an answer, as if that duration hotkey was pressed. */
+    duration?: number | null;
+};
+
+/** Emitted as `audio:wake-word` when a configured wake word is heard. */
+export type WakeWordPayload = {
+    word: string;
+};
+
+export type SessionEntry = {
+    timestamp: string;
+    kind: 'transcript' | 'answer' | 'summary' | 'translation';
+    speaker?: string | null;
+    text: string;
+    interactionId?: string | null;
+};
+
+/** Emitted as `session:summary-request`; answered with `session_summary_complete`. */
+export type SessionSummaryRequest = {
+    requestId: string;
+    prompt: string;
+    text: string;
+    coveringFrom: string;
+    coveringTo: string;
+};
+
+/** Emitted as `session:summary` once a rolling summary is recorded. */
+export type SessionSummary = {
+    text: string;
+    coveringFrom: string;
+    coveringTo: string;
+};
+
+export type SessionInteraction = {
+    id: string;
+    trigger: InteractionTrigger;
+    startedAt: string;
+    clipSeconds?: number | null;
+    entries: SessionEntry[];
+};
+
+export type SessionTimeline = {
+    sessionId: string;
+    startedAt: string;
+    interactions: SessionInteraction[];
+};
+
+export type PrivacyWarning = {
+    hideApp: boolean;
+    excludedFromCapture: boolean | null;
+    affinityError: string | null;
+    notify: boolean;
+};
+
+export type WaveformMode = 'rms' | 'peaks';
+
+/**
+ * From `getCaptureProtectionStatus`, and emitted as `privacy:reapplied` or
+ * `privacy:lost` after the main window got a new HWND.
+ */
+export type CaptureProtectionStatus = {
+    hideApp: boolean;
+    /** What Windows reports for the main window; `null` where it cannot be checked. */
+    excludedFromCapture: boolean | null;
+    affinityError: string | null;
+    windowRecreations: number;
+};
+
+export type ConfigPersistenceStatus = {
+    persistenceAvailable: boolean;
+    path: string;
+    fallbackDir: string;
+    error: string | null;
+    /** The running copy holding `config.lock`, while this one is read-only because of it. */
+    lockedBy: ConfigLockHolder | null;
+};
+
+export type ConfigLockHolder = {
+    pid: number;
+    startedAt: string;
+    exe: string | null;
+};
+
+/** Emitted as `config:updated` after every change, in revision order. */
+export type ConfigUpdated = AppSettings & {
+    /**
+     * Label of the window that made the change, or the native component;
+     * `resize` is a window size saved once a drag ends.
+     */
+    origin: string;
+    revision: number;
+};
+
+/** Emitted as `config:persisted` when a retried config write reaches disk. */
+
+export type ConfigPersisted = {
+    path: string;
+    attempts: number;
+};
+
+export type SystemResumed = {
+    source: 'power-broadcast' | 'clock-gap';
+    sleptSeconds: number | null;
+    audioRestarted: boolean;
+    localSpeechRunning: boolean | null;
+    failed: string[];
+};
+
+export type WindowPrefs = {
+    scale: number;
+};
+
+/** One clip of a `transcribe_batch` call, at the position it was sent in. */
+export type TranscriptionBatchItem = {
+    index: number;
+    response: { text: string; interactionId: string | null } | null;
+    error: CommandErrorPayload | null;
+    skipped: boolean;
+};
+
+export type TranscriptionBatchProgress = {
+    batchId: string;
+    index: number;
+    total: number;
+    state: 'started' | 'done' | 'failed' | 'skipped';
+};
+
+export type AudioCaptureStatus = {
+    active: boolean;
+    source: 'microphone' | 'system' | 'mixed' | null;
+    error: string | null;
+    chunkProcessingMicros: number | null;
+    backpressure: boolean;
+    activeSources: CaptureSource[];
+    missingSources: MissingCaptureSource[];
+    /** Newest `seq` handed out on `audio:chunk`. */
+    lastSeq: number;
+    /** Chunks never emitted because the webview fell behind; each is a gap in `seq`. */
+    droppedChunks: number;
+    threadPriorities: AudioThreadBoost[];
+    /** Capture is stopped, but the pre-roll keeps the last `preRollSeconds`. */
+    preRollActive: boolean;
+    /** Counters of the running capture, or of the last one once it stopped. */
+    stats: AudioCaptureStats;
+};
+
+/** Cumulative since the last start, for the recording stats panel. */
+export type AudioCaptureStats = {
+    /** `null` while stopped. */
+    uptimeMs: number | null;
+    frames: number;
+    /** Base64 payload bytes sent on `audio:chunk`. */
+    bytesEmitted: number;
+    chunksEmitted: number;
+    /** Frames of each source that never reached the webview. */
+    droppedFrames: { source: CaptureSource; frames: number }[];
+    /** Capture to emit for the newest live chunk. */
+    latencyMicros: number | null;
+    /** Chunks scaled down because they hit the edge of the sample range. */
+    peakNormalizations: number;
+};
+
+/** Whether a capture, mixing or encoder thread runs above normal priority. */
+export type AudioThreadBoost = {
+    thread: 'audio-capture' | 'audio-encoder' | 'wasapi-loopback';
+    applied: boolean;
+    method: 'above-normal' | 'round-robin' | 'nice' | null;
+    mmcss: boolean;
+    error: string | null;
+};
+
+export type WasapiFailure = {
+    context: string;
+    code: string;
+    name: string | null;
+    description: string | null;
+    timestamp: number;
+};
+
+export type SourceValidation = {
+    source: CaptureSource;
+    ok: boolean;
+    deviceName: string | null;
+    sampleRate: number | null;
+    channels: number | null;
+    /** RMS over the listening window in dBFS. */
+    levelDb: number | null;
+    error: string | null;
+};
+
+export type MicrophoneAccessState = 'allowed' | 'denied' | 'restricted' | 'not-determined' | 'unknown';
+
+/** Also the data of a `microphone_access_denied` error. */
+export type MicrophoneAccess = {
+    state: MicrophoneAccessState;
+    /** The Windows switch that is off: 'system', 'apps' or 'desktop-apps'. */
+    blockedBy: string | null;
+    settingsUrl: string | null;
+};
+
+export type AudioValidation = {
+    /** A capture was running; the test used its own streams next to it. */
+    captureActive: boolean;
+    sources: SourceValidation[];
+    microphoneAccess: MicrophoneAccess;
+};
+
+/** Emitted as `audio:calibration` when a phase begins. */
+export type AudioCalibrationPhase = {
+    phase: 'silence' | 'speech' | 'done';
+    durationMs: number;
+};
+
+export type AudioCalibration = {
+    /** Median level while silent, in dBFS before any gain. */
+    noiseFloorDb: number;
+    /** Loud end of the levels while speaking, in dBFS before any gain. */
+    speechLevelDb: number;
+    micGain: number;
+    vadThresholdDb: number;
+    agcTargetDb: number;
+    /** Saved to the config. */
+    applied: boolean;
+};
+
+export type AudioDiagnostics = {
+    status: AudioCaptureStatus;
+    wasapiFailures: WasapiFailure[];
+    microphoneAccess: MicrophoneAccess;
 };